@@ -128,12 +128,12 @@ fn main() -> Result<()> {
     
     for (reference, _, _) in &components_with_models {
         let prefix = extract_component_prefix(reference);
-        component_groups.entry(prefix).or_insert_with(Vec::new).push((reference.clone(), true));
+        component_groups.entry(prefix).or_default().push((reference.clone(), true));
     }
     
     for reference in &components_without_models {
         let prefix = extract_component_prefix(reference);
-        component_groups.entry(prefix).or_insert_with(Vec::new).push((reference.clone(), false));
+        component_groups.entry(prefix).or_default().push((reference.clone(), false));
     }
     
     // Display component groups
@@ -163,7 +163,7 @@ fn main() -> Result<()> {
     if !components_with_models.is_empty() {
         println!("\n📋 SAMPLE COMPONENTS WITH 3D MODELS:");
         for (reference, footprint, model) in components_with_models.iter().take(5) {
-            let model_file = model.split('/').last().unwrap_or(model);
+            let model_file = model.split('/').next_back().unwrap_or(model);
             println!("  {} ({}): {}", reference, footprint, model_file);
         }
         if components_with_models.len() > 5 {
@@ -214,7 +214,7 @@ fn natural_sort(a: &str, b: &str) -> std::cmp::Ordering {
     let (a_prefix, a_num) = split_reference(a);
     let (b_prefix, b_num) = split_reference(b);
     
-    match a_prefix.cmp(&b_prefix) {
+    match a_prefix.cmp(b_prefix) {
         std::cmp::Ordering::Equal => a_num.cmp(&b_num),
         other => other,
     }
@@ -42,7 +42,7 @@ fn main() -> Result<()> {
         let comp_type = extract_component_type(&component.reference);
         type_groups
             .entry(comp_type.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(component);
     }
     
@@ -38,7 +38,7 @@ fn main() -> Result<()> {
         let prefix = extract_prefix(&component.reference);
         component_groups
             .entry(prefix.to_string())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(component);
     }
     
@@ -32,11 +32,11 @@ fn main() -> Result<()> {
     }
     
     // Categorize symbols by type
-    let mut categories = std::collections::HashMap::new();
+    let mut categories: std::collections::HashMap<&str, Vec<_>> = std::collections::HashMap::new();
     
     for symbol in &symbols {
         let category = categorize_symbol(&symbol.name);
-        categories.entry(category).or_insert_with(Vec::new).push(symbol);
+        categories.entry(category).or_default().push(symbol);
     }
     
     // Display symbols by category
@@ -55,8 +55,8 @@ fn main() -> Result<()> {
         println!("\n✓ Board Dimensions:");
         println!("  - Size: {:.1} × {:.1} mm", outline.width_mm, outline.height_mm);
         println!("  - Size: {:.0} × {:.0} mils", 
-                 outline.width_mm * 39.3701, 
-                 outline.height_mm * 39.3701);
+                 Length::mm(outline.width_mm).as_mils(),
+                 Length::mm(outline.height_mm).as_mils());
     }
     
     // Extract 3D models
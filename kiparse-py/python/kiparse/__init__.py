@@ -0,0 +1,4 @@
+"""Fast KiCad board and symbol library parser, backed by the Rust kiparse crate."""
+
+from ._kiparse import *  # noqa: F401,F403
+from ._kiparse import __version__  # noqa: F401
@@ -0,0 +1,170 @@
+from typing import Any, Dict, List, Optional, Tuple
+
+__version__: str
+
+class Layer:
+    id: int
+    name: str
+    layer_type: str
+    user_name: Optional[str]
+
+class Pad:
+    number: str
+    pad_type: str
+    shape: str
+    x: float
+    y: float
+    rotation: float
+    width: float
+    height: float
+    drill: Optional[float]
+    layers: List[str]
+    net: Optional[str]
+
+class Footprint:
+    reference: str
+    value: str
+    name: str
+    x: float
+    y: float
+    rotation: float
+    layer: str
+    dnp: bool
+    properties: Dict[str, str]
+    pads: List[Pad]
+
+class Track:
+    start: Tuple[float, float]
+    end: Tuple[float, float]
+    width: float
+    layer: str
+    net: Optional[str]
+
+class Via:
+    x: float
+    y: float
+    size: float
+    drill: float
+    layers: List[str]
+    net: Optional[str]
+
+class Board:
+    @property
+    def version(self) -> str: ...
+    @property
+    def generator(self) -> str: ...
+    @property
+    def thickness(self) -> Optional[float]: ...
+    @property
+    def layers(self) -> List[Layer]: ...
+    @property
+    def footprints(self) -> List[Footprint]: ...
+    @property
+    def tracks(self) -> List[Track]: ...
+    @property
+    def vias(self) -> List[Via]: ...
+    @property
+    def nets(self) -> Dict[int, str]: ...
+    def footprint(self, reference: str) -> Optional[Footprint]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+
+class PcbParser:
+    def __init__(self, content: str) -> None: ...
+    def parse(self) -> Board: ...
+
+class ComponentInfo:
+    reference: str
+    footprint: str
+    x: float
+    y: float
+    rotation: float
+    layer: str
+    value: Optional[str]
+
+class Model3DInfo:
+    reference: str
+    footprint: str
+    model_path: str
+    model_type: str
+    embedded: bool
+
+class TrackInfo:
+    start: Tuple[float, float]
+    end: Tuple[float, float]
+    width: float
+    layer: str
+    net: Optional[int]
+    net_name: Optional[str]
+
+class ViaInfo:
+    x: float
+    y: float
+    size: float
+    drill: float
+    layers: Tuple[str, str]
+    net: Optional[int]
+    net_name: Optional[str]
+
+class BoardOutline:
+    min_x: float
+    min_y: float
+    max_x: float
+    max_y: float
+    width_mm: float
+    height_mm: float
+
+class DetailParser:
+    def __init__(self, content: str) -> None: ...
+    def extract_components(self) -> List[ComponentInfo]: ...
+    def extract_3d_models(self) -> List[Model3DInfo]: ...
+    def extract_nets(self) -> Dict[int, str]: ...
+    def extract_tracks(self) -> List[TrackInfo]: ...
+    def extract_vias(self) -> List[ViaInfo]: ...
+    def extract_board_outline(self) -> Optional[BoardOutline]: ...
+    def extract_component_summary(self) -> Dict[str, int]: ...
+
+class SymbolInfo:
+    name: str
+    description: str
+    properties: Dict[str, str]
+
+class Pin:
+    number: str
+    name: str
+    pin_type: str
+    shape: str
+    x: float
+    y: float
+    length: float
+    rotation: float
+    hidden: bool
+    unit: int
+
+class Symbol:
+    @property
+    def name(self) -> str: ...
+    @property
+    def extends(self) -> Optional[str]: ...
+    @property
+    def description(self) -> Optional[str]: ...
+    @property
+    def keywords(self) -> List[str]: ...
+    @property
+    def fp_filters(self) -> List[str]: ...
+    @property
+    def power(self) -> bool: ...
+    @property
+    def unit_count(self) -> int: ...
+    @property
+    def properties(self) -> List[Tuple[str, str]]: ...
+    @property
+    def pins(self) -> List[Pin]: ...
+    def property(self, name: str) -> Optional[str]: ...
+    def to_dict(self) -> Dict[str, Any]: ...
+
+def parse_layers_only(content: str) -> Board: ...
+def parse_pcb(content: str) -> Board: ...
+def parse_pcb_file(path: str) -> Board: ...
+def parse_symbol_lib(content: str) -> List[SymbolInfo]: ...
+def parse_symbol_lib_full(content: str) -> List[Symbol]: ...
+def parse_symbol_lib_file(path: str) -> List[Symbol]: ...
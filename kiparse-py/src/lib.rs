@@ -0,0 +1,734 @@
+//! Python bindings for kiparse
+//!
+//! Exposes the layer, board, detail and symbol parsers to Python. Results are
+//! read-only classes with typed attributes; the board and symbol classes also
+//! have `to_dict()`, which returns every parsed field as plain Python values.
+//!
+//! ```python
+//! import kiparse
+//!
+//! board = kiparse.parse_pcb_file("board.kicad_pcb")
+//! for fp in board.footprints:
+//!     print(fp.reference, fp.value, fp.x, fp.y)
+//! ```
+
+// pyo3 0.22's `#[pymethods]` expansion converts `PyResult` returns into
+// themselves, which clippy flags in the generated code rather than ours
+#![allow(clippy::useless_conversion)]
+
+use std::collections::HashMap;
+
+use kiparse::pcb::detail_parser;
+use kiparse::units::Length;
+use kiparse::KicadError;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+
+fn to_py_err(error: KicadError) -> PyErr {
+    match error {
+        KicadError::IoError(e) => PyIOError::new_err(e.to_string()),
+        other => PyValueError::new_err(other.to_string()),
+    }
+}
+
+/// A serializable value as Python dicts, lists and scalars, through `json.loads`
+fn to_py_value(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let text = serde_json::to_string(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (text,))?
+        .unbind())
+}
+
+fn read(path: &str) -> PyResult<String> {
+    std::fs::read_to_string(path).map_err(|e| PyIOError::new_err(format!("{}: {}", path, e)))
+}
+
+/// A board layer
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Layer {
+    id: i32,
+    name: String,
+    layer_type: String,
+    user_name: Option<String>,
+}
+
+#[pymethods]
+impl Layer {
+    fn __repr__(&self) -> String {
+        format!("Layer(id={}, name={:?}, layer_type={:?})", self.id, self.name, self.layer_type)
+    }
+}
+
+impl From<&kiparse::Layer> for Layer {
+    fn from(layer: &kiparse::Layer) -> Self {
+        Layer {
+            id: layer.id,
+            name: layer.name.clone(),
+            layer_type: layer.layer_type.clone(),
+            user_name: layer.user_name.clone(),
+        }
+    }
+}
+
+/// A footprint pad, positioned on the board
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Pad {
+    number: String,
+    pad_type: String,
+    shape: String,
+    /// Board position in mm
+    x: f64,
+    y: f64,
+    rotation: f64,
+    width: f64,
+    height: f64,
+    drill: Option<f64>,
+    layers: Vec<String>,
+    net: Option<String>,
+}
+
+#[pymethods]
+impl Pad {
+    fn __repr__(&self) -> String {
+        format!("Pad(number={:?}, net={:?}, x={}, y={})", self.number, self.net, self.x, self.y)
+    }
+}
+
+/// A placed footprint
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Footprint {
+    reference: String,
+    value: String,
+    /// Library identifier, e.g. `Resistor_SMD:R_0603_1608Metric`
+    name: String,
+    x: f64,
+    y: f64,
+    rotation: f64,
+    layer: String,
+    dnp: bool,
+    properties: HashMap<String, String>,
+    pads: Vec<Pad>,
+}
+
+#[pymethods]
+impl Footprint {
+    fn __repr__(&self) -> String {
+        format!(
+            "Footprint(reference={:?}, value={:?}, x={}, y={}, layer={:?})",
+            self.reference, self.value, self.x, self.y, self.layer
+        )
+    }
+}
+
+impl From<&kiparse::Footprint> for Footprint {
+    fn from(footprint: &kiparse::Footprint) -> Self {
+        let property = |name| footprint.property(name).unwrap_or_default().to_string();
+        Footprint {
+            reference: property("Reference"),
+            value: property("Value"),
+            name: footprint.name.clone(),
+            x: footprint.position.x.as_mm(),
+            y: footprint.position.y.as_mm(),
+            rotation: footprint.rotation,
+            layer: footprint.layer.clone(),
+            dnp: footprint.attributes.dnp,
+            properties: footprint.properties.clone(),
+            pads: footprint
+                .pads
+                .iter()
+                .map(|pad| {
+                    let at = footprint.pad_position(pad);
+                    Pad {
+                        number: pad.number.clone(),
+                        pad_type: pad.pad_type.clone(),
+                        shape: pad.shape.clone(),
+                        x: at.x.as_mm(),
+                        y: at.y.as_mm(),
+                        rotation: pad.rotation,
+                        width: pad.size.x.as_mm(),
+                        height: pad.size.y.as_mm(),
+                        drill: pad.drill.map(Length::as_mm),
+                        layers: pad.layers.clone(),
+                        net: pad.net.clone(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A straight track segment
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Track {
+    start: (f64, f64),
+    end: (f64, f64),
+    width: f64,
+    layer: String,
+    net: Option<String>,
+}
+
+#[pymethods]
+impl Track {
+    fn __repr__(&self) -> String {
+        format!(
+            "Track(start={:?}, end={:?}, width={}, layer={:?}, net={:?})",
+            self.start, self.end, self.width, self.layer, self.net
+        )
+    }
+}
+
+/// A via
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Via {
+    x: f64,
+    y: f64,
+    size: f64,
+    drill: f64,
+    layers: Vec<String>,
+    net: Option<String>,
+}
+
+#[pymethods]
+impl Via {
+    fn __repr__(&self) -> String {
+        format!("Via(x={}, y={}, size={}, drill={}, net={:?})", self.x, self.y, self.size, self.drill, self.net)
+    }
+}
+
+/// A parsed board
+///
+/// Attributes cover the common elements; `to_dict()` has everything else (zones,
+/// graphics, stackup, net classes, ...).
+#[pyclass(module = "kiparse", frozen)]
+struct Board {
+    inner: kiparse::PcbFile,
+}
+
+#[pymethods]
+impl Board {
+    #[getter]
+    fn version(&self) -> &str {
+        &self.inner.version
+    }
+
+    #[getter]
+    fn generator(&self) -> &str {
+        &self.inner.generator
+    }
+
+    /// Board thickness in mm
+    #[getter]
+    fn thickness(&self) -> Option<f64> {
+        self.inner.board_thickness.map(Length::as_mm)
+    }
+
+    /// Layers in id order
+    #[getter]
+    fn layers(&self) -> Vec<Layer> {
+        let mut layers: Vec<Layer> = self.inner.layers.values().map(Layer::from).collect();
+        layers.sort_by_key(|l| l.id);
+        layers
+    }
+
+    #[getter]
+    fn footprints(&self) -> Vec<Footprint> {
+        self.inner.footprints.iter().map(Footprint::from).collect()
+    }
+
+    #[getter]
+    fn tracks(&self) -> Vec<Track> {
+        self.inner
+            .tracks
+            .iter()
+            .map(|t| Track {
+                start: (t.start.x.as_mm(), t.start.y.as_mm()),
+                end: (t.end.x.as_mm(), t.end.y.as_mm()),
+                width: t.width.as_mm(),
+                layer: t.layer.clone(),
+                net: t.net.clone(),
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn vias(&self) -> Vec<Via> {
+        self.inner
+            .vias
+            .iter()
+            .map(|v| Via {
+                x: v.position.x.as_mm(),
+                y: v.position.y.as_mm(),
+                size: v.size.as_mm(),
+                drill: v.drill.as_mm(),
+                layers: v.layers.clone(),
+                net: v.net.clone(),
+            })
+            .collect()
+    }
+
+    /// Net names by net number
+    #[getter]
+    fn nets(&self) -> HashMap<u32, String> {
+        self.inner.nets.clone()
+    }
+
+    /// Footprint with the given reference, if any
+    fn footprint(&self, reference: &str) -> Option<Footprint> {
+        self.inner.footprint_by_ref(reference).map(Footprint::from)
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py_value(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Board(version={:?}, layers={}, footprints={}, tracks={}, vias={})",
+            self.inner.version,
+            self.inner.layers.len(),
+            self.inner.footprints.len(),
+            self.inner.tracks.len(),
+            self.inner.vias.len()
+        )
+    }
+}
+
+/// Full board parser, as `PcbParser(content).parse()`
+#[pyclass(module = "kiparse", name = "PcbParser")]
+struct PyPcbParser {
+    content: String,
+}
+
+#[pymethods]
+impl PyPcbParser {
+    #[new]
+    fn new(content: String) -> Self {
+        PyPcbParser { content }
+    }
+
+    fn parse(&self) -> PyResult<Board> {
+        let inner = kiparse::PcbParser::new(&self.content).parse().map_err(to_py_err)?;
+        Ok(Board { inner })
+    }
+}
+
+/// Component found by the detail parser
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct ComponentInfo {
+    reference: String,
+    footprint: String,
+    x: f64,
+    y: f64,
+    rotation: f64,
+    layer: String,
+    value: Option<String>,
+}
+
+#[pymethods]
+impl ComponentInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "ComponentInfo(reference={:?}, footprint={:?}, x={}, y={})",
+            self.reference, self.footprint, self.x, self.y
+        )
+    }
+}
+
+/// 3D model reference found by the detail parser
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Model3DInfo {
+    reference: String,
+    footprint: String,
+    model_path: String,
+    /// `wrl`, `step`, `iges` or `other`
+    model_type: String,
+    embedded: bool,
+}
+
+#[pymethods]
+impl Model3DInfo {
+    fn __repr__(&self) -> String {
+        format!("Model3DInfo(reference={:?}, model_path={:?})", self.reference, self.model_path)
+    }
+}
+
+/// Track found by the detail parser
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct TrackInfo {
+    start: (f64, f64),
+    end: (f64, f64),
+    width: f64,
+    layer: String,
+    net: Option<i32>,
+    net_name: Option<String>,
+}
+
+#[pymethods]
+impl TrackInfo {
+    fn __repr__(&self) -> String {
+        format!("TrackInfo(start={:?}, end={:?}, layer={:?})", self.start, self.end, self.layer)
+    }
+}
+
+/// Via found by the detail parser
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct ViaInfo {
+    x: f64,
+    y: f64,
+    size: f64,
+    drill: f64,
+    layers: (String, String),
+    net: Option<i32>,
+    net_name: Option<String>,
+}
+
+#[pymethods]
+impl ViaInfo {
+    fn __repr__(&self) -> String {
+        format!("ViaInfo(x={}, y={}, size={}, drill={})", self.x, self.y, self.size, self.drill)
+    }
+}
+
+/// Bounding box of the Edge.Cuts outline
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct BoardOutline {
+    min_x: f64,
+    min_y: f64,
+    max_x: f64,
+    max_y: f64,
+    width_mm: f64,
+    height_mm: f64,
+}
+
+#[pymethods]
+impl BoardOutline {
+    fn __repr__(&self) -> String {
+        format!("BoardOutline(width_mm={}, height_mm={})", self.width_mm, self.height_mm)
+    }
+}
+
+/// Fast extraction of single element kinds, as `DetailParser(content).extract_components()`
+#[pyclass(module = "kiparse", name = "DetailParser")]
+struct PyDetailParser {
+    content: String,
+}
+
+#[pymethods]
+impl PyDetailParser {
+    #[new]
+    fn new(content: String) -> Self {
+        PyDetailParser { content }
+    }
+
+    fn extract_components(&self) -> PyResult<Vec<ComponentInfo>> {
+        let components = self.parser().extract_components().map_err(to_py_err)?;
+        Ok(components
+            .into_iter()
+            .map(|c| ComponentInfo {
+                reference: c.reference,
+                footprint: c.footprint,
+                x: c.position.0,
+                y: c.position.1,
+                rotation: c.rotation,
+                layer: c.layer,
+                value: c.value,
+            })
+            .collect())
+    }
+
+    fn extract_3d_models(&self) -> PyResult<Vec<Model3DInfo>> {
+        let models = self.parser().extract_3d_models().map_err(to_py_err)?;
+        Ok(models
+            .into_iter()
+            .map(|m| Model3DInfo {
+                embedded: m.is_embedded(),
+                model_type: match m.model_type {
+                    detail_parser::ModelType::Wrl => "wrl",
+                    detail_parser::ModelType::Step => "step",
+                    detail_parser::ModelType::Iges => "iges",
+                    detail_parser::ModelType::Other => "other",
+                }
+                .to_string(),
+                reference: m.reference,
+                footprint: m.footprint,
+                model_path: m.model_path,
+            })
+            .collect())
+    }
+
+    fn extract_nets(&self) -> PyResult<HashMap<u32, String>> {
+        self.parser().extract_nets().map_err(to_py_err)
+    }
+
+    fn extract_tracks(&self) -> PyResult<Vec<TrackInfo>> {
+        let tracks = self.parser().extract_tracks().map_err(to_py_err)?;
+        Ok(tracks
+            .into_iter()
+            .map(|t| TrackInfo {
+                start: t.start,
+                end: t.end,
+                width: t.width,
+                layer: t.layer,
+                net: t.net,
+                net_name: t.net_name,
+            })
+            .collect())
+    }
+
+    fn extract_vias(&self) -> PyResult<Vec<ViaInfo>> {
+        let vias = self.parser().extract_vias().map_err(to_py_err)?;
+        Ok(vias
+            .into_iter()
+            .map(|v| ViaInfo {
+                x: v.position.0,
+                y: v.position.1,
+                size: v.size,
+                drill: v.drill,
+                layers: v.layers,
+                net: v.net,
+                net_name: v.net_name,
+            })
+            .collect())
+    }
+
+    fn extract_board_outline(&self) -> PyResult<Option<BoardOutline>> {
+        let outline = self.parser().extract_board_outline().map_err(to_py_err)?;
+        Ok(outline.map(|o| BoardOutline {
+            min_x: o.min_x,
+            min_y: o.min_y,
+            max_x: o.max_x,
+            max_y: o.max_y,
+            width_mm: o.width_mm,
+            height_mm: o.height_mm,
+        }))
+    }
+
+    /// Component counts by reference prefix
+    fn extract_component_summary(&self) -> PyResult<HashMap<String, usize>> {
+        self.parser().extract_component_summary().map_err(to_py_err)
+    }
+}
+
+impl PyDetailParser {
+    fn parser(&self) -> kiparse::DetailParser<'_> {
+        kiparse::DetailParser::new(&self.content)
+    }
+}
+
+/// Symbol name, description and properties from the fast library scanner
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct SymbolInfo {
+    name: String,
+    description: String,
+    properties: HashMap<String, String>,
+}
+
+#[pymethods]
+impl SymbolInfo {
+    fn __repr__(&self) -> String {
+        format!("SymbolInfo(name={:?}, description={:?})", self.name, self.description)
+    }
+}
+
+/// A symbol pin
+#[pyclass(module = "kiparse", frozen, get_all)]
+#[derive(Clone)]
+struct Pin {
+    number: String,
+    name: String,
+    /// Electrical type, e.g. `input`, `bidirectional`, `power_in`
+    pin_type: String,
+    /// Graphic style, e.g. `line`, `inverted`, `clock`
+    shape: String,
+    x: f64,
+    y: f64,
+    length: f64,
+    rotation: f64,
+    hidden: bool,
+    /// Unit the pin belongs to; 0 means common to all units
+    unit: u32,
+}
+
+#[pymethods]
+impl Pin {
+    fn __repr__(&self) -> String {
+        format!("Pin(number={:?}, name={:?}, pin_type={:?})", self.number, self.name, self.pin_type)
+    }
+}
+
+/// A library symbol with its pins and properties
+#[pyclass(module = "kiparse", frozen)]
+struct Symbol {
+    inner: kiparse::pcb::types::Symbol,
+}
+
+#[pymethods]
+impl Symbol {
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[getter]
+    fn extends(&self) -> Option<&str> {
+        self.inner.extends.as_deref()
+    }
+
+    #[getter]
+    fn description(&self) -> Option<&str> {
+        self.inner.description()
+    }
+
+    #[getter]
+    fn keywords(&self) -> Vec<&str> {
+        self.inner.keywords()
+    }
+
+    #[getter]
+    fn fp_filters(&self) -> Vec<&str> {
+        self.inner.fp_filters()
+    }
+
+    #[getter]
+    fn power(&self) -> bool {
+        self.inner.power
+    }
+
+    #[getter]
+    fn unit_count(&self) -> u32 {
+        self.inner.unit_count
+    }
+
+    /// Properties by name, in file order
+    #[getter]
+    fn properties(&self) -> Vec<(String, String)> {
+        self.inner
+            .properties
+            .iter()
+            .map(|p| (p.name.clone(), p.value.clone()))
+            .collect()
+    }
+
+    /// Electrical pins across all units, alternate body styles left out
+    #[getter]
+    fn pins(&self) -> Vec<Pin> {
+        self.inner
+            .pins()
+            .into_iter()
+            .map(|p| Pin {
+                number: p.number.clone(),
+                name: p.name.clone(),
+                pin_type: p.pin_type.clone(),
+                shape: p.shape.clone(),
+                x: p.at.x.as_mm(),
+                y: p.at.y.as_mm(),
+                length: p.length.as_mm(),
+                rotation: p.rotation,
+                hidden: p.hidden,
+                unit: p.unit,
+            })
+            .collect()
+    }
+
+    /// Value of the named property
+    fn property(&self, name: &str) -> Option<&str> {
+        self.inner.property(name)
+    }
+
+    fn to_dict(&self, py: Python<'_>) -> PyResult<PyObject> {
+        to_py_value(py, &self.inner)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Symbol(name={:?}, pins={})", self.inner.name, self.inner.pins().len())
+    }
+}
+
+/// Layers of a board, without parsing anything else
+#[pyfunction]
+fn parse_layers_only(content: &str) -> PyResult<Board> {
+    let inner = kiparse::parse_layers_only(content).map_err(to_py_err)?;
+    Ok(Board { inner })
+}
+
+/// Parse a whole board from its text
+#[pyfunction]
+fn parse_pcb(content: &str) -> PyResult<Board> {
+    let inner = kiparse::parse_pcb(content).map_err(to_py_err)?;
+    Ok(Board { inner })
+}
+
+/// Parse a whole board from a `.kicad_pcb` file
+#[pyfunction]
+fn parse_pcb_file(path: &str) -> PyResult<Board> {
+    parse_pcb(&read(path)?)
+}
+
+/// Names, descriptions and properties of the symbols of a library
+#[pyfunction]
+fn parse_symbol_lib(content: &str) -> PyResult<Vec<SymbolInfo>> {
+    let symbols = kiparse::parse_symbol_lib(content).map_err(to_py_err)?;
+    Ok(symbols
+        .into_iter()
+        .map(|s| SymbolInfo {
+            name: s.name,
+            description: s.description,
+            properties: s.properties,
+        })
+        .collect())
+}
+
+/// Full symbols of a library, with derived symbols resolved against their parents
+#[pyfunction]
+fn parse_symbol_lib_full(content: &str) -> PyResult<Vec<Symbol>> {
+    let mut symbols = kiparse::parse_symbol_lib_full(content).map_err(to_py_err)?;
+    kiparse::symbol::resolve_extends(&mut symbols);
+    Ok(symbols.into_iter().map(|inner| Symbol { inner }).collect())
+}
+
+/// Full symbols of a `.kicad_sym` file
+#[pyfunction]
+fn parse_symbol_lib_file(path: &str) -> PyResult<Vec<Symbol>> {
+    parse_symbol_lib_full(&read(path)?)
+}
+
+#[pymodule]
+fn _kiparse(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(parse_layers_only, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_pcb, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_pcb_file, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_symbol_lib, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_symbol_lib_full, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_symbol_lib_file, m)?)?;
+    m.add_class::<Board>()?;
+    m.add_class::<Layer>()?;
+    m.add_class::<Footprint>()?;
+    m.add_class::<Pad>()?;
+    m.add_class::<Track>()?;
+    m.add_class::<Via>()?;
+    m.add_class::<PyPcbParser>()?;
+    m.add_class::<PyDetailParser>()?;
+    m.add_class::<ComponentInfo>()?;
+    m.add_class::<Model3DInfo>()?;
+    m.add_class::<TrackInfo>()?;
+    m.add_class::<ViaInfo>()?;
+    m.add_class::<BoardOutline>()?;
+    m.add_class::<SymbolInfo>()?;
+    m.add_class::<Symbol>()?;
+    m.add_class::<Pin>()?;
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! JavaScript shapes of the detail parser results
+//!
+//! The bindings have always returned components and vias with flat `x`/`y`
+//! fields and 3D models with an `embedded` flag. The serde derives of the
+//! detail parser types give `position: [x, y]` and no flag, so these wrappers
+//! keep the shapes scripts rely on. Tracks and the board outline are sent as
+//! the types serialize.
+
+use kiparse::pcb::copper::copper_layers;
+use kiparse::pcb::detail_parser::{ComponentInfo, Model3DInfo, ModelType, ViaInfo};
+use kiparse::{DetailParser, PcbFile, Result};
+use serde::Serialize;
+
+/// `{reference, footprint, x, y, rotation, layer, value}`
+#[derive(Debug, Clone, Serialize)]
+pub struct Component {
+    pub reference: String,
+    pub footprint: String,
+    pub x: f64,
+    pub y: f64,
+    pub rotation: f64,
+    pub layer: String,
+    pub value: Option<String>,
+}
+
+impl From<ComponentInfo> for Component {
+    fn from(c: ComponentInfo) -> Self {
+        Component {
+            reference: c.reference,
+            footprint: c.footprint,
+            x: c.position.0,
+            y: c.position.1,
+            rotation: c.rotation,
+            layer: c.layer,
+            value: c.value,
+        }
+    }
+}
+
+/// `{reference, footprint, embedded, model_path, model_type}`
+#[derive(Debug, Clone, Serialize)]
+pub struct Model {
+    pub reference: String,
+    pub footprint: String,
+    /// `kicad-embed://` model stored in the file rather than on disk
+    pub embedded: bool,
+    pub model_path: String,
+    pub model_type: ModelType,
+}
+
+impl From<Model3DInfo> for Model {
+    fn from(m: Model3DInfo) -> Self {
+        Model {
+            embedded: m.is_embedded(),
+            reference: m.reference,
+            footprint: m.footprint,
+            model_path: m.model_path,
+            model_type: m.model_type,
+        }
+    }
+}
+
+/// `{x, y, size, drill, layers: [from, to], net, net_name}`
+#[derive(Debug, Clone, Serialize)]
+pub struct Via {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub drill: f64,
+    pub layers: (String, String),
+    pub net: Option<i32>,
+    pub net_name: Option<String>,
+}
+
+impl From<ViaInfo> for Via {
+    fn from(v: ViaInfo) -> Self {
+        Via {
+            x: v.position.0,
+            y: v.position.1,
+            size: v.size,
+            drill: v.drill,
+            layers: v.layers,
+            net: v.net,
+            net_name: v.net_name,
+        }
+    }
+}
+
+pub fn components(content: &str) -> Result<Vec<Component>> {
+    let components = DetailParser::new(content).extract_components()?;
+    Ok(components.into_iter().map(Component::from).collect())
+}
+
+pub fn models(content: &str) -> Result<Vec<Model>> {
+    let models = DetailParser::new(content).extract_3d_models()?;
+    Ok(models.into_iter().map(Model::from).collect())
+}
+
+pub fn vias(content: &str) -> Result<Vec<Via>> {
+    let vias = DetailParser::new(content).extract_vias()?;
+    Ok(vias.into_iter().map(Via::from).collect())
+}
+
+/// Copper layers from back to front, then Edge.Cuts, for drawing bottom up
+pub fn default_layers(pcb: &PcbFile) -> Vec<String> {
+    let mut layers = copper_layers(pcb);
+    layers.reverse();
+    layers.push("Edge.Cuts".to_string());
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_shape() {
+        let content = r#"
+        (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 10 20 90)
+            (property "Reference" "U1")
+            (model "kicad-embed://SOIC-8.step"))
+        (via (at 1 2) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 0))
+        "#;
+
+        let component = serde_json::to_value(&components(content).unwrap()[0]).unwrap();
+        assert_eq!((component["x"].as_f64(), component["y"].as_f64()), (Some(10.0), Some(20.0)));
+        let model = serde_json::to_value(&models(content).unwrap()[0]).unwrap();
+        assert_eq!(model["embedded"], true);
+        assert_eq!(model["model_type"], "step");
+        let via = serde_json::to_value(&vias(content).unwrap()[0]).unwrap();
+        assert_eq!(via["layers"], serde_json::json!(["F.Cu", "B.Cu"]));
+        assert_eq!(via["x"], 1.0);
+    }
+}
@@ -0,0 +1,156 @@
+//! WebAssembly bindings for kiparse
+//!
+//! Parses boards and symbol libraries in the browser. Every export takes the file
+//! text and returns a plain JavaScript object (maps become objects, not `Map`s),
+//! or a JSON string for the `*Json` variants; parse errors are thrown as `Error`.
+//!
+//! ```js
+//! import init, { parsePcb, DetailParser, renderSvg } from "kiparse-wasm";
+//!
+//! await init();
+//! const text = await (await fetch("board.kicad_pcb")).text();
+//! const board = parsePcb(text);
+//! console.log(board.footprints.length, board.board_thickness);
+//! const outline = new DetailParser(text).extractBoardOutline();
+//! document.body.innerHTML = renderSvg(text, ["F.Cu", "Edge.Cuts"]);
+//! ```
+//!
+//! The shapes of the `DetailParser` results are the structs in [`json`], which
+//! are available without the `wasm` feature.
+
+pub mod json;
+
+#[cfg(feature = "wasm")]
+mod bindings {
+    use serde::Serialize;
+    use wasm_bindgen::prelude::*;
+
+    use crate::json;
+    use kiparse::render::{render_svg, RenderOptions};
+    use kiparse::KicadError;
+
+    fn to_js_error(error: KicadError) -> JsError {
+        JsError::new(&error.to_string())
+    }
+
+    /// Plain objects rather than `Map`s, so results can go straight into `JSON.stringify`
+    fn to_js(value: &impl Serialize) -> Result<JsValue, JsError> {
+        value
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Board with only its header, layers and stackup filled in
+    #[wasm_bindgen(js_name = parseLayersOnly)]
+    pub fn parse_layers_only(content: &str) -> Result<JsValue, JsError> {
+        to_js(&kiparse::parse_layers_only(content).map_err(to_js_error)?)
+    }
+
+    /// Whole board
+    #[wasm_bindgen(js_name = parsePcb)]
+    pub fn parse_pcb(content: &str) -> Result<JsValue, JsError> {
+        to_js(&kiparse::parse_pcb(content).map_err(to_js_error)?)
+    }
+
+    /// Whole board as a JSON string, cheaper than an object for large boards that
+    /// are only passed on
+    #[wasm_bindgen(js_name = parsePcbJson)]
+    pub fn parse_pcb_json(content: &str) -> Result<String, JsError> {
+        let pcb = kiparse::parse_pcb(content).map_err(to_js_error)?;
+        serde_json::to_string(&pcb).map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Counts, track lengths, minimum sizes and area of a board
+    #[wasm_bindgen(js_name = boardStats)]
+    pub fn board_stats(content: &str) -> Result<JsValue, JsError> {
+        to_js(&kiparse::parse_pcb(content).map_err(to_js_error)?.stats())
+    }
+
+    /// SVG of the given layers, first at the bottom; all copper layers and
+    /// Edge.Cuts when `layers` is empty
+    #[wasm_bindgen(js_name = renderSvg)]
+    pub fn render(content: &str, layers: Vec<String>) -> Result<String, JsError> {
+        let pcb = kiparse::parse_pcb(content).map_err(to_js_error)?;
+        let layers = if layers.is_empty() {
+            json::default_layers(&pcb)
+        } else {
+            layers
+        };
+        let layers: Vec<&str> = layers.iter().map(String::as_str).collect();
+        render_svg(&pcb, &layers, &RenderOptions::default()).map_err(to_js_error)
+    }
+
+    /// Symbol names, descriptions and properties from the fast scanner
+    #[wasm_bindgen(js_name = parseSymbolLib)]
+    pub fn parse_symbol_lib(content: &str) -> Result<JsValue, JsError> {
+        to_js(&kiparse::parse_symbol_lib(content).map_err(to_js_error)?)
+    }
+
+    /// Full symbols with pins, units and graphics, derived symbols resolved
+    #[wasm_bindgen(js_name = parseSymbolLibFull)]
+    pub fn parse_symbol_lib_full(content: &str) -> Result<JsValue, JsError> {
+        let mut symbols = kiparse::parse_symbol_lib_full(content).map_err(to_js_error)?;
+        kiparse::symbol::resolve_extends(&mut symbols);
+        to_js(&symbols)
+    }
+
+    /// Fast extraction of single element kinds
+    #[wasm_bindgen]
+    pub struct DetailParser {
+        content: String,
+    }
+
+    #[wasm_bindgen]
+    impl DetailParser {
+        #[wasm_bindgen(constructor)]
+        pub fn new(content: String) -> DetailParser {
+            DetailParser { content }
+        }
+
+        #[wasm_bindgen(js_name = extractComponents)]
+        pub fn extract_components(&self) -> Result<JsValue, JsError> {
+            to_js(&json::components(&self.content).map_err(to_js_error)?)
+        }
+
+        #[wasm_bindgen(js_name = extract3dModels)]
+        pub fn extract_3d_models(&self) -> Result<JsValue, JsError> {
+            to_js(&json::models(&self.content).map_err(to_js_error)?)
+        }
+
+        #[wasm_bindgen(js_name = extractNets)]
+        pub fn extract_nets(&self) -> Result<JsValue, JsError> {
+            to_js(&self.parser().extract_nets().map_err(to_js_error)?)
+        }
+
+        #[wasm_bindgen(js_name = extractTracks)]
+        pub fn extract_tracks(&self) -> Result<JsValue, JsError> {
+            to_js(&self.parser().extract_tracks().map_err(to_js_error)?)
+        }
+
+        #[wasm_bindgen(js_name = extractVias)]
+        pub fn extract_vias(&self) -> Result<JsValue, JsError> {
+            to_js(&json::vias(&self.content).map_err(to_js_error)?)
+        }
+
+        /// Edge.Cuts bounding box, or `null` without an outline
+        #[wasm_bindgen(js_name = extractBoardOutline)]
+        pub fn extract_board_outline(&self) -> Result<JsValue, JsError> {
+            to_js(&self.parser().extract_board_outline().map_err(to_js_error)?)
+        }
+
+        /// Component counts by reference prefix
+        #[wasm_bindgen(js_name = extractComponentSummary)]
+        pub fn extract_component_summary(&self) -> Result<JsValue, JsError> {
+            to_js(&self.parser().extract_component_summary().map_err(to_js_error)?)
+        }
+    }
+
+    impl DetailParser {
+        fn parser(&self) -> kiparse::DetailParser<'_> {
+            kiparse::DetailParser::new(&self.content)
+        }
+    }
+}
+
+#[cfg(feature = "wasm")]
+pub use bindings::*;
@@ -0,0 +1,269 @@
+//! Annotation checks on schematic hierarchies and boards
+//!
+//! [`check_hierarchy`] and [`check_board`] look for the annotation problems
+//! that break netlists and cross-probing: parts never annotated (`R?`, or
+//! `REF**` on a board), one reference given to different parts, and gaps in
+//! the numbering of a reference prefix (`R1`, `R2`, `R4`). Each
+//! [`AnnotationIssue`] names the sheet it belongs to, so a report can be
+//! grouped per sheet; a reference used on several sheets is reported on each.
+//!
+//! The units of a multi-unit part share a reference, so a schematic duplicate
+//! is a unit placed twice or a reference shared by different library symbols.
+//! Power symbols (`#PWR01`) only need annotating, and board-only footprints
+//! such as logos are left out. Numbering by sheet (`R101`, `R201`) is not
+//! taken for a gap: a jump to a number ending in `01` starts a new block.
+//!
+//! ```rust
+//! use kiparse::annotation::{check_hierarchy, AnnotationCheck};
+//! use kiparse::schematic::Hierarchy;
+//!
+//! let hierarchy = Hierarchy::build("demo.kicad_sch", r#"(kicad_sch (uuid "root")
+//!   (symbol (lib_id "Device:R") (at 0 0 0) (property "Reference" "R1"))
+//!   (symbol (lib_id "Device:R") (at 10 0 0) (property "Reference" "R3"))
+//!   (symbol (lib_id "Device:C") (at 20 0 0) (property "Reference" "C?")))"#, |_| unreachable!())?;
+//!
+//! let issues = check_hierarchy(&hierarchy);
+//! let checks: Vec<AnnotationCheck> = issues.iter().map(|i| i.check).collect();
+//! assert_eq!(checks, [AnnotationCheck::Unannotated, AnnotationCheck::NumberingGap]);
+//! assert_eq!(issues[1].message, "R2 is missing between R1 and R3");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::bom::natural_order;
+use crate::pcb::types::PcbFile;
+use crate::schematic::Hierarchy;
+use crate::validate::Severity;
+
+/// Check an [`AnnotationIssue`] comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AnnotationCheck {
+    /// A reference without a number, e.g. `R?`
+    Unannotated,
+    /// One reference on different parts, or on the same unit twice
+    DuplicateReference,
+    /// Numbers missing between two references of a prefix
+    NumberingGap,
+}
+
+impl AnnotationCheck {
+    /// Name used in reports, e.g. `duplicate_reference`
+    pub fn name(self) -> &'static str {
+        match self {
+            AnnotationCheck::Unannotated => "unannotated",
+            AnnotationCheck::DuplicateReference => "duplicate_reference",
+            AnnotationCheck::NumberingGap => "numbering_gap",
+        }
+    }
+}
+
+/// One finding of [`check_hierarchy`] or [`check_board`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnotationIssue {
+    pub check: AnnotationCheck,
+    pub severity: Severity,
+    /// References involved; for [`AnnotationCheck::NumberingGap`] the two
+    /// either side of the gap
+    pub references: Vec<String>,
+    /// Name path of the sheet, e.g. `/` or `/Power/`; for a gap, the sheet of
+    /// the reference after it
+    pub sheet: String,
+    pub message: String,
+}
+
+/// A reference as placed on a sheet
+struct Placement {
+    reference: String,
+    unit: u32,
+    /// Library symbol or footprint, which tells units of one part from
+    /// different parts
+    part: String,
+    sheet: String,
+}
+
+/// Annotation checks of every sheet instance of a design, in the order of
+/// [`AnnotationCheck`] and then of references
+///
+/// Unannotated parts and duplicates are errors; gaps are warnings, as deleted
+/// parts leave them behind without harm.
+pub fn check_hierarchy(hierarchy: &Hierarchy) -> Vec<AnnotationIssue> {
+    let placements = hierarchy
+        .symbols()
+        .into_iter()
+        .map(|s| Placement {
+            reference: s.reference,
+            unit: s.unit,
+            part: s.symbol.lib_id.clone(),
+            sheet: s.sheet_path,
+        })
+        .collect();
+    check(placements)
+}
+
+/// Annotation checks of the footprints of a board, like [`check_hierarchy`]
+///
+/// Footprints are reported under the schematic sheet they were placed from,
+/// or `/` when the board does not record it.
+pub fn check_board(pcb: &PcbFile) -> Vec<AnnotationIssue> {
+    let placements = pcb
+        .footprints
+        .iter()
+        .filter(|f| !f.attributes.board_only)
+        .map(|f| Placement {
+            reference: f.property("Reference").unwrap_or_default().to_string(),
+            unit: 1,
+            part: f.name.clone(),
+            sheet: f.sheet_name.clone().unwrap_or_else(|| "/".to_string()),
+        })
+        .collect();
+    check(placements)
+}
+
+fn check(placements: Vec<Placement>) -> Vec<AnnotationIssue> {
+    let mut issues = Vec::new();
+    let mut annotated: BTreeMap<String, Vec<Placement>> = BTreeMap::new();
+    for placement in placements {
+        if number(&placement.reference).is_none() {
+            let shown = if placement.reference.is_empty() { "(empty)" } else { placement.reference.as_str() };
+            issues.push(AnnotationIssue {
+                check: AnnotationCheck::Unannotated,
+                severity: Severity::Error,
+                references: vec![placement.reference.clone()],
+                message: format!("{} ({}) is not annotated", shown, placement.part),
+                sheet: placement.sheet,
+            });
+        } else if !placement.reference.starts_with('#') {
+            annotated.entry(placement.reference.clone()).or_default().push(placement);
+        }
+    }
+
+    for (reference, same) in &annotated {
+        let parts: BTreeSet<&str> = same.iter().map(|p| p.part.as_str()).collect();
+        let units: BTreeSet<u32> = same.iter().map(|p| p.unit).collect();
+        if parts.len() == 1 && units.len() == same.len() {
+            continue;
+        }
+        let sheets: BTreeSet<&str> = same.iter().map(|p| p.sheet.as_str()).collect();
+        let message = format!(
+            "{} is used {} times ({}) on {}",
+            reference,
+            same.len(),
+            parts.iter().copied().collect::<Vec<_>>().join(", "),
+            sheets.iter().copied().collect::<Vec<_>>().join(", ")
+        );
+        for sheet in sheets {
+            issues.push(AnnotationIssue {
+                check: AnnotationCheck::DuplicateReference,
+                severity: Severity::Error,
+                references: vec![reference.clone()],
+                sheet: sheet.to_string(),
+                message: message.clone(),
+            });
+        }
+    }
+
+    let mut by_prefix: BTreeMap<&str, BTreeMap<u64, &Placement>> = BTreeMap::new();
+    for (reference, same) in &annotated {
+        if let Some((prefix, n)) = number(reference) {
+            by_prefix.entry(prefix).or_default().insert(n, &same[0]);
+        }
+    }
+    for (prefix, numbers) in by_prefix {
+        let numbers: Vec<(u64, &Placement)> = numbers.into_iter().collect();
+        for pair in numbers.windows(2) {
+            let ((before, _), (after, placement)) = (pair[0], pair[1]);
+            if after == before + 1 || (after % 100 == 1 && after > 100) {
+                continue;
+            }
+            let missing = if after == before + 2 {
+                format!("{}{} is", prefix, before + 1)
+            } else {
+                format!("{}{} to {}{} are", prefix, before + 1, prefix, after - 1)
+            };
+            issues.push(AnnotationIssue {
+                check: AnnotationCheck::NumberingGap,
+                severity: Severity::Warning,
+                references: vec![format!("{}{}", prefix, before), placement.reference.clone()],
+                sheet: placement.sheet.clone(),
+                message: format!("{} missing between {}{} and {}", missing, prefix, before, placement.reference),
+            });
+        }
+    }
+
+    issues.sort_by(|a, b| {
+        a.check
+            .cmp(&b.check)
+            .then_with(|| natural_order(&a.references[0], &b.references[0]))
+            .then_with(|| a.sheet.cmp(&b.sheet))
+    });
+    issues
+}
+
+/// Prefix and number of an annotated reference, `None` for `R?` or `REF**`
+fn number(reference: &str) -> Option<(&str, u64)> {
+    let prefix = reference.trim_end_matches(|c: char| c.is_ascii_digit());
+    if prefix.is_empty() || prefix.len() == reference.len() {
+        return None;
+    }
+    Some((prefix, reference[prefix.len()..].parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    #[test]
+    fn test_hierarchy_annotation() {
+        let root = r##"(kicad_sch (uuid "root")
+  (symbol (lib_id "Amplifier:LM358") (at 0 0 0) (unit 1) (property "Reference" "U1"))
+  (symbol (lib_id "Amplifier:LM358") (at 0 20 0) (unit 2) (property "Reference" "U1"))
+  (symbol (lib_id "power:GND") (at 0 40 0) (property "Reference" "#PWR?"))
+  (sheet (at 0 0) (size 20 10) (uuid "ch1") (property "Sheetname" "CH1") (property "Sheetfile" "channel.kicad_sch"))
+  (sheet (at 0 20) (size 20 10) (uuid "ch2") (property "Sheetname" "CH2") (property "Sheetfile" "channel.kicad_sch")))"##;
+        let channel = r#"(kicad_sch (uuid "channel")
+  (symbol (lib_id "Device:R") (at 10 10 0) (uuid "r") (property "Reference" "R?")
+    (instances (project "demo" (path "/root/ch1" (reference "R1") (unit 1)) (path "/root/ch2" (reference "R1") (unit 1)))))
+  (symbol (lib_id "Device:C") (at 10 20 0) (uuid "c") (property "Reference" "C?")
+    (instances (project "demo" (path "/root/ch1" (reference "C1") (unit 1)) (path "/root/ch2" (reference "C5") (unit 1))))))"#;
+        let hierarchy = Hierarchy::build("demo.kicad_sch", root, |_| Ok(channel.to_string())).unwrap();
+
+        let issues = check_hierarchy(&hierarchy);
+        let found: Vec<(AnnotationCheck, &str, &str)> =
+            issues.iter().map(|i| (i.check, i.references[0].as_str(), i.sheet.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                (AnnotationCheck::Unannotated, "#PWR?", "/"),
+                (AnnotationCheck::DuplicateReference, "R1", "/CH1/"),
+                (AnnotationCheck::DuplicateReference, "R1", "/CH2/"),
+                (AnnotationCheck::NumberingGap, "C1", "/CH2/"),
+            ]
+        );
+        assert_eq!(issues[1].message, "R1 is used 2 times (Device:R) on /CH1/, /CH2/");
+        assert_eq!(issues[3].message, "C2 to C4 are missing between C1 and C5");
+    }
+
+    #[test]
+    fn test_board_annotation() {
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 0) (property "Reference" "R1"))
+  (footprint "Capacitor_SMD:C_0603" (layer "F.Cu") (at 2 0) (property "Reference" "R1"))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 4 0) (property "Reference" "REF**"))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 6 0) (property "Reference" "R101"))
+  (footprint "Logo" (layer "F.Cu") (at 8 0) (property "Reference" "G***") (attr board_only)))"#).unwrap();
+
+        let checks: Vec<(AnnotationCheck, Severity)> = check_board(&pcb).iter().map(|i| (i.check, i.severity)).collect();
+        assert_eq!(
+            checks,
+            [
+                (AnnotationCheck::Unannotated, Severity::Error),
+                (AnnotationCheck::DuplicateReference, Severity::Error),
+            ]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+//! Arrow and Parquet export (`arrow` feature)
+//!
+//! Turns the components, pads, tracks and vias of a board into one Arrow
+//! [`RecordBatch`] each, one row per element, so boards can be analysed in
+//! Polars, pandas or DuckDB. Lengths are in mm in board coordinates (Y down),
+//! angles in degrees. [`export_parquet`] writes all four tables as Parquet files.
+//!
+//! ```rust
+//! use kiparse::{arrow, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND")
+//!   (segment (start 0 0) (end 3 4) (width 0.25) (layer "F.Cu") (net 1))
+//! )"#)?;
+//! let tracks = arrow::tracks(&pcb)?;
+//!
+//! assert_eq!(tracks.num_rows(), 1);
+//! assert!(tracks.column_by_name("length").is_some());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow_array::builder::{ListBuilder, StringBuilder};
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, StringArray, UInt32Array};
+use parquet::arrow::ArrowWriter;
+
+pub use arrow_array::RecordBatch;
+
+use crate::error::Result;
+use crate::pcb::types::{Footprint, PcbFile, Via, ViaType};
+use crate::units::Length;
+
+/// One row per footprint
+///
+/// Columns: `reference`, `value`, `footprint`, `layer`, `x`, `y`, `rotation`,
+/// `mount`, `dnp`, `exclude_from_bom`, `exclude_from_pos_files`, `pad_count`.
+pub fn components(pcb: &PcbFile) -> Result<RecordBatch> {
+    let footprints = &pcb.footprints;
+    let text = |f: fn(&Footprint) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(footprints.iter().map(f)))
+    };
+    let number = |f: fn(&Footprint) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(footprints.iter().map(f)))
+    };
+    let flag = |f: fn(&Footprint) -> bool| -> ArrayRef {
+        Arc::new(BooleanArray::from(
+            footprints.iter().map(f).collect::<Vec<_>>(),
+        ))
+    };
+
+    Ok(RecordBatch::try_from_iter([
+        (
+            "reference",
+            text(|f| f.property("Reference").unwrap_or_default()),
+        ),
+        ("value", text(|f| f.property("Value").unwrap_or_default())),
+        ("footprint", text(|f| &f.name)),
+        ("layer", text(|f| &f.layer)),
+        ("x", number(|f| f.position.x.as_mm())),
+        ("y", number(|f| f.position.y.as_mm())),
+        ("rotation", number(|f| f.rotation)),
+        ("mount", text(|f| &f.attributes.mount)),
+        ("dnp", flag(|f| f.attributes.dnp)),
+        ("exclude_from_bom", flag(|f| f.attributes.exclude_from_bom)),
+        (
+            "exclude_from_pos_files",
+            flag(|f| f.attributes.exclude_from_pos_files),
+        ),
+        (
+            "pad_count",
+            Arc::new(UInt32Array::from_iter_values(
+                footprints.iter().map(|f| f.pads.len() as u32),
+            )),
+        ),
+    ])?)
+}
+
+/// One row per footprint pad, positioned on the board
+///
+/// Columns: `reference`, `number`, `pad_type`, `shape`, `layers` (list), `x`, `y`,
+/// `rotation`, `width`, `height`, `drill` (nullable), `net` (nullable).
+pub fn pads(pcb: &PcbFile) -> Result<RecordBatch> {
+    let pads: Vec<_> = pcb.pads_absolute().collect();
+
+    let mut layers = ListBuilder::new(StringBuilder::new());
+    for pad in &pads {
+        for layer in &pad.pad.layers {
+            layers.values().append_value(layer);
+        }
+        layers.append(true);
+    }
+    let text = |values: Vec<&str>| -> ArrayRef { Arc::new(StringArray::from(values)) };
+    let number = |values: Vec<f64>| -> ArrayRef { Arc::new(Float64Array::from(values)) };
+
+    Ok(RecordBatch::try_from_iter([
+        (
+            "reference",
+            text(
+                pads.iter().map(|p| p.reference).collect(),
+            ),
+        ),
+        (
+            "number",
+            text(pads.iter().map(|p| p.pad.number.as_str()).collect()),
+        ),
+        (
+            "pad_type",
+            text(pads.iter().map(|p| p.pad.pad_type.as_str()).collect()),
+        ),
+        (
+            "shape",
+            text(pads.iter().map(|p| p.pad.shape.as_str()).collect()),
+        ),
+        ("layers", Arc::new(layers.finish()) as ArrayRef),
+        ("x", number(pads.iter().map(|p| p.position.x.as_mm()).collect())),
+        ("y", number(pads.iter().map(|p| p.position.y.as_mm()).collect())),
+        (
+            "rotation",
+            number(pads.iter().map(|p| p.rotation).collect()),
+        ),
+        (
+            "width",
+            number(pads.iter().map(|p| p.pad.size.x.as_mm()).collect()),
+        ),
+        (
+            "height",
+            number(pads.iter().map(|p| p.pad.size.y.as_mm()).collect()),
+        ),
+        (
+            "drill",
+            Arc::new(Float64Array::from(
+                pads.iter().map(|p| p.pad.drill.map(Length::as_mm)).collect::<Vec<_>>(),
+            )),
+        ),
+        (
+            "net",
+            Arc::new(StringArray::from(
+                pads.iter()
+                    .map(|p| p.pad.net.as_deref())
+                    .collect::<Vec<_>>(),
+            )),
+        ),
+    ])?)
+}
+
+/// One row per track segment or arc
+///
+/// Columns: `kind` (`segment` or `arc`), `layer`, `net` (nullable), `start_x`,
+/// `start_y`, `end_x`, `end_y`, `mid_x` and `mid_y` (arcs only), `width`, `length`.
+pub fn tracks(pcb: &PcbFile) -> Result<RecordBatch> {
+    let segments = pcb.tracks.iter().map(|t| {
+        (
+            "segment",
+            &t.layer,
+            &t.net,
+            &t.start,
+            &t.end,
+            None,
+            t.width,
+            t.length(),
+        )
+    });
+    let arcs = pcb.arcs.iter().map(|a| {
+        (
+            "arc",
+            &a.layer,
+            &a.net,
+            &a.start,
+            &a.end,
+            Some(&a.mid),
+            a.width,
+            a.length(),
+        )
+    });
+    let rows: Vec<_> = segments.chain(arcs).collect();
+    let number = |values: Vec<f64>| -> ArrayRef { Arc::new(Float64Array::from(values)) };
+    let optional = |values: Vec<Option<f64>>| -> ArrayRef { Arc::new(Float64Array::from(values)) };
+
+    Ok(RecordBatch::try_from_iter([
+        (
+            "kind",
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.0))) as ArrayRef,
+        ),
+        (
+            "layer",
+            Arc::new(StringArray::from_iter_values(rows.iter().map(|r| r.1))),
+        ),
+        (
+            "net",
+            Arc::new(StringArray::from(
+                rows.iter().map(|r| r.2.as_deref()).collect::<Vec<_>>(),
+            )),
+        ),
+        ("start_x", number(rows.iter().map(|r| r.3.x.as_mm()).collect())),
+        ("start_y", number(rows.iter().map(|r| r.3.y.as_mm()).collect())),
+        ("end_x", number(rows.iter().map(|r| r.4.x.as_mm()).collect())),
+        ("end_y", number(rows.iter().map(|r| r.4.y.as_mm()).collect())),
+        (
+            "mid_x",
+            optional(rows.iter().map(|r| r.5.map(|m| m.x.as_mm())).collect()),
+        ),
+        (
+            "mid_y",
+            optional(rows.iter().map(|r| r.5.map(|m| m.y.as_mm())).collect()),
+        ),
+        ("width", number(rows.iter().map(|r| r.6.as_mm()).collect())),
+        ("length", number(rows.iter().map(|r| r.7).collect())),
+    ])?)
+}
+
+/// One row per via
+///
+/// Columns: `x`, `y`, `size`, `drill`, `via_type` (`through`, `blind_buried` or
+/// `micro`), `start_layer`, `end_layer`, `net` (nullable).
+pub fn vias(pcb: &PcbFile) -> Result<RecordBatch> {
+    let vias = &pcb.vias;
+    let number = |f: fn(&Via) -> f64| -> ArrayRef {
+        Arc::new(Float64Array::from_iter_values(vias.iter().map(f)))
+    };
+    let via_type = |v: &Via| match v.via_type {
+        ViaType::Through => "through",
+        ViaType::BlindBuried => "blind_buried",
+        ViaType::Micro => "micro",
+    };
+    let layer = |end: fn(&[String]) -> Option<&String>| -> ArrayRef {
+        Arc::new(StringArray::from(
+            vias.iter()
+                .map(|v| end(&v.layers).map(String::as_str))
+                .collect::<Vec<_>>(),
+        ))
+    };
+
+    Ok(RecordBatch::try_from_iter([
+        ("x", number(|v| v.position.x.as_mm())),
+        ("y", number(|v| v.position.y.as_mm())),
+        ("size", number(|v| v.size.as_mm())),
+        ("drill", number(|v| v.drill.as_mm())),
+        (
+            "via_type",
+            Arc::new(StringArray::from_iter_values(vias.iter().map(via_type))) as ArrayRef,
+        ),
+        ("start_layer", layer(<[String]>::first)),
+        ("end_layer", layer(<[String]>::last)),
+        (
+            "net",
+            Arc::new(StringArray::from(
+                vias.iter().map(|v| v.net.as_deref()).collect::<Vec<_>>(),
+            )),
+        ),
+    ])?)
+}
+
+/// Write one batch as a Parquet file
+pub fn write_parquet<W: Write + Send>(batch: &RecordBatch, writer: W) -> Result<()> {
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+/// Write `components.parquet`, `pads.parquet`, `tracks.parquet` and `vias.parquet`
+/// into `dir`, returning the paths written
+pub fn export_parquet(pcb: &PcbFile, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let tables = [
+        ("components", components(pcb)?),
+        ("pads", pads(pcb)?),
+        ("tracks", tracks(pcb)?),
+        ("vias", vias(pcb)?),
+    ];
+    let mut paths = Vec::new();
+    for (name, batch) in tables {
+        let path = dir.as_ref().join(format!("{}.parquet", name));
+        write_parquet(&batch, File::create(&path)?)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+    use arrow_array::{Array, ListArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (net 0 "") (net 1 "GND")
+  (footprint "R_0603" (layer "B.Cu") (at 10 20 90) (property "Reference" "R1") (property "Value" "1k") (attr smd dnp)
+    (pad "1" smd rect (at 1 0 90) (size 1 0.5) (layers "B.Cu" "B.Mask") (net 1 "GND"))
+    (pad "2" smd rect (at -1 0 90) (size 1 0.5) (layers "B.Cu" "B.Mask")))
+  (segment (start 0 0) (end 3 4) (width 0.25) (layer "F.Cu") (net 1))
+  (arc (start 0 0) (mid 1 1) (end 2 0) (width 0.2) (layer "B.Cu"))
+  (via micro (at 5 5) (size 0.3) (drill 0.1) (layers "F.Cu" "In1.Cu") (net 1))
+)"#;
+
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> &'a T {
+        batch
+            .column_by_name(name)
+            .unwrap()
+            .as_any()
+            .downcast_ref::<T>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_batches() {
+        let pcb = parse_pcb(BOARD).unwrap();
+
+        let components = components(&pcb).unwrap();
+        assert_eq!(components.num_rows(), 1);
+        assert_eq!(column::<StringArray>(&components, "layer").value(0), "B.Cu");
+        assert!(column::<BooleanArray>(&components, "dnp").value(0));
+        assert_eq!(column::<UInt32Array>(&components, "pad_count").value(0), 2);
+
+        let pads = pads(&pcb).unwrap();
+        assert_eq!(pads.num_rows(), 2);
+        assert_eq!(column::<Float64Array>(&pads, "y").value(0), 19.0);
+        assert!(column::<StringArray>(&pads, "net").is_null(1));
+        assert!(column::<Float64Array>(&pads, "drill").is_null(0));
+        assert_eq!(column::<ListArray>(&pads, "layers").value(0).len(), 2);
+
+        let tracks = tracks(&pcb).unwrap();
+        assert_eq!(column::<StringArray>(&tracks, "kind").value(1), "arc");
+        assert_eq!(column::<Float64Array>(&tracks, "length").value(0), 5.0);
+        assert!(column::<Float64Array>(&tracks, "mid_x").is_null(0));
+        assert_eq!(column::<Float64Array>(&tracks, "mid_y").value(1), 1.0);
+
+        let vias = vias(&pcb).unwrap();
+        assert_eq!(column::<StringArray>(&vias, "via_type").value(0), "micro");
+        assert_eq!(column::<StringArray>(&vias, "end_layer").value(0), "In1.Cu");
+    }
+
+    #[test]
+    fn test_parquet_round_trip() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let dir = std::env::temp_dir().join(format!("kiparse-arrow-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let paths = export_parquet(&pcb, &dir).unwrap();
+        assert_eq!(paths.len(), 4);
+
+        let file = File::open(dir.join("tracks.parquet")).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<RecordBatch> = reader.map(|b| b.unwrap()).collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(batches, [tracks(&pcb).unwrap()]);
+    }
+}
@@ -0,0 +1,53 @@
+//! Base64 decoding for payloads embedded in KiCad files (images, embedded files)
+
+use alloc::format;
+use alloc::vec::Vec;
+
+use crate::error::{KicadError, Result};
+
+/// Decode standard (RFC 4648) base64, ignoring whitespace and trailing padding
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for b in input.bytes() {
+        let value = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            b if b.is_ascii_whitespace() => continue,
+            other => {
+                return Err(KicadError::ParseError(format!(
+                    "Invalid base64 character '{}'",
+                    other as char
+                )))
+            }
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        assert_eq!(decode("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode("aGVs\nbG8h").unwrap(), b"hello!");
+        assert_eq!(decode("").unwrap(), b"");
+        assert!(decode("aGV$").is_err());
+    }
+}
@@ -1,151 +1,2202 @@
-use clap::{Parser, Subcommand};
-use kiparse::{pcb, symbol, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use kiparse::{annotation, bom, cam, netlist, pcb, report, schematic, spice, symbol, units, validate, Result};
+use kiparse::units::Length;
 use prettytable::{row, Table};
-use regex::Regex;
-use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "kpx")]
 #[command(about = "KiCad file parser and analyzer", long_about = None)]
 #[command(version)]
 struct Cli {
-    /// The KiCad file to analyze
-    file: PathBuf,
+    /// The KiCad file to analyze (`-` for stdin), or a directory or quoted glob (`libs/**/*.kicad_sym`)
+    /// to run the command on every matching file
+    file: Option<PathBuf>,
 
     #[command(subcommand)]
     command: Commands,
 
-    /// Output in JSON format
-    #[arg(short, long)]
+    /// Output in JSON format (same as `--format json`)
+    #[arg(short, long, global = true)]
     json: bool,
+
+    /// Output format; csv and md write tables, with nested fields as dotted columns
+    #[arg(long, value_enum, global = true, conflicts_with = "json")]
+    format: Option<OutputFormat>,
+
+    /// File type when it cannot be told from the file name, e.g. when reading
+    /// stdin with `-` (detected from the content if not given)
+    #[arg(long = "type", value_enum, global = true)]
+    file_type: Option<FileType>,
 }
 
-#[derive(Subcommand)]
+#[derive(Clone, Subcommand)]
 enum Commands {
     /// Get detailed PCB information
     Details,
     
+    /// Board statistics: components, pads, tracks, vias, minimum sizes and area
+    Stats,
+    
     /// Extract layer information
     Layers,
     
+    /// Physical stackup: layer order, materials, thicknesses, dielectric constants
+    Stackup,
+    
+    /// Via drill spans (through, blind, buried) with counts, sizes and depths
+    DrillPairs,
+    
     /// Analyze 3D model coverage
     #[command(name = "3d")]
-    ThreeDModels,
+    ThreeDModels {
+        /// List each model with its placement and transform as CSV, for mechanical CAD
+        #[arg(long)]
+        placement: bool,
+        /// Resolve model paths and list the files missing on disk; exits non-zero
+        /// when any are missing
+        #[arg(long, conflicts_with = "placement")]
+        check: bool,
+    },
     
     /// Extract component positions
     Positions,
     
     /// Parse symbol libraries
-    Symbols,
+    Symbols {
+        /// Print the pinout of each symbol, by unit and body side
+        #[arg(long)]
+        pins: bool,
+        /// Print every property of each symbol (datasheet, keywords, footprint filters)
+        #[arg(long)]
+        properties: bool,
+    },
+    
+    /// List the labels of a .kicad_sch design with their sheet paths, e.g. to
+    /// lint net naming in CI
+    Labels {
+        /// Only labels of this kind
+        #[arg(long, value_enum)]
+        kind: Option<LabelFilter>,
+    },
+    
+    /// Generate a design report (Markdown by default)
+    Report {
+        /// Write a standalone HTML page instead of Markdown
+        #[arg(long)]
+        html: bool,
+    },
+    
+    /// Grouped bill of materials from a board or schematic (CSV by default)
+    Bom {
+        /// Fields parts must share to be grouped, e.g. `Value,Footprint,LCSC`
+        #[arg(long, value_delimiter = ',')]
+        group_by: Vec<String>,
+        /// Extra property columns, e.g. `LCSC,Tolerance`
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<String>,
+        /// List do-not-populate parts on their own lines
+        #[arg(long)]
+        dnp: bool,
+        /// Apply the DNP flags and field values of this design variant (schematics)
+        #[arg(long)]
+        variant: Option<String>,
+        /// Write a Markdown table instead of CSV
+        #[arg(long)]
+        markdown: bool,
+    },
+    
+    /// List nets with pad counts, routed length, layers and net class
+    Nets {
+        /// Only nets matching this name or glob, e.g. `GND` or `/USB_*`
+        #[arg(long)]
+        net: Option<String>,
+        #[arg(long, value_enum, default_value = "name")]
+        sort: NetSort,
+        /// Reverse the order, e.g. longest first with `--sort length`
+        #[arg(long)]
+        reverse: bool,
+    },
+    
+    /// Nets and their pads (REF.PIN), with routing status from board copper;
+    /// for a root .kicad_sch, the nets of the design's sheets
+    Netlist {
+        /// Only nets matching this name or glob, e.g. `GND` or `/USB_*`
+        #[arg(long)]
+        net: Option<String>,
+        /// List the as-routed copper islands instead of the declared nets
+        #[arg(long)]
+        routed: bool,
+        /// Write a KiCad XML netlist instead
+        #[arg(long, conflicts_with = "routed")]
+        xml: bool,
+        /// Write a SPICE netlist of a .kicad_sch sheet for simulation
+        #[arg(long, conflicts_with_all = ["routed", "xml"])]
+        spice: bool,
+    },
+    
+    /// Select board elements, e.g. `footprint[ref=U*] .pad[net="3V3"]`
+    Query {
+        /// Selector: element kinds (footprint, pad, track, arc, via, zone, net,
+        /// text, graphic, model, layer) with [attr=glob] or [attr<n] filters
+        selector: String,
+        /// Only print the number of matches
+        #[arg(long)]
+        count: bool,
+    },
+    
+    /// Check syntax, sections and format version; exits non-zero on errors
+    Validate {
+        /// The KiCad file to check (alternative to the leading FILE argument)
+        #[arg(value_name = "FILE")]
+        path: Option<PathBuf>,
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+    
+    /// Run design rule checks; exits non-zero when violations are found
+    Drc {
+        /// The .kicad_pcb file (instead of giving it before the command)
+        #[arg(value_name = "FILE")]
+        board: Option<PathBuf>,
+        /// TOML rules file: minimum clearance, track width, via, hole and ring
+        /// sizes, and a [severity] table (error, warning or ignore per rule)
+        #[arg(long)]
+        rules: Option<PathBuf>,
+        /// Lowest severity to report
+        #[arg(long, value_enum, default_value = "warning")]
+        severity: SeverityFilter,
+    },
+    
+    /// Electrical rule checks of a .kicad_sch design and the sheets below it
+    Erc {
+        /// Lowest severity to report
+        #[arg(long, value_enum, default_value = "warning")]
+        severity: SeverityFilter,
+    },
+    
+    /// Duplicate, unannotated and skipped references of a design or board, per sheet
+    CheckAnnotation {
+        /// Lowest severity to report
+        #[arg(long, value_enum, default_value = "warning")]
+        severity: SeverityFilter,
+    },
+    
+    /// Write fabrication data (Gerber, drill, position, SVG or DXF files) to a directory
+    Export {
+        /// The .kicad_pcb file (instead of giving it before the command)
+        #[arg(value_name = "FILE")]
+        board: Option<PathBuf>,
+        /// Kind of files to write
+        #[arg(long, value_enum)]
+        to: ExportFormat,
+        /// Output directory, created if missing
+        #[arg(short, long, default_value = ".")]
+        output: PathBuf,
+        /// Layers to plot for gerber, svg and dxf, comma separated; all by default
+        #[arg(long, value_delimiter = ',')]
+        layers: Vec<String>,
+        /// Draw the layers into one svg or dxf file instead of one file per layer
+        #[arg(long)]
+        combined: bool,
+    },
+    
+    /// Find footprints, values, nets and texts, with their layer and coordinates
+    Find {
+        /// The .kicad_pcb file (instead of giving it before the command)
+        #[arg(value_name = "FILE")]
+        board: Option<PathBuf>,
+        /// Reference glob, e.g. U12 or 'R1*'
+        #[arg(long = "ref", value_name = "GLOB")]
+        reference: Option<String>,
+        /// Footprint value glob, e.g. '10k' or 'STM32*'
+        #[arg(long, value_name = "GLOB")]
+        value: Option<String>,
+        /// Net name glob, e.g. '*CLK*'
+        #[arg(long, value_name = "GLOB")]
+        net: Option<String>,
+        /// Board and footprint text containing this, ignoring case
+        #[arg(long)]
+        text: Option<String>,
+    },
+    
+    /// Renumber references by position or rename prefixes, writing the changed board
+    ///
+    /// Unannotated references such as `R?` are numbered as well. Only the reference
+    /// fields of the file are rewritten: the board does not go through kiparse's
+    /// writer, which leaves out net classes, the stackup, groups, images, rule
+    /// areas and tuning patterns.
+    Annotate {
+        /// Numbering order; keep leaves the numbers and only renames prefixes
+        #[arg(long, value_enum, default_value = "rows")]
+        order: AnnotateOrder,
+        /// First number of each prefix
+        #[arg(long, default_value = "1")]
+        start: u32,
+        /// Positions closer than this (mm) share a row or column
+        #[arg(long, default_value = "1.0")]
+        grid: f64,
+        /// Prefix renames, comma separated, e.g. U=IC,Y=X
+        #[arg(long = "prefix", value_name = "OLD=NEW", value_delimiter = ',')]
+        prefixes: Vec<String>,
+        /// Write the annotated board to this file (`-` for stdout)
+        #[arg(short, long, conflicts_with = "in_place")]
+        output: Option<PathBuf>,
+        /// Overwrite the input board
+        #[arg(long)]
+        in_place: bool,
+    },
+    
+    /// Compare two boards: components, properties, nets, layers, stackup and outline
+    Diff {
+        /// The original .kicad_pcb file
+        old: PathBuf,
+        /// The changed .kicad_pcb file
+        new: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable tables
+    Text,
+    Json,
+    Csv,
+    Yaml,
+    Toml,
+    /// Markdown tables
+    Md,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum FileType {
+    Pcb,
+    Sch,
+    Sym,
+}
+
+impl FileType {
+    fn extension(self) -> &'static str {
+        match self {
+            FileType::Pcb => "kicad_pcb",
+            FileType::Sch => "kicad_sch",
+            FileType::Sym => "kicad_sym",
+        }
+    }
+
+    /// Type from the root element of the content
+    fn detect(content: &str) -> Option<Self> {
+        let root = content.trim_start().strip_prefix('(')?.trim_start();
+        let name = root.split(|c: char| c.is_whitespace() || c == '(' || c == ')').next()?;
+        match name {
+            "kicad_pcb" => Some(FileType::Pcb),
+            "kicad_sch" => Some(FileType::Sch),
+            "kicad_symbol_lib" => Some(FileType::Sym),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum NetSort {
+    Name,
+    Pads,
+    Length,
+    Vias,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum SeverityFilter {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LabelFilter {
+    Local,
+    Global,
+    Hierarchical,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum AnnotateOrder {
+    Rows,
+    Columns,
+    Keep,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    Gerber,
+    Drill,
+    Pos,
+    Svg,
+    Dxf,
+    /// The parsed board in kiparse's binary format, for caching
+    Kpcb,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let format = match cli.format {
+        Some(format) => format,
+        None if cli.json => OutputFormat::Json,
+        None => OutputFormat::Text,
+    };
+    OUTPUT_FORMAT.with(|f| f.set(format));
+    
+    if let Commands::Diff { old, new } = &cli.command {
+        return handle_diff(old, new, format != OutputFormat::Text);
+    }
+    let file = match &cli.command {
+        Commands::Validate { path: Some(path), .. } => Some(path.clone()),
+        Commands::Export { board: Some(board), .. }
+        | Commands::Drc { board: Some(board), .. }
+        | Commands::Find { board: Some(board), .. } => {
+            Some(board.clone())
+        }
+        _ => cli.file.clone(),
+    };
+    let Some(file) = file else {
+        eprintln!("A KiCad file is required: kpx <FILE> <COMMAND>");
+        std::process::exit(1);
+    };
+    if file.is_dir() || is_glob(&file) {
+        return handle_batch(&cli.command, &file, format);
+    }
+    let (content, file_type) = if file == Path::new("-") {
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)?;
+        let file_type = cli.file_type.or_else(|| FileType::detect(&content));
+        if file_type.is_none() && !matches!(cli.command, Commands::Validate { .. }) {
+            eprintln!("Cannot tell the type of the stdin content; pass --type pcb|sch|sym");
+            std::process::exit(1);
+        }
+        (content, file_type)
+    } else {
+        (fs::read_to_string(&file)?, cli.file_type)
+    };
+    if !run(cli.command, &file, file_type, &content, format)? {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run a command on one file (`-` for stdin); `false` when a check (validate, drc) failed
+///
+/// The file type comes from `file_type` when given, otherwise from the file extension.
+fn run(
+    command: Commands,
+    file: &Path,
+    file_type: Option<FileType>,
+    content: &str,
+    format: OutputFormat,
+) -> Result<bool> {
+    let json = format != OutputFormat::Text;
+    let stdin = file == Path::new("-");
+    let filename = if stdin { "<stdin>" } else { file.to_str().unwrap_or("unknown") };
+    let extension = match file_type {
+        Some(file_type) => file_type.extension(),
+        None => file.extension().and_then(|e| e.to_str()).unwrap_or_default(),
+    };
+    
+    match command {
+        Commands::Details => {
+            if extension == "kicad_pcb" {
+                handle_pcb_details(content, json)?;
+            } else {
+                eprintln!("Details command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Stats => {
+            if extension == "kicad_pcb" {
+                handle_stats(content, json)?;
+            } else {
+                eprintln!("Stats command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Stackup => {
+            if extension == "kicad_pcb" && format == OutputFormat::Csv {
+                print!("{}", cam::stackup::StackupTable::from_pcb(&pcb::parse_pcb(content)?).to_csv());
+            } else if extension == "kicad_pcb" {
+                handle_stackup(content, json)?;
+            } else {
+                eprintln!("Stackup command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::DrillPairs => {
+            if extension == "kicad_pcb" {
+                handle_drill_pairs(content, json)?;
+            } else {
+                eprintln!("Drill-pairs command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Layers => {
+            if extension == "kicad_pcb" {
+                handle_layers(content, json)?;
+            } else {
+                eprintln!("Layers command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::ThreeDModels { placement, check } => {
+            if extension == "kicad_pcb" && check {
+                return handle_model_check(content, file, json);
+            } else if extension == "kicad_pcb" && placement {
+                handle_model_placement(content, file, json)?;
+            } else if extension == "kicad_pcb" {
+                handle_3d_models(content, json)?;
+            } else {
+                eprintln!("3d command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Positions => {
+            if extension == "kicad_pcb" {
+                handle_positions(content, json, format == OutputFormat::Csv)?;
+            } else {
+                eprintln!("Positions command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Symbols { pins, properties } => {
+            if extension == "kicad_sym" {
+                handle_symbols(content, json, pins, properties)?;
+            } else {
+                eprintln!("Symbols command requires a .kicad_sym file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Labels { kind } => {
+            if extension == "kicad_sch" && !stdin {
+                handle_labels(file, json, kind)?;
+            } else {
+                eprintln!("Labels command requires a root .kicad_sch file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Report { html } => {
+            if extension == "kicad_pcb" {
+                handle_report(content, json, html)?;
+            } else {
+                eprintln!("Report command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Bom { group_by, columns, dnp, variant, markdown } => {
+            let options = bom::BomOptions { include_dnp: dnp, group_by, columns, variant };
+            let bom = if extension == "kicad_pcb" {
+                bom::Bom::with_options(&pcb::parse_pcb(content)?, &options)
+            } else if extension == "kicad_sch" && !stdin {
+                bom::Bom::from_hierarchy(&schematic::Hierarchy::load(file)?, &options)
+            } else if extension == "kicad_sch" {
+                bom::Bom::from_schematic(content, &options)?
+            } else {
+                eprintln!("Bom command requires a .kicad_pcb or .kicad_sch file");
+                std::process::exit(1);
+            };
+            match format {
+                OutputFormat::Csv => print!("{}", bom.to_csv()),
+                OutputFormat::Md => print!("{}", bom.to_markdown()),
+                _ => handle_bom(&bom, json, markdown)?,
+            }
+        }
+        Commands::Nets { net, sort, reverse } => {
+            if extension == "kicad_pcb" {
+                handle_nets(content, json, net.as_deref(), sort, reverse)?;
+            } else {
+                eprintln!("Nets command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Netlist { net, routed, xml, spice } => {
+            if extension == "kicad_sch" && spice {
+                let schematic = schematic::parse_schematic(content)?;
+                print!("{}", spice::SpiceNetlist::from_schematic(&schematic).to_spice());
+            } else if spice {
+                eprintln!("Netlist --spice requires a .kicad_sch file");
+                std::process::exit(1);
+            } else if extension == "kicad_pcb" {
+                handle_netlist(content, json, net.as_deref(), routed, xml)?;
+            } else if extension == "kicad_sch" && !routed {
+                handle_schematic_netlist(file, json, net.as_deref(), xml)?;
+            } else {
+                eprintln!("Netlist command requires a .kicad_pcb or .kicad_sch file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Query { selector, count } => {
+            if extension == "kicad_pcb" {
+                handle_query(content, json, &selector, count)?;
+            } else {
+                eprintln!("Query command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Validate { strict, .. } => {
+            return handle_validate(content, filename, json, strict);
+        }
+        Commands::Drc { rules, severity, .. } => {
+            if extension == "kicad_pcb" {
+                return handle_drc(content, json, filename, rules.as_deref(), severity);
+            } else {
+                eprintln!("DRC command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Erc { severity } => {
+            if extension == "kicad_sch" && !stdin {
+                return handle_erc(file, json, filename, severity);
+            } else {
+                eprintln!("ERC command requires a root .kicad_sch file");
+                std::process::exit(1);
+            }
+        }
+        Commands::CheckAnnotation { severity } => {
+            let issues = if extension == "kicad_sch" && !stdin {
+                annotation::check_hierarchy(&schematic::Hierarchy::load(file)?)
+            } else if extension == "kicad_pcb" {
+                annotation::check_board(&pcb::parse_pcb(content)?)
+            } else {
+                eprintln!("Check-annotation command requires a root .kicad_sch or a .kicad_pcb file");
+                std::process::exit(1);
+            };
+            return handle_check_annotation(issues, json, filename, severity);
+        }
+        Commands::Export { to, output, layers, combined, .. } => {
+            if extension == "kicad_pcb" {
+                let board = file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .filter(|_| !stdin)
+                    .unwrap_or("board");
+                handle_export(content, json, board, to, &output, &layers, combined)?;
+            } else {
+                eprintln!("Export command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+        }
+        Commands::Find { reference, value, net, text, .. } => {
+            if extension != "kicad_pcb" {
+                eprintln!("Find command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+            if reference.is_none() && value.is_none() && net.is_none() && text.is_none() {
+                eprintln!("Give at least one of --ref, --value, --net or --text");
+                std::process::exit(1);
+            }
+            let search = Search { reference, value, net, text };
+            return handle_find(content, json, filename, &search);
+        }
+        Commands::Annotate { order, start, grid, prefixes, output, in_place } => {
+            if extension != "kicad_pcb" {
+                eprintln!("Annotate command requires a .kicad_pcb file");
+                std::process::exit(1);
+            }
+            let output = match (output, in_place) {
+                (_, true) if stdin => {
+                    eprintln!("--in-place cannot be used when reading stdin");
+                    std::process::exit(1);
+                }
+                (_, true) => Some(file.to_path_buf()),
+                (output, false) => output,
+            };
+            let order = match order {
+                AnnotateOrder::Rows => Some(pcb::annotate::AnnotationOrder::Rows),
+                AnnotateOrder::Columns => Some(pcb::annotate::AnnotationOrder::Columns),
+                AnnotateOrder::Keep => None,
+            };
+            let mut options = pcb::annotate::AnnotateOptions {
+                order,
+                start,
+                grid: Length::mm(grid),
+                ..Default::default()
+            };
+            for mapping in &prefixes {
+                let Some((old, new)) = mapping.split_once('=') else {
+                    eprintln!("Invalid prefix mapping '{}', expected OLD=NEW", mapping);
+                    std::process::exit(1);
+                };
+                options.prefixes.insert(old.trim().to_string(), new.trim().to_string());
+            }
+            handle_annotate(content, json, &options, output.as_deref())?;
+        }
+        Commands::Diff { .. } => unreachable!(),
+    }
+    
+    Ok(true)
+}
+
+impl Commands {
+    /// File extensions the command accepts, used to pick files for a batch run
+    fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Commands::Symbols { .. } => &["kicad_sym"],
+            Commands::Bom { .. } | Commands::CheckAnnotation { .. } => &["kicad_pcb", "kicad_sch"],
+            Commands::Validate { .. } => &["kicad_pcb", "kicad_sch", "kicad_sym", "kicad_mod"],
+            _ => &["kicad_pcb"],
+        }
+    }
+}
+
+thread_local! {
+    /// JSON documents printed by a command while a batch run collects them
+    static CAPTURED_JSON: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+    /// Format structured output is converted to when printed
+    static OUTPUT_FORMAT: Cell<OutputFormat> = const { Cell::new(OutputFormat::Json) };
+}
+
+/// Print a JSON document in the output format, or hand it to the batch run
+/// collecting the output
+fn print_json(json: String) {
+    let json = match CAPTURED_JSON.with(|captured| match captured.borrow_mut().as_mut() {
+        Some(documents) => {
+            documents.push(json);
+            None
+        }
+        None => Some(json),
+    }) {
+        Some(json) => json,
+        None => return,
+    };
+    let format = OUTPUT_FORMAT.with(Cell::get);
+    let ordered = || serde_json::from_str::<Json>(&json).ok();
+    match format {
+        OutputFormat::Csv => print!("{}", ordered().map(|v| format_csv(&v)).unwrap_or(json)),
+        OutputFormat::Md => print!("{}", ordered().map(|v| format_markdown(&v)).unwrap_or(json)),
+        OutputFormat::Yaml => print!("{}", ordered().map(|v| format_yaml(&v)).unwrap_or(json)),
+        OutputFormat::Toml => match format_toml(serde_json::from_str(&json).unwrap_or_default()) {
+            Ok(toml) => print!("{}", toml),
+            Err(e) => {
+                eprintln!("Cannot write TOML: {}", e);
+                std::process::exit(1);
+            }
+        },
+        OutputFormat::Text | OutputFormat::Json => println!("{}", json),
+    }
+}
+
+/// JSON value that keeps the key order of objects, so columns follow struct fields
+enum Json {
+    Scalar(serde_json::Value),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl<'de> serde::Deserialize<'de> for Json {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        use serde::de::{MapAccess, SeqAccess, Visitor};
+        use serde_json::Value;
+        
+        struct JsonVisitor;
+        
+        impl<'de> Visitor<'de> for JsonVisitor {
+            type Value = Json;
+            
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a JSON value")
+            }
+            fn visit_unit<E>(self) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::Null))
+            }
+            fn visit_bool<E>(self, v: bool) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::Bool(v)))
+            }
+            fn visit_i64<E>(self, v: i64) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::from(v)))
+            }
+            fn visit_u64<E>(self, v: u64) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::from(v)))
+            }
+            fn visit_f64<E>(self, v: f64) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::from(v)))
+            }
+            fn visit_str<E>(self, v: &str) -> std::result::Result<Json, E> {
+                Ok(Json::Scalar(Value::from(v)))
+            }
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Json, A::Error> {
+                let mut items = Vec::new();
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(Json::Array(items))
+            }
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Json, A::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Json::Object(entries))
+            }
+        }
+        
+        deserializer.deserialize_any(JsonVisitor)
+    }
+}
+
+impl Json {
+    fn is_object(&self) -> bool {
+        matches!(self, Json::Object(_))
+    }
+    
+    /// Scalar as table cell text; null is empty and strings are unquoted
+    fn cell(&self) -> String {
+        match self {
+            Json::Scalar(serde_json::Value::Null) => String::new(),
+            Json::Scalar(serde_json::Value::String(s)) => s.clone(),
+            Json::Scalar(other) => other.to_string(),
+            Json::Array(items) => format!("[{}]", items.iter().map(Json::cell).collect::<Vec<_>>().join(",")),
+            Json::Object(_) => "{...}".to_string(),
+        }
+    }
+}
+
+/// Rows of a table: the elements of an array, the array of objects inside an
+/// object (e.g. the components of `positions`), or the object as one row;
+/// nested objects become dotted columns and lists of values are joined with `;`
+fn table_rows(value: &Json) -> (Vec<String>, Vec<Vec<String>>) {
+    fn flatten(prefix: &str, value: &Json, row: &mut Vec<(String, String)>) {
+        match value {
+            Json::Object(entries) => {
+                for (key, value) in entries {
+                    let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    flatten(&key, value, row);
+                }
+            }
+            Json::Array(items) if items.iter().all(|i| matches!(i, Json::Scalar(_))) => {
+                let items: Vec<String> = items.iter().map(Json::cell).collect();
+                row.push((prefix.to_string(), items.join(";")));
+            }
+            other => row.push((prefix.to_string(), other.cell())),
+        }
+    }
+    
+    let records: Vec<&Json> = match value {
+        Json::Array(items) => items.iter().collect(),
+        Json::Object(entries) => {
+            let mut tables = entries.iter().filter(|(_, v)| match v {
+                Json::Array(items) => !items.is_empty() && items.iter().all(Json::is_object),
+                _ => false,
+            });
+            match (tables.next(), tables.next()) {
+                (Some((_, Json::Array(items))), None) => items.iter().collect(),
+                _ => vec![value],
+            }
+        }
+        other => vec![other],
+    };
+    let mut header: Vec<String> = Vec::new();
+    let mut flat = Vec::new();
+    for record in records {
+        let mut row = Vec::new();
+        flatten("", record, &mut row);
+        for (key, _) in &row {
+            if !header.contains(key) {
+                header.push(key.clone());
+            }
+        }
+        flat.push(row);
+    }
+    let rows = flat
+        .into_iter()
+        .map(|row| {
+            header
+                .iter()
+                .map(|h| row.iter().find(|(k, _)| k == h).map(|(_, v)| v.clone()).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    (header, rows)
+}
+
+fn format_csv(value: &Json) -> String {
+    let field = |s: &str| {
+        if s.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", s.replace('"', "\"\""))
+        } else {
+            s.to_string()
+        }
+    };
+    let (header, rows) = table_rows(value);
+    let mut csv = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        csv.push_str(&row.iter().map(|c| field(c)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn format_markdown(value: &Json) -> String {
+    let cell = |s: &str| s.replace('|', "\\|").replace('\n', " ");
+    let (header, rows) = table_rows(value);
+    let line = |cells: &[String]| format!("| {} |\n", cells.iter().map(|c| cell(c)).collect::<Vec<_>>().join(" | "));
+    let mut md = line(&header);
+    md.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+    for row in &rows {
+        md.push_str(&line(row));
+    }
+    md
+}
+
+/// Block-style YAML; strings that could be read as another type are quoted
+fn format_yaml(value: &Json) -> String {
+    fn text(s: &str) -> String {
+        let plain = s.chars().next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_')
+            && s.chars().all(|c| c.is_ascii_alphanumeric() || "_-. /+".contains(c))
+            && !s.ends_with(' ')
+            && !matches!(
+                s.to_ascii_lowercase().as_str(),
+                "true" | "false" | "null" | "yes" | "no" | "on" | "off" | "y" | "n"
+            );
+        if plain {
+            s.to_string()
+        } else {
+            serde_json::to_string(s).unwrap_or_default()
+        }
+    }
+    fn scalar(value: &Json) -> String {
+        match value {
+            Json::Scalar(serde_json::Value::String(s)) => text(s),
+            Json::Scalar(other) => other.to_string(),
+            Json::Array(_) => "[]".to_string(),
+            Json::Object(_) => "{}".to_string(),
+        }
+    }
+    fn is_block(value: &Json) -> bool {
+        match value {
+            Json::Array(items) => !items.is_empty(),
+            Json::Object(entries) => !entries.is_empty(),
+            Json::Scalar(_) => false,
+        }
+    }
+    fn write(value: &Json, indent: usize, out: &mut String) {
+        let pad = " ".repeat(indent);
+        match value {
+            Json::Object(entries) if !entries.is_empty() => {
+                for (key, value) in entries {
+                    if is_block(value) {
+                        out.push_str(&format!("{}{}:\n", pad, text(key)));
+                        write(value, indent + 2, out);
+                    } else {
+                        out.push_str(&format!("{}{}: {}\n", pad, text(key), scalar(value)));
+                    }
+                }
+            }
+            Json::Array(items) if !items.is_empty() => {
+                for item in items {
+                    if is_block(item) {
+                        // The first line of the nested block goes after the dash
+                        let mut nested = String::new();
+                        write(item, indent + 2, &mut nested);
+                        out.push_str(&format!("{}- {}", pad, &nested[indent + 2..]));
+                    } else {
+                        out.push_str(&format!("{}- {}\n", pad, scalar(item)));
+                    }
+                }
+            }
+            other => out.push_str(&format!("{}{}\n", pad, scalar(other))),
+        }
+    }
+    
+    let mut yaml = String::from("---\n");
+    write(value, 0, &mut yaml);
+    yaml
+}
+
+/// TOML document; nulls are left out and a top-level list becomes `items`
+fn format_toml(value: serde_json::Value) -> std::result::Result<String, toml::ser::Error> {
+    use serde_json::Value;
+    
+    fn strip_nulls(value: Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .filter(|(_, v)| !v.is_null())
+                    .map(|(k, v)| (k, strip_nulls(v)))
+                    .collect(),
+            ),
+            Value::Array(items) => {
+                Value::Array(items.into_iter().filter(|v| !v.is_null()).map(strip_nulls).collect())
+            }
+            other => other,
+        }
+    }
+    let value = match strip_nulls(value) {
+        Value::Object(map) => Value::Object(map),
+        other => serde_json::json!({ "items": other }),
+    };
+    // Converting first lets toml order plain values before tables
+    let value = toml::Value::try_from(value)?;
+    toml::to_string(&value)
+}
+
+/// Outcome of a command on one file of a batch run
+#[derive(Serialize)]
+struct BatchResult {
+    file: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+}
+
+/// Run a command on every matching file of a directory or glob
+///
+/// Text output is printed per file under a `==> file <==` header and followed by
+/// a summary; JSON output is one array with the result of each file. Exits
+/// non-zero when any file fails to parse or fails a check.
+fn handle_batch(command: &Commands, input: &Path, format: OutputFormat) -> Result<()> {
+    let json_output = format != OutputFormat::Text;
+    let extensions = command.extensions();
+    let files = batch_files(input, extensions)?;
+    if files.is_empty() {
+        eprintln!("No .{} files match {}", extensions.join("/."), input.display());
+        std::process::exit(1);
+    }
+    
+    let mut results = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        if json_output {
+            CAPTURED_JSON.with(|c| *c.borrow_mut() = Some(Vec::new()));
+        } else {
+            if i > 0 {
+                println!();
+            }
+            println!("==> {} <==", file.display());
+        }
+        let outcome = fs::read_to_string(file)
+            .map_err(Into::into)
+            .and_then(|content| run(command.clone(), file, None, &content, format));
+        let documents = CAPTURED_JSON.with(|c| c.borrow_mut().take()).unwrap_or_default();
+        let mut values: Vec<serde_json::Value> = documents
+            .iter()
+            .filter_map(|d| serde_json::from_str(d).ok())
+            .collect();
+        let result = match values.len() {
+            0 => None,
+            1 => values.pop(),
+            _ => Some(serde_json::Value::Array(values)),
+        };
+        let (ok, error) = match outcome {
+            Ok(passed) => (passed, None),
+            Err(e) => {
+                if !json_output {
+                    eprintln!("{}: {}", file.display(), e);
+                }
+                (false, Some(e.to_string()))
+            }
+        };
+        results.push(BatchResult { file: file.display().to_string(), ok, error, result });
+    }
     
-    let content = fs::read_to_string(&cli.file)?;
-    let filename = cli.file.to_str().unwrap_or("unknown");
+    if json_output {
+        print_json(serde_json::to_string_pretty(&results).map_err(|e| kiparse::KicadError::ParseError(e.to_string()))?);
+    } else {
+        println!();
+        println!("Batch Summary");
+        println!("=============");
+        let mut table = Table::new();
+        table.add_row(row!["File", "Result"]);
+        for r in &results {
+            let status = match (&r.error, r.ok) {
+                (Some(e), _) => format!("error: {}", e),
+                (None, true) => "ok".to_string(),
+                (None, false) => "failed".to_string(),
+            };
+            table.add_row(row![r.file, status]);
+        }
+        table.printstd();
+        let failed = results.iter().filter(|r| !r.ok).count();
+        println!("{} file(s), {} ok, {} failed", results.len(), results.len() - failed, failed);
+    }
+    
+    if results.iter().any(|r| !r.ok) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Whether a path contains `*` or `?` wildcards
+fn is_glob(path: &Path) -> bool {
+    path.to_str().map_or(false, |p| p.contains(['*', '?']))
+}
+
+/// Files with one of `extensions` under a directory, or matching a glob where
+/// `**` stands for any number of directories; sorted by path
+fn batch_files(input: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    if input.is_dir() {
+        walk_dir(input, &mut files)?;
+    } else {
+        // Walk from the longest directory prefix without wildcards
+        let mut base = PathBuf::new();
+        let mut pattern: Vec<String> = Vec::new();
+        for component in input.components() {
+            let text = component.as_os_str().to_string_lossy();
+            if pattern.is_empty() && !text.contains(['*', '?']) {
+                base.push(component);
+            } else {
+                pattern.push(text.into_owned());
+            }
+        }
+        let root = if base.as_os_str().is_empty() { Path::new(".") } else { base.as_path() };
+        if root.is_dir() {
+            walk_dir(root, &mut files)?;
+        }
+        files.retain(|f| {
+            let relative: Vec<String> = f
+                .strip_prefix(root)
+                .unwrap_or(f)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect();
+            glob_components(&pattern, &relative)
+        });
+        if base.as_os_str().is_empty() {
+            for f in &mut files {
+                *f = f.strip_prefix(root).unwrap_or(f).to_path_buf();
+            }
+        }
+    }
+    files.retain(|f| {
+        f.extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| extensions.contains(&e))
+    });
+    Ok(files)
+}
+
+fn walk_dir(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+    for entry in entries {
+        if entry.file_type()?.is_dir() {
+            walk_dir(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Match path components against glob components, `**` matching zero or more
+fn glob_components(pattern: &[String], path: &[String]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| glob_components(rest, &path[skip..]))
+        }
+        Some((first, rest)) => path.split_first().map_or(false, |(name, tail)| {
+            symbol::query::glob_match(first, name) && glob_components(rest, tail)
+        }),
+    }
+}
+
+fn handle_pcb_details(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let stats = pcb.stats();
+    
+    // Board dimensions from the Edge.Cuts outline
+    let outline: Vec<pcb::Point> = pcb.board_outline().into_iter().flat_map(|p| p.outline).collect();
+    let (min, max) = pcb::geometry::bounds(&outline);
+    let (board_width_mm, board_height_mm) = ((max.x - min.x).as_mm(), (max.y - min.y).as_mm());
+    let board_width_mils = Length::mm(board_width_mm).as_mils();
+    let board_height_mils = Length::mm(board_height_mm).as_mils();
+    let board_area_mm2 = stats.board_area.unwrap_or(board_width_mm * board_height_mm);
+    let board_area_sq_in = units::mm2_to_sq_in(board_area_mm2);
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            let output = serde_json::json!({
+                "layers": pcb.layers.len(),
+                "signal_layers": pcb.layers.values().filter(|l| l.layer_type == "signal").count(),
+                "file_size_kb": content.len() as f64 / 1024.0,
+                "complexity": estimate_complexity(&stats),
+                "board_size": {
+                    "width_mm": board_width_mm,
+                    "height_mm": board_height_mm,
+                    "width_mils": board_width_mils,
+                    "height_mils": board_height_mils,
+                    "area_mm2": board_area_mm2,
+                    "area_sq_in": board_area_sq_in,
+                },
+                "components": stats.component_count,
+                "tracks": stats.track_count,
+                "vias": stats.via_count,
+                "stats": stats,
+            });
+            print_json(serde_json::to_string_pretty(&output)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("KiCad PCB Analysis");
+        println!("==================");
+        println!("Layers: {}", pcb.layers.len());
+        println!("Signal layers: {}", pcb.layers.values().filter(|l| l.layer_type == "signal").count());
+        println!("File size: {:.2} KB", content.len() as f64 / 1024.0);
+        println!("Complexity: {}", estimate_complexity(&stats));
+        
+        // Board dimensions
+        if board_width_mm > 0.0 && board_height_mm > 0.0 {
+            println!("\nBoard Dimensions:");
+            println!("  Width:  {:.2} mm ({:.0} mils)", board_width_mm, board_width_mils);
+            println!("  Height: {:.2} mm ({:.0} mils)", board_height_mm, board_height_mils);
+            println!("  Area:   {:.2} mm² ({:.2} sq in)", board_area_mm2, board_area_sq_in);
+        }
+        
+        println!("\nBoard Statistics:");
+        println!("  Components: {}", stats.component_count);
+        println!("  Pads: {}", stats.pad_count);
+        println!("  Tracks: {} ({:.1} mm routed)", stats.track_count, stats.track_length);
+        println!("  Vias: {}", stats.via_count);
+        if let Some(width) = stats.min_track_width {
+            println!("  Min track width: {:.3} mm", width);
+        }
+        if let Some(drill) = stats.min_drill {
+            println!("  Min drill: {:.3} mm", drill);
+        }
+        
+        if board_area_mm2 > 0.0 && stats.component_count > 0 {
+            let density = stats.component_count as f64 / board_area_sq_in; // components per sq inch
+            println!("  Density: {:.1} components/sq inch", density);
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_stats(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let stats = pcb.stats();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&stats)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        let mm = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.3} mm", v));
+        let mut table = Table::new();
+        table.add_row(row!["Statistic", "Value"]);
+        table.add_row(row!["Layers", stats.layer_count]);
+        table.add_row(row!["Copper layers", stats.copper_layer_count]);
+        table.add_row(row!["Board area", stats.board_area.map_or("-".to_string(), |a| format!("{:.2} mm²", a))]);
+        table.add_row(row!["Components", stats.component_count]);
+        for (prefix, count) in &stats.components_by_prefix {
+            table.add_row(row![format!("  {}", prefix), count]);
+        }
+        table.add_row(row!["Pads", stats.pad_count]);
+        table.add_row(row!["Tracks", stats.track_count]);
+        table.add_row(row!["Track length", format!("{:.1} mm", stats.track_length)]);
+        for (layer, length) in &stats.track_length_by_layer {
+            table.add_row(row![format!("  {}", layer), format!("{:.1} mm", length)]);
+        }
+        table.add_row(row!["Vias", stats.via_count]);
+        for (via_type, count) in &stats.vias_by_type {
+            table.add_row(row![format!("  {:?}", via_type), count]);
+        }
+        table.add_row(row!["Min track width", mm(stats.min_track_width)]);
+        table.add_row(row!["Min drill", mm(stats.min_drill)]);
+        
+        println!("Board Statistics");
+        println!("================");
+        table.printstd();
+    }
+    
+    Ok(())
+}
+
+fn handle_drill_pairs(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let pairs = cam::drill::drill_pairs(&pcb);
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&pairs)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    println!("Drill Pairs");
+    println!("===========");
+    if pairs.is_empty() {
+        println!("No vias");
+        return Ok(());
+    }
+    let mut table = Table::new();
+    table.add_row(row!["Span", "From", "To", "Type", "Vias", "Drills (mm × count)", "Depth (mm)", "Aspect"]);
+    for pair in &pairs {
+        let sizes: Vec<String> = pair.sizes.iter().map(|(d, n)| format!("{:.3} × {}", d.as_mm(), n)).collect();
+        let kind = if pair.microvias > 0 {
+            format!("{} ({} micro)", pair.kind.name(), pair.microvias)
+        } else {
+            pair.kind.name().to_string()
+        };
+        table.add_row(row![
+            pair.label(),
+            pair.from,
+            pair.to,
+            kind,
+            pair.via_count,
+            sizes.join(", "),
+            pair.depth.map_or(String::new(), |d| format!("{:.3}", d.as_mm())),
+            pair.aspect_ratio().map_or(String::new(), |r| format!("{:.1}:1", r)),
+        ]);
+    }
+    table.printstd();
+    
+    Ok(())
+}
+
+fn handle_stackup(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let report = cam::stackup::StackupTable::from_pcb(&pcb);
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(report.to_json()?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    println!("Board Stackup");
+    println!("=============");
+    if pcb.stackup.is_empty() {
+        println!("No stackup defined in the board setup");
+        if let Some(thickness) = pcb.board_thickness {
+            println!("Board thickness: {:.3} mm", thickness.as_mm());
+        }
+        return Ok(());
+    }
+    let number = |value: Option<f64>, precision: usize| {
+        value.map_or(String::new(), |v| format!("{:.*}", precision, v))
+    };
+    let mut table = Table::new();
+    table.add_row(row!["#", "Layer", "Type", "Material", "Thickness (mm)", "εr", "tan δ", "Color"]);
+    for (i, layer) in pcb.stackup.iter().enumerate() {
+        let mut thickness = number(layer.thickness.map(Length::as_mm), 4);
+        // Copper weight, 1 oz/ft² being 35 µm
+        if let (Some(t), "copper") = (layer.thickness, layer.layer_type.as_str()) {
+            thickness = format!("{} ({:.1} oz)", thickness, t.as_mm() / 0.035);
+        }
+        table.add_row(row![
+            i + 1,
+            layer.name,
+            layer.layer_type,
+            layer.material.as_deref().unwrap_or_default(),
+            thickness,
+            number(layer.epsilon_r, 2),
+            number(layer.loss_tangent, 4),
+            layer.color.as_deref().unwrap_or_default(),
+        ]);
+    }
+    table.printstd();
+    
+    println!("Copper layers: {}", report.copper_layers);
+    print!("Stackup thickness: {:.3} mm", report.total_thickness);
+    match pcb.board_thickness {
+        Some(board) => println!(" (board {:.3} mm)", board.as_mm()),
+        None => println!(),
+    }
+    if let Some(finish) = &report.copper_finish {
+        println!("Copper finish: {}", finish);
+    }
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    println!("Impedance controlled: {}", yes_no(report.impedance_controlled));
+    if let Some(edge) = &report.edge_connector {
+        println!("Edge card connector: {}", edge);
+    }
+    if report.castellated_pads || report.edge_plating {
+        println!("Castellated pads: {}, edge plating: {}", yes_no(report.castellated_pads), yes_no(report.edge_plating));
+    }
+    
+    Ok(())
+}
+
+fn handle_layers(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_layers_only(content)?;
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&pcb)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("KiCad PCB Layer Information");
+        println!("===========================");
+        println!("Total layers: {}", pcb.layers.len());
+        
+        let mut table = Table::new();
+        table.add_row(row!["ID", "Name", "Type", "User Name"]);
+        
+        let mut sorted_layers: Vec<_> = pcb.layers.iter().collect();
+        sorted_layers.sort_by_key(|(id, _)| *id);
+        
+        for (id, layer) in sorted_layers {
+            table.add_row(row![
+                id,
+                layer.name,
+                layer.layer_type,
+                layer.user_name.as_deref().unwrap_or("-")
+            ]);
+        }
+        
+        table.printstd();
+    }
+    
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ModelCoverage {
+    total_components: usize,
+    with_3d_models: usize,
+    without_3d_models: usize,
+    coverage_percent: f64,
+    model_types: BTreeMap<&'static str, usize>,
+    embedded_models: usize,
+    embedded_not_found: usize,
+}
+
+/// Print how many footprints carry a 3D model; board-only footprints without
+/// one are left out, as in `3d --check`
+fn handle_3d_models(content: &str, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    
+    let mut total = 0;
+    let mut with_models = 0;
+    let mut model_types: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut embedded_models = 0;
+    let mut embedded_not_found = 0;
+    for footprint in &pcb.footprints {
+        if footprint.models.is_empty() {
+            if !footprint.attributes.board_only {
+                total += 1;
+            }
+            continue;
+        }
+        total += 1;
+        with_models += 1;
+        for model in &footprint.models {
+            let model_type = match model.model_type() {
+                pcb::types::ModelType::Wrl => "WRL",
+                pcb::types::ModelType::Step => "STEP",
+                pcb::types::ModelType::Iges => "IGES",
+                pcb::types::ModelType::Other => "Other",
+            };
+            *model_types.entry(model_type).or_insert(0) += 1;
+            if model.embedded_name().is_some() {
+                embedded_models += 1;
+                if pcb.resolve_embedded_model(footprint, model).is_none() {
+                    embedded_not_found += 1;
+                }
+            }
+        }
+    }
+    let without_models = total - with_models;
+    let coverage = if total > 0 { (with_models as f64 / total as f64) * 100.0 } else { 0.0 };
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            let output = ModelCoverage {
+                total_components: total,
+                with_3d_models: with_models,
+                without_3d_models: without_models,
+                coverage_percent: coverage,
+                model_types,
+                embedded_models,
+                embedded_not_found,
+            };
+            print_json(serde_json::to_string_pretty(&output)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("3D Model Coverage Analysis");
+        println!("==========================");
+        println!("Total components: {}", total);
+        println!("With 3D models: {} ({:.1}%)", with_models, coverage);
+        println!("Without 3D models: {} ({:.1}%)", without_models, 100.0 - coverage);
+        
+        if !model_types.is_empty() {
+            println!("\nModel Types:");
+            for (model_type, count) in &model_types {
+                println!("  {}: {}", model_type, count);
+            }
+        }
+        if embedded_models > 0 {
+            println!("\nEmbedded models: {} ({} not found in the board)", embedded_models, embedded_not_found);
+        }
+    }
+    
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct NetSummary {
+    id: u32,
+    name: String,
+    pads: usize,
+    /// Routed track and arc length in mm
+    track_length: f64,
+    /// Track length plus via barrels in mm
+    total_length: f64,
+    vias: usize,
+    /// Copper layers carrying tracks, arcs, vias or zones of the net
+    layers: Vec<String>,
+    /// Net class, `Default` for nets not assigned to one
+    class: String,
+}
+
+fn handle_nets(
+    content: &str,
+    json_output: bool,
+    net_filter: Option<&str>,
+    sort: NetSort,
+    reverse: bool,
+) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let copper = pcb::copper::copper_layers(&pcb);
+    let lengths: HashMap<String, pcb::routing::NetLength> = pcb::routing::net_lengths(&pcb)
+        .into_iter()
+        .map(|l| (l.net.clone(), l))
+        .collect();
+    let mut pads: HashMap<&str, usize> = HashMap::new();
+    for pad in pcb.footprints.iter().flat_map(|f| &f.pads) {
+        if let Some(net) = pad.net.as_deref() {
+            *pads.entry(net).or_default() += 1;
+        }
+    }
+    let mut layers: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    let tracks = pcb.tracks.iter().map(|t| (&t.net, vec![t.layer.clone()]));
+    let arcs = pcb.arcs.iter().map(|a| (&a.net, vec![a.layer.clone()]));
+    let vias = pcb.vias.iter().map(|v| (&v.net, pcb::copper::expand_layers(&v.layers, &copper, true)));
+    let zones = pcb.zones.iter().map(|z| (&z.net, pcb::copper::expand_layers(&z.layers, &copper, false)));
+    for (net, on) in tracks.chain(arcs).chain(vias).chain(zones) {
+        let Some(net) = net.as_deref() else { continue };
+        let entry = layers.entry(net).or_default();
+        for layer in on {
+            if let Some(known) = copper.iter().find(|c| **c == layer) {
+                entry.insert(known.as_str());
+            }
+        }
+    }
+    // Copper order (F.Cu, In1.Cu, ..., B.Cu) reads better than alphabetical
+    let order = |layer: &&str| copper.iter().position(|c| c == layer);
+    
+    let mut nets: Vec<NetSummary> = pcb
+        .nets
+        .iter()
+        .filter(|(_, name)| !name.is_empty())
+        .filter(|(_, name)| net_filter.map_or(true, |f| symbol::query::glob_match(f, name)))
+        .map(|(&id, name)| {
+            let length = lengths.get(name);
+            let mut on: Vec<&str> = layers.get(name.as_str()).map(|l| l.iter().copied().collect()).unwrap_or_default();
+            on.sort_by_key(order);
+            NetSummary {
+                id,
+                name: name.clone(),
+                pads: pads.get(name.as_str()).copied().unwrap_or(0),
+                track_length: length.map_or(0.0, |l| l.track_length),
+                total_length: length.map_or(0.0, |l| l.total()),
+                vias: length.map_or(0, |l| l.via_count),
+                layers: on.into_iter().map(str::to_string).collect(),
+                class: pcb.net_class_of(name).map_or_else(|| "Default".to_string(), |c| c.name.clone()),
+            }
+        })
+        .collect();
+    nets.sort_by(|a, b| {
+        let by_name = a.name.cmp(&b.name);
+        match sort {
+            NetSort::Name => by_name,
+            NetSort::Pads => a.pads.cmp(&b.pads).then(by_name),
+            NetSort::Length => a.total_length.total_cmp(&b.total_length).then(by_name),
+            NetSort::Vias => a.vias.cmp(&b.vias).then(by_name),
+        }
+    });
+    if reverse {
+        nets.reverse();
+    }
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&nets)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("Nets");
+        println!("====");
+        println!("Total nets: {}", nets.len());
+        
+        if !nets.is_empty() {
+            let mut table = Table::new();
+            table.add_row(row!["Net", "Pads", "Length (mm)", "Vias", "Layers", "Class"]);
+            for net in &nets {
+                table.add_row(row![
+                    net.name,
+                    net.pads,
+                    format!("{:.2}", net.total_length),
+                    net.vias,
+                    net.layers.join(" "),
+                    net.class,
+                ]);
+            }
+            table.printstd();
+        }
+    }
+    
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct NetPads {
+    /// Declared net; empty for as-routed islands
+    net: String,
+    pads: Vec<String>,
+    /// Copper islands the pads are split across; 1 when fully routed
+    islands: usize,
+}
+
+fn handle_netlist(
+    content: &str,
+    json_output: bool,
+    net_filter: Option<&str>,
+    routed: bool,
+    xml: bool,
+) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    if xml {
+        print!("{}", netlist::Netlist::from_pcb(&pcb).to_xml());
+        return Ok(());
+    }
+    let connectivity = pcb::connectivity::Connectivity::build(&pcb);
+    let pad_name = |p: &pcb::connectivity::PadRef| format!("{}.{}", p.reference, p.pad);
+    let matches = |net: &str| net_filter.map_or(true, |f| symbol::query::glob_match(f, net));
+    
+    let nets: Vec<NetPads> = if routed {
+        connectivity
+            .routed_netlist()
+            .into_iter()
+            .filter(|pads| pads.len() > 1)
+            .map(|pads| {
+                let mut declared: Vec<String> = pads
+                    .iter()
+                    .filter_map(|p| {
+                        let fp = pcb.footprint_by_ref(&p.reference)?;
+                        fp.pads.iter().find(|pad| pad.number == p.pad)?.net.clone()
+                    })
+                    .collect();
+                declared.sort();
+                declared.dedup();
+                let mut names: Vec<String> = pads.iter().map(pad_name).collect();
+                names.dedup();
+                NetPads { net: declared.join(", "), pads: names, islands: 1 }
+            })
+            .filter(|n| matches(&n.net))
+            .collect()
+    } else {
+        let islands: HashMap<String, usize> = connectivity
+            .opens()
+            .into_iter()
+            .map(|open| (open.net, open.islands.len()))
+            .collect();
+        let mut by_net: BTreeMap<&str, Vec<pcb::connectivity::PadRef>> = BTreeMap::new();
+        for footprint in &pcb.footprints {
+            for pad in footprint.pads.iter().filter(|p| !p.number.is_empty()) {
+                if let Some(net) = pad.net.as_deref().filter(|n| !n.is_empty()) {
+                    by_net.entry(net).or_default().push(pcb::connectivity::PadRef {
+                        reference: footprint.property("Reference").unwrap_or_default().to_string(),
+                        pad: pad.number.clone(),
+                    });
+                }
+            }
+        }
+        by_net
+            .into_iter()
+            .filter(|(net, _)| matches(net))
+            .map(|(net, mut pads)| {
+                pads.sort();
+                pads.dedup();
+                NetPads {
+                    net: net.to_string(),
+                    pads: pads.iter().map(pad_name).collect(),
+                    islands: islands.get(net).copied().unwrap_or(1),
+                }
+            })
+            .collect()
+    };
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&nets)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("{}", if routed { "Routed Netlist" } else { "Netlist" });
+        println!("=======");
+        println!("Total nets: {}", nets.len());
+        
+        if !nets.is_empty() {
+            let mut table = Table::new();
+            if routed {
+                table.add_row(row!["Declared net(s)", "Pads"]);
+            } else {
+                table.add_row(row!["Net", "Pads", "Status"]);
+            }
+            for net in &nets {
+                if routed {
+                    table.add_row(row![net.net, net.pads.join(" ")]);
+                } else {
+                    let status = match net.islands {
+                        1 => "routed".to_string(),
+                        n => format!("open ({} islands)", n),
+                    };
+                    table.add_row(row![net.net, net.pads.join(" "), status]);
+                }
+            }
+            table.printstd();
+        }
+    }
+    
+    Ok(())
+}
+
+/// One label of `kpx labels`
+#[derive(Serialize)]
+struct LabelRow {
+    /// `local`, `global` or `hierarchical`
+    kind: &'static str,
+    text: String,
+    /// Net name the label gives, with the sheet path for local and hierarchical labels
+    net: String,
+    sheet: String,
+    x: f64,
+    y: f64,
+    shape: Option<String>,
+}
+
+fn handle_labels(file: &Path, json_output: bool, kind: Option<LabelFilter>) -> Result<()> {
+    use schematic::LabelKind;
+    
+    let hierarchy = schematic::Hierarchy::load(file)?;
+    let wanted = kind.map(|k| match k {
+        LabelFilter::Local => LabelKind::Local,
+        LabelFilter::Global => LabelKind::Global,
+        LabelFilter::Hierarchical => LabelKind::Hierarchical,
+    });
+    let labels: Vec<LabelRow> = hierarchy
+        .labels()
+        .iter()
+        .filter(|l| wanted.map_or(true, |k| l.label.kind == k))
+        .map(|l| LabelRow {
+            kind: l.label.kind.name(),
+            text: l.label.text.clone(),
+            net: l.net_name(),
+            sheet: l.sheet_path.clone(),
+            x: l.label.at.x.as_mm(),
+            y: l.label.at.y.as_mm(),
+            shape: l.label.shape.clone(),
+        })
+        .collect();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&labels)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("Labels");
+        println!("======");
+        println!("Total labels: {}", labels.len());
+        
+        if !labels.is_empty() {
+            let mut table = Table::new();
+            table.add_row(row!["Kind", "Text", "Sheet", "X (mm)", "Y (mm)"]);
+            for label in &labels {
+                table.add_row(row![
+                    label.kind,
+                    label.text,
+                    label.sheet,
+                    format!("{:.2}", label.x),
+                    format!("{:.2}", label.y),
+                ]);
+            }
+            table.printstd();
+        }
+    }
+    
+    Ok(())
+}
+
+/// Netlist of a design from its root sheet and the sheets below it
+fn handle_schematic_netlist(file: &Path, json_output: bool, net_filter: Option<&str>, xml: bool) -> Result<()> {
+    let hierarchy = schematic::Hierarchy::load(file)?;
+    let netlist = netlist::Netlist::from_hierarchy(&hierarchy);
+    if xml {
+        print!("{}", netlist.to_xml());
+        return Ok(());
+    }
+    let matches = |net: &str| net_filter.map_or(true, |f| symbol::query::glob_match(f, net));
+    let nets: Vec<&netlist::NetlistNet> = netlist.nets.iter().filter(|n| matches(&n.name)).collect();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&nets)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        println!("Netlist");
+        println!("=======");
+        println!("Sheets: {}", hierarchy.sheets.len());
+        println!("Total nets: {}", nets.len());
+        
+        if !nets.is_empty() {
+            let mut table = Table::new();
+            table.add_row(row!["Net", "Pins"]);
+            for net in &nets {
+                let pins: Vec<String> = net.nodes.iter().map(|n| format!("{}.{}", n.reference, n.pin)).collect();
+                table.add_row(row![net.name, pins.join(" ")]);
+            }
+            table.printstd();
+        }
+    }
+    
+    Ok(())
+}
+
+fn handle_drc(
+    content: &str,
+    json_output: bool,
+    filename: &str,
+    rules_file: Option<&Path>,
+    severity: SeverityFilter,
+) -> Result<bool> {
+    use pcb::drc::Severity;
+    
+    let rules: pcb::drc::DrcRules = match rules_file {
+        Some(path) => match toml::from_str(&fs::read_to_string(path)?) {
+            Ok(rules) => rules,
+            Err(e) => {
+                eprintln!("Invalid rules file {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => Default::default(),
+    };
+    let pcb = pcb::parse_pcb(content)?;
+    let minimum = match severity {
+        SeverityFilter::Error => Severity::Error,
+        SeverityFilter::Warning => Severity::Warning,
+    };
+    let violations: Vec<pcb::drc::Violation> = pcb::drc::run_drc(&pcb, &rules)
+        .into_iter()
+        .filter(|v| v.severity >= minimum)
+        .collect();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&violations)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        for v in &violations {
+            let severity = if v.severity == Severity::Error { "error" } else { "warning" };
+            let layer = v.layer.as_deref().map(|l| format!(" {}", l)).unwrap_or_default();
+            println!(
+                "{}: {} [{}] at ({:.3}, {:.3}){}: {}",
+                filename, severity, v.rule.name(), v.location.x.as_mm(), v.location.y.as_mm(), layer, v.message
+            );
+        }
+        let errors = violations.iter().filter(|v| v.severity == Severity::Error).count();
+        println!("{}: {} error(s), {} warning(s)", filename, errors, violations.len() - errors);
+    }
+    
+    Ok(violations.is_empty())
+}
+
+fn handle_erc(file: &Path, json_output: bool, filename: &str, severity: SeverityFilter) -> Result<bool> {
+    use validate::Severity;
+    
+    let hierarchy = schematic::Hierarchy::load(file)?;
+    let minimum = match severity {
+        SeverityFilter::Error => Severity::Error,
+        SeverityFilter::Warning => Severity::Warning,
+    };
+    let violations: Vec<schematic::ErcViolation> = hierarchy
+        .erc()
+        .into_iter()
+        .filter(|v| v.severity >= minimum)
+        .collect();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&violations)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        for v in &violations {
+            let severity = if v.severity == Severity::Error { "error" } else { "warning" };
+            println!(
+                "{}: {} [{}] at ({:.3}, {:.3}) in {}: {}",
+                filename, severity, v.check.name(), v.location.x.as_mm(), v.location.y.as_mm(), v.sheet, v.message
+            );
+        }
+        let errors = violations.iter().filter(|v| v.severity == Severity::Error).count();
+        println!("{}: {} error(s), {} warning(s)", filename, errors, violations.len() - errors);
+    }
+    
+    Ok(violations.is_empty())
+}
+
+fn handle_check_annotation(
+    issues: Vec<annotation::AnnotationIssue>,
+    json_output: bool,
+    filename: &str,
+    severity: SeverityFilter,
+) -> Result<bool> {
+    use validate::Severity;
+    
+    let minimum = match severity {
+        SeverityFilter::Error => Severity::Error,
+        SeverityFilter::Warning => Severity::Warning,
+    };
+    let issues: Vec<annotation::AnnotationIssue> = issues.into_iter().filter(|i| i.severity >= minimum).collect();
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&issues)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        let mut by_sheet: BTreeMap<&str, Vec<&annotation::AnnotationIssue>> = BTreeMap::new();
+        for issue in &issues {
+            by_sheet.entry(issue.sheet.as_str()).or_default().push(issue);
+        }
+        for (sheet, issues) in by_sheet {
+            println!("{}", sheet);
+            for i in issues {
+                let severity = if i.severity == Severity::Error { "error" } else { "warning" };
+                println!("  {} [{}]: {}", severity, i.check.name(), i.message);
+            }
+        }
+        let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+        println!("{}: {} error(s), {} warning(s)", filename, errors, issues.len() - errors);
+    }
+    
+    Ok(issues.is_empty())
+}
+
+/// Patterns of `kpx find`; an element matches when any given pattern does
+struct Search {
+    reference: Option<String>,
+    value: Option<String>,
+    net: Option<String>,
+    text: Option<String>,
+}
+
+/// One element found by `kpx find`
+#[derive(Serialize)]
+struct Hit {
+    /// `footprint`, `value`, `net` or `text`
+    kind: &'static str,
+    text: String,
+    layer: String,
+    x: f64,
+    y: f64,
+}
+
+/// Print the elements matching a search; `false` when nothing was found
+fn handle_find(content: &str, json_output: bool, filename: &str, search: &Search) -> Result<bool> {
+    use symbol::query::glob_match;
+    
+    let pcb = pcb::parse_pcb(content)?;
+    let matches = |pattern: &Option<String>, text: &str| {
+        pattern.as_deref().map_or(false, |p| glob_match(p, text))
+    };
+    let needle = search.text.as_deref().map(str::to_lowercase);
+    let contains = |text: &str| needle.as_deref().map_or(false, |n| text.to_lowercase().contains(n));
+    let mut hits = Vec::new();
+    
+    for footprint in &pcb.footprints {
+        let hit = |kind, text: &str| Hit {
+            kind,
+            text: text.to_string(),
+            layer: footprint.layer.clone(),
+            x: footprint.position.x.as_mm(),
+            y: footprint.position.y.as_mm(),
+        };
+        let reference = footprint.property("Reference").unwrap_or_default();
+        if matches(&search.reference, reference) {
+            hits.push(hit("footprint", reference));
+        }
+        let value = footprint.property("Value").unwrap_or_default();
+        if matches(&search.value, value) {
+            hits.push(hit("value", &format!("{} {}", reference, value)));
+        }
+        for text in footprint.texts.iter().filter(|t| contains(&t.text)) {
+            let at = pcb::geometry::to_board(&text.position, &footprint.position, footprint.rotation);
+            hits.push(Hit { kind: "text", text: text.text.clone(), layer: text.layer.clone(), x: at.x.as_mm(), y: at.y.as_mm() });
+        }
+    }
+    
+    if search.net.is_some() {
+        let mut nets: Vec<&String> = pcb.nets.values().filter(|n| !n.is_empty() && matches(&search.net, n)).collect();
+        nets.sort();
+        for net in nets {
+            let pads: Vec<(&pcb::Footprint, &pcb::Pad)> = pcb
+                .footprints
+                .iter()
+                .flat_map(|f| f.pads.iter().map(move |p| (f, p)))
+                .filter(|(_, p)| p.net.as_ref() == Some(net))
+                .collect();
+            // Located at its first pad, or its first track when no pad is on the net
+            let (layer, at) = match pads.first() {
+                Some((f, p)) => (f.layer.clone(), f.pad_position(p)),
+                None => match pcb.tracks.iter().find(|t| t.net.as_ref() == Some(net)) {
+                    Some(t) => (t.layer.clone(), t.start.clone()),
+                    None => (String::new(), pcb::Point::default()),
+                },
+            };
+            hits.push(Hit { kind: "net", text: format!("{} ({} pads)", net, pads.len()), layer, x: at.x.as_mm(), y: at.y.as_mm() });
+        }
+    }
+    
+    for text in pcb.texts.iter().filter(|t| contains(&t.text)) {
+        hits.push(Hit { kind: "text", text: text.text.clone(), layer: text.layer.clone(), x: text.position.x.as_mm(), y: text.position.y.as_mm() });
+    }
+    for text in pcb.text_boxes.iter().filter(|t| contains(&t.text)) {
+        hits.push(Hit { kind: "text", text: text.text.clone(), layer: text.layer.clone(), x: text.start.x.as_mm(), y: text.start.y.as_mm() });
+    }
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&hits)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else if hits.is_empty() {
+        println!("{}: no matches", filename);
+    } else {
+        let mut table = Table::new();
+        table.add_row(row!["Type", "Match", "Layer", "X (mm)", "Y (mm)"]);
+        for hit in &hits {
+            table.add_row(row![
+                hit.kind,
+                hit.text.replace('\n', " "),
+                hit.layer,
+                format!("{:.3}", hit.x),
+                format!("{:.3}", hit.y)
+            ]);
+        }
+        table.printstd();
+        println!("{} match(es)", hits.len());
+    }
+    
+    Ok(!hits.is_empty())
+}
+
+fn handle_annotate(
+    content: &str,
+    json_output: bool,
+    options: &pcb::annotate::AnnotateOptions,
+    output: Option<&Path>,
+) -> Result<()> {
+    let (annotated, renames) = pcb::annotate::annotate(content, options)?;
+    let to_stdout = output == Some(Path::new("-"));
+    match output {
+        Some(_) if to_stdout => print!("{}", annotated),
+        Some(path) => fs::write(path, &annotated)?,
+        None => {}
+    }
+    
+    if json_output && !to_stdout {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&renames)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else if !to_stdout {
+        println!("Annotation");
+        println!("==========");
+        if !renames.is_empty() {
+            let mut table = Table::new();
+            table.add_row(row!["Old", "New"]);
+            for rename in &renames {
+                table.add_row(row![rename.from, rename.to]);
+            }
+            table.printstd();
+        }
+        println!("{} reference(s) changed", renames.len());
+        match output {
+            Some(path) => println!("Wrote {}", path.display()),
+            None => println!("Preview only; pass --output FILE or --in-place to write the board"),
+        }
+    } else {
+        eprintln!("{} reference(s) changed", renames.len());
+    }
+    
+    Ok(())
+}
+
+fn handle_export(
+    content: &str,
+    json_output: bool,
+    board: &str,
+    format: ExportFormat,
+    output: &Path,
+    layers: &[String],
+    combined: bool,
+) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let plottable = cam::gerber::plottable_layers(&pcb);
+    let layers: Vec<&str> = if layers.is_empty() {
+        plottable.iter().map(String::as_str).collect()
+    } else {
+        layers.iter().map(String::as_str).collect()
+    };
+    if let Some(unknown) = layers.iter().find(|l| !plottable.iter().any(|p| p == *l)) {
+        eprintln!("Cannot export layer {} (available: {})", unknown, plottable.join(", "));
+        std::process::exit(1);
+    }
     
-    match cli.command {
-        Commands::Details => {
-            if filename.ends_with(".kicad_pcb") {
-                handle_pcb_details(&content, cli.json)?;
-            } else {
-                eprintln!("Details command requires a .kicad_pcb file");
-                std::process::exit(1);
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    match format {
+        ExportFormat::Gerber => {
+            for layer in &layers {
+                files.push((cam::gerber::file_name(board, layer), cam::gerber::plot_layer(&pcb, layer)?.into_bytes()));
             }
         }
-        Commands::Layers => {
-            if filename.ends_with(".kicad_pcb") {
-                handle_layers(&content, cli.json)?;
-            } else {
-                eprintln!("Layers command requires a .kicad_pcb file");
-                std::process::exit(1);
+        ExportFormat::Drill => {
+            for drill in cam::drill::drill_files(&pcb) {
+                files.push((drill.file_name(board), drill.to_excellon().into_bytes()));
             }
         }
-        Commands::ThreeDModels => {
-            if filename.ends_with(".kicad_pcb") {
-                handle_3d_models(&content, cli.json)?;
-            } else {
-                eprintln!("3d command requires a .kicad_pcb file");
-                std::process::exit(1);
+        ExportFormat::Pos => {
+            for (side, name) in [(cam::position::SideFilter::Top, "top"), (cam::position::SideFilter::Bottom, "bottom")] {
+                let options = cam::position::PositionOptions { side, ..Default::default() };
+                let positions = cam::position::PositionFile::from_pcb(&pcb, &options);
+                if !positions.placements.is_empty() {
+                    files.push((format!("{}-{}.pos", board, name), positions.to_ascii().into_bytes()));
+                }
             }
         }
-        Commands::Positions => {
-            if filename.ends_with(".kicad_pcb") {
-                handle_positions(&content, cli.json)?;
+        ExportFormat::Svg | ExportFormat::Dxf => {
+            let options = kiparse::render::RenderOptions::default();
+            let draw = |layers: &[&str]| match format {
+                ExportFormat::Svg => kiparse::render::render_svg(&pcb, layers, &options),
+                _ => kiparse::render::render_dxf(&pcb, layers),
+            };
+            let extension = if format == ExportFormat::Svg { "svg" } else { "dxf" };
+            if combined {
+                files.push((format!("{}.{}", board, extension), draw(&layers)?.into_bytes()));
             } else {
-                eprintln!("Positions command requires a .kicad_pcb file");
-                std::process::exit(1);
+                for layer in &layers {
+                    let name = format!("{}-{}.{}", board, layer.replace('.', "_"), extension);
+                    files.push((name, draw(&[layer])?.into_bytes()));
+                }
             }
         }
-        Commands::Symbols => {
-            if filename.ends_with(".kicad_sym") {
-                handle_symbols(&content, cli.json)?;
-            } else {
-                eprintln!("Symbols command requires a .kicad_sym file");
-                std::process::exit(1);
-            }
+        ExportFormat::Kpcb => {
+            files.push((format!("{}.kpcb", board), pcb.to_binary()?));
         }
     }
     
-    Ok(())
-}
-
-fn handle_pcb_details(content: &str, json_output: bool) -> Result<()> {
-    let pcb = pcb::parse_layers_only(content)?;
-    
-    // Extract board dimensions from Edge.Cuts layer
-    let edge_cuts_re = Regex::new(
-        r#"(?s)\(gr_line\s*\(start\s+([\d.-]+)\s+([\d.-]+)\)\s*\(end\s+([\d.-]+)\s+([\d.-]+)\).*?\(layer\s+"Edge\.Cuts"\)"#
-    ).unwrap();
-    
-    let mut min_x = f64::MAX;
-    let mut min_y = f64::MAX;
-    let mut max_x = f64::MIN;
-    let mut max_y = f64::MIN;
-    
-    for cap in edge_cuts_re.captures_iter(content) {
-        let x1: f64 = cap[1].parse().unwrap_or(0.0);
-        let y1: f64 = cap[2].parse().unwrap_or(0.0);
-        let x2: f64 = cap[3].parse().unwrap_or(0.0);
-        let y2: f64 = cap[4].parse().unwrap_or(0.0);
-        
-        min_x = min_x.min(x1).min(x2);
-        min_y = min_y.min(y1).min(y2);
-        max_x = max_x.max(x1).max(x2);
-        max_y = max_y.max(y1).max(y2);
+    fs::create_dir_all(output)?;
+    let mut written = Vec::new();
+    for (name, data) in &files {
+        let path = output.join(name);
+        fs::write(&path, data)?;
+        written.push(path);
     }
     
-    let board_width_mm = if max_x > min_x { max_x - min_x } else { 0.0 };
-    let board_height_mm = if max_y > min_y { max_y - min_y } else { 0.0 };
-    let board_width_mils = board_width_mm * 39.3701; // 1mm = 39.3701 mils
-    let board_height_mils = board_height_mm * 39.3701;
-    let board_area_mm2 = board_width_mm * board_height_mm;
-    let board_area_sq_in = board_area_mm2 / 645.16; // 1 sq inch = 645.16 mm²
-    
     if json_output {
         #[cfg(feature = "json")]
         {
-            let output = serde_json::json!({
-                "layers": pcb.layers.len(),
-                "signal_layers": pcb.layers.values().filter(|l| l.layer_type == "signal").count(),
-                "file_size_kb": content.len() as f64 / 1024.0,
-                "complexity": estimate_complexity(content),
-                "board_size": {
-                    "width_mm": board_width_mm,
-                    "height_mm": board_height_mm,
-                    "width_mils": board_width_mils,
-                    "height_mils": board_height_mils,
-                    "area_mm2": board_area_mm2,
-                    "area_sq_in": board_area_sq_in,
-                },
-                "components": content.matches("(footprint").count(),
-                "tracks": content.matches("(segment").count(),
-                "vias": content.matches("(via").count(),
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            print_json(serde_json::to_string_pretty(&written)?);
         }
         #[cfg(not(feature = "json"))]
         {
@@ -153,47 +2204,34 @@ fn handle_pcb_details(content: &str, json_output: bool) -> Result<()> {
             std::process::exit(1);
         }
     } else {
-        println!("KiCad PCB Analysis");
-        println!("==================");
-        println!("Layers: {}", pcb.layers.len());
-        println!("Signal layers: {}", pcb.layers.values().filter(|l| l.layer_type == "signal").count());
-        println!("File size: {:.2} KB", content.len() as f64 / 1024.0);
-        println!("Complexity: {}", estimate_complexity(content));
-        
-        // Board dimensions
-        if board_width_mm > 0.0 && board_height_mm > 0.0 {
-            println!("\nBoard Dimensions:");
-            println!("  Width:  {:.2} mm ({:.0} mils)", board_width_mm, board_width_mils);
-            println!("  Height: {:.2} mm ({:.0} mils)", board_height_mm, board_height_mils);
-            println!("  Area:   {:.2} mm² ({:.2} sq in)", board_area_mm2, board_area_sq_in);
+        for path in &written {
+            println!("Wrote {}", path.display());
         }
-        
-        // Component count
-        let footprint_count = content.matches("(footprint").count();
-        let track_count = content.matches("(segment").count();
-        let via_count = content.matches("(via").count();
-        
-        println!("\nBoard Statistics:");
-        println!("  Components: {}", footprint_count);
-        println!("  Tracks: {}", track_count);
-        println!("  Vias: {}", via_count);
-        
-        if board_area_mm2 > 0.0 && footprint_count > 0 {
-            let density = (footprint_count as f64 / board_area_mm2) * 645.16; // components per sq inch
-            println!("  Density: {:.1} components/sq inch", density);
+        if written.is_empty() {
+            println!("Nothing to export");
         }
     }
     
     Ok(())
 }
 
-fn handle_layers(content: &str, json_output: bool) -> Result<()> {
-    let pcb = pcb::parse_layers_only(content)?;
+fn handle_query(content: &str, json_output: bool, selector: &str, count: bool) -> Result<()> {
+    let selector = match pcb::query::Selector::parse(selector) {
+        Ok(selector) => selector,
+        Err(e) => {
+            eprintln!("Invalid selector: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let pcb = pcb::parse_pcb(content)?;
+    let elements = selector.select(&pcb);
     
-    if json_output {
+    if count {
+        println!("{}", elements.len());
+    } else if json_output {
         #[cfg(feature = "json")]
         {
-            println!("{}", serde_json::to_string_pretty(&pcb)?);
+            print_json(serde_json::to_string_pretty(&elements)?);
         }
         #[cfg(not(feature = "json"))]
         {
@@ -201,83 +2239,143 @@ fn handle_layers(content: &str, json_output: bool) -> Result<()> {
             std::process::exit(1);
         }
     } else {
-        println!("KiCad PCB Layer Information");
-        println!("===========================");
-        println!("Total layers: {}", pcb.layers.len());
-        
-        let mut table = Table::new();
-        table.add_row(row!["ID", "Name", "Type", "User Name"]);
-        
-        let mut sorted_layers: Vec<_> = pcb.layers.iter().collect();
-        sorted_layers.sort_by_key(|(id, _)| *id);
-        
-        for (id, layer) in sorted_layers {
-            table.add_row(row![
-                id,
-                layer.name,
-                layer.layer_type,
-                layer.user_name.as_deref().unwrap_or("-")
-            ]);
+        for element in &elements {
+            println!("{}", describe_element(element));
         }
-        
-        table.printstd();
     }
     
     Ok(())
 }
 
-fn handle_3d_models(content: &str, json_output: bool) -> Result<()> {
-    let pcb = pcb::parse_layers_only(content)?;
+/// One-line summary of a queried element: its kind, name and main attributes
+fn describe_element(element: &pcb::query::Element) -> String {
+    use pcb::query::ElementKind;
+    let attr = |name: &str| element.attr(name).join(",");
+    let kind = element.kind();
+    let name = match kind {
+        ElementKind::Footprint => format!("{} {} {}", attr("ref"), attr("value"), attr("footprint")),
+        ElementKind::Pad => format!("{}.{}", attr("ref"), attr("number")),
+        ElementKind::Net | ElementKind::Layer => attr("name"),
+        ElementKind::Text => format!("{:?}", attr("text")),
+        ElementKind::Model => format!("{} {}", attr("ref"), attr("path")),
+        ElementKind::Graphic => attr("type"),
+        _ => String::new(),
+    };
+    let mut line = format!("{} {}", kind, name.trim());
+    for key in ["net", "layer", "x", "y", "width", "length", "size", "drill"] {
+        let value = attr(key);
+        if !value.is_empty() && kind != ElementKind::Net {
+            line = format!("{} {}={}", line.trim_end(), key, value);
+        }
+    }
+    line.trim_end().to_string()
+}
+
+fn handle_validate(content: &str, filename: &str, json_output: bool, strict: bool) -> Result<bool> {
+    let result = validate::validate(content);
+    let failed = !result.is_valid() || (strict && result.warnings().next().is_some());
     
-    // Extract 3D model information
-    let footprint_re = Regex::new(
-        r#"(?s)\(footprint\s+"([^"]+)".*?\(property\s+"Reference"\s+"([^"]+)".*?\(model\s+"([^"]+)""#
-    ).unwrap();
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&result)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        for issue in &result.issues {
+            let severity = match issue.severity {
+                validate::Severity::Error => "error",
+                validate::Severity::Warning => "warning",
+            };
+            match issue.line {
+                Some(line) => println!("{}:{}: {}: {}", filename, line, severity, issue.message),
+                None => println!("{}: {}: {}", filename, severity, issue.message),
+            }
+        }
+        println!(
+            "{}: {} ({} errors, {} warnings)",
+            filename,
+            if failed { "FAILED" } else { "OK" },
+            result.errors().count(),
+            result.warnings().count()
+        );
+    }
     
-    let all_refs_re = Regex::new(
-        r#"(?s)\(footprint\s+"[^"]+?".*?\(property\s+"Reference"\s+"([^"]+)""#
-    ).unwrap();
+    Ok(!failed)
+}
+
+fn handle_bom(bom: &bom::Bom, json_output: bool, markdown: bool) -> Result<()> {
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(bom.to_json()?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else if markdown {
+        print!("{}", bom.to_markdown());
+    } else {
+        print!("{}", bom.to_csv());
+    }
     
-    let mut components_with_models = Vec::new();
-    let mut model_types: HashMap<String, usize> = HashMap::new();
+    Ok(())
+}
+
+/// Model path variables from the environment, with `KIPRJMOD` set to the board's directory
+fn model_paths(file: &Path) -> Result<cam::models::ModelPaths> {
+    // Models of a board read from stdin resolve against the working directory
+    let project_dir = if file == Path::new("-") {
+        Some(std::env::current_dir()?)
+    } else {
+        file.canonicalize()?.parent().map(Path::to_path_buf)
+    };
+    Ok(cam::models::ModelPaths::from_env(project_dir.as_deref()))
+}
+
+fn handle_model_placement(content: &str, file: &Path, json_output: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let paths = model_paths(file)?;
+    let placements = cam::models::ModelPlacementFile::from_pcb(
+        &pcb,
+        &paths,
+        &cam::models::ModelPlacementOptions::default(),
+    );
     
-    for cap in footprint_re.captures_iter(content) {
-        let reference = cap[2].to_string();
-        let model_path = &cap[3];
-        
-        components_with_models.push(reference);
-        
-        let model_type = if model_path.ends_with(".wrl") {
-            "WRL"
-        } else if model_path.ends_with(".step") || model_path.ends_with(".stp") {
-            "STEP"
-        } else {
-            "Other"
-        };
-        *model_types.entry(model_type.to_string()).or_insert(0) += 1;
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(placements.to_json()?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else {
+        print!("{}", placements.to_csv());
     }
     
-    let mut all_refs = HashSet::new();
-    for cap in all_refs_re.captures_iter(content) {
-        all_refs.insert(cap[1].to_string());
-    }
+    Ok(())
+}
+
+/// Report missing model files; `false` when any model cannot be loaded
+fn handle_model_check(content: &str, file: &Path, json_output: bool) -> Result<bool> {
+    use cam::models::ModelProblem;
     
-    let total = all_refs.len();
-    let with_models = components_with_models.len();
-    let without_models = total - with_models;
-    let coverage = if total > 0 { (with_models as f64 / total as f64) * 100.0 } else { 0.0 };
+    let pcb = pcb::parse_pcb(content)?;
+    let check = cam::models::check_models(&pcb, &model_paths(file)?);
     
     if json_output {
         #[cfg(feature = "json")]
         {
-            let output = serde_json::json!({
-                "total_components": total,
-                "with_3d_models": with_models,
-                "without_3d_models": without_models,
-                "coverage_percent": coverage,
-                "model_types": model_types,
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            print_json(serde_json::to_string_pretty(&check)?);
         }
         #[cfg(not(feature = "json"))]
         {
@@ -285,57 +2383,78 @@ fn handle_3d_models(content: &str, json_output: bool) -> Result<()> {
             std::process::exit(1);
         }
     } else {
-        println!("3D Model Coverage Analysis");
-        println!("==========================");
-        println!("Total components: {}", total);
-        println!("With 3D models: {} ({:.1}%)", with_models, coverage);
-        println!("Without 3D models: {} ({:.1}%)", without_models, 100.0 - coverage);
+        println!("3D Model File Check");
+        println!("===================");
+        println!("Models found: {}", check.found);
+        println!("Models missing: {}", check.missing.len());
+        println!("Components without a model: {}", check.without_model.len());
         
-        if !model_types.is_empty() {
-            println!("\nModel Types:");
-            for (model_type, count) in &model_types {
-                println!("  {}: {}", model_type, count);
+        if !check.missing.is_empty() {
+            println!("\nMissing model files:");
+            let mut table = Table::new();
+            table.add_row(row!["Reference", "Model", "Problem"]);
+            for m in &check.missing {
+                let problem = match (m.problem, &m.resolved) {
+                    (ModelProblem::FileNotFound, Some(path)) => format!("not found: {}", path),
+                    (ModelProblem::FileNotFound, None) => "not found".to_string(),
+                    (ModelProblem::UnresolvedVariable, _) => "undefined path variable".to_string(),
+                    (ModelProblem::NotEmbedded, _) => "embedded file missing".to_string(),
+                };
+                table.add_row(row![m.reference, m.model, problem]);
             }
+            table.printstd();
+        }
+        if !check.without_model.is_empty() {
+            println!("\nComponents without a model: {}", check.without_model.join(", "));
         }
     }
     
-    Ok(())
+    Ok(check.missing.is_empty())
 }
 
-fn handle_positions(content: &str, json_output: bool) -> Result<()> {
-    let _pcb = pcb::parse_layers_only(content)?;
-    
-    // Extract component positions
-    let component_re = Regex::new(
-        r#"(?s)\(footprint\s+"([^"]+)".*?\(at\s+([\d.-]+)\s+([\d.-]+)(?:\s+([\d.-]+))?\).*?\(property\s+"Reference"\s+"([^"]+)""#
-    ).unwrap();
+fn handle_report(content: &str, json_output: bool, html: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let report = report::DesignReport::from_pcb(&pcb, &report::ReportOptions::default());
     
-    let mut components = Vec::new();
-    
-    for cap in component_re.captures_iter(content) {
-        let footprint = &cap[1];
-        let x: f64 = cap[2].parse().unwrap_or(0.0);
-        let y: f64 = cap[3].parse().unwrap_or(0.0);
-        let rotation: f64 = cap.get(4).map_or(0.0, |m| m.as_str().parse().unwrap_or(0.0));
-        let reference = &cap[5];
-        
-        components.push(serde_json::json!({
-            "reference": reference,
-            "footprint": footprint,
-            "x": x,
-            "y": y,
-            "rotation": rotation,
-        }));
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&report)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+    } else if html {
+        print!("{}", report.to_html());
+    } else {
+        print!("{}", report.to_markdown());
     }
     
-    if json_output {
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct PositionsReport<'a> {
+    component_count: usize,
+    components: &'a [cam::position::Placement],
+}
+
+/// Print the footprint placements; CSV is KiCad's position file layout
+fn handle_positions(content: &str, json_output: bool, csv: bool) -> Result<()> {
+    let pcb = pcb::parse_pcb(content)?;
+    let options = cam::position::PositionOptions { exclude_dnp: false, ..Default::default() };
+    let positions = cam::position::PositionFile::from_pcb(&pcb, &options);
+    let components = &positions.placements;
+    
+    if csv {
+        print!("{}", positions.to_csv());
+    } else if json_output {
         #[cfg(feature = "json")]
         {
-            let output = serde_json::json!({
-                "component_count": components.len(),
-                "components": components,
-            });
-            println!("{}", serde_json::to_string_pretty(&output)?);
+            let output = PositionsReport { component_count: components.len(), components };
+            print_json(serde_json::to_string_pretty(&output)?);
         }
         #[cfg(not(feature = "json"))]
         {
@@ -349,22 +2468,16 @@ fn handle_positions(content: &str, json_output: bool) -> Result<()> {
         
         if !components.is_empty() {
             let mut table = Table::new();
-            table.add_row(row!["Reference", "X (mm)", "Y (mm)", "Rotation", "Footprint"]);
-            
-            // Sort by reference
-            components.sort_by(|a, b| {
-                let ref_a = a["reference"].as_str().unwrap_or("");
-                let ref_b = b["reference"].as_str().unwrap_or("");
-                natural_sort(ref_a, ref_b)
-            });
+            table.add_row(row!["Reference", "X (mm)", "Y (mm)", "Rotation", "Side", "Package"]);
             
-            for comp in &components {
+            for comp in components {
                 table.add_row(row![
-                    comp["reference"].as_str().unwrap_or("-"),
-                    format!("{:.2}", comp["x"].as_f64().unwrap_or(0.0)),
-                    format!("{:.2}", comp["y"].as_f64().unwrap_or(0.0)),
-                    format!("{:.0}°", comp["rotation"].as_f64().unwrap_or(0.0)),
-                    comp["footprint"].as_str().unwrap_or("-"),
+                    comp.reference,
+                    format!("{:.2}", comp.x),
+                    format!("{:.2}", comp.y),
+                    format!("{:.0}°", comp.rotation),
+                    format!("{:?}", comp.side),
+                    comp.package,
                 ]);
             }
             
@@ -375,13 +2488,16 @@ fn handle_positions(content: &str, json_output: bool) -> Result<()> {
     Ok(())
 }
 
-fn handle_symbols(content: &str, json_output: bool) -> Result<()> {
+fn handle_symbols(content: &str, json_output: bool, pins: bool, properties: bool) -> Result<()> {
+    if pins || properties {
+        return handle_symbol_details(content, json_output, pins, properties);
+    }
     let symbols = symbol::parse_symbol_lib(content)?;
     
     if json_output {
         #[cfg(feature = "json")]
         {
-            println!("{}", serde_json::to_string_pretty(&symbols)?);
+            print_json(serde_json::to_string_pretty(&symbols)?);
         }
         #[cfg(not(feature = "json"))]
         {
@@ -415,42 +2531,209 @@ fn handle_symbols(content: &str, json_output: bool) -> Result<()> {
     Ok(())
 }
 
-fn estimate_complexity(content: &str) -> &'static str {
-    let footprint_count = content.matches("(footprint").count();
-    let track_count = content.matches("(segment").count();
-    
-    match (footprint_count, track_count) {
-        (0..=10, 0..=50) => "Simple",
-        (11..=100, 51..=500) => "Moderate",
-        (101..=500, 501..=2000) => "Complex",
-        _ => "Very Complex"
+/// Per-symbol property and pin listing, with inherited fields of derived symbols
+fn handle_symbol_details(content: &str, json_output: bool, pins: bool, properties: bool) -> Result<()> {
+    let mut symbols = symbol::parse_symbol_lib_full(content)?;
+    symbol::resolve_extends(&mut symbols);
+
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&symbols)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
+        }
+        return Ok(());
     }
-}
 
-fn natural_sort(a: &str, b: &str) -> std::cmp::Ordering {
-    let (a_prefix, a_num) = split_reference(a);
-    let (b_prefix, b_num) = split_reference(b);
-    
-    match a_prefix.cmp(&b_prefix) {
-        std::cmp::Ordering::Equal => a_num.cmp(&b_num),
-        other => other,
+    println!("Symbol Library Analysis");
+    println!("=======================");
+    println!("Total symbols: {}", symbols.len());
+
+    for sym in &symbols {
+        println!();
+        println!("{}", sym.name);
+        println!("{}", "-".repeat(sym.name.chars().count()));
+        if let Some(parent) = &sym.extends {
+            println!("Extends: {}", parent);
+        }
+        if sym.unit_count > 1 {
+            println!("Units: {}", sym.unit_count);
+        }
+        if sym.power {
+            println!("Power symbol");
+        }
+
+        if properties {
+            let mut table = Table::new();
+            table.add_row(row!["Property", "Value"]);
+            for property in &sym.properties {
+                let value = if property.value.is_empty() {
+                    "-"
+                } else {
+                    &property.value
+                };
+                table.add_row(row![property.name, value]);
+            }
+            table.printstd();
+        }
+
+        if pins {
+            let (header, rows) = sym.pinout().rows();
+            if rows.is_empty() {
+                println!("No pins");
+                continue;
+            }
+            let mut table = Table::new();
+            table.add_row(header.iter().collect());
+            for cells in &rows {
+                table.add_row(cells.iter().collect());
+            }
+            table.printstd();
+        }
     }
+
+    Ok(())
 }
 
-fn split_reference(reference: &str) -> (&str, u32) {
-    let mut split_pos = 0;
-    for (i, ch) in reference.chars().enumerate() {
-        if ch.is_numeric() {
-            split_pos = i;
-            break;
+fn handle_diff(old_path: &Path, new_path: &Path, json_output: bool) -> Result<()> {
+    let old = pcb::parse_pcb(&fs::read_to_string(old_path)?)?;
+    let new = pcb::parse_pcb(&fs::read_to_string(new_path)?)?;
+    let changes = pcb::diff::diff_boards(&old, &new);
+    
+    if json_output {
+        #[cfg(feature = "json")]
+        {
+            print_json(serde_json::to_string_pretty(&changes)?);
+        }
+        #[cfg(not(feature = "json"))]
+        {
+            eprintln!("JSON output requires the 'json' feature");
+            std::process::exit(1);
         }
+        return Ok(());
     }
     
-    if split_pos > 0 {
-        let (prefix, num_str) = reference.split_at(split_pos);
-        let num = num_str.parse().unwrap_or(0);
-        (prefix, num)
-    } else {
-        (reference, 0)
+    println!("Board Diff: {} -> {}", old_path.display(), new_path.display());
+    println!("==========");
+    if changes.is_empty() {
+        println!("No differences");
+        return Ok(());
     }
-}
\ No newline at end of file
+    
+    if !(changes.added_components.is_empty()
+        && changes.removed_components.is_empty()
+        && changes.moved_components.is_empty())
+    {
+        println!(
+            "\nComponents: {} added, {} removed, {} moved",
+            changes.added_components.len(),
+            changes.removed_components.len(),
+            changes.moved_components.len()
+        );
+        for c in &changes.added_components {
+            println!("  + {} {} ({})", c.reference, c.value, c.footprint);
+        }
+        for c in &changes.removed_components {
+            println!("  - {} {} ({})", c.reference, c.value, c.footprint);
+        }
+        for m in &changes.moved_components {
+            println!(
+                "  ~ {} ({:.3}, {:.3}) {}° {} -> ({:.3}, {:.3}) {}° {}",
+                m.reference, m.from.x, m.from.y, m.from.rotation, m.from.side,
+                m.to.x, m.to.y, m.to.rotation, m.to.side
+            );
+        }
+    }
+    
+    if !changes.property_changes.is_empty() {
+        println!("\nProperties:");
+        for c in &changes.property_changes {
+            println!(
+                "  ~ {} {}: {} -> {}",
+                c.reference,
+                c.property,
+                c.from.as_deref().unwrap_or("-"),
+                c.to.as_deref().unwrap_or("-")
+            );
+        }
+    }
+    
+    if !(changes.added_nets.is_empty()
+        && changes.removed_nets.is_empty()
+        && changes.pad_net_changes.is_empty())
+    {
+        println!(
+            "\nNets: {} added, {} removed, {} pads reassigned",
+            changes.added_nets.len(),
+            changes.removed_nets.len(),
+            changes.pad_net_changes.len()
+        );
+        for net in &changes.added_nets {
+            println!("  + {}", net);
+        }
+        for net in &changes.removed_nets {
+            println!("  - {}", net);
+        }
+        for c in &changes.pad_net_changes {
+            println!(
+                "  ~ {}: {} -> {}",
+                c.pad,
+                c.from.as_deref().unwrap_or("(none)"),
+                c.to.as_deref().unwrap_or("(none)")
+            );
+        }
+    }
+    
+    if !changes.layer_changes.is_empty() {
+        println!("\nLayers:");
+        for c in &changes.layer_changes {
+            println!(
+                "  ~ {} {}: {} -> {}",
+                c.id,
+                c.property,
+                c.from.as_deref().unwrap_or("-"),
+                c.to.as_deref().unwrap_or("-")
+            );
+        }
+    }
+    
+    if !changes.stackup_changes.is_empty() {
+        println!("\nStackup:");
+        for c in &changes.stackup_changes {
+            println!(
+                "  ~ {} {}: {} -> {}",
+                c.layer,
+                c.property,
+                c.from.as_deref().unwrap_or("-"),
+                c.to.as_deref().unwrap_or("-")
+            );
+        }
+    }
+    
+    if let Some(outline) = &changes.outline {
+        let describe = |o: &pcb::diff::OutlineSummary| {
+            format!(
+                "{:.2} x {:.2} mm, {:.2} mm², {} polygon(s), {} vertices",
+                o.width, o.height, o.area, o.polygons, o.vertices
+            )
+        };
+        println!("\nBoard outline:");
+        println!("  - {}", describe(&outline.from));
+        println!("  + {}", describe(&outline.to));
+    }
+    
+    Ok(())
+}
+
+fn estimate_complexity(stats: &pcb::types::BoardStats) -> &'static str {
+    match (stats.component_count, stats.track_count) {
+        (0..=10, 0..=50) => "Simple",
+        (11..=100, 51..=500) => "Moderate",
+        (101..=500, 501..=2000) => "Complex",
+        _ => "Very Complex"
+    }
+}
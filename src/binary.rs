@@ -0,0 +1,87 @@
+//! Binary serialization of parsed boards
+//!
+//! [`PcbFile::to_binary`] writes a board with [bincode](https://docs.rs/bincode)
+//! (variable-length integers, little-endian) behind a `KPCB` header naming the
+//! kiparse version, and [`PcbFile::from_binary`] refuses data written by another
+//! version. Bincode is not self-describing, so a board is only readable by the
+//! version that wrote it; the check makes the bytes safe to keep as a cache next
+//! to the board file.
+//!
+//! ```rust
+//! use kiparse::prelude::*;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+//!   (net 0 "") (net 1 "GND")
+//!   (segment (start 0 0) (end 10 0) (width 0.25) (layer "F.Cu") (net 1)))"#)?;
+//! let bytes = pcb.to_binary()?;
+//!
+//! assert_eq!(PcbFile::from_binary(&bytes)?, pcb);
+//! # Ok::<(), KicadError>(())
+//! ```
+
+use bincode::Options;
+
+use crate::error::{KicadError, Result};
+use crate::pcb::types::PcbFile;
+
+/// Start of [`PcbFile::to_binary`] output
+const MAGIC: &[u8; 4] = b"KPCB";
+
+/// Varint integers, little-endian, trailing bytes rejected
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+}
+
+impl PcbFile {
+    /// The board in bincode, behind a header naming the kiparse version
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let mut out = MAGIC.to_vec();
+        options().serialize_into(&mut out, env!("CARGO_PKG_VERSION"))?;
+        options().serialize_into(&mut out, self)?;
+        Ok(out)
+    }
+
+    /// Read a board written by [`PcbFile::to_binary`] with this version of kiparse
+    pub fn from_binary(bytes: &[u8]) -> Result<PcbFile> {
+        let Some(mut rest) = bytes.strip_prefix(MAGIC) else {
+            return Err(KicadError::InvalidFormat("Not a kiparse binary board".to_string()));
+        };
+        let version: String = options().allow_trailing_bytes().deserialize_from(&mut rest)?;
+        if version != env!("CARGO_PKG_VERSION") {
+            return Err(KicadError::InvalidFormat(format!(
+                "Binary board written by kiparse {}, this is {}; parse the board again",
+                version,
+                env!("CARGO_PKG_VERSION")
+            )));
+        }
+        Ok(options().deserialize(rest)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::pcb_parser::parse_pcb;
+
+    #[test]
+    fn test_board_header() {
+        let pcb = parse_pcb(r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (footprint "R" (layer "F.Cu") (at 1 2 90) (property "Reference" "R1")
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 1 "GND")))
+  (via (at 5 5) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu")))"#)
+        .unwrap();
+        let bytes = pcb.to_binary().unwrap();
+        assert_eq!(PcbFile::from_binary(&bytes).unwrap(), pcb);
+
+        assert!(PcbFile::from_binary(&bytes[4..]).is_err());
+        assert!(PcbFile::from_binary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(PcbFile::from_binary(&[bytes.as_slice(), &[0]].concat()).is_err());
+
+        let mut old = MAGIC.to_vec();
+        options().serialize_into(&mut old, "0.0.1").unwrap();
+        options().serialize_into(&mut old, &pcb).unwrap();
+        let error = PcbFile::from_binary(&old).unwrap_err();
+        assert!(error.to_string().contains("kiparse 0.0.1"));
+    }
+}
@@ -0,0 +1,522 @@
+//! Bill of materials from a parsed board or schematic
+//!
+//! Footprints are grouped by value, footprint and manufacturer part number, or
+//! by the fields in [`BomOptions::group_by`]. Footprints flagged
+//! `exclude_from_bom` are always left out; do-not-populate parts are left out
+//! unless [`BomOptions::include_dnp`] is set, in which case they get lines of
+//! their own. [`Bom::from_schematic`] builds the same table from the symbols of a
+//! `.kicad_sch` sheet, and [`Bom::from_hierarchy`] from those of a whole design,
+//! with the DNP flags and field values of a design variant when
+//! [`BomOptions::variant`] names one.
+//!
+//! ```rust
+//! use kiparse::{bom::Bom, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 0)
+//!     (property "Reference" "R1") (property "Value" "10k"))
+//!   (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 2 0)
+//!     (property "Reference" "R2") (property "Value" "10k"))
+//! )"#)?;
+//! let bom = Bom::from_pcb(&pcb);
+//!
+//! assert_eq!(bom.lines[0].references, ["R1", "R2"]);
+//! assert!(bom.to_csv().contains("\"R1, R2\",2,10k,Resistor_SMD:R_0603"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::error::{KicadError, Result};
+use crate::pcb::types::PcbFile;
+use crate::schematic::Hierarchy;
+use crate::sexpr::parse_sexpr;
+
+/// Property names (compared case-insensitively, ignoring spaces, `_`, `-` and `.`)
+/// that hold the manufacturer part number
+const MPN_FIELDS: &[&str] = &["mpn", "manufacturerpartnumber", "mfrpartnumber", "mfrpn", "mfgpartnumber", "partnumber"];
+
+/// Property names that hold the manufacturer
+const MANUFACTURER_FIELDS: &[&str] = &["manufacturer", "mfr", "mfg", "manufacturername"];
+
+/// One BOM row: identical parts and where they go
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BomLine {
+    /// Reference designators in natural order (`R2` before `R10`)
+    pub references: Vec<String>,
+    pub quantity: usize,
+    pub value: String,
+    /// Library footprint identifier, e.g. `Resistor_SMD:R_0603_1608Metric`
+    pub footprint: String,
+    pub mpn: Option<String>,
+    pub manufacturer: Option<String>,
+    pub dnp: bool,
+    /// Values of [`BomOptions::columns`], from the first part that sets them
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "BTreeMap::is_empty"))]
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Which parts go into the BOM and how they are grouped
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BomOptions {
+    /// List do-not-populate parts on separate lines instead of dropping them
+    pub include_dnp: bool,
+    /// Fields that must be equal for parts to share a line (`Value`, `Footprint`,
+    /// `Reference` or any property); empty groups by value, footprint and MPN
+    pub group_by: Vec<String>,
+    /// Extra properties to output as columns, e.g. `LCSC` or `Tolerance`
+    pub columns: Vec<String>,
+    /// Design variant whose per-instance overrides apply to schematic parts
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub variant: Option<String>,
+}
+
+/// Grouped bill of materials
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bom {
+    pub lines: Vec<BomLine>,
+    /// Extra columns of [`Bom::to_csv`] and [`Bom::to_markdown`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub columns: Vec<String>,
+}
+
+/// A footprint or schematic symbol as seen by the BOM
+struct Part<'a> {
+    reference: String,
+    footprint: String,
+    properties: &'a HashMap<String, String>,
+    dnp: bool,
+}
+
+impl Part<'_> {
+    fn field(&self, name: &str) -> Option<String> {
+        match name.to_ascii_lowercase().as_str() {
+            "reference" => Some(self.reference.clone()),
+            "footprint" => Some(self.footprint.clone()),
+            "mpn" => field(self.properties, MPN_FIELDS),
+            "manufacturer" => field(self.properties, MANUFACTURER_FIELDS),
+            _ => field(self.properties, &[&normalise(name)]),
+        }
+    }
+}
+
+impl Bom {
+    /// BOM of the populated parts of `pcb`
+    pub fn from_pcb(pcb: &PcbFile) -> Self {
+        Self::with_options(pcb, &BomOptions::default())
+    }
+
+    /// BOM of `pcb` with explicit options
+    pub fn with_options(pcb: &PcbFile, options: &BomOptions) -> Self {
+        let parts = pcb
+            .footprints
+            .iter()
+            .filter(|f| !f.attributes.exclude_from_bom)
+            .map(|f| Part {
+                reference: f.property("Reference").unwrap_or_default().to_string(),
+                footprint: f.name.clone(),
+                properties: &f.properties,
+                dnp: f.attributes.dnp,
+            });
+        Self::group(parts, options)
+    }
+
+    /// BOM of the symbols placed in a schematic sheet (`.kicad_sch`)
+    ///
+    /// Symbols marked `(in_bom no)` and power symbols (references starting with
+    /// `#`) are left out, and the units of a multi-unit part count once. When a
+    /// symbol carries `(instances ...)` every instance path becomes a part, so a
+    /// sheet used several times contributes each of its annotated copies.
+    pub fn from_schematic(content: &str, options: &BomOptions) -> Result<Self> {
+        let root = parse_sexpr(content)?;
+        if !root.is("kicad_sch") {
+            return Err(KicadError::InvalidFormat("Expected a kicad_sch file".to_string()));
+        }
+
+        let mut symbols: Vec<(String, HashMap<String, String>, bool)> = Vec::new();
+        for symbol in root.children("symbol") {
+            if symbol.child_bool("in_bom") == Some(false) {
+                continue;
+            }
+            let properties: HashMap<String, String> = symbol
+                .children("property")
+                .filter_map(|p| Some((p.arg_str(0)?.to_string(), p.arg_str(1)?.to_string())))
+                .collect();
+            let dnp = symbol.child_bool("dnp").unwrap_or(false);
+            let instances: Vec<String> = symbol
+                .children("instances")
+                .flat_map(|i| i.children("project"))
+                .flat_map(|p| p.children("path"))
+                .filter_map(|p| p.child_str("reference").map(str::to_string))
+                .collect();
+            let references = if instances.is_empty() {
+                properties.get("Reference").cloned().into_iter().collect()
+            } else {
+                instances
+            };
+            for reference in references {
+                if !reference.starts_with('#') && !symbols.iter().any(|(r, _, _)| *r == reference) {
+                    symbols.push((reference, properties.clone(), dnp));
+                }
+            }
+        }
+
+        Ok(Self::from_symbols(&symbols, options))
+    }
+
+    /// BOM of the symbols of a whole design, each sheet instance contributing
+    /// the parts annotated for it
+    ///
+    /// Per-instance values and footprints (KiCad 6) replace the symbol's. With
+    /// [`BomOptions::variant`] set, an instance's overrides for that variant
+    /// apply as well: its DNP and `in_bom` flags and its field values, so the
+    /// variant's values also decide the grouping. Power symbols and further
+    /// units of multi-unit parts are left out as in [`Bom::from_schematic`].
+    pub fn from_hierarchy(hierarchy: &Hierarchy, options: &BomOptions) -> Self {
+        let mut symbols: Vec<(String, HashMap<String, String>, bool)> = Vec::new();
+        for placed in hierarchy.symbols() {
+            let variant = options
+                .variant
+                .as_deref()
+                .and_then(|name| placed.instance?.variant(name));
+            let in_bom = variant.and_then(|v| v.in_bom).unwrap_or(placed.symbol.in_bom);
+            let reference = placed.reference;
+            if !in_bom || reference.starts_with('#') || symbols.iter().any(|(r, _, _)| *r == reference) {
+                continue;
+            }
+            let mut properties = placed.properties;
+            if let Some(variant) = variant {
+                properties.extend(variant.fields.iter().map(|(k, v)| (k.clone(), v.clone())));
+            }
+            let dnp = variant.and_then(|v| v.dnp).unwrap_or(placed.symbol.dnp);
+            symbols.push((reference, properties, dnp));
+        }
+        Self::from_symbols(&symbols, options)
+    }
+
+    /// BOM of schematic parts given as reference, properties and DNP flag
+    fn from_symbols(symbols: &[(String, HashMap<String, String>, bool)], options: &BomOptions) -> Self {
+        let parts = symbols.iter().map(|(reference, properties, dnp)| Part {
+            reference: reference.clone(),
+            footprint: properties.get("Footprint").cloned().unwrap_or_default(),
+            properties,
+            dnp: *dnp,
+        });
+        Self::group(parts, options)
+    }
+
+    fn group<'a>(parts: impl Iterator<Item = Part<'a>>, options: &BomOptions) -> Self {
+        let mut groups: BTreeMap<(bool, Vec<Option<String>>), BomLine> = BTreeMap::new();
+        for part in parts {
+            if part.dnp && !options.include_dnp {
+                continue;
+            }
+            let value = part.properties.get("Value").cloned().unwrap_or_default();
+            let mpn = part.field("mpn");
+            let key = if options.group_by.is_empty() {
+                vec![Some(value.clone()), Some(part.footprint.clone()), mpn.clone()]
+            } else {
+                options.group_by.iter().map(|name| part.field(name)).collect()
+            };
+            let line = groups.entry((part.dnp, key)).or_insert_with(|| BomLine {
+                references: Vec::new(),
+                quantity: 0,
+                value,
+                footprint: part.footprint.clone(),
+                mpn,
+                manufacturer: None,
+                dnp: part.dnp,
+                fields: BTreeMap::new(),
+            });
+            line.quantity += 1;
+            if line.manufacturer.is_none() {
+                line.manufacturer = part.field("manufacturer");
+            }
+            for column in &options.columns {
+                if !line.fields.contains_key(column) {
+                    if let Some(value) = part.field(column).filter(|v| !v.is_empty()) {
+                        line.fields.insert(column.clone(), value);
+                    }
+                }
+            }
+            line.references.push(part.reference);
+        }
+
+        let mut lines: Vec<BomLine> = groups.into_values().collect();
+        for line in &mut lines {
+            line.references.sort_by(|a, b| natural_order(a, b));
+        }
+        lines.sort_by(|a, b| {
+            a.dnp
+                .cmp(&b.dnp)
+                .then_with(|| natural_order(&a.references[0], &b.references[0]))
+        });
+        Bom { lines, columns: options.columns.clone() }
+    }
+
+    /// Number of parts to place
+    pub fn total_quantity(&self) -> usize {
+        self.lines.iter().filter(|l| !l.dnp).map(|l| l.quantity).sum()
+    }
+
+    /// Header and cells of each line, shared by the CSV and Markdown layouts
+    fn rows(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let mut header: Vec<String> = ["Reference", "Quantity", "Value", "Footprint", "MPN", "Manufacturer", "DNP"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        header.extend(self.columns.iter().cloned());
+        let rows = self
+            .lines
+            .iter()
+            .map(|line| {
+                let mut row = vec![
+                    line.references.join(", "),
+                    line.quantity.to_string(),
+                    line.value.clone(),
+                    line.footprint.clone(),
+                    line.mpn.clone().unwrap_or_default(),
+                    line.manufacturer.clone().unwrap_or_default(),
+                    if line.dnp { "DNP".to_string() } else { String::new() },
+                ];
+                row.extend(
+                    self.columns
+                        .iter()
+                        .map(|c| line.fields.get(c).cloned().unwrap_or_default()),
+                );
+                row
+            })
+            .collect();
+        (header, rows)
+    }
+
+    /// CSV with a header row; references of a line are joined with `", "`
+    pub fn to_csv(&self) -> String {
+        let (header, rows) = self.rows();
+        let mut csv = header.iter().map(|h| csv_field(h)).collect::<Vec<_>>().join(",");
+        csv.push('\n');
+        for row in rows {
+            csv.push_str(&row.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// GitHub-flavoured Markdown table
+    pub fn to_markdown(&self) -> String {
+        let (header, rows) = self.rows();
+        let cell = |s: &str| s.replace('|', "\\|");
+        let mut md = format!("| {} |\n", header.join(" | "));
+        md.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+        for row in rows {
+            md.push_str(&format!(
+                "| {} |\n",
+                row.iter().map(|c| cell(c)).collect::<Vec<_>>().join(" | ")
+            ));
+        }
+        md
+    }
+
+    /// Pretty-printed JSON of the BOM lines
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.lines)?)
+    }
+}
+
+/// Lower-case property name without spaces, `_`, `-` and `.`
+fn normalise(name: &str) -> String {
+    name.chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-' | '.'))
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// First non-empty property matching `names`, in the order the names are listed
+fn field(properties: &HashMap<String, String>, names: &[&str]) -> Option<String> {
+    names.iter().find_map(|wanted| {
+        properties
+            .iter()
+            .find(|(name, value)| normalise(name) == *wanted && !value.is_empty())
+            .map(|(_, value)| value.clone())
+    })
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Compare reference designators by prefix, then numerically (`R2` < `R10`)
+pub(crate) fn natural_order(a: &str, b: &str) -> Ordering {
+    let split = |r: &str| {
+        let digits = r.len() - r.trim_start_matches(|c: char| !c.is_ascii_digit()).len();
+        let (prefix, rest) = r.split_at(digits);
+        let number: Option<u64> = rest.parse().ok();
+        (prefix.to_string(), number, rest.to_string())
+    };
+    split(a).cmp(&split(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 0)
+    (property "Reference" "R10") (property "Value" "10k") (property "MPN" "RC0603FR-0710KL")
+    (property "Manufacturer" "Yageo"))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 2 0)
+    (property "Reference" "R2") (property "Value" "10k") (property "MPN" "RC0603FR-0710KL"))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 4 0)
+    (property "Reference" "R3") (property "Value" "10k") (property "Manufacturer Part Number" "ERJ-3EKF1002V"))
+  (footprint "Capacitor_SMD:C_0603" (layer "F.Cu") (at 6 0)
+    (property "Reference" "C1") (property "Value" "100n, 50V") (attr smd dnp))
+  (footprint "Logo" (layer "F.Cu") (at 8 0)
+    (property "Reference" "G1") (property "Value" "LOGO") (attr board_only exclude_from_pos_files exclude_from_bom))
+)"#;
+
+    #[test]
+    fn test_grouping() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let bom = Bom::from_pcb(&pcb);
+
+        assert_eq!(bom.lines.len(), 2);
+        assert_eq!(bom.lines[0].references, ["R2", "R10"]);
+        assert_eq!(bom.lines[0].mpn.as_deref(), Some("RC0603FR-0710KL"));
+        assert_eq!(bom.lines[0].manufacturer.as_deref(), Some("Yageo"));
+        assert_eq!(bom.lines[1].references, ["R3"]);
+        assert_eq!(bom.lines[1].mpn.as_deref(), Some("ERJ-3EKF1002V"));
+        assert_eq!(bom.total_quantity(), 3);
+
+        let with_dnp = Bom::with_options(&pcb, &BomOptions { include_dnp: true, ..Default::default() });
+        assert_eq!(with_dnp.lines.len(), 3);
+        assert!(with_dnp.lines[2].dnp);
+        assert_eq!(with_dnp.total_quantity(), 3);
+    }
+
+    #[test]
+    fn test_csv() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let csv = Bom::with_options(&pcb, &BomOptions { include_dnp: true, ..Default::default() }).to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(rows[0], "Reference,Quantity,Value,Footprint,MPN,Manufacturer,DNP");
+        assert_eq!(rows[1], "\"R2, R10\",2,10k,Resistor_SMD:R_0603,RC0603FR-0710KL,Yageo,");
+        assert_eq!(rows[3], "C1,1,\"100n, 50V\",Capacitor_SMD:C_0603,,,DNP");
+    }
+
+    #[test]
+    fn test_grouping_fields_and_columns() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let options = BomOptions {
+            group_by: vec!["value".to_string(), "Footprint".to_string()],
+            columns: vec!["Manufacturer Part Number".to_string(), "Manufacturer".to_string()],
+            ..Default::default()
+        };
+        let bom = Bom::with_options(&pcb, &options);
+        assert_eq!(bom.lines.len(), 1);
+        assert_eq!(bom.lines[0].references, ["R2", "R3", "R10"]);
+        assert_eq!(bom.lines[0].fields["Manufacturer"], "Yageo");
+
+        let csv = bom.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows[0],
+            "Reference,Quantity,Value,Footprint,MPN,Manufacturer,DNP,Manufacturer Part Number,Manufacturer"
+        );
+        assert_eq!(
+            rows[1],
+            "\"R2, R3, R10\",3,10k,Resistor_SMD:R_0603,RC0603FR-0710KL,Yageo,,ERJ-3EKF1002V,Yageo"
+        );
+
+        let md = bom.to_markdown();
+        let rows: Vec<&str> = md.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].starts_with("|---|---|"));
+        assert!(rows[2].starts_with("| R2, R3, R10 | 3 | 10k |"));
+    }
+
+    #[test]
+    fn test_from_schematic() {
+        let schematic = r##"(kicad_sch (version 20231120)
+  (lib_symbols (symbol "Device:R" (property "Reference" "R")))
+  (symbol (lib_id "Device:R") (at 10 10 0) (unit 1) (in_bom yes) (on_board yes) (dnp no)
+    (property "Reference" "R1") (property "Value" "10k") (property "Footprint" "Resistor_SMD:R_0603")
+    (instances (project "demo" (path "/a" (reference "R1") (unit 1)) (path "/b" (reference "R7") (unit 1)))))
+  (symbol (lib_id "Amplifier:LM358") (at 20 10 0) (unit 1) (in_bom yes) (on_board yes) (dnp no)
+    (property "Reference" "U1") (property "Value" "LM358") (property "Footprint" "Package_SO:SOIC-8"))
+  (symbol (lib_id "Amplifier:LM358") (at 20 30 0) (unit 2) (in_bom yes) (on_board yes) (dnp no)
+    (property "Reference" "U1") (property "Value" "LM358") (property "Footprint" "Package_SO:SOIC-8"))
+  (symbol (lib_id "Device:C") (at 30 10 0) (unit 1) (in_bom yes) (on_board yes) (dnp yes)
+    (property "Reference" "C1") (property "Value" "1u") (property "Footprint" "Capacitor_SMD:C_0603"))
+  (symbol (lib_id "Mechanical:MountingHole") (at 40 10 0) (unit 1) (in_bom no) (on_board yes)
+    (property "Reference" "H1") (property "Value" "MountingHole"))
+  (symbol (lib_id "power:GND") (at 50 10 0) (unit 1) (in_bom yes) (on_board yes)
+    (property "Reference" "#PWR01") (property "Value" "GND"))
+)"##;
+        let bom = Bom::from_schematic(schematic, &BomOptions::default()).unwrap();
+        assert_eq!(bom.lines.len(), 2);
+        assert_eq!(bom.lines[0].references, ["R1", "R7"]);
+        assert_eq!(bom.lines[0].footprint, "Resistor_SMD:R_0603");
+        assert_eq!(bom.lines[1].references, ["U1"]);
+        assert_eq!(bom.total_quantity(), 3);
+
+        let options = BomOptions { include_dnp: true, ..Default::default() };
+        let bom = Bom::from_schematic(schematic, &options).unwrap();
+        assert_eq!(bom.lines[2].references, ["C1"]);
+        assert!(bom.lines[2].dnp);
+
+        assert!(Bom::from_schematic("(kicad_pcb)", &options).is_err());
+    }
+
+    #[test]
+    fn test_from_hierarchy_with_variant() {
+        let root = r#"(kicad_sch (uuid "root")
+  (sheet (at 0 0) (size 20 10) (uuid "ch1") (property "Sheetname" "CH1") (property "Sheetfile" "channel.kicad_sch"))
+  (sheet (at 0 20) (size 20 10) (uuid "ch2") (property "Sheetname" "CH2") (property "Sheetfile" "channel.kicad_sch")))"#;
+        let channel = r##"(kicad_sch (uuid "channel")
+  (symbol (lib_id "Device:R") (at 10 10 0) (unit 1) (in_bom yes) (dnp no) (uuid "r")
+    (property "Reference" "R?") (property "Value" "1k") (property "Footprint" "Resistor_SMD:R_0603")
+    (instances (project "demo"
+      (path "/root/ch1" (reference "R1") (unit 1))
+      (path "/root/ch2" (reference "R2") (unit 1)
+        (variant (name "Lite") (dnp yes))
+        (variant (name "HiGain") (field (name "Value") (value "4k7")))))))
+  (symbol (lib_id "power:GND") (at 10 20 0) (unit 1) (uuid "g") (property "Reference" "#PWR?")
+    (instances (project "demo" (path "/root/ch1" (reference "#PWR01") (unit 1)) (path "/root/ch2" (reference "#PWR02") (unit 1))))))"##;
+        let hierarchy = Hierarchy::build("demo.kicad_sch", root, |_| Ok(channel.to_string())).unwrap();
+
+        let bom = Bom::from_hierarchy(&hierarchy, &BomOptions::default());
+        assert_eq!(bom.lines.len(), 1);
+        assert_eq!(bom.lines[0].references, ["R1", "R2"]);
+
+        let lite = BomOptions { variant: Some("Lite".to_string()), include_dnp: true, ..Default::default() };
+        let bom = Bom::from_hierarchy(&hierarchy, &lite);
+        assert_eq!(bom.total_quantity(), 1);
+        assert_eq!(bom.lines[1].references, ["R2"]);
+        assert!(bom.lines[1].dnp);
+
+        let high_gain = BomOptions { variant: Some("HiGain".to_string()), ..Default::default() };
+        let bom = Bom::from_hierarchy(&hierarchy, &high_gain);
+        let values: Vec<(&str, &[String])> =
+            bom.lines.iter().map(|l| (l.value.as_str(), l.references.as_slice())).collect();
+        assert_eq!(values, [("1k", &["R1".to_string()][..]), ("4k7", &["R2".to_string()][..])]);
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let mut refs = vec!["U1", "R10", "R2", "C1", "R1"];
+        refs.sort_by(|a, b| natural_order(a, b));
+        assert_eq!(refs, ["C1", "R1", "R2", "R10", "U1"]);
+    }
+}
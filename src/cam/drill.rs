@@ -0,0 +1,537 @@
+//! Excellon drill files
+//!
+//! Splits the holes of a board the way KiCad's drill file dialog does with
+//! *PTH and NPTH in separate files*: one file of plated through holes, one of
+//! non-plated holes, and one per layer pair of blind, buried and micro vias.
+//! Oval drills are written as routed slots (`G85`). Files are metric, decimal,
+//! absolute, with Y pointing up and X2 attributes in comments.
+//!
+//! [`drill_pairs`] summarises the via spans behind that split, with the via
+//! count, drill sizes and stackup depth of each, as a fabricator quotes them.
+//!
+//! ```rust
+//! use kiparse::{cam::drill::drill_files, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+//!   (via (at 10 5) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu"))
+//! )"#)?;
+//! let files = drill_files(&pcb);
+//!
+//! assert_eq!(files[0].file_name("board"), "board-PTH.drl");
+//! let excellon = files[0].to_excellon();
+//! assert!(excellon.contains("T1C0.300\n"));
+//! assert!(excellon.contains("X10.0Y-5.0\n"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::pcb::copper::copper_layers;
+use crate::pcb::geometry::to_board;
+use crate::pcb::types::{PcbFile, Point, Via, ViaType};
+use crate::units::Length;
+
+/// What a hole is drilled for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HoleFunction {
+    Via,
+    Component,
+}
+
+/// One drilled hole or routed slot
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrillHole {
+    pub position: Point,
+    pub diameter: f64,
+    /// Far end of a slot routed from `position`
+    pub slot_end: Option<Point>,
+    pub function: HoleFunction,
+}
+
+/// Kind of drill file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrillKind {
+    /// Plated holes through all copper layers
+    PlatedThrough,
+    NonPlated,
+    /// Plated holes between two copper layers, not both outer ones
+    LayerPair,
+}
+
+/// How far a via span reaches through the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SpanKind {
+    /// From the first copper layer to the last
+    Through,
+    /// From an outer copper layer to an inner one
+    Blind,
+    /// Between inner copper layers only
+    Buried,
+}
+
+impl SpanKind {
+    /// Kind of the 1-based copper layer span `(from, to)` on a board with
+    /// `copper_layer_count` copper layers
+    pub fn of(span: (usize, usize), copper_layer_count: usize) -> Self {
+        match span {
+            (1, to) if to == copper_layer_count => SpanKind::Through,
+            (1, _) => SpanKind::Blind,
+            (_, to) if to == copper_layer_count => SpanKind::Blind,
+            _ => SpanKind::Buried,
+        }
+    }
+
+    /// Name used in reports and X2 attributes, e.g. `Blind`
+    pub fn name(self) -> &'static str {
+        match self {
+            SpanKind::Through => "Through",
+            SpanKind::Blind => "Blind",
+            SpanKind::Buried => "Buried",
+        }
+    }
+}
+
+/// Vias drilled between the same two copper layers
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrillPair {
+    /// Copper layer names the vias start and end on
+    pub from: String,
+    pub to: String,
+    /// 1-based copper layer numbers of `from` and `to`
+    pub span: (usize, usize),
+    pub kind: SpanKind,
+    pub via_count: usize,
+    /// Laser-drilled microvias among them
+    pub microvias: usize,
+    /// Drill diameters in mm, rounded to the µm, with the number of vias of
+    /// each, smallest first
+    pub sizes: Vec<(Length, usize)>,
+    /// Stackup thickness from `from` to `to`, both copper layers included,
+    /// when the board has a stackup (or, for through vias, a board thickness)
+    pub depth: Option<Length>,
+}
+
+impl DrillPair {
+    /// Span as fabricators write it, e.g. `1-2` or `1-4`
+    pub fn label(&self) -> String {
+        format!("{}-{}", self.span.0, self.span.1)
+    }
+
+    /// Depth over the smallest drill, the figure that limits plating
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        let smallest = self.sizes.first()?.0;
+        Some(self.depth? / smallest).filter(|_| smallest > Length::ZERO)
+    }
+}
+
+/// Via spans of a board, through vias first and then blind and buried pairs
+/// from the top down, as [`drill_files`] splits them
+///
+/// Component holes are left out: plated ones always go through the board.
+pub fn drill_pairs(pcb: &PcbFile) -> Vec<DrillPair> {
+    let copper = copper_layers(pcb);
+    let count = copper.len().max(2);
+
+    let mut spans: BTreeMap<(bool, (usize, usize)), Vec<&Via>> = BTreeMap::new();
+    for via in &pcb.vias {
+        let span = via_span(via, &copper);
+        spans.entry((span != (1, count), span)).or_default().push(via);
+    }
+
+    spans
+        .into_iter()
+        .map(|((_, span), vias)| {
+            let mut sizes: Vec<(Length, usize)> = Vec::new();
+            for via in &vias {
+                let drill = Length::mm(round_um(via.drill.as_mm()));
+                match sizes.iter_mut().find(|(d, _)| *d == drill) {
+                    Some((_, n)) => *n += 1,
+                    None => sizes.push((drill, 1)),
+                }
+            }
+            sizes.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let from = layer_name(&copper, span.0);
+            let to = layer_name(&copper, span.1);
+            DrillPair {
+                depth: pcb.copper_distance(&from, &to),
+                from,
+                to,
+                span,
+                kind: SpanKind::of(span, count),
+                via_count: vias.len(),
+                microvias: vias.iter().filter(|v| v.via_type == ViaType::Micro).count(),
+                sizes,
+            }
+        })
+        .collect()
+}
+
+/// 1-based copper layer numbers a via starts and ends on; through the board
+/// when its layers are not copper layers of the board
+fn via_span(via: &Via, copper: &[String]) -> (usize, usize) {
+    let number = |layer: &str| copper.iter().position(|l| l == layer).map(|i| i + 1);
+    match (
+        via.layers.first().and_then(|l| number(l)),
+        via.layers.last().and_then(|l| number(l)),
+    ) {
+        (Some(a), Some(b)) => (a.min(b), a.max(b)),
+        _ => (1, copper.len().max(2)),
+    }
+}
+
+/// Name of the 1-based copper layer `n`
+fn layer_name(copper: &[String], n: usize) -> String {
+    copper
+        .get(n - 1)
+        .cloned()
+        .unwrap_or_else(|| if n == 1 { "F.Cu" } else { "B.Cu" }.to_string())
+}
+
+/// Holes of one drill file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrillFile {
+    pub kind: DrillKind,
+    /// Copper layer names the holes start and end on
+    pub from: String,
+    pub to: String,
+    /// 1-based copper layer numbers of `from` and `to`
+    pub span: (usize, usize),
+    pub copper_layer_count: usize,
+    pub holes: Vec<DrillHole>,
+}
+
+/// Drill files of a board: plated through holes first, then non-plated holes,
+/// then layer pairs from the top down. Files without holes are left out, except
+/// the plated one.
+pub fn drill_files(pcb: &PcbFile) -> Vec<DrillFile> {
+    let copper = copper_layers(pcb);
+    let count = copper.len().max(2);
+
+    let mut plated = Vec::new();
+    let mut non_plated = Vec::new();
+    let mut pairs: BTreeMap<(usize, usize), Vec<DrillHole>> = BTreeMap::new();
+
+    for footprint in &pcb.footprints {
+        for pad in &footprint.pads {
+            let Some(drill) = pad.drill else { continue };
+            if drill <= Length::ZERO {
+                continue;
+            }
+            let position = footprint.pad_position(pad);
+            let (diameter, slot_end) = match &pad.drill_oval {
+                Some(oval) if (oval.x - oval.y).abs().as_mm() > 1e-9 => {
+                    let diameter = oval.x.min(oval.y);
+                    let half = (oval.x.max(oval.y) - diameter) / 2.0;
+                    let (start, end) = if oval.x > oval.y {
+                        (Point { x: -half, y: Length::ZERO }, Point { x: half, y: Length::ZERO })
+                    } else {
+                        (Point { x: Length::ZERO, y: -half }, Point { x: Length::ZERO, y: half })
+                    };
+                    let start = to_board(&start, &position, pad.rotation);
+                    let end = to_board(&end, &position, pad.rotation);
+                    (diameter.as_mm(), Some((start, end)))
+                }
+                _ => (drill.as_mm(), None),
+            };
+            let hole = match slot_end {
+                Some((start, end)) => DrillHole {
+                    position: start,
+                    diameter,
+                    slot_end: Some(end),
+                    function: HoleFunction::Component,
+                },
+                None => DrillHole {
+                    position,
+                    diameter,
+                    slot_end: None,
+                    function: HoleFunction::Component,
+                },
+            };
+            if pad.pad_type == "np_thru_hole" {
+                non_plated.push(hole);
+            } else {
+                plated.push(hole);
+            }
+        }
+    }
+
+    for via in &pcb.vias {
+        let hole = DrillHole {
+            position: via.position.clone(),
+            diameter: via.drill.as_mm(),
+            slot_end: None,
+            function: HoleFunction::Via,
+        };
+        let span = via_span(via, &copper);
+        if span == (1, count) {
+            plated.push(hole);
+        } else {
+            pairs.entry(span).or_default().push(hole);
+        }
+    }
+
+    let file = |kind, span: (usize, usize), holes| DrillFile {
+        kind,
+        from: layer_name(&copper, span.0),
+        to: layer_name(&copper, span.1),
+        span,
+        copper_layer_count: count,
+        holes,
+    };
+
+    let mut files = vec![file(DrillKind::PlatedThrough, (1, count), plated)];
+    if !non_plated.is_empty() {
+        files.push(file(DrillKind::NonPlated, (1, count), non_plated));
+    }
+    for (span, holes) in pairs {
+        files.push(file(DrillKind::LayerPair, span, holes));
+    }
+    files
+}
+
+impl DrillFile {
+    /// KiCad file name: `<board>-PTH.drl`, `<board>-NPTH.drl` or `<board>-front-in1.drl`
+    pub fn file_name(&self, board: &str) -> String {
+        let layer = |n: usize| match n {
+            1 => "front".to_string(),
+            n if n == self.copper_layer_count => "back".to_string(),
+            n => format!("in{}", n - 1),
+        };
+        match self.kind {
+            DrillKind::PlatedThrough => format!("{}-PTH.drl", board),
+            DrillKind::NonPlated => format!("{}-NPTH.drl", board),
+            DrillKind::LayerPair => format!(
+                "{}-{}-{}.drl",
+                board,
+                layer(self.span.0),
+                layer(self.span.1)
+            ),
+        }
+    }
+
+    /// X2 `.FileFunction` value, e.g. `Plated,1,4,PTH` or `Plated,1,2,Blind`
+    pub fn file_function(&self) -> String {
+        let (from, to) = self.span;
+        match self.kind {
+            DrillKind::PlatedThrough => format!("Plated,{},{},PTH", from, to),
+            DrillKind::NonPlated => format!("NonPlated,{},{},NPTH", from, to),
+            DrillKind::LayerPair => {
+                format!("Plated,{},{},{}", from, to, SpanKind::of(self.span, self.copper_layer_count).name())
+            }
+        }
+    }
+
+    /// Tools as `(diameter, function)`, numbered from T1 in ascending size
+    pub fn tools(&self) -> Vec<(f64, HoleFunction)> {
+        let mut tools: Vec<(f64, HoleFunction)> = Vec::new();
+        for hole in &self.holes {
+            let tool = (round_um(hole.diameter), hole.function);
+            if !tools.contains(&tool) {
+                tools.push(tool);
+            }
+        }
+        tools.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        tools
+    }
+
+    /// Excellon file text
+    pub fn to_excellon(&self) -> String {
+        let tools = self.tools();
+        let (plating, hole_type) = match self.kind {
+            DrillKind::PlatedThrough => ("Plated", "PTH"),
+            DrillKind::NonPlated => ("NonPlated", "NPTH"),
+            DrillKind::LayerPair => ("Plated", SpanKind::of(self.span, self.copper_layer_count).name()),
+        };
+
+        let mut out = String::from("M48\n");
+        out.push_str(&format!("; DRILL file {{kiparse {}}}\n", crate::VERSION));
+        out.push_str("; FORMAT={-:-/ absolute / metric / decimal}\n");
+        out.push_str(&format!(
+            "; #@! TF.GenerationSoftware,KiParse,kiparse,{}\n",
+            crate::VERSION
+        ));
+        out.push_str(&format!("; #@! TF.FileFunction,{}\n", self.file_function()));
+        out.push_str("FMAT,2\nMETRIC\n");
+        for (i, (diameter, function)) in tools.iter().enumerate() {
+            let function = match function {
+                HoleFunction::Via => "ViaDrill",
+                HoleFunction::Component => "ComponentDrill",
+            };
+            out.push_str(&format!(
+                "; #@! TA.AperFunction,{},{},{}\n",
+                plating, hole_type, function
+            ));
+            out.push_str(&format!("T{}C{:.3}\n", i + 1, diameter));
+        }
+        out.push_str("%\nG90\nG05\n");
+
+        for (i, tool) in tools.iter().enumerate() {
+            out.push_str(&format!("T{}\n", i + 1));
+            for hole in self
+                .holes
+                .iter()
+                .filter(|h| (round_um(h.diameter), h.function) == *tool)
+            {
+                match &hole.slot_end {
+                    Some(end) => out.push_str(&format!(
+                        "{}G85{}\n",
+                        coordinate(&hole.position),
+                        coordinate(end)
+                    )),
+                    None => out.push_str(&format!("{}\n", coordinate(&hole.position))),
+                }
+            }
+        }
+        out.push_str("M30\n");
+        out
+    }
+}
+
+fn round_um(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Excellon coordinate in mm (Y up), always with a decimal point
+fn coordinate(p: &Point) -> String {
+    let number = |v: f64| {
+        let s = format!("{:.4}", v);
+        let s = s.trim_end_matches('0');
+        let s = if s.ends_with('.') {
+            format!("{}0", s)
+        } else {
+            s.to_string()
+        };
+        if s == "-0.0" {
+            "0.0".to_string()
+        } else {
+            s
+        }
+    };
+    format!("X{}Y{}", number(p.x.as_mm()), number(-p.y.as_mm()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (1 "In1.Cu" signal) (2 "In2.Cu" signal) (31 "B.Cu" signal))
+  (footprint "J" (layer "F.Cu") (at 20 10 90) (property "Reference" "J1")
+    (pad "1" thru_hole circle (at 0 0) (size 1.7 1.7) (drill 1) (layers "*.Cu" "*.Mask"))
+    (pad "2" thru_hole oval (at 0 2.54 90) (size 1.2 2.2) (drill oval 0.6 1.6) (layers "*.Cu" "*.Mask"))
+    (pad "" np_thru_hole circle (at 5 0 90) (size 3 3) (drill 3) (layers "*.Cu" "*.Mask"))
+    (pad "3" smd rect (at 0 6 90) (size 1 1) (layers "F.Cu")))
+  (via (at 1.5 2.25) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu"))
+  (via (at 3 3) (size 0.6) (drill 1) (layers "F.Cu" "B.Cu"))
+  (via blind (at 4 4) (size 0.5) (drill 0.2) (layers "F.Cu" "In1.Cu"))
+  (via blind (at 5 5) (size 0.5) (drill 0.2) (layers "In2.Cu" "In1.Cu"))
+)"#;
+
+    #[test]
+    fn test_drill_files() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let files = drill_files(&pcb);
+        let names: Vec<_> = files.iter().map(|f| f.file_name("board")).collect();
+        assert_eq!(
+            names,
+            [
+                "board-PTH.drl",
+                "board-NPTH.drl",
+                "board-front-in1.drl",
+                "board-in1-in2.drl"
+            ]
+        );
+
+        let functions: Vec<_> = files.iter().map(DrillFile::file_function).collect();
+        assert_eq!(
+            functions,
+            [
+                "Plated,1,4,PTH",
+                "NonPlated,1,4,NPTH",
+                "Plated,1,2,Blind",
+                "Plated,2,3,Buried"
+            ]
+        );
+
+        let pth = &files[0];
+        assert_eq!(pth.holes.len(), 4);
+        // Same diameter, different function: separate tools
+        assert_eq!(
+            pth.tools(),
+            [
+                (0.3, HoleFunction::Via),
+                (0.6, HoleFunction::Component),
+                (1.0, HoleFunction::Via),
+                (1.0, HoleFunction::Component)
+            ]
+        );
+        assert_eq!(files[3].from, "In1.Cu");
+    }
+
+    #[test]
+    fn test_drill_pairs() {
+        let mut pcb = parse_pcb(BOARD).unwrap();
+        let pairs = drill_pairs(&pcb);
+        let spans: Vec<(String, SpanKind, usize)> = pairs.iter().map(|p| (p.label(), p.kind, p.via_count)).collect();
+        assert_eq!(
+            spans,
+            [
+                ("1-4".to_string(), SpanKind::Through, 2),
+                ("1-2".to_string(), SpanKind::Blind, 1),
+                ("2-3".to_string(), SpanKind::Buried, 1),
+            ]
+        );
+        assert_eq!(pairs[0].sizes, [(Length::mm(0.3), 1), (Length::mm(1.0), 1)]);
+        assert_eq!(pairs[1].depth, None);
+
+        pcb.board_thickness = Some(Length::mm(1.6));
+        let through = &drill_pairs(&pcb)[0];
+        assert_eq!(through.depth, Some(Length::mm(1.6)));
+        assert!((through.aspect_ratio().unwrap() - 1.6 / 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drill_pair_depth_through_stackup() {
+        let stackup = r#"(setup (stackup
+    (layer "F.Mask" (type "Top Solder Mask") (thickness 0.01))
+    (layer "F.Cu" (type "copper") (thickness 0.035))
+    (layer "dielectric 1" (type "prepreg") (thickness 0.1))
+    (layer "In1.Cu" (type "copper") (thickness 0.035))
+    (layer "dielectric 2" (type "core") (thickness 1.2))
+    (layer "In2.Cu" (type "copper") (thickness 0.035))
+    (layer "dielectric 3" (type "prepreg") (thickness 0.1))
+    (layer "B.Cu" (type "copper") (thickness 0.035))
+    (layer "B.Mask" (type "Bottom Solder Mask") (thickness 0.01))))
+  (via "#;
+        let pcb = parse_pcb(&BOARD.replacen("(via ", stackup, 1)).unwrap();
+        let depths: Vec<f64> = drill_pairs(&pcb).iter().map(|p| p.depth.unwrap().as_mm()).collect();
+
+        let expected = [1.54, 0.17, 1.27];
+        assert!(depths.iter().zip(expected).all(|(d, e)| (d - e).abs() < 1e-9), "{:?}", depths);
+    }
+
+    #[test]
+    fn test_excellon() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let excellon = drill_files(&pcb)[0].to_excellon();
+        let lines: Vec<&str> = excellon.lines().collect();
+
+        assert_eq!(lines[0], "M48");
+        assert!(lines.contains(&"; #@! TF.FileFunction,Plated,1,4,PTH"));
+        assert!(excellon.contains("; #@! TA.AperFunction,Plated,PTH,ViaDrill\nT1C0.300\n"));
+        assert!(excellon.contains("; #@! TA.AperFunction,Plated,PTH,ComponentDrill\nT4C1.000\n"));
+        assert!(excellon.contains("%\nG90\nG05\nT1\nX1.5Y-2.25\nT2\n"));
+        // Pad 2 sits at (22.54, 10); its slot runs along the pad's local y axis, turned to board x
+        assert!(excellon.contains("X22.04Y-10.0G85X23.04Y-10.0\n"));
+        assert!(excellon.contains("T4\nX20.0Y-10.0\nM30\n"));
+    }
+}
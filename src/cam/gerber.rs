@@ -0,0 +1,410 @@
+//! Gerber X2 plotting
+//!
+//! Plots one board layer to an RS-274X Gerber file with X2 attributes
+//! (`.FileFunction`, `.FilePolarity` and `.AperFunction`). Supported are copper,
+//! solder mask, paste, silkscreen and Edge.Cuts layers:
+//!
+//! - tracks and vias are drawn with round apertures, track arcs as true arcs
+//! - round, rectangular and oval pads at right angles are flashed; other pads
+//!   (rotated, rounded rectangles, custom) are drawn as regions
+//! - on mask and paste layers pads are grown or shrunk by their solder mask and
+//!   paste margins, see [`PcbFile::pad_margins`]
+//! - zone fills become regions
+//! - lines, arcs, circles, rectangles and polygons of the layer are stroked, and
+//!   filled shapes become regions; text is not plotted
+//!
+//! Coordinates are in mm with six decimals and the Y axis pointing up.
+//!
+//! ```rust
+//! use kiparse::{cam::gerber::plot_layer, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "A")
+//!   (segment (start 0 0) (end 10 0) (width 0.25) (layer "F.Cu") (net 1))
+//! )"#)?;
+//! let gerber = plot_layer(&pcb, "F.Cu")?;
+//!
+//! assert!(gerber.contains("%TF.FileFunction,Copper,L1,Top*%"));
+//! assert!(gerber.contains("%ADD10C,0.250000*%"));
+//! assert!(gerber.contains("X10000000Y0D01*"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::error::{KicadError, Result};
+use crate::pcb::copper::{copper_layers, expand_layers, pad_shape, Shape};
+use crate::pcb::geometry::{arc_from_points, outline_piece, to_board, OutlinePiece};
+use crate::pcb::types::{Footprint, Graphic, Pad, PcbFile, Point};
+use crate::units::Length;
+
+/// Gerber coordinate units per mm (format 4.6)
+const SCALE: f64 = 1e6;
+
+/// Layers [`plot_layer`] accepts for a board, copper first
+pub fn plottable_layers(pcb: &PcbFile) -> Vec<String> {
+    let mut layers = copper_layers(pcb);
+    for layer in ["F.Mask", "B.Mask", "F.Paste", "B.Paste", "F.SilkS", "B.SilkS", "Edge.Cuts"] {
+        layers.push(layer.to_string());
+    }
+    layers
+}
+
+/// KiCad file name for a plotted layer, e.g. `<board>-F_Cu.gbr`
+pub fn file_name(board: &str, layer: &str) -> String {
+    format!("{}-{}.gbr", board, layer.replace('.', "_"))
+}
+
+/// Plot one layer of `pcb` to a Gerber X2 file
+pub fn plot_layer(pcb: &PcbFile, layer: &str) -> Result<String> {
+    let copper = copper_layers(pcb);
+    let (function, polarity) = file_function(layer, &copper)
+        .ok_or_else(|| KicadError::InvalidFormat(format!("Cannot plot layer {}", layer)))?;
+    let is_copper = layer.ends_with(".Cu");
+    let mut plotter = Plotter::default();
+
+    if is_copper {
+        for zone in &pcb.zones {
+            for fill in zone.filled_polygons.iter().filter(|f| f.layer == layer) {
+                plotter.region(&fill.points, Some("Conductor"));
+            }
+        }
+        for track in pcb.tracks.iter().filter(|t| t.layer == layer) {
+            let d = plotter.circle(track.width, Some("Conductor"));
+            plotter.stroke(d, &[track.start.clone(), track.end.clone()]);
+        }
+        for arc in pcb.arcs.iter().filter(|a| a.layer == layer) {
+            let d = plotter.circle(arc.width, Some("Conductor"));
+            plotter.arc(d, &arc.start, &arc.mid, &arc.end);
+        }
+        for via in &pcb.vias {
+            if expand_layers(&via.layers, &copper, true).iter().any(|l| l == layer) {
+                let d = plotter.circle(via.size, Some("ViaPad"));
+                plotter.flash(d, &via.position);
+            }
+        }
+    }
+
+    for footprint in &pcb.footprints {
+        for pad in footprint.pads.iter().filter(|p| pad_on_layer(p, layer, &copper)) {
+            let function = if !is_copper {
+                None
+            } else if pad.pad_type == "smd" {
+                Some("SMDPad,CuDef")
+            } else {
+                Some("ComponentPad")
+            };
+            // Mask openings and stencil apertures follow the pad's effective margins
+            let margins = pcb.pad_margins(footprint, pad);
+            let size = match layer.rsplit('.').next() {
+                Some("Mask") => margins.mask_size(&pad.size),
+                Some("Paste") => margins.paste_size(&pad.size),
+                _ => pad.size.clone(),
+            };
+            if size == pad.size {
+                plotter.pad(footprint, pad, function);
+            } else if size.x > Length::ZERO && size.y > Length::ZERO {
+                plotter.pad(footprint, &Pad { size, ..pad.clone() }, function);
+            }
+        }
+    }
+
+    let edge_function = (layer == "Edge.Cuts").then_some("Profile");
+    for graphic in pcb.graphics.iter().filter(|g| g.layer() == layer) {
+        plotter.graphic(graphic, |p| p.clone(), edge_function);
+    }
+    for footprint in &pcb.footprints {
+        for graphic in footprint.graphics.iter().filter(|g| g.layer() == layer) {
+            plotter.graphic(graphic, |p| to_board(p, &footprint.position, footprint.rotation), edge_function);
+        }
+    }
+
+    Ok(plotter.finish(&function, polarity))
+}
+
+/// X2 `.FileFunction` value and polarity of a layer
+fn file_function(layer: &str, copper: &[String]) -> Option<(String, &'static str)> {
+    if let Some(index) = copper.iter().position(|l| l == layer) {
+        let position = match layer {
+            "F.Cu" => "Top",
+            "B.Cu" => "Bot",
+            _ => "Inr",
+        };
+        return Some((format!("Copper,L{},{}", index + 1, position), "Positive"));
+    }
+    let function = match layer {
+        "F.Mask" => ("Soldermask,Top", "Negative"),
+        "B.Mask" => ("Soldermask,Bot", "Negative"),
+        "F.Paste" => ("Paste,Top", "Positive"),
+        "B.Paste" => ("Paste,Bot", "Positive"),
+        "F.SilkS" => ("Legend,Top", "Positive"),
+        "B.SilkS" => ("Legend,Bot", "Positive"),
+        "Edge.Cuts" => ("Profile,NP", "Positive"),
+        _ => return None,
+    };
+    Some((function.0.to_string(), function.1))
+}
+
+/// Whether a pad has copper, mask or paste on `layer`
+pub(crate) fn pad_on_layer(pad: &Pad, layer: &str, copper: &[String]) -> bool {
+    if layer.ends_with(".Cu") {
+        return pad.pad_type != "np_thru_hole" && expand_layers(&pad.layers, copper, false).iter().any(|l| l == layer);
+    }
+    let (side, kind) = layer.split_once('.').unwrap_or_default();
+    pad.layers.iter().any(|l| match l.split_once('.') {
+        Some(("*", k)) | Some(("F&B", k)) => k == kind,
+        Some((s, k)) => s == side && k == kind,
+        None => false,
+    })
+}
+
+/// Gerber coordinate of a board point (Y up)
+fn coordinate(p: &Point) -> String {
+    format!("X{}Y{}", (p.x.as_mm() * SCALE).round() as i64, (-p.y.as_mm() * SCALE).round() as i64)
+}
+
+/// Accumulates apertures and drawing commands for one layer
+#[derive(Default)]
+struct Plotter {
+    /// Aperture templates (`C,0.25`) and their `.AperFunction`, numbered from D10
+    apertures: Vec<(String, Option<&'static str>)>,
+    body: String,
+    current: Option<usize>,
+}
+
+impl Plotter {
+    /// D code of an aperture, defining it on first use
+    fn aperture(&mut self, template: String, function: Option<&'static str>) -> usize {
+        let key = (template, function);
+        let index = match self.apertures.iter().position(|a| *a == key) {
+            Some(index) => index,
+            None => {
+                self.apertures.push(key);
+                self.apertures.len() - 1
+            }
+        };
+        index + 10
+    }
+
+    fn circle(&mut self, diameter: Length, function: Option<&'static str>) -> usize {
+        self.aperture(format!("C,{:.6}", diameter.as_mm()), function)
+    }
+
+    fn select(&mut self, d: usize) {
+        if self.current != Some(d) {
+            self.body.push_str(&format!("D{}*\n", d));
+            self.current = Some(d);
+        }
+    }
+
+    fn flash(&mut self, d: usize, at: &Point) {
+        self.select(d);
+        self.body.push_str(&format!("{}D03*\n", coordinate(at)));
+    }
+
+    fn stroke(&mut self, d: usize, points: &[Point]) {
+        self.select(d);
+        for (i, p) in points.iter().enumerate() {
+            self.body.push_str(&format!("{}{}*\n", coordinate(p), if i == 0 { "D02" } else { "D01" }));
+        }
+    }
+
+    /// Circular arc from `start` through `mid` to `end`
+    fn arc(&mut self, d: usize, start: &Point, mid: &Point, end: &Point) {
+        let circle = match arc_from_points(start, mid, end) {
+            Some(circle) => circle,
+            None => return self.stroke(d, &[start.clone(), end.clone()]),
+        };
+        // Turning direction with Y up: counter-clockwise is G03
+        let (start_x, start_y) = (start.x.as_mm(), start.y.as_mm());
+        let cross = (mid.x.as_mm() - start_x) * (start_y - end.y.as_mm())
+            - (start_y - mid.y.as_mm()) * (end.x.as_mm() - start_x);
+        let direction = if cross > 0.0 { "G03" } else { "G02" };
+        self.select(d);
+        self.body.push_str(&format!(
+            "{}D02*\n{}*\n{}I{}J{}D01*\nG01*\n",
+            coordinate(start),
+            direction,
+            coordinate(end),
+            ((circle.center.x - start.x).as_mm() * SCALE).round() as i64,
+            ((start.y - circle.center.y).as_mm() * SCALE).round() as i64,
+        ));
+    }
+
+    fn region(&mut self, points: &[Point], function: Option<&'static str>) {
+        if points.len() < 3 {
+            return;
+        }
+        if let Some(function) = function {
+            self.body.push_str(&format!("%TA.AperFunction,{}*%\n", function));
+        }
+        self.body.push_str("G36*\n");
+        self.body.push_str(&format!("{}D02*\n", coordinate(&points[0])));
+        for p in points[1..].iter().chain(std::iter::once(&points[0])) {
+            self.body.push_str(&format!("{}D01*\n", coordinate(p)));
+        }
+        self.body.push_str("G37*\n");
+        if function.is_some() {
+            self.body.push_str("%TD*%\n");
+        }
+    }
+
+    fn pad(&mut self, footprint: &Footprint, pad: &Pad, function: Option<&'static str>) {
+        let center = footprint.pad_position(pad);
+        let quarter_turns = pad.rotation / 90.0;
+        let square = (quarter_turns - quarter_turns.round()).abs() < 1e-6;
+        let (w, h) = if square && (quarter_turns.round() as i64).rem_euclid(2) == 1 {
+            (pad.size.y, pad.size.x)
+        } else {
+            (pad.size.x, pad.size.y)
+        };
+        let template = match pad.shape.as_str() {
+            "circle" => Some(format!("C,{:.6}", w.as_mm())),
+            "rect" if square => Some(format!("R,{:.6}X{:.6}", w.as_mm(), h.as_mm())),
+            "oval" if square => Some(format!("O,{:.6}X{:.6}", w.as_mm(), h.as_mm())),
+            _ => None,
+        };
+        if let Some(template) = template {
+            let d = self.aperture(template, function);
+            return self.flash(d, &center);
+        }
+        match pad_shape(footprint, pad) {
+            Shape::Capsule { a, b, radius } => {
+                let d = self.circle(Length::mm(2.0 * radius), function);
+                self.stroke(d, &[a, b]);
+            }
+            Shape::Polygon(points) => self.region(&points, function),
+        }
+    }
+
+    /// Stroke or fill a drawing, mapping its points to board coordinates
+    fn graphic(&mut self, graphic: &Graphic, transform: impl Fn(&Point) -> Point, function: Option<&'static str>) {
+        let (width, filled) = match graphic {
+            Graphic::Line { width, .. } | Graphic::Arc { width, .. } | Graphic::Bezier { width, .. } => (*width, false),
+            Graphic::Circle { width, filled, .. }
+            | Graphic::Rectangle { width, filled, .. }
+            | Graphic::Polygon { width, filled, .. } => (*width, *filled),
+        };
+        let (points, closed) = match outline_piece(graphic) {
+            OutlinePiece::Open(points) => (points, false),
+            OutlinePiece::Closed(points) => (points, true),
+        };
+        let mut points: Vec<Point> = points.iter().map(&transform).collect();
+        if filled && closed {
+            self.region(&points, function);
+        }
+        if width > Length::ZERO && !points.is_empty() {
+            if closed {
+                points.push(points[0].clone());
+            }
+            let d = self.circle(width, function);
+            self.stroke(d, &points);
+        }
+    }
+
+    fn finish(self, file_function: &str, polarity: &str) -> String {
+        let mut out = format!(
+            "%TF.GenerationSoftware,KiParse,kiparse,{}*%\n%TF.FileFunction,{}*%\n%TF.FilePolarity,{}*%\n",
+            crate::VERSION,
+            file_function,
+            polarity,
+        );
+        out.push_str("%FSLAX46Y46*%\n%MOMM*%\n%LPD*%\nG01*\nG75*\n");
+        for (i, (template, function)) in self.apertures.iter().enumerate() {
+            match function {
+                Some(function) => out.push_str(&format!(
+                    "%TA.AperFunction,{}*%\n%ADD{}{}*%\n%TD*%\n",
+                    function,
+                    i + 10,
+                    template
+                )),
+                None => out.push_str(&format!("%ADD{}{}*%\n", i + 10, template)),
+            }
+        }
+        out.push_str(&self.body);
+        out.push_str("M02*\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "A") (net 2 "GND")
+  (gr_rect (start 0 0) (end 30 20) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+  (footprint "R" (layer "F.Cu") (at 10 10 90) (property "Reference" "R1")
+    (fp_line (start -1 -1) (end 1 -1) (stroke (width 0.12) (type solid)) (layer "F.SilkS"))
+    (pad "1" smd rect (at -1 0 90) (size 1 0.5) (layers "F.Cu" "F.Paste" "F.Mask") (net 1 "A"))
+    (pad "2" smd roundrect (at 1 0 90) (size 1 0.5) (layers "F.Cu" "F.Paste" "F.Mask") (roundrect_rratio 0.25) (net 2 "GND")))
+  (footprint "J" (layer "F.Cu") (at 20 10) (property "Reference" "J1")
+    (pad "1" thru_hole circle (at 0 0) (size 1.7 1.7) (drill 1) (layers "*.Cu" "*.Mask") (net 1 "A"))
+    (pad "" np_thru_hole circle (at 3 0) (size 2 2) (drill 2) (layers "*.Cu" "*.Mask")))
+  (segment (start 10 9) (end 20 10) (width 0.25) (layer "F.Cu") (net 1))
+  (arc (start 0 5) (mid 1 4) (end 2 5) (width 0.2) (layer "B.Cu") (net 1))
+  (via (at 5 5) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+  (zone (net 2) (net_name "GND") (layer "B.Cu")
+    (filled_polygon (layer "B.Cu") (pts (xy 0 10) (xy 10 10) (xy 10 20) (xy 0 20))))
+)"#;
+
+    #[test]
+    fn test_plot_copper() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let top = plot_layer(&pcb, "F.Cu").unwrap();
+
+        assert!(top.starts_with("%TF.GenerationSoftware,KiParse,kiparse,"));
+        assert!(top.contains("%TF.FileFunction,Copper,L1,Top*%\n%TF.FilePolarity,Positive*%"));
+        assert!(top.contains("%TA.AperFunction,Conductor*%\n%ADD10C,0.250000*%"));
+        assert!(top.contains("X10000000Y-9000000D02*\nX20000000Y-10000000D01*"));
+        assert!(top.contains("%TA.AperFunction,ViaPad*%\n%ADD11C,0.600000*%"));
+        // Pad rotated by a quarter turn swaps its flashed width and height
+        assert!(top.contains("%TA.AperFunction,SMDPad,CuDef*%\n%ADD12R,0.500000X1.000000*%"));
+        assert!(top.contains("%TA.AperFunction,ComponentPad*%\n%ADD13C,1.700000*%"));
+        // Rounded rectangle as a region, non-plated hole left out
+        assert_eq!(top.matches("G36*").count(), 1);
+        assert_eq!(top.matches("D03*").count(), 3);
+        assert!(top.ends_with("M02*\n"));
+
+        let bottom = plot_layer(&pcb, "B.Cu").unwrap();
+        assert!(bottom.contains("%TF.FileFunction,Copper,L2,Bot*%"));
+        // Arc bulging towards -y on the board, i.e. up in Gerber: clockwise from the left
+        assert!(bottom.contains("X0Y-5000000D02*\nG02*\nX2000000Y-5000000I1000000J0D01*"));
+        assert!(bottom.contains("%TA.AperFunction,Conductor*%\nG36*\nX0Y-10000000D02*"));
+    }
+
+    #[test]
+    fn test_plot_technical_layers() {
+        let pcb = parse_pcb(BOARD).unwrap();
+
+        let mask = plot_layer(&pcb, "F.Mask").unwrap();
+        assert!(mask.contains("%TF.FileFunction,Soldermask,Top*%\n%TF.FilePolarity,Negative*%"));
+        // Both SMD pads, the plated and the non-plated hole
+        assert_eq!(mask.matches("D03*").count() + mask.matches("G36*").count(), 4);
+
+        let paste = plot_layer(&pcb, "F.Paste").unwrap();
+        assert_eq!(paste.matches("D03*").count() + paste.matches("G36*").count(), 2);
+        assert!(!paste.contains("AperFunction"));
+
+        // Board mask clearance, and a pad shrinking its own paste aperture by 10%
+        let margins = BOARD
+            .replace("(net 0 \"\")", "(setup (pad_to_mask_clearance 0.05)) (net 0 \"\")")
+            .replace("(layers \"F.Cu\" \"F.Paste\" \"F.Mask\") (net 1", "(layers \"F.Cu\" \"F.Paste\" \"F.Mask\") (solder_paste_margin_ratio -0.1) (net 1");
+        let margins = parse_pcb(&margins).unwrap();
+        assert!(plot_layer(&margins, "F.Mask").unwrap().contains("%ADD10R,0.600000X1.100000*%"));
+        assert!(plot_layer(&margins, "F.Paste").unwrap().contains("%ADD10R,0.400000X0.800000*%"));
+
+        let silk = plot_layer(&pcb, "F.SilkS").unwrap();
+        assert!(silk.contains("%ADD10C,0.120000*%"));
+        // Footprint line rotated with its footprint
+        assert!(silk.contains("X9000000Y-11000000D02*\nX9000000Y-9000000D01*"));
+
+        let edge = plot_layer(&pcb, "Edge.Cuts").unwrap();
+        assert!(edge.contains("%TF.FileFunction,Profile,NP*%"));
+        assert!(edge.contains("%TA.AperFunction,Profile*%\n%ADD10C,0.100000*%"));
+        assert_eq!(edge.matches("D01*").count(), 4);
+
+        assert!(plot_layer(&pcb, "F.Fab").is_err());
+        assert_eq!(plottable_layers(&pcb).len(), 9);
+    }
+}
@@ -0,0 +1,29 @@
+//! Manufacturing output generated from a parsed board
+//!
+//! - [`position`] - Pick-and-place (.pos) files
+//! - [`gerber`] - Gerber X2 layer plots
+//! - [`drill`] - Excellon drill files
+//! - [`models`] - 3D model placement for mechanical CAD
+//! - [`stackup`] - Stackup table for fabricators
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! use kiparse::cam::position::{PositionFile, PositionOptions};
+//! use kiparse::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 10 20 90)
+//!     (property "Reference" "R1") (property "Value" "10k") (attr smd))
+//! )"#)?;
+//! let pos = PositionFile::from_pcb(&pcb, &PositionOptions::default());
+//!
+//! assert!(pos.to_csv().contains("\"R1\",\"10k\",\"R_0603\",10.0000,-20.0000,90.0000,top"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod drill;
+pub mod gerber;
+pub mod models;
+pub mod position;
+pub mod stackup;
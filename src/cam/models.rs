@@ -0,0 +1,475 @@
+//! 3D model placement for mechanical CAD
+//!
+//! Lists every 3D model attached to a footprint together with the full transform
+//! needed to place it in an assembly: the footprint position, rotation and side,
+//! and the model's own offset, rotation and scale. Model paths are kept as written
+//! and also resolved against KiCad path variables (`${KICAD8_3DMODEL_DIR}`,
+//! `${KIPRJMOD}`, ...), so a script can load the STEP files directly.
+//!
+//! Like position files, coordinates are relative to the chosen origin with Y
+//! pointing up. [`check_models`] reports model files that do not exist on disk.
+//!
+//! ```rust
+//! use kiparse::cam::models::{ModelPaths, ModelPlacementFile, ModelPlacementOptions};
+//! use kiparse::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "Capacitor_SMD:C_0805" (layer "F.Cu") (at 10 20 90)
+//!     (property "Reference" "C1")
+//!     (model "${KICAD8_3DMODEL_DIR}/Capacitor_SMD.3dshapes/C_0805.step"
+//!       (offset (xyz 0 0 0.5)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 0))))
+//! )"#)?;
+//! let mut paths = ModelPaths::new();
+//! paths.set("KICAD8_3DMODEL_DIR", "/usr/share/kicad/3dmodels");
+//! let file = ModelPlacementFile::from_pcb(&pcb, &paths, &ModelPlacementOptions::default());
+//!
+//! let c1 = &file.placements[0];
+//! assert_eq!(c1.resolved.as_deref(), Some("/usr/share/kicad/3dmodels/Capacitor_SMD.3dshapes/C_0805.step"));
+//! assert_eq!((c1.x, c1.y, c1.rotation), (10.0, -20.0, 90.0));
+//! assert_eq!(c1.offset, [0.0, 0.0, 0.5]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::position::{PositionOrigin, Side};
+use crate::bom::natural_order;
+use crate::pcb::types::PcbFile;
+
+/// KiCad path variables used to resolve model paths
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelPaths {
+    vars: HashMap<String, String>,
+}
+
+impl ModelPaths {
+    /// No variables set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Variables from the environment, with `KIPRJMOD` set to `project_dir`
+    pub fn from_env(project_dir: Option<&Path>) -> Self {
+        let mut paths = ModelPaths {
+            vars: std::env::vars().collect(),
+        };
+        if let Some(dir) = project_dir {
+            paths.set("KIPRJMOD", &dir.to_string_lossy());
+        }
+        paths
+    }
+
+    /// Define or replace a variable
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.vars.insert(name.to_string(), value.to_string());
+    }
+
+    /// Value of a variable
+    ///
+    /// An unset `KICADn_3DMODEL_DIR` falls back to the model directory of another
+    /// KiCad version, since the libraries keep their layout between releases.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        if let Some(value) = self.vars.get(name) {
+            return Some(value);
+        }
+        model_dir_version(name)?;
+        self.vars
+            .iter()
+            .filter_map(|(k, v)| Some((model_dir_version(k)?, v)))
+            .max_by_key(|(version, _)| *version)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Model path with `${VAR}` references expanded and relative paths taken from
+    /// `KIPRJMOD`
+    ///
+    /// Returns `None` for embedded models and paths using an undefined variable.
+    /// The file is not required to exist.
+    pub fn resolve(&self, path: &str) -> Option<PathBuf> {
+        if crate::embedded::embedded_name(path).is_some() {
+            return None;
+        }
+        let mut expanded = String::new();
+        let mut rest = path;
+        while let Some(start) = rest.find("${") {
+            let end = rest[start..].find('}')? + start;
+            expanded.push_str(&rest[..start]);
+            expanded.push_str(self.get(&rest[start + 2..end])?);
+            rest = &rest[end + 1..];
+        }
+        expanded.push_str(rest);
+
+        let resolved = PathBuf::from(expanded);
+        if resolved.is_relative() {
+            if let Some(project) = self.get("KIPRJMOD") {
+                return Some(Path::new(project).join(resolved));
+            }
+        }
+        Some(resolved)
+    }
+}
+
+/// KiCad version of a `KICADn_3DMODEL_DIR` variable
+fn model_dir_version(name: &str) -> Option<u32> {
+    name.strip_prefix("KICAD")?
+        .strip_suffix("_3DMODEL_DIR")?
+        .parse()
+        .ok()
+}
+
+/// Which models to list and where coordinates are measured from
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelPlacementOptions {
+    pub origin: PositionOrigin,
+    /// Include models hidden in the 3D viewer
+    pub include_hidden: bool,
+    /// Leave out do-not-populate footprints
+    pub exclude_dnp: bool,
+}
+
+impl Default for ModelPlacementOptions {
+    fn default() -> Self {
+        Self {
+            origin: PositionOrigin::Page,
+            include_hidden: false,
+            exclude_dnp: true,
+        }
+    }
+}
+
+/// One 3D model with its footprint placement and model transform
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelPlacement {
+    pub reference: String,
+    pub footprint: String,
+    /// Model path as written in the board
+    pub model: String,
+    /// Absolute path after variable expansion, when it could be resolved
+    pub resolved: Option<String>,
+    /// Footprint position in mm, Y up
+    pub x: f64,
+    pub y: f64,
+    /// Footprint rotation in degrees, as shown in KiCad
+    pub rotation: f64,
+    pub side: Side,
+    /// Model offset from the footprint origin in mm (x, y, z)
+    pub offset: [f64; 3],
+    /// Model rotation about the x, y and z axes in degrees
+    pub rotate: [f64; 3],
+    pub scale: [f64; 3],
+    pub hidden: bool,
+}
+
+/// Model placements of a board, sorted by reference
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelPlacementFile {
+    pub placements: Vec<ModelPlacement>,
+}
+
+impl ModelPlacementFile {
+    /// Collect the models of `pcb`, resolving their paths with `paths`
+    pub fn from_pcb(pcb: &PcbFile, paths: &ModelPaths, options: &ModelPlacementOptions) -> Self {
+        let origin = match options.origin {
+            PositionOrigin::Page => None,
+            PositionOrigin::AuxAxis => pcb.metadata.aux_axis_origin.clone(),
+        }
+        .unwrap_or_default();
+
+        let mut placements: Vec<ModelPlacement> = pcb
+            .footprints
+            .iter()
+            .filter(|f| !(options.exclude_dnp && f.attributes.dnp))
+            .flat_map(|f| {
+                let origin = &origin;
+                f.models
+                    .iter()
+                    .filter(|m| options.include_hidden || !m.hide)
+                    .map(move |m| ModelPlacement {
+                        reference: f.property("Reference").unwrap_or_default().to_string(),
+                        footprint: f.name.clone(),
+                        model: m.path.clone(),
+                        resolved: paths
+                            .resolve(&m.path)
+                            .map(|p| p.to_string_lossy().into_owned()),
+                        x: (f.position.x - origin.x).as_mm(),
+                        y: (origin.y - f.position.y).as_mm(),
+                        rotation: f.rotation,
+                        side: if f.layer == "B.Cu" {
+                            Side::Bottom
+                        } else {
+                            Side::Top
+                        },
+                        offset: m.offset,
+                        rotate: m.rotate,
+                        scale: m.scale,
+                        hidden: m.hide,
+                    })
+            })
+            .collect();
+        placements.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+
+        ModelPlacementFile { placements }
+    }
+
+    /// One row per model; the resolved path is empty when it could not be resolved
+    pub fn to_csv(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+        let mut out = String::from(
+            "Ref,Footprint,Model,Path,PosX,PosY,Rot,Side,OffsetX,OffsetY,OffsetZ,RotX,RotY,RotZ,ScaleX,ScaleY,ScaleZ\n",
+        );
+        for p in &self.placements {
+            out.push_str(&format!(
+                "{},{},{},{},{:.4},{:.4},{:.4},{}",
+                quote(&p.reference),
+                quote(&p.footprint),
+                quote(&p.model),
+                quote(p.resolved.as_deref().unwrap_or_default()),
+                p.x,
+                p.y,
+                p.rotation,
+                match p.side {
+                    Side::Top => "top",
+                    Side::Bottom => "bottom",
+                },
+            ));
+            for value in p.offset.iter().chain(&p.rotate).chain(&p.scale) {
+                out.push_str(&format!(",{:.4}", value));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Placements as a JSON array
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> crate::error::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.placements)?)
+    }
+}
+
+/// Why a model file cannot be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ModelProblem {
+    /// The path uses a variable that is not defined
+    UnresolvedVariable,
+    /// The resolved path does not exist
+    FileNotFound,
+    /// A `kicad-embed://` path naming a file embedded in neither the footprint nor
+    /// the board
+    NotEmbedded,
+}
+
+/// A footprint model whose file cannot be loaded
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MissingModel {
+    pub reference: String,
+    pub footprint: String,
+    /// Model path as written in the board
+    pub model: String,
+    /// Path after variable expansion, when it could be resolved
+    pub resolved: Option<String>,
+    pub problem: ModelProblem,
+}
+
+/// Outcome of [`check_models`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelCheck {
+    /// Number of models whose files were found
+    pub found: usize,
+    /// Models that cannot be loaded, sorted by reference
+    pub missing: Vec<MissingModel>,
+    /// References of footprints with no model at all, leaving out board-only
+    /// footprints such as logos and fiducials
+    pub without_model: Vec<String>,
+}
+
+/// Check that the model files of every footprint exist, resolving paths with `paths`
+///
+/// Hidden models are checked as well, since KiCad still loads them for export.
+pub fn check_models(pcb: &PcbFile, paths: &ModelPaths) -> ModelCheck {
+    let mut check = ModelCheck {
+        found: 0,
+        missing: Vec::new(),
+        without_model: Vec::new(),
+    };
+    for footprint in &pcb.footprints {
+        let reference = footprint.property("Reference").unwrap_or_default();
+        if footprint.models.is_empty() {
+            if !footprint.attributes.board_only {
+                check.without_model.push(reference.to_string());
+            }
+            continue;
+        }
+        for model in &footprint.models {
+            let resolved = paths.resolve(&model.path);
+            let problem = match (crate::embedded::embedded_name(&model.path), &resolved) {
+                (Some(name), _) => {
+                    let mut embedded = footprint.embedded_files.iter().chain(&pcb.embedded_files);
+                    (!embedded.any(|f| f.name == name)).then_some(ModelProblem::NotEmbedded)
+                }
+                (None, None) => Some(ModelProblem::UnresolvedVariable),
+                (None, Some(path)) if !path.is_file() => Some(ModelProblem::FileNotFound),
+                (None, Some(_)) => None,
+            };
+            match problem {
+                None => check.found += 1,
+                Some(problem) => check.missing.push(MissingModel {
+                    reference: reference.to_string(),
+                    footprint: footprint.name.clone(),
+                    model: model.path.clone(),
+                    resolved: resolved.map(|p| p.to_string_lossy().into_owned()),
+                    problem,
+                }),
+            }
+        }
+    }
+    check.missing.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+    check.without_model.sort_by(|a, b| natural_order(a, b));
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (setup (aux_axis_origin 100 100))
+  (footprint "Package_SO:SOIC-8" (layer "B.Cu") (at 120 110 180)
+    (property "Reference" "U1")
+    (model "${KICAD9_3DMODEL_DIR}/Package_SO.3dshapes/SOIC-8.step"
+      (offset (xyz 0 0 0)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 -90)))
+    (model "${KICAD9_3DMODEL_DIR}/Package_SO.3dshapes/SOIC-8.wrl" hide
+      (offset (xyz 0 0 0)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 0))))
+  (footprint "Local:Logo" (layer "F.Cu") (at 105 95)
+    (property "Reference" "LOGO1")
+    (model "models/logo.step" (offset (xyz 1 2 0)) (scale (xyz 2 2 2)) (rotate (xyz 90 0 0))))
+  (footprint "Connector:Custom" (layer "F.Cu") (at 90 90)
+    (property "Reference" "J2")
+    (model "${MY_LIBS}/custom.step" (offset (xyz 0 0 0)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 0))))
+  (footprint "Capacitor_SMD:C_0603" (layer "F.Cu") (at 130 100)
+    (property "Reference" "C1") (attr smd dnp)
+    (model "c.step" (offset (xyz 0 0 0)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 0))))
+)"#;
+
+    fn paths() -> ModelPaths {
+        let mut paths = ModelPaths::new();
+        paths.set("KICAD7_3DMODEL_DIR", "/opt/kicad7/3d");
+        paths.set("KICAD8_3DMODEL_DIR", "/opt/kicad8/3d");
+        paths.set("KIPRJMOD", "/work/board");
+        paths
+    }
+
+    #[test]
+    fn test_resolve() {
+        let paths = paths();
+        assert_eq!(
+            paths.resolve("${KICAD8_3DMODEL_DIR}/R.3dshapes/R.step"),
+            Some(PathBuf::from("/opt/kicad8/3d/R.3dshapes/R.step"))
+        );
+        // Unset model directories fall back to the newest one defined
+        assert_eq!(
+            paths.resolve("${KICAD9_3DMODEL_DIR}/R.step"),
+            Some(PathBuf::from("/opt/kicad8/3d/R.step"))
+        );
+        assert_eq!(
+            paths.resolve("${KIPRJMOD}/3d/part.step"),
+            Some(PathBuf::from("/work/board/3d/part.step"))
+        );
+        assert_eq!(
+            paths.resolve("3d/part.step"),
+            Some(PathBuf::from("/work/board/3d/part.step"))
+        );
+        assert_eq!(paths.resolve("${UNKNOWN}/part.step"), None);
+        assert_eq!(paths.resolve("${KIPRJMOD/part.step"), None);
+        assert_eq!(paths.resolve("kicad-embed://part.step"), None);
+    }
+
+    #[test]
+    fn test_placements() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let file = ModelPlacementFile::from_pcb(&pcb, &paths(), &ModelPlacementOptions::default());
+        let refs: Vec<&str> = file
+            .placements
+            .iter()
+            .map(|p| p.reference.as_str())
+            .collect();
+        assert_eq!(refs, ["J2", "LOGO1", "U1"]);
+
+        let u1 = &file.placements[2];
+        assert_eq!(u1.side, Side::Bottom);
+        assert_eq!((u1.x, u1.y, u1.rotation), (120.0, -110.0, 180.0));
+        assert_eq!(u1.rotate, [0.0, 0.0, -90.0]);
+        assert_eq!(
+            u1.resolved.as_deref(),
+            Some("/opt/kicad8/3d/Package_SO.3dshapes/SOIC-8.step")
+        );
+        assert_eq!(file.placements[0].resolved, None);
+
+        let options = ModelPlacementOptions {
+            origin: PositionOrigin::AuxAxis,
+            include_hidden: true,
+            exclude_dnp: false,
+        };
+        let file = ModelPlacementFile::from_pcb(&pcb, &paths(), &options);
+        assert_eq!(file.placements.len(), 5);
+        let logo = file
+            .placements
+            .iter()
+            .find(|p| p.reference == "LOGO1")
+            .unwrap();
+        assert_eq!((logo.x, logo.y), (5.0, 5.0));
+
+        let csv = file.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert!(rows[0].starts_with("Ref,Footprint,Model,Path,PosX"));
+        assert_eq!(
+            rows[3],
+            "\"LOGO1\",\"Local:Logo\",\"models/logo.step\",\"/work/board/models/logo.step\",\
+             5.0000,5.0000,0.0000,top,1.0000,2.0000,0.0000,90.0000,0.0000,0.0000,2.0000,2.0000,2.0000"
+        );
+        assert!(rows[2].starts_with("\"J2\",\"Connector:Custom\",\"${MY_LIBS}/custom.step\",\"\","));
+    }
+
+    #[test]
+    fn test_check_models() {
+        let dir = std::env::temp_dir().join(format!("kiparse-models-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("models")).unwrap();
+        std::fs::write(dir.join("models/logo.step"), "ISO-10303-21;").unwrap();
+        let mut paths = ModelPaths::new();
+        paths.set("KIPRJMOD", &dir.to_string_lossy());
+        let board = BOARD.replace(
+            "(footprint \"Capacitor_SMD:C_0603\"",
+            "(footprint \"Fiducial\" (attr board_only) (property \"Reference\" \"FID1\"))\n  \
+             (footprint \"TestPoint\" (property \"Reference\" \"TP1\"))\n  \
+             (footprint \"Capacitor_SMD:C_0603\"",
+        );
+        let check = check_models(&parse_pcb(&board).unwrap(), &paths);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(check.found, 1);
+        let missing: Vec<(&str, ModelProblem)> = check
+            .missing
+            .iter()
+            .map(|m| (m.reference.as_str(), m.problem))
+            .collect();
+        assert_eq!(
+            missing,
+            [
+                ("C1", ModelProblem::FileNotFound),
+                ("J2", ModelProblem::UnresolvedVariable),
+                ("U1", ModelProblem::UnresolvedVariable),
+                ("U1", ModelProblem::UnresolvedVariable),
+            ]
+        );
+        assert_eq!(check.without_model, ["TP1"]);
+    }
+}
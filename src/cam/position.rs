@@ -0,0 +1,277 @@
+//! Pick-and-place (footprint position) files
+//!
+//! Writes the ASCII `.pos` and CSV layouts produced by KiCad's *Fabrication Outputs >
+//! Component Placement* dialog. Coordinates are relative to the chosen origin with
+//! Y pointing up, as assembly machines expect.
+
+use crate::bom::natural_order;
+use crate::pcb::types::PcbFile;
+use crate::units::MM_PER_INCH;
+
+/// Length unit of the coordinates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Units {
+    Millimeters,
+    Inches,
+}
+
+/// Point the coordinates are measured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PositionOrigin {
+    /// Page origin (top-left corner of the sheet)
+    Page,
+    /// Drill/place file origin from the board setup; the page origin when unset
+    AuxAxis,
+}
+
+/// Board side a footprint is mounted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Side {
+    Top,
+    Bottom,
+}
+
+/// Which sides to include
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SideFilter {
+    Top,
+    Bottom,
+    Both,
+}
+
+/// Position file settings, defaulting to KiCad's dialog defaults except that
+/// through-hole parts are kept
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionOptions {
+    pub units: Units,
+    pub origin: PositionOrigin,
+    pub side: SideFilter,
+    /// Only footprints with the `smd` mount attribute
+    pub smd_only: bool,
+    /// Leave out do-not-populate footprints
+    pub exclude_dnp: bool,
+}
+
+impl Default for PositionOptions {
+    fn default() -> Self {
+        Self {
+            units: Units::Millimeters,
+            origin: PositionOrigin::Page,
+            side: SideFilter::Both,
+            smd_only: false,
+            exclude_dnp: true,
+        }
+    }
+}
+
+/// One footprint placement
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placement {
+    pub reference: String,
+    pub value: String,
+    /// Footprint name without its library prefix
+    pub package: String,
+    pub x: f64,
+    pub y: f64,
+    /// Rotation in degrees, normalised to (-180, 180]
+    pub rotation: f64,
+    pub side: Side,
+}
+
+/// Placements of a board in the requested units, sorted by reference
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PositionFile {
+    pub units: Units,
+    pub side: SideFilter,
+    pub placements: Vec<Placement>,
+}
+
+impl PositionFile {
+    /// Collect the placements of `pcb`, skipping footprints excluded from position files
+    pub fn from_pcb(pcb: &PcbFile, options: &PositionOptions) -> Self {
+        let origin = match options.origin {
+            PositionOrigin::Page => None,
+            PositionOrigin::AuxAxis => pcb.metadata.aux_axis_origin.clone(),
+        }
+        .unwrap_or_default();
+        let scale = match options.units {
+            Units::Millimeters => 1.0,
+            Units::Inches => 1.0 / MM_PER_INCH,
+        };
+
+        let mut placements: Vec<Placement> = pcb
+            .footprints
+            .iter()
+            .filter(|f| !f.attributes.exclude_from_pos_files && !f.attributes.board_only)
+            .filter(|f| !(options.exclude_dnp && f.attributes.dnp))
+            .filter(|f| !options.smd_only || f.attributes.mount == "smd")
+            .filter_map(|f| {
+                let side = if f.layer == "B.Cu" { Side::Bottom } else { Side::Top };
+                let wanted = match options.side {
+                    SideFilter::Top => side == Side::Top,
+                    SideFilter::Bottom => side == Side::Bottom,
+                    SideFilter::Both => true,
+                };
+                wanted.then(|| Placement {
+                    reference: f.property("Reference").unwrap_or_default().to_string(),
+                    value: f.property("Value").unwrap_or_default().to_string(),
+                    package: f.name.rsplit(':').next().unwrap_or_default().to_string(),
+                    x: (f.position.x - origin.x).as_mm() * scale,
+                    y: (origin.y - f.position.y).as_mm() * scale,
+                    rotation: normalize_rotation(f.rotation),
+                    side,
+                })
+            })
+            .collect();
+        placements.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+
+        PositionFile { units: options.units, side: options.side, placements }
+    }
+
+    /// KiCad ASCII `.pos` layout with aligned columns
+    pub fn to_ascii(&self) -> String {
+        let width = |header: &str, column: fn(&Placement) -> &str| {
+            self.placements.iter().map(|p| column(p).len()).fold(header.len(), usize::max)
+        };
+        let (ref_w, val_w, pkg_w) = (
+            width("# Ref", |p| &p.reference),
+            width("Val", |p| &p.value),
+            width("Package", |p| &p.package),
+        );
+        let unit = match self.units {
+            Units::Millimeters => "mm",
+            Units::Inches => "in",
+        };
+        let side = match self.side {
+            SideFilter::Top => "top",
+            SideFilter::Bottom => "bottom",
+            SideFilter::Both => "All",
+        };
+
+        let mut out = String::from("### Footprint positions - created by kiparse ###\n");
+        out.push_str(&format!("## Unit = {}, Angle = deg.\n## Side : {}\n", unit, side));
+        out.push_str(&format!(
+            "{:<ref_w$} {:<val_w$} {:<pkg_w$} {:>10} {:>10} {:>9}  Side\n",
+            "# Ref", "Val", "Package", "PosX", "PosY", "Rot",
+        ));
+        for p in &self.placements {
+            out.push_str(&format!(
+                "{:<ref_w$} {:<val_w$} {:<pkg_w$} {:>10.4} {:>10.4} {:>9.4}  {}\n",
+                p.reference, p.value, p.package, p.x, p.y, p.rotation, side_name(p.side),
+            ));
+        }
+        out.push_str("## End\n");
+        out
+    }
+
+    /// KiCad CSV layout: `Ref,Val,Package,PosX,PosY,Rot,Side`
+    pub fn to_csv(&self) -> String {
+        let quote = |s: &str| format!("\"{}\"", s.replace('"', "\"\""));
+        let mut out = String::from("Ref,Val,Package,PosX,PosY,Rot,Side\n");
+        for p in &self.placements {
+            out.push_str(&format!(
+                "{},{},{},{:.4},{:.4},{:.4},{}\n",
+                quote(&p.reference),
+                quote(&p.value),
+                quote(&p.package),
+                p.x,
+                p.y,
+                p.rotation,
+                side_name(p.side),
+            ));
+        }
+        out
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+    }
+}
+
+fn normalize_rotation(degrees: f64) -> f64 {
+    let r = degrees.rem_euclid(360.0);
+    if r > 180.0 {
+        r - 360.0
+    } else {
+        r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (setup (aux_axis_origin 100 100))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 110 95 270)
+    (property "Reference" "R10") (property "Value" "10k") (attr smd))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 125.4 100)
+    (property "Reference" "R2") (property "Value" "4k7") (attr smd))
+  (footprint "Package_SO:SOIC-8" (layer "B.Cu") (at 120 120 180)
+    (property "Reference" "U1") (property "Value" "LM358") (attr smd))
+  (footprint "Connector:Pin_Header_1x02" (layer "F.Cu") (at 90 90)
+    (property "Reference" "J1") (property "Value" "Conn") (attr through_hole))
+  (footprint "Capacitor_SMD:C_0603" (layer "F.Cu") (at 130 100)
+    (property "Reference" "C1") (property "Value" "DNP") (attr smd dnp))
+  (footprint "Fiducial" (layer "F.Cu") (at 101 101)
+    (property "Reference" "FID1") (property "Value" "Fiducial") (attr smd exclude_from_pos_files))
+)"#;
+
+    #[test]
+    fn test_placements() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let pos = PositionFile::from_pcb(&pcb, &PositionOptions::default());
+        let refs: Vec<&str> = pos.placements.iter().map(|p| p.reference.as_str()).collect();
+        assert_eq!(refs, ["J1", "R2", "R10", "U1"]);
+
+        let r10 = &pos.placements[2];
+        assert_eq!(r10.package, "R_0603");
+        assert_eq!((r10.x, r10.y, r10.rotation), (110.0, -95.0, -90.0));
+        assert_eq!(pos.placements[3].side, Side::Bottom);
+
+        let options = PositionOptions {
+            units: Units::Inches,
+            origin: PositionOrigin::AuxAxis,
+            side: SideFilter::Top,
+            smd_only: true,
+            exclude_dnp: false,
+        };
+        let pos = PositionFile::from_pcb(&pcb, &options);
+        let refs: Vec<&str> = pos.placements.iter().map(|p| p.reference.as_str()).collect();
+        assert_eq!(refs, ["C1", "R2", "R10"]);
+        assert!((pos.placements[1].x - 1.0).abs() < 1e-9);
+        assert!((pos.placements[2].y - 5.0 / 25.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_output_formats() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let options = PositionOptions { origin: PositionOrigin::AuxAxis, ..Default::default() };
+        let pos = PositionFile::from_pcb(&pcb, &options);
+
+        let csv = pos.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "Ref,Val,Package,PosX,PosY,Rot,Side");
+        assert_eq!(rows[3], "\"R10\",\"10k\",\"R_0603\",10.0000,5.0000,-90.0000,top");
+        assert_eq!(rows[4], "\"U1\",\"LM358\",\"SOIC-8\",20.0000,-20.0000,180.0000,bottom");
+
+        let ascii = pos.to_ascii();
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines[1], "## Unit = mm, Angle = deg.");
+        assert_eq!(lines[3], "# Ref Val   Package               PosX       PosY       Rot  Side");
+        assert_eq!(lines[5], "R2    4k7   R_0603             25.4000     0.0000    0.0000  top");
+        assert_eq!(lines[6], "R10   10k   R_0603             10.0000     5.0000  -90.0000  top");
+        assert_eq!(*lines.last().unwrap(), "## End");
+    }
+}
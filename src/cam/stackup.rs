@@ -0,0 +1,185 @@
+//! Stackup table for board fabricators
+//!
+//! Turns the physical stackup of the board setup into the table fab houses ask
+//! for with an order: one row per layer, top to bottom, with its type, material,
+//! thickness and, for copper, the weight in oz/ft² (35 µm per ounce, rounded to
+//! a quarter ounce). Solder paste layers are left out, as paste goes on a
+//! stencil rather than the board. The board-wide options a quote needs (finish,
+//! impedance control, edge connector, castellation and edge plating) come
+//! along.
+//!
+//! ```rust
+//! use kiparse::cam::stackup::StackupTable;
+//! use kiparse::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (setup (stackup
+//!     (layer "F.Cu" (type "copper") (thickness 0.035))
+//!     (layer "dielectric 1" (type "core") (thickness 1.51) (material "FR4"))
+//!     (layer "B.Cu" (type "copper") (thickness 0.035))
+//!     (copper_finish "ENIG")))
+//! )"#)?;
+//! let table = StackupTable::from_pcb(&pcb);
+//!
+//! assert_eq!(table.layers[0].copper_weight, Some(1.0));
+//! assert_eq!(table.to_csv().lines().nth(2), Some("dielectric 1,Core,FR4,1.5100,"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(feature = "json")]
+use crate::error::Result;
+use crate::pcb::types::PcbFile;
+use crate::units::Length;
+
+/// Copper thickness of one ounce per square foot, in mm
+const MM_PER_OUNCE: f64 = 0.035;
+
+/// One layer of the table
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackupRow {
+    /// Board layer name (`F.Cu`) or dielectric label (`dielectric 1`)
+    pub layer: String,
+    /// Stackup type with a capital, e.g. `Copper`, `Prepreg` or `Top Solder Mask`
+    pub layer_type: String,
+    pub material: Option<String>,
+    /// Thickness in mm
+    pub thickness: Option<f64>,
+    /// Copper weight in oz/ft², for copper layers with a thickness
+    pub copper_weight: Option<f64>,
+}
+
+/// Stackup of a board as sent to a fabricator
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackupTable {
+    /// Board thickness from the general section
+    pub board_thickness: Option<f64>,
+    /// Sum of the layer thicknesses
+    pub total_thickness: f64,
+    pub copper_layers: usize,
+    pub copper_finish: Option<String>,
+    /// The board setup's "impedance controlled" option
+    pub impedance_controlled: bool,
+    pub edge_connector: Option<String>,
+    pub castellated_pads: bool,
+    pub edge_plating: bool,
+    /// Layers top to bottom
+    pub layers: Vec<StackupRow>,
+}
+
+impl StackupTable {
+    /// Table of the stackup in `pcb`'s setup; empty when the board defines none
+    pub fn from_pcb(pcb: &PcbFile) -> Self {
+        let layers: Vec<StackupRow> = pcb
+            .stackup
+            .iter()
+            .filter(|l| !l.layer_type.ends_with("Solder Paste"))
+            .map(|l| {
+                let copper = l.layer_type == "copper";
+                let thickness = l.thickness.map(Length::as_mm);
+                StackupRow {
+                    layer: l.name.clone(),
+                    layer_type: capitalise(&l.layer_type),
+                    material: l.material.clone(),
+                    thickness,
+                    copper_weight: thickness
+                        .filter(|_| copper)
+                        .map(|t| (t / MM_PER_OUNCE * 4.0).round() / 4.0),
+                }
+            })
+            .collect();
+        let metadata = &pcb.metadata;
+        StackupTable {
+            board_thickness: pcb.board_thickness.map(Length::as_mm),
+            total_thickness: layers.iter().filter_map(|l| l.thickness).sum(),
+            copper_layers: layers.iter().filter(|l| l.layer_type == "Copper").count(),
+            copper_finish: metadata.copper_finish.clone(),
+            impedance_controlled: metadata.dielectric_constraints,
+            edge_connector: metadata.edge_connector.clone(),
+            castellated_pads: metadata.castellated_pads,
+            edge_plating: metadata.edge_plating,
+            layers,
+        }
+    }
+
+    /// `Layer,Type,Material,Thickness (mm),Copper Weight (oz)`, one row per layer
+    pub fn to_csv(&self) -> String {
+        let field = |s: &str| {
+            if s.contains([',', '"', '\n']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_string()
+            }
+        };
+        let mut out = String::from("Layer,Type,Material,Thickness (mm),Copper Weight (oz)\n");
+        for row in &self.layers {
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                field(&row.layer),
+                field(&row.layer_type),
+                field(row.material.as_deref().unwrap_or_default()),
+                row.thickness.map_or(String::new(), |t| format!("{:.4}", t)),
+                row.copper_weight.map_or(String::new(), |w| w.to_string()),
+            ));
+        }
+        out
+    }
+
+    /// Pretty-printed JSON of the table
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// `prepreg` as `Prepreg`; types KiCad already capitalises stay as they are
+fn capitalise(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    #[test]
+    fn test_fabrication_table() {
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (general (thickness 1.6))
+  (setup
+    (stackup
+      (layer "F.Paste" (type "Top Solder Paste"))
+      (layer "F.Mask" (type "Top Solder Mask") (color "Green") (thickness 0.01))
+      (layer "F.Cu" (type "copper") (thickness 0.035))
+      (layer "dielectric 1" (type "prepreg") (thickness 0.2) (material "FR4, 7628") (epsilon_r 4.5))
+      (layer "In1.Cu" (type "copper") (thickness 0.0175))
+      (layer "dielectric 2" (type "core") (thickness 1.2) (material "FR4"))
+      (layer "B.Cu" (type "copper") (thickness 0.07))
+      (copper_finish "ENIG")
+      (dielectric_constraints yes)))
+)"#).unwrap();
+        let table = StackupTable::from_pcb(&pcb);
+
+        assert_eq!(table.layers.len(), 6);
+        assert_eq!(table.copper_layers, 3);
+        assert!(table.impedance_controlled);
+        let weights: Vec<Option<f64>> = table.layers.iter().map(|l| l.copper_weight).collect();
+        assert_eq!(weights, [None, Some(1.0), None, Some(0.5), None, Some(2.0)]);
+        assert!((table.total_thickness - 1.5325).abs() < 1e-9);
+
+        let csv = table.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "Layer,Type,Material,Thickness (mm),Copper Weight (oz)");
+        assert_eq!(rows[1], "F.Mask,Top Solder Mask,,0.0100,");
+        assert_eq!(rows[2], "F.Cu,Copper,,0.0350,1");
+        assert_eq!(rows[3], "dielectric 1,Prepreg,\"FR4, 7628\",0.2000,");
+        assert_eq!(rows[6], "B.Cu,Copper,,0.0700,2");
+
+        assert!(StackupTable::from_pcb(&parse_pcb("(kicad_pcb)").unwrap()).layers.is_empty());
+    }
+}
@@ -0,0 +1,10 @@
+//! Hash maps of the board and symbol types
+//!
+//! std's with the `std` feature, hashbrown's on `no_std` + `alloc`, so the
+//! field types stay `std::collections::HashMap` for std users.
+
+#[cfg(feature = "std")]
+pub use std::collections::{HashMap, HashSet};
+
+#[cfg(not(feature = "std"))]
+pub use hashbrown::{HashMap, HashSet};
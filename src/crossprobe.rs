@@ -0,0 +1,301 @@
+//! Links between schematic symbols and board footprints
+//!
+//! [`CrossProbe::build`] pairs the parts of a schematic [`Hierarchy`] with the
+//! footprints of a board the way Pcbnew's *Update PCB from Schematic* does:
+//! first by the symbol path each footprint stores (sheet uuids and the symbol
+//! uuid), then by reference for what is left. The links let a viewer jump
+//! from a symbol to its footprint and back, and [`CrossProbe::issues`] lists
+//! what is out of sync: parts missing on either side and linked parts whose
+//! reference, footprint or value differ.
+//!
+//! Power symbols and symbols excluded from the board have no footprint;
+//! board-only footprints such as logos have no symbol. Both are left out.
+//!
+//! ```rust
+//! use kiparse::crossprobe::{CrossProbe, SyncIssueKind};
+//! use kiparse::{parse_pcb, schematic::Hierarchy};
+//!
+//! let hierarchy = Hierarchy::build("demo.kicad_sch", r#"(kicad_sch (uuid "root")
+//!   (symbol (lib_id "Device:R") (at 0 0 0) (uuid "r1") (property "Reference" "R1")
+//!     (property "Value" "10k") (property "Footprint" "Resistor_SMD:R_0603"))
+//!   (symbol (lib_id "Device:C") (at 10 0 0) (uuid "c1") (property "Reference" "C1")
+//!     (property "Value" "100n") (property "Footprint" "Capacitor_SMD:C_0603")))"#, |_| unreachable!())?;
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "Resistor_SMD:R_0805" (layer "F.Cu") (at 0 0) (path "/r1")
+//!     (property "Reference" "R1") (property "Value" "10k")))"#)?;
+//!
+//! let probe = CrossProbe::build(&hierarchy, &pcb);
+//! assert_eq!(probe.footprint("R1").map(|f| f.name.as_str()), Some("Resistor_SMD:R_0805"));
+//! let kinds: Vec<SyncIssueKind> = probe.issues().iter().map(|i| i.kind).collect();
+//! assert_eq!(kinds, [SyncIssueKind::NotOnBoard, SyncIssueKind::FootprintMismatch]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::bom::natural_order;
+use crate::pcb::types::{Footprint, PcbFile};
+use crate::schematic::{HierarchicalSymbol, Hierarchy};
+
+/// The units of one schematic part, placed under one reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchematicPart<'a> {
+    pub reference: String,
+    /// Units in sheet order; the first one's properties stand for the part
+    pub units: Vec<HierarchicalSymbol<'a>>,
+}
+
+impl SchematicPart<'_> {
+    pub fn value(&self) -> &str {
+        self.units[0].property("Value").unwrap_or_default()
+    }
+
+    /// Assigned footprint, e.g. `Resistor_SMD:R_0603`
+    pub fn footprint(&self) -> &str {
+        self.units[0].property("Footprint").unwrap_or_default()
+    }
+
+    /// Symbol paths as a board footprint stores them: sheet uuids below the
+    /// root, then the symbol uuid, one per unit
+    pub fn paths(&self, hierarchy: &Hierarchy) -> Vec<String> {
+        self.units
+            .iter()
+            .filter_map(|unit| {
+                let uuid = unit.symbol.uuid.as_deref()?;
+                let sheets = hierarchy.sheets[unit.sheet].uuid_path.splitn(3, '/').nth(2);
+                Some(match sheets {
+                    Some(sheets) => format!("/{}/{}", sheets, uuid),
+                    None => format!("/{}", uuid),
+                })
+            })
+            .collect()
+    }
+}
+
+/// How a part and a footprint were paired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum MatchedBy {
+    /// The footprint's symbol path
+    Uuid,
+    /// The reference designator, for footprints without a matching path
+    Reference,
+}
+
+/// A schematic part and its footprint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link<'a> {
+    pub part: SchematicPart<'a>,
+    pub footprint: &'a Footprint,
+    pub matched_by: MatchedBy,
+}
+
+/// Kind of a [`SyncIssue`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SyncIssueKind {
+    /// A schematic part without a footprint on the board
+    NotOnBoard,
+    /// A board footprint without a schematic part
+    NotInSchematic,
+    /// Linked by path, but annotated differently
+    ReferenceMismatch,
+    /// The footprint differs from the one assigned in the schematic
+    FootprintMismatch,
+    ValueMismatch,
+}
+
+/// Something out of sync between schematic and board
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SyncIssue {
+    pub kind: SyncIssueKind,
+    /// Schematic reference, or the board's for [`SyncIssueKind::NotInSchematic`]
+    pub reference: String,
+    /// What the schematic has, e.g. the assigned footprint
+    pub schematic: Option<String>,
+    /// What the board has
+    pub board: Option<String>,
+    pub message: String,
+}
+
+/// Schematic parts paired with board footprints, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrossProbe<'a> {
+    /// Linked parts in reference order
+    pub links: Vec<Link<'a>>,
+    /// Schematic parts without a footprint, in reference order
+    pub not_on_board: Vec<SchematicPart<'a>>,
+    /// Footprints without a schematic part, in board order
+    pub not_in_schematic: Vec<&'a Footprint>,
+}
+
+impl<'a> CrossProbe<'a> {
+    /// Pair the parts of `hierarchy` with the footprints of `pcb`
+    pub fn build(hierarchy: &'a Hierarchy, pcb: &'a PcbFile) -> Self {
+        let mut parts: Vec<SchematicPart<'a>> = Vec::new();
+        for symbol in hierarchy.symbols() {
+            if symbol.reference.starts_with('#') || !symbol.symbol.on_board {
+                continue;
+            }
+            match parts.iter_mut().find(|p| p.reference == symbol.reference) {
+                Some(part) => part.units.push(symbol),
+                None => parts.push(SchematicPart { reference: symbol.reference.clone(), units: vec![symbol] }),
+            }
+        }
+        parts.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+
+        let mut by_path: HashMap<String, usize> = HashMap::new();
+        for (index, part) in parts.iter().enumerate() {
+            for path in part.paths(hierarchy) {
+                by_path.insert(path, index);
+            }
+        }
+        let footprints: Vec<&Footprint> = pcb.footprints.iter().filter(|f| !f.attributes.board_only).collect();
+        let mut part_footprint: Vec<Option<(usize, MatchedBy)>> = vec![None; parts.len()];
+        let mut linked = vec![false; footprints.len()];
+        for (f, footprint) in footprints.iter().enumerate() {
+            let Some(&index) = footprint.path.as_deref().and_then(|p| by_path.get(p)) else {
+                continue;
+            };
+            if part_footprint[index].is_none() {
+                part_footprint[index] = Some((f, MatchedBy::Uuid));
+                linked[f] = true;
+            }
+        }
+        for (f, footprint) in footprints.iter().enumerate() {
+            if linked[f] {
+                continue;
+            }
+            let reference = footprint.property("Reference").unwrap_or_default();
+            let found = parts.iter().zip(&part_footprint).position(|(p, linked)| linked.is_none() && p.reference == reference);
+            if let Some(index) = found {
+                part_footprint[index] = Some((f, MatchedBy::Reference));
+                linked[f] = true;
+            }
+        }
+
+        let mut probe = CrossProbe { links: Vec::new(), not_on_board: Vec::new(), not_in_schematic: Vec::new() };
+        for (part, linked) in parts.into_iter().zip(part_footprint) {
+            match linked {
+                Some((f, matched_by)) => probe.links.push(Link { part, footprint: footprints[f], matched_by }),
+                None => probe.not_on_board.push(part),
+            }
+        }
+        probe.not_in_schematic = footprints.into_iter().zip(linked).filter(|(_, l)| !l).map(|(f, _)| f).collect();
+        probe
+    }
+
+    /// Footprint of the schematic part with this reference
+    pub fn footprint(&self, reference: &str) -> Option<&'a Footprint> {
+        self.links.iter().find(|l| l.part.reference == reference).map(|l| l.footprint)
+    }
+
+    /// Schematic part of the board footprint with this reference
+    pub fn part(&self, board_reference: &str) -> Option<&SchematicPart<'a>> {
+        self.links
+            .iter()
+            .find(|l| l.footprint.property("Reference") == Some(board_reference))
+            .map(|l| &l.part)
+    }
+
+    /// What is out of sync, in the order of [`SyncIssueKind`] and then of
+    /// references
+    pub fn issues(&self) -> Vec<SyncIssue> {
+        let mut issues = Vec::new();
+        for part in &self.not_on_board {
+            issues.push(SyncIssue {
+                kind: SyncIssueKind::NotOnBoard,
+                reference: part.reference.clone(),
+                schematic: Some(part.footprint().to_string()).filter(|f| !f.is_empty()),
+                board: None,
+                message: format!("{} has no footprint on the board", part.reference),
+            });
+        }
+        for footprint in &self.not_in_schematic {
+            let reference = footprint.property("Reference").unwrap_or_default();
+            issues.push(SyncIssue {
+                kind: SyncIssueKind::NotInSchematic,
+                reference: reference.to_string(),
+                schematic: None,
+                board: Some(footprint.name.clone()),
+                message: format!("Footprint {} has no symbol in the schematic", reference),
+            });
+        }
+        for link in &self.links {
+            let reference = &link.part.reference;
+            let board_reference = link.footprint.property("Reference").unwrap_or_default();
+            let compared = [
+                (SyncIssueKind::ReferenceMismatch, "reference", reference.as_str(), board_reference),
+                (SyncIssueKind::FootprintMismatch, "footprint", link.part.footprint(), link.footprint.name.as_str()),
+                (SyncIssueKind::ValueMismatch, "value", link.part.value(), link.footprint.property("Value").unwrap_or_default()),
+            ];
+            for (kind, what, schematic, board) in compared {
+                if schematic != board {
+                    issues.push(SyncIssue {
+                        kind,
+                        reference: reference.clone(),
+                        schematic: Some(schematic.to_string()),
+                        board: Some(board.to_string()),
+                        message: format!("{} {} is {} in the schematic but {} on the board", reference, what, schematic, board),
+                    });
+                }
+            }
+        }
+        issues.sort_by(|a, b| a.kind.cmp(&b.kind).then_with(|| natural_order(&a.reference, &b.reference)));
+        issues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    #[test]
+    fn test_links_by_path_then_reference() {
+        let root = r#"(kicad_sch (uuid "root")
+  (sheet (at 0 0) (size 20 10) (uuid "s1") (property "Sheetname" "Amp") (property "Sheetfile" "amp.kicad_sch")))"#;
+        let amp = r#"(kicad_sch (uuid "amp")
+  (symbol (lib_id "Amplifier_Operational:LM358") (at 0 0 0) (unit 1) (uuid "u1a") (property "Reference" "U1")
+    (property "Value" "LM358") (property "Footprint" "Package_SO:SOIC-8"))
+  (symbol (lib_id "Amplifier_Operational:LM358") (at 20 0 0) (unit 2) (uuid "u1b") (property "Reference" "U1")
+    (property "Value" "LM358") (property "Footprint" "Package_SO:SOIC-8"))
+  (symbol (lib_id "Device:R") (at 0 20 0) (uuid "r1") (property "Reference" "R1")
+    (property "Value" "1k") (property "Footprint" "Resistor_SMD:R_0603"))
+  (symbol (lib_id "Device:R") (at 0 30 0) (uuid "r9") (on_board no) (property "Reference" "R9") (property "Value" "1k")))"#;
+        let hierarchy = Hierarchy::build("top.kicad_sch", root, |_| Ok(amp.to_string())).unwrap();
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 0 0) (path "/s1/u1b")
+    (property "Reference" "U2") (property "Value" "LM358"))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 5)
+    (property "Reference" "R1") (property "Value" "2k2"))
+  (footprint "Connector:TestPoint" (layer "F.Cu") (at 0 9) (path "/s1/gone")
+    (property "Reference" "TP1"))
+  (footprint "Logo" (layer "F.Cu") (at 9 9) (attr board_only) (property "Reference" "G1")))"#)
+        .unwrap();
+
+        let probe = CrossProbe::build(&hierarchy, &pcb);
+        let links: Vec<(&str, &str, MatchedBy)> = probe
+            .links
+            .iter()
+            .map(|l| (l.part.reference.as_str(), l.footprint.property("Reference").unwrap(), l.matched_by))
+            .collect();
+        assert_eq!(links, [("R1", "R1", MatchedBy::Reference), ("U1", "U2", MatchedBy::Uuid)]);
+        assert_eq!(probe.links[1].part.units.len(), 2);
+        assert_eq!(probe.part("U2").map(|p| p.reference.as_str()), Some("U1"));
+
+        let issues = probe.issues();
+        let found: Vec<(SyncIssueKind, &str)> = issues.iter().map(|i| (i.kind, i.reference.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                (SyncIssueKind::NotInSchematic, "TP1"),
+                (SyncIssueKind::ReferenceMismatch, "U1"),
+                (SyncIssueKind::ValueMismatch, "R1"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Embedded files (KiCad 8/9)
+//!
+//! Boards, schematics and footprints can carry files such as 3D models, fonts or
+//! datasheets inside an `(embedded_files ...)` section. Each payload is stored as
+//! base64-encoded zstd data between `|` markers, and items refer to it with a
+//! `kicad-embed://<name>` URI.
+//!
+//! ```rust
+//! use kiparse::embedded::{embedded_name, extract_embedded_files};
+//!
+//! let content = r#"(kicad_sch
+//!   (embedded_files
+//!     (file (name "logo.png") (type other) (data |KLUv/QBYIQAAZGF0YQ==|) (checksum "ABC"))
+//!   )
+//! )"#;
+//! let files = extract_embedded_files(content)?;
+//!
+//! assert_eq!(files[0].name, "logo.png");
+//! assert_eq!(embedded_name("kicad-embed://logo.png"), Some("logo.png"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::base64;
+use crate::error::{KicadError, Result};
+use crate::sexpr::{parse_sexpr, SExpr};
+
+/// URI scheme KiCad uses to reference embedded files
+pub const EMBED_URI_PREFIX: &str = "kicad-embed://";
+
+/// A file embedded in a KiCad document
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmbeddedFile {
+    pub name: String,
+    /// Content kind as written by KiCad: `model`, `font`, `datasheet`, `worksheet` or `other`
+    pub file_type: String,
+    pub checksum: Option<String>,
+    /// zstd-compressed payload, already base64-decoded
+    pub compressed: Vec<u8>,
+}
+
+impl EmbeddedFile {
+    /// Decompressed file contents
+    ///
+    /// Requires the `zstd` feature; without it an error is returned.
+    pub fn contents(&self) -> Result<Vec<u8>> {
+        #[cfg(feature = "zstd")]
+        {
+            use std::io::Read;
+
+            let mut decoder = ruzstd::decoding::StreamingDecoder::new(self.compressed.as_slice())
+                .map_err(|e| KicadError::ParseError(format!("{}: {}", self.name, e)))?;
+            let mut contents = Vec::new();
+            decoder.read_to_end(&mut contents)?;
+            Ok(contents)
+        }
+        #[cfg(not(feature = "zstd"))]
+        {
+            Err(KicadError::InvalidFormat(format!(
+                "{}: decompressing embedded files requires the 'zstd' feature",
+                self.name
+            )))
+        }
+    }
+}
+
+/// Name of the embedded file a `kicad-embed://` path refers to
+pub fn embedded_name(path: &str) -> Option<&str> {
+    path.strip_prefix(EMBED_URI_PREFIX)
+}
+
+/// Parse the files of an `(embedded_files (file ...) ...)` node
+///
+/// Entries with an undecodable payload are kept with an empty `compressed` buffer
+/// so their names still resolve.
+pub fn parse_embedded_files(node: &SExpr) -> Vec<EmbeddedFile> {
+    node.children("file")
+        .map(|file| {
+            // The |...| payload may wrap over several lines and so several atoms
+            let encoded: String = file
+                .child("data")
+                .map(|d| d.args().iter().filter_map(SExpr::as_str).collect())
+                .unwrap_or_default();
+            EmbeddedFile {
+                name: file.child_str("name").unwrap_or_default().to_string(),
+                file_type: file.child_str("type").unwrap_or("other").to_string(),
+                checksum: file.child_str("checksum").map(str::to_string),
+                compressed: base64::decode(encoded.trim_matches('|')).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Extract the top-level embedded files of any KiCad document (board, schematic, library)
+pub fn extract_embedded_files(content: &str) -> Result<Vec<EmbeddedFile>> {
+    let root = parse_sexpr(content)?;
+    Ok(root
+        .child("embedded_files")
+        .map(parse_embedded_files)
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMATIC: &str = r#"(kicad_sch
+  (version 20231120)
+  (embedded_files
+    (file
+      (name "QFN-32.step")
+      (type model)
+      (data |KLUv/QBY
+        IQAAZGF0YQ==|)
+      (checksum "0F3A")
+    )
+    (file (name "font.ttf") (type font) (data |!!!|))
+  )
+)"#;
+
+    #[test]
+    fn test_extract_embedded_files() {
+        let files = extract_embedded_files(SCHEMATIC).unwrap();
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].name, "QFN-32.step");
+        assert_eq!(files[0].file_type, "model");
+        assert_eq!(files[0].checksum.as_deref(), Some("0F3A"));
+        // zstd frame magic number
+        assert!(files[0].compressed.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+        assert!(files[1].compressed.is_empty());
+    }
+
+    #[test]
+    fn test_embedded_name() {
+        assert_eq!(embedded_name("kicad-embed://QFN-32.step"), Some("QFN-32.step"));
+        assert_eq!(embedded_name("${KICAD8_3DMODEL_DIR}/x.step"), None);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_contents() {
+        let files = extract_embedded_files(SCHEMATIC).unwrap();
+        assert_eq!(files[0].contents().unwrap(), b"data");
+    }
+}
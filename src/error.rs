@@ -1,9 +1,11 @@
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
 
 /// Error types for KiCad file parsing operations
 #[derive(Debug)]
 pub enum KicadError {
     /// IO error occurred while reading files
+    #[cfg(feature = "std")]
     IoError(std::io::Error),
     
     /// Parse error with descriptive message
@@ -17,27 +19,76 @@ pub enum KicadError {
     
     /// Unexpected token encountered during parsing
     UnexpectedToken(String),
+    
+    /// The operation was cancelled through its cancellation flag
+    Cancelled,
 }
 
 impl fmt::Display for KicadError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            #[cfg(feature = "std")]
             KicadError::IoError(e) => write!(f, "IO error: {}", e),
             KicadError::ParseError(msg) => write!(f, "Parse error: {}", msg),
             KicadError::InvalidFormat(msg) => write!(f, "Invalid format: {}", msg),
             KicadError::MissingField(field) => write!(f, "Missing field: {}", field),
             KicadError::UnexpectedToken(token) => write!(f, "Unexpected token: {}", token),
+            KicadError::Cancelled => write!(f, "Cancelled"),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for KicadError {}
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for KicadError {
     fn from(error: std::io::Error) -> Self {
         KicadError::IoError(error)
     }
 }
 
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for KicadError {
+    fn from(error: serde_json::Error) -> Self {
+        KicadError::ParseError(error.to_string())
+    }
+}
+
+#[cfg(all(feature = "std", feature = "serde"))]
+impl From<bincode::Error> for KicadError {
+    fn from(error: bincode::Error) -> Self {
+        KicadError::InvalidFormat(error.to_string())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow_schema::ArrowError> for KicadError {
+    fn from(error: arrow_schema::ArrowError) -> Self {
+        KicadError::InvalidFormat(error.to_string())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<parquet::errors::ParquetError> for KicadError {
+    fn from(error: parquet::errors::ParquetError) -> Self {
+        KicadError::InvalidFormat(error.to_string())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for KicadError {
+    fn from(error: rusqlite::Error) -> Self {
+        KicadError::InvalidFormat(error.to_string())
+    }
+}
+
+#[cfg(feature = "png")]
+impl From<png::EncodingError> for KicadError {
+    fn from(error: png::EncodingError) -> Self {
+        KicadError::InvalidFormat(error.to_string())
+    }
+}
+
 /// Result type for KiCad parsing operations
-pub type Result<T> = std::result::Result<T, KicadError>;
\ No newline at end of file
+pub type Result<T> = core::result::Result<T, KicadError>;
\ No newline at end of file
@@ -0,0 +1,6 @@
+//! Board export to external data stores
+//!
+//! - [`sqlite`] - Normalized SQLite database (`sqlite` feature)
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
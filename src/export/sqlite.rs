@@ -0,0 +1,438 @@
+//! SQLite export
+//!
+//! Writes a parsed board into a normalized schema for ad-hoc SQL queries:
+//!
+//! | Table | Rows |
+//! |-------|------|
+//! | `board` | One row: version, generator, thickness, title block |
+//! | `layers` | Board layers by KiCad layer id |
+//! | `nets` | Nets by id, with their net class |
+//! | `footprints` | Placed footprints |
+//! | `footprint_properties` | Footprint fields (`footprint_id`, `name`, `value`) |
+//! | `pads` | Pads in board coordinates, referencing `footprints` and `nets` |
+//! | `pad_layers` | Layers of each pad (`*.Cu` style wildcards kept as written) |
+//! | `tracks` | Track segments and arcs (`kind`), referencing `nets` |
+//! | `vias` | Vias, referencing `nets` |
+//!
+//! Coordinates are in mm with Y pointing down, as in the board file. Nets used by
+//! items but missing from the net table are added with new ids.
+//!
+//! ```rust
+//! use kiparse::{export::sqlite, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND")
+//!   (segment (start 0 0) (end 3 4) (width 0.25) (layer "F.Cu") (net 1))
+//! )"#)?;
+//! let mut connection = rusqlite::Connection::open_in_memory()?;
+//! sqlite::write_connection(&pcb, &mut connection)?;
+//!
+//! let length: f64 = connection.query_row(
+//!     "SELECT sum(length) FROM tracks JOIN nets ON nets.id = tracks.net_id WHERE nets.name = 'GND'",
+//!     [],
+//!     |row| row.get(0),
+//! )?;
+//! assert_eq!(length, 5.0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use rusqlite::types::ToSqlOutput;
+use rusqlite::{params, Connection, ToSql, Transaction};
+
+use crate::error::Result;
+use crate::pcb::types::{PcbFile, ViaType};
+use crate::units::Length;
+
+const SCHEMA: &str = "
+CREATE TABLE board (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    version TEXT NOT NULL,
+    generator TEXT NOT NULL,
+    thickness REAL,
+    title TEXT,
+    revision TEXT,
+    date TEXT,
+    company TEXT
+);
+CREATE TABLE layers (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    type TEXT NOT NULL,
+    user_name TEXT
+);
+CREATE TABLE nets (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    net_class TEXT
+);
+CREATE TABLE footprints (
+    id INTEGER PRIMARY KEY,
+    reference TEXT NOT NULL,
+    value TEXT NOT NULL,
+    footprint TEXT NOT NULL,
+    layer TEXT NOT NULL,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    rotation REAL NOT NULL,
+    mount TEXT NOT NULL,
+    dnp INTEGER NOT NULL,
+    exclude_from_bom INTEGER NOT NULL,
+    exclude_from_pos_files INTEGER NOT NULL,
+    locked INTEGER NOT NULL,
+    uuid TEXT NOT NULL
+);
+CREATE TABLE footprint_properties (
+    footprint_id INTEGER NOT NULL REFERENCES footprints(id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (footprint_id, name)
+);
+CREATE TABLE pads (
+    id INTEGER PRIMARY KEY,
+    footprint_id INTEGER NOT NULL REFERENCES footprints(id),
+    number TEXT NOT NULL,
+    pad_type TEXT NOT NULL,
+    shape TEXT NOT NULL,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    rotation REAL NOT NULL,
+    width REAL NOT NULL,
+    height REAL NOT NULL,
+    drill REAL,
+    net_id INTEGER REFERENCES nets(id),
+    pin_function TEXT,
+    pin_type TEXT
+);
+CREATE TABLE pad_layers (
+    pad_id INTEGER NOT NULL REFERENCES pads(id),
+    layer TEXT NOT NULL,
+    PRIMARY KEY (pad_id, layer)
+);
+CREATE TABLE tracks (
+    id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('segment', 'arc')),
+    layer TEXT NOT NULL,
+    net_id INTEGER REFERENCES nets(id),
+    start_x REAL NOT NULL,
+    start_y REAL NOT NULL,
+    end_x REAL NOT NULL,
+    end_y REAL NOT NULL,
+    mid_x REAL,
+    mid_y REAL,
+    width REAL NOT NULL,
+    length REAL NOT NULL,
+    uuid TEXT NOT NULL
+);
+CREATE TABLE vias (
+    id INTEGER PRIMARY KEY,
+    x REAL NOT NULL,
+    y REAL NOT NULL,
+    size REAL NOT NULL,
+    drill REAL NOT NULL,
+    via_type TEXT NOT NULL,
+    start_layer TEXT,
+    end_layer TEXT,
+    net_id INTEGER REFERENCES nets(id),
+    uuid TEXT NOT NULL
+);
+CREATE INDEX pads_footprint ON pads(footprint_id);
+CREATE INDEX pads_net ON pads(net_id);
+CREATE INDEX tracks_net ON tracks(net_id);
+CREATE INDEX vias_net ON vias(net_id);
+";
+
+/// Lengths are stored as REAL millimeters
+impl ToSql for Length {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::from(self.as_mm()))
+    }
+}
+
+/// Create the SQLite database `path` and write `pcb` into it
+///
+/// Fails if the file already holds any of the tables.
+pub fn write(pcb: &PcbFile, path: impl AsRef<Path>) -> Result<()> {
+    let mut connection = Connection::open(path)?;
+    write_connection(pcb, &mut connection)
+}
+
+/// Create the schema on an open connection and write `pcb` in one transaction
+pub fn write_connection(pcb: &PcbFile, connection: &mut Connection) -> Result<()> {
+    let tx = connection.transaction()?;
+    tx.execute_batch(SCHEMA)?;
+    write_board(pcb, &tx)?;
+    let nets = write_nets(pcb, &tx)?;
+    write_footprints(pcb, &tx, &nets)?;
+    write_tracks(pcb, &tx, &nets)?;
+    tx.commit()?;
+    Ok(())
+}
+
+fn write_board(pcb: &PcbFile, tx: &Transaction) -> Result<()> {
+    let title_block = pcb.title_block.clone().unwrap_or_default();
+    tx.execute(
+        "INSERT INTO board VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            pcb.version,
+            pcb.generator,
+            pcb.board_thickness,
+            title_block.title,
+            title_block.revision,
+            title_block.date,
+            title_block.company,
+        ],
+    )?;
+
+    let mut insert = tx.prepare("INSERT INTO layers VALUES (?1, ?2, ?3, ?4)")?;
+    for layer in pcb.layers.values() {
+        insert.execute(params![
+            layer.id,
+            layer.name,
+            layer.layer_type,
+            layer.user_name
+        ])?;
+    }
+    Ok(())
+}
+
+/// Insert the net table plus any net only named by items; returns ids by name
+fn write_nets(pcb: &PcbFile, tx: &Transaction) -> Result<HashMap<String, u32>> {
+    let mut ids: HashMap<String, u32> = pcb
+        .nets
+        .iter()
+        .filter(|(_, name)| !name.is_empty())
+        .map(|(id, name)| (name.clone(), *id))
+        .collect();
+    let used = pcb
+        .footprints
+        .iter()
+        .flat_map(|f| f.pads.iter().map(|p| &p.net))
+        .chain(pcb.tracks.iter().map(|t| &t.net))
+        .chain(pcb.arcs.iter().map(|a| &a.net))
+        .chain(pcb.vias.iter().map(|v| &v.net))
+        .filter_map(Option::as_ref);
+    let mut next = ids.values().max().map_or(1, |id| id + 1);
+    for name in used {
+        if !ids.contains_key(name) {
+            ids.insert(name.clone(), next);
+            next += 1;
+        }
+    }
+
+    let mut insert = tx.prepare("INSERT INTO nets VALUES (?1, ?2, ?3)")?;
+    for (name, id) in &ids {
+        let class = pcb.net_class_of(name).map(|c| c.name.as_str());
+        insert.execute(params![id, name, class])?;
+    }
+    Ok(ids)
+}
+
+fn write_footprints(pcb: &PcbFile, tx: &Transaction, nets: &HashMap<String, u32>) -> Result<()> {
+    let mut footprint_insert = tx.prepare(
+        "INSERT INTO footprints VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+    )?;
+    let mut property_insert = tx.prepare("INSERT INTO footprint_properties VALUES (?1, ?2, ?3)")?;
+    let mut pad_insert = tx.prepare(
+        "INSERT INTO pads VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+    )?;
+    let mut layer_insert = tx.prepare("INSERT OR IGNORE INTO pad_layers VALUES (?1, ?2)")?;
+
+    let mut pad_id = 0;
+    for (i, footprint) in pcb.footprints.iter().enumerate() {
+        let id = i + 1;
+        let attributes = &footprint.attributes;
+        footprint_insert.execute(params![
+            id,
+            footprint.property("Reference").unwrap_or_default(),
+            footprint.property("Value").unwrap_or_default(),
+            footprint.name,
+            footprint.layer,
+            footprint.position.x,
+            footprint.position.y,
+            footprint.rotation,
+            attributes.mount,
+            attributes.dnp,
+            attributes.exclude_from_bom,
+            attributes.exclude_from_pos_files,
+            footprint.locked,
+            footprint.uuid,
+        ])?;
+        for (name, value) in &footprint.properties {
+            property_insert.execute(params![id, name, value])?;
+        }
+
+        for pad in &footprint.pads {
+            pad_id += 1;
+            let position = footprint.pad_position(pad);
+            pad_insert.execute(params![
+                pad_id,
+                id,
+                pad.number,
+                pad.pad_type,
+                pad.shape,
+                position.x,
+                position.y,
+                pad.rotation,
+                pad.size.x,
+                pad.size.y,
+                pad.drill,
+                pad.net.as_ref().and_then(|n| nets.get(n)),
+                pad.pin_function,
+                pad.pin_type,
+            ])?;
+            for layer in &pad.layers {
+                layer_insert.execute(params![pad_id, layer])?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_tracks(pcb: &PcbFile, tx: &Transaction, nets: &HashMap<String, u32>) -> Result<()> {
+    let net_id = |net: &Option<String>| net.as_ref().and_then(|n| nets.get(n)).copied();
+
+    let mut insert = tx.prepare(
+        "INSERT INTO tracks (kind, layer, net_id, start_x, start_y, end_x, end_y, mid_x, mid_y, width, length, uuid)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+    )?;
+    for t in &pcb.tracks {
+        insert.execute(params![
+            "segment",
+            t.layer,
+            net_id(&t.net),
+            t.start.x,
+            t.start.y,
+            t.end.x,
+            t.end.y,
+            None::<f64>,
+            None::<f64>,
+            t.width,
+            t.length(),
+            t.uuid,
+        ])?;
+    }
+    for a in &pcb.arcs {
+        insert.execute(params![
+            "arc",
+            a.layer,
+            net_id(&a.net),
+            a.start.x,
+            a.start.y,
+            a.end.x,
+            a.end.y,
+            a.mid.x,
+            a.mid.y,
+            a.width,
+            a.length(),
+            a.uuid,
+        ])?;
+    }
+
+    let mut insert = tx.prepare(
+        "INSERT INTO vias (x, y, size, drill, via_type, start_layer, end_layer, net_id, uuid)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+    )?;
+    for v in &pcb.vias {
+        let via_type = match v.via_type {
+            ViaType::Through => "through",
+            ViaType::BlindBuried => "blind_buried",
+            ViaType::Micro => "micro",
+        };
+        insert.execute(params![
+            v.position.x,
+            v.position.y,
+            v.size,
+            v.drill,
+            via_type,
+            v.layers.first(),
+            v.layers.last(),
+            net_id(&v.net),
+            v.uuid,
+        ])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (version 20240108) (generator "pcbnew")
+  (general (thickness 1.6))
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (title_block (title "Audit") (rev "2"))
+  (net 0 "") (net 1 "GND") (net 2 "VCC")
+  (net_class "Power" "" (clearance 0.3) (add_net "VCC"))
+  (footprint "R_0603" (layer "F.Cu") (at 10 10) (property "Reference" "R1") (property "Value" "1k")
+    (property "MPN" "RC0603") (attr smd)
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu" "F.Mask") (net 1 "GND") (pintype "passive"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu" "F.Mask") (net 2 "VCC")))
+  (footprint "TP" (layer "B.Cu") (at 20 10) (property "Reference" "TP1") (property "Value" "TP")
+    (pad "1" thru_hole circle (at 0 0) (size 1.5 1.5) (drill 0.8) (layers "*.Cu") (net 3 "SIG")))
+  (segment (start 11 10) (end 20 10) (width 0.3) (layer "F.Cu") (net 2))
+  (arc (start 0 0) (mid 1 1) (end 2 0) (width 0.2) (layer "B.Cu") (net 1))
+  (via (at 15 10) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 2))
+)"#;
+
+    #[test]
+    fn test_write_connection() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let mut connection = Connection::open_in_memory().unwrap();
+        write_connection(&pcb, &mut connection).unwrap();
+
+        let count = |sql: &str| -> i64 { connection.query_row(sql, [], |row| row.get(0)).unwrap() };
+        assert_eq!(count("SELECT count(*) FROM layers"), 2);
+        assert_eq!(count("SELECT count(*) FROM footprints"), 2);
+        assert_eq!(count("SELECT count(*) FROM pads"), 3);
+        assert_eq!(count("SELECT count(*) FROM pad_layers"), 5);
+        assert_eq!(count("SELECT count(*) FROM tracks WHERE kind = 'arc'"), 1);
+        // SIG is only named by a pad and gets a fresh id
+        assert_eq!(count("SELECT id FROM nets WHERE name = 'SIG'"), 3);
+
+        let (title, thickness): (String, f64) = connection
+            .query_row("SELECT title, thickness FROM board", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert_eq!((title.as_str(), thickness), ("Audit", 1.6));
+
+        // Everything on the VCC net, joined through the normalized tables
+        let items: Vec<String> = connection
+            .prepare(
+                "SELECT f.reference || '.' || p.number FROM pads p
+                   JOIN footprints f ON f.id = p.footprint_id
+                   JOIN nets n ON n.id = p.net_id
+                 WHERE n.net_class = 'Power'
+                 UNION ALL
+                 SELECT 'via ' || v.via_type FROM vias v JOIN nets n ON n.id = v.net_id WHERE n.name = 'VCC'",
+            )
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(items, ["R1.2", "via through"]);
+
+        let mpn: String = connection
+            .query_row(
+                "SELECT value FROM footprint_properties WHERE footprint_id = 1 AND name = 'MPN'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(mpn, "RC0603");
+    }
+
+    #[test]
+    fn test_write_existing_file_fails() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let mut connection = Connection::open_in_memory().unwrap();
+        write_connection(&pcb, &mut connection).unwrap();
+        assert!(write_connection(&pcb, &mut connection).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+//! Float methods of std for `no_std` builds, from libm
+//!
+//! Imported where the geometry calls them when std is not linked, so the code
+//! reads the same in both builds. `serde` links std through bincode, and std's
+//! own methods then take over.
+
+pub(crate) trait Float {
+    fn sqrt(self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn ceil(self) -> Self;
+    fn round(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn sin_cos(self) -> (Self, Self)
+    where
+        Self: Sized;
+    fn atan2(self, other: Self) -> Self;
+}
+
+impl Float for f64 {
+    fn sqrt(self) -> f64 {
+        libm::sqrt(self)
+    }
+
+    fn powi(self, n: i32) -> f64 {
+        libm::pow(self, n as f64)
+    }
+
+    fn ceil(self) -> f64 {
+        libm::ceil(self)
+    }
+
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+
+    fn rem_euclid(self, rhs: f64) -> f64 {
+        let r = self % rhs;
+        let modulus = if rhs < 0.0 { -rhs } else { rhs };
+        if r < 0.0 { r + modulus } else { r }
+    }
+
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+
+    fn cos(self) -> f64 {
+        libm::cos(self)
+    }
+
+    fn sin_cos(self) -> (f64, f64) {
+        libm::sincos(self)
+    }
+
+    fn atan2(self, other: f64) -> f64 {
+        libm::atan2(self, other)
+    }
+}
@@ -0,0 +1,519 @@
+//! Altium schematic library import
+//!
+//! Converts the components of an Altium schematic library, saved in its ASCII
+//! form (`|RECORD=1|LIBREFERENCE=...|` records, one per line), into KiParse
+//! [`Symbol`]s; write the result with
+//! [`write_symbol_library`](crate::symbol::write_symbol_library) to get a
+//! `.kicad_sym` file. Binary `.SchLib` files (OLE compound documents) are not
+//! read directly: save them as ASCII from the Altium library editor first.
+//!
+//! Each component (`RECORD=1`) becomes a symbol named after its library
+//! reference, with one unit per Altium part. The designator, parameters and the
+//! current footprint implementation become the Reference, custom, and Footprint
+//! properties; pins keep their designator, name (`\` overbars become `~{...}`),
+//! electrical type, length and dot/clock symbols, and rectangles, lines,
+//! polylines, polygons, arcs and ellipses become symbol drawings. Altium's
+//! 10 mil coordinate units are converted to mm; both tools have y pointing up.
+//!
+//! ```rust
+//! use kiparse::import::altium::parse_altium_schlib;
+//!
+//! let lib = parse_altium_schlib("passives", "\
+//! |HEADER=Protel for Windows - Schematic Library Editor Ascii File Version 5.0
+//! |RECORD=1|LIBREFERENCE=RES|COMPONENTDESCRIPTION=Resistor|PARTCOUNT=2|DISPLAYMODECOUNT=1
+//! |RECORD=34|OWNERINDEX=1|LOCATION.X=0|LOCATION.Y=10|TEXT=R?|NAME=Designator
+//! |RECORD=14|OWNERINDEX=1|OWNERPARTID=1|LOCATION.X=-10|LOCATION.Y=-4|CORNER.X=10|CORNER.Y=4|LINEWIDTH=1
+//! |RECORD=2|OWNERINDEX=1|OWNERPARTID=1|DESIGNATOR=1|NAME=1|ELECTRICAL=4|PINLENGTH=10|LOCATION.X=-10|LOCATION.Y=0|PINCONGLOMERATE=2
+//! ")?;
+//!
+//! let res = lib.symbol("RES").unwrap();
+//! assert_eq!(res.property("Reference"), Some("R"));
+//! assert_eq!(res.pins[0].pin_type, "passive");
+//! // Pin 1 points left from its body end, so its connection point is 2.54 mm further out
+//! assert_eq!(res.pins[0].at.x.as_mm(), -5.08);
+//! assert!(lib.to_kicad_sym().contains("(symbol \"RES_1_1\""));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::error::{KicadError, Result};
+use crate::pcb::types::{
+    Circle, Effects, Fill, Font, Pin, Point, Polyline, Property, Rectangle, Stroke, Symbol,
+    SymbolArc,
+};
+use crate::symbol::types::SymbolLib;
+use crate::units::Length;
+
+/// mm per Altium schematic coordinate unit (10 mil)
+const UNIT: f64 = 0.254;
+
+/// Stroke widths of Altium's smallest, small, medium and large lines
+const LINE_WIDTHS: [f64; 4] = [0.0, 0.254, 0.508, 0.762];
+
+/// One `|KEY=VALUE|...` record with upper-cased keys
+struct Record {
+    fields: HashMap<String, String>,
+}
+
+impl Record {
+    fn parse(line: &str) -> Record {
+        let mut fields = HashMap::new();
+        for field in line.split('|') {
+            if let Some((key, value)) = field.split_once('=') {
+                let key = key.trim().to_ascii_uppercase();
+                // %UTF8%KEY carries the Unicode form of a value stored in the ANSI codepage
+                match key.strip_prefix("%UTF8%") {
+                    Some(key) => {
+                        fields.insert(key.to_string(), value.to_string());
+                    }
+                    None => {
+                        fields.entry(key).or_insert_with(|| value.to_string());
+                    }
+                }
+            }
+        }
+        Record { fields }
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.fields.get(key).map(String::as_str)
+    }
+
+    fn int(&self, key: &str) -> i64 {
+        self.get(key).and_then(|v| v.trim().parse().ok()).unwrap_or(0)
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        matches!(self.get(key), Some("T") | Some("TRUE"))
+    }
+
+    /// Coordinate in mm from `KEY` and its `KEY_FRAC` (1/100000 unit) part
+    fn coord(&self, key: &str) -> f64 {
+        let frac = self.int(&format!("{}_FRAC", key)) as f64 / 100_000.0;
+        (self.int(key) as f64 + frac) * UNIT
+    }
+
+    fn point(&self, x: &str, y: &str) -> Point {
+        Point::new(self.coord(x), self.coord(y))
+    }
+
+    /// Unit the record belongs to; Altium's -1 (all parts) maps to 0
+    fn unit(&self) -> u32 {
+        self.int("OWNERPARTID").max(0) as u32
+    }
+
+    /// Body style from the display mode: 0 is the normal body, 1 the first alternate
+    fn body_style(&self) -> u32 {
+        self.int("OWNERPARTDISPLAYMODE").max(0) as u32 + 1
+    }
+
+    fn stroke(&self) -> Stroke {
+        Stroke {
+            width: Length::mm(LINE_WIDTHS[self.int("LINEWIDTH").clamp(0, 3) as usize]),
+            stroke_type: "default".to_string(),
+            color: None,
+        }
+    }
+
+    /// Solid shapes take the body background fill
+    fn fill(&self) -> Fill {
+        Fill {
+            fill_type: if self.flag("ISSOLID") { "background" } else { "none" }.to_string(),
+            color: None,
+        }
+    }
+
+    /// Vertices of a polyline or polygon: `LOCATIONCOUNT` with `X1`/`Y1`, `X2`/`Y2`, ...
+    fn vertices(&self) -> Vec<Point> {
+        (1..=self.int("LOCATIONCOUNT"))
+            .map(|i| self.point(&format!("X{}", i), &format!("Y{}", i)))
+            .collect()
+    }
+}
+
+/// KiCad pin electrical type for Altium's `ELECTRICAL` code
+fn pin_type(code: i64) -> &'static str {
+    match code {
+        0 => "input",
+        1 => "bidirectional",
+        2 => "output",
+        3 => "open_collector",
+        4 => "passive",
+        5 => "tri_state",
+        6 => "open_emitter",
+        7 => "power_in",
+        _ => "unspecified",
+    }
+}
+
+/// Altium marks overbarred characters with a trailing `\`: `R\S\T\` -> `~{RST}`
+fn pin_name(name: &str) -> String {
+    if !name.contains('\\') {
+        return name.to_string();
+    }
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    let mut in_bar = false;
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '\\' {
+            i += 1;
+            continue;
+        }
+        let barred = chars.get(i + 1) == Some(&'\\');
+        if barred != in_bar {
+            out.push_str(if barred { "~{" } else { "}" });
+            in_bar = barred;
+        }
+        out.push(chars[i]);
+        i += 1;
+    }
+    if in_bar {
+        out.push('}');
+    }
+    out
+}
+
+fn effects(hide: bool) -> Option<Effects> {
+    Some(Effects {
+        font: Font {
+            size: Point::new(1.27, 1.27),
+            thickness: None,
+            bold: false,
+            italic: false,
+        },
+        justify: None,
+        hide,
+    })
+}
+
+fn new_symbol(component: &Record) -> Symbol {
+    Symbol {
+        name: component.get("LIBREFERENCE").unwrap_or_default().to_string(),
+        extends: None,
+        pin_names_offset: Length::mm(0.508),
+        pin_names_hidden: false,
+        pin_numbers_hidden: false,
+        power: false,
+        in_bom: true,
+        on_board: true,
+        exclude_from_sim: false,
+        // PARTCOUNT counts one more than the number of parts
+        unit_count: (component.int("PARTCOUNT") - 1).max(1) as u32,
+        unit_names: HashMap::new(),
+        properties: Vec::new(),
+        pins: Vec::new(),
+        rectangles: Vec::new(),
+        circles: Vec::new(),
+        arcs: Vec::new(),
+        polylines: Vec::new(),
+    }
+}
+
+fn set_property(symbol: &mut Symbol, name: &str, value: &str, at: Point, hide: bool) {
+    match symbol.properties.iter_mut().find(|p| p.name == name) {
+        Some(property) => {
+            property.value = value.to_string();
+            property.at = at;
+        }
+        None => symbol.properties.push(Property {
+            name: name.to_string(),
+            value: value.to_string(),
+            id: symbol.properties.len() as i32,
+            at,
+            effects: effects(hide),
+        }),
+    }
+}
+
+/// Add one drawing or pin record to the symbol being built
+fn add_record(symbol: &mut Symbol, record: &Record) {
+    let (unit, body_style) = (record.unit(), record.body_style());
+    match record.int("RECORD") {
+        // Pin: LOCATION is the body end, the pin extends PINLENGTH outwards
+        2 => {
+            let conglomerate = record.int("PINCONGLOMERATE");
+            let direction = (conglomerate & 0x03) as f64 * 90.0;
+            let length = record.coord("PINLENGTH");
+            let body = record.point("LOCATION.X", "LOCATION.Y");
+            let (sin, cos) = direction.to_radians().sin_cos();
+            let shape = match (record.int("SYMBOL_OUTEREDGE") == 1, record.int("SYMBOL_INNEREDGE") == 3) {
+                (true, true) => "inverted_clock",
+                (true, false) => "inverted",
+                (false, true) => "clock",
+                (false, false) => "line",
+            };
+            symbol.pins.push(Pin {
+                number: record.get("DESIGNATOR").unwrap_or_default().to_string(),
+                name: pin_name(record.get("NAME").unwrap_or_default()),
+                pin_type: pin_type(record.int("ELECTRICAL")).to_string(),
+                shape: shape.to_string(),
+                at: Point::new(round(body.x.as_mm() + length * cos), round(body.y.as_mm() + length * sin)),
+                length: Length::mm(length),
+                // KiCad pins point from the connection point back to the body
+                rotation: (direction + 180.0) % 360.0,
+                hidden: conglomerate & 0x04 != 0,
+                unit,
+                body_style,
+                name_effects: None,
+                number_effects: None,
+            });
+        }
+        // Rectangle and rounded rectangle
+        14 | 10 => symbol.rectangles.push(Rectangle {
+            start: record.point("LOCATION.X", "LOCATION.Y"),
+            end: record.point("CORNER.X", "CORNER.Y"),
+            stroke: record.stroke(),
+            fill: record.fill(),
+            unit,
+            body_style,
+        }),
+        // Line
+        13 => symbol.polylines.push(Polyline {
+            points: vec![record.point("LOCATION.X", "LOCATION.Y"), record.point("CORNER.X", "CORNER.Y")],
+            stroke: record.stroke(),
+            fill: Fill { fill_type: "none".to_string(), color: None },
+            unit,
+            body_style,
+        }),
+        // Polyline, and polygon which closes on its first vertex
+        6 | 7 => {
+            let mut points = record.vertices();
+            let closed = record.int("RECORD") == 7;
+            if closed && points.len() > 2 && points.first() != points.last() {
+                points.push(points[0].clone());
+            }
+            symbol.polylines.push(Polyline {
+                points,
+                stroke: record.stroke(),
+                fill: if closed { record.fill() } else { Fill { fill_type: "none".to_string(), color: None } },
+                unit,
+                body_style,
+            });
+        }
+        // Arc, counterclockwise from STARTANGLE to ENDANGLE around LOCATION
+        12 => {
+            let center = record.point("LOCATION.X", "LOCATION.Y");
+            let radius = record.coord("RADIUS");
+            let start: f64 = record.get("STARTANGLE").and_then(|a| a.parse().ok()).unwrap_or(0.0);
+            let mut end: f64 = record.get("ENDANGLE").and_then(|a| a.parse().ok()).unwrap_or(360.0);
+            if end <= start {
+                end += 360.0;
+            }
+            if end - start >= 360.0 {
+                symbol.circles.push(Circle {
+                    center,
+                    radius: Length::mm(radius),
+                    stroke: record.stroke(),
+                    fill: Fill { fill_type: "none".to_string(), color: None },
+                    unit,
+                    body_style,
+                });
+            } else {
+                let at = |angle: f64| {
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    Point::new(round(center.x.as_mm() + radius * cos), round(center.y.as_mm() + radius * sin))
+                };
+                symbol.arcs.push(SymbolArc {
+                    start: at(start),
+                    mid: at((start + end) / 2.0),
+                    end: at(end),
+                    stroke: record.stroke(),
+                    fill: Fill { fill_type: "none".to_string(), color: None },
+                    unit,
+                    body_style,
+                });
+            }
+        }
+        // Ellipse; KiCad symbols have no ellipses, so it is drawn as a circle of the X radius
+        8 => symbol.circles.push(Circle {
+            center: record.point("LOCATION.X", "LOCATION.Y"),
+            radius: Length::mm(record.coord("RADIUS")),
+            stroke: record.stroke(),
+            fill: record.fill(),
+            unit,
+            body_style,
+        }),
+        // Designator, e.g. U? -> Reference "U"
+        34 => {
+            let text = record.get("TEXT").unwrap_or_default().trim_end_matches('?');
+            let at = record.point("LOCATION.X", "LOCATION.Y");
+            set_property(symbol, "Reference", text, at, false);
+        }
+        // Parameter; values starting with = refer to other parameters and are skipped
+        41 => {
+            let (Some(name), Some(value)) = (record.get("NAME"), record.get("TEXT")) else {
+                return;
+            };
+            if value.starts_with('=') || value == "*" {
+                return;
+            }
+            let name = if name.eq_ignore_ascii_case("comment") { "Value" } else { name };
+            let at = record.point("LOCATION.X", "LOCATION.Y");
+            set_property(symbol, name, value, at, record.flag("ISHIDDEN"));
+        }
+        // Footprint implementation; the current one wins
+        45 if record.get("MODELTYPE") == Some("PCBLIB") => {
+            let current = record.flag("ISCURRENT");
+            let has_footprint = symbol.properties.iter().any(|p| p.name == "Footprint" && !p.value.is_empty());
+            if current || !has_footprint {
+                let name = record.get("MODELNAME").unwrap_or_default();
+                set_property(symbol, "Footprint", name, Point::default(), true);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Drop float noise from unit conversions, e.g. 7.619999999 -> 7.62
+fn round(value: f64) -> f64 {
+    (value * 1e6).round() / 1e6
+}
+
+/// Parse an Altium ASCII schematic library into a [`SymbolLib`] named `name`
+pub fn parse_altium_schlib(name: &str, content: &str) -> Result<SymbolLib> {
+    let records: Vec<Record> = content
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.starts_with('|'))
+        .map(Record::parse)
+        .collect();
+    if !records.iter().any(|r| r.get("HEADER").is_some() || r.get("RECORD").is_some()) {
+        return Err(KicadError::InvalidFormat(
+            "No Altium records found; save binary .SchLib files as ASCII first".to_string(),
+        ));
+    }
+
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for record in &records {
+        if record.int("RECORD") == 1 {
+            let mut symbol = new_symbol(record);
+            let description = record.get("COMPONENTDESCRIPTION").unwrap_or_default();
+            // KiCad expects the four mandatory fields in this order
+            for field in ["Reference", "Value", "Footprint", "Datasheet"] {
+                set_property(&mut symbol, field, "", Point::default(), field != "Reference" && field != "Value");
+            }
+            let value = symbol.name.clone();
+            set_property(&mut symbol, "Value", &value, Point::default(), false);
+            set_property(&mut symbol, "Description", description, Point::default(), true);
+            symbols.push(symbol);
+        } else if let Some(symbol) = symbols.last_mut() {
+            add_record(symbol, record);
+        }
+    }
+
+    for symbol in &mut symbols {
+        symbol.unit_count = symbol
+            .pins
+            .iter()
+            .map(|p| p.unit)
+            .fold(symbol.unit_count, u32::max);
+        if symbol.unit_count > 1 {
+            // Altium names parts A, B, C, ...
+            for unit in 1..=symbol.unit_count.min(26) {
+                let letter = char::from(b'A' + (unit - 1) as u8);
+                symbol.unit_names.insert(unit, letter.to_string());
+            }
+        }
+    }
+
+    Ok(SymbolLib {
+        name: name.to_string(),
+        version: crate::symbol::writer::DEFAULT_VERSION.to_string(),
+        generator: "altium".to_string(),
+        symbols,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::lib_parser::parse_symbol_library;
+
+    const LIBRARY: &str = "\
+|HEADER=Protel for Windows - Schematic Library Editor Ascii File Version 5.0|WEIGHT=20
+|RECORD=1|LIBREFERENCE=LM358|COMPONENTDESCRIPTION=Dual op-amp|PARTCOUNT=3|DISPLAYMODECOUNT=1|CURRENTPARTID=1
+|RECORD=7|OWNERINDEX=1|OWNERPARTID=1|ISSOLID=T|LINEWIDTH=1|LOCATIONCOUNT=3|X1=-20|Y1=20|X2=20|Y2=0|X3=-20|Y3=-20
+|RECORD=2|OWNERINDEX=1|OWNERPARTID=1|ELECTRICAL=2|PINCONGLOMERATE=0|PINLENGTH=30|LOCATION.X=20|LOCATION.Y=0|NAME=OUT|DESIGNATOR=1
+|RECORD=2|OWNERINDEX=1|OWNERPARTID=1|ELECTRICAL=0|PINCONGLOMERATE=2|PINLENGTH=30|LOCATION.X=-20|LOCATION.Y=10|NAME=+|DESIGNATOR=3
+|RECORD=2|OWNERINDEX=1|OWNERPARTID=2|ELECTRICAL=0|PINCONGLOMERATE=2|PINLENGTH=30|LOCATION.X=-20|LOCATION.Y=-10|NAME=-|DESIGNATOR=6|SYMBOL_OUTEREDGE=1
+|RECORD=2|OWNERINDEX=1|OWNERPARTID=-1|ELECTRICAL=7|PINCONGLOMERATE=5|PINLENGTH=20|LOCATION.X=0|LOCATION.Y=20|LOCATION.Y_FRAC=50000|NAME=V\\+\\|DESIGNATOR=8
+|RECORD=12|OWNERINDEX=1|OWNERPARTID=2|LOCATION.X=0|LOCATION.Y=0|RADIUS=5|STARTANGLE=0.000|ENDANGLE=180.000
+|RECORD=8|OWNERINDEX=1|OWNERPARTID=2|LOCATION.X=0|LOCATION.Y=0|RADIUS=2|SECONDARYRADIUS=2
+|RECORD=34|OWNERINDEX=1|LOCATION.X=-20|LOCATION.Y=25|TEXT=U?|NAME=Designator
+|RECORD=41|OWNERINDEX=1|NAME=Comment|TEXT=LM358
+|RECORD=41|OWNERINDEX=1|NAME=Manufacturer|TEXT=TI|%UTF8%TEXT=Texas Instruments|ISHIDDEN=T
+|RECORD=41|OWNERINDEX=1|NAME=Value|TEXT==Comment
+|RECORD=45|OWNERINDEX=1|MODELNAME=SO8|MODELTYPE=PCBLIB|ISCURRENT=F
+|RECORD=45|OWNERINDEX=1|MODELNAME=SOIC-8_3.9x4.9mm|MODELTYPE=PCBLIB|ISCURRENT=T
+|RECORD=45|OWNERINDEX=1|MODELNAME=LM358.SPICE|MODELTYPE=SIM
+|RECORD=1|LIBREFERENCE=LED|PARTCOUNT=2
+|RECORD=2|OWNERINDEX=2|OWNERPARTID=1|ELECTRICAL=4|PINCONGLOMERATE=0|PINLENGTH=10|LOCATION.X=10|LOCATION.Y=0|NAME=K|DESIGNATOR=1
+";
+
+    #[test]
+    fn test_parse_components() {
+        let lib = parse_altium_schlib("altium", LIBRARY).unwrap();
+        assert_eq!(lib.symbols.len(), 2);
+
+        let opamp = lib.symbol("LM358").unwrap();
+        assert_eq!(opamp.unit_count, 2);
+        assert_eq!(opamp.unit_names.get(&2).map(String::as_str), Some("B"));
+        assert_eq!(opamp.property("Reference"), Some("U"));
+        assert_eq!(opamp.property("Value"), Some("LM358"));
+        assert_eq!(opamp.property("Footprint"), Some("SOIC-8_3.9x4.9mm"));
+        assert_eq!(opamp.property("Manufacturer"), Some("Texas Instruments"));
+        assert_eq!(opamp.description(), Some("Dual op-amp"));
+        let names: Vec<_> = opamp.properties.iter().take(4).map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Reference", "Value", "Footprint", "Datasheet"]);
+
+        let out = opamp.pin("1").unwrap();
+        assert_eq!((out.pin_type.as_str(), out.unit), ("output", 1));
+        assert_eq!(out.at, Point::new(12.7, 0.0));
+        assert_eq!(out.rotation, 180.0);
+        assert!((out.body_end().x.as_mm() - 5.08).abs() < 1e-9);
+        let inverting = opamp.pin("6").unwrap();
+        assert_eq!((inverting.shape.as_str(), inverting.unit), ("inverted", 2));
+        let supply = opamp.pin("8").unwrap();
+        assert_eq!(supply.name, "~{V+}");
+        assert_eq!((supply.unit, supply.pin_type.as_str()), (0, "power_in"));
+        assert!(supply.hidden);
+        assert_eq!(supply.rotation, 270.0);
+        assert!((supply.at.y.as_mm() - 10.287).abs() < 1e-9);
+
+        assert_eq!(opamp.polylines[0].points.len(), 4);
+        assert_eq!(opamp.polylines[0].fill.fill_type, "background");
+        assert_eq!(opamp.polylines[0].stroke.width, Length::mm(0.254));
+        assert_eq!(opamp.arcs[0].mid, Point::new(0.0, 1.27));
+        assert_eq!(opamp.circles[0].radius, Length::mm(0.508));
+        assert_eq!(opamp.circles[0].unit, 2);
+
+        assert_eq!(lib.symbols[1].pins.len(), 1);
+        assert!(lib.symbols[1].unit_names.is_empty());
+    }
+
+    #[test]
+    fn test_converts_to_kicad_sym() {
+        let lib = parse_altium_schlib("altium", LIBRARY).unwrap();
+        let text = lib.to_kicad_sym();
+        let reparsed = parse_symbol_library("altium", &text).unwrap();
+        for (written, imported) in reparsed.symbols.iter().zip(&lib.symbols) {
+            assert_eq!(written.properties, imported.properties);
+            assert_eq!((written.unit_count, &written.unit_names), (imported.unit_count, &imported.unit_names));
+            // Pins come back grouped by unit
+            for pin in &imported.pins {
+                assert_eq!(written.pin(&pin.number), Some(pin));
+            }
+        }
+        assert!(text.contains("(symbol \"LM358_2_1\"\n\t\t\t(unit_name \"B\")"));
+    }
+
+    #[test]
+    fn test_pin_names_and_errors() {
+        assert_eq!(pin_name("R\\S\\T\\"), "~{RST}");
+        assert_eq!(pin_name("C\\S\\/D"), "~{CS}/D");
+        assert_eq!(pin_name("A\\"), "~{A}");
+        assert!(parse_altium_schlib("x", "\u{d0}\u{cf}\u{11}\u{e0}binary").is_err());
+    }
+}
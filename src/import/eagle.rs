@@ -0,0 +1,851 @@
+//! Eagle XML board import
+//!
+//! Reads Eagle 6+ `.brd` files (XML) into the same [`PcbFile`] model the KiCad
+//! parser produces, so the analysis passes and CAM exporters work on legacy
+//! Eagle designs too. Eagle layers are mapped to their KiCad equivalents
+//! (Top -> `F.Cu`, tPlace -> `F.SilkS`, Dimension -> `Edge.Cuts`, ...), the y
+//! axis is flipped to KiCad's downward convention, and parts placed mirrored
+//! (`MR90`) end up on the bottom side. Elements become footprints with the
+//! pads, graphics and texts of their package, signals provide the nets, tracks,
+//! vias and zones, and net classes are taken over with their widths and
+//! clearances.
+//!
+//! Eagle does not store poured copper, so zones come without `filled_polygons`;
+//! items on layers without a KiCad counterpart (restrict, unrouted, ...) are
+//! dropped. Board-level holes become board-only footprints with one
+//! non-plated pad, as KiCad's own Eagle importer does.
+//!
+//! ```rust
+//! use kiparse::import::eagle::parse_eagle_board;
+//!
+//! let pcb = parse_eagle_board(r#"<?xml version="1.0" encoding="utf-8"?>
+//! <eagle version="9.6.2"><drawing><board>
+//!   <plain><wire x1="0" y1="0" x2="20" y2="0" width="0" layer="20"/></plain>
+//!   <libraries><library name="rcl"><packages><package name="R0603">
+//!     <smd name="1" x="-0.8" y="0" dx="0.9" dy="0.8" layer="1"/>
+//!     <smd name="2" x="0.8" y="0" dx="0.9" dy="0.8" layer="1"/>
+//!   </package></packages></library></libraries>
+//!   <elements><element name="R1" library="rcl" package="R0603" value="10k" x="10" y="5"/></elements>
+//!   <signals><signal name="GND"><contactref element="R1" pad="1"/>
+//!     <wire x1="9.2" y1="5" x2="5" y2="5" width="0.25" layer="1"/>
+//!   </signal></signals>
+//! </board></drawing></eagle>"#)?;
+//!
+//! let r1 = &pcb.footprints[0];
+//! assert_eq!(r1.property("Reference"), Some("R1"));
+//! assert_eq!(r1.position.y.as_mm(), -5.0);
+//! assert_eq!(r1.pads[0].net.as_deref(), Some("GND"));
+//! assert_eq!(pcb.tracks[0].layer, "F.Cu");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::xml::{parse_xml, Element};
+use crate::error::{KicadError, Result};
+use crate::pcb::builder::{flip_layer, layer_id};
+use crate::pcb::geometry::arc_from_points;
+use crate::pcb::types::*;
+use crate::units::Length;
+
+/// KiCad layer an Eagle layer number maps to; copper layers 2-15 become inner layers
+fn kicad_layer(number: u32) -> Option<String> {
+    let name = match number {
+        1 => "F.Cu",
+        2..=15 => return Some(format!("In{}.Cu", number - 1)),
+        16 => "B.Cu",
+        20 | 46 => "Edge.Cuts",
+        21 | 25 => "F.SilkS",
+        22 | 26 => "B.SilkS",
+        27 | 51 => "F.Fab",
+        28 | 52 => "B.Fab",
+        29 => "F.Mask",
+        30 => "B.Mask",
+        31 => "F.Paste",
+        32 => "B.Paste",
+        35 => "F.Adhes",
+        36 => "B.Adhes",
+        39 => "F.CrtYd",
+        40 => "B.CrtYd",
+        47 => "Dwgs.User",
+        48 | 49 => "Cmts.User",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Parse an Eagle rotation such as `R90`, `MR270` or `SMR45` into (degrees, mirrored)
+fn rotation(element: &Element) -> (f64, bool) {
+    let rot = element.attr("rot").unwrap_or("R0");
+    let mirror = rot.contains('M');
+    let degrees = rot
+        .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+        .parse()
+        .unwrap_or(0.0);
+    (degrees, mirror)
+}
+
+/// Eagle y-up coordinates to KiCad y-down, mirrored about the y axis for bottom parts
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    mirror: bool,
+}
+
+impl Frame {
+    const BOARD: Frame = Frame { mirror: false };
+
+    fn point(self, x: f64, y: f64) -> Point {
+        Point::new(if self.mirror { -x } else { x }, -y)
+    }
+
+    fn xy(self, element: &Element, x: &str, y: &str) -> Point {
+        self.point(element.attr_f64(x).unwrap_or(0.0), element.attr_f64(y).unwrap_or(0.0))
+    }
+
+    /// KiCad layer of an item, flipped to the bottom for mirrored parts
+    fn layer(self, element: &Element) -> Option<String> {
+        let layer = kicad_layer(element.attr("layer")?.parse().ok()?)?;
+        Some(if self.mirror { flip_layer(&layer) } else { layer })
+    }
+}
+
+/// Center, radius and start angle (radians) of an Eagle arc from `a` to `b`
+/// sweeping `curve` degrees counterclockwise
+fn arc_center(a: (f64, f64), b: (f64, f64), curve: f64) -> Option<((f64, f64), f64, f64)> {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let chord = dx.hypot(dy);
+    if chord == 0.0 || curve == 0.0 {
+        return None;
+    }
+    // The center lies on the chord's perpendicular bisector, left of it for positive sweeps
+    let offset = (chord / 2.0) / (curve.to_radians() / 2.0).tan();
+    let center = (
+        (a.0 + b.0) / 2.0 - dy / chord * offset,
+        (a.1 + b.1) / 2.0 + dx / chord * offset,
+    );
+    let radius = (a.0 - center.0).hypot(a.1 - center.1);
+    Some((center, radius, (a.1 - center.1).atan2(a.0 - center.0)))
+}
+
+/// Points along an arc from `a` (excluded) to `b` (included), one per 10 degrees
+fn arc_points(a: (f64, f64), b: (f64, f64), curve: f64) -> Vec<(f64, f64)> {
+    let Some((center, radius, start)) = arc_center(a, b, curve) else {
+        return vec![b];
+    };
+    let steps = ((curve.abs() / 10.0).ceil() as usize).max(1);
+    (1..=steps)
+        .map(|i| {
+            let angle = start + (curve * i as f64 / steps as f64).to_radians();
+            (center.0 + radius * angle.cos(), center.1 + radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Outline of an Eagle `polygon`, with curved edges flattened
+fn vertices(polygon: &Element, frame: Frame) -> Vec<Point> {
+    let corners: Vec<((f64, f64), f64)> = polygon
+        .children("vertex")
+        .map(|v| {
+            let xy = (v.attr_f64("x").unwrap_or(0.0), v.attr_f64("y").unwrap_or(0.0));
+            (xy, v.attr_f64("curve").unwrap_or(0.0))
+        })
+        .collect();
+    let mut points = Vec::new();
+    for (i, &(xy, curve)) in corners.iter().enumerate() {
+        points.push(frame.point(xy.0, xy.1));
+        if curve != 0.0 {
+            let next = corners[(i + 1) % corners.len()].0;
+            let arc = arc_points(xy, next, curve);
+            // The arc end is the next vertex, which is pushed on its own
+            points.extend(arc[..arc.len() - 1].iter().map(|p| frame.point(p.0, p.1)));
+        }
+    }
+    points
+}
+
+/// Pour settings of an Eagle `polygon`; `spacing` is measured between hatch line centres
+fn polygon_fill(polygon: &Element) -> ZoneFill {
+    let width = polygon.attr_f64("width").unwrap_or(0.0);
+    ZoneFill {
+        mode: match polygon.attr("pour") {
+            Some("hatch") => ZoneFillMode::Hatch {
+                thickness: Length::mm(width),
+                gap: Length::mm((polygon.attr_f64("spacing").unwrap_or(0.0) - width).max(0.0)),
+                orientation: 0.0,
+            },
+            _ => ZoneFillMode::Solid,
+        },
+        island_removal: if polygon.attr("orphans") == Some("yes") { IslandRemoval::Never } else { IslandRemoval::Always },
+        ..ZoneFill::default()
+    }
+}
+
+/// Eagle `align` (`bottom-left`, `center`, `top-right`, ...) as a KiCad justify string
+fn justify(align: Option<&str>) -> Option<String> {
+    let (vertical, horizontal) = match align.unwrap_or("bottom-left") {
+        "center" => return None,
+        align => align.split_once('-').unwrap_or((align, "center")),
+    };
+    let parts: Vec<&str> = [horizontal, vertical]
+        .into_iter()
+        .filter(|p| *p != "center")
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(" "))
+}
+
+fn text(element: &Element, frame: Frame, content: String) -> Option<Text> {
+    let size = element.attr_f64("size").unwrap_or(1.27);
+    Some(Text {
+        text: content,
+        position: frame.xy(element, "x", "y"),
+        layer: frame.layer(element)?,
+        effects: TextEffects {
+            font_size: Point::new(size, size),
+            // Eagle gives the stroke width as a percentage of the size
+            thickness: Length::mm(size * element.attr_f64("ratio").unwrap_or(8.0) / 100.0),
+            bold: false,
+            italic: false,
+            justify: justify(element.attr("align")),
+        },
+    })
+}
+
+/// Board or package drawing element as a [`Graphic`]
+fn graphic(element: &Element, frame: Frame) -> Option<Graphic> {
+    let layer = frame.layer(element)?;
+    let width = Length::mm(element.attr_f64("width").unwrap_or(0.0));
+    match element.name.as_str() {
+        "wire" => {
+            let a = (element.attr_f64("x1")?, element.attr_f64("y1")?);
+            let b = (element.attr_f64("x2")?, element.attr_f64("y2")?);
+            let (start, end) = (frame.point(a.0, a.1), frame.point(b.0, b.1));
+            match arc_mid(a, b, element.attr_f64("curve").unwrap_or(0.0)) {
+                Some(mid) => Some(Graphic::Arc {
+                    arc: arc_from_points(&start, &frame.point(mid.0, mid.1), &end)?,
+                    layer,
+                    width,
+                    uuid: String::new(),
+                }),
+                None => Some(Graphic::Line { start, end, layer, width, uuid: String::new() }),
+            }
+        }
+        "circle" => Some(Graphic::Circle {
+            center: frame.xy(element, "x", "y"),
+            radius: Length::mm(element.attr_f64("radius")?),
+            layer,
+            width,
+            // A zero-width circle is a filled disc in Eagle
+            filled: width == Length::ZERO,
+            uuid: String::new(),
+        }),
+        "rectangle" => {
+            let (x1, y1) = (element.attr_f64("x1")?, element.attr_f64("y1")?);
+            let (x2, y2) = (element.attr_f64("x2")?, element.attr_f64("y2")?);
+            let (angle, _) = rotation(element);
+            let (sin, cos) = angle.to_radians().sin_cos();
+            let (cx, cy) = ((x1 + x2) / 2.0, (y1 + y2) / 2.0);
+            // Rectangles rotate about their center
+            let points: Vec<Point> = [(x1, y1), (x2, y1), (x2, y2), (x1, y2)]
+                .iter()
+                .map(|&(x, y)| {
+                    let (dx, dy) = (x - cx, y - cy);
+                    frame.point(cx + dx * cos - dy * sin, cy + dx * sin + dy * cos)
+                })
+                .collect();
+            if angle.rem_euclid(90.0) == 0.0 {
+                let (start, end) = crate::pcb::geometry::bounds(&points);
+                Some(Graphic::Rectangle { rect: Rect { start, end }, layer, width: Length::ZERO, filled: true, uuid: String::new() })
+            } else {
+                Some(Graphic::Polygon { points, layer, width: Length::ZERO, filled: true, uuid: String::new() })
+            }
+        }
+        "polygon" => Some(Graphic::Polygon {
+            points: vertices(element, frame),
+            layer,
+            width,
+            filled: true,
+            uuid: String::new(),
+        }),
+        _ => None,
+    }
+}
+
+/// Point halfway along a curved wire, `None` for straight wires
+fn arc_mid(a: (f64, f64), b: (f64, f64), curve: f64) -> Option<(f64, f64)> {
+    let (center, radius, start) = arc_center(a, b, curve)?;
+    let angle = start + (curve / 2.0).to_radians();
+    Some((center.0 + radius * angle.cos(), center.1 + radius * angle.sin()))
+}
+
+/// Board design rules, with lengths converted to mm
+struct DesignRules {
+    params: HashMap<String, String>,
+}
+
+impl DesignRules {
+    fn new(board: &Element) -> Self {
+        let params = board
+            .child("designrules")
+            .map(|rules| {
+                rules
+                    .children("param")
+                    .filter_map(|p| Some((p.attr("name")?.to_string(), p.attr("value")?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        DesignRules { params }
+    }
+
+    /// Length parameter such as `10mil` or `0.2mm` in mm; bare numbers are mm or ratios
+    fn length(&self, name: &str, default: f64) -> f64 {
+        let Some(value) = self.params.get(name).map(|v| v.trim()) else {
+            return default;
+        };
+        Length::parse(value).map_or(default, Length::as_mm)
+    }
+
+    /// Annular ring width Eagle derives from a drill: a ratio of it, clamped
+    fn restring(&self, kind: &str, drill: f64) -> f64 {
+        let ratio = self.length(&format!("rv{}", kind), 0.25);
+        // Eagle's defaults: 10 mil minimum on pads, 8 mil on vias
+        let min = self.length(&format!("rlMin{}", kind), if kind == "ViaOuter" { 0.2032 } else { 0.254 });
+        let max = self.length(&format!("rlMax{}", kind), 0.508);
+        (drill * ratio).max(min).min(max)
+    }
+
+    /// Copper layers from the `layerSetup` parameter, e.g. `(1*2*15*16)`
+    fn copper_layers(&self) -> BTreeSet<u32> {
+        let setup = self.params.get("layerSetup").map_or("(1*16)", String::as_str);
+        setup
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|n| n.parse().ok())
+            .filter(|n| (1..=16).contains(n))
+            .collect()
+    }
+}
+
+/// Convert a `smd`, `pad` or `hole` of a package into a [`Pad`]
+fn pad(element: &Element, frame: Frame, angle: f64, rules: &DesignRules) -> Option<Pad> {
+    let (pad_angle, _) = rotation(element);
+    let orientation = (angle + if frame.mirror { -pad_angle } else { pad_angle }).rem_euclid(360.0);
+    let position = frame.xy(element, "x", "y");
+    let mut pad = Pad {
+        number: element.attr("name").unwrap_or_default().to_string(),
+        pad_type: String::new(),
+        shape: String::new(),
+        position,
+        rotation: orientation,
+        size: Point::default(),
+        drill: element.attr_f64("drill").map(Length::mm),
+        drill_oval: None,
+        layers: Vec::new(),
+        net: None,
+        pin_function: None,
+        pin_type: None,
+        die_length: None,
+        roundrect_ratio: None,
+        margins: MaskPasteMargins::default(),
+    };
+    let masked = element.attr("stop") != Some("no");
+    match element.name.as_str() {
+        "smd" => {
+            let (dx, dy) = (element.attr_f64("dx")?, element.attr_f64("dy")?);
+            let roundness = element.attr_f64("roundness").unwrap_or(0.0);
+            pad.pad_type = "smd".to_string();
+            pad.size = Point::new(dx, dy);
+            pad.shape = match roundness {
+                r if r <= 0.0 => "rect",
+                r if r >= 100.0 && dx == dy => "circle",
+                r if r >= 100.0 => "oval",
+                r => {
+                    pad.roundrect_ratio = Some(r / 200.0);
+                    "roundrect"
+                }
+            }
+            .to_string();
+            let copper = frame.layer(element)?;
+            let side = copper.strip_suffix(".Cu").unwrap_or("F");
+            pad.layers.push(copper.clone());
+            if element.attr("cream") != Some("no") {
+                pad.layers.push(format!("{}.Paste", side));
+            }
+            if masked {
+                pad.layers.push(format!("{}.Mask", side));
+            }
+        }
+        "pad" => {
+            let drill = pad.drill.map_or(0.0, Length::as_mm);
+            let diameter = element
+                .attr_f64("diameter")
+                .filter(|d| *d > 0.0)
+                .unwrap_or_else(|| drill + 2.0 * rules.restring("PadTop", drill));
+            pad.pad_type = "thru_hole".to_string();
+            let (shape, size) = match element.attr("shape").unwrap_or("round") {
+                "square" => ("rect", Point::new(diameter, diameter)),
+                "long" | "offset" => ("oval", Point::new(2.0 * diameter, diameter)),
+                // Octagons have no KiCad pad shape; the circle is the closest fit
+                _ => ("circle", Point::new(diameter, diameter)),
+            };
+            pad.shape = shape.to_string();
+            pad.size = size;
+            pad.layers.push("*.Cu".to_string());
+            if masked {
+                pad.layers.push("*.Mask".to_string());
+            }
+        }
+        "hole" => {
+            let drill = pad.drill?;
+            pad.number = String::new();
+            pad.pad_type = "np_thru_hole".to_string();
+            pad.shape = "circle".to_string();
+            pad.size = Point { x: drill, y: drill };
+            pad.layers = vec!["*.Cu".to_string(), "*.Mask".to_string()];
+        }
+        _ => return None,
+    }
+    Some(pad)
+}
+
+fn empty_footprint(name: String, position: Point, rotation: f64, layer: &str) -> Footprint {
+    Footprint {
+        name,
+        uuid: String::new(),
+        position,
+        rotation,
+        layer: layer.to_string(),
+        locked: false,
+        placed: false,
+        attributes: FootprintAttributes::default(),
+        properties: HashMap::new(),
+        path: None,
+        sheet_name: None,
+        sheet_file: None,
+        net_tie_pad_groups: Vec::new(),
+        margins: MaskPasteMargins::default(),
+        fields: Vec::new(),
+        pads: Vec::new(),
+        graphics: Vec::new(),
+        texts: Vec::new(),
+        models: Vec::new(),
+        embedded_files: Vec::new(),
+    }
+}
+
+/// Footprint for a placed element, with the pads and drawings of its package
+fn footprint(
+    element: &Element,
+    package: &Element,
+    rules: &DesignRules,
+    contacts: &HashMap<(&str, &str), &str>,
+) -> Footprint {
+    let name = element.attr("name").unwrap_or_default();
+    let value = element.attr("value").unwrap_or_default();
+    let (angle, mirror) = rotation(element);
+    let frame = Frame { mirror };
+    let footprint_name = format!(
+        "{}:{}",
+        element.attr("library").unwrap_or_default(),
+        element.attr("package").unwrap_or_default()
+    );
+
+    let mut footprint = empty_footprint(
+        footprint_name.clone(),
+        Frame::BOARD.xy(element, "x", "y"),
+        angle,
+        if mirror { "B.Cu" } else { "F.Cu" },
+    );
+    footprint.locked = element.attr("locked") == Some("yes");
+    footprint.attributes.dnp = element.attr("populate") == Some("no");
+    footprint.properties.insert("Reference".to_string(), name.to_string());
+    footprint.properties.insert("Value".to_string(), value.to_string());
+    footprint.properties.insert("Footprint".to_string(), footprint_name);
+    for attribute in element.children("attribute") {
+        if let (Some(key), Some(value)) = (attribute.attr("name"), attribute.attr("value")) {
+            footprint.properties.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    for child in &package.children {
+        match child.name.as_str() {
+            "smd" | "pad" | "hole" => {
+                if let Some(mut pad) = pad(child, frame, angle, rules) {
+                    pad.net = contacts.get(&(name, pad.number.as_str())).map(|n| n.to_string());
+                    footprint.pads.push(pad);
+                }
+            }
+            "text" => {
+                let content = child.text.replace(">NAME", name).replace(">VALUE", value);
+                footprint.texts.extend(text(child, frame, content));
+            }
+            _ => footprint.graphics.extend(graphic(child, frame)),
+        }
+    }
+
+    footprint.attributes.mount = if footprint.pads.iter().any(|p| p.pad_type == "thru_hole") {
+        "through_hole"
+    } else if footprint.pads.iter().any(|p| p.pad_type == "smd") {
+        "smd"
+    } else {
+        ""
+    }
+    .to_string();
+    footprint
+}
+
+/// Parse an Eagle XML board (`.brd`) into a [`PcbFile`]
+pub fn parse_eagle_board(content: &str) -> Result<PcbFile> {
+    let root = parse_xml(content)?;
+    if root.name != "eagle" {
+        return Err(KicadError::InvalidFormat("Expected an <eagle> root element".to_string()));
+    }
+    let board = root
+        .path(&["drawing", "board"])
+        .ok_or_else(|| KicadError::InvalidFormat("Eagle file has no <board>; is it a schematic?".to_string()))?;
+    let rules = DesignRules::new(board);
+
+    let mut pcb = PcbFile::new();
+    pcb.version = root.attr("version").unwrap_or("unknown").to_string();
+    pcb.generator = "eagle".to_string();
+
+    // Copper layers come from the layer setup, the others from the layer table
+    let mut layer_names: BTreeSet<String> = rules.copper_layers().into_iter().filter_map(kicad_layer).collect();
+    if let Some(layers) = root.path(&["drawing", "layers"]) {
+        layer_names.extend(
+            layers
+                .children("layer")
+                .filter_map(|l| l.attr("number")?.parse().ok())
+                .filter(|n| !(1..=16).contains(n))
+                .filter_map(kicad_layer),
+        );
+    }
+    for name in layer_names {
+        let id = layer_id(&name);
+        let layer_type = if name.ends_with(".Cu") { "signal" } else { "user" };
+        pcb.layers.insert(id, Layer { id, name, layer_type: layer_type.to_string(), user_name: None });
+    }
+
+    // Nets are numbered in signal order, after KiCad's unnamed net 0
+    let signals: Vec<&Element> = board
+        .child("signals")
+        .map(|s| s.children("signal").collect())
+        .unwrap_or_default();
+    pcb.nets.insert(0, String::new());
+    let mut contacts = HashMap::new();
+    for (i, signal) in signals.iter().enumerate() {
+        let net = signal.attr("name").unwrap_or_default();
+        pcb.nets.insert(i as u32 + 1, net.to_string());
+        for contact in signal.children("contactref") {
+            if let (Some(element), Some(pad)) = (contact.attr("element"), contact.attr("pad")) {
+                contacts.insert((element, pad), net);
+            }
+        }
+    }
+
+    if let Some(classes) = board.child("classes") {
+        for class in classes.children("class") {
+            let number = class.attr("number").unwrap_or("0");
+            let name = class.attr("name").unwrap_or(number).to_string();
+            let positive = |v: Option<f64>| v.filter(|v| *v > 0.0).map(Length::mm);
+            pcb.net_classes.insert(
+                name.clone(),
+                NetClass {
+                    name,
+                    description: String::new(),
+                    clearance: positive(
+                        class
+                            .children("clearance")
+                            .find(|c| c.attr("class") == Some(number))
+                            .and_then(|c| c.attr_f64("value")),
+                    ),
+                    track_width: positive(class.attr_f64("width")),
+                    via_diameter: None,
+                    via_drill: positive(class.attr_f64("drill")),
+                    uvia_diameter: None,
+                    uvia_drill: None,
+                    diff_pair_width: None,
+                    diff_pair_gap: None,
+                    nets: signals
+                        .iter()
+                        .filter(|s| s.attr("class").unwrap_or("0") == number)
+                        .filter_map(|s| s.attr("name"))
+                        .map(str::to_string)
+                        .collect(),
+                },
+            );
+        }
+    }
+
+    if let Some(plain) = board.child("plain") {
+        for item in &plain.children {
+            match item.name.as_str() {
+                "text" => pcb.texts.extend(text(item, Frame::BOARD, item.text.clone())),
+                "hole" => {
+                    let position = Frame::BOARD.xy(item, "x", "y");
+                    let mut footprint = empty_footprint("MountingHole".to_string(), position, 0.0, "F.Cu");
+                    footprint.attributes.board_only = true;
+                    footprint.attributes.exclude_from_bom = true;
+                    footprint.attributes.exclude_from_pos_files = true;
+                    let mut hole = item.clone();
+                    hole.attributes.retain(|(name, _)| name != "x" && name != "y");
+                    footprint.pads.extend(pad(&hole, Frame::BOARD, 0.0, &rules));
+                    pcb.footprints.push(footprint);
+                }
+                _ => pcb.graphics.extend(graphic(item, Frame::BOARD)),
+            }
+        }
+    }
+
+    // Packages by library name, library URN (Eagle 9 managed libraries) and package name
+    let mut packages = HashMap::new();
+    for library in board.child("libraries").iter().flat_map(|l| l.children("library")) {
+        let urn = library.attr("urn").unwrap_or_default();
+        for package in library.child("packages").iter().flat_map(|p| p.children("package")) {
+            let key = (library.attr("name").unwrap_or_default(), urn, package.attr("name").unwrap_or_default());
+            packages.insert(key, package);
+        }
+    }
+    for element in board.child("elements").iter().flat_map(|e| e.children("element")) {
+        let key = (
+            element.attr("library").unwrap_or_default(),
+            element.attr("library_urn").unwrap_or_default(),
+            element.attr("package").unwrap_or_default(),
+        );
+        let package = packages.get(&key).ok_or_else(|| {
+            KicadError::MissingField(format!(
+                "package {}:{} of element {}",
+                key.0,
+                key.2,
+                element.attr("name").unwrap_or_default()
+            ))
+        })?;
+        pcb.footprints.push(footprint(element, package, &rules, &contacts));
+    }
+
+    for signal in &signals {
+        let net = signal.attr("name").map(str::to_string);
+        for item in &signal.children {
+            match item.name.as_str() {
+                "wire" => {
+                    let Some(layer) = Frame::BOARD.layer(item).filter(|l| l.ends_with(".Cu")) else {
+                        continue;
+                    };
+                    let a = (item.attr_f64("x1").unwrap_or(0.0), item.attr_f64("y1").unwrap_or(0.0));
+                    let b = (item.attr_f64("x2").unwrap_or(0.0), item.attr_f64("y2").unwrap_or(0.0));
+                    let (start, end) = (Frame::BOARD.point(a.0, a.1), Frame::BOARD.point(b.0, b.1));
+                    let width = Length::mm(item.attr_f64("width").unwrap_or(0.0));
+                    match arc_mid(a, b, item.attr_f64("curve").unwrap_or(0.0)) {
+                        Some(mid) => pcb.arcs.push(TrackArc {
+                            start,
+                            mid: Frame::BOARD.point(mid.0, mid.1),
+                            end,
+                            width,
+                            layer,
+                            net: net.clone(),
+                            uuid: String::new(),
+                        }),
+                        None => pcb.tracks.push(Track { start, end, width, layer, net: net.clone(), uuid: String::new() }),
+                    }
+                }
+                "via" => {
+                    let drill = item.attr_f64("drill").unwrap_or(0.0);
+                    let size = item
+                        .attr_f64("diameter")
+                        .filter(|d| *d > 0.0)
+                        .unwrap_or_else(|| drill + 2.0 * rules.restring("ViaOuter", drill));
+                    let (from, to) = item
+                        .attr("extent")
+                        .and_then(|e| e.split_once('-'))
+                        .and_then(|(a, b)| Some((a.parse::<u32>().ok()?, b.parse::<u32>().ok()?)))
+                        .unwrap_or((1, 16));
+                    let layers: Vec<String> = [from.min(to), from.max(to)].into_iter().filter_map(kicad_layer).collect();
+                    pcb.vias.push(Via {
+                        position: Frame::BOARD.xy(item, "x", "y"),
+                        via_type: if (from.min(to), from.max(to)) == (1, 16) { ViaType::Through } else { ViaType::BlindBuried },
+                        size: Length::mm(size),
+                        drill: Length::mm(drill),
+                        layers,
+                        net: net.clone(),
+                        uuid: String::new(),
+                        finish: ViaFinish::default(),
+                    });
+                }
+                "polygon" => {
+                    let Some(layer) = Frame::BOARD.layer(item).filter(|l| l.ends_with(".Cu")) else {
+                        continue;
+                    };
+                    pcb.zones.push(Zone {
+                        uuid: String::new(),
+                        net: net.clone(),
+                        layer: layer.clone(),
+                        layers: vec![layer],
+                        // Eagle pours lower ranks first and lets them win
+                        priority: 6 - item.attr_f64("rank").unwrap_or(1.0) as i32,
+                        connect_pads: true,
+                        polygon: vertices(item, Frame::BOARD),
+                        filled_polygons: Vec::new(),
+                        fill: polygon_fill(item),
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(pcb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE eagle SYSTEM "eagle.dtd">
+<eagle version="9.6.2">
+<drawing>
+<layers>
+<layer number="1" name="Top" color="4" fill="1" visible="yes" active="yes"/>
+<layer number="16" name="Bottom" color="1" fill="1" visible="yes" active="yes"/>
+<layer number="20" name="Dimension" color="24" fill="1" visible="yes" active="yes"/>
+<layer number="21" name="tPlace" color="7" fill="1" visible="yes" active="yes"/>
+<layer number="41" name="tRestrict" color="4" fill="10" visible="yes" active="yes"/>
+</layers>
+<board>
+<plain>
+<wire x1="0" y1="0" x2="30" y2="0" width="0" layer="20"/>
+<wire x1="30" y1="0" x2="30" y2="20" width="0" layer="20" curve="90"/>
+<text x="2" y="18" size="1.778" layer="21" align="top-left">REV A</text>
+<hole x="3" y="3" drill="3.2"/>
+<rectangle x1="0" y1="0" x2="5" y2="5" layer="41"/>
+</plain>
+<libraries>
+<library name="con">
+<packages>
+<package name="HDR2">
+<pad name="1" x="-1.27" y="0" drill="1" shape="square"/>
+<pad name="2" x="1.27" y="0" drill="1"/>
+<wire x1="-2.5" y1="1.25" x2="2.5" y2="1.25" width="0.127" layer="21"/>
+<text x="-2.5" y="1.5" size="1" layer="25">&gt;NAME</text>
+</package>
+<package name="C0402">
+<smd name="1" x="-0.5" y="0" dx="0.6" dy="0.5" layer="1" roundness="50"/>
+<smd name="2" x="0.5" y="0" dx="0.6" dy="0.5" layer="1" roundness="50" cream="no"/>
+</package>
+</packages>
+</library>
+</libraries>
+<designrules name="default">
+<param name="layerSetup" value="(1*16)"/>
+<param name="rvPadTop" value="0.25"/>
+<param name="rlMinPadTop" value="10mil"/>
+<param name="rlMaxPadTop" value="20mil"/>
+</designrules>
+<classes>
+<class number="0" name="default" width="0" drill="0"><clearance class="0" value="0.2"/></class>
+<class number="1" name="power" width="0.5" drill="0"><clearance class="1" value="0.3"/></class>
+</classes>
+<elements>
+<element name="J1" library="con" package="HDR2" value="PWR" x="10" y="10" locked="yes">
+<attribute name="MPN" value="61300211121" x="10" y="12" size="1" layer="27" display="off"/>
+</element>
+<element name="C1" library="con" package="C0402" value="100n" x="20" y="10" rot="MR90" populate="no"/>
+</elements>
+<signals>
+<signal name="VCC" class="1">
+<contactref element="J1" pad="1"/>
+<contactref element="C1" pad="1"/>
+<wire x1="8.73" y1="10" x2="8.73" y2="5" width="0.5" layer="1"/>
+<wire x1="8.73" y1="5" x2="20" y2="5" width="0.5" layer="16" curve="-45"/>
+<wire x1="20" y1="5" x2="20" y2="9.5" width="0" layer="19"/>
+<via x="8.73" y="5" extent="1-16" drill="0.4"/>
+</signal>
+<signal name="GND">
+<contactref element="J1" pad="2"/>
+<polygon width="0.2" layer="16" rank="2">
+<vertex x="0" y="0"/>
+<vertex x="30" y="0"/>
+<vertex x="30" y="20"/>
+<vertex x="0" y="20"/>
+</polygon>
+</signal>
+</signals>
+</board>
+</drawing>
+</eagle>"#;
+
+    #[test]
+    fn test_parse_board() {
+        let pcb = parse_eagle_board(BOARD).unwrap();
+        assert_eq!(pcb.version, "9.6.2");
+        assert_eq!(pcb.layers[&0].name, "F.Cu");
+        assert_eq!(pcb.layers[&31].layer_type, "signal");
+        assert_eq!(pcb.layers[&44].name, "Edge.Cuts");
+        assert_eq!(pcb.layers.len(), 4);
+        assert_eq!(pcb.net_name(1), Some("VCC"));
+        assert_eq!(pcb.net_class_of("VCC").unwrap().track_width, Some(Length::mm(0.5)));
+        assert_eq!(pcb.net_classes["power"].clearance, Some(Length::mm(0.3)));
+
+        // Outline arc plus line; the restrict rectangle has no KiCad layer
+        assert_eq!(pcb.graphics.len(), 2);
+        match &pcb.graphics[1] {
+            Graphic::Arc { arc, .. } => {
+                assert!((arc.end_angle - arc.start_angle - 90.0).abs() < 1e-9);
+                assert!((arc.center.x.as_mm() - 20.0).abs() < 1e-9 && (arc.center.y.as_mm() + 10.0).abs() < 1e-9);
+            }
+            other => panic!("expected an arc, got {:?}", other),
+        }
+        assert_eq!(pcb.texts[0].text, "REV A");
+        assert_eq!(pcb.texts[0].position.y, Length::mm(-18.0));
+        assert_eq!(pcb.texts[0].effects.justify.as_deref(), Some("left top"));
+
+        assert_eq!(pcb.tracks.len(), 1);
+        assert_eq!(pcb.arcs[0].layer, "B.Cu");
+        assert_eq!(pcb.arcs[0].net.as_deref(), Some("VCC"));
+        assert_eq!(pcb.vias[0].via_type, ViaType::Through);
+        assert_eq!(pcb.vias[0].layers, vec!["F.Cu", "B.Cu"]);
+        // 0.4 mm drill: the 8 mil minimum annular ring applies
+        assert!((pcb.vias[0].size.as_mm() - 0.8064).abs() < 1e-9);
+        assert_eq!(pcb.zones[0].net.as_deref(), Some("GND"));
+        assert_eq!(pcb.zones[0].polygon[2], Point::new(30.0, -20.0));
+    }
+
+    #[test]
+    fn test_elements_become_footprints() {
+        let pcb = parse_eagle_board(BOARD).unwrap();
+        let hole = &pcb.footprints[0];
+        assert!(hole.attributes.board_only);
+        assert_eq!(hole.pads[0].pad_type, "np_thru_hole");
+        assert_eq!(hole.pads[0].position, Point::new(0.0, 0.0));
+        assert_eq!(hole.position, Point::new(3.0, -3.0));
+
+        let j1 = &pcb.footprints[1];
+        assert_eq!(j1.name, "con:HDR2");
+        assert!(j1.locked);
+        assert_eq!(j1.property("MPN"), Some("61300211121"));
+        assert_eq!(j1.attributes.mount, "through_hole");
+        assert_eq!(j1.pads[0].shape, "rect");
+        assert!((j1.pads[0].size.x.as_mm() - 1.508).abs() < 1e-9);
+        assert_eq!(j1.pads[1].net.as_deref(), Some("GND"));
+        assert_eq!(j1.texts[0].text, "J1");
+        assert_eq!(j1.graphics[0].layer(), "F.SilkS");
+
+        let c1 = &pcb.footprints[2];
+        assert_eq!(c1.layer, "B.Cu");
+        assert_eq!(c1.rotation, 90.0);
+        assert!(c1.attributes.dnp);
+        assert_eq!(c1.pads[0].layers, vec!["B.Cu", "B.Paste", "B.Mask"]);
+        assert_eq!(c1.pads[1].layers, vec!["B.Cu", "B.Mask"]);
+        assert_eq!(c1.pads[0].roundrect_ratio, Some(0.25));
+        // Mirrored: pad 1 lies right of the origin before rotation
+        let pad = c1.pad_position(&c1.pads[0]);
+        assert!((pad.x.as_mm() - 20.0).abs() < 1e-9 && (pad.y.as_mm() + 10.5).abs() < 1e-9);
+        assert_eq!(c1.pads[0].net.as_deref(), Some("VCC"));
+    }
+
+    #[test]
+    fn test_rejects_other_files() {
+        assert!(parse_eagle_board("<eagle><drawing><schematic/></drawing></eagle>").is_err());
+        assert!(parse_eagle_board("<kicad/>").is_err());
+        let missing = BOARD.replace(r#"package="C0402" value"#, r#"package="C0603" value"#);
+        assert!(parse_eagle_board(&missing).unwrap_err().to_string().contains("C0603"));
+    }
+}
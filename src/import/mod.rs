@@ -0,0 +1,10 @@
+//! Import of designs from other EDA tools into the kiparse model
+//!
+//! - [`altium`] - Altium schematic libraries saved as ASCII, as a
+//!   [`SymbolLib`](crate::symbol::types::SymbolLib)
+//! - [`eagle`] - Eagle XML boards (`.brd`) as a [`PcbFile`](crate::pcb::types::PcbFile)
+
+pub mod altium;
+pub mod eagle;
+
+mod xml;
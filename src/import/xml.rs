@@ -0,0 +1,296 @@
+//! Minimal XML reader for the importers
+//!
+//! Reads elements, attributes and text into a small [`Element`] tree, in the
+//! same spirit as [`sexpr`](crate::sexpr). The prolog, comments, processing
+//! instructions and the DOCTYPE are skipped; namespaces are not interpreted.
+
+use crate::error::{KicadError, Result};
+
+/// An XML element with its attributes, child elements and text content
+#[derive(Debug, Clone, PartialEq, Default)]
+pub(crate) struct Element {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<Element>,
+    /// Concatenated text directly inside the element, entities resolved
+    pub text: String,
+}
+
+impl Element {
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Numeric attribute, `None` when absent or not a number
+    pub(crate) fn attr_f64(&self, name: &str) -> Option<f64> {
+        self.attr(name).and_then(|v| v.trim().parse().ok())
+    }
+
+    /// First child element named `name`
+    pub(crate) fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// All child elements named `name`
+    pub(crate) fn children<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s Element> + 's {
+        self.children.iter().filter(move |c| c.name == name)
+    }
+
+    /// Element reached by following child names, e.g. `["drawing", "board"]`
+    pub(crate) fn path(&self, names: &[&str]) -> Option<&Element> {
+        names.iter().try_fold(self, |element, name| element.child(name))
+    }
+}
+
+/// Parse the root element of an XML document
+pub(crate) fn parse_xml(content: &str) -> Result<Element> {
+    let mut reader = Reader { text: content, pos: 0 };
+    reader.skip_misc()?;
+    if !reader.rest().starts_with('<') {
+        return Err(reader.error("expected a root element"));
+    }
+    reader.read_element()
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn error(&self, msg: &str) -> KicadError {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        KicadError::ParseError(format!("XML line {}: {}", line, msg))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// Skip past `end`, failing when it never appears
+    fn skip_past(&mut self, end: &str) -> Result<()> {
+        match self.rest().find(end) {
+            Some(at) => {
+                self.pos += at + end.len();
+                Ok(())
+            }
+            None => Err(self.error(&format!("missing '{}'", end))),
+        }
+    }
+
+    /// Skip whitespace, comments, processing instructions and DOCTYPE declarations
+    fn skip_misc(&mut self) -> Result<()> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<!") {
+                // DOCTYPE, possibly with an internal subset in brackets
+                let close = if rest.find('[').map_or(false, |b| rest.find('>').map_or(false, |g| b < g)) {
+                    "]>"
+                } else {
+                    ">"
+                };
+                self.skip_past(close)?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn read_name(&mut self) -> Result<&'a str> {
+        let rest = self.rest();
+        let len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '>' | '/' | '='))
+            .unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Read an element starting at its `<`
+    fn read_element(&mut self) -> Result<Element> {
+        // Explicit stack so deeply nested input cannot overflow the call stack
+        let mut stack: Vec<Element> = Vec::new();
+        loop {
+            let rest = self.rest();
+            let finished = if rest.starts_with("</") {
+                self.pos += 2;
+                let name = self.read_name()?;
+                self.skip_past(">")?;
+                match stack.pop() {
+                    Some(element) if element.name == name => element,
+                    Some(element) => {
+                        return Err(self.error(&format!("</{}> closes <{}>", name, element.name)))
+                    }
+                    None => return Err(self.error(&format!("unexpected </{}>", name))),
+                }
+            } else if rest.starts_with("<!--") || rest.starts_with("<?") {
+                self.skip_past(if rest.starts_with("<?") { "?>" } else { "-->" })?;
+                self.read_text(&mut stack)?;
+                continue;
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += 9;
+                let end = self.rest().find("]]>").ok_or_else(|| self.error("unterminated CDATA"))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.text.push_str(&self.rest()[..end]);
+                }
+                self.pos += end + 3;
+                self.read_text(&mut stack)?;
+                continue;
+            } else if rest.starts_with('<') {
+                self.pos += 1;
+                let mut element = Element {
+                    name: self.read_name()?.to_string(),
+                    ..Element::default()
+                };
+                let empty = self.read_attributes(&mut element)?;
+                if !empty {
+                    stack.push(element);
+                    self.read_text(&mut stack)?;
+                    continue;
+                }
+                element
+            } else {
+                return Err(self.error("unexpected end of input"));
+            };
+
+            match stack.last_mut() {
+                Some(parent) => parent.children.push(finished),
+                None => return Ok(finished),
+            }
+            self.read_text(&mut stack)?;
+        }
+    }
+
+    /// Read attributes up to the end of a start tag; returns whether the tag was `/>`
+    fn read_attributes(&mut self, element: &mut Element) -> Result<bool> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.pos += 2;
+                return Ok(true);
+            }
+            if rest.starts_with('>') {
+                self.pos += 1;
+                return Ok(false);
+            }
+            let name = self.read_name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(self.error(&format!("attribute {} without a value", name)));
+            }
+            self.pos += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(q @ ('"' | '\'')) => q,
+                _ => return Err(self.error(&format!("unquoted value for attribute {}", name))),
+            };
+            self.pos += 1;
+            let end = self
+                .rest()
+                .find(quote)
+                .ok_or_else(|| self.error(&format!("unterminated value for attribute {}", name)))?;
+            let value = unescape(&self.rest()[..end]);
+            self.pos += end + 1;
+            element.attributes.push((name.to_string(), value));
+        }
+    }
+
+    /// Append the text before the next tag to the open element
+    fn read_text(&mut self, stack: &mut [Element]) -> Result<()> {
+        let rest = self.rest();
+        let end = rest.find('<').unwrap_or(rest.len());
+        if let Some(parent) = stack.last_mut() {
+            parent.text.push_str(&unescape(&rest[..end]));
+        }
+        self.pos += end;
+        if self.pos == self.text.len() && !stack.is_empty() {
+            return Err(self.error(&format!("unclosed <{}>", stack[stack.len() - 1].name)));
+        }
+        Ok(())
+    }
+}
+
+/// Resolve the predefined and numeric character entities
+fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('&') {
+        out.push_str(&rest[..at]);
+        rest = &rest[at..];
+        let entity = rest.find(';').map(|end| (&rest[1..end], end));
+        let resolved = entity.and_then(|(name, end)| {
+            let c = match name {
+                "lt" => '<',
+                "gt" => '>',
+                "amp" => '&',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = match name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+                        Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                        None => name.strip_prefix('#')?.parse().ok()?,
+                    };
+                    char::from_u32(code)?
+                }
+            };
+            Some((c, end))
+        });
+        match resolved {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_document() {
+        let root = parse_xml(
+            "<?xml version=\"1.0\"?>\n<!DOCTYPE eagle SYSTEM \"eagle.dtd\">\n<!-- board -->\n\
+             <eagle version='9.6'><text x=\"1\">R&amp;D &#x3A9;<![CDATA[<raw>]]></text><empty a=\"&lt;\"/></eagle>",
+        )
+        .unwrap();
+        assert_eq!(root.name, "eagle");
+        assert_eq!(root.attr("version"), Some("9.6"));
+        let text = root.child("text").unwrap();
+        assert_eq!(text.attr_f64("x"), Some(1.0));
+        assert_eq!(text.text, "R&D \u{3a9}<raw>");
+        assert_eq!(root.child("empty").unwrap().attr("a"), Some("<"));
+    }
+
+    #[test]
+    fn test_mismatched_tags() {
+        assert!(parse_xml("<a><b></a>").unwrap_err().to_string().contains("</a> closes <b>"));
+        assert!(parse_xml("<a><b/>").is_err());
+    }
+}
@@ -0,0 +1,307 @@
+//! Client for the KiCad 9 IPC API
+//!
+//! Talks to a running KiCad over its API socket (enable the API server under
+//! Preferences > Plugins) so the live board can be pulled out for analysis with
+//! the rest of kiparse, and items can be pushed back into the open editor.
+//!
+//! The API is protobuf messages over an NNG request/reply socket; both layers are
+//! implemented here on top of `std`, so the `ipc` feature adds no dependencies. It
+//! is available on Unix only, where the socket is a Unix domain socket.
+//!
+//! ```rust,no_run
+//! use kiparse::ipc::KicadClient;
+//!
+//! let mut kicad = KicadClient::connect()?;
+//! println!("Connected to KiCad {}", kicad.version()?.full);
+//!
+//! let board = kicad.open_boards()?.remove(0);
+//! let pcb = kicad.pull_board(&board)?;
+//! println!("{}: {} footprints", board.filename, pcb.footprints.len());
+//!
+//! kicad.push_items(&board, r#"(gr_text "checked" (at 10 10) (layer "F.SilkS"))"#)?;
+//! # Ok::<(), kiparse::KicadError>(())
+//! ```
+
+mod proto;
+
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::{KicadError, Result};
+use crate::pcb::pcb_parser::parse_pcb;
+use crate::pcb::types::PcbFile;
+use proto::{Message, Writer};
+
+/// Socket KiCad listens on when `KICAD_API_SOCKET` is not set
+pub const DEFAULT_SOCKET: &str = "/tmp/kicad/api.sock";
+
+/// NNG protocol numbers of the request and reply sockets
+const NNG_REQ: u16 = 0x30;
+const NNG_REP: u16 = 0x31;
+
+/// `kiapi.common.types.DocumentType.DOCTYPE_PCB`
+const DOCTYPE_PCB: u64 = 3;
+
+/// Boards can take a while to save to a string
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Version of the connected KiCad
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KicadVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    /// Full version string, e.g. `9.0.2-1.fc41`
+    pub full: String,
+}
+
+/// Board open in the connected KiCad
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardDocument {
+    /// File name of the board, e.g. `fpga.kicad_pcb`
+    pub filename: String,
+    pub project_name: String,
+    /// Project directory
+    pub project_path: String,
+}
+
+impl BoardDocument {
+    /// `kiapi.common.types.DocumentSpecifier`
+    fn specifier(&self) -> Writer {
+        let project = Writer::new()
+            .string(1, &self.project_name)
+            .string(2, &self.project_path);
+        Writer::new()
+            .uint(1, DOCTYPE_PCB)
+            .string(3, &self.filename)
+            .message(4, project)
+    }
+}
+
+/// Connection to a running KiCad
+#[derive(Debug)]
+pub struct KicadClient {
+    stream: UnixStream,
+    token: String,
+    client_name: String,
+    next_id: u32,
+}
+
+impl KicadClient {
+    /// Connect to the socket named by `KICAD_API_SOCKET` (set for plugins started
+    /// by KiCad), or to [`DEFAULT_SOCKET`]
+    pub fn connect() -> Result<Self> {
+        let socket = std::env::var("KICAD_API_SOCKET").unwrap_or_else(|_| DEFAULT_SOCKET.to_string());
+        Self::connect_to(socket.strip_prefix("ipc://").unwrap_or(&socket))
+    }
+
+    /// Connect to the API socket at `path`
+    pub fn connect_to(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut stream = UnixStream::connect(path).map_err(|e| {
+            KicadError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("Cannot connect to KiCad at {} ({}); is the API server enabled?", path.display(), e),
+            ))
+        })?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        // NNG connection header: "\0SP\0", our protocol, two reserved bytes
+        let [hi, lo] = NNG_REQ.to_be_bytes();
+        stream.write_all(&[0, b'S', b'P', 0, hi, lo, 0, 0])?;
+        let mut header = [0u8; 8];
+        stream.read_exact(&mut header)?;
+        if header[..4] != [0, b'S', b'P', 0] || u16::from_be_bytes([header[4], header[5]]) != NNG_REP {
+            return Err(KicadError::InvalidFormat(format!(
+                "{} is not a KiCad API socket",
+                path.display()
+            )));
+        }
+
+        Ok(KicadClient {
+            stream,
+            token: std::env::var("KICAD_API_TOKEN").unwrap_or_default(),
+            client_name: format!("kiparse-{}", std::process::id()),
+            next_id: 1,
+        })
+    }
+
+    /// Name KiCad shows for this client
+    pub fn with_client_name(mut self, name: &str) -> Self {
+        self.client_name = name.to_string();
+        self
+    }
+
+    pub fn version(&mut self) -> Result<KicadVersion> {
+        let response = self.call("kiapi.common.commands.GetVersion", Writer::new())?;
+        let version = Message::parse(&response)?.message(1)?;
+        Ok(KicadVersion {
+            major: version.uint(1) as u32,
+            minor: version.uint(2) as u32,
+            patch: version.uint(3) as u32,
+            full: version.string(4)?,
+        })
+    }
+
+    /// Boards open in the PCB editor
+    pub fn open_boards(&mut self) -> Result<Vec<BoardDocument>> {
+        let command = Writer::new().uint(1, DOCTYPE_PCB);
+        let response = self.call("kiapi.common.commands.GetOpenDocuments", command)?;
+        Message::parse(&response)?
+            .messages(1)?
+            .into_iter()
+            .filter(|document| document.uint(1) == DOCTYPE_PCB)
+            .map(|document| {
+                let project = document.message(4)?;
+                Ok(BoardDocument {
+                    filename: document.string(3)?,
+                    project_name: project.string(1)?,
+                    project_path: project.string(2)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Current contents of an open board, including unsaved edits, as
+    /// `.kicad_pcb` text
+    pub fn board_text(&mut self, board: &BoardDocument) -> Result<String> {
+        let command = Writer::new().message(1, board.specifier());
+        let response = self.call("kiapi.common.commands.SaveDocumentToString", command)?;
+        Message::parse(&response)?.string(2)
+    }
+
+    /// Parse the current contents of an open board
+    pub fn pull_board(&mut self, board: &BoardDocument) -> Result<PcbFile> {
+        parse_pcb(&self.board_text(board)?)
+    }
+
+    /// Add items, given as KiCad S-expressions (`(footprint ...)`, `(segment ...)`,
+    /// `(gr_text ...)`, ...), to an open board as one undoable change
+    pub fn push_items(&mut self, board: &BoardDocument, items: &str) -> Result<()> {
+        let command = Writer::new().message(1, board.specifier()).string(2, items);
+        self.call("kiapi.common.commands.ParseAndCreateItemsFromString", command)?;
+        Ok(())
+    }
+
+    /// Send a command and return the body of its response
+    fn call(&mut self, command_type: &str, command: Writer) -> Result<Vec<u8>> {
+        let id = self.next_id | 0x8000_0000;
+        self.next_id = self.next_id % 0x7fff_ffff + 1;
+
+        let mut payload = id.to_be_bytes().to_vec();
+        payload.extend(proto::request(&self.token, &self.client_name, command_type, command));
+        let mut frame = vec![1];
+        frame.extend((payload.len() as u64).to_be_bytes());
+        frame.extend(payload);
+        self.stream.write_all(&frame)?;
+
+        let reply = loop {
+            let mut header = [0u8; 9];
+            self.stream.read_exact(&mut header)?;
+            let len = u64::from_be_bytes(header[1..].try_into().unwrap());
+            let mut reply = vec![0; len as usize];
+            self.stream.read_exact(&mut reply)?;
+            // The reply starts with the routing words of the request, the last one
+            // (high bit set) being its id; stale replies to timed-out requests are dropped
+            let Some(end) = reply.chunks_exact(4).position(|word| word[0] & 0x80 != 0) else {
+                return Err(KicadError::InvalidFormat("KiCad API reply without a request id".to_string()));
+            };
+            if reply[end * 4..end * 4 + 4] == id.to_be_bytes() {
+                break reply.split_off(end * 4 + 4);
+            }
+        };
+
+        let response = proto::response(&reply)?;
+        if response.status != proto::STATUS_OK {
+            let message = if response.error_message.is_empty() {
+                format!("status {}", response.status)
+            } else {
+                response.error_message
+            };
+            return Err(KicadError::InvalidFormat(format!("KiCad API {}: {}", command_type, message)));
+        }
+        if self.token.is_empty() {
+            self.token = response.token;
+        }
+        Ok(response.body.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    /// Minimal KiCad: answers each command with `reply(type, command)`
+    fn serve(path: &Path, reply: fn(&str, &Message) -> Writer) -> std::thread::JoinHandle<()> {
+        let listener = UnixListener::bind(path).unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut header = [0u8; 8];
+            stream.read_exact(&mut header).unwrap();
+            assert_eq!(header, [0, b'S', b'P', 0, 0, 0x30, 0, 0]);
+            stream.write_all(&[0, b'S', b'P', 0, 0, 0x31, 0, 0]).unwrap();
+
+            let mut frame = [0u8; 9];
+            while stream.read_exact(&mut frame).is_ok() {
+                let mut payload = vec![0; u64::from_be_bytes(frame[1..].try_into().unwrap()) as usize];
+                stream.read_exact(&mut payload).unwrap();
+                let request = Message::parse(&payload[4..]).unwrap();
+                let any = request.message(2).unwrap();
+                let type_url = any.string(1).unwrap();
+                let command = any.message(2).unwrap();
+                let body = reply(type_url.rsplit('.').next().unwrap(), &command);
+
+                let response = Writer::new()
+                    .message(1, Writer::new().string(1, "abc"))
+                    .message(2, Writer::new().uint(1, 1))
+                    .message(3, Writer::new().string(1, "type.googleapis.com/x").message(2, body))
+                    .finish();
+                let mut reply = payload[..4].to_vec();
+                reply.extend(response);
+                stream.write_all(&[1]).unwrap();
+                stream.write_all(&(reply.len() as u64).to_be_bytes()).unwrap();
+                stream.write_all(&reply).unwrap();
+            }
+        })
+    }
+
+    #[test]
+    fn test_round_trip_with_fake_kicad() {
+        let path = std::env::temp_dir().join(format!("kiparse-ipc-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let server = serve(&path, |command, body| match command {
+            "GetVersion" => Writer::new().message(1, Writer::new().uint(1, 9).uint(3, 1).string(4, "9.0.1")),
+            "GetOpenDocuments" => {
+                assert_eq!(body.uint(1), DOCTYPE_PCB);
+                let project = Writer::new().string(1, "demo").string(2, "/work/demo");
+                let document = Writer::new().uint(1, DOCTYPE_PCB).string(3, "demo.kicad_pcb").message(4, project);
+                Writer::new().message(1, document)
+            }
+            "SaveDocumentToString" => {
+                assert_eq!(body.message(1).unwrap().string(3).unwrap(), "demo.kicad_pcb");
+                Writer::new().string(2, r#"(kicad_pcb (version 20241229) (generator "pcbnew") (net 1 "GND"))"#)
+            }
+            other => panic!("unexpected command {}", other),
+        });
+
+        let mut kicad = KicadClient::connect_to(&path).unwrap();
+        let version = kicad.version().unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (9, 0, 1));
+        assert_eq!(kicad.token, "abc");
+
+        let boards = kicad.open_boards().unwrap();
+        assert_eq!(boards[0].project_name, "demo");
+        let pcb = kicad.pull_board(&boards[0]).unwrap();
+        assert_eq!(pcb.nets.get(&1).map(String::as_str), Some("GND"));
+
+        drop(kicad);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+    }
+}
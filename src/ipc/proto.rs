@@ -0,0 +1,255 @@
+//! Protobuf wire format for the KiCad API envelope and the few commands used here
+//!
+//! Only what the client needs: varint and length-delimited fields, written in
+//! field order and read back by field number. Unknown fields are skipped.
+
+use crate::error::{KicadError, Result};
+
+/// Prefix of `google.protobuf.Any` type URLs
+const TYPE_URL_PREFIX: &str = "type.googleapis.com/";
+
+/// `kiapi.common.ApiStatusCode.AS_OK`
+pub(crate) const STATUS_OK: u64 = 1;
+
+/// Message being written
+#[derive(Debug, Default)]
+pub(crate) struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(u64::from(field) << 3 | u64::from(wire_type));
+    }
+
+    /// Integer or enum field; zero is the default and is left out
+    pub(crate) fn uint(mut self, field: u32, value: u64) -> Self {
+        if value != 0 {
+            self.key(field, 0);
+            self.varint(value);
+        }
+        self
+    }
+
+    /// String field; empty strings are left out
+    pub(crate) fn string(self, field: u32, value: &str) -> Self {
+        if value.is_empty() {
+            self
+        } else {
+            self.bytes(field, value.as_bytes())
+        }
+    }
+
+    pub(crate) fn bytes(mut self, field: u32, value: &[u8]) -> Self {
+        self.key(field, 2);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+        self
+    }
+
+    /// Embedded message, written even when empty so that it is present
+    pub(crate) fn message(self, field: u32, message: Writer) -> Self {
+        self.bytes(field, &message.buf)
+    }
+
+    pub(crate) fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Field value of a received message
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+/// Received message, its fields in wire order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Message<'a> {
+    fields: Vec<(u32, Value<'a>)>,
+}
+
+impl<'a> Message<'a> {
+    pub(crate) fn parse(buf: &'a [u8]) -> Result<Self> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while pos < buf.len() {
+            let key = read_varint(buf, &mut pos)?;
+            let field = (key >> 3) as u32;
+            let value = match key & 7 {
+                0 => Value::Varint(read_varint(buf, &mut pos)?),
+                1 => Value::Fixed64(u64::from_le_bytes(take(buf, &mut pos, 8)?.try_into().unwrap())),
+                2 => {
+                    let len = read_varint(buf, &mut pos)? as usize;
+                    Value::Bytes(take(buf, &mut pos, len)?)
+                }
+                5 => Value::Fixed32(u32::from_le_bytes(take(buf, &mut pos, 4)?.try_into().unwrap())),
+                other => return Err(malformed(&format!("unsupported wire type {}", other))),
+            };
+            fields.push((field, value));
+        }
+        Ok(Message { fields })
+    }
+
+    /// Integer or enum field, 0 when absent
+    pub(crate) fn uint(&self, field: u32) -> u64 {
+        self.fields
+            .iter()
+            .rev()
+            .find_map(|(f, v)| match v {
+                Value::Varint(n) if *f == field => Some(*n),
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn bytes(&self, field: u32) -> Option<&'a [u8]> {
+        self.all_bytes(field).last()
+    }
+
+    /// String field, empty when absent
+    pub(crate) fn string(&self, field: u32) -> Result<String> {
+        match self.bytes(field) {
+            Some(bytes) => String::from_utf8(bytes.to_vec()).map_err(|_| malformed("invalid UTF-8 in string")),
+            None => Ok(String::new()),
+        }
+    }
+
+    /// Embedded message, empty when absent
+    pub(crate) fn message(&self, field: u32) -> Result<Message<'a>> {
+        Message::parse(self.bytes(field).unwrap_or_default())
+    }
+
+    /// Every occurrence of a repeated message field
+    pub(crate) fn messages(&self, field: u32) -> Result<Vec<Message<'a>>> {
+        self.all_bytes(field).map(Message::parse).collect()
+    }
+
+    fn all_bytes(&self, field: u32) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.fields.iter().filter_map(move |(f, v)| match v {
+            Value::Bytes(b) if *f == field => Some(*b),
+            _ => None,
+        })
+    }
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *take(buf, pos, 1)?.first().unwrap();
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(malformed("varint too long"))
+}
+
+fn take<'a>(buf: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    if buf.len() - *pos < len {
+        return Err(malformed("message ends early"));
+    }
+    let bytes = &buf[*pos..*pos + len];
+    *pos += len;
+    Ok(bytes)
+}
+
+fn malformed(what: &str) -> KicadError {
+    KicadError::InvalidFormat(format!("Malformed KiCad API message: {}", what))
+}
+
+/// `kiapi.common.ApiRequest` wrapping a command in a `google.protobuf.Any`
+pub(crate) fn request(token: &str, client_name: &str, type_name: &str, command: Writer) -> Vec<u8> {
+    let header = Writer::new().string(1, token).string(2, client_name);
+    let any = Writer::new()
+        .string(1, &format!("{}{}", TYPE_URL_PREFIX, type_name))
+        .message(2, command);
+    Writer::new().message(1, header).message(2, any).finish()
+}
+
+/// Parts of a `kiapi.common.ApiResponse`
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Response<'a> {
+    pub token: String,
+    /// `ApiStatusCode`, [`STATUS_OK`] on success
+    pub status: u64,
+    pub error_message: String,
+    /// Full name of the response message type
+    pub type_name: String,
+    /// Encoded response message
+    pub body: &'a [u8],
+}
+
+pub(crate) fn response(buf: &[u8]) -> Result<Response<'_>> {
+    let envelope = Message::parse(buf)?;
+    let status = envelope.message(2)?;
+    let any = envelope.message(3)?;
+    let type_url = any.string(1)?;
+    Ok(Response {
+        token: envelope.message(1)?.string(1)?,
+        status: status.uint(1),
+        error_message: status.string(2)?,
+        type_name: type_url.strip_prefix(TYPE_URL_PREFIX).unwrap_or(&type_url).to_string(),
+        body: any.bytes(2).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_encoding() {
+        let command = Writer::new().uint(1, 3);
+        let bytes = request("", "kiparse", "kiapi.common.commands.GetOpenDocuments", command);
+        let envelope = Message::parse(&bytes).unwrap();
+
+        assert_eq!(envelope.message(1).unwrap().string(2).unwrap(), "kiparse");
+        let any = envelope.message(2).unwrap();
+        assert_eq!(
+            any.string(1).unwrap(),
+            "type.googleapis.com/kiapi.common.commands.GetOpenDocuments"
+        );
+        assert_eq!(any.message(2).unwrap().uint(1), 3);
+        // Field 1, length 9: the header with only the client name
+        assert_eq!(&bytes[..4], &[0x0a, 9, 0x12, 7]);
+    }
+
+    #[test]
+    fn test_response_decoding() {
+        let version = Writer::new().uint(1, 9).uint(3, 2).string(4, "9.0.2");
+        let any = Writer::new()
+            .string(1, "type.googleapis.com/kiapi.common.commands.GetVersionResponse")
+            .message(2, Writer::new().message(1, version));
+        let bytes = Writer::new()
+            .message(1, Writer::new().string(1, "token"))
+            .message(2, Writer::new().uint(1, STATUS_OK))
+            .message(3, any)
+            .finish();
+
+        let response = response(&bytes).unwrap();
+        assert_eq!(response.token, "token");
+        assert_eq!(response.status, STATUS_OK);
+        assert_eq!(response.type_name, "kiapi.common.commands.GetVersionResponse");
+        let version = Message::parse(response.body).unwrap().message(1).unwrap();
+        assert_eq!((version.uint(1), version.uint(2), version.uint(3)), (9, 0, 2));
+        assert_eq!(version.string(4).unwrap(), "9.0.2");
+
+        assert!(Message::parse(&[0x0a, 5, 1]).is_err());
+    }
+}
@@ -38,9 +38,38 @@
 //! 
 //! ## Module Organization
 //! 
-//! - [`pcb`] - PCB file layer extraction (.kicad_pcb)
+//! - [`pcb`] - PCB file layer extraction (.kicad_pcb), board builders and writer
 //! - [`symbol`] - Symbol library parsing (.kicad_sym) 
+//! - [`schematic`] - Schematic sheet parsing and nets (.kicad_sch)
 //! - [`error`] - Error types and handling
+//! - [`sexpr`] - S-expression reader used by the typed parsers
+//! - [`units`] - Unit-aware lengths (mm, mils, inches, µm)
+//! - [`progress`] - Progress callbacks for long parses
+//! - [`embedded`] - Embedded files (KiCad 8/9)
+//! - [`bom`] - Bill of materials export
+//! - [`cam`] - Manufacturing outputs (placement, Gerber and drill files)
+//! - [`netlist`] - KiCad XML netlist export
+//! - [`spice`] - SPICE netlist export from schematics
+//! - [`crossprobe`] - Links between schematic symbols and board footprints
+//! - [`annotation`] - Duplicate, unannotated and skipped references
+//! - [`report`] - Markdown/HTML design reports
+//! - `arrow` - Arrow record batches and Parquet files (`arrow` feature)
+//! - [`export`] - SQLite export (`sqlite` feature)
+//! - [`render`] - Raster images of board layers, PNG encoding (`png` feature)
+//! - [`validate`] - Syntax, section and version checks for CI
+//! - [`binary`] - Bincode serialization for caching parsed boards
+//! - [`import`] - Eagle board and Altium schematic library import
+//! - `ipc` - Client for the KiCad 9 IPC API (`ipc` feature, Unix)
+//! 
+//! ## Cargo Features
+//! 
+//! `std`, `serde` and `regex` are on by default. `serde` adds `Serialize` and
+//! `Deserialize` to the parsed types (and the bincode [`binary`] format), `regex`
+//! enables [`DetailParser`] and reference renumbering. With default features
+//! off the crate is `no_std` and keeps [`sexpr`] and [`error`]; adding `alloc`
+//! brings in the board and symbol types with [`PcbParser`] and
+//! [`parse_symbol_lib_full`], their maps coming from hashbrown and their float
+//! math from libm. Everything else needs `std`. The `kpx` binary is behind `cli`.
 //! 
 //! ## Performance Characteristics
 //! 
@@ -50,26 +79,89 @@
 //! - **Symbol parsing**: ~15MB/s
 //! - **Memory usage**: ~1.5x file size during parsing
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod pcb;
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub mod symbol;
+#[cfg(feature = "std")]
+pub mod schematic;
 pub mod error;
+#[cfg(feature = "std")]
 pub mod prelude;
+pub mod sexpr;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod embedded;
+#[cfg(feature = "std")]
+pub mod bom;
+#[cfg(feature = "std")]
+pub mod cam;
+#[cfg(feature = "std")]
+pub mod netlist;
+#[cfg(feature = "std")]
+pub mod spice;
+#[cfg(feature = "std")]
+pub mod crossprobe;
+#[cfg(feature = "std")]
+pub mod annotation;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "std")]
+pub mod export;
+#[cfg(feature = "std")]
+pub mod render;
+#[cfg(feature = "std")]
+pub mod validate;
+#[cfg(all(feature = "std", feature = "serde"))]
+pub mod binary;
+#[cfg(feature = "std")]
+pub mod import;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod units;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub mod progress;
+#[cfg(all(feature = "ipc", unix))]
+pub mod ipc;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod base64;
+#[cfg(any(feature = "std", feature = "alloc"))]
+mod collections;
+#[cfg(all(feature = "alloc", not(any(feature = "std", feature = "serde"))))]
+mod float;
 
 // Re-export commonly used types at the crate root
 pub use error::{KicadError, Result};
 
 // Re-export the main parsing functions for convenience
+#[cfg(feature = "std")]
 pub use pcb::parse_layers_only;
+#[cfg(all(feature = "std", feature = "regex"))]
 pub use pcb::detail_parser::DetailParser;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use pcb::pcb_parser::{parse_pcb, PcbParser};
+#[cfg(feature = "std")]
 pub use symbol::symbol_parser::parse_symbol_lib;
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub use symbol::lib_parser::parse_symbol_lib_full;
 
 // Re-export PCB data types with module prefix to avoid conflicts
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub use pcb::types::{
     PcbFile, Layer, Track, Footprint, Pad, Via, Zone, Text, Graphic,
-    Point, Rect, Arc
+    Point, Rect, Arc, NetClass, TextBox, Group, Image, Model3D, TitleBlock, RuleArea,
+    FootprintAttributes, BoardMetadata, BoardPolygon, StackupLayer, BoardStats, ViaType,
+    TuningPattern, TuningMode, TuningStatus, LengthTarget, MeanderParameters,
+    TrackArc, FilledPolygon
 };
 
 // Re-export Symbol types with explicit naming to avoid conflicts
+#[cfg(any(feature = "std", feature = "alloc"))]
 pub use symbol::types::Symbol;
 
 /// Library version information
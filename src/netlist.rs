@@ -0,0 +1,484 @@
+//! KiCad XML netlist export
+//!
+//! Builds the netlist KiCad writes with *File > Export > Netlist* (the
+//! `kicadxml` format read by BOM and simulation plugins) from the footprints
+//! of a board, or headless from the schematic sheets of a design with
+//! [`Netlist::from_hierarchy`]. Board-only footprints are left out, as they
+//! have no schematic symbol. Component fields, sheet paths and pin names/types
+//! are taken from what the board stores; library part information is not
+//! available there and `<libparts>`/`<libraries>` are written empty.
+//!
+//! ```rust
+//! use kiparse::{netlist::Netlist, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND")
+//!   (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 0)
+//!     (property "Reference" "R1") (property "Value" "10k")
+//!     (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 1 "GND")))
+//! )"#)?;
+//! let xml = Netlist::from_pcb(&pcb).to_xml();
+//!
+//! assert!(xml.contains(r#"<comp ref="R1">"#));
+//! assert!(xml.contains(r#"<net code="1" name="GND">"#));
+//! assert!(xml.contains(r#"<node ref="R1" pin="1"/>"#));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::bom::natural_order;
+use crate::pcb::types::PcbFile;
+use crate::schematic::Hierarchy;
+
+/// Properties written as dedicated elements rather than `<field>`s
+const BUILTIN_FIELDS: &[&str] = &[
+    "Reference",
+    "Value",
+    "Footprint",
+    "Datasheet",
+    "Description",
+    "Sheetname",
+    "Sheetfile",
+];
+
+/// One schematic component
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetlistComponent {
+    pub reference: String,
+    pub value: String,
+    pub footprint: String,
+    pub datasheet: Option<String>,
+    pub description: Option<String>,
+    /// User fields such as MPN, sorted by name
+    pub fields: Vec<(String, String)>,
+    /// Sheet uuids from the root, e.g. `/` or `/5c1e.../`
+    pub sheet_path: String,
+    pub sheet_name: Option<String>,
+    pub sheet_file: Option<String>,
+    /// Symbol uuid
+    pub tstamp: Option<String>,
+}
+
+/// Pad of a component on a net
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetlistNode {
+    pub reference: String,
+    pub pin: String,
+    pub pin_function: Option<String>,
+    pub pin_type: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetlistNet {
+    /// 1-based code in name order
+    pub code: usize,
+    pub name: String,
+    pub nodes: Vec<NetlistNode>,
+}
+
+/// Components and nets of a design
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Netlist {
+    pub components: Vec<NetlistComponent>,
+    pub nets: Vec<NetlistNet>,
+}
+
+impl Netlist {
+    /// Netlist of the footprints placed on `pcb`
+    pub fn from_pcb(pcb: &PcbFile) -> Self {
+        let footprints = pcb.footprints.iter().filter(|f| !f.attributes.board_only);
+
+        let mut components = Vec::new();
+        let mut nets: BTreeMap<&str, Vec<NetlistNode>> = BTreeMap::new();
+        for footprint in footprints {
+            let reference = footprint
+                .property("Reference")
+                .unwrap_or_default()
+                .to_string();
+            let (sheet_path, tstamp) =
+                match footprint.path.as_deref().and_then(|p| p.rsplit_once('/')) {
+                    Some((sheets, symbol)) => (format!("{}/", sheets), Some(symbol.to_string())),
+                    None => ("/".to_string(), None),
+                };
+            let mut fields: Vec<(String, String)> = footprint
+                .properties
+                .iter()
+                .filter(|(name, _)| {
+                    !BUILTIN_FIELDS.contains(&name.as_str()) && !name.starts_with("ki_")
+                })
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            fields.sort();
+            let optional = |name: &str| {
+                footprint
+                    .property(name)
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string)
+            };
+
+            components.push(NetlistComponent {
+                value: footprint.property("Value").unwrap_or_default().to_string(),
+                footprint: footprint.name.clone(),
+                datasheet: optional("Datasheet"),
+                description: optional("Description"),
+                fields,
+                sheet_path,
+                sheet_name: footprint.sheet_name.clone(),
+                sheet_file: footprint.sheet_file.clone(),
+                tstamp,
+                reference: reference.clone(),
+            });
+
+            for pad in &footprint.pads {
+                let Some(net) = pad.net.as_deref() else {
+                    continue;
+                };
+                if pad.number.is_empty() {
+                    continue;
+                }
+                let nodes = nets.entry(net).or_default();
+                if !nodes
+                    .iter()
+                    .any(|n| n.reference == reference && n.pin == pad.number)
+                {
+                    nodes.push(NetlistNode {
+                        reference: reference.clone(),
+                        pin: pad.number.clone(),
+                        pin_function: pad.pin_function.clone(),
+                        pin_type: pad.pin_type.clone(),
+                    });
+                }
+            }
+        }
+
+        components.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+        let nets = nets
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, mut nodes))| {
+                nodes.sort_by(|a, b| {
+                    natural_order(&a.reference, &b.reference)
+                        .then_with(|| natural_order(&a.pin, &b.pin))
+                });
+                NetlistNet {
+                    code: i + 1,
+                    name: name.to_string(),
+                    nodes,
+                }
+            })
+            .collect();
+
+        Netlist { components, nets }
+    }
+
+    /// Netlist of a schematic design, with nets derived from its sheets
+    ///
+    /// Components are the symbols of every sheet instance under their annotated
+    /// references, the units of a part making up one component; power symbols
+    /// (references starting with `#`) are left out.
+    pub fn from_hierarchy(hierarchy: &Hierarchy) -> Self {
+        let mut components: Vec<NetlistComponent> = Vec::new();
+        for symbol in hierarchy.symbols() {
+            if symbol.reference.starts_with('#') || components.iter().any(|c| c.reference == symbol.reference) {
+                continue;
+            }
+            let sheet = &hierarchy.sheets[symbol.sheet];
+            let mut fields: Vec<(String, String)> = symbol
+                .properties
+                .iter()
+                .filter(|(name, _)| !BUILTIN_FIELDS.contains(&name.as_str()) && !name.starts_with("ki_"))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+            fields.sort();
+            let optional = |name: &str| symbol.property(name).filter(|v| !v.is_empty()).map(str::to_string);
+            // Symbol instance paths start with the root uuid, netlist paths do not
+            let sheet_path = match sheet.uuid_path.splitn(3, '/').nth(2) {
+                Some(sheets) => format!("/{}/", sheets),
+                None => "/".to_string(),
+            };
+            components.push(NetlistComponent {
+                reference: symbol.reference.clone(),
+                value: symbol.property("Value").unwrap_or_default().to_string(),
+                footprint: symbol.property("Footprint").unwrap_or_default().to_string(),
+                datasheet: optional("Datasheet"),
+                description: optional("Description"),
+                fields,
+                sheet_path,
+                sheet_name: Some(sheet.path.clone()),
+                sheet_file: Some(sheet.file.clone()),
+                tstamp: symbol.symbol.uuid.clone(),
+            });
+        }
+        components.sort_by(|a, b| natural_order(&a.reference, &b.reference));
+
+        let nets = hierarchy
+            .nets()
+            .into_iter()
+            .enumerate()
+            .map(|(i, net)| NetlistNet {
+                code: i + 1,
+                name: net.name,
+                nodes: net
+                    .nodes
+                    .into_iter()
+                    .map(|node| NetlistNode {
+                        pin_function: Some(node.pin_name).filter(|n| !n.is_empty() && n != "~"),
+                        pin_type: Some(node.pin_type).filter(|t| !t.is_empty()),
+                        reference: node.reference,
+                        pin: node.pin,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Netlist { components, nets }
+    }
+
+    /// KiCad XML netlist (`<export version="E">`)
+    pub fn to_xml(&self) -> String {
+        let mut xml =
+            String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<export version=\"E\">\n");
+        xml.push_str(&format!(
+            "  <design>\n    <tool>kiparse {}</tool>\n  </design>\n",
+            crate::VERSION
+        ));
+
+        xml.push_str("  <components>\n");
+        for c in &self.components {
+            xml.push_str(&format!("    <comp ref=\"{}\">\n", escape(&c.reference)));
+            xml.push_str(&format!("      <value>{}</value>\n", escape(&c.value)));
+            xml.push_str(&format!(
+                "      <footprint>{}</footprint>\n",
+                escape(&c.footprint)
+            ));
+            if let Some(datasheet) = &c.datasheet {
+                xml.push_str(&format!(
+                    "      <datasheet>{}</datasheet>\n",
+                    escape(datasheet)
+                ));
+            }
+            if let Some(description) = &c.description {
+                xml.push_str(&format!(
+                    "      <description>{}</description>\n",
+                    escape(description)
+                ));
+            }
+            if !c.fields.is_empty() {
+                xml.push_str("      <fields>\n");
+                for (name, value) in &c.fields {
+                    xml.push_str(&format!(
+                        "        <field name=\"{}\">{}</field>\n",
+                        escape(name),
+                        escape(value)
+                    ));
+                }
+                xml.push_str("      </fields>\n");
+            }
+            if let Some(name) = &c.sheet_name {
+                xml.push_str(&format!(
+                    "      <property name=\"Sheetname\" value=\"{}\"/>\n",
+                    escape(name)
+                ));
+            }
+            if let Some(file) = &c.sheet_file {
+                xml.push_str(&format!(
+                    "      <property name=\"Sheetfile\" value=\"{}\"/>\n",
+                    escape(file)
+                ));
+            }
+            let names = match c.sheet_name.as_deref() {
+                Some(name) if !name.is_empty() && name != "/" => {
+                    format!("/{}/", name.trim_matches('/'))
+                }
+                _ => "/".to_string(),
+            };
+            xml.push_str(&format!(
+                "      <sheetpath names=\"{}\" tstamps=\"{}\"/>\n",
+                escape(&names),
+                escape(&c.sheet_path)
+            ));
+            if let Some(tstamp) = &c.tstamp {
+                xml.push_str(&format!("      <tstamps>{}</tstamps>\n", escape(tstamp)));
+            }
+            xml.push_str("    </comp>\n");
+        }
+        xml.push_str("  </components>\n  <libparts/>\n  <libraries/>\n");
+
+        xml.push_str("  <nets>\n");
+        for net in &self.nets {
+            xml.push_str(&format!(
+                "    <net code=\"{}\" name=\"{}\">\n",
+                net.code,
+                escape(&net.name)
+            ));
+            for node in &net.nodes {
+                let mut attributes = format!(
+                    "ref=\"{}\" pin=\"{}\"",
+                    escape(&node.reference),
+                    escape(&node.pin)
+                );
+                if let Some(function) = &node.pin_function {
+                    attributes.push_str(&format!(" pinfunction=\"{}\"", escape(function)));
+                }
+                if let Some(pin_type) = &node.pin_type {
+                    attributes.push_str(&format!(" pintype=\"{}\"", escape(pin_type)));
+                }
+                xml.push_str(&format!("      <node {}/>\n", attributes));
+            }
+            xml.push_str("    </net>\n");
+        }
+        xml.push_str("  </nets>\n</export>\n");
+        xml
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (net 0 "") (net 1 "GND") (net 2 "/IN&OUT")
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 0 0)
+    (property "Reference" "R10") (property "Value" "10k") (property "Datasheet" "~")
+    (property "MPN" "RC0603") (property "ki_fp_filters" "R_*")
+    (path "/5c1e0000-0000-0000-0000-000000000001/a0000000-0000-0000-0000-000000000010")
+    (sheetname "Power") (sheetfile "power.kicad_sch")
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu") (net 2 "/IN&OUT") (pinfunction "B") (pintype "passive"))
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu") (net 1 "GND") (pintype "passive")))
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") (at 5 0)
+    (property "Reference" "R2") (property "Value" "1k") (property "Sheetname" "") (property "Sheetfile" "top.kicad_sch")
+    (path "/a0000000-0000-0000-0000-000000000002")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu") (net 1 "GND"))
+    (pad "1" smd rect (at -1 1) (size 1 1) (layers "F.Cu") (net 1 "GND")))
+  (footprint "Logo" (layer "F.Cu") (at 9 0)
+    (property "Reference" "G1") (attr board_only)
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 1 "GND")))
+)"#;
+
+    #[test]
+    fn test_from_pcb() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let netlist = Netlist::from_pcb(&pcb);
+
+        let refs: Vec<_> = netlist
+            .components
+            .iter()
+            .map(|c| c.reference.as_str())
+            .collect();
+        assert_eq!(refs, ["R2", "R10"]);
+        let r10 = &netlist.components[1];
+        assert_eq!(r10.fields, [("MPN".to_string(), "RC0603".to_string())]);
+        assert_eq!(r10.sheet_path, "/5c1e0000-0000-0000-0000-000000000001/");
+        assert_eq!(
+            r10.tstamp.as_deref(),
+            Some("a0000000-0000-0000-0000-000000000010")
+        );
+        // KiCad 7 sheet properties
+        assert_eq!(
+            netlist.components[0].sheet_file.as_deref(),
+            Some("top.kicad_sch")
+        );
+
+        let names: Vec<_> = netlist
+            .nets
+            .iter()
+            .map(|n| (n.code, n.name.as_str()))
+            .collect();
+        assert_eq!(names, [(1, "/IN&OUT"), (2, "GND")]);
+        let gnd: Vec<_> = netlist.nets[1]
+            .nodes
+            .iter()
+            .map(|n| format!("{}.{}", n.reference, n.pin))
+            .collect();
+        assert_eq!(gnd, ["R2.1", "R10.1"]);
+    }
+
+    #[test]
+    fn test_to_xml() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let xml = Netlist::from_pcb(&pcb).to_xml();
+
+        assert!(
+            xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<export version=\"E\">\n")
+        );
+        assert!(xml.contains(
+            "    <comp ref=\"R10\">\n      <value>10k</value>\n      <footprint>Resistor_SMD:R_0603</footprint>\n      <datasheet>~</datasheet>\n      <fields>\n        <field name=\"MPN\">RC0603</field>\n      </fields>\n"
+        ));
+        assert!(xml.contains(
+            "<sheetpath names=\"/Power/\" tstamps=\"/5c1e0000-0000-0000-0000-000000000001/\"/>\n      <tstamps>a0000000-0000-0000-0000-000000000010</tstamps>"
+        ));
+        assert!(xml.contains("<sheetpath names=\"/\" tstamps=\"/\"/>"));
+        assert!(xml.contains(
+            "    <net code=\"1\" name=\"/IN&amp;OUT\">\n      <node ref=\"R10\" pin=\"2\" pinfunction=\"B\" pintype=\"passive\"/>\n    </net>"
+        ));
+        assert!(!xml.contains("G1"));
+        assert!(xml.ends_with("  </nets>\n</export>\n"));
+    }
+
+    const LIB: &str = r#"(lib_symbols (symbol "Device:R" (symbol "R_1_1"
+    (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+    (pin passive line (at 0 -3.81 90) (length 1.27) (name "~") (number "2")))))"#;
+
+    #[test]
+    fn test_from_hierarchy() {
+        // R1 drives the OUT pin of sheet A; sheet B, the same file, has its own OUT
+        let root = format!(
+            r#"(kicad_sch (uuid "root") {}
+  (symbol (lib_id "Device:R") (at 100 50 0) (unit 1) (uuid "r1") (property "Reference" "R1") (property "Value" "1k")
+    (property "MPN" "RC0603"))
+  (global_label "VCC" (shape input) (at 100 46.19 0))
+  (wire (pts (xy 100 53.81) (xy 120 53.81)))
+  (sheet (at 120 40) (size 20 20) (uuid "sa") (property "Sheetname" "A") (property "Sheetfile" "child.kicad_sch")
+    (pin "OUT" output (at 120 53.81 180)))
+  (sheet (at 120 70) (size 20 20) (uuid "sb") (property "Sheetname" "B") (property "Sheetfile" "child.kicad_sch")
+    (pin "OUT" output (at 120 80 180))))"#,
+            LIB
+        );
+        let child = format!(
+            r#"(kicad_sch (uuid "child") {}
+  (symbol (lib_id "Device:R") (at 50 50 0) (unit 1) (uuid "rc") (property "Reference" "R?") (property "Value" "10k")
+    (instances (project "demo" (path "/root/sa" (reference "R2") (unit 1)) (path "/root/sb" (reference "R3") (unit 1)))))
+  (hierarchical_label "OUT" (shape output) (at 50 53.81 0))
+  (global_label "VCC" (shape input) (at 50 46.19 0)))"#,
+            LIB
+        );
+        let hierarchy = Hierarchy::build("demo.kicad_sch", &root, |_| Ok(child.clone())).unwrap();
+        let netlist = Netlist::from_hierarchy(&hierarchy);
+
+        let nets: Vec<(usize, &str, Vec<String>)> = netlist
+            .nets
+            .iter()
+            .map(|n| (n.code, n.name.as_str(), n.nodes.iter().map(|p| format!("{}.{}", p.reference, p.pin)).collect()))
+            .collect();
+        assert_eq!(
+            nets,
+            [
+                (1, "/B/OUT", vec!["R3.2".to_string()]),
+                (2, "/OUT", vec!["R1.2".to_string(), "R2.2".to_string()]),
+                (3, "VCC", vec!["R1.1".to_string(), "R2.1".to_string(), "R3.1".to_string()]),
+            ]
+        );
+
+        let r2 = &netlist.components[1];
+        assert_eq!((r2.reference.as_str(), r2.value.as_str()), ("R2", "10k"));
+        assert_eq!(r2.sheet_path, "/sa/");
+        assert_eq!(netlist.components[0].fields, [("MPN".to_string(), "RC0603".to_string())]);
+        let xml = netlist.to_xml();
+        assert!(xml.contains("<sheetpath names=\"/A/\" tstamps=\"/sa/\"/>\n      <tstamps>rc</tstamps>"));
+        assert!(xml.contains("<node ref=\"R3\" pin=\"2\" pintype=\"passive\"/>"));
+    }
+}
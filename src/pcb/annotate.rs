@@ -0,0 +1,295 @@
+//! Re-annotation of footprint references
+//!
+//! [`plan`] works out new reference designators for a parsed board, renumbering
+//! each prefix by position and optionally renaming prefixes, and [`apply`] writes
+//! them into the original file text. Only the reference fields change; the rest of
+//! the file is kept byte for byte. The [writer](super::writer) is not used for
+//! this, as it rebuilds the board from the parsed types and leaves out what they
+//! do not write back (net classes, the stackup, groups, images, rule areas and
+//! tuning patterns).
+//!
+//! ```rust
+//! use kiparse::pcb::annotate::{annotate, AnnotateOptions};
+//!
+//! let content = r#"(kicad_pcb
+//!   (footprint "R" (layer "F.Cu") (at 20 10) (property "Reference" "R7"))
+//!   (footprint "R" (layer "F.Cu") (at 5 10) (property "Reference" "R3"))
+//!   (footprint "C" (layer "F.Cu") (at 5 30) (property "Reference" "C1"))
+//! )"#;
+//! let (annotated, renames) = annotate(content, &AnnotateOptions::default())?;
+//!
+//! assert_eq!(renames.len(), 2);
+//! assert!(annotated.contains(r#"(at 5 10) (property "Reference" "R1")"#));
+//! assert!(annotated.contains(r#"(at 20 10) (property "Reference" "R2")"#));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::pcb_parser::parse_pcb;
+use super::types::{Footprint, PcbFile};
+use crate::error::{KicadError, Result};
+use crate::units::Length;
+
+/// Order in which the footprints of a prefix are numbered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnnotationOrder {
+    /// Top to bottom, then left to right within a row
+    Rows,
+    /// Left to right, then top to bottom within a column
+    Columns,
+}
+
+/// What [`plan`] changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotateOptions {
+    /// How to renumber; `None` keeps the numbers and only renames prefixes
+    pub order: Option<AnnotationOrder>,
+    /// First number of each prefix
+    pub start: u32,
+    /// Positions closer than this across the numbering direction share a row or
+    /// column
+    pub grid: Length,
+    /// Prefix renames, e.g. `U` to `IC`
+    pub prefixes: BTreeMap<String, String>,
+}
+
+impl Default for AnnotateOptions {
+    fn default() -> Self {
+        Self {
+            order: Some(AnnotationOrder::Rows),
+            start: 1,
+            grid: Length::mm(1.0),
+            prefixes: BTreeMap::new(),
+        }
+    }
+}
+
+/// One changed reference designator
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// New references for the footprints of a board, in board order
+///
+/// Front footprints are numbered before back ones. Every unannotated `R?` is
+/// numbered; references without a trailing number or `?` (such as `REF**`) and
+/// numbered references used by more than one footprint are left alone. Fails
+/// when a new reference would clash with an existing one.
+pub fn plan(pcb: &PcbFile, options: &AnnotateOptions) -> Result<Vec<Rename>> {
+    let references: Vec<&str> = pcb.footprints.iter().map(reference).collect();
+    let mut uses: HashMap<&str, usize> = HashMap::new();
+    for r in &references {
+        *uses.entry(r).or_default() += 1;
+    }
+
+    // Footprints to annotate by board index, grouped by their new prefix
+    let mut groups: BTreeMap<String, Vec<(usize, &Footprint, &str)>> = BTreeMap::new();
+    for (i, (footprint, &r)) in pcb.footprints.iter().zip(&references).enumerate() {
+        let Some((prefix, number)) = split(r) else { continue };
+        if number != "?" && uses[r] > 1 {
+            continue;
+        }
+        let prefix = options.prefixes.get(prefix).map_or(prefix, String::as_str);
+        groups.entry(prefix.to_string()).or_default().push((i, footprint, number));
+    }
+
+    let mut new_names: Vec<Option<String>> = vec![None; references.len()];
+    for (prefix, mut members) in groups {
+        match options.order {
+            None => {
+                for (i, _, number) in members {
+                    new_names[i] = Some(format!("{}{}", prefix, number));
+                }
+            }
+            Some(order) => {
+                let grid = if options.grid > Length::ZERO { options.grid } else { Length::mm(1.0) };
+                let key = |f: &Footprint| {
+                    let (across, along) = match order {
+                        AnnotationOrder::Rows => (f.position.y, f.position.x),
+                        AnnotationOrder::Columns => (f.position.x, f.position.y),
+                    };
+                    (f.layer.starts_with("B."), (across / grid).round() as i64, along)
+                };
+                members.sort_by(|(_, a, _), (_, b, _)| {
+                    let (a, b) = (key(a), key(b));
+                    (a.0, a.1).cmp(&(b.0, b.1)).then(a.2.total_cmp(&b.2))
+                });
+                for (n, (i, ..)) in members.into_iter().enumerate() {
+                    new_names[i] = Some(format!("{}{}", prefix, options.start as usize + n));
+                }
+            }
+        }
+    }
+
+    let mut taken: HashMap<&str, usize> = HashMap::new();
+    for (r, new) in references.iter().zip(&new_names) {
+        *taken.entry(new.as_deref().unwrap_or(r)).or_default() += 1;
+    }
+    let clash = new_names.iter().flatten().find(|n| !n.ends_with('?') && taken[n.as_str()] > 1);
+    if let Some(clash) = clash {
+        return Err(KicadError::InvalidFormat(format!(
+            "Annotation would give two footprints the reference {}",
+            clash
+        )));
+    }
+
+    Ok(references
+        .iter()
+        .zip(new_names)
+        .filter_map(|(r, to)| {
+            let to = to?;
+            (to != *r).then(|| Rename {
+                from: r.to_string(),
+                to,
+            })
+        })
+        .collect())
+}
+
+/// `(property "Reference" X` (KiCad 8) and `(fp_text reference X` (KiCad 6 and 7)
+static REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(\((?:property\s+"Reference"|fp_text\s+reference)\s+)("(?:[^"\\]|\\.)*"|[^\s()"]+)"#)
+        .unwrap()
+});
+
+/// Write renamed references into the text of a board
+///
+/// `renames` are taken in board order, so footprints sharing a reference such as
+/// `R?` each get their own new one.
+pub fn apply(content: &str, renames: &[Rename]) -> String {
+    let mut names: HashMap<&str, VecDeque<&str>> = HashMap::new();
+    for rename in renames {
+        names.entry(rename.from.as_str()).or_default().push_back(rename.to.as_str());
+    }
+    REFERENCE_REGEX
+        .replace_all(content, |caps: &Captures| {
+            let token = &caps[2];
+            let current = match token.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+                Some(quoted) => quoted.replace("\\\"", "\"").replace("\\\\", "\\"),
+                None => token.to_string(),
+            };
+            match names.get_mut(current.as_str()).and_then(VecDeque::pop_front) {
+                Some(new) => format!(
+                    "{}\"{}\"",
+                    &caps[1],
+                    new.replace('\\', "\\\\").replace('"', "\\\"")
+                ),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// Parse a board, plan the new references and write them into its text
+pub fn annotate(content: &str, options: &AnnotateOptions) -> Result<(String, Vec<Rename>)> {
+    let pcb = parse_pcb(content)?;
+    let renames = plan(&pcb, options)?;
+    Ok((apply(content, &renames), renames))
+}
+
+fn reference(footprint: &Footprint) -> &str {
+    footprint.property("Reference").unwrap_or_default()
+}
+
+/// Prefix and number of `R12` or `R?`; `None` for references without either
+fn split(reference: &str) -> Option<(&str, &str)> {
+    let prefix = match reference.strip_suffix('?') {
+        Some(prefix) => prefix,
+        None => reference.trim_end_matches(|c: char| c.is_ascii_digit()),
+    };
+    let valid = !prefix.is_empty()
+        && prefix.len() < reference.len()
+        && prefix.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '#');
+    valid.then(|| (prefix, &reference[prefix.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (footprint "R" (layer "F.Cu") (at 30 10.4) (property "Reference" "R1"))
+  (footprint "R" (layer "F.Cu") (at 10 10) (property "Reference" "R5"))
+  (footprint "R" (layer "B.Cu") (at 0 0) (property "Reference" "R2"))
+  (footprint "R" (layer "F.Cu") (at 0 20) (property "Reference" "R?"))
+  (footprint "U" (layer "F.Cu") (at 0 0) (property "Reference" "U1"))
+  (footprint "Logo" (layer "F.Cu") (at 0 0) (property "Reference" "REF**"))
+  (footprint "Logo" (layer "F.Cu") (at 5 5) (property "Reference" "G1"))
+  (footprint "Logo" (layer "F.Cu") (at 9 9) (property "Reference" "G1"))
+)"#;
+
+    fn renames(options: &AnnotateOptions) -> Vec<(String, String)> {
+        plan(&parse_pcb(BOARD).unwrap(), options)
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.from, r.to))
+            .collect()
+    }
+
+    fn pair(from: &str, to: &str) -> (String, String) {
+        (from.to_string(), to.to_string())
+    }
+
+    #[test]
+    fn test_renumber_by_position() {
+        // Front rows first (y 10 and 10.4 share a row), then the back side
+        assert_eq!(
+            renames(&AnnotateOptions::default()),
+            vec![pair("R1", "R2"), pair("R5", "R1"), pair("R2", "R4"), pair("R?", "R3")]
+        );
+        let columns = AnnotateOptions {
+            order: Some(AnnotationOrder::Columns),
+            start: 10,
+            ..AnnotateOptions::default()
+        };
+        assert_eq!(
+            renames(&columns),
+            vec![pair("R1", "R12"), pair("R5", "R11"), pair("R2", "R13"), pair("R?", "R10"), pair("U1", "U10")]
+        );
+    }
+
+    #[test]
+    fn test_numbers_every_unannotated() {
+        let content = r#"(kicad_pcb
+  (footprint "R" (layer "F.Cu") (at 20 0) (property "Reference" "R?"))
+  (footprint "R" (layer "F.Cu") (at 10 0) (property "Reference" "R?"))
+  (footprint "R" (layer "F.Cu") (at 0 0) (property "Reference" "R?"))
+)"#;
+        let (annotated, renames) = annotate(content, &AnnotateOptions::default()).unwrap();
+        let to: Vec<&str> = renames.iter().map(|r| r.to.as_str()).collect();
+        assert_eq!(to, ["R3", "R2", "R1"]);
+        assert!(annotated.contains(r#"(at 20 0) (property "Reference" "R3")"#));
+        assert!(annotated.contains(r#"(at 0 0) (property "Reference" "R1")"#));
+    }
+
+    #[test]
+    fn test_prefixes_and_clashes() {
+        let mut options = AnnotateOptions {
+            order: None,
+            ..AnnotateOptions::default()
+        };
+        options.prefixes.insert("U".to_string(), "IC".to_string());
+        assert_eq!(renames(&options), vec![pair("U1", "IC1")]);
+
+        options.prefixes.insert("U".to_string(), "R".to_string());
+        let error = plan(&parse_pcb(BOARD).unwrap(), &options).unwrap_err();
+        assert!(error.to_string().contains("reference R1"));
+    }
+
+    #[test]
+    fn test_apply_keeps_the_rest() {
+        let content = "(kicad_pcb\n  (footprint \"R\" (at 1 2)\n    (fp_text reference R3 (at 0 -1.5))\n    (fp_text value \"R3\" (at 0 1.5))))";
+        let (annotated, renames) = annotate(content, &AnnotateOptions::default()).unwrap();
+        assert_eq!(renames, vec![Rename { from: "R3".to_string(), to: "R1".to_string() }]);
+        assert_eq!(annotated, content.replacen("reference R3", "reference \"R1\"", 1));
+        assert_eq!(apply(BOARD, &[]), BOARD);
+    }
+}
@@ -0,0 +1,655 @@
+//! Fluent builders for creating boards in code
+//!
+//! [`PcbBuilder`] sets up the layer table, board outline, nets, tracks and vias;
+//! [`FootprintBuilder`] and [`PadBuilder`] describe the parts placed on it. Nets
+//! named on pads, tracks and vias are added to the net table automatically, in
+//! the order they first appear. The finished [`PcbFile`] can be analysed like a
+//! parsed board or written out with [`PcbFile::to_kicad_pcb`].
+//!
+//! ```rust
+//! use kiparse::pcb::builder::{FootprintBuilder, PadBuilder, PcbBuilder};
+//!
+//! let pcb = PcbBuilder::new()
+//!     .outline_rect((0.0, 0.0), (30.0, 20.0))
+//!     .footprint(
+//!         FootprintBuilder::new("Resistor_SMD:R_0603_1608Metric", "R1")
+//!             .at(10.0, 10.0)
+//!             .value("10k")
+//!             .pad(PadBuilder::smd("1").at(-0.8, 0.0).size(0.8, 0.95).net("VIN"))
+//!             .pad(PadBuilder::smd("2").at(0.8, 0.0).size(0.8, 0.95).net("GND")),
+//!     )
+//!     .route(&[(9.2, 10.0), (5.0, 10.0), (5.0, 5.0)], 0.25, "F.Cu", "VIN")
+//!     .via((5.0, 5.0), 0.6, 0.3, "VIN")
+//!     .build();
+//!
+//! assert_eq!(pcb.layers.len(), 20);
+//! assert_eq!(pcb.tracks.len(), 2);
+//! assert_eq!(pcb.nets.get(&1).map(String::as_str), Some("VIN"));
+//! assert!(pcb.to_kicad_pcb().contains("(footprint \"Resistor_SMD:R_0603_1608Metric\""));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use super::types::{
+    FieldText, Footprint, FootprintAttributes, Graphic, Layer, MaskPasteMargins, Model3D, Pad, PcbFile, Point,
+    Rect, Track, Via, ViaFinish, ViaType, Zone, ZoneFill,
+};
+use crate::units::Length;
+
+/// Non-copper layers of a KiCad 6+ board, in layer number order from 32
+const TECHNICAL_LAYERS: &[(&str, &str)] = &[
+    ("B.Adhes", "B.Adhesive"),
+    ("F.Adhes", "F.Adhesive"),
+    ("B.Paste", ""),
+    ("F.Paste", ""),
+    ("B.SilkS", "B.Silkscreen"),
+    ("F.SilkS", "F.Silkscreen"),
+    ("B.Mask", ""),
+    ("F.Mask", ""),
+    ("Dwgs.User", "User.Drawings"),
+    ("Cmts.User", "User.Comments"),
+    ("Eco1.User", "User.Eco1"),
+    ("Eco2.User", "User.Eco2"),
+    ("Edge.Cuts", ""),
+    ("Margin", ""),
+    ("B.CrtYd", "B.Courtyard"),
+    ("F.CrtYd", "F.Courtyard"),
+    ("B.Fab", ""),
+    ("F.Fab", ""),
+];
+
+/// Layer number KiCad 6 to 8 use for a standard layer name, -1 when unknown
+pub fn layer_id(name: &str) -> i32 {
+    match name {
+        "F.Cu" => 0,
+        "B.Cu" => 31,
+        _ => match name.strip_prefix("In").and_then(|n| n.strip_suffix(".Cu")) {
+            Some(inner) => inner.parse().unwrap_or(-1),
+            None => TECHNICAL_LAYERS
+                .iter()
+                .position(|(l, _)| *l == name)
+                .map_or(-1, |i| i as i32 + 32),
+        },
+    }
+}
+
+/// Layer table of a board with `copper` copper layers (at least 2) and every
+/// technical layer
+pub fn standard_layers(copper: usize) -> HashMap<i32, Layer> {
+    let copper = copper.max(2);
+    let mut names: Vec<(String, &str, &str)> = vec![("F.Cu".to_string(), "signal", "")];
+    names.extend((1..copper - 1).map(|i| (format!("In{}.Cu", i), "signal", "")));
+    names.push(("B.Cu".to_string(), "signal", ""));
+    names.extend(TECHNICAL_LAYERS.iter().map(|(name, user_name)| (name.to_string(), "user", *user_name)));
+    names
+        .into_iter()
+        .map(|(name, kind, user_name)| {
+            let id = layer_id(&name);
+            let layer = Layer {
+                id,
+                name,
+                layer_type: kind.to_string(),
+                user_name: Some(user_name.to_string()).filter(|u| !u.is_empty()),
+            };
+            (id, layer)
+        })
+        .collect()
+}
+
+/// The same layer on the other side of the board; inner, user and `*.` layers stay
+pub(crate) fn flip_layer(layer: &str) -> String {
+    if let Some(rest) = layer.strip_prefix("F.") {
+        format!("B.{}", rest)
+    } else if let Some(rest) = layer.strip_prefix("B.") {
+        format!("F.{}", rest)
+    } else {
+        layer.to_string()
+    }
+}
+
+fn point((x, y): (f64, f64)) -> Point {
+    Point::new(x, y)
+}
+
+fn net_name(net: &str) -> Option<String> {
+    Some(net.to_string()).filter(|n| !n.is_empty())
+}
+
+/// Builder for a whole board
+#[derive(Debug, Clone)]
+pub struct PcbBuilder {
+    pcb: PcbFile,
+    net_order: Vec<String>,
+}
+
+impl Default for PcbBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PcbBuilder {
+    /// Empty 1.6 mm two-layer board
+    pub fn new() -> Self {
+        let mut pcb = PcbFile::new();
+        pcb.version = "20240108".to_string();
+        pcb.generator = "kiparse".to_string();
+        pcb.board_thickness = Some(Length::mm(1.6));
+        pcb.paper_size = Some("A4".to_string());
+        pcb.layers = standard_layers(2);
+        PcbBuilder { pcb, net_order: Vec::new() }
+    }
+
+    /// Number of copper layers (2, 4, 6, ...); replaces the layer table
+    pub fn copper_layers(mut self, count: usize) -> Self {
+        self.pcb.layers = standard_layers(count);
+        self
+    }
+
+    /// Board thickness in mm
+    pub fn thickness(mut self, mm: f64) -> Self {
+        self.pcb.board_thickness = Some(Length::mm(mm));
+        self
+    }
+
+    /// Declare a net even if nothing uses it yet
+    pub fn net(mut self, name: &str) -> Self {
+        self.add_net(name);
+        self
+    }
+
+    fn add_net(&mut self, name: &str) {
+        if !name.is_empty() && !self.net_order.iter().any(|n| n == name) {
+            self.net_order.push(name.to_string());
+        }
+    }
+
+    /// Rectangular board outline between two corners
+    pub fn outline_rect(mut self, start: (f64, f64), end: (f64, f64)) -> Self {
+        self.pcb.graphics.push(Graphic::Rectangle {
+            rect: Rect { start: point(start), end: point(end) },
+            layer: "Edge.Cuts".to_string(),
+            width: Length::mm(0.05),
+            filled: false,
+            uuid: String::new(),
+        });
+        self
+    }
+
+    /// Polygonal board outline; the last point connects back to the first
+    pub fn outline(mut self, points: &[(f64, f64)]) -> Self {
+        self.pcb.graphics.push(Graphic::Polygon {
+            points: points.iter().copied().map(point).collect(),
+            layer: "Edge.Cuts".to_string(),
+            width: Length::mm(0.05),
+            filled: false,
+            uuid: String::new(),
+        });
+        self
+    }
+
+    /// Any board-level drawing
+    pub fn graphic(mut self, graphic: Graphic) -> Self {
+        self.pcb.graphics.push(graphic);
+        self
+    }
+
+    /// Place a footprint
+    pub fn footprint(mut self, footprint: FootprintBuilder) -> Self {
+        let footprint = footprint.build();
+        for pad in &footprint.pads {
+            if let Some(net) = &pad.net {
+                self.add_net(net);
+            }
+        }
+        self.pcb.footprints.push(footprint);
+        self
+    }
+
+    /// Straight track; an empty `net` leaves it unconnected
+    pub fn track(mut self, start: (f64, f64), end: (f64, f64), width: f64, layer: &str, net: &str) -> Self {
+        self.add_net(net);
+        self.pcb.tracks.push(Track {
+            start: point(start),
+            end: point(end),
+            width: Length::mm(width),
+            layer: layer.to_string(),
+            net: net_name(net),
+            uuid: String::new(),
+        });
+        self
+    }
+
+    /// Tracks joining consecutive points
+    pub fn route(mut self, points: &[(f64, f64)], width: f64, layer: &str, net: &str) -> Self {
+        for pair in points.windows(2) {
+            self = self.track(pair[0], pair[1], width, layer, net);
+        }
+        self
+    }
+
+    /// Through via spanning the outer copper layers
+    pub fn via(mut self, at: (f64, f64), size: f64, drill: f64, net: &str) -> Self {
+        self.add_net(net);
+        self.pcb.vias.push(Via {
+            position: point(at),
+            via_type: ViaType::Through,
+            size: Length::mm(size),
+            drill: Length::mm(drill),
+            layers: vec!["F.Cu".to_string(), "B.Cu".to_string()],
+            net: net_name(net),
+            uuid: String::new(),
+            finish: ViaFinish::default(),
+        });
+        self
+    }
+
+    /// Copper pour outline on one layer, left unfilled
+    pub fn zone(mut self, net: &str, layer: &str, outline: &[(f64, f64)]) -> Self {
+        self.add_net(net);
+        self.pcb.zones.push(Zone {
+            uuid: String::new(),
+            net: net_name(net),
+            layer: layer.to_string(),
+            layers: vec![layer.to_string()],
+            priority: 0,
+            connect_pads: true,
+            polygon: outline.iter().copied().map(point).collect(),
+            filled_polygons: Vec::new(),
+            fill: ZoneFill::default(),
+        });
+        self
+    }
+
+    /// The finished board; nets are numbered from 1 in order of first use
+    pub fn build(mut self) -> PcbFile {
+        self.pcb.nets.insert(0, String::new());
+        for (i, name) in self.net_order.into_iter().enumerate() {
+            self.pcb.nets.insert(i as u32 + 1, name);
+        }
+        self.pcb
+    }
+}
+
+/// Builder for a footprint placed on the board
+#[derive(Debug, Clone)]
+pub struct FootprintBuilder {
+    footprint: Footprint,
+    back: bool,
+}
+
+impl FootprintBuilder {
+    /// Footprint `lib_id` (e.g. `Resistor_SMD:R_0603_1608Metric`) with reference `reference`
+    pub fn new(lib_id: &str, reference: &str) -> Self {
+        let mut properties = HashMap::new();
+        properties.insert("Reference".to_string(), reference.to_string());
+        properties.insert("Value".to_string(), String::new());
+        properties.insert("Footprint".to_string(), lib_id.to_string());
+        FootprintBuilder {
+            footprint: Footprint {
+                name: lib_id.to_string(),
+                uuid: String::new(),
+                position: Point::default(),
+                rotation: 0.0,
+                layer: "F.Cu".to_string(),
+                locked: false,
+                placed: false,
+                attributes: FootprintAttributes::default(),
+                properties,
+                path: None,
+                sheet_name: None,
+                sheet_file: None,
+                net_tie_pad_groups: Vec::new(),
+                margins: MaskPasteMargins::default(),
+                fields: Vec::new(),
+                pads: Vec::new(),
+                graphics: Vec::new(),
+                texts: Vec::new(),
+                models: Vec::new(),
+                embedded_files: Vec::new(),
+            },
+            back: false,
+        }
+    }
+
+    /// Position of the footprint origin on the board
+    pub fn at(mut self, x: f64, y: f64) -> Self {
+        self.footprint.position = Point::new(x, y);
+        self
+    }
+
+    /// Rotation in degrees, counter-clockwise as displayed
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.footprint.rotation = degrees;
+        self
+    }
+
+    /// Place on the bottom side: layers are flipped and local x mirrored, as KiCad's flip does
+    pub fn back(mut self) -> Self {
+        self.back = true;
+        self
+    }
+
+    pub fn value(self, value: &str) -> Self {
+        self.property("Value", value)
+    }
+
+    /// Set a property such as `MPN` or `Datasheet`
+    pub fn property(mut self, name: &str, value: &str) -> Self {
+        self.footprint.properties.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Mount as an SMD part (placement files list it)
+    pub fn smd(mut self) -> Self {
+        self.footprint.attributes.mount = "smd".to_string();
+        self
+    }
+
+    /// Mount as a through-hole part
+    pub fn through_hole(mut self) -> Self {
+        self.footprint.attributes.mount = "through_hole".to_string();
+        self
+    }
+
+    /// Fabrication flags such as `dnp` or `exclude_from_bom`
+    pub fn attributes(mut self, attributes: FootprintAttributes) -> Self {
+        self.footprint.attributes = attributes;
+        self
+    }
+
+    pub fn pad(mut self, pad: PadBuilder) -> Self {
+        self.footprint.pads.push(pad.pad);
+        self
+    }
+
+    /// Drawing in footprint-local coordinates, e.g. a courtyard or silkscreen line
+    pub fn graphic(mut self, graphic: Graphic) -> Self {
+        self.footprint.graphics.push(graphic);
+        self
+    }
+
+    /// Rectangle of `width` × `height` centred on the origin, e.g. on `F.CrtYd`
+    pub fn rect(self, layer: &str, width: f64, height: f64, line_width: f64) -> Self {
+        self.graphic(Graphic::Rectangle {
+            rect: Rect {
+                start: Point::new(-width / 2.0, -height / 2.0),
+                end: Point::new(width / 2.0, height / 2.0),
+            },
+            layer: layer.to_string(),
+            width: Length::mm(line_width),
+            filled: false,
+            uuid: String::new(),
+        })
+    }
+
+    /// 3D model file, e.g. `${KICAD8_3DMODEL_DIR}/Resistor_SMD.3dshapes/R_0603_1608Metric.wrl`
+    pub fn model(mut self, path: &str) -> Self {
+        self.footprint.models.push(Model3D {
+            path: path.to_string(),
+            offset: [0.0; 3],
+            scale: [1.0; 3],
+            rotate: [0.0; 3],
+            hide: false,
+            opacity: None,
+        });
+        self
+    }
+
+    /// The footprint, with pad orientations made absolute and bottom-side items flipped
+    pub fn build(self) -> Footprint {
+        let mut footprint = self.footprint;
+        footprint.fields = super::writer::ordered_properties(&footprint)
+            .into_iter()
+            .map(|(name, value)| FieldText::new(name, value))
+            .collect();
+        let rotation = footprint.rotation;
+        for pad in &mut footprint.pads {
+            pad.rotation = (pad.rotation + rotation).rem_euclid(360.0);
+        }
+        if self.back {
+            footprint.layer = "B.Cu".to_string();
+            for pad in &mut footprint.pads {
+                pad.position.x = -pad.position.x;
+                pad.layers = pad.layers.iter().map(|l| flip_layer(l)).collect();
+            }
+            for field in &mut footprint.fields {
+                field.text.layer = flip_layer(&field.text.layer);
+            }
+            for graphic in &mut footprint.graphics {
+                flip_graphic(graphic);
+            }
+        }
+        footprint
+    }
+}
+
+/// Mirror a footprint drawing left to right onto the other side
+fn flip_graphic(graphic: &mut Graphic) {
+    let mirror = |p: &mut Point| p.x = -p.x;
+    match graphic {
+        Graphic::Line { start, end, layer, .. } => {
+            mirror(start);
+            mirror(end);
+            *layer = flip_layer(layer);
+        }
+        Graphic::Circle { center, layer, .. } => {
+            mirror(center);
+            *layer = flip_layer(layer);
+        }
+        Graphic::Arc { arc, layer, .. } => {
+            mirror(&mut arc.center);
+            let (start, end) = (180.0 - arc.end_angle, 180.0 - arc.start_angle);
+            arc.start_angle = start;
+            arc.end_angle = end;
+            *layer = flip_layer(layer);
+        }
+        Graphic::Rectangle { rect, layer, .. } => {
+            mirror(&mut rect.start);
+            mirror(&mut rect.end);
+            *layer = flip_layer(layer);
+        }
+        Graphic::Polygon { points, layer, .. } => {
+            points.iter_mut().for_each(mirror);
+            *layer = flip_layer(layer);
+        }
+        Graphic::Bezier { start, control1, control2, end, layer, .. } => {
+            [start, control1, control2, end].into_iter().for_each(mirror);
+            *layer = flip_layer(layer);
+        }
+    }
+}
+
+/// Builder for a footprint pad
+#[derive(Debug, Clone)]
+pub struct PadBuilder {
+    pad: Pad,
+}
+
+impl PadBuilder {
+    fn new(number: &str, pad_type: &str, shape: &str, layers: &[&str]) -> Self {
+        PadBuilder {
+            pad: Pad {
+                number: number.to_string(),
+                pad_type: pad_type.to_string(),
+                shape: shape.to_string(),
+                position: Point::default(),
+                rotation: 0.0,
+                size: Point::new(1.0, 1.0),
+                drill: None,
+                drill_oval: None,
+                layers: layers.iter().map(|l| l.to_string()).collect(),
+                net: None,
+                pin_function: None,
+                pin_type: None,
+                die_length: None,
+                roundrect_ratio: None,
+                margins: MaskPasteMargins::default(),
+            },
+        }
+    }
+
+    /// Rectangular surface-mount pad on `F.Cu`, `F.Paste` and `F.Mask`
+    pub fn smd(number: &str) -> Self {
+        Self::new(number, "smd", "rect", &["F.Cu", "F.Paste", "F.Mask"])
+    }
+
+    /// Plated round through-hole pad on every copper layer
+    pub fn through_hole(number: &str, drill: f64) -> Self {
+        let mut builder = Self::new(number, "thru_hole", "circle", &["*.Cu", "*.Mask"]);
+        builder.pad.drill = Some(Length::mm(drill));
+        builder.pad.size = Point::new(drill + 0.6, drill + 0.6);
+        builder
+    }
+
+    /// Unplated mounting hole
+    pub fn npth(drill: f64) -> Self {
+        let mut builder = Self::new("", "np_thru_hole", "circle", &["*.Cu", "*.Mask"]);
+        builder.pad.drill = Some(Length::mm(drill));
+        builder.pad.size = Point::new(drill, drill);
+        builder
+    }
+
+    /// Position relative to the footprint origin
+    pub fn at(mut self, x: f64, y: f64) -> Self {
+        self.pad.position = Point::new(x, y);
+        self
+    }
+
+    /// Rotation relative to the footprint, in degrees
+    pub fn rotation(mut self, degrees: f64) -> Self {
+        self.pad.rotation = degrees;
+        self
+    }
+
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.pad.size = Point::new(width, height);
+        self
+    }
+
+    /// Pad shape: `rect`, `circle`, `oval`, `roundrect`, `trapezoid` or `custom`
+    pub fn shape(mut self, shape: &str) -> Self {
+        self.pad.shape = shape.to_string();
+        self
+    }
+
+    /// Rounded rectangle with the corner radius as a fraction of the smaller side
+    pub fn roundrect(mut self, ratio: f64) -> Self {
+        self.pad.shape = "roundrect".to_string();
+        self.pad.roundrect_ratio = Some(ratio);
+        self
+    }
+
+    pub fn drill(mut self, diameter: f64) -> Self {
+        self.pad.drill = Some(Length::mm(diameter));
+        self.pad.drill_oval = None;
+        self
+    }
+
+    /// Slot of `width` × `height`
+    pub fn oval_drill(mut self, width: f64, height: f64) -> Self {
+        self.pad.drill = Some(Length::mm(width));
+        self.pad.drill_oval = Some(Point::new(width, height));
+        self
+    }
+
+    /// Layers the pad is on, e.g. `["B.Cu", "B.Paste", "B.Mask"]`
+    pub fn layers(mut self, layers: &[&str]) -> Self {
+        self.pad.layers = layers.iter().map(|l| l.to_string()).collect();
+        self
+    }
+
+    pub fn net(mut self, net: &str) -> Self {
+        self.pad.net = net_name(net);
+        self
+    }
+
+    /// Schematic pin name and electrical type, e.g. `("VCC", "power_in")`
+    pub fn pin(mut self, function: &str, pin_type: &str) -> Self {
+        self.pad.pin_function = Some(function.to_string());
+        self.pad.pin_type = Some(pin_type.to_string());
+        self
+    }
+
+    /// Length from the pad to the die inside the package, in mm
+    pub fn die_length(mut self, length: f64) -> Self {
+        self.pad.die_length = Some(Length::mm(length));
+        self
+    }
+
+    pub fn build(self) -> Pad {
+        self.pad
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    fn board() -> PcbFile {
+        PcbBuilder::new()
+            .copper_layers(4)
+            .outline(&[(0.0, 0.0), (40.0, 0.0), (40.0, 25.0), (0.0, 25.0)])
+            .net("3V3")
+            .footprint(
+                FootprintBuilder::new("Connector_PinHeader_2.54mm:PinHeader_1x02_P2.54mm_Vertical", "J1")
+                    .at(5.0, 10.0)
+                    .rotation(90.0)
+                    .through_hole()
+                    .pad(PadBuilder::through_hole("1", 1.0).shape("rect").size(1.7, 1.7).net("GND"))
+                    .pad(PadBuilder::through_hole("2", 1.0).at(0.0, 2.54).size(1.7, 1.7).net("3V3"))
+                    .rect("F.CrtYd", 2.5, 5.0, 0.05),
+            )
+            .footprint(
+                FootprintBuilder::new("Capacitor_SMD:C_0402_1005Metric", "C1")
+                    .at(20.0, 10.0)
+                    .back()
+                    .value("100n")
+                    .property("MPN", "GRM155R71C104KA88D")
+                    .pad(PadBuilder::smd("1").at(-0.5, 0.0).size(0.6, 0.6).roundrect(0.25).net("3V3"))
+                    .pad(PadBuilder::smd("2").at(0.5, 0.0).size(0.6, 0.6).roundrect(0.25).net("GND")),
+            )
+            .track((5.0, 10.0), (19.5, 10.0), 0.3, "B.Cu", "3V3")
+            .via((30.0, 20.0), 0.6, 0.3, "GND")
+            .zone("GND", "In1.Cu", &[(0.0, 0.0), (40.0, 0.0), (40.0, 25.0), (0.0, 25.0)])
+            .build()
+    }
+
+    #[test]
+    fn test_builds_board() {
+        let pcb = board();
+        assert_eq!(pcb.layers.len(), 22);
+        assert_eq!(pcb.layers[&2].name, "In2.Cu");
+        assert_eq!(pcb.layers[&37].user_name.as_deref(), Some("F.Silkscreen"));
+        assert_eq!(pcb.nets.len(), 3);
+        assert_eq!(pcb.nets[&1], "3V3");
+        assert_eq!(pcb.nets[&2], "GND");
+
+        let header = &pcb.footprints[0];
+        assert_eq!(header.property("Reference"), Some("J1"));
+        assert_eq!(header.pads[1].rotation, 90.0);
+        assert_eq!(header.attributes.mount, "through_hole");
+        let cap = &pcb.footprints[1];
+        assert_eq!(cap.layer, "B.Cu");
+        assert_eq!(cap.pads[0].position.x, Length::mm(0.5));
+        assert_eq!(cap.pads[0].layers, ["B.Cu", "B.Paste", "B.Mask"]);
+        assert_eq!(layer_id("Edge.Cuts"), 44);
+        assert_eq!(layer_id("In3.Cu"), 3);
+    }
+
+    #[test]
+    fn test_written_board_parses_back() {
+        let pcb = board();
+        let parsed = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(parsed.layers, pcb.layers);
+        assert_eq!(parsed.nets, pcb.nets);
+        assert_eq!(parsed.footprints, pcb.footprints);
+        assert_eq!(parsed.tracks, pcb.tracks);
+        assert_eq!(parsed.vias, pcb.vias);
+        assert_eq!(parsed.zones, pcb.zones);
+        assert_eq!(parsed.graphics, pcb.graphics);
+        assert_eq!(parsed.board_thickness, Some(Length::mm(1.6)));
+    }
+}
@@ -0,0 +1,590 @@
+//! Copper connectivity analysis
+//!
+//! Builds the physical connection graph of a board from its pads, tracks, arcs,
+//! vias and zone fills: two items are connected when their copper touches on a
+//! common layer. Comparing the resulting clusters with the declared nets reveals
+//! shorts (one cluster, several nets) and opens (one net, several clusters).
+//! Nets joined through the pads of a net-tie footprint are not reported as shorts.
+//! A lighter screen flags pads nothing is routed to and track ends left dangling.
+//!
+//! ```rust
+//! use kiparse::pcb::{connectivity::Connectivity, parse_pcb};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND")
+//!   (footprint "R" (layer "F.Cu") (at 0 0) (property "Reference" "R1")
+//!     (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu") (net 1 "GND"))
+//!     (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu") (net 1 "GND")))
+//!   (segment (start -1 0) (end 1 0) (width 0.25) (layer "F.Cu") (net 1))
+//! )"#)?;
+//! let connectivity = Connectivity::build(&pcb);
+//!
+//! assert!(connectivity.opens().is_empty());
+//! assert!(connectivity.shorts().is_empty());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::copper::{copper_shapes, CopperItem, CopperShape, IndexedPolygon, Shape, EPSILON};
+use super::types::{PcbFile, Point};
+use crate::units::Length;
+
+/// Grid cell size (mm) used to find neighbouring copper
+const CELL_SIZE: f64 = 1.0;
+
+/// A pad identified by its footprint reference and pad number
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PadRef {
+    pub reference: String,
+    pub pad: String,
+}
+
+/// Items whose copper is physically joined, with the nets they declare
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cluster {
+    pub items: Vec<CopperItem>,
+    /// Distinct declared nets of the items, sorted; more than one means a short
+    pub nets: Vec<String>,
+}
+
+/// A net whose pads are split across several unconnected islands
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OpenNet {
+    pub net: String,
+    pub islands: Vec<Vec<PadRef>>,
+}
+
+/// Track or arc end that touches no other copper
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DanglingEnd {
+    pub item: CopperItem,
+    pub net: Option<String>,
+    pub layer: String,
+    pub position: Point,
+}
+
+/// Physical connection graph of a board
+pub struct Connectivity<'a> {
+    pcb: &'a PcbFile,
+    shapes: Vec<CopperShape>,
+    items: Vec<CopperItem>,
+    index: HashMap<CopperItem, usize>,
+    parent: Vec<usize>,
+}
+
+impl<'a> Connectivity<'a> {
+    /// Compute connectivity for every copper item of `pcb`
+    pub fn build(pcb: &'a PcbFile) -> Self {
+        let shapes = copper_shapes(pcb);
+        let mut connectivity = Connectivity {
+            pcb,
+            shapes: Vec::new(),
+            items: Vec::new(),
+            index: HashMap::new(),
+            parent: Vec::new(),
+        };
+        let ids: Vec<usize> = shapes.iter().map(|s| connectivity.add(s.item)).collect();
+
+        // Items overlapping on a layer, found through a uniform grid
+        let boxes: Vec<_> = shapes.iter().map(|s| s.shape.bounds()).collect();
+        let mut grid: HashMap<(&str, i64, i64), Vec<usize>> = HashMap::new();
+        for (i, shape) in shapes.iter().enumerate() {
+            let (lo, hi) = &boxes[i];
+            for cx in cell(lo.x)..=cell(hi.x) {
+                for cy in cell(lo.y)..=cell(hi.y) {
+                    let neighbours = grid.entry((shape.layer.as_str(), cx, cy)).or_default();
+                    for &j in neighbours.iter() {
+                        let (other_lo, other_hi) = &boxes[j];
+                        let epsilon = Length::mm(EPSILON);
+                        let overlap = lo.x <= other_hi.x + epsilon
+                            && other_lo.x <= hi.x + epsilon
+                            && lo.y <= other_hi.y + epsilon
+                            && other_lo.y <= hi.y + epsilon;
+                        if overlap
+                            && connectivity.find(ids[i]) != connectivity.find(ids[j])
+                            && shape.shape.distance(&shapes[j].shape) <= EPSILON
+                        {
+                            connectivity.union(ids[i], ids[j]);
+                        }
+                    }
+                    neighbours.push(i);
+                }
+            }
+        }
+
+        // Zone fills join everything they touch on their layer
+        for (z, zone) in pcb.zones.iter().enumerate() {
+            for (f, fill) in zone.filled_polygons.iter().enumerate() {
+                if fill.points.len() < 3 {
+                    continue;
+                }
+                let id = connectivity.add(CopperItem::Zone { zone: z, fill: f });
+                let polygon = IndexedPolygon::new(&fill.points);
+                for (i, shape) in shapes.iter().enumerate() {
+                    if shape.layer == fill.layer
+                        && connectivity.find(ids[i]) != connectivity.find(id)
+                        && polygon.touches(&shape.shape)
+                    {
+                        connectivity.union(ids[i], id);
+                    }
+                }
+            }
+        }
+        connectivity.join_overlapping_fills();
+        connectivity.shapes = shapes;
+        connectivity
+    }
+
+    /// Every copper item taking part in the graph
+    pub fn items(&self) -> &[CopperItem] {
+        &self.items
+    }
+
+    /// Whether the copper of two items is joined, directly or through other items
+    pub fn is_connected(&self, a: CopperItem, b: CopperItem) -> bool {
+        match (self.index.get(&a), self.index.get(&b)) {
+            (Some(&a), Some(&b)) => self.root(a) == self.root(b),
+            _ => false,
+        }
+    }
+
+    /// Groups of physically connected items
+    pub fn clusters(&self) -> Vec<Cluster> {
+        let mut groups: HashMap<usize, Vec<CopperItem>> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            groups.entry(self.root(i)).or_default().push(*item);
+        }
+        let mut clusters: Vec<Cluster> = groups
+            .into_values()
+            .map(|mut items| {
+                items.sort();
+                let nets: BTreeSet<String> = items.iter().filter_map(|i| self.net_of(*i)).collect();
+                Cluster { items, nets: nets.into_iter().collect() }
+            })
+            .collect();
+        clusters.sort_by(|a, b| a.items.cmp(&b.items));
+        clusters
+    }
+
+    /// Clusters joining copper of more than one declared net
+    ///
+    /// A cluster whose nets are all tied together by the
+    /// [net-tie groups](super::types::Footprint::net_tie_pad_groups) of pads it
+    /// contains is intentional and not reported.
+    pub fn shorts(&self) -> Vec<Cluster> {
+        self.clusters()
+            .into_iter()
+            .filter(|c| c.nets.len() > 1 && !self.is_net_tied(c))
+            .collect()
+    }
+
+    /// Nets whose pads are not all connected by copper
+    pub fn opens(&self) -> Vec<OpenNet> {
+        let mut islands: HashMap<&str, HashMap<usize, Vec<PadRef>>> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            if let CopperItem::Pad { footprint, pad } = *item {
+                if let Some(net) = &self.pcb.footprints[footprint].pads[pad].net {
+                    islands
+                        .entry(net.as_str())
+                        .or_default()
+                        .entry(self.root(i))
+                        .or_default()
+                        .push(self.pad_ref(footprint, pad));
+                }
+            }
+        }
+
+        let mut opens: Vec<OpenNet> = islands
+            .into_iter()
+            .filter(|(_, groups)| groups.len() > 1)
+            .map(|(net, groups)| {
+                let mut islands: Vec<Vec<PadRef>> = groups
+                    .into_values()
+                    .map(|mut pads| {
+                        pads.sort();
+                        pads
+                    })
+                    .collect();
+                islands.sort();
+                OpenNet { net: net.to_string(), islands }
+            })
+            .collect();
+        opens.sort_by(|a, b| a.net.cmp(&b.net));
+        opens
+    }
+
+    /// Pads of multi-pad nets that no track, via or zone reaches
+    ///
+    /// Pads touching only other pads count as unconnected unless that already joins
+    /// every pad of their net. Nets with a single pad need no routing and are skipped.
+    pub fn unconnected_pads(&self) -> Vec<PadRef> {
+        let mut routed = vec![false; self.items.len()];
+        let mut pads: HashMap<&str, Vec<(usize, usize, usize)>> = HashMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            match *item {
+                CopperItem::Pad { footprint, pad } => {
+                    if let Some(net) = self.pcb.footprints[footprint].pads[pad].net.as_deref() {
+                        pads.entry(net).or_default().push((self.root(i), footprint, pad));
+                    }
+                }
+                _ => routed[self.root(i)] = true,
+            }
+        }
+
+        let routed = &routed;
+        let mut unconnected: Vec<PadRef> = pads
+            .values()
+            .flat_map(|net_pads| {
+                let spans_net = |root: usize| net_pads.iter().all(|(r, ..)| *r == root);
+                net_pads
+                    .iter()
+                    .filter(move |(root, ..)| !routed[*root] && !spans_net(*root))
+                    .map(|&(_, footprint, pad)| self.pad_ref(footprint, pad))
+            })
+            .collect();
+        unconnected.sort();
+        unconnected
+    }
+
+    /// Track and arc ends whose end cap touches no other copper of their layer
+    pub fn dangling_ends(&self) -> Vec<DanglingEnd> {
+        let mut grid: HashMap<(&str, i64, i64), Vec<usize>> = HashMap::new();
+        for (i, shape) in self.shapes.iter().enumerate() {
+            let (lo, hi) = shape.shape.bounds();
+            let epsilon = Length::mm(EPSILON);
+            for cx in cell(lo.x - epsilon)..=cell(hi.x + epsilon) {
+                for cy in cell(lo.y - epsilon)..=cell(hi.y + epsilon) {
+                    grid.entry((shape.layer.as_str(), cx, cy)).or_default().push(i);
+                }
+            }
+        }
+        let fills: Vec<(&str, IndexedPolygon)> = self
+            .pcb
+            .zones
+            .iter()
+            .flat_map(|zone| &zone.filled_polygons)
+            .filter(|fill| fill.points.len() >= 3)
+            .map(|fill| (fill.layer.as_str(), IndexedPolygon::new(&fill.points)))
+            .collect();
+
+        let pcb = self.pcb;
+        let tracks = pcb.tracks.iter().enumerate().map(|(i, t)| (CopperItem::Track(i), t.width, &t.layer, &t.net, [&t.start, &t.end]));
+        let arcs = pcb.arcs.iter().enumerate().map(|(i, a)| (CopperItem::Arc(i), a.width, &a.layer, &a.net, [&a.start, &a.end]));
+        let mut dangling = Vec::new();
+        for (item, width, layer, net, ends) in tracks.chain(arcs) {
+            for end in ends {
+                let cap = Shape::Capsule { a: end.clone(), b: end.clone(), radius: width.as_mm() / 2.0 };
+                let (lo, hi) = cap.bounds();
+                let mut near = (cell(lo.x)..=cell(hi.x))
+                    .flat_map(|cx| (cell(lo.y)..=cell(hi.y)).map(move |cy| (cx, cy)))
+                    .filter_map(|(cx, cy)| grid.get(&(layer.as_str(), cx, cy)))
+                    .flatten()
+                    .map(|&j| &self.shapes[j]);
+                let joined = near.any(|other| other.item != item && other.shape.distance(&cap) <= EPSILON)
+                    || fills.iter().any(|(l, fill)| l == layer && fill.touches(&cap));
+                if !joined {
+                    dangling.push(DanglingEnd {
+                        item,
+                        net: net.clone(),
+                        layer: layer.clone(),
+                        position: end.clone(),
+                    });
+                }
+            }
+        }
+        dangling
+    }
+
+    /// Pads joined by copper to the given pad, itself included
+    pub fn connected_pads(&self, footprint: usize, pad: usize) -> Vec<PadRef> {
+        let root = match self.index.get(&CopperItem::Pad { footprint, pad }) {
+            Some(&i) => self.root(i),
+            None => return Vec::new(),
+        };
+        let mut pads: Vec<PadRef> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.root(*i) == root)
+            .filter_map(|(_, item)| match *item {
+                CopperItem::Pad { footprint, pad } => Some(self.pad_ref(footprint, pad)),
+                _ => None,
+            })
+            .collect();
+        pads.sort();
+        pads
+    }
+
+    /// As-routed netlist: each entry lists the pads one piece of copper connects
+    ///
+    /// Pads connected to nothing else form single-pad entries.
+    pub fn routed_netlist(&self) -> Vec<Vec<PadRef>> {
+        let mut nets: Vec<Vec<PadRef>> = self
+            .clusters()
+            .into_iter()
+            .map(|c| {
+                let mut pads: Vec<PadRef> = c
+                    .items
+                    .iter()
+                    .filter_map(|item| match *item {
+                        CopperItem::Pad { footprint, pad } => Some(self.pad_ref(footprint, pad)),
+                        _ => None,
+                    })
+                    .collect();
+                pads.sort();
+                pads
+            })
+            .filter(|pads| !pads.is_empty())
+            .collect();
+        nets.sort();
+        nets
+    }
+
+    /// Declared net of an item
+    pub fn net_of(&self, item: CopperItem) -> Option<String> {
+        let pcb = self.pcb;
+        match item {
+            CopperItem::Pad { footprint, pad } => pcb.footprints[footprint].pads[pad].net.clone(),
+            CopperItem::Track(i) => pcb.tracks[i].net.clone(),
+            CopperItem::Arc(i) => pcb.arcs[i].net.clone(),
+            CopperItem::Via(i) => pcb.vias[i].net.clone(),
+            CopperItem::Zone { zone, .. } => pcb.zones[zone].net.clone(),
+        }
+    }
+
+    /// Whether the nets of a cluster all merge into one through its net-tie pads
+    fn is_net_tied(&self, cluster: &Cluster) -> bool {
+        let mut class: HashMap<&str, usize> = cluster.nets.iter().enumerate().map(|(i, n)| (n.as_str(), i)).collect();
+        for item in &cluster.items {
+            let CopperItem::Pad { footprint, pad } = *item else { continue };
+            let footprint = &self.pcb.footprints[footprint];
+            let pad = &footprint.pads[pad];
+            let (Some(net), Some(group)) = (pad.net.as_deref(), footprint.net_tie_group(&pad.number)) else {
+                continue;
+            };
+            for other in footprint.pads.iter().filter(|p| group.contains(&p.number)) {
+                if let (Some(&a), Some(&b)) = (class.get(net), other.net.as_deref().and_then(|n| class.get(n))) {
+                    class.values_mut().filter(|c| **c == b).for_each(|c| *c = a);
+                }
+            }
+        }
+        let mut classes = class.values();
+        let first = classes.next();
+        classes.all(|c| Some(c) == first)
+    }
+
+    fn pad_ref(&self, footprint: usize, pad: usize) -> PadRef {
+        let footprint = &self.pcb.footprints[footprint];
+        PadRef {
+            reference: footprint.property("Reference").unwrap_or_default().to_string(),
+            pad: footprint.pads[pad].number.clone(),
+        }
+    }
+
+    /// Fills of different zones on the same layer that overlap (e.g. same-net zones
+    /// drawn on top of each other)
+    fn join_overlapping_fills(&mut self) {
+        let fills: Vec<(CopperItem, &str, &[_])> = self
+            .pcb
+            .zones
+            .iter()
+            .enumerate()
+            .flat_map(|(z, zone)| {
+                zone.filled_polygons
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, fill)| fill.points.len() >= 3)
+                    .map(move |(f, fill)| (CopperItem::Zone { zone: z, fill: f }, fill.layer.as_str(), fill.points.as_slice()))
+            })
+            .collect();
+        for (i, (a, layer, points)) in fills.iter().enumerate() {
+            let index = IndexedPolygon::new(points);
+            for (b, other_layer, other) in &fills[i + 1..] {
+                if layer != other_layer {
+                    continue;
+                }
+                let touching = other.iter().any(|p| index.contains(p))
+                    || IndexedPolygon::new(other).contains(&points[0]);
+                if touching {
+                    let (a, b) = (self.index[a], self.index[b]);
+                    self.union(a, b);
+                }
+            }
+        }
+    }
+
+    fn add(&mut self, item: CopperItem) -> usize {
+        if let Some(&id) = self.index.get(&item) {
+            return id;
+        }
+        let id = self.items.len();
+        self.items.push(item);
+        self.index.insert(item, id);
+        self.parent.push(id);
+        id
+    }
+
+    /// Root with path compression, used while building
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn root(&self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parent[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+fn cell(v: Length) -> i64 {
+    (v.as_mm() / CELL_SIZE).floor() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "VCC") (net 2 "GND") (net 3 "SIG")
+  (footprint "R" (layer "F.Cu") (at 10 10) (property "Reference" "R1")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu") (net 1 "VCC"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu") (net 3 "SIG")))
+  (footprint "C" (layer "F.Cu") (at 20 10 90) (property "Reference" "C1")
+    (pad "1" smd roundrect (at 0 -1) (size 1 1) (layers "F.Cu") (net 3 "SIG"))
+    (pad "2" smd roundrect (at 0 1) (size 1 1) (layers "F.Cu") (net 2 "GND")))
+  (footprint "J" (layer "F.Cu") (at 30 10) (property "Reference" "J1")
+    (pad "1" thru_hole circle (at 0 0) (size 1.7 1.7) (drill 1) (layers "*.Cu" "*.Mask") (net 3 "SIG"))
+    (pad "2" thru_hole oval (at 0 2.54) (size 1.7 2) (drill 1) (layers "*.Cu" "*.Mask") (net 1 "VCC"))
+    (pad "" np_thru_hole circle (at 5 0) (size 3 3) (drill 3) (layers "*.Cu" "*.Mask")))
+  (segment (start 11 10) (end 15 10) (width 0.25) (layer "F.Cu") (net 3))
+  (arc (start 15 10) (mid 16.5 9.4) (end 18 10) (width 0.25) (layer "F.Cu") (net 3))
+  (segment (start 18 10) (end 19 10) (width 0.25) (layer "F.Cu") (net 3))
+  (via (at 19 10) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 3))
+  (segment (start 19 10) (end 30 10) (width 0.25) (layer "B.Cu") (net 3))
+  (segment (start 21 10) (end 21 15) (width 0.25) (layer "F.Cu") (net 2))
+  (zone (net 2) (net_name "GND") (layer "B.Cu")
+    (polygon (pts (xy 0 20) (xy 40 20) (xy 40 30) (xy 0 30)))
+    (filled_polygon (layer "B.Cu") (pts (xy 0 20) (xy 40 20) (xy 40 30) (xy 0 30))))
+)"#;
+
+    fn pad(reference: &str, pad: &str) -> PadRef {
+        PadRef { reference: reference.to_string(), pad: pad.to_string() }
+    }
+
+    #[test]
+    fn test_connected_pads() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let connectivity = Connectivity::build(&pcb);
+
+        // R1.2 -> track -> arc -> track -> C1.1 (rotated footprint, pad at x=19)
+        // -> via in pad -> B.Cu track -> J1.1
+        assert_eq!(connectivity.connected_pads(0, 1), vec![pad("C1", "1"), pad("J1", "1"), pad("R1", "2")]);
+        assert!(connectivity.is_connected(CopperItem::Via(0), CopperItem::Arc(0)));
+        assert!(!connectivity.is_connected(CopperItem::Pad { footprint: 0, pad: 0 }, CopperItem::Track(0)));
+        // The non-plated hole has no copper
+        assert!(!connectivity.items().contains(&CopperItem::Pad { footprint: 2, pad: 2 }));
+        assert!(connectivity.shorts().is_empty());
+    }
+
+    #[test]
+    fn test_opens_and_routed_netlist() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let connectivity = Connectivity::build(&pcb);
+
+        let opens = connectivity.opens();
+        assert_eq!(opens.len(), 1);
+        assert_eq!(opens[0].net, "VCC");
+        assert_eq!(opens[0].islands, vec![vec![pad("J1", "2")], vec![pad("R1", "1")]]);
+
+        let netlist = connectivity.routed_netlist();
+        assert!(netlist.contains(&vec![pad("C1", "1"), pad("J1", "1"), pad("R1", "2")]));
+        assert!(netlist.contains(&vec![pad("C1", "2")]));
+    }
+
+    #[test]
+    fn test_zone_short() {
+        // A VCC track crossing the GND pour on B.Cu shorts the two nets
+        let board = BOARD.replace(
+            "(segment (start 21 10)",
+            "(segment (start 5 25) (end 6 25) (width 0.25) (layer \"B.Cu\") (net 1))\n  (segment (start 21 10)",
+        );
+        let pcb = parse_pcb(&board).unwrap();
+        let shorts = Connectivity::build(&pcb).shorts();
+
+        assert_eq!(shorts.len(), 1);
+        assert_eq!(shorts[0].nets, vec!["GND", "VCC"]);
+        assert!(shorts[0].items.contains(&CopperItem::Zone { zone: 0, fill: 0 }));
+    }
+
+    #[test]
+    fn test_net_tie_is_not_a_short() {
+        let tie = r#"(kicad_pcb
+  (net 0 "") (net 1 "GND") (net 2 "AGND")
+  (footprint "NetTie:NetTie-2_SMD_Pad0.5mm" (layer "F.Cu") (at 10 10) (property "Reference" "NT1")
+    (net_tie_pad_groups "1, 2")
+    (pad "1" smd circle (at -0.25 0) (size 0.5 0.5) (layers "F.Cu") (net 1 "GND"))
+    (pad "2" smd circle (at 0.25 0) (size 0.5 0.5) (layers "F.Cu") (net 2 "AGND")))
+  (segment (start 5 10) (end 9.75 10) (width 0.25) (layer "F.Cu") (net 1))
+  (segment (start 10.25 10) (end 15 10) (width 0.25) (layer "F.Cu") (net 2))
+)"#;
+        let pcb = parse_pcb(tie).unwrap();
+        assert!(pcb.footprints[0].is_net_tie());
+        assert_eq!(pcb.footprints[0].net_tie_group("2").unwrap(), ["1", "2"]);
+        assert!(Connectivity::build(&pcb).shorts().is_empty());
+        let reparsed = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(reparsed.footprints[0].net_tie_pad_groups, pcb.footprints[0].net_tie_pad_groups);
+
+        // The same copper without the declaration is a short, as is a third net
+        // reaching the tie
+        let untied = parse_pcb(&tie.replace("(net_tie_pad_groups \"1, 2\")", "")).unwrap();
+        assert_eq!(Connectivity::build(&untied).shorts()[0].nets, vec!["AGND", "GND"]);
+        let third = tie.replace(
+            "\n)",
+            "\n  (segment (start 15 10) (end 16 10) (width 0.25) (layer \"F.Cu\") (net \"VCC\"))\n)",
+        );
+        assert_eq!(Connectivity::build(&parse_pcb(&third).unwrap()).shorts()[0].nets, vec!["AGND", "GND", "VCC"]);
+    }
+
+    #[test]
+    fn test_unconnected_pads_and_dangling_ends() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let connectivity = Connectivity::build(&pcb);
+
+        // Nothing is routed to either VCC pad; the lone GND pad needs no routing
+        assert_eq!(connectivity.unconnected_pads(), vec![pad("J1", "2"), pad("R1", "1")]);
+
+        // The GND stub leaves C1.2 and stops short of the pour
+        let dangling = connectivity.dangling_ends();
+        assert_eq!(dangling.len(), 1);
+        assert_eq!(dangling[0].item, CopperItem::Track(3));
+        assert_eq!(dangling[0].net.as_deref(), Some("GND"));
+        assert_eq!(dangling[0].position, Point::new(21.0, 15.0));
+
+        // Stitching the stub down into the pour connects it
+        let board = BOARD.replace(
+            "(segment (start 21 10) (end 21 15)",
+            "(via (at 21 21) (size 0.6) (drill 0.3) (layers \"F.Cu\" \"B.Cu\") (net 2))\n  (segment (start 21 10) (end 21 21)",
+        );
+        let pcb = parse_pcb(&board).unwrap();
+        assert!(Connectivity::build(&pcb).dangling_ends().is_empty());
+    }
+}
@@ -0,0 +1,507 @@
+//! Copper geometry of a board
+//!
+//! Turns pads, tracks, arcs and vias into simple per-layer shapes that analysis
+//! passes (connectivity, clearance checks) can compare. Circles and ovals become
+//! stadiums, rounded rectangles get flattened corners and other pad shapes are
+//! approximated by their bounding rectangle; arcs are flattened into short segments.
+//! [`copper_areas`] totals the copper of each net per layer, zone fills included.
+
+use super::geometry::{
+    arc_from_points, bounds, distance, flatten_arc, point_in_polygon, polygon_area,
+    segment_closest_points, segment_distance, to_board,
+};
+use super::types::{Arc, Footprint, Pad, PcbFile, Point};
+use crate::units::Length;
+
+/// A copper object of the board, by position in the corresponding [`PcbFile`] list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CopperItem {
+    Pad { footprint: usize, pad: usize },
+    Track(usize),
+    Arc(usize),
+    Via(usize),
+    /// One filled island of a zone
+    Zone { zone: usize, fill: usize },
+}
+
+/// Outline of a piece of copper
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    /// All points within `radius` of the segment `a`-`b` (tracks, vias, round pads)
+    Capsule { a: Point, b: Point, radius: f64 },
+    /// Closed polygon (rectangular pads)
+    Polygon(Vec<Point>),
+}
+
+impl Shape {
+    /// Axis-aligned bounding box as (min, max)
+    pub fn bounds(&self) -> (Point, Point) {
+        match self {
+            Shape::Capsule { a, b, radius } => {
+                let radius = Length::mm(*radius);
+                (
+                    Point { x: a.x.min(b.x) - radius, y: a.y.min(b.y) - radius },
+                    Point { x: a.x.max(b.x) + radius, y: a.y.max(b.y) + radius },
+                )
+            }
+            Shape::Polygon(points) => bounds(points),
+        }
+    }
+
+    /// Gap between the two copper shapes; zero when they touch or overlap
+    pub fn distance(&self, other: &Shape) -> f64 {
+        self.closest(other).0
+    }
+
+    /// Gap between the two shapes and the point midway across it
+    ///
+    /// When the shapes overlap the gap is zero and the point lies in the overlap.
+    pub fn closest(&self, other: &Shape) -> (f64, Point) {
+        match (self, other) {
+            (Shape::Capsule { a, b, radius: r1 }, Shape::Capsule { a: c, b: d, radius: r2 }) => {
+                let (p, q) = segment_closest_points(a, b, c, d);
+                gap_between(&p, *r1, &q, *r2)
+            }
+            (Shape::Capsule { a, b, radius }, Shape::Polygon(polygon))
+            | (Shape::Polygon(polygon), Shape::Capsule { a, b, radius }) => {
+                if point_in_polygon(a, polygon) {
+                    return (0.0, a.clone());
+                }
+                let (p, q) = nearest_pair(edges(polygon).map(|(c, d)| segment_closest_points(a, b, c, d)));
+                gap_between(&p, *radius, &q, 0.0)
+            }
+            (Shape::Polygon(p), Shape::Polygon(q)) => {
+                if let Some(v) = p.iter().find(|v| point_in_polygon(v, q)).or_else(|| q.iter().find(|v| point_in_polygon(v, p))) {
+                    return (0.0, v.clone());
+                }
+                let pairs = edges(p).flat_map(|(a, b)| edges(q).map(move |(c, d)| segment_closest_points(a, b, c, d)));
+                let (p, q) = nearest_pair(pairs);
+                gap_between(&p, 0.0, &q, 0.0)
+            }
+        }
+    }
+
+    /// Enclosed area in mm²
+    pub fn area(&self) -> f64 {
+        match self {
+            Shape::Capsule { a, b, radius } => {
+                2.0 * radius * distance(a, b) + std::f64::consts::PI * radius * radius
+            }
+            Shape::Polygon(points) => polygon_area(points).abs(),
+        }
+    }
+
+    /// Representative point inside the shape
+    pub fn anchor(&self) -> &Point {
+        match self {
+            Shape::Capsule { a, .. } => a,
+            Shape::Polygon(points) => &points[0],
+        }
+    }
+}
+
+/// Closest of several candidate point pairs
+fn nearest_pair(pairs: impl Iterator<Item = (Point, Point)>) -> (Point, Point) {
+    pairs
+        .min_by(|(p, q), (r, s)| distance(p, q).total_cmp(&distance(r, s)))
+        .unwrap_or_default()
+}
+
+/// Gap between copper around `p` (radius `rp`) and around `q` (radius `rq`), with
+/// the point midway between the two copper edges
+fn gap_between(p: &Point, rp: f64, q: &Point, rq: f64) -> (f64, Point) {
+    let d = distance(p, q);
+    let gap = (d - rp - rq).max(0.0);
+    if d == 0.0 {
+        return (0.0, p.clone());
+    }
+    // Parameter along p -> q of the gap's midpoint
+    let t = ((rp + (d - rq)) / 2.0 / d).clamp(0.0, 1.0);
+    (gap, Point { x: p.x + t * (q.x - p.x), y: p.y + t * (q.y - p.y) })
+}
+
+/// Closing edges of a polygon as point pairs
+pub(crate) fn edges(polygon: &[Point]) -> impl Iterator<Item = (&Point, &Point)> {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .take(polygon.len())
+}
+
+/// A shape on one copper layer belonging to a board item
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopperShape {
+    pub item: CopperItem,
+    pub layer: String,
+    pub net: Option<String>,
+    pub shape: Shape,
+}
+
+/// Copper layer names from front to back (`F.Cu`, `In1.Cu`, ..., `B.Cu`)
+pub fn copper_layers(pcb: &PcbFile) -> Vec<String> {
+    let mut layers: Vec<String> = pcb
+        .layers
+        .values()
+        .map(|l| l.name.clone())
+        .filter(|n| n.ends_with(".Cu"))
+        .collect();
+    if layers.is_empty() {
+        layers = vec!["F.Cu".to_string(), "B.Cu".to_string()];
+    }
+    layers.sort_by_key(|name| copper_order(name));
+    layers
+}
+
+/// Stacking position of a copper layer: `F.Cu` first, inner layers by number, `B.Cu` last
+fn copper_order(name: &str) -> u32 {
+    match name {
+        "F.Cu" => 0,
+        "B.Cu" => u32::MAX,
+        inner => inner
+            .trim_start_matches("In")
+            .trim_end_matches(".Cu")
+            .parse()
+            .unwrap_or(u32::MAX - 1),
+    }
+}
+
+/// Copper layers a pad or via layer list covers, expanding `*.Cu`, `F&B.Cu` and
+/// via spans such as `("F.Cu" "In2.Cu")`
+pub fn expand_layers(layers: &[String], copper: &[String], span: bool) -> Vec<String> {
+    if span && layers.len() == 2 {
+        let position = |name: &str| copper.iter().position(|l| l == name);
+        if let (Some(a), Some(b)) = (position(&layers[0]), position(&layers[1])) {
+            return copper[a.min(b)..=a.max(b)].to_vec();
+        }
+    }
+    let mut expanded = Vec::new();
+    for layer in layers {
+        match layer.as_str() {
+            "*.Cu" => expanded.extend(copper.iter().cloned()),
+            "F&B.Cu" => expanded.extend(["F.Cu".to_string(), "B.Cu".to_string()]),
+            name if name.ends_with(".Cu") => expanded.push(name.to_string()),
+            _ => {}
+        }
+    }
+    expanded.dedup();
+    expanded
+}
+
+/// Board-space shape of a pad
+pub fn pad_shape(footprint: &Footprint, pad: &Pad) -> Shape {
+    let center = footprint.pad_position(pad);
+    let (w, h) = (pad.size.x.as_mm(), pad.size.y.as_mm());
+    let local = |x: f64, y: f64| to_board(&Point::new(x, y), &center, pad.rotation);
+    match pad.shape.as_str() {
+        "circle" => Shape::Capsule { a: center.clone(), b: center, radius: w / 2.0 },
+        "oval" if w >= h => Shape::Capsule {
+            a: local(-(w - h) / 2.0, 0.0),
+            b: local((w - h) / 2.0, 0.0),
+            radius: h / 2.0,
+        },
+        "oval" => Shape::Capsule {
+            a: local(0.0, -(h - w) / 2.0),
+            b: local(0.0, (h - w) / 2.0),
+            radius: w / 2.0,
+        },
+        "roundrect" => {
+            // Corner radius is the ratio of the smaller side (KiCad default 0.25)
+            let r = pad.roundrect_ratio.unwrap_or(0.25) * w.min(h);
+            let corners = [(1.0, 1.0, 0.0), (-1.0, 1.0, 90.0), (-1.0, -1.0, 180.0), (1.0, -1.0, 270.0)];
+            let mut points = Vec::new();
+            for (sx, sy, start) in corners {
+                let center = Point::new(sx * (w / 2.0 - r), sy * (h / 2.0 - r));
+                let arc = Arc { center, radius: Length::mm(r), start_angle: start, end_angle: start + 90.0 };
+                points.extend(flatten_arc(&arc).iter().map(|p| local(p.x.as_mm(), p.y.as_mm())));
+            }
+            Shape::Polygon(points)
+        }
+        _ => Shape::Polygon(vec![
+            local(-w / 2.0, -h / 2.0),
+            local(w / 2.0, -h / 2.0),
+            local(w / 2.0, h / 2.0),
+            local(-w / 2.0, h / 2.0),
+        ]),
+    }
+}
+
+/// Copper shapes of every pad, track, arc and via (zone fills are not included)
+///
+/// Non-plated holes carry no copper and are skipped.
+pub fn copper_shapes(pcb: &PcbFile) -> Vec<CopperShape> {
+    let copper = copper_layers(pcb);
+    let mut shapes = Vec::new();
+
+    for (f, footprint) in pcb.footprints.iter().enumerate() {
+        for (p, pad) in footprint.pads.iter().enumerate() {
+            if pad.pad_type == "np_thru_hole" {
+                continue;
+            }
+            let shape = pad_shape(footprint, pad);
+            for layer in expand_layers(&pad.layers, &copper, false) {
+                shapes.push(CopperShape {
+                    item: CopperItem::Pad { footprint: f, pad: p },
+                    layer,
+                    net: pad.net.clone(),
+                    shape: shape.clone(),
+                });
+            }
+        }
+    }
+    for (i, track) in pcb.tracks.iter().enumerate() {
+        shapes.push(CopperShape {
+            item: CopperItem::Track(i),
+            layer: track.layer.clone(),
+            net: track.net.clone(),
+            shape: Shape::Capsule {
+                a: track.start.clone(),
+                b: track.end.clone(),
+                radius: track.width.as_mm() / 2.0,
+            },
+        });
+    }
+    for (i, arc) in pcb.arcs.iter().enumerate() {
+        let points = match arc_from_points(&arc.start, &arc.mid, &arc.end) {
+            Some(circle) => flatten_arc(&circle),
+            None => vec![arc.start.clone(), arc.end.clone()],
+        };
+        for pair in points.windows(2) {
+            shapes.push(CopperShape {
+                item: CopperItem::Arc(i),
+                layer: arc.layer.clone(),
+                net: arc.net.clone(),
+                shape: Shape::Capsule {
+                    a: pair[0].clone(),
+                    b: pair[1].clone(),
+                    radius: arc.width.as_mm() / 2.0,
+                },
+            });
+        }
+    }
+    for (i, via) in pcb.vias.iter().enumerate() {
+        for layer in expand_layers(&via.layers, &copper, true) {
+            shapes.push(CopperShape {
+                item: CopperItem::Via(i),
+                layer,
+                net: via.net.clone(),
+                shape: Shape::Capsule {
+                    a: via.position.clone(),
+                    b: via.position.clone(),
+                    radius: via.size.as_mm() / 2.0,
+                },
+            });
+        }
+    }
+    shapes
+}
+
+/// Copper area of one net on one layer
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CopperArea {
+    pub net: Option<String>,
+    pub layer: String,
+    /// Area in mm²
+    pub area: f64,
+}
+
+/// Approximate copper area per net and layer, sorted by layer then net
+///
+/// Sums pads (less their drill holes), tracks, arcs, via annular rings and zone fills.
+/// Overlapping items are each counted in full, so tracks running inside a zone fill or
+/// into a pad add slightly more than the copper actually present.
+pub fn copper_areas(pcb: &PcbFile) -> Vec<CopperArea> {
+    let hole = |drill: Option<Length>| drill.map_or(0.0, |d| std::f64::consts::PI * d.as_mm() * d.as_mm() / 4.0);
+    let mut areas: std::collections::BTreeMap<(usize, String, Option<String>), f64> = Default::default();
+    let copper = copper_layers(pcb);
+    let mut add = |layer: &str, net: &Option<String>, area: f64| {
+        let order = copper.iter().position(|l| l == layer).unwrap_or(copper.len());
+        *areas.entry((order, layer.to_string(), net.clone())).or_default() += area;
+    };
+
+    for shape in copper_shapes(pcb) {
+        let area = match shape.item {
+            // Flattened arcs overlap at every joint; measured along the curve below
+            CopperItem::Arc(_) => continue,
+            CopperItem::Pad { footprint, pad } => {
+                shape.shape.area() - hole(pcb.footprints[footprint].pads[pad].drill)
+            }
+            CopperItem::Via(i) => shape.shape.area() - hole(Some(pcb.vias[i].drill)),
+            _ => shape.shape.area(),
+        };
+        add(&shape.layer, &shape.net, area.max(0.0));
+    }
+    for arc in &pcb.arcs {
+        let radius = arc.width.as_mm() / 2.0;
+        add(&arc.layer, &arc.net, arc.length() * arc.width.as_mm() + std::f64::consts::PI * radius * radius);
+    }
+    for zone in &pcb.zones {
+        for fill in &zone.filled_polygons {
+            add(&fill.layer, &zone.net, polygon_area(&fill.points).abs());
+        }
+    }
+
+    areas
+        .into_iter()
+        .map(|((_, layer, net), area)| CopperArea { net, layer, area })
+        .collect()
+}
+
+/// Polygon with its edges bucketed into horizontal bands, for fast containment and
+/// proximity tests against large zone fills
+pub(crate) struct IndexedPolygon<'a> {
+    points: &'a [Point],
+    min: Point,
+    max: Point,
+    bands: Vec<Vec<usize>>,
+}
+
+const BAND_HEIGHT: f64 = 0.5;
+
+impl<'a> IndexedPolygon<'a> {
+    pub(crate) fn new(points: &'a [Point]) -> Self {
+        let (min, max) = bounds(points);
+        let count = (((max.y - min.y).as_mm() / BAND_HEIGHT) as usize) + 1;
+        let mut bands = vec![Vec::new(); count];
+        let band = |y: Length| (((y - min.y).as_mm() / BAND_HEIGHT) as usize).min(count - 1);
+        for i in 0..points.len() {
+            let (a, b) = (&points[i], &points[(i + 1) % points.len()]);
+            for edges in &mut bands[band(a.y.min(b.y))..=band(a.y.max(b.y))] {
+                edges.push(i);
+            }
+        }
+        Self { points, min, max, bands }
+    }
+
+    fn edge(&self, i: usize) -> (&Point, &Point) {
+        (&self.points[i], &self.points[(i + 1) % self.points.len()])
+    }
+
+    fn band_range(&self, y0: Length, y1: Length) -> std::ops::Range<usize> {
+        let clamp = |y: Length| (((y - self.min.y).as_mm() / BAND_HEIGHT).max(0.0) as usize).min(self.bands.len());
+        clamp(y0)..(clamp(y1) + 1).min(self.bands.len())
+    }
+
+    pub(crate) fn contains(&self, p: &Point) -> bool {
+        if p.x < self.min.x || p.x > self.max.x || p.y < self.min.y || p.y > self.max.y {
+            return false;
+        }
+        let mut inside = false;
+        for &i in self.bands[self.band_range(p.y, p.y)].iter().flatten() {
+            let (a, b) = self.edge(i);
+            let (ax, ay, bx, by) = (a.x.as_mm(), a.y.as_mm(), b.x.as_mm(), b.y.as_mm());
+            let (px, py) = (p.x.as_mm(), p.y.as_mm());
+            if (ay > py) != (by > py) && px < (bx - ax) * (py - ay) / (by - ay) + ax {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Whether `shape` touches or overlaps the polygon
+    pub(crate) fn touches(&self, shape: &Shape) -> bool {
+        let (lo, hi) = shape.bounds();
+        if hi.x < self.min.x || lo.x > self.max.x || hi.y < self.min.y || lo.y > self.max.y {
+            return false;
+        }
+        if self.contains(shape.anchor()) {
+            return true;
+        }
+        let near = self.bands[self.band_range(lo.y, hi.y)].iter().flatten().map(|&i| self.edge(i));
+        match shape {
+            Shape::Capsule { a, b, radius } => {
+                let mut near = near;
+                near.any(|(c, d)| segment_distance(a, b, c, d) <= radius + EPSILON)
+            }
+            Shape::Polygon(polygon) => {
+                let near: Vec<_> = near
+                    .filter(|(c, d)| c.x.max(d.x) >= lo.x && c.x.min(d.x) <= hi.x)
+                    .collect();
+                edges(polygon).any(|(a, b)| near.iter().any(|(c, d)| segment_distance(a, b, c, d) <= EPSILON))
+                    || point_in_polygon(&self.points[0], polygon)
+            }
+        }
+    }
+}
+
+/// Distance below which copper is considered touching
+pub const EPSILON: f64 = 1e-6;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> Point {
+        Point::new(x, y)
+    }
+
+    #[test]
+    fn test_shape_distance() {
+        let track = Shape::Capsule { a: pt(0.0, 0.0), b: pt(10.0, 0.0), radius: 0.1 };
+        let via = Shape::Capsule { a: pt(5.0, 0.5), b: pt(5.0, 0.5), radius: 0.3 };
+        let pad = Shape::Polygon(vec![pt(9.0, -1.0), pt(11.0, -1.0), pt(11.0, 1.0), pt(9.0, 1.0)]);
+        let far_pad = Shape::Polygon(vec![pt(20.0, -1.0), pt(22.0, -1.0), pt(22.0, 1.0), pt(20.0, 1.0)]);
+
+        assert!((track.distance(&via) - 0.1).abs() < 1e-9);
+        assert_eq!(track.distance(&pad), 0.0);
+        assert!((pad.distance(&far_pad) - 9.0).abs() < 1e-9);
+        let (gap, at) = via.closest(&track);
+        assert!((gap - 0.1).abs() < 1e-9);
+        assert!((at.x.as_mm() - 5.0).abs() < 1e-9 && (at.y.as_mm() - 0.15).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_expand_layers() {
+        let copper: Vec<String> = ["F.Cu", "In1.Cu", "In2.Cu", "B.Cu"].iter().map(|s| s.to_string()).collect();
+        let layers = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(expand_layers(&layers(&["*.Cu", "*.Mask"]), &copper, false), copper);
+        assert_eq!(expand_layers(&layers(&["F.Cu", "In2.Cu"]), &copper, true), copper[..3].to_vec());
+        assert_eq!(expand_layers(&layers(&["F.Cu", "F.Paste"]), &copper, false), layers(&["F.Cu"]));
+    }
+
+    #[test]
+    fn test_indexed_polygon() {
+        let square = vec![pt(0.0, 0.0), pt(10.0, 0.0), pt(10.0, 10.0), pt(0.0, 10.0)];
+        let index = IndexedPolygon::new(&square);
+
+        assert!(index.contains(&pt(5.0, 5.0)));
+        assert!(!index.contains(&pt(15.0, 5.0)));
+        let crossing = Shape::Capsule { a: pt(-1.0, 5.0), b: pt(-0.2, 5.0), radius: 0.25 };
+        assert!(index.touches(&crossing));
+        let outside = Shape::Capsule { a: pt(-1.0, 5.0), b: pt(-0.5, 5.0), radius: 0.25 };
+        assert!(!index.touches(&outside));
+    }
+
+    #[test]
+    fn test_copper_areas() {
+        let pcb = crate::pcb::parse_pcb(
+            r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "GND") (net 2 "SIG")
+  (footprint "TP" (layer "F.Cu") (at 0 0)
+    (pad "1" thru_hole rect (at 0 0) (size 2 2) (drill 1) (layers "*.Cu") (net 2 "SIG")))
+  (segment (start 0 0) (end 10 0) (width 0.2) (layer "F.Cu") (net 2))
+  (arc (start 0 0) (mid 1 1) (end 2 0) (width 0.2) (layer "B.Cu") (net 2))
+  (via (at 10 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 2))
+  (zone (net 1) (net_name "GND") (layer "B.Cu")
+    (filled_polygon (layer "B.Cu") (pts (xy 0 20) (xy 40 20) (xy 40 30) (xy 0 30))))
+)"#,
+        )
+        .unwrap();
+        let areas = copper_areas(&pcb);
+        let area = |layer: &str, net: &str| {
+            areas.iter().find(|a| a.layer == layer && a.net.as_deref() == Some(net)).unwrap().area
+        };
+        let pi = std::f64::consts::PI;
+        let pad = 4.0 - pi / 4.0;
+        let ring = pi * (0.09 - 0.0225);
+
+        assert_eq!(areas.len(), 3);
+        assert_eq!(areas[0].layer, "F.Cu");
+        assert!((area("F.Cu", "SIG") - (pad + 2.0 + pi * 0.01 + ring)).abs() < 1e-9);
+        assert!((area("B.Cu", "SIG") - (pad + pi * 0.2 + pi * 0.01 + ring)).abs() < 1e-9);
+        assert!((area("B.Cu", "GND") - 400.0).abs() < 1e-9);
+    }
+}
@@ -7,10 +7,19 @@
 use regex::Regex;
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
 use crate::error::Result;
+use crate::progress::{ParseStage, Progress, ProgressCallback, Reporter};
+use crate::sexpr::parse_sexpr;
+use crate::units::Length;
+use super::geometry::{outline_piece, reconstruct_polygons};
+use super::pcb_parser::PcbParser;
+pub use super::types::ModelType;
+use super::types::BoardPolygon;
 
 /// Component information extracted from footprints
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ComponentInfo {
     pub reference: String,
     pub footprint: String,
@@ -22,6 +31,7 @@ pub struct ComponentInfo {
 
 /// 3D model information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Model3DInfo {
     pub reference: String,
     pub footprint: String,
@@ -29,36 +39,40 @@ pub struct Model3DInfo {
     pub model_type: ModelType,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum ModelType {
-    Wrl,
-    Step,
-    Iges,
-    Other,
+impl Model3DInfo {
+    /// Whether the model is embedded in the file (`kicad-embed://`) rather than on disk
+    pub fn is_embedded(&self) -> bool {
+        crate::embedded::embedded_name(&self.model_path).is_some()
+    }
 }
 
 /// Track/trace information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrackInfo {
     pub start: (f64, f64),
     pub end: (f64, f64),
     pub width: f64,
     pub layer: String,
     pub net: Option<i32>,
+    pub net_name: Option<String>,
 }
 
 /// Via information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ViaInfo {
     pub position: (f64, f64),
     pub size: f64,
     pub drill: f64,
     pub layers: (String, String),
     pub net: Option<i32>,
+    pub net_name: Option<String>,
 }
 
 /// Board outline from Edge.Cuts
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoardOutline {
     pub min_x: f64,
     pub min_y: f64,
@@ -89,38 +103,67 @@ static MODEL_3D_REGEX: Lazy<Regex> = Lazy::new(|| {
 
 static TRACK_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r#"\(segment\s*\(start\s+([\d.-]+)\s+([\d.-]+)\)\s*\(end\s+([\d.-]+)\s+([\d.-]+)\)\s*\(width\s+([\d.-]+)\)\s*\(layer\s+"([^"]+)"\)(?:\s*\(net\s+(\d+)\))?"#
+        r#"\(segment\s*\(start\s+([\d.-]+)\s+([\d.-]+)\)\s*\(end\s+([\d.-]+)\s+([\d.-]+)\)\s*\(width\s+([\d.-]+)\)\s*\(layer\s+"([^"]+)"\)(?:\s*\(net\s+(?:(\d+)|"([^"]*)")\))?"#
     ).unwrap()
 });
 
 static VIA_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
-        r#"\(via\s*\(at\s+([\d.-]+)\s+([\d.-]+)\)\s*\(size\s+([\d.-]+)\)\s*\(drill\s+([\d.-]+)\)\s*\(layers\s+"([^"]+)"\s+"([^"]+)"\)(?:\s*\(net\s+(\d+)\))?"#
+        r#"\(via\s*\(at\s+([\d.-]+)\s+([\d.-]+)\)\s*\(size\s+([\d.-]+)\)\s*\(drill\s+([\d.-]+)\)\s*\(layers\s+"([^"]+)"\s+"([^"]+)"\)(?:\s*\(net\s+(?:(\d+)|"([^"]*)")\))?"#
     ).unwrap()
 });
 
+static NET_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\(net\s+(\d+)\s+"([^"]*)"\)"#).unwrap()
+});
+
 static EDGE_CUTS_REGEX: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r#"(?s)\(gr_line\s*\(start\s+([\d.-]+)\s+([\d.-]+)\)\s*\(end\s+([\d.-]+)\s+([\d.-]+)\).*?\(layer\s+"Edge\.Cuts"\)"#
     ).unwrap()
 });
 
+static BOARD_GRAPHIC_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\(gr_(?:line|arc|circle|rect|poly|curve)\s"#).unwrap()
+});
+
 /// Detail parser for extracting specific PCB elements
 pub struct DetailParser<'a> {
     content: &'a str,
+    progress: Option<ProgressCallback<'a>>,
+    cancel: Option<&'a AtomicBool>,
 }
 
 impl<'a> DetailParser<'a> {
     /// Create a new detail parser for the given PCB content
     pub fn new(content: &'a str) -> Self {
-        Self { content }
+        Self { content, progress: None, cancel: None }
+    }
+
+    /// Call `callback` during each extraction's pass over the content, see [`crate::progress`]
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Stop extractions with [`KicadError::Cancelled`](crate::KicadError::Cancelled) once `cancel` is set
+    pub fn cancel_on(mut self, cancel: &'a AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Progress reporter for one regex pass over the content
+    fn scan(&self) -> Reporter<'_> {
+        Reporter::new(self.progress.as_deref(), self.cancel, ParseStage::Scanning, self.content.len())
     }
 
     /// Extract all component information
     pub fn extract_components(&self) -> Result<Vec<ComponentInfo>> {
         let mut components = Vec::new();
         
+        let mut scan = self.scan();
         for cap in COMPONENT_WITH_VALUE_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
             let footprint = cap[1].to_string();
             let x: f64 = cap[2].parse().unwrap_or(0.0);
             let y: f64 = cap[3].parse().unwrap_or(0.0);
@@ -141,8 +184,12 @@ impl<'a> DetailParser<'a> {
             });
         }
         
+        scan.finish();
+        
         // Also capture components without explicit value
+        let mut scan = self.scan();
         for cap in COMPONENT_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
             let reference = &cap[5];
             // Skip if we already have this component
             if components.iter().any(|c| c.reference == reference) {
@@ -164,6 +211,7 @@ impl<'a> DetailParser<'a> {
                 value: None,
             });
         }
+        scan.finish();
         
         Ok(components)
     }
@@ -172,20 +220,14 @@ impl<'a> DetailParser<'a> {
     pub fn extract_3d_models(&self) -> Result<Vec<Model3DInfo>> {
         let mut models = Vec::new();
         
+        let mut scan = self.scan();
         for cap in MODEL_3D_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
             let footprint = cap[1].to_string();
             let reference = cap[2].to_string();
             let model_path = cap[3].to_string();
             
-            let model_type = if model_path.ends_with(".wrl") {
-                ModelType::Wrl
-            } else if model_path.ends_with(".step") || model_path.ends_with(".stp") {
-                ModelType::Step
-            } else if model_path.ends_with(".igs") || model_path.ends_with(".iges") {
-                ModelType::Iges
-            } else {
-                ModelType::Other
-            };
+            let model_type = ModelType::from_path(&model_path);
             
             models.push(Model3DInfo {
                 reference,
@@ -194,15 +236,35 @@ impl<'a> DetailParser<'a> {
                 model_type,
             });
         }
+        scan.finish();
         
         Ok(models)
     }
 
+    /// Extract the net declaration table (net number -> net name)
+    pub fn extract_nets(&self) -> Result<HashMap<u32, String>> {
+        let mut nets = HashMap::new();
+
+        let mut scan = self.scan();
+        for cap in NET_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
+            if let Ok(id) = cap[1].parse() {
+                nets.entry(id).or_insert_with(|| cap[2].to_string());
+            }
+        }
+        scan.finish();
+
+        Ok(nets)
+    }
+
     /// Extract track/trace information
     pub fn extract_tracks(&self) -> Result<Vec<TrackInfo>> {
         let mut tracks = Vec::new();
+        let nets = self.extract_nets()?;
         
+        let mut scan = self.scan();
         for cap in TRACK_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
             let start_x: f64 = cap[1].parse().unwrap_or(0.0);
             let start_y: f64 = cap[2].parse().unwrap_or(0.0);
             let end_x: f64 = cap[3].parse().unwrap_or(0.0);
@@ -210,6 +272,7 @@ impl<'a> DetailParser<'a> {
             let width: f64 = cap[5].parse().unwrap_or(0.0);
             let layer = cap[6].to_string();
             let net = cap.get(7).and_then(|m| m.as_str().parse().ok());
+            let net_name = resolve_net_name(&nets, net, cap.get(8).map(|m| m.as_str()));
             
             tracks.push(TrackInfo {
                 start: (start_x, start_y),
@@ -217,8 +280,10 @@ impl<'a> DetailParser<'a> {
                 width,
                 layer,
                 net,
+                net_name,
             });
         }
+        scan.finish();
         
         Ok(tracks)
     }
@@ -226,8 +291,11 @@ impl<'a> DetailParser<'a> {
     /// Extract via information
     pub fn extract_vias(&self) -> Result<Vec<ViaInfo>> {
         let mut vias = Vec::new();
+        let nets = self.extract_nets()?;
         
+        let mut scan = self.scan();
         for cap in VIA_REGEX.captures_iter(self.content) {
+            scan.element(cap.get(0).map_or(0, |m| m.end()))?;
             let x: f64 = cap[1].parse().unwrap_or(0.0);
             let y: f64 = cap[2].parse().unwrap_or(0.0);
             let size: f64 = cap[3].parse().unwrap_or(0.0);
@@ -235,6 +303,7 @@ impl<'a> DetailParser<'a> {
             let layer1 = cap[5].to_string();
             let layer2 = cap[6].to_string();
             let net = cap.get(7).and_then(|m| m.as_str().parse().ok());
+            let net_name = resolve_net_name(&nets, net, cap.get(8).map(|m| m.as_str()));
             
             vias.push(ViaInfo {
                 position: (x, y),
@@ -242,14 +311,38 @@ impl<'a> DetailParser<'a> {
                 drill,
                 layers: (layer1, layer2),
                 net,
+                net_name,
             });
         }
+        scan.finish();
         
         Ok(vias)
     }
 
     /// Extract board outline from Edge.Cuts layer
     pub fn extract_board_outline(&self) -> Result<Option<BoardOutline>> {
+        let polygons = self.extract_board_polygon()?;
+        if !polygons.is_empty() {
+            let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+            let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+            for polygon in &polygons {
+                let bounds = polygon.bounds();
+                min_x = min_x.min(bounds.start.x.as_mm());
+                min_y = min_y.min(bounds.start.y.as_mm());
+                max_x = max_x.max(bounds.end.x.as_mm());
+                max_y = max_y.max(bounds.end.y.as_mm());
+            }
+            return Ok(Some(BoardOutline {
+                min_x,
+                min_y,
+                max_x,
+                max_y,
+                width_mm: max_x - min_x,
+                height_mm: max_y - min_y,
+            }));
+        }
+
+        // Outline does not close: fall back to the extent of the Edge.Cuts lines
         let mut min_x = f64::MAX;
         let mut min_y = f64::MAX;
         let mut max_x = f64::MIN;
@@ -286,6 +379,36 @@ impl<'a> DetailParser<'a> {
         }))
     }
 
+    /// Extract the board outline as closed polygons with their cutouts
+    ///
+    /// Board-level Edge.Cuts lines, arcs, circles, rectangles and polygons are chained
+    /// end to end; segments that never close a loop are ignored. Edge.Cuts drawings
+    /// inside footprints are not considered here, use [`PcbFile::board_outline`] for
+    /// those.
+    ///
+    /// [`PcbFile::board_outline`]: super::types::PcbFile::board_outline
+    pub fn extract_board_polygon(&self) -> Result<Vec<BoardPolygon>> {
+        let mut pieces = Vec::new();
+        let mut scan = self.scan();
+        for m in BOARD_GRAPHIC_REGEX.find_iter(self.content) {
+            scan.element(m.end())?;
+            let element = match balanced_element(&self.content[m.start()..]) {
+                Some(element) => element,
+                None => continue,
+            };
+            if !element.contains("Edge.Cuts") {
+                continue;
+            }
+            if let Some(graphic) = PcbParser::parse_graphic(&parse_sexpr(element)?) {
+                if graphic.layer() == "Edge.Cuts" {
+                    pieces.push(outline_piece(&graphic));
+                }
+            }
+        }
+        scan.finish();
+        Ok(reconstruct_polygons(pieces))
+    }
+
     /// Extract component counts by type
     pub fn extract_component_summary(&self) -> Result<HashMap<String, usize>> {
         let components = self.extract_components()?;
@@ -308,6 +431,50 @@ impl<'a> DetailParser<'a> {
     }
 }
 
+/// Resolve a net reference that is either a number from the net table or a literal name
+/// The parenthesized element starting at the beginning of `text`, quotes respected
+fn balanced_element(text: &str) -> Option<&str> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, b) in text.bytes().enumerate() {
+        if in_string {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match b {
+            b'"' => in_string = true,
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&text[..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn resolve_net_name(nets: &HashMap<u32, String>, id: Option<i32>, name: Option<&str>) -> Option<String> {
+    let name = match (id, name) {
+        (_, Some(name)) => name,
+        (Some(id), None) => nets.get(&u32::try_from(id).ok()?)?.as_str(),
+        (None, None) => return None,
+    };
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
 /// Extract component reference prefix (R, C, U, etc.)
 fn extract_component_prefix(reference: &str) -> String {
     reference.chars()
@@ -317,12 +484,12 @@ fn extract_component_prefix(reference: &str) -> String {
 
 /// Convert millimeters to mils
 pub fn mm_to_mils(mm: f64) -> f64 {
-    mm * 39.3701
+    Length::mm(mm).as_mils()
 }
 
 /// Convert square millimeters to square inches
 pub fn mm2_to_sq_in(mm2: f64) -> f64 {
-    mm2 / 645.16
+    crate::units::mm2_to_sq_in(mm2)
 }
 
 #[cfg(test)]
@@ -372,6 +539,56 @@ mod tests {
         assert_eq!(models[0].model_type, ModelType::Wrl);
     }
 
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serde_round_trip() {
+        let content = r#"
+        (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 10 20 90)
+            (property "Reference" "U1")
+            (model "${KICAD8_3DMODEL_DIR}/Package_SO.3dshapes/SOIC-8.step"))
+        (via (at 1 2) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 0))
+        "#;
+        let parser = DetailParser::new(content);
+
+        let models = serde_json::to_value(parser.extract_3d_models().unwrap()).unwrap();
+        assert_eq!(models[0]["model_type"], "step");
+        let models: Vec<Model3DInfo> = serde_json::from_value(models).unwrap();
+        assert_eq!(models[0].model_type, ModelType::Step);
+
+        let vias = serde_json::to_string(&parser.extract_vias().unwrap()).unwrap();
+        let vias: Vec<ViaInfo> = serde_json::from_str(&vias).unwrap();
+        assert_eq!(vias[0].position, (1.0, 2.0));
+        assert_eq!(vias[0].layers, ("F.Cu".to_string(), "B.Cu".to_string()));
+
+        let components = serde_json::to_value(parser.extract_components().unwrap()).unwrap();
+        assert_eq!(components[0]["reference"], "U1");
+        assert_eq!(components[0]["position"], serde_json::json!([10.0, 20.0]));
+    }
+
+    #[test]
+    fn test_track_and_via_net_names() {
+        let content = r#"
+        (net 0 "")
+        (net 1 "GND")
+        (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net 1))
+        (segment (start 1 0) (end 2 0) (width 0.2) (layer "F.Cu") (net "VCC"))
+        (via (at 0 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 0))
+        "#;
+        
+        let parser = DetailParser::new(content);
+        assert_eq!(parser.extract_nets().unwrap().get(&1).map(String::as_str), Some("GND"));
+        
+        let tracks = parser.extract_tracks().unwrap();
+        assert_eq!(tracks[0].net, Some(1));
+        assert_eq!(tracks[0].net_name.as_deref(), Some("GND"));
+        assert_eq!(tracks[1].net, None);
+        assert_eq!(tracks[1].net_name.as_deref(), Some("VCC"));
+        
+        let vias = parser.extract_vias().unwrap();
+        assert_eq!(vias[0].net, Some(0));
+        assert_eq!(vias[0].net_name, None);
+    }
+
     #[test]
     fn test_board_outline() {
         let content = r#"
@@ -387,4 +604,39 @@ mod tests {
         assert_eq!(outline.width_mm, 100.0);
         assert_eq!(outline.height_mm, 50.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_board_polygon() {
+        let content = r#"
+        (gr_line (start 0 0) (end 100 0) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+        (gr_line (start 0 50) (end 0 0) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+        (gr_line (start 100 0) (end 100 50) (stroke (width 0.1) (type default)) (layer "F.SilkS"))
+        (gr_arc (start 100 0) (mid 125 25) (end 100 50) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+        (gr_line (start 100 50) (end 0 50) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+        (gr_circle (center 20 25) (end 23 25) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+        "#;
+
+        let parser = DetailParser::new(content);
+        let polygons = parser.extract_board_polygon().unwrap();
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].cutouts.len(), 1);
+
+        let outline = parser.extract_board_outline().unwrap().unwrap();
+        assert!((outline.max_x - 125.0).abs() < 1e-6);
+        assert_eq!(outline.height_mm, 50.0);
+    }
+
+    #[test]
+    fn test_progress_per_scan() {
+        let content = r#"(kicad_pcb (net 0 "") (net 1 "GND")
+  (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net 1))
+  (segment (start 1 0) (end 2 0) (width 0.2) (layer "F.Cu") (net 1)))"#;
+        let reports = std::cell::RefCell::new(Vec::new());
+        let parser = DetailParser::new(content).on_progress(|p| reports.borrow_mut().push(p));
+        assert_eq!(parser.extract_tracks().unwrap().len(), 2);
+
+        // The net table scan, then the track scan, each closed by a final report
+        let finals: Vec<usize> = reports.borrow().iter().filter(|p| p.fraction() == 1.0).map(|p| p.elements_parsed).collect();
+        assert_eq!(finals, [2, 2]);
+    }
+}
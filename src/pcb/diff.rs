@@ -0,0 +1,418 @@
+//! Structured differences between two boards
+//!
+//! [`diff_boards`] compares an old and a new revision of a board and returns a
+//! [`BoardDiff`]: components added, removed, moved or with changed properties
+//! (matched by reference), nets added or removed, pads whose net changed, layer
+//! table and stackup changes, and whether the board outline changed. It is what
+//! `kpx diff` prints, available to any tool that wants to report changes.
+//!
+//! ```rust
+//! use kiparse::pcb::{diff::diff_boards, parse_pcb};
+//!
+//! let old = parse_pcb(r#"(kicad_pcb (net 0 "") (net 1 "GND")
+//!   (footprint "R_0603" (layer "F.Cu") (at 10 10) (property "Reference" "R1") (property "Value" "10k")))"#)?;
+//! let new = parse_pcb(r#"(kicad_pcb (net 0 "") (net 1 "GND") (net 2 "VCC")
+//!   (footprint "R_0603" (layer "F.Cu") (at 12 10) (property "Reference" "R1") (property "Value" "4k7")))"#)?;
+//! let diff = diff_boards(&old, &new);
+//!
+//! assert_eq!(diff.moved_components[0].to.x, 12.0);
+//! assert_eq!(diff.property_changes[0].to.as_deref(), Some("4k7"));
+//! assert_eq!(diff.added_nets, ["VCC"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::geometry::bounds;
+use super::types::{BoardPolygon, Footprint, Layer, PcbFile, Point, StackupLayer};
+use crate::units::Length;
+
+/// Positions and rotations closer than this (mm, degrees) count as unchanged
+pub const DIFF_TOLERANCE: f64 = 1e-4;
+
+/// Everything that changed between two boards
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardDiff {
+    pub added_components: Vec<ComponentEntry>,
+    pub removed_components: Vec<ComponentEntry>,
+    pub moved_components: Vec<ComponentMove>,
+    /// Footprint name and property changes of components on both boards
+    pub property_changes: Vec<PropertyChange>,
+    pub added_nets: Vec<String>,
+    pub removed_nets: Vec<String>,
+    /// Pads of components on both boards whose net changed
+    pub pad_net_changes: Vec<PadNetChange>,
+    pub layer_changes: Vec<LayerChange>,
+    pub stackup_changes: Vec<StackupChange>,
+    pub outline: Option<OutlineChange>,
+}
+
+impl BoardDiff {
+    /// Whether the boards are the same in every compared respect
+    pub fn is_empty(&self) -> bool {
+        self.added_components.is_empty()
+            && self.removed_components.is_empty()
+            && self.moved_components.is_empty()
+            && self.property_changes.is_empty()
+            && self.added_nets.is_empty()
+            && self.removed_nets.is_empty()
+            && self.pad_net_changes.is_empty()
+            && self.layer_changes.is_empty()
+            && self.stackup_changes.is_empty()
+            && self.outline.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentEntry {
+    pub reference: String,
+    pub footprint: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Placement {
+    pub x: f64,
+    pub y: f64,
+    pub rotation: f64,
+    /// `top` or `bottom`
+    pub side: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentMove {
+    pub reference: String,
+    pub from: Placement,
+    pub to: Placement,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyChange {
+    pub reference: String,
+    /// Property name, or `footprint` for the library footprint
+    pub property: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PadNetChange {
+    /// `REF.PIN`
+    pub pad: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerChange {
+    /// Layer number in the board's layer table
+    pub id: i32,
+    /// Changed property (`name`, `type` or `user_name`), or `layer` when the
+    /// layer was added or removed
+    pub property: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackupChange {
+    pub layer: String,
+    /// Changed property, or `layer` when the layer was added or removed
+    pub property: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineSummary {
+    pub polygons: usize,
+    pub vertices: usize,
+    pub width: f64,
+    pub height: f64,
+    pub area: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutlineChange {
+    pub from: OutlineSummary,
+    pub to: OutlineSummary,
+}
+
+/// Compare `old` with `new`; components are matched by reference
+pub fn diff_boards(old: &PcbFile, new: &PcbFile) -> BoardDiff {
+    let (old_parts, new_parts) = (by_reference(old), by_reference(new));
+    let entry = |reference: &String, f: &Footprint| ComponentEntry {
+        reference: reference.clone(),
+        footprint: f.name.clone(),
+        value: f.property("Value").unwrap_or_default().to_string(),
+    };
+
+    let mut diff = BoardDiff {
+        added_components: new_parts
+            .iter()
+            .filter(|(r, _)| !old_parts.contains_key(*r))
+            .map(|(r, f)| entry(r, f))
+            .collect(),
+        removed_components: old_parts
+            .iter()
+            .filter(|(r, _)| !new_parts.contains_key(*r))
+            .map(|(r, f)| entry(r, f))
+            .collect(),
+        layer_changes: diff_layers(old, new),
+        stackup_changes: diff_stackup(&old.stackup, &new.stackup),
+        ..Default::default()
+    };
+
+    for (reference, old_fp) in &old_parts {
+        let Some(new_fp) = new_parts.get(reference) else { continue };
+        let (from, to) = (placement(old_fp), placement(new_fp));
+        let turn = (from.rotation - to.rotation).rem_euclid(360.0);
+        let moved = (from.x - to.x).abs() > DIFF_TOLERANCE
+            || (from.y - to.y).abs() > DIFF_TOLERANCE
+            || turn.min(360.0 - turn) > DIFF_TOLERANCE
+            || from.side != to.side;
+        if moved {
+            diff.moved_components.push(ComponentMove { reference: reference.clone(), from, to });
+        }
+
+        if old_fp.name != new_fp.name {
+            diff.property_changes.push(PropertyChange {
+                reference: reference.clone(),
+                property: "footprint".to_string(),
+                from: Some(old_fp.name.clone()),
+                to: Some(new_fp.name.clone()),
+            });
+        }
+        let names: BTreeSet<&String> = old_fp.properties.keys().chain(new_fp.properties.keys()).collect();
+        for name in names.into_iter().filter(|n| *n != "Reference") {
+            let (from, to) = (old_fp.properties.get(name), new_fp.properties.get(name));
+            if from != to {
+                diff.property_changes.push(PropertyChange {
+                    reference: reference.clone(),
+                    property: name.clone(),
+                    from: from.cloned(),
+                    to: to.cloned(),
+                });
+            }
+        }
+
+        let new_nets = pad_nets(new_fp);
+        for (number, from) in pad_nets(old_fp) {
+            let Some(to) = new_nets.get(&number) else { continue };
+            if &from != to {
+                diff.pad_net_changes.push(PadNetChange {
+                    pad: format!("{}.{}", reference, number),
+                    from,
+                    to: to.clone(),
+                });
+            }
+        }
+    }
+
+    let net_names = |board: &PcbFile| -> BTreeSet<String> {
+        board.nets.values().filter(|n| !n.is_empty()).cloned().collect()
+    };
+    let (old_nets, new_nets) = (net_names(old), net_names(new));
+    diff.added_nets = new_nets.difference(&old_nets).cloned().collect();
+    diff.removed_nets = old_nets.difference(&new_nets).cloned().collect();
+
+    let (old_outline, new_outline) = (old.board_outline(), new.board_outline());
+    if !same_outline(&old_outline, &new_outline) {
+        diff.outline = Some(OutlineChange {
+            from: outline_summary(&old_outline),
+            to: outline_summary(&new_outline),
+        });
+    }
+
+    diff
+}
+
+fn by_reference(board: &PcbFile) -> BTreeMap<String, &Footprint> {
+    board
+        .footprints
+        .iter()
+        .map(|f| (f.property("Reference").unwrap_or_default().to_string(), f))
+        .collect()
+}
+
+fn placement(footprint: &Footprint) -> Placement {
+    Placement {
+        x: footprint.position.x.as_mm(),
+        y: footprint.position.y.as_mm(),
+        rotation: footprint.rotation,
+        side: if footprint.layer == "B.Cu" { "bottom" } else { "top" }.to_string(),
+    }
+}
+
+/// Net of each numbered pad
+fn pad_nets(footprint: &Footprint) -> BTreeMap<String, Option<String>> {
+    footprint
+        .pads
+        .iter()
+        .filter(|p| !p.number.is_empty())
+        .map(|p| (p.number.clone(), p.net.clone().filter(|n| !n.is_empty())))
+        .collect()
+}
+
+fn diff_layers(old: &PcbFile, new: &PcbFile) -> Vec<LayerChange> {
+    let ids: BTreeSet<i32> = old.layers.keys().chain(new.layers.keys()).copied().collect();
+    let mut changes = Vec::new();
+    for id in ids {
+        let change = |property: &str, from: Option<String>, to: Option<String>| LayerChange {
+            id,
+            property: property.to_string(),
+            from,
+            to,
+        };
+        match (old.layers.get(&id), new.layers.get(&id)) {
+            (Some(before), Some(after)) => {
+                let properties = [
+                    ("name", Some(before.name.clone()), Some(after.name.clone())),
+                    ("type", Some(before.layer_type.clone()), Some(after.layer_type.clone())),
+                    ("user_name", before.user_name.clone(), after.user_name.clone()),
+                ];
+                for (property, from, to) in properties {
+                    if from != to {
+                        changes.push(change(property, from, to));
+                    }
+                }
+            }
+            (before, after) => {
+                let name = |layer: Option<&Layer>| layer.map(|l| l.name.clone());
+                changes.push(change("layer", name(before), name(after)));
+            }
+        }
+    }
+    changes
+}
+
+fn diff_stackup(old: &[StackupLayer], new: &[StackupLayer]) -> Vec<StackupChange> {
+    let mut changes = Vec::new();
+    let layer_change = |layer: &StackupLayer, added: bool| StackupChange {
+        layer: layer.name.clone(),
+        property: "layer".to_string(),
+        from: (!added).then(|| layer.layer_type.clone()),
+        to: added.then(|| layer.layer_type.clone()),
+    };
+    for layer in old.iter().filter(|l| !new.iter().any(|n| n.name == l.name)) {
+        changes.push(layer_change(layer, false));
+    }
+    for layer in new {
+        let Some(before) = old.iter().find(|o| o.name == layer.name) else {
+            changes.push(layer_change(layer, true));
+            continue;
+        };
+        let number = |v: Option<f64>| v.map(|v| format!("{}", v));
+        let properties = [
+            ("type", Some(before.layer_type.clone()), Some(layer.layer_type.clone())),
+            ("thickness", number(before.thickness.map(Length::as_mm)), number(layer.thickness.map(Length::as_mm))),
+            ("material", before.material.clone(), layer.material.clone()),
+            ("epsilon_r", number(before.epsilon_r), number(layer.epsilon_r)),
+            ("loss_tangent", number(before.loss_tangent), number(layer.loss_tangent)),
+        ];
+        for (property, from, to) in properties {
+            if from != to {
+                changes.push(StackupChange { layer: layer.name.clone(), property: property.to_string(), from, to });
+            }
+        }
+    }
+    changes
+}
+
+fn same_outline(old: &[BoardPolygon], new: &[BoardPolygon]) -> bool {
+    let same = |p: &[Point], q: &[Point]| {
+        p.len() == q.len()
+            && p.iter().zip(q).all(|(p, q)| {
+                (p.x - q.x).abs().as_mm() <= DIFF_TOLERANCE && (p.y - q.y).abs().as_mm() <= DIFF_TOLERANCE
+            })
+    };
+    old.len() == new.len()
+        && old.iter().zip(new).all(|(a, b)| {
+            same(&a.outline, &b.outline)
+                && a.cutouts.len() == b.cutouts.len()
+                && a.cutouts.iter().zip(&b.cutouts).all(|(c, d)| same(c, d))
+        })
+}
+
+fn outline_summary(polygons: &[BoardPolygon]) -> OutlineSummary {
+    let corners: Vec<Point> = polygons
+        .iter()
+        .flat_map(|p| [p.bounds().start, p.bounds().end])
+        .collect();
+    let (width, height) = if corners.is_empty() {
+        (0.0, 0.0)
+    } else {
+        let (min, max) = bounds(&corners);
+        ((max.x - min.x).as_mm(), (max.y - min.y).as_mm())
+    };
+    OutlineSummary {
+        polygons: polygons.len(),
+        vertices: polygons
+            .iter()
+            .map(|p| p.outline.len() + p.cutouts.iter().map(Vec::len).sum::<usize>())
+            .sum(),
+        width,
+        height,
+        area: polygons.iter().fold(0.0, |area, p| area + p.area()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const FPGA: &str = include_str!("../../assets/fpga.kicad_pcb");
+
+    #[test]
+    fn test_identical_boards() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let diff = diff_boards(&pcb, &pcb.clone());
+        assert!(diff.is_empty());
+        assert_eq!(diff, BoardDiff::default());
+    }
+
+    #[test]
+    fn test_component_net_and_layer_changes() {
+        let old = parse_pcb(r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal) (44 "Edge.Cuts" user))
+  (net 0 "") (net 1 "GND") (net 2 "SIG")
+  (footprint "R_0603" (layer "F.Cu") (at 10 10) (property "Reference" "R1") (property "Value" "10k")
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 2 "SIG")))
+  (footprint "C_0603" (layer "F.Cu") (at 20 10) (property "Reference" "C1") (property "Value" "100n"))
+  (gr_rect (start 0 0) (end 50 30) (layer "Edge.Cuts") (width 0.1)))"#).unwrap();
+        let new = parse_pcb(r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (1 "In1.Cu" power) (31 "B.Cu" mixed) (44 "Edge.Cuts" user))
+  (net 0 "") (net 1 "GND") (net 3 "VCC")
+  (footprint "R_0805" (layer "B.Cu") (at 10 10 180) (property "Reference" "R1") (property "Value" "10k")
+    (property "MPN" "RC0805") (pad "1" smd rect (at 0 0) (size 1 1) (layers "B.Cu") (net 1 "GND")))
+  (footprint "U" (layer "F.Cu") (at 30 10) (property "Reference" "U1"))
+  (gr_rect (start 0 0) (end 60 30) (layer "Edge.Cuts") (width 0.1)))"#).unwrap();
+        let diff = diff_boards(&old, &new);
+
+        assert_eq!(diff.added_components[0].reference, "U1");
+        assert_eq!(diff.removed_components[0].reference, "C1");
+        assert_eq!(diff.moved_components[0].to.side, "bottom");
+        let properties: Vec<(&str, Option<&str>)> = diff
+            .property_changes
+            .iter()
+            .map(|c| (c.property.as_str(), c.to.as_deref()))
+            .collect();
+        assert_eq!(properties, [("footprint", Some("R_0805")), ("MPN", Some("RC0805"))]);
+        assert_eq!((diff.added_nets.as_slice(), diff.removed_nets.as_slice()), (&["VCC".to_string()][..], &["SIG".to_string()][..]));
+        assert_eq!(diff.pad_net_changes[0].pad, "R1.1");
+        let layers: Vec<(i32, &str)> = diff.layer_changes.iter().map(|c| (c.id, c.property.as_str())).collect();
+        assert_eq!(layers, [(1, "layer"), (31, "type")]);
+        assert_eq!(diff.outline.as_ref().unwrap().to.width, 60.0);
+    }
+}
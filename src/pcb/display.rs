@@ -0,0 +1,241 @@
+//! Short human-readable descriptions of boards and their parts
+//!
+//! `Display` gives one line per value, suitable for logs; `summary()` adds a few
+//! lines of detail for interactive debugging. Both stay far shorter than the
+//! `Debug` output of a whole board.
+//!
+//! ```rust
+//! use kiparse::pcb::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+//!   (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+//!   (net 0 "") (net 1 "GND")
+//!   (footprint "Resistor_SMD:R_0603_1608Metric" (layer "F.Cu") (at 10 5 90)
+//!     (property "Reference" "R1") (property "Value" "10k")
+//!     (pad "1" smd rect (at -0.8 0) (size 0.8 0.95) (layers "F.Cu") (net 1 "GND"))))"#)?;
+//!
+//! assert_eq!(pcb.layers[&0].to_string(), "F.Cu (0, signal)");
+//! assert_eq!(
+//!     pcb.footprints[0].to_string(),
+//!     "R1 10k Resistor_SMD:R_0603_1608Metric at (10, 5) 90° on F.Cu, 1 pad"
+//! );
+//! assert_eq!(pcb.to_string(), "board 20240108: 2 copper layers, 1 footprint, 0 tracks, 0 vias, 1 net");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt::{self, Write};
+
+use super::geometry::bounds;
+use super::types::{Footprint, Layer, PcbFile, Point, Symbol};
+use crate::bom::natural_order;
+
+/// `1 pad`, `2 pads`
+fn count(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+/// Coordinates without trailing zeros, rounded to the micrometer
+fn xy(point: &Point) -> String {
+    let number = |v: f64| {
+        let text = format!("{:.3}", v);
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        if text == "-0" { "0".to_string() } else { text.to_string() }
+    };
+    format!("({}, {})", number(point.x.as_mm()), number(point.y.as_mm()))
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(user_name) = self.user_name.as_deref().filter(|u| *u != self.name) {
+            write!(f, " \"{}\"", user_name)?;
+        }
+        write!(f, " ({}, {})", self.id, self.layer_type)
+    }
+}
+
+/// `R1 10k Lib:Name at (x, y) 90° on F.Cu, N pads`
+impl fmt::Display for Footprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.property("Reference").filter(|r| !r.is_empty()).unwrap_or("?"))?;
+        if let Some(value) = self.property("Value").filter(|v| !v.is_empty()) {
+            write!(f, " {}", value)?;
+        }
+        write!(f, " {} at {}", self.name, xy(&self.position))?;
+        if self.rotation != 0.0 {
+            write!(f, " {}°", self.rotation)?;
+        }
+        write!(f, " on {}, {}", self.layer, count(self.pads.len(), "pad"))
+    }
+}
+
+impl Footprint {
+    /// The `Display` line followed by the fabrication flags and each pad's net
+    pub fn summary(&self) -> String {
+        let mut out = self.to_string();
+        let attributes = &self.attributes;
+        let flags: Vec<&str> = [
+            (!attributes.mount.is_empty(), attributes.mount.as_str()),
+            (attributes.dnp, "dnp"),
+            (attributes.exclude_from_bom, "exclude_from_bom"),
+            (attributes.exclude_from_pos_files, "exclude_from_pos_files"),
+            (attributes.board_only, "board_only"),
+            (self.locked, "locked"),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect();
+        if !flags.is_empty() {
+            let _ = write!(out, "\n  attributes: {}", flags.join(", "));
+        }
+        let mut pads: Vec<_> = self.pads.iter().filter(|p| !p.number.is_empty()).collect();
+        pads.sort_by(|a, b| natural_order(&a.number, &b.number));
+        if !pads.is_empty() {
+            let pads: Vec<String> = pads
+                .iter()
+                .map(|p| format!("{}={}", p.number, p.net.as_deref().unwrap_or("-")))
+                .collect();
+            let _ = write!(out, "\n  pads: {}", pads.join(" "));
+        }
+        out
+    }
+}
+
+/// `board VERSION: N copper layers, N footprints, N tracks, N vias, N nets`
+impl fmt::Display for PcbFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let copper = self.layers.values().filter(|l| l.name.ends_with(".Cu")).count();
+        write!(
+            f,
+            "board {}: {}, {}, {}, {}, {}",
+            self.version,
+            count(copper, "copper layer"),
+            count(self.footprints.len(), "footprint"),
+            count(self.tracks.len() + self.arcs.len(), "track"),
+            count(self.vias.len(), "via"),
+            count(self.nets.values().filter(|n| !n.is_empty()).count(), "net"),
+        )
+    }
+}
+
+impl PcbFile {
+    /// A few lines on the board: size, stackup, parts by prefix and routing totals
+    pub fn summary(&self) -> String {
+        let stats = self.stats();
+        let mut out = format!("{} ({})", self, self.generator);
+
+        let outline: Vec<Point> = self.board_outline().into_iter().flat_map(|p| p.outline).collect();
+        if !outline.is_empty() {
+            let (min, max) = bounds(&outline);
+            let _ = write!(out, "\n  size: {:.2} x {:.2} mm", (max.x - min.x).as_mm(), (max.y - min.y).as_mm());
+            if let Some(thickness) = self.board_thickness {
+                let _ = write!(out, ", {} mm thick", thickness.as_mm());
+            }
+        }
+        if !stats.components_by_prefix.is_empty() {
+            let mut prefixes: Vec<_> = stats.components_by_prefix.iter().collect();
+            prefixes.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            let parts: Vec<String> = prefixes
+                .iter()
+                .map(|(prefix, n)| format!("{}×{}", if prefix.is_empty() { "?" } else { prefix }, n))
+                .collect();
+            let _ = write!(out, "\n  footprints: {}", parts.join(" "));
+        }
+        let _ = write!(out, "\n  routing: {:.1} mm of track", stats.track_length);
+        if let Some(width) = stats.min_track_width {
+            let _ = write!(out, ", min width {} mm", width);
+        }
+        if let Some(drill) = stats.min_drill {
+            let _ = write!(out, ", min drill {} mm", drill);
+        }
+        if !self.zones.is_empty() {
+            let _ = write!(out, "\n  zones: {}", self.zones.len());
+        }
+        out
+    }
+}
+
+/// `Name (U, 8 pins, 2 units)`, marking power and derived symbols
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(parent) = &self.extends {
+            write!(f, " extends {}", parent)?;
+        }
+        write!(f, " (")?;
+        if self.power {
+            write!(f, "power, ")?;
+        } else if let Some(reference) = self.property("Reference") {
+            write!(f, "{}, ", reference)?;
+        }
+        write!(f, "{}", count(self.pins.len(), "pin"))?;
+        if self.unit_count > 1 {
+            write!(f, ", {} units", self.unit_count)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Symbol {
+    /// The `Display` line, the description and one line per pin in number order
+    pub fn summary(&self) -> String {
+        let mut out = self.to_string();
+        if let Some(description) = self.description().filter(|d| !d.is_empty()) {
+            let _ = write!(out, "\n  {}", description);
+        }
+        let mut pins: Vec<_> = self.pins.iter().collect();
+        pins.sort_by(|a, b| natural_order(&a.number, &b.number));
+        for pin in pins {
+            let _ = write!(out, "\n  {:>4} {} ({})", pin.number, pin.name, pin.pin_type);
+            if self.unit_count > 1 && pin.unit > 0 {
+                let _ = write!(out, " unit {}", pin.unit);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcb::parse_pcb;
+    use crate::symbol::parse_symbol_library;
+
+    #[test]
+    fn test_board_summary() {
+        let pcb = parse_pcb(include_str!("../../assets/fpga.kicad_pcb")).unwrap();
+        let summary = pcb.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines[0], format!("{} ({})", pcb, pcb.generator));
+        assert!(lines[1].starts_with("  size: "));
+        assert!(summary.contains("  footprints: "));
+        assert!(lines.len() < 8);
+
+        let footprint = pcb.footprints.iter().find(|f| f.pads.len() > 1).unwrap();
+        let summary = footprint.summary();
+        assert!(summary.starts_with(&footprint.to_string()));
+        assert!(summary.lines().last().unwrap().starts_with("  pads: "));
+    }
+
+    #[test]
+    fn test_symbol_display() {
+        let lib = parse_symbol_library("demo", r##"(kicad_symbol_lib
+  (symbol "LM358" (property "Reference" "U") (property "Description" "Dual op-amp")
+    (symbol "LM358_1_1" (pin output line (at 0 0 0) (length 2.54) (name "OUT") (number "1")))
+    (symbol "LM358_2_1" (pin output line (at 0 0 0) (length 2.54) (name "OUT") (number "7"))))
+  (symbol "GND" (power) (property "Reference" "#PWR")
+    (symbol "GND_1_1" (pin power_in line (at 0 0 0) (length 0) (name "GND") (number "1")))))"##).unwrap();
+
+        assert_eq!(lib.symbols[0].to_string(), "LM358 (U, 2 pins, 2 units)");
+        assert_eq!(lib.symbols[1].to_string(), "GND (power, 1 pin)");
+        let simple = crate::symbol::types::Symbol {
+            name: "LM358".to_string(),
+            description: "Dual op-amp".to_string(),
+            properties: Default::default(),
+        };
+        assert_eq!(simple.to_string(), "LM358 - Dual op-amp");
+        assert_eq!(
+            lib.symbols[0].summary(),
+            "LM358 (U, 2 pins, 2 units)\n  Dual op-amp\n     1 OUT (output) unit 1\n     7 OUT (output) unit 2"
+        );
+    }
+}
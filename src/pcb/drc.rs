@@ -0,0 +1,621 @@
+//! Design rule checks
+//!
+//! [`check_clearance`] checks the copper-to-copper clearance between pads, tracks,
+//! arcs and vias of different nets on each layer. The required clearance is the
+//! larger of the configured minimum and, when enabled, the clearances of both
+//! items' net classes.
+//!
+//! [`run_drc`] runs the clearance check together with minimum track width, via
+//! size, hole size and annular ring rules, shorts and unconnected nets, as set up
+//! in [`DrcRules`], and reports each [`Violation`] with a location and severity.
+//!
+//! ```rust
+//! use kiparse::pcb::{drc::{check_clearance, DrcConfig}, parse_pcb};
+//! use kiparse::units::Length;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "A") (net 2 "B")
+//!   (segment (start 0 0) (end 10 0) (width 0.2) (layer "F.Cu") (net 1))
+//!   (segment (start 0 0.3) (end 10 0.3) (width 0.2) (layer "F.Cu") (net 2))
+//! )"#)?;
+//! let config = DrcConfig { min_clearance: Length::mm(0.15), ..Default::default() };
+//! let violations = check_clearance(&pcb, &config);
+//!
+//! assert_eq!(violations.len(), 1);
+//! assert!((violations[0].actual.as_mm() - 0.1).abs() < 1e-9);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use super::connectivity::Connectivity;
+use super::copper::{copper_shapes, CopperItem, EPSILON};
+use super::types::{PcbFile, Point};
+use crate::units::Length;
+
+/// Clearance check settings
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrcConfig {
+    /// Minimum copper clearance applied to every pair, e.g. the fab limit
+    pub min_clearance: Length,
+    /// Also enforce the clearance of each item's net class (or the `Default` class)
+    pub use_net_classes: bool,
+}
+
+impl Default for DrcConfig {
+    fn default() -> Self {
+        Self {
+            min_clearance: Length::ZERO,
+            use_net_classes: true,
+        }
+    }
+}
+
+/// Two copper items closer than allowed
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClearanceViolation {
+    pub a: CopperItem,
+    pub b: CopperItem,
+    pub net_a: Option<String>,
+    pub net_b: Option<String>,
+    pub layer: String,
+    /// Point midway across the gap (inside the overlap for shorts)
+    pub location: Point,
+    /// Measured gap; zero when the copper touches
+    pub actual: Length,
+    pub required: Length,
+}
+
+/// Check copper clearance between items of different nets on every layer
+///
+/// Each pair of items is reported at most once per layer, at its closest point.
+/// Items without a net are checked against everything else.
+pub fn check_clearance(pcb: &PcbFile, config: &DrcConfig) -> Vec<ClearanceViolation> {
+    let shapes = copper_shapes(pcb);
+    let clearances: Vec<Length> = shapes
+        .iter()
+        .map(|s| required_clearance(pcb, config, s.net.as_deref()))
+        .collect();
+    let reach = clearances.iter().copied().fold(Length::ZERO, Length::max);
+    if reach <= Length::ZERO {
+        return Vec::new();
+    }
+
+    // Shapes are bucketed by their bounding box grown by the largest clearance,
+    // so any pair closer than that shares a cell
+    let cell_size = reach.as_mm().max(1.0);
+    let cell = |v: Length| (v.as_mm() / cell_size).floor() as i64;
+    let boxes: Vec<_> = shapes.iter().map(|s| s.shape.bounds()).collect();
+    let mut grid: HashMap<(&str, i64, i64), Vec<usize>> = HashMap::new();
+    let mut found: HashMap<(CopperItem, CopperItem, &str), ClearanceViolation> = HashMap::new();
+
+    for (i, shape) in shapes.iter().enumerate() {
+        let (lo, hi) = &boxes[i];
+        let mut candidates = Vec::new();
+        for cx in cell(lo.x - reach)..=cell(hi.x + reach) {
+            for cy in cell(lo.y - reach)..=cell(hi.y + reach) {
+                if let Some(cell) = grid.get(&(shape.layer.as_str(), cx, cy)) {
+                    candidates.extend_from_slice(cell);
+                }
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        for j in candidates {
+            let other = &shapes[j];
+            if other.item == shape.item
+                || (shape.net.is_some() && other.net == shape.net)
+                || net_tied(pcb, other.item, shape.item)
+            {
+                continue;
+            }
+            let required = clearances[i].max(clearances[j]);
+            let (other_lo, other_hi) = &boxes[j];
+            if lo.x - required > other_hi.x
+                || other_lo.x - required > hi.x
+                || lo.y - required > other_hi.y
+                || other_lo.y - required > hi.y
+            {
+                continue;
+            }
+            let (actual, location) = other.shape.closest(&shape.shape);
+            let actual = Length::mm(actual);
+            if actual.as_mm() >= required.as_mm() - EPSILON {
+                continue;
+            }
+            let key = (other.item.min(shape.item), other.item.max(shape.item), shape.layer.as_str());
+            let worse = found.get(&key).map_or(true, |v| actual < v.actual);
+            if worse {
+                found.insert(
+                    key,
+                    ClearanceViolation {
+                        a: key.0,
+                        b: key.1,
+                        net_a: if key.0 == other.item { other.net.clone() } else { shape.net.clone() },
+                        net_b: if key.0 == other.item { shape.net.clone() } else { other.net.clone() },
+                        layer: shape.layer.clone(),
+                        location,
+                        actual,
+                        required,
+                    },
+                );
+            }
+        }
+
+        for cx in cell(lo.x)..=cell(hi.x) {
+            for cy in cell(lo.y)..=cell(hi.y) {
+                grid.entry((shape.layer.as_str(), cx, cy)).or_default().push(i);
+            }
+        }
+    }
+
+    let mut violations: Vec<ClearanceViolation> = found.into_values().collect();
+    violations.sort_by(|a, b| (a.a, a.b, &a.layer).cmp(&(b.a, b.b, &b.layer)));
+    violations
+}
+
+/// How a rule's violations are reported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Severity {
+    /// Not checked
+    Ignore,
+    Warning,
+    Error,
+}
+
+/// Rule a [`Violation`] breaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DrcRule {
+    Clearance,
+    TrackWidth,
+    ViaDiameter,
+    ViaDrill,
+    HoleSize,
+    AnnularRing,
+    Short,
+    Unconnected,
+}
+
+impl DrcRule {
+    /// Name used in rules files and reports, e.g. `annular_ring`
+    pub fn name(self) -> &'static str {
+        match self {
+            DrcRule::Clearance => "clearance",
+            DrcRule::TrackWidth => "track_width",
+            DrcRule::ViaDiameter => "via_diameter",
+            DrcRule::ViaDrill => "via_drill",
+            DrcRule::HoleSize => "hole_size",
+            DrcRule::AnnularRing => "annular_ring",
+            DrcRule::Short => "short",
+            DrcRule::Unconnected => "unconnected",
+        }
+    }
+}
+
+/// Limits and severities for [`run_drc`]
+///
+/// Deserializes from a rules file such as this TOML; every field is optional and
+/// unset limits are not checked, but unknown keys are rejected so that a
+/// misspelled limit is not silently skipped:
+///
+/// ```toml
+/// min_clearance = 0.15
+/// use_net_classes = true
+/// min_track_width = 0.127
+/// min_via_diameter = 0.45
+/// min_via_drill = 0.2
+/// min_hole = 0.3
+/// min_annular_ring = 0.1
+///
+/// [severity]
+/// annular_ring = "warning"
+/// unconnected = "ignore"
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct DrcRules {
+    /// Minimum copper clearance
+    pub min_clearance: Length,
+    /// Also enforce net class clearances
+    pub use_net_classes: bool,
+    pub min_track_width: Option<Length>,
+    /// Minimum via pad diameter
+    pub min_via_diameter: Option<Length>,
+    pub min_via_drill: Option<Length>,
+    /// Minimum drill of pad holes
+    pub min_hole: Option<Length>,
+    /// Minimum copper ring around via and pad holes
+    pub min_annular_ring: Option<Length>,
+    pub severity: RuleSeverities,
+}
+
+/// Severity per rule; rules not set are errors
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct RuleSeverities {
+    pub clearance: Option<Severity>,
+    pub track_width: Option<Severity>,
+    pub via_diameter: Option<Severity>,
+    pub via_drill: Option<Severity>,
+    pub hole_size: Option<Severity>,
+    pub annular_ring: Option<Severity>,
+    pub short: Option<Severity>,
+    pub unconnected: Option<Severity>,
+}
+
+impl Default for DrcRules {
+    fn default() -> Self {
+        Self {
+            min_clearance: Length::ZERO,
+            use_net_classes: true,
+            min_track_width: None,
+            min_via_diameter: None,
+            min_via_drill: None,
+            min_hole: None,
+            min_annular_ring: None,
+            severity: RuleSeverities::default(),
+        }
+    }
+}
+
+impl DrcRules {
+    pub fn severity(&self, rule: DrcRule) -> Severity {
+        let s = &self.severity;
+        match rule {
+            DrcRule::Clearance => s.clearance,
+            DrcRule::TrackWidth => s.track_width,
+            DrcRule::ViaDiameter => s.via_diameter,
+            DrcRule::ViaDrill => s.via_drill,
+            DrcRule::HoleSize => s.hole_size,
+            DrcRule::AnnularRing => s.annular_ring,
+            DrcRule::Short => s.short,
+            DrcRule::Unconnected => s.unconnected,
+        }
+        .unwrap_or(Severity::Error)
+    }
+}
+
+/// One design rule violation
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Violation {
+    pub rule: DrcRule,
+    pub severity: Severity,
+    pub message: String,
+    /// Where to look on the board
+    pub location: Point,
+    pub layer: Option<String>,
+}
+
+/// Run every rule not set to [`Severity::Ignore`]
+///
+/// Violations are sorted errors first, then by rule and location.
+pub fn run_drc(pcb: &PcbFile, rules: &DrcRules) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut report = |rule: DrcRule, message: String, location: Point, layer: Option<&str>| {
+        let severity = rules.severity(rule);
+        if severity != Severity::Ignore {
+            violations.push(Violation {
+                rule,
+                severity,
+                message,
+                location,
+                layer: layer.map(str::to_string),
+            });
+        }
+    };
+    // Message for a value under its limit, or None when the rule is unset or met
+    let below = |what: &dyn Fn() -> String, value: Length, limit: Option<Length>| match limit {
+        Some(limit) if value.as_mm() < limit.as_mm() - EPSILON => {
+            Some(format!("{} {:.3} mm < {:.3} mm", what(), value.as_mm(), limit.as_mm()))
+        }
+        _ => None,
+    };
+
+    if rules.severity(DrcRule::Clearance) != Severity::Ignore {
+        let config = DrcConfig {
+            min_clearance: rules.min_clearance,
+            use_net_classes: rules.use_net_classes,
+        };
+        for v in check_clearance(pcb, &config) {
+            let message = format!(
+                "Clearance {:.3} mm < {:.3} mm between {} and {}",
+                v.actual.as_mm(),
+                v.required.as_mm(),
+                describe(pcb, v.a, v.net_a.as_deref()),
+                describe(pcb, v.b, v.net_b.as_deref())
+            );
+            report(DrcRule::Clearance, message, v.location, Some(&v.layer));
+        }
+    }
+
+    for track in &pcb.tracks {
+        if let Some(message) = below(&|| "Track width".to_string(), track.width, rules.min_track_width) {
+            let middle = Point {
+                x: (track.start.x + track.end.x) / 2.0,
+                y: (track.start.y + track.end.y) / 2.0,
+            };
+            report(DrcRule::TrackWidth, message, middle, Some(&track.layer));
+        }
+    }
+    for arc in &pcb.arcs {
+        if let Some(message) = below(&|| "Arc width".to_string(), arc.width, rules.min_track_width) {
+            report(DrcRule::TrackWidth, message, arc.mid.clone(), Some(&arc.layer));
+        }
+    }
+
+    for via in &pcb.vias {
+        let at = || via.position.clone();
+        if let Some(message) = below(&|| "Via diameter".to_string(), via.size, rules.min_via_diameter) {
+            report(DrcRule::ViaDiameter, message, at(), None);
+        }
+        if let Some(message) = below(&|| "Via drill".to_string(), via.drill, rules.min_via_drill) {
+            report(DrcRule::ViaDrill, message, at(), None);
+        }
+        let ring = (via.size - via.drill) / 2.0;
+        if let Some(message) = below(&|| "Via annular ring".to_string(), ring, rules.min_annular_ring) {
+            report(DrcRule::AnnularRing, message, at(), None);
+        }
+    }
+
+    for footprint in &pcb.footprints {
+        let reference = footprint.property("Reference").unwrap_or_default();
+        for pad in &footprint.pads {
+            let Some(drill) = pad.drill else { continue };
+            let name = |what: &str| format!("Pad {}.{} {}", reference, pad.number, what);
+            if let Some(message) = below(&|| name("hole"), drill, rules.min_hole) {
+                report(DrcRule::HoleSize, message, footprint.pad_position(pad), None);
+            }
+            // Only plated holes have a ring; a slot is measured across its width
+            let hole = pad.drill_oval.as_ref().map_or(drill, |o| o.x.min(o.y));
+            let ring = (pad.size.x.min(pad.size.y) - hole) / 2.0;
+            if pad.pad_type != "thru_hole" {
+                continue;
+            }
+            if let Some(message) = below(&|| name("annular ring"), ring, rules.min_annular_ring) {
+                report(DrcRule::AnnularRing, message, footprint.pad_position(pad), None);
+            }
+        }
+    }
+
+    let check_short = rules.severity(DrcRule::Short) != Severity::Ignore;
+    let check_open = rules.severity(DrcRule::Unconnected) != Severity::Ignore;
+    if check_short || check_open {
+        let connectivity = Connectivity::build(pcb);
+        if check_short {
+            for cluster in connectivity.shorts() {
+                let location = cluster
+                    .items
+                    .first()
+                    .map_or(Point::default(), |&item| item_location(pcb, item));
+                let message = format!("Short between nets {}", cluster.nets.join(", "));
+                report(DrcRule::Short, message, location, None);
+            }
+        }
+        if check_open {
+            for open in connectivity.opens() {
+                let islands: Vec<String> = open
+                    .islands
+                    .iter()
+                    .map(|island| {
+                        let pads: Vec<String> = island.iter().map(|p| format!("{}.{}", p.reference, p.pad)).collect();
+                        format!("[{}]", pads.join(" "))
+                    })
+                    .collect();
+                // Point at the first pad of the second island, the first one left unrouted
+                let location = open
+                    .islands
+                    .get(1)
+                    .and_then(|island| island.first())
+                    .and_then(|p| pad_location(pcb, &p.reference, &p.pad))
+                    .unwrap_or_default();
+                let message = format!("Net {} is split into {} islands: {}", open.net, open.islands.len(), islands.join(" "));
+                report(DrcRule::Unconnected, message, location, None);
+            }
+        }
+    }
+
+    violations.sort_by(|a, b| {
+        (b.severity, a.rule)
+            .cmp(&(a.severity, b.rule))
+            .then(a.location.x.total_cmp(&b.location.x))
+            .then(a.location.y.total_cmp(&b.location.y))
+    });
+    violations
+}
+
+/// Whether two items are pads of the same net-tie group, which touch by design
+fn net_tied(pcb: &PcbFile, a: CopperItem, b: CopperItem) -> bool {
+    match (a, b) {
+        (CopperItem::Pad { footprint, pad }, CopperItem::Pad { footprint: other, pad: other_pad }) if footprint == other => {
+            let footprint = &pcb.footprints[footprint];
+            footprint
+                .net_tie_group(&footprint.pads[pad].number)
+                .map_or(false, |group| group.contains(&footprint.pads[other_pad].number))
+        }
+        _ => false,
+    }
+}
+
+/// Human-readable name of a copper item, with its net
+fn describe(pcb: &PcbFile, item: CopperItem, net: Option<&str>) -> String {
+    let name = match item {
+        CopperItem::Pad { footprint, pad } => {
+            let fp = &pcb.footprints[footprint];
+            format!("pad {}.{}", fp.property("Reference").unwrap_or_default(), fp.pads[pad].number)
+        }
+        CopperItem::Track(_) => "track".to_string(),
+        CopperItem::Arc(_) => "arc".to_string(),
+        CopperItem::Via(_) => "via".to_string(),
+        CopperItem::Zone { .. } => "zone".to_string(),
+    };
+    match net {
+        Some(net) => format!("{} ({})", name, net),
+        None => format!("{} (no net)", name),
+    }
+}
+
+/// A representative point of a copper item
+fn item_location(pcb: &PcbFile, item: CopperItem) -> Point {
+    match item {
+        CopperItem::Pad { footprint, pad } => {
+            let fp = &pcb.footprints[footprint];
+            fp.pad_position(&fp.pads[pad])
+        }
+        CopperItem::Track(i) => pcb.tracks[i].start.clone(),
+        CopperItem::Arc(i) => pcb.arcs[i].mid.clone(),
+        CopperItem::Via(i) => pcb.vias[i].position.clone(),
+        CopperItem::Zone { zone, fill } => pcb.zones[zone].filled_polygons[fill]
+            .points
+            .first()
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+fn pad_location(pcb: &PcbFile, reference: &str, number: &str) -> Option<Point> {
+    let footprint = pcb.footprint_by_ref(reference)?;
+    let pad = footprint.pads.iter().find(|p| p.number == number)?;
+    Some(footprint.pad_position(pad))
+}
+
+/// Clearance an item of `net` asks for
+fn required_clearance(pcb: &PcbFile, config: &DrcConfig, net: Option<&str>) -> Length {
+    if !config.use_net_classes {
+        return config.min_clearance;
+    }
+    let class = net
+        .and_then(|n| pcb.net_class_of(n))
+        .or_else(|| pcb.net_classes.get("Default"));
+    class
+        .and_then(|c| c.clearance)
+        .unwrap_or_default()
+        .max(config.min_clearance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+    use crate::pcb::types::NetClass;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "A") (net 2 "B") (net 3 "HV")
+  (footprint "R" (layer "F.Cu") (at 10 10) (property "Reference" "R1")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu") (net 1 "A"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu") (net 2 "B")))
+  (segment (start 11.5 9.5) (end 11.5 5) (width 0.2) (layer "F.Cu") (net 1))
+  (segment (start 0 20) (end 10 20) (width 0.2) (layer "F.Cu") (net 3))
+  (arc (start 0 20.8) (mid 5 20.3) (end 10 20.8) (width 0.2) (layer "F.Cu") (net 2))
+  (segment (start 0 20) (end 10 20) (width 0.2) (layer "B.Cu") (net 2))
+  (via (at 20 20) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+  (segment (start 20.5 20) (end 25 20) (width 0.2) (layer "B.Cu") (net 2))
+)"#;
+
+    #[test]
+    fn test_check_clearance() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let config = DrcConfig { min_clearance: Length::mm(0.2), use_net_classes: false };
+        let violations = check_clearance(&pcb, &config);
+
+        // Track of A running into the edge of pad B
+        let pad = &violations[0];
+        assert_eq!((pad.a, pad.b), (CopperItem::Pad { footprint: 0, pad: 1 }, CopperItem::Track(0)));
+        assert_eq!((pad.net_a.as_deref(), pad.net_b.as_deref()), (Some("B"), Some("A")));
+        assert_eq!(pad.actual, Length::ZERO);
+        assert_eq!(pad.layer, "F.Cu");
+
+        // Arc dipping to 0.1 mm from the HV track; tracks stacked on other layers are fine
+        let arc = violations.iter().find(|v| v.b == CopperItem::Arc(0)).unwrap();
+        assert_eq!(arc.a, CopperItem::Track(1));
+        // Arcs are measured on their 5 degree chords, which sit slightly inside the curve
+        assert!((arc.actual.as_mm() - 0.1).abs() < 0.03);
+        assert!((arc.location.y.as_mm() - 20.15).abs() < 0.02);
+
+        // Via ring (r 0.3) to track end cap (r 0.1) on B.Cu only
+        let via: Vec<_> = violations.iter().filter(|v| v.b == CopperItem::Via(0)).collect();
+        assert_eq!(via.len(), 1);
+        assert_eq!(via[0].layer, "B.Cu");
+        assert!((via[0].actual.as_mm() - 0.1).abs() < 1e-9);
+        assert_eq!(violations.len(), 3);
+    }
+
+    #[test]
+    fn test_net_class_clearance() {
+        let mut pcb = parse_pcb(BOARD).unwrap();
+        let config = DrcConfig::default();
+        assert!(check_clearance(&pcb, &config).is_empty());
+
+        let class = |name: &str, clearance: f64, nets: &[&str]| NetClass {
+            name: name.to_string(),
+            description: String::new(),
+            clearance: Some(Length::mm(clearance)),
+            track_width: None,
+            via_diameter: None,
+            via_drill: None,
+            uvia_diameter: None,
+            uvia_drill: None,
+            diff_pair_width: None,
+            diff_pair_gap: None,
+            nets: nets.iter().map(|n| n.to_string()).collect(),
+        };
+        pcb.net_classes.insert("Default".to_string(), class("Default", 0.05, &[]));
+        pcb.net_classes.insert("HV".to_string(), class("HV", 0.5, &["HV"]));
+
+        let violations = check_clearance(&pcb, &config);
+        assert_eq!(violations.len(), 2);
+        assert!(violations.iter().any(|v| v.b == CopperItem::Arc(0) && v.required == Length::mm(0.5)));
+        assert!(violations.iter().any(|v| v.actual == Length::ZERO && v.required == Length::mm(0.05)));
+    }
+
+    #[test]
+    fn test_run_drc() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let mut rules = DrcRules {
+            min_clearance: Length::mm(0.2),
+            min_track_width: Some(Length::mm(0.25)),
+            min_via_drill: Some(Length::mm(0.3)),
+            min_annular_ring: Some(Length::mm(0.2)),
+            ..DrcRules::default()
+        };
+        rules.severity.unconnected = Some(Severity::Ignore);
+        rules.severity.track_width = Some(Severity::Warning);
+        let violations = run_drc(&pcb, &rules);
+        let count = |rule: DrcRule| violations.iter().filter(|v| v.rule == rule).count();
+
+        assert_eq!(count(DrcRule::Clearance), 3);
+        assert_eq!(count(DrcRule::TrackWidth), 5);
+        // A 0.3 mm drill meets the limit; its 0.15 mm ring does not
+        assert_eq!(count(DrcRule::ViaDrill), 0);
+        assert_eq!(count(DrcRule::AnnularRing), 1);
+        assert_eq!(count(DrcRule::Short), 1);
+        assert_eq!(count(DrcRule::Unconnected), 0);
+        assert_eq!(violations.last().unwrap().severity, Severity::Warning);
+
+        let pad = violations.iter().find(|v| v.message.contains("pad R1.2 (B)")).unwrap();
+        assert_eq!(pad.message, "Clearance 0.000 mm < 0.200 mm between pad R1.2 (B) and track (A)");
+        assert_eq!(pad.layer.as_deref(), Some("F.Cu"));
+    }
+
+    #[test]
+    fn test_rules_file() {
+        let rules: DrcRules = serde_json::from_str(r#"{"min_hole": 0.3, "severity": {"short": "warning"}}"#).unwrap();
+        assert_eq!(rules.min_hole, Some(Length::mm(0.3)));
+        assert!(rules.use_net_classes);
+        assert_eq!(rules.severity(DrcRule::Short), Severity::Warning);
+        assert_eq!(rules.severity(DrcRule::Clearance), Severity::Error);
+        assert!(serde_json::from_str::<DrcRules>(r#"{"severity": {"shorts": "warning"}}"#).is_err());
+        // `clearance` for `min_clearance` must not pass as an empty rule set
+        assert!(serde_json::from_str::<DrcRules>(r#"{"clearance": 0.5, "track_width": 0.3}"#).is_err());
+    }
+}
@@ -0,0 +1,384 @@
+//! Planar geometry helpers shared by the PCB parser and analysis passes
+//!
+//! All coordinates are in millimeters in KiCad's board coordinate system
+//! (x to the right, y downwards); angles are in degrees.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use super::types::{Arc, BoardPolygon, Graphic, Point};
+#[cfg(not(any(feature = "std", feature = "serde")))]
+use crate::float::Float;
+use crate::units::Length;
+
+/// Euclidean distance between two points
+pub fn distance(a: &Point, b: &Point) -> f64 {
+    ((b.x - a.x).as_mm().powi(2) + (b.y - a.y).as_mm().powi(2)).sqrt()
+}
+
+/// Angle in degrees of `p` around `center`
+pub fn angle(center: &Point, p: &Point) -> f64 {
+    (p.y - center.y).as_mm().atan2((p.x - center.x).as_mm()).to_degrees()
+}
+
+/// Circle through three points, expressed as an [`Arc`] sweeping from start through mid to end
+///
+/// Angles are in degrees with `end_angle > start_angle`; for arcs running the other
+/// way start and end are swapped so the swept region stays the same.
+pub fn arc_from_points(start: &Point, mid: &Point, end: &Point) -> Option<Arc> {
+    let (sx, sy, mx, my, ex, ey) =
+        (start.x.as_mm(), start.y.as_mm(), mid.x.as_mm(), mid.y.as_mm(), end.x.as_mm(), end.y.as_mm());
+    let d = 2.0 * (sx * (my - ey) + mx * (ey - sy) + ex * (sy - my));
+    if d.abs() < 1e-12 {
+        return None;
+    }
+    let s2 = sx.powi(2) + sy.powi(2);
+    let m2 = mx.powi(2) + my.powi(2);
+    let e2 = ex.powi(2) + ey.powi(2);
+    let center = Point::new(
+        (s2 * (my - ey) + m2 * (ey - sy) + e2 * (sy - my)) / d,
+        (s2 * (ex - mx) + m2 * (sx - ex) + e2 * (mx - sx)) / d,
+    );
+
+    let sweep = |from: f64, to: f64| (to - from).rem_euclid(360.0);
+    let a_start = angle(&center, start);
+    let a_mid = angle(&center, mid);
+    let a_end = angle(&center, end);
+    let (from, to) = if sweep(a_start, a_mid) <= sweep(a_start, a_end) {
+        (a_start, a_end)
+    } else {
+        (a_end, a_start)
+    };
+
+    Some(Arc {
+        radius: Length::mm(distance(&center, start)),
+        center,
+        start_angle: from,
+        end_angle: from + sweep(from, to),
+    })
+}
+
+/// Axis-aligned corners (min, max) enclosing `points`
+pub fn bounds(points: &[Point]) -> (Point, Point) {
+    if points.is_empty() {
+        return (Point::default(), Point::default());
+    }
+    let (mut min, mut max) = (points[0].clone(), points[0].clone());
+    for p in points {
+        min.x = min.x.min(p.x);
+        min.y = min.y.min(p.y);
+        max.x = max.x.max(p.x);
+        max.y = max.y.max(p.y);
+    }
+    (min, max)
+}
+
+/// Map a footprint-local point to board coordinates
+///
+/// KiCad rotates counter-clockwise as displayed, which with y pointing down is a
+/// clockwise rotation in the usual mathematical sense.
+pub fn to_board(local: &Point, origin: &Point, rotation: f64) -> Point {
+    let (sin, cos) = rotation.to_radians().sin_cos();
+    Point {
+        x: origin.x + local.x * cos + local.y * sin,
+        y: origin.y - local.x * sin + local.y * cos,
+    }
+}
+
+/// Approximate an arc with a polyline, one segment per `ARC_STEP_DEGREES` of sweep
+pub fn flatten_arc(arc: &Arc) -> Vec<Point> {
+    let sweep = arc.end_angle - arc.start_angle;
+    let steps = ((sweep.abs() / ARC_STEP_DEGREES).ceil() as usize).max(1);
+    (0..=steps)
+        .map(|i| {
+            let a = (arc.start_angle + sweep * i as f64 / steps as f64).to_radians();
+            Point {
+                x: arc.center.x + arc.radius * a.cos(),
+                y: arc.center.y + arc.radius * a.sin(),
+            }
+        })
+        .collect()
+}
+
+/// Angular resolution used when flattening arcs and circles
+pub const ARC_STEP_DEGREES: f64 = 5.0;
+
+/// Point at parameter `t` (0 to 1) on the cubic Bezier curve `p0`..`p3`
+pub fn bezier_point(p0: &Point, p1: &Point, p2: &Point, p3: &Point, t: f64) -> Point {
+    let u = 1.0 - t;
+    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+    Point {
+        x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+        y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+    }
+}
+
+/// Approximate a cubic Bezier curve with a polyline, one segment per
+/// `BEZIER_STEP` of control polygon length and at most 64 segments
+pub fn flatten_bezier(p0: &Point, p1: &Point, p2: &Point, p3: &Point) -> Vec<Point> {
+    let hull = distance(p0, p1) + distance(p1, p2) + distance(p2, p3);
+    let steps = ((hull / BEZIER_STEP).ceil() as usize).clamp(1, 64);
+    (0..=steps)
+        .map(|i| bezier_point(p0, p1, p2, p3, i as f64 / steps as f64))
+        .collect()
+}
+
+/// Length resolution (mm) used when flattening Bezier curves
+pub const BEZIER_STEP: f64 = 0.25;
+
+/// Signed polygon area (shoelace formula); the sign depends on winding order
+pub fn polygon_area(points: &[Point]) -> f64 {
+    let n = points.len();
+    (0..n)
+        .map(|i| {
+            let (a, b) = (&points[i], &points[(i + 1) % n]);
+            a.x.as_mm() * b.y.as_mm() - b.x.as_mm() * a.y.as_mm()
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+/// Outline contribution of a drawing: an open polyline to be chained, or a closed shape
+pub enum OutlinePiece {
+    Open(Vec<Point>),
+    Closed(Vec<Point>),
+}
+
+/// Convert a graphic into outline geometry
+pub fn outline_piece(graphic: &Graphic) -> OutlinePiece {
+    match graphic {
+        Graphic::Line { start, end, .. } => OutlinePiece::Open(vec![start.clone(), end.clone()]),
+        Graphic::Arc { arc, .. } => OutlinePiece::Open(flatten_arc(arc)),
+        Graphic::Circle { center, radius, .. } => {
+            let mut points = flatten_arc(&Arc {
+                center: center.clone(),
+                radius: *radius,
+                start_angle: 0.0,
+                end_angle: 360.0,
+            });
+            points.pop();
+            OutlinePiece::Closed(points)
+        }
+        Graphic::Rectangle { rect, .. } => OutlinePiece::Closed(vec![
+            rect.start.clone(),
+            Point { x: rect.end.x, y: rect.start.y },
+            rect.end.clone(),
+            Point { x: rect.start.x, y: rect.end.y },
+        ]),
+        Graphic::Polygon { points, .. } => OutlinePiece::Closed(points.clone()),
+        Graphic::Bezier { start, control1, control2, end, .. } => {
+            OutlinePiece::Open(flatten_bezier(start, control1, control2, end))
+        }
+    }
+}
+
+/// Endpoint matching tolerance (mm) when chaining outline segments
+pub const CHAIN_TOLERANCE: f64 = 1e-3;
+
+/// Chain outline pieces into closed polygons and sort them into outlines and cutouts
+///
+/// Open polylines are joined end to end (reversing where needed); chains that never
+/// close are dropped. A polygon nested inside an odd number of others is a cutout of
+/// the smallest outline containing it.
+pub fn reconstruct_polygons(pieces: Vec<OutlinePiece>) -> Vec<BoardPolygon> {
+    let mut closed = Vec::new();
+    let mut open = Vec::new();
+    for piece in pieces {
+        match piece {
+            OutlinePiece::Closed(points) if points.len() >= 3 => closed.push(points),
+            OutlinePiece::Closed(_) => {}
+            OutlinePiece::Open(points) if points.len() >= 2 => open.push(points),
+            OutlinePiece::Open(_) => {}
+        }
+    }
+
+    let near = |a: &Point, b: &Point| distance(a, b) <= CHAIN_TOLERANCE;
+    while let Some(mut chain) = open.pop() {
+        loop {
+            if chain.len() > 2 && near(&chain[0], &chain[chain.len() - 1]) {
+                chain.pop();
+                closed.push(chain);
+                break;
+            }
+            let tail = chain[chain.len() - 1].clone();
+            let next = open.iter().position(|p| near(&p[0], &tail) || near(&p[p.len() - 1], &tail));
+            match next {
+                Some(index) => {
+                    let mut piece = open.swap_remove(index);
+                    if !near(&piece[0], &tail) {
+                        piece.reverse();
+                    }
+                    chain.extend(piece.into_iter().skip(1));
+                }
+                None => break,
+            }
+        }
+    }
+
+    // Largest first so every polygon's containers are classified before it
+    closed.sort_by(|a, b| polygon_area(b).abs().total_cmp(&polygon_area(a).abs()));
+    let mut result: Vec<BoardPolygon> = Vec::new();
+    let mut containers: Vec<(Vec<Point>, Option<usize>)> = Vec::new();
+    for polygon in closed {
+        let inside: Vec<&(Vec<Point>, Option<usize>)> = containers
+            .iter()
+            .filter(|(outer, _)| point_in_polygon(&polygon[0], outer))
+            .collect();
+        let owner = if inside.len() % 2 == 1 {
+            inside.iter().rev().find_map(|(_, outline)| *outline)
+        } else {
+            None
+        };
+        match owner {
+            Some(index) => {
+                result[index].cutouts.push(polygon.clone());
+                containers.push((polygon, None));
+            }
+            None => {
+                result.push(BoardPolygon {
+                    outline: polygon.clone(),
+                    cutouts: Vec::new(),
+                });
+                containers.push((polygon, Some(result.len() - 1)));
+            }
+        }
+    }
+    result
+}
+
+/// Point of the segment `a`-`b` nearest to `p`
+pub fn closest_on_segment(p: &Point, a: &Point, b: &Point) -> Point {
+    let (dx, dy) = ((b.x - a.x).as_mm(), (b.y - a.y).as_mm());
+    let length2 = dx * dx + dy * dy;
+    if length2 == 0.0 {
+        return a.clone();
+    }
+    let t = (((p.x - a.x).as_mm() * dx + (p.y - a.y).as_mm() * dy) / length2).clamp(0.0, 1.0);
+    Point { x: a.x + Length::mm(t * dx), y: a.y + Length::mm(t * dy) }
+}
+
+/// Shortest distance from `p` to the segment `a`-`b`
+pub fn point_segment_distance(p: &Point, a: &Point, b: &Point) -> f64 {
+    distance(p, &closest_on_segment(p, a, b))
+}
+
+/// Nearest pair of points between segments `a`-`b` and `c`-`d`
+///
+/// When the segments cross, both points are the crossing point.
+pub fn segment_closest_points(a: &Point, b: &Point, c: &Point, d: &Point) -> (Point, Point) {
+    let cross = |o: &Point, p: &Point, q: &Point| {
+        ((p.x - o.x) * (q.y - o.y).as_mm() - (p.y - o.y) * (q.x - o.x).as_mm()).as_mm()
+    };
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    if ((d1 > 0.0 && d2 < 0.0) || (d1 < 0.0 && d2 > 0.0)) && ((d3 > 0.0 && d4 < 0.0) || (d3 < 0.0 && d4 > 0.0)) {
+        let t = d1 / (d1 - d2);
+        let p = Point { x: a.x + t * (b.x - a.x), y: a.y + t * (b.y - a.y) };
+        return (p.clone(), p);
+    }
+    [
+        (a.clone(), closest_on_segment(a, c, d)),
+        (b.clone(), closest_on_segment(b, c, d)),
+        (closest_on_segment(c, a, b), c.clone()),
+        (closest_on_segment(d, a, b), d.clone()),
+    ]
+    .into_iter()
+    .min_by(|(p, q), (r, s)| distance(p, q).total_cmp(&distance(r, s)))
+    .unwrap()
+}
+
+/// Shortest distance between segments `a`-`b` and `c`-`d`; zero when they cross
+pub fn segment_distance(a: &Point, b: &Point, c: &Point, d: &Point) -> f64 {
+    let (p, q) = segment_closest_points(a, b, c, d);
+    distance(&p, &q)
+}
+
+/// Whether `p` lies inside the closed polygon `polygon` (even-odd rule)
+pub fn point_in_polygon(p: &Point, polygon: &[Point]) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[j];
+        let (ax, ay, bx, by) = (a.x.as_mm(), a.y.as_mm(), b.x.as_mm(), b.y.as_mm());
+        let (px, py) = (p.x.as_mm(), p.y.as_mm());
+        if (ay > py) != (by > py) && px < (bx - ax) * (py - ay) / (by - ay) + ax {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pt(x: f64, y: f64) -> Point {
+        Point::new(x, y)
+    }
+
+    #[test]
+    fn test_arc_from_points() {
+        let arc = arc_from_points(&pt(10.0, 0.0), &pt(0.0, 10.0), &pt(-10.0, 0.0)).unwrap();
+        assert!((arc.radius.as_mm() - 10.0).abs() < 1e-9);
+        assert!((arc.end_angle - arc.start_angle - 180.0).abs() < 1e-9);
+        assert!(arc_from_points(&pt(0.0, 0.0), &pt(1.0, 1.0), &pt(2.0, 2.0)).is_none());
+    }
+
+    #[test]
+    fn test_to_board() {
+        let p = to_board(&pt(1.0, 0.0), &pt(10.0, 10.0), 90.0);
+        assert!((p.x.as_mm() - 10.0).abs() < 1e-9 && (p.y.as_mm() - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_reconstruct_polygons() {
+        let line = |a: (f64, f64), b: (f64, f64)| OutlinePiece::Open(vec![pt(a.0, a.1), pt(b.0, b.1)]);
+        let pieces = vec![
+            // Square outline with segments out of order and one reversed
+            line((0.0, 0.0), (100.0, 0.0)),
+            line((100.0, 50.0), (0.0, 50.0)),
+            line((100.0, 50.0), (100.0, 0.0)),
+            line((0.0, 50.0), (0.0, 0.0)),
+            // Mounting hole cutout and a dangling segment
+            OutlinePiece::Closed(vec![pt(10.0, 10.0), pt(12.0, 10.0), pt(12.0, 12.0), pt(10.0, 12.0)]),
+            line((200.0, 0.0), (210.0, 0.0)),
+        ];
+        let polygons = reconstruct_polygons(pieces);
+
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].outline.len(), 4);
+        assert_eq!(polygons[0].cutouts.len(), 1);
+        assert!((polygons[0].area() - (5000.0 - 4.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_flatten_bezier() {
+        let (p0, p1, p2, p3) = (pt(0.0, 0.0), pt(0.0, 1.0), pt(1.0, 1.0), pt(1.0, 0.0));
+        assert_eq!(bezier_point(&p0, &p1, &p2, &p3, 0.5), pt(0.5, 0.75));
+        let points = flatten_bezier(&p0, &p1, &p2, &p3);
+        assert_eq!((&points[0], &points[points.len() - 1]), (&p0, &p3));
+        assert!(points.windows(2).all(|w| distance(&w[0], &w[1]) <= BEZIER_STEP));
+        assert_eq!(flatten_bezier(&p3, &p3, &p3, &p3).len(), 2);
+    }
+
+    #[test]
+    fn test_segment_distance() {
+        assert_eq!(point_segment_distance(&pt(5.0, 3.0), &pt(0.0, 0.0), &pt(10.0, 0.0)), 3.0);
+        assert_eq!(point_segment_distance(&pt(13.0, 4.0), &pt(0.0, 0.0), &pt(10.0, 0.0)), 5.0);
+        assert_eq!(segment_distance(&pt(0.0, -1.0), &pt(0.0, 1.0), &pt(-1.0, 0.0), &pt(1.0, 0.0)), 0.0);
+        assert_eq!(segment_distance(&pt(0.0, 0.0), &pt(10.0, 0.0), &pt(0.0, 2.0), &pt(10.0, 2.0)), 2.0);
+        let (p, q) = segment_closest_points(&pt(0.0, 0.0), &pt(4.0, 0.0), &pt(6.0, 1.0), &pt(6.0, 5.0));
+        assert_eq!((p, q), (pt(4.0, 0.0), pt(6.0, 1.0)));
+        let (p, _) = segment_closest_points(&pt(0.0, -1.0), &pt(0.0, 1.0), &pt(-1.0, 0.5), &pt(1.0, 0.5));
+        assert_eq!(p, pt(0.0, 0.5));
+    }
+
+    #[test]
+    fn test_point_in_polygon() {
+        let square = [pt(0.0, 0.0), pt(10.0, 0.0), pt(10.0, 10.0), pt(0.0, 10.0)];
+        assert!(point_in_polygon(&pt(5.0, 5.0), &square));
+        assert!(!point_in_polygon(&pt(15.0, 5.0), &square));
+        assert!(!point_in_polygon(&pt(5.0, 5.0), &[]));
+    }
+}
@@ -1,6 +1,9 @@
 //! PCB file parsing module for KiCad .kicad_pcb files
 //! 
-//! This module provides layer extraction from KiCad PCB files.
+//! This module provides layer extraction from KiCad PCB files, regex-based
+//! element extraction with [`DetailParser`], and typed parsing of the whole
+//! board with [`PcbParser`]. Boards can also be created with [`PcbBuilder`] and
+//! written back out with [`write_pcb`].
 //! 
 //! ## Usage Example
 //! 
@@ -24,8 +27,60 @@
 //! ```
 
 pub mod types;
+#[cfg(feature = "std")]
 pub mod simple_parser;
+#[cfg(all(feature = "std", feature = "regex"))]
 pub mod detail_parser;
+pub mod pcb_parser;
+pub mod geometry;
+#[cfg(feature = "std")]
+pub mod copper;
+#[cfg(feature = "std")]
+pub mod connectivity;
+#[cfg(feature = "std")]
+pub mod drc;
+#[cfg(feature = "std")]
+pub mod routing;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(all(feature = "std", feature = "regex"))]
+pub mod annotate;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod writer;
+#[cfg(feature = "std")]
+pub mod visit;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "std")]
+pub mod spatial;
+#[cfg(feature = "std")]
+pub mod uuid_index;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+mod display;
+
+// Re-export commonly used items
+pub use types::*;
+#[cfg(feature = "std")]
+pub use simple_parser::parse_layers_only;
+#[cfg(all(feature = "std", feature = "regex"))]
+pub use detail_parser::DetailParser;
+pub use pcb_parser::{parse_footprint, parse_pcb, ParserConfig, PcbParser};
+#[cfg(feature = "std")]
+pub use builder::{FootprintBuilder, PadBuilder, PcbBuilder};
+#[cfg(feature = "std")]
+pub use writer::write_pcb;
+#[cfg(feature = "std")]
+pub use net::NetView;
+#[cfg(feature = "std")]
+pub use spatial::SpatialIndex;
+#[cfg(feature = "std")]
+pub use uuid_index::UuidIndex;
+#[cfg(feature = "std")]
+pub use diff::{diff_boards, BoardDiff};
 
 #[cfg(test)]
 mod tests {
@@ -87,9 +142,9 @@ mod tests {
 
     #[test]
     fn test_point_creation() {
-        let point = Point { x: 10.5, y: -20.3 };
-        assert_eq!(point.x, 10.5);
-        assert_eq!(point.y, -20.3);
+        let point = Point::new(10.5, -20.3);
+        assert_eq!(point.x.as_mm(), 10.5);
+        assert_eq!(point.y.as_mm(), -20.3);
     }
 
     #[test]
@@ -107,8 +162,3 @@ mod tests {
         assert_eq!(layer.user_name, None);
     }
 }
-
-// Re-export commonly used items
-pub use types::*;
-pub use simple_parser::parse_layers_only;
-pub use detail_parser::DetailParser;
\ No newline at end of file
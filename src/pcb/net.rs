@@ -0,0 +1,221 @@
+//! Net-centric view of a board
+//!
+//! [`PcbFile::net`] gathers everything on one net — pads with their board
+//! positions, tracks, arcs, vias and zones — and [`PcbFile::nets`] does the same
+//! for every net at once, which is how power-integrity reviews usually walk a
+//! board.
+//!
+//! ```rust
+//! use kiparse::pcb::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND") (net 2 "3V3")
+//!   (footprint "C" (at 10 10) (property "Reference" "C1")
+//!     (pad "1" smd rect (at -0.5 0) (size 0.6 0.6) (layers "F.Cu") (net 2 "3V3"))
+//!     (pad "2" smd rect (at 0.5 0) (size 0.6 0.6) (layers "F.Cu") (net 1 "GND")))
+//!   (segment (start 10.5 10) (end 15 10) (width 0.4) (layer "F.Cu") (net 1))
+//!   (via (at 15 10) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1)))"#)?;
+//!
+//! let gnd = pcb.net("GND").unwrap();
+//! assert_eq!(gnd.pads[0].reference, "C1");
+//! assert_eq!((gnd.tracks.len(), gnd.vias.len()), (1, 1));
+//! assert_eq!(gnd.track_length(), 4.5);
+//! assert_eq!(pcb.nets().map(|n| n.name).collect::<Vec<_>>(), ["GND", "3V3"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use super::types::{BoardPad, NetClass, PcbFile, Track, TrackArc, Via, Zone};
+use crate::bom::natural_order;
+
+/// Everything on one net
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetView<'a> {
+    pub name: &'a str,
+    /// Number in the board's net table; `None` for nets only named on elements
+    pub id: Option<u32>,
+    /// Net class the net is explicitly assigned to
+    pub class: Option<&'a NetClass>,
+    pub pads: Vec<BoardPad<'a>>,
+    pub tracks: Vec<&'a Track>,
+    pub arcs: Vec<&'a TrackArc>,
+    pub vias: Vec<&'a Via>,
+    pub zones: Vec<&'a Zone>,
+}
+
+impl<'a> NetView<'a> {
+    fn new(pcb: &'a PcbFile, name: &'a str) -> Self {
+        NetView {
+            name,
+            id: pcb.net_id(name),
+            class: pcb.net_class_of(name),
+            pads: Vec::new(),
+            tracks: Vec::new(),
+            arcs: Vec::new(),
+            vias: Vec::new(),
+            zones: Vec::new(),
+        }
+    }
+
+    /// Routed centreline length of tracks and arcs in mm, via barrels excluded
+    pub fn track_length(&self) -> f64 {
+        self.tracks.iter().map(|t| t.length()).sum::<f64>() + self.arcs.iter().map(|a| a.length()).sum::<f64>()
+    }
+
+    /// References of the footprints with a pad on the net, in board order without repeats
+    pub fn references(&self) -> Vec<&'a str> {
+        let mut references: Vec<&str> = Vec::new();
+        for pad in &self.pads {
+            if !references.contains(&pad.reference) {
+                references.push(pad.reference);
+            }
+        }
+        references
+    }
+
+    /// Copper layers the net's tracks, arcs and zones are on, sorted
+    pub fn layers(&self) -> Vec<&'a str> {
+        let mut layers: Vec<&str> = self
+            .tracks
+            .iter()
+            .map(|t| t.layer.as_str())
+            .chain(self.arcs.iter().map(|a| a.layer.as_str()))
+            .chain(self.zones.iter().flat_map(|z| z.layers.iter().map(String::as_str)))
+            .collect();
+        layers.sort_unstable();
+        layers.dedup();
+        layers
+    }
+
+    /// Whether nothing on the board uses the net
+    pub fn is_empty(&self) -> bool {
+        self.pads.is_empty() && self.tracks.is_empty() && self.arcs.is_empty() && self.vias.is_empty() && self.zones.is_empty()
+    }
+}
+
+impl PcbFile {
+    /// Pads, tracks, vias and zones on the net `name`
+    ///
+    /// Declared nets are returned even when nothing uses them; unknown names give `None`.
+    pub fn net<'a>(&'a self, name: &str) -> Option<NetView<'a>> {
+        let name: &'a str = match self.nets.values().find(|n| *n == name) {
+            Some(declared) => declared,
+            None => self.net_names_in_use().find(|n| *n == name)?,
+        };
+        if name.is_empty() {
+            return None;
+        }
+        let on_net = |net: &Option<String>| net.as_deref() == Some(name);
+        let mut view = NetView::new(self, name);
+        view.pads = self.pads_absolute().filter(|p| on_net(&p.pad.net)).collect();
+        view.tracks = self.tracks.iter().filter(|t| on_net(&t.net)).collect();
+        view.arcs = self.arcs.iter().filter(|a| on_net(&a.net)).collect();
+        view.vias = self.vias.iter().filter(|v| on_net(&v.net)).collect();
+        view.zones = self.zones.iter().filter(|z| on_net(&z.net)).collect();
+        Some(view)
+    }
+
+    /// Every named net in net table order, then nets used but not declared by name
+    pub fn nets(&self) -> impl Iterator<Item = NetView<'_>> {
+        let mut views: HashMap<&str, NetView> = HashMap::new();
+        for pad in self.pads_absolute() {
+            if let Some(v) = view_of(&mut views, self, &pad.pad.net) {
+                v.pads.push(pad);
+            }
+        }
+        for track in &self.tracks {
+            if let Some(v) = view_of(&mut views, self, &track.net) {
+                v.tracks.push(track);
+            }
+        }
+        for arc in &self.arcs {
+            if let Some(v) = view_of(&mut views, self, &arc.net) {
+                v.arcs.push(arc);
+            }
+        }
+        for via in &self.vias {
+            if let Some(v) = view_of(&mut views, self, &via.net) {
+                v.vias.push(via);
+            }
+        }
+        for zone in &self.zones {
+            if let Some(v) = view_of(&mut views, self, &zone.net) {
+                v.zones.push(zone);
+            }
+        }
+        for name in self.nets.values().filter(|n| !n.is_empty()) {
+            views.entry(name).or_insert_with(|| NetView::new(self, name));
+        }
+
+        let mut views: Vec<NetView> = views.into_values().collect();
+        views.sort_by(|a, b| match (a.id, b.id) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => natural_order(a.name, b.name),
+        });
+        views.into_iter()
+    }
+
+    fn net_names_in_use(&self) -> impl Iterator<Item = &str> {
+        self.footprints
+            .iter()
+            .flat_map(|f| f.pads.iter().map(|p| &p.net))
+            .chain(self.tracks.iter().map(|t| &t.net))
+            .chain(self.arcs.iter().map(|a| &a.net))
+            .chain(self.vias.iter().map(|v| &v.net))
+            .chain(self.zones.iter().map(|z| &z.net))
+            .filter_map(|n| n.as_deref())
+    }
+}
+
+/// The view collecting an element's net, created on first use
+fn view_of<'a, 'v>(
+    views: &'v mut HashMap<&'a str, NetView<'a>>,
+    pcb: &'a PcbFile,
+    net: &'a Option<String>,
+) -> Option<&'v mut NetView<'a>> {
+    let name = net.as_deref()?;
+    Some(views.entry(name).or_insert_with(|| NetView::new(pcb, name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcb::parse_pcb;
+
+    const FPGA: &str = include_str!("../../assets/fpga.kicad_pcb");
+
+    #[test]
+    fn test_net_view() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let gnd = pcb.net("GND").unwrap();
+        assert_eq!(gnd.id, pcb.net_id("GND"));
+        assert!(!gnd.pads.is_empty());
+        assert!(gnd.pads.iter().all(|p| p.pad.net.as_deref() == Some("GND")));
+        assert_eq!(gnd.vias.len(), pcb.vias.iter().filter(|v| v.net.as_deref() == Some("GND")).count());
+        assert!(gnd.references().len() <= gnd.pads.len());
+        assert!(pcb.net("NO_SUCH_NET").is_none());
+        assert!(pcb.net("").is_none());
+    }
+
+    #[test]
+    fn test_nets_cover_every_element() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let nets: Vec<_> = pcb.nets().collect();
+        assert_eq!(nets.len(), pcb.nets.values().filter(|n| !n.is_empty()).count());
+        let ids: Vec<_> = nets.iter().filter_map(|n| n.id).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+        let pads: usize = nets.iter().map(|n| n.pads.len()).sum();
+        let connected = pcb.pads_absolute().filter(|p| p.pad.net.is_some()).count();
+        assert_eq!(pads, connected);
+        let tracks: usize = nets.iter().map(|n| n.tracks.len()).sum();
+        assert_eq!(tracks, pcb.tracks.iter().filter(|t| t.net.is_some()).count());
+
+        let undeclared = parse_pcb(r#"(kicad_pcb (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net "SDA")))"#).unwrap();
+        let sda = undeclared.net("SDA").unwrap();
+        assert_eq!((sda.id, sda.tracks.len()), (None, 1));
+        assert_eq!(undeclared.nets().count(), 1);
+    }
+}
@@ -0,0 +1,1920 @@
+//! Typed parser for KiCad PCB files
+//!
+//! Where [`parse_layers_only`](super::parse_layers_only) extracts just the layer
+//! table and [`DetailParser`](super::DetailParser) pulls individual elements out
+//! with regex patterns, `PcbParser` reads the whole board into the typed
+//! [`PcbFile`] model: footprints with their pads, tracks, vias, zones, board
+//! graphics and net classes.
+//!
+//! ```rust
+//! use kiparse::pcb::PcbParser;
+//!
+//! let content = r#"(kicad_pcb
+//!   (version 20240108)
+//!   (generator "pcbnew")
+//!   (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+//!   (segment (start 0 0) (end 10 0) (width 0.25) (layer "F.Cu") (net 1))
+//! )"#;
+//! let pcb = PcbParser::new(content).parse()?;
+//!
+//! assert_eq!(pcb.layers.len(), 2);
+//! assert_eq!(pcb.tracks[0].width.as_mm(), 0.25);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::types::*;
+use crate::base64;
+use crate::embedded::parse_embedded_files;
+use crate::error::{KicadError, Result};
+use super::geometry::{angle, arc_from_points, bounds, distance};
+use crate::progress::{ParseStage, Progress, ProgressCallback, Reporter};
+use crate::sexpr::{parse_sexpr_tracked, SExpr};
+use crate::units::Length;
+use crate::collections::HashMap;
+use core::sync::atomic::AtomicBool;
+
+/// Limits and options for [`PcbParser`]
+///
+/// The default is lenient and unlimited, which suits files from KiCad itself.
+/// Hosts parsing uploads or other untrusted input should cap the nesting depth
+/// and element count, see [`ParserConfig::untrusted`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParserConfig {
+    /// Reject unknown board elements and malformed layer or drawing entries
+    /// instead of skipping them
+    pub strict: bool,
+    /// Deepest list nesting accepted, the `(kicad_pcb ...)` list being depth 1
+    pub max_depth: usize,
+    /// Most elements accepted directly inside `(kicad_pcb ...)`
+    pub max_elements: usize,
+    /// Parse copper zones and rule areas; when off they are skipped
+    pub parse_zones: bool,
+    /// Parse board and footprint drawings; when off only those on Edge.Cuts are kept
+    pub parse_graphics: bool,
+    /// Keep the original text of skipped board elements in [`PcbFile::skipped`]
+    pub keep_skipped: bool,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        ParserConfig {
+            strict: false,
+            max_depth: usize::MAX,
+            max_elements: usize::MAX,
+            parse_zones: true,
+            parse_graphics: true,
+            keep_skipped: false,
+        }
+    }
+}
+
+impl ParserConfig {
+    /// Limits for input that does not come from a trusted KiCad: nesting up to
+    /// 64 lists deep and at most ten million board elements
+    pub fn untrusted() -> Self {
+        ParserConfig {
+            max_depth: 64,
+            max_elements: 10_000_000,
+            ..Default::default()
+        }
+    }
+}
+
+/// Board elements read elsewhere or known but not modelled, accepted in strict mode
+const KNOWN_ELEMENTS: &[&str] = &[
+    "version", "generator", "generator_version", "net", "property", "dimension", "target",
+    "embedded_fonts", "table", "generated",
+];
+
+/// Typed parser producing a fully populated [`PcbFile`]
+pub struct PcbParser<'a> {
+    content: &'a str,
+    config: ParserConfig,
+    progress: Option<ProgressCallback<'a>>,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<'a> PcbParser<'a> {
+    /// Create a new parser for the given PCB content
+    pub fn new(content: &'a str) -> Self {
+        Self { content, config: ParserConfig::default(), progress: None, cancel: None }
+    }
+
+    /// Parse with the given limits and options instead of the defaults
+    pub fn with_config(mut self, config: ParserConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Call `callback` as the board is read and converted, see [`crate::progress`]
+    pub fn on_progress(mut self, callback: impl Fn(Progress) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Stop with [`KicadError::Cancelled`] once `cancel` is set, e.g. from another thread
+    pub fn cancel_on(mut self, cancel: &'a AtomicBool) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Parse the board into a [`PcbFile`]
+    pub fn parse(&self) -> Result<PcbFile> {
+        let callback = self.progress.as_deref();
+        let mut reading = Reporter::new(callback, self.cancel, ParseStage::Reading, self.content.len());
+        reading.check()?;
+        let config = &self.config;
+        let mut spans = Vec::new();
+        let root = parse_sexpr_tracked(self.content, config.max_depth, &mut |start, end| {
+            if spans.len() == config.max_elements {
+                return Err(KicadError::InvalidFormat(format!(
+                    "more than {} board elements",
+                    config.max_elements
+                )));
+            }
+            spans.push((start, end));
+            reading.element(end)
+        })?;
+        reading.finish();
+        if !root.is("kicad_pcb") {
+            return Err(KicadError::InvalidFormat(
+                "Expected a (kicad_pcb ...) root element".to_string(),
+            ));
+        }
+
+        let mut pcb = PcbFile::new();
+        pcb.version = root.child_str("version").unwrap_or("unknown").to_string();
+        pcb.generator = root.child_str("generator").unwrap_or("unknown").to_string();
+        pcb.metadata.generator_version = root.child_str("generator_version").map(str::to_string);
+
+        // Net declarations normally lead the file, but collect them up front so
+        // element nets resolve regardless of ordering
+        let nets = Self::parse_nets(&root);
+
+        let mut converting = Reporter::new(callback, self.cancel, ParseStage::Converting, self.content.len());
+        for (node, (start, end)) in root.lists().zip(spans) {
+            converting.element(end)?;
+            let mut skip = || {
+                if config.keep_skipped {
+                    pcb.skipped.push(self.content[start..end].to_string());
+                }
+            };
+            let name = node.name().unwrap_or_default();
+            let drawing = name.starts_with("gr_") && !matches!(name, "gr_text" | "gr_text_box");
+            if (name == "zone" && !config.parse_zones)
+                || (drawing && !config.parse_graphics && node.child_str("layer") != Some("Edge.Cuts"))
+            {
+                skip();
+                continue;
+            }
+            match node.name() {
+                Some("general") => {
+                    pcb.board_thickness = length(node, "thickness");
+                    pcb.metadata.legacy_teardrops = node.child_bool("legacy_teardrops").unwrap_or(false);
+                }
+                Some("paper") => {
+                    // (paper "A4" [portrait]) or (paper "User" WIDTH HEIGHT [portrait])
+                    pcb.paper_size = node.arg_str(0).map(str::to_string);
+                    pcb.metadata.paper_portrait = node.has_flag("portrait");
+                    pcb.metadata.paper_custom_size = node.arg_f64(1).zip(node.arg_f64(2));
+                }
+                Some("title_block") => pcb.title_block = Some(parse_title_block(node)),
+                Some("setup") => {
+                    if let Some(stackup) = node.child("stackup") {
+                        pcb.stackup = stackup.children("layer").map(Self::parse_stackup_layer).collect();
+                        let metadata = &mut pcb.metadata;
+                        metadata.copper_finish = stackup.child_str("copper_finish").map(str::to_string);
+                        metadata.dielectric_constraints = stackup.child_bool("dielectric_constraints").unwrap_or(false);
+                        metadata.edge_connector = stackup.child_str("edge_connector").map(str::to_string);
+                        metadata.castellated_pads = stackup.child_bool("castellated_pads").unwrap_or(false);
+                        metadata.edge_plating = stackup.child_bool("edge_plating").unwrap_or(false);
+                    }
+                    pcb.metadata.margins = MaskPasteMargins {
+                        solder_mask_margin: length(node, "pad_to_mask_clearance"),
+                        solder_paste_margin: length(node, "pad_to_paste_clearance"),
+                        solder_paste_ratio: node.child_f64("pad_to_paste_clearance_ratio"),
+                    };
+                    pcb.metadata.via_defaults = Self::parse_via_finish(node);
+                    pcb.metadata.plot_params = node.child("pcbplotparams").map(Self::parse_plot_params);
+                    pcb.metadata.aux_axis_origin = point(node, "aux_axis_origin");
+                    pcb.metadata.grid_origin = point(node, "grid_origin");
+                }
+                Some("layers") => {
+                    for entry in node.lists() {
+                        match Self::parse_layer(entry) {
+                            Some(layer) => {
+                                pcb.layers.insert(layer.id, layer);
+                            }
+                            None if config.strict => {
+                                return Err(KicadError::InvalidFormat(format!(
+                                    "malformed layer entry {}",
+                                    entry.to_kicad_string().trim_end()
+                                )))
+                            }
+                            None => {}
+                        }
+                    }
+                }
+                Some("net_class") => {
+                    let class = Self::parse_net_class(node);
+                    pcb.net_classes.insert(class.name.clone(), class);
+                }
+                Some("footprint") | Some("module") => {
+                    let mut footprint = Self::parse_footprint(node, &nets);
+                    if !config.parse_graphics {
+                        footprint.graphics.retain(|g| g.layer() == "Edge.Cuts");
+                    }
+                    pcb.footprints.push(footprint);
+                }
+                Some("segment") => pcb.tracks.push(Self::parse_track(node, &nets)),
+                Some("arc") => pcb.arcs.push(Self::parse_track_arc(node, &nets)),
+                Some("via") => pcb.vias.push(Self::parse_via(node, &nets)),
+                Some("zone") if node.child("keepout").is_some() => {
+                    pcb.rule_areas.push(Self::parse_rule_area(node))
+                }
+                Some("zone") => pcb.zones.push(Self::parse_zone(node, &nets)),
+                Some("group") => pcb.groups.push(Self::parse_group(node)),
+                Some("generated") if node.child_str("type") == Some("tuning_pattern") => {
+                    pcb.tuning_patterns.push(Self::parse_tuning_pattern(node))
+                }
+                Some("image") => pcb.images.push(Self::parse_image(node)),
+                Some("embedded_files") => pcb.embedded_files = parse_embedded_files(node),
+                Some("gr_text") => pcb.texts.push(Self::parse_text(node)),
+                Some("gr_text_box") => pcb.text_boxes.push(Self::parse_text_box(node)),
+                Some(name) if name.starts_with("gr_") => match Self::parse_graphic(node) {
+                    Some(graphic) => pcb.graphics.push(graphic),
+                    None if config.strict => {
+                        return Err(KicadError::InvalidFormat(format!("malformed {} drawing", name)))
+                    }
+                    None => skip(),
+                },
+                Some(name) if config.strict && !KNOWN_ELEMENTS.contains(&name) => {
+                    return Err(KicadError::UnexpectedToken(format!("unknown board element ({} ...)", name)))
+                }
+                Some("version" | "generator" | "generator_version" | "net") => {}
+                _ => skip(),
+            }
+        }
+
+        converting.finish();
+
+        pcb.nets = nets;
+        Ok(pcb)
+    }
+
+    /// Collect the `(net N "NAME")` declarations into a number -> name table
+    fn parse_nets(root: &SExpr) -> HashMap<u32, String> {
+        root.children("net")
+            .filter_map(|decl| {
+                let id = decl.arg_str(0)?.parse().ok()?;
+                Some((id, decl.arg_str(1)?.to_string()))
+            })
+            .collect()
+    }
+
+    /// Parse a layer entry such as `(0 "F.Cu" signal "Front")`
+    fn parse_layer(node: &SExpr) -> Option<Layer> {
+        let items = node.items();
+        let id = items.first()?.as_str()?.parse().ok()?;
+        Some(Layer {
+            id,
+            name: items.get(1)?.as_str()?.to_string(),
+            layer_type: items.get(2)?.as_str()?.to_string(),
+            user_name: items.get(3).and_then(SExpr::as_str).map(str::to_string),
+        })
+    }
+
+    /// Parse a stackup entry such as `(layer "dielectric 1" (type "core") (thickness 1.5) ...)`
+    fn parse_stackup_layer(node: &SExpr) -> StackupLayer {
+        StackupLayer {
+            name: node.arg_str(0).unwrap_or_default().to_string(),
+            layer_type: node.child_str("type").unwrap_or_default().to_string(),
+            thickness: length(node, "thickness"),
+            material: node.child_str("material").map(str::to_string),
+            epsilon_r: node.child_f64("epsilon_r"),
+            loss_tangent: node.child_f64("loss_tangent"),
+            color: node.child_str("color").map(str::to_string),
+        }
+    }
+
+    /// Parse a `(net_class NAME "description" (clearance ..) (add_net ..) ...)` block
+    fn parse_net_class(node: &SExpr) -> NetClass {
+        NetClass {
+            name: node.arg_str(0).unwrap_or_default().to_string(),
+            description: node.arg_str(1).unwrap_or_default().to_string(),
+            clearance: length(node, "clearance"),
+            track_width: length(node, "trace_width"),
+            via_diameter: length(node, "via_dia"),
+            via_drill: length(node, "via_drill"),
+            uvia_diameter: length(node, "uvia_dia"),
+            uvia_drill: length(node, "uvia_drill"),
+            diff_pair_width: length(node, "diff_pair_width"),
+            diff_pair_gap: length(node, "diff_pair_gap"),
+            nets: node
+                .children("add_net")
+                .filter_map(|n| n.arg_str(0))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    fn parse_footprint(node: &SExpr, nets: &HashMap<u32, String>) -> Footprint {
+        let (position, rotation) = at(node);
+        let mut footprint = Footprint {
+            name: node.arg_str(0).unwrap_or_default().to_string(),
+            uuid: uuid(node),
+            position,
+            rotation,
+            layer: node.child_str("layer").unwrap_or("F.Cu").to_string(),
+            locked: node.flag("locked"),
+            placed: node.flag("placed"),
+            attributes: Self::parse_attributes(node),
+            properties: node
+                .children("property")
+                .filter_map(|p| Some((p.arg_str(0)?.to_string(), p.arg_str(1)?.to_string())))
+                .collect(),
+            path: node.child_str("path").map(str::to_string),
+            sheet_name: node.child_str("sheetname").map(str::to_string),
+            sheet_file: node.child_str("sheetfile").map(str::to_string),
+            net_tie_pad_groups: node
+                .child("net_tie_pad_groups")
+                .map(|groups| {
+                    groups
+                        .args()
+                        .iter()
+                        .filter_map(SExpr::as_str)
+                        .map(|group| {
+                            group.split(',').map(str::trim).filter(|n| !n.is_empty()).map(str::to_string).collect()
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            margins: Self::parse_margins(node),
+            fields: Vec::new(),
+            pads: Vec::new(),
+            graphics: Vec::new(),
+            texts: Vec::new(),
+            models: node.children("model").map(Self::parse_model).collect(),
+            embedded_files: node
+                .child("embedded_files")
+                .map(parse_embedded_files)
+                .unwrap_or_default(),
+        };
+
+        for child in node.lists() {
+            match child.name() {
+                Some("pad") => footprint.pads.push(Self::parse_pad(child, nets)),
+                Some("fp_text") => match child.arg_str(0) {
+                    Some("reference") => footprint.fields.push(Self::parse_field(child, "Reference")),
+                    Some("value") => footprint.fields.push(Self::parse_field(child, "Value")),
+                    _ => footprint.texts.push(Self::parse_text(child)),
+                },
+                Some("property") if child.child("at").is_some() => {
+                    let name = child.arg_str(0).unwrap_or_default();
+                    footprint.fields.push(Self::parse_field(child, name));
+                }
+                Some(name) if name.starts_with("fp_") => {
+                    if let Some(graphic) = Self::parse_graphic(child) {
+                        footprint.graphics.push(graphic);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // KiCad 6 and 7 keep the reference and value as texts instead of properties
+        for field in &footprint.fields {
+            footprint.properties.entry(field.name.clone()).or_insert_with(|| field.text.text.clone());
+        }
+
+        // KiCad 7 keeps the sheet as properties instead of children
+        if footprint.sheet_name.is_none() {
+            footprint.sheet_name = footprint.properties.get("Sheetname").cloned();
+        }
+        if footprint.sheet_file.is_none() {
+            footprint.sheet_file = footprint.properties.get("Sheetfile").cloned();
+        }
+
+        footprint
+    }
+
+    /// Parse the `(attr smd exclude_from_bom dnp ...)` flags of a footprint
+    ///
+    /// Flags written as separate `(dnp)`-style children are honored as well, and the
+    /// KiCad 5 `virtual` attribute maps to board-only with both exclusions.
+    fn parse_attributes(node: &SExpr) -> FootprintAttributes {
+        let attr = node.child("attr");
+        let has = |flag: &str| attr.map_or(false, |a| a.has_flag(flag)) || node.flag(flag);
+        let legacy_virtual = has("virtual");
+        FootprintAttributes {
+            mount: attr
+                .and_then(|a| a.arg_str(0))
+                .filter(|m| *m == "smd" || *m == "through_hole")
+                .unwrap_or_default()
+                .to_string(),
+            board_only: has("board_only") || legacy_virtual,
+            exclude_from_pos_files: has("exclude_from_pos_files") || legacy_virtual,
+            exclude_from_bom: has("exclude_from_bom") || legacy_virtual,
+            dnp: has("dnp"),
+            allow_missing_courtyard: has("allow_missing_courtyard"),
+        }
+    }
+
+    /// Parse `(model "path" (offset (xyz ..)) (scale (xyz ..)) (rotate (xyz ..)))`
+    fn parse_model(node: &SExpr) -> Model3D {
+        let xyz = |name: &str, default: f64| {
+            node.child(name)
+                .and_then(|c| c.child("xyz"))
+                .map(|xyz| {
+                    [
+                        xyz.arg_f64(0).unwrap_or(default),
+                        xyz.arg_f64(1).unwrap_or(default),
+                        xyz.arg_f64(2).unwrap_or(default),
+                    ]
+                })
+        };
+        Model3D {
+            path: node.arg_str(0).unwrap_or_default().to_string(),
+            // KiCad 5 named the offset (at (xyz ..)) and expressed it in inches
+            offset: xyz("offset", 0.0)
+                .or_else(|| xyz("at", 0.0).map(|at| at.map(|v| Length::inches(v).as_mm())))
+                .unwrap_or([0.0; 3]),
+            scale: xyz("scale", 1.0).unwrap_or([1.0; 3]),
+            rotate: xyz("rotate", 0.0).unwrap_or([0.0; 3]),
+            hide: node.flag("hide"),
+            opacity: node.child_f64("opacity"),
+        }
+    }
+
+    fn parse_pad(node: &SExpr, nets: &HashMap<u32, String>) -> Pad {
+        Pad {
+            number: node.arg_str(0).unwrap_or_default().to_string(),
+            pad_type: node.arg_str(1).unwrap_or_default().to_string(),
+            shape: node.arg_str(2).unwrap_or_default().to_string(),
+            position: at(node).0,
+            rotation: at(node).1,
+            size: point(node, "size").unwrap_or_default(),
+            // (drill 0.8) or (drill oval 1.0 2.0): the first number is the diameter/width
+            drill: node
+                .child("drill")
+                .and_then(|d| d.args().iter().find_map(SExpr::as_f64))
+                .map(Length::mm),
+            drill_oval: node.child("drill").filter(|d| d.has_flag("oval")).and_then(|d| {
+                let mut sizes = d.args().iter().filter_map(SExpr::as_f64);
+                let x = sizes.next()?;
+                Some(Point::new(x, sizes.next().unwrap_or(x)))
+            }),
+            layers: strings(node.child("layers")),
+            net: net(node, nets),
+            pin_function: node.child_str("pinfunction").map(str::to_string),
+            pin_type: node.child_str("pintype").map(str::to_string),
+            die_length: length(node, "die_length"),
+            roundrect_ratio: node.child_f64("roundrect_rratio"),
+            margins: Self::parse_margins(node),
+        }
+    }
+
+    fn parse_track(node: &SExpr, nets: &HashMap<u32, String>) -> Track {
+        Track {
+            start: point(node, "start").unwrap_or_default(),
+            end: point(node, "end").unwrap_or_default(),
+            width: length(node, "width").unwrap_or_default(),
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            net: net(node, nets),
+            uuid: uuid(node),
+        }
+    }
+
+    /// Parse a board-level `(arc (start ..) (mid ..) (end ..) (width ..) ...)` track
+    fn parse_track_arc(node: &SExpr, nets: &HashMap<u32, String>) -> TrackArc {
+        TrackArc {
+            start: point(node, "start").unwrap_or_default(),
+            mid: point(node, "mid").unwrap_or_default(),
+            end: point(node, "end").unwrap_or_default(),
+            width: length(node, "width").unwrap_or_default(),
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            net: net(node, nets),
+            uuid: uuid(node),
+        }
+    }
+
+    fn parse_via(node: &SExpr, nets: &HashMap<u32, String>) -> Via {
+        // (via [blind|micro] (at ..) ...)
+        let via_type = if node.has_flag("micro") {
+            ViaType::Micro
+        } else if node.has_flag("blind") {
+            ViaType::BlindBuried
+        } else {
+            ViaType::Through
+        };
+        Via {
+            position: at(node).0,
+            via_type,
+            size: length(node, "size").unwrap_or_default(),
+            drill: length(node, "drill").unwrap_or_default(),
+            layers: strings(node.child("layers")),
+            net: net(node, nets),
+            uuid: uuid(node),
+            finish: Self::parse_via_finish(node),
+        }
+    }
+
+    /// Via protection settings of a via or the board `setup`
+    fn parse_via_finish(node: &SExpr) -> ViaFinish {
+        let sides = |name: &str| {
+            let Some(setting) = node.child(name) else { return ViaSides::default() };
+            if setting.child("front").is_some() || setting.child("back").is_some() {
+                // KiCad 9: (tenting (front yes) (back no))
+                ViaSides { front: setting.child_bool("front"), back: setting.child_bool("back") }
+            } else {
+                // KiCad 8 board setup lists the tented sides: (tenting front back) or (tenting none)
+                ViaSides { front: Some(setting.has_flag("front")), back: Some(setting.has_flag("back")) }
+            }
+        };
+        ViaFinish {
+            tenting: sides("tenting"),
+            covering: sides("covering"),
+            plugging: sides("plugging"),
+            filling: node.child_bool("filling"),
+            capping: node.child_bool("capping"),
+        }
+    }
+
+    /// Parse the `(pcbplotparams ...)` block of the board setup
+    fn parse_plot_params(node: &SExpr) -> PlotParams {
+        let flag = |name: &str| node.child_bool(name).unwrap_or(false);
+        let mask = |name: &str| node.child_str(name).and_then(layer_mask_ids).unwrap_or_default();
+        let code = |name: &str| node.child_f64(name).map(|v| v as u32);
+        PlotParams {
+            layers: mask("layerselection"),
+            common_layers: mask("plot_on_all_layers_selection"),
+            format: code("outputformat").and_then(PlotFormat::from_code).unwrap_or(PlotFormat::Gerber),
+            output_directory: node.child_str("outputdirectory").unwrap_or_default().to_string(),
+            mirror: flag("mirror"),
+            drill_marks: match code("drillshape") {
+                Some(1) => DrillMarks::Small,
+                Some(2) => DrillMarks::Full,
+                _ => DrillMarks::None,
+            },
+            sketch: code("mode") == Some(2),
+            use_aux_origin: flag("useauxorigin"),
+            plot_frame_ref: flag("plotframeref"),
+            subtract_mask_from_silk: flag("subtractmaskfromsilk"),
+            sketch_pads_on_fab: flag("sketchpadsonfab"),
+            gerber_extensions: flag("usegerberextensions"),
+            gerber_attributes: flag("usegerberattributes"),
+            gerber_advanced_attributes: flag("usegerberadvancedattributes"),
+            gerber_job_file: flag("creategerberjobfile"),
+            gerber_precision: code("gerberprecision"),
+            svg_precision: code("svgprecision"),
+            black_and_white: flag("plot_black_and_white"),
+        }
+    }
+
+    fn parse_zone(node: &SExpr, nets: &HashMap<u32, String>) -> Zone {
+        let mut layers = strings(node.child("layers"));
+        if let Some(layer) = node.child_str("layer") {
+            layers.push(layer.to_string());
+        }
+        Zone {
+            uuid: uuid(node),
+            net: node
+                .child_str("net_name")
+                .filter(|n| !n.is_empty())
+                .map(str::to_string)
+                .or_else(|| net(node, nets)),
+            layer: layers.first().cloned().unwrap_or_default(),
+            priority: node.child_f64("priority").unwrap_or(0.0) as i32,
+            // (connect_pads no) disables pad connections, any other form keeps them
+            connect_pads: node.child("connect_pads").map_or(true, |c| c.arg_str(0) != Some("no")),
+            polygon: node.child("polygon").map(points).unwrap_or_default(),
+            filled_polygons: node
+                .children("filled_polygon")
+                .map(|fill| FilledPolygon {
+                    // Single-layer zones may omit the layer on their fills
+                    layer: fill
+                        .child_str("layer")
+                        .map(str::to_string)
+                        .unwrap_or_else(|| layers.first().cloned().unwrap_or_default()),
+                    points: points(fill),
+                })
+                .collect(),
+            fill: Self::parse_zone_fill(node),
+            layers,
+        }
+    }
+
+    /// Local `solder_mask_margin`, `solder_paste_margin` and paste ratio of a pad or footprint
+    fn parse_margins(node: &SExpr) -> MaskPasteMargins {
+        MaskPasteMargins {
+            solder_mask_margin: length(node, "solder_mask_margin"),
+            solder_paste_margin: length(node, "solder_paste_margin"),
+            // Footprints write solder_paste_ratio, pads solder_paste_margin_ratio
+            solder_paste_ratio: node
+                .child_f64("solder_paste_margin_ratio")
+                .or_else(|| node.child_f64("solder_paste_ratio")),
+        }
+    }
+
+    /// Parse `(fill yes (mode hatch) (thermal_gap 0.5) (island_removal_mode 2) ...)`
+    fn parse_zone_fill(node: &SExpr) -> ZoneFill {
+        let defaults = ZoneFill::default();
+        let Some(fill) = node.child("fill") else { return defaults };
+        ZoneFill {
+            mode: match fill.child_str("mode") {
+                Some("hatch") => ZoneFillMode::Hatch {
+                    thickness: length(fill, "hatch_thickness").unwrap_or(Length::mm(1.0)),
+                    gap: length(fill, "hatch_gap").unwrap_or(Length::mm(1.5)),
+                    orientation: fill.child_f64("hatch_orientation").unwrap_or(0.0),
+                },
+                _ => ZoneFillMode::Solid,
+            },
+            thermal_gap: length(fill, "thermal_gap").unwrap_or(defaults.thermal_gap),
+            thermal_bridge_width: length(fill, "thermal_bridge_width").unwrap_or(defaults.thermal_bridge_width),
+            island_removal: match fill.child_f64("island_removal_mode").map(|m| m as i32) {
+                Some(1) => IslandRemoval::Never,
+                Some(2) => IslandRemoval::BelowArea(fill.child_f64("island_area_min").unwrap_or(10.0)),
+                _ => IslandRemoval::Always,
+            },
+        }
+    }
+
+    /// Parse a keepout `(zone ... (keepout (tracks not_allowed) ...))` into a [`RuleArea`]
+    fn parse_rule_area(node: &SExpr) -> RuleArea {
+        let keepout = node.child("keepout");
+        let forbidden = |name: &str| keepout.and_then(|k| k.child_str(name)) == Some("not_allowed");
+        let mut layers = strings(node.child("layers"));
+        if let Some(layer) = node.child_str("layer") {
+            layers.push(layer.to_string());
+        }
+
+        RuleArea {
+            name: node.child_str("name").map(str::to_string),
+            uuid: uuid(node),
+            layers,
+            keepout: KeepoutRules {
+                tracks: forbidden("tracks"),
+                vias: forbidden("vias"),
+                pads: forbidden("pads"),
+                copper_pour: forbidden("copperpour"),
+                footprints: forbidden("footprints"),
+            },
+            polygon: node.child("polygon").map(points).unwrap_or_default(),
+        }
+    }
+
+    /// Parse `(group "name" (uuid ..) (members uuid ...))`
+    fn parse_group(node: &SExpr) -> Group {
+        Group {
+            name: node.arg_str(0).unwrap_or_default().to_string(),
+            // KiCad 6 used (id ...) for the group's own identifier
+            uuid: node
+                .child_str("uuid")
+                .or_else(|| node.child_str("id"))
+                .unwrap_or_default()
+                .to_string(),
+            locked: node.flag("locked"),
+            members: strings(node.child("members")),
+        }
+    }
+
+    /// Parse a `(generated (type tuning_pattern) ...)` length-tuning object
+    fn parse_tuning_pattern(node: &SExpr) -> TuningPattern {
+        let target = |name: &str| {
+            Some(LengthTarget {
+                min: node.child_f64(&format!("{}_min", name))?,
+                target: node.child_f64(name)?,
+                max: node.child_f64(&format!("{}_max", name))?,
+            })
+        };
+        let time_domain = node.child_bool("is_time_domain").unwrap_or(false);
+        let mode = match node.child_str("tuning_mode") {
+            Some("diff_pair") => TuningMode::DiffPair,
+            Some("diff_pair_skew") => TuningMode::DiffPairSkew,
+            _ => TuningMode::Single,
+        };
+        // Length and skew targets are both stored; only the one matching the mode applies
+        let (target_length, target_skew) = match mode {
+            _ if time_domain => (None, None),
+            TuningMode::DiffPairSkew => (None, target("target_skew")),
+            _ => (target("target_length"), None),
+        };
+
+        TuningPattern {
+            uuid: uuid(node),
+            name: node.child_str("name").unwrap_or_default().to_string(),
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            mode,
+            net: node
+                .child_str("last_netname")
+                .filter(|n| !n.is_empty())
+                .map(str::to_string),
+            status: match node.child_str("last_status") {
+                Some("tuned") => TuningStatus::Tuned,
+                Some("too_short") => TuningStatus::TooShort,
+                Some("too_long") => TuningStatus::TooLong,
+                _ => TuningStatus::Unknown,
+            },
+            last_tuning: node.child_str("last_tuning").map(str::to_string),
+            origin: nested_point(node, "origin").unwrap_or_default(),
+            end: nested_point(node, "end").unwrap_or_default(),
+            base_line: node.child("base_line").map(points).unwrap_or_default(),
+            base_line_coupled: node.child("base_line_coupled").map(points),
+            target_length,
+            target_skew,
+            target_delay: if time_domain { target("target_delay") } else { None },
+            time_domain,
+            meander: MeanderParameters {
+                min_amplitude: length(node, "min_amplitude").unwrap_or_default(),
+                max_amplitude: length(node, "max_amplitude").unwrap_or_default(),
+                min_spacing: length(node, "min_spacing").unwrap_or_default(),
+                corner_radius_percent: node.child_f64("corner_radius_percent").unwrap_or(100.0),
+                rounded: node.child_bool("rounded").unwrap_or(true),
+                single_sided: node.child_bool("single_sided").unwrap_or(false),
+                initial_side: node.child_str("initial_side").unwrap_or("default").to_string(),
+            },
+            members: strings(node.child("members")),
+        }
+    }
+
+    /// Parse `(image (at ..) (layer ..) (scale ..) (data "base64" ...))`
+    ///
+    /// The payload may be split across several strings; they are joined before
+    /// decoding. A corrupt payload leaves `data` empty rather than failing the board.
+    fn parse_image(node: &SExpr) -> Image {
+        let encoded: String = node
+            .child("data")
+            .map(|d| d.args().iter().filter_map(SExpr::as_str).collect())
+            .unwrap_or_default();
+        Image {
+            position: at(node).0,
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            scale: node.child_f64("scale").unwrap_or(1.0),
+            uuid: uuid(node),
+            data: base64::decode(&encoded).unwrap_or_default(),
+        }
+    }
+
+    /// Parse `(gr_text "text" ...)` or `(fp_text kind "text" ...)`
+    fn parse_text(node: &SExpr) -> Text {
+        // fp_text carries its kind (reference/value/user) before the text itself,
+        // a property its name
+        let index = if node.is("fp_text") || node.is("property") { 1 } else { 0 };
+        Text {
+            text: node.arg_str(index).unwrap_or_default().to_string(),
+            position: at(node).0,
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            effects: text_effects(node),
+        }
+    }
+
+    /// Parse an `fp_text reference`/`value` or a positioned `property` as the drawn field `name`
+    fn parse_field(node: &SExpr, name: &str) -> FieldText {
+        // KiCad 6 and 7 write a bare `hide`, KiCad 8 `(hide yes)`
+        let hidden = node.flag("hide") || node.child("effects").map_or(false, |e| e.flag("hide"));
+        FieldText { name: name.to_string(), text: Self::parse_text(node), hidden }
+    }
+
+    /// Parse a `(gr_text_box "text" (start ..) (end ..) ...)` block
+    fn parse_text_box(node: &SExpr) -> TextBox {
+        // Rotated boxes store their four corners in (pts ...) instead of start/end
+        let corners = points(node);
+        let (start, end) = match (point(node, "start"), point(node, "end")) {
+            (Some(start), Some(end)) => (start, end),
+            _ => bounds(&corners),
+        };
+        let margins = node.child("margins").and_then(|m| {
+            Some([m.arg_f64(0)?, m.arg_f64(1)?, m.arg_f64(2)?, m.arg_f64(3)?].map(Length::mm))
+        });
+
+        TextBox {
+            text: node.arg_str(0).unwrap_or_default().to_string(),
+            start,
+            end,
+            margins,
+            layer: node.child_str("layer").unwrap_or_default().to_string(),
+            effects: text_effects(node),
+            border: node.child_bool("border").unwrap_or(true),
+            border_width: node
+                .child("stroke")
+                .and_then(|s| length(s, "width"))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Parse a `gr_*` or `fp_*` drawing into a [`Graphic`]
+    pub(crate) fn parse_graphic(node: &SExpr) -> Option<Graphic> {
+        let kind = node.name()?.split_once('_')?.1;
+        let layer = node.child_str("layer").unwrap_or_default().to_string();
+        let width = node
+            .child("stroke")
+            .and_then(|s| length(s, "width"))
+            .or_else(|| length(node, "width"))
+            .unwrap_or_default();
+        let filled = node
+            .child_str("fill")
+            .map_or(false, |f| f == "solid" || f == "yes");
+        let uuid = uuid(node);
+
+        match kind {
+            "line" => Some(Graphic::Line {
+                start: point(node, "start")?,
+                end: point(node, "end")?,
+                layer,
+                width,
+                uuid,
+            }),
+            "circle" => {
+                let center = point(node, "center")?;
+                let end = point(node, "end")?;
+                Some(Graphic::Circle {
+                    radius: Length::mm(distance(&center, &end)),
+                    center,
+                    layer,
+                    width,
+                    filled,
+                    uuid,
+                })
+            }
+            "arc" => {
+                let start = point(node, "start")?;
+                let end = point(node, "end")?;
+                let arc = match point(node, "mid") {
+                    Some(mid) => arc_from_points(&start, &mid, &end)?,
+                    // Legacy form: start is the center, end the arc start, plus a sweep angle
+                    None => {
+                        let start_angle = angle(&start, &end);
+                        Arc {
+                            radius: Length::mm(distance(&start, &end)),
+                            center: start,
+                            start_angle,
+                            end_angle: start_angle + node.child_f64("angle").unwrap_or(0.0),
+                        }
+                    }
+                };
+                Some(Graphic::Arc { arc, layer, width, uuid })
+            }
+            "rect" => Some(Graphic::Rectangle {
+                rect: Rect {
+                    start: point(node, "start")?,
+                    end: point(node, "end")?,
+                },
+                layer,
+                width,
+                filled,
+                uuid,
+            }),
+            "poly" => Some(Graphic::Polygon {
+                points: points(node),
+                layer,
+                width,
+                filled,
+                uuid,
+            }),
+            "curve" | "bezier" => {
+                let [start, control1, control2, end]: [Point; 4] = points(node).try_into().ok()?;
+                Some(Graphic::Bezier { start, control1, control2, end, layer, width, uuid })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Parse a complete board; shorthand for `PcbParser::new(content).parse()`
+pub fn parse_pcb(content: &str) -> Result<PcbFile> {
+    PcbParser::new(content).parse()
+}
+
+/// Parse a footprint library file (`.kicad_mod`)
+///
+/// The footprint's name is the bare name from the file, without a library
+/// nickname, and its pads have no nets.
+pub fn parse_footprint(content: &str) -> Result<Footprint> {
+    let root = crate::sexpr::parse_sexpr(content)?;
+    if !root.is("footprint") && !root.is("module") {
+        return Err(KicadError::InvalidFormat("Expected a (footprint ...) root element".to_string()));
+    }
+    Ok(PcbParser::parse_footprint(&root, &HashMap::new()))
+}
+
+/// Read net classes from a KiCad 6+ project file (`.kicad_pro`)
+///
+/// Since KiCad 6 net classes are stored in the project file rather than the board,
+/// so for modern designs use this to fill [`PcbFile::net_classes`]. Both the KiCad 6
+/// per-class `nets` lists and the later `netclass_assignments` map are honored.
+#[cfg(feature = "json")]
+pub fn parse_project_net_classes(content: &str) -> Result<HashMap<String, NetClass>> {
+    let project: serde_json::Value = serde_json::from_str(content)?;
+    let settings = &project["net_settings"];
+    let mut classes = HashMap::new();
+
+    for class in settings["classes"].as_array().into_iter().flatten() {
+        let value = |key: &str| class[key].as_f64().map(Length::mm);
+        let name = class["name"].as_str().unwrap_or_default().to_string();
+        classes.insert(
+            name.clone(),
+            NetClass {
+                name,
+                description: class["description"].as_str().unwrap_or_default().to_string(),
+                clearance: value("clearance"),
+                track_width: value("track_width"),
+                via_diameter: value("via_diameter"),
+                via_drill: value("via_drill"),
+                uvia_diameter: value("microvia_diameter"),
+                uvia_drill: value("microvia_drill"),
+                diff_pair_width: value("diff_pair_width"),
+                diff_pair_gap: value("diff_pair_gap"),
+                nets: class["nets"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|n| n.as_str().map(str::to_string))
+                    .collect(),
+            },
+        );
+    }
+
+    // KiCad 7 maps net -> "Class", KiCad 8+ maps net -> ["Class", ...]
+    for (net, assigned) in settings["netclass_assignments"].as_object().into_iter().flatten() {
+        let names: Vec<&str> = match assigned {
+            serde_json::Value::Array(list) => list.iter().filter_map(|c| c.as_str()).collect(),
+            other => other.as_str().into_iter().collect(),
+        };
+        for name in names {
+            if let Some(class) = classes.get_mut(name) {
+                class.nets.push(net.clone());
+            }
+        }
+    }
+
+    Ok(classes)
+}
+
+/// Parse `(title_block (title ..) (date ..) (rev ..) (company ..) (comment N ..))`
+///
+/// The layout is shared by boards, schematics and drawing sheets.
+pub fn parse_title_block(node: &SExpr) -> TitleBlock {
+    let field = |name: &str| node.child_str(name).unwrap_or_default().to_string();
+    TitleBlock {
+        title: field("title"),
+        date: field("date"),
+        revision: field("rev"),
+        company: field("company"),
+        comments: node
+            .children("comment")
+            .filter_map(|c| Some((c.arg_str(0)?.parse().ok()?, c.arg_str(1)?.to_string())))
+            .collect(),
+    }
+}
+
+/// Position and rotation from an `(at x y [rot])` child
+fn at(node: &SExpr) -> (Point, f64) {
+    match node.child("at") {
+        Some(at) => (
+            Point::new(at.arg_f64(0).unwrap_or(0.0), at.arg_f64(1).unwrap_or(0.0)),
+            at.arg_f64(2).unwrap_or(0.0),
+        ),
+        None => (Point::default(), 0.0),
+    }
+}
+
+/// Font and justification from an `(effects (font ...) (justify ...))` child
+fn text_effects(node: &SExpr) -> TextEffects {
+    let effects = node.child("effects");
+    let font = effects.and_then(|e| e.child("font"));
+    TextEffects {
+        font_size: font
+            .and_then(|f| point(f, "size"))
+            .unwrap_or(Point::new(1.0, 1.0)),
+        thickness: font.and_then(|f| length(f, "thickness")).unwrap_or(Length::mm(0.15)),
+        bold: font.map_or(false, |f| f.flag("bold")),
+        italic: font.map_or(false, |f| f.flag("italic")),
+        justify: effects.and_then(|e| e.child("justify")).map(|j| {
+            j.args()
+                .iter()
+                .filter_map(SExpr::as_str)
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+    }
+}
+
+/// Element identifier: `(uuid ..)` in KiCad 7+, `(tstamp ..)` in earlier files
+fn uuid(node: &SExpr) -> String {
+    node.child_str("uuid")
+        .or_else(|| node.child_str("tstamp"))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn point(node: &SExpr, name: &str) -> Option<Point> {
+    node.child_xy(name).map(|(x, y)| Point::new(x, y))
+}
+
+/// Length in mm from a `(name value)` child
+fn length(node: &SExpr, name: &str) -> Option<Length> {
+    node.child_f64(name).map(Length::mm)
+}
+
+/// Point wrapped in its own list, e.g. `(origin (xy 1 2))`
+fn nested_point(node: &SExpr, name: &str) -> Option<Point> {
+    node.child(name).and_then(|n| point(n, "xy"))
+}
+
+fn points(node: &SExpr) -> Vec<Point> {
+    node.pts().into_iter().map(|(x, y)| Point::new(x, y)).collect()
+}
+
+fn strings(node: Option<&SExpr>) -> Vec<String> {
+    node.map(|n| n.args().iter().filter_map(SExpr::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Net name of an element, resolving numeric references through the net table
+///
+/// Handles `(net 3 "GND")` on pads, `(net 3)` on tracks and vias, and the
+/// name-only `(net "GND")` form. The unconnected net 0 resolves to `None`.
+fn net(node: &SExpr, nets: &HashMap<u32, String>) -> Option<String> {
+    let net = node.child("net")?;
+    let name = match (net.args().first()?, net.arg_str(1)) {
+        (_, Some(name)) => name,
+        (SExpr::Str(name), None) => name.as_str(),
+        (id, None) => nets.get(&id.as_str()?.parse::<u32>().ok()?)?.as_str(),
+    };
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::types::ModelType;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (version 20221018)
+  (generator pcbnew)
+  (layers
+    (0 "F.Cu" signal)
+    (31 "B.Cu" signal)
+    (44 "Edge.Cuts" user)
+  )
+  (net 0 "")
+  (net 1 "GND")
+  (net_class "Default" "This is the default net class."
+    (clearance 0.2)
+    (trace_width 0.25)
+    (via_dia 0.8)
+    (via_drill 0.4)
+    (uvia_dia 0.3)
+    (uvia_drill 0.1)
+    (add_net "GND")
+  )
+  (net_class "Power" ""
+    (clearance 0.3)
+    (trace_width 0.5)
+    (diff_pair_width 0.2)
+    (diff_pair_gap 0.25)
+    (add_net "+5V")
+    (add_net "VBUS")
+  )
+  (footprint "Resistor_SMD:R_0603" (layer "F.Cu") locked
+    (at 100 50 90)
+    (uuid "f0000000-0000-0000-0000-000000000001")
+    (fp_line (start -1 -1) (end 1 -1) (stroke (width 0.12) (type solid)) (layer "F.SilkS"))
+    (pad "1" smd roundrect (at -0.8 0) (size 0.8 0.9) (layers "F.Cu" "F.Paste" "F.Mask")
+      (roundrect_rratio 0.25) (net 1 "GND"))
+    (pad "2" thru_hole oval (at 0.8 0) (size 1.2 1.7) (drill oval 0.6 1.1) (layers "*.Cu" "*.Mask"))
+  )
+  (gr_line (start 0 0) (end 100 0) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+  (gr_circle (center 10 10) (end 13 14) (stroke (width 0.1) (type default)) (fill none) (layer "F.SilkS"))
+  (gr_arc (start 10 0) (mid 0 10) (end -10 0) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+  (segment (start 1 2) (end 3 4) (width 0.25) (layer "F.Cu") (net 1))
+  (via (at 5 5) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+  (zone (net 1) (net_name "GND") (layer "B.Cu") (priority 2) (connect_pads no)
+    (polygon (pts (xy 0 0) (xy 10 0) (xy 10 10))))
+)"#;
+
+    #[test]
+    fn test_parse_board_elements() {
+        let pcb = PcbParser::new(BOARD).parse().unwrap();
+
+        assert_eq!(pcb.version, "20221018");
+        assert_eq!(pcb.generator, "pcbnew");
+        assert_eq!(pcb.layers.len(), 3);
+        assert_eq!(pcb.layers[&44].name, "Edge.Cuts");
+
+        let fp = &pcb.footprints[0];
+        assert_eq!(fp.name, "Resistor_SMD:R_0603");
+        assert_eq!(fp.position, Point::new(100.0, 50.0));
+        assert_eq!(fp.rotation, 90.0);
+        assert!(fp.locked);
+        assert_eq!(fp.graphics.len(), 1);
+        assert_eq!(fp.pads.len(), 2);
+        assert_eq!(fp.pads[0].net.as_deref(), Some("GND"));
+        assert_eq!(fp.pads[0].roundrect_ratio, Some(0.25));
+        assert_eq!(fp.pads[1].drill, Some(Length::mm(0.6)));
+        assert_eq!(fp.pads[1].drill_oval, Some(Point::new(0.6, 1.1)));
+        assert_eq!(fp.pads[0].drill_oval, None);
+        assert_eq!(fp.pads[1].layers, vec!["*.Cu", "*.Mask"]);
+
+        assert_eq!(pcb.tracks.len(), 1);
+        assert_eq!(pcb.tracks[0].end, Point::new(3.0, 4.0));
+        assert_eq!(pcb.vias[0].drill, Length::mm(0.3));
+        assert_eq!(pcb.zones[0].net.as_deref(), Some("GND"));
+        assert!(!pcb.zones[0].connect_pads);
+        assert_eq!(pcb.zones[0].polygon.len(), 3);
+        assert_eq!(pcb.graphics.len(), 3);
+    }
+
+    #[test]
+    fn test_net_table_resolution() {
+        let pcb = parse_pcb(BOARD).unwrap();
+
+        assert_eq!(pcb.nets.len(), 2);
+        assert_eq!(pcb.net_name(1), Some("GND"));
+        assert_eq!(pcb.net_id("GND"), Some(1));
+        assert_eq!(pcb.tracks[0].net.as_deref(), Some("GND"));
+        assert_eq!(pcb.vias[0].net.as_deref(), Some("GND"));
+        assert_eq!(pcb.footprints[0].pads[1].net, None);
+
+        let modern = r#"(kicad_pcb (net 0 "") (net 7 "SDA")
+          (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net "SCL"))
+          (via (at 0 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 0)))"#;
+        let pcb = parse_pcb(modern).unwrap();
+        assert_eq!(pcb.tracks[0].net.as_deref(), Some("SCL"));
+        assert_eq!(pcb.vias[0].net, None);
+    }
+
+    #[test]
+    fn test_parse_net_classes() {
+        let pcb = parse_pcb(BOARD).unwrap();
+
+        assert_eq!(pcb.net_classes.len(), 2);
+        let default = &pcb.net_classes["Default"];
+        assert_eq!(default.description, "This is the default net class.");
+        assert_eq!(default.clearance, Some(Length::mm(0.2)));
+        assert_eq!(default.via_drill, Some(Length::mm(0.4)));
+        assert_eq!(default.diff_pair_gap, None);
+
+        let power = &pcb.net_classes["Power"];
+        assert_eq!(power.diff_pair_width, Some(Length::mm(0.2)));
+        assert_eq!(power.nets, vec!["+5V", "VBUS"]);
+        assert_eq!(pcb.net_class_of("VBUS").map(|c| c.name.as_str()), Some("Power"));
+        assert!(pcb.net_class_of("SDA").is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_project_net_classes() {
+        let project = r#"{"net_settings": {
+            "classes": [
+                {"name": "Default", "clearance": 0.2, "track_width": 0.25, "via_diameter": 0.6},
+                {"name": "HS", "clearance": 0.15, "diff_pair_width": 0.1, "diff_pair_gap": 0.12}
+            ],
+            "netclass_assignments": {"USB_D+": ["HS"], "USB_D-": "HS"}
+        }}"#;
+        let classes = parse_project_net_classes(project).unwrap();
+
+        assert_eq!(classes["Default"].via_diameter, Some(Length::mm(0.6)));
+        assert_eq!(classes["HS"].diff_pair_gap, Some(Length::mm(0.12)));
+        assert_eq!(classes["HS"].nets.len(), 2);
+    }
+
+    #[test]
+    fn test_graphic_geometry() {
+        let pcb = parse_pcb(BOARD).unwrap();
+
+        match &pcb.graphics[1] {
+            Graphic::Circle { radius, .. } => assert_eq!(*radius, Length::mm(5.0)),
+            other => panic!("expected circle, got {:?}", other),
+        }
+        match &pcb.graphics[2] {
+            Graphic::Arc { arc, .. } => {
+                assert!((arc.radius.as_mm() - 10.0).abs() < 1e-9);
+                assert!(arc.center.x.abs().as_mm() < 1e-9 && arc.center.y.abs().as_mm() < 1e-9);
+                assert!((arc.end_angle - arc.start_angle - 180.0).abs() < 1e-9);
+            }
+            other => panic!("expected arc, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_texts() {
+        let content = r#"(kicad_pcb
+  (gr_text "Delay\nline" (at 224.25 116 0) (layer "F.SilkS")
+    (effects (font (size 1 1.2) (thickness 0.2) (bold yes)) (justify left bottom)))
+  (gr_text_box "Notes" (start 89.75 36.25) (end 131 53) (margins 0.8 0.8 0.8 0.8)
+    (layer "Cmts.User") (effects (font (size 1 1) (thickness 0.15)))
+    (border yes) (stroke (width 0.1) (type solid)))
+  (footprint "R" (at 1 2) (fp_text reference "R1" (at 0 -1.5) (layer "F.SilkS")
+    (effects (font (size 1 1) (thickness 0.15) italic))))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        assert_eq!(pcb.texts.len(), 1);
+        let text = &pcb.texts[0];
+        assert_eq!(text.text, "Delay\nline");
+        assert_eq!(text.position, Point::new(224.25, 116.0));
+        assert_eq!(text.effects.font_size, Point::new(1.0, 1.2));
+        assert!(text.effects.bold && !text.effects.italic);
+        assert_eq!(text.effects.justify.as_deref(), Some("left bottom"));
+
+        let text_box = &pcb.text_boxes[0];
+        assert_eq!(text_box.text, "Notes");
+        assert_eq!(text_box.end, Point::new(131.0, 53.0));
+        assert_eq!(text_box.margins, Some([Length::mm(0.8); 4]));
+        assert!(text_box.border);
+        assert_eq!(text_box.border_width, Length::mm(0.1));
+
+        let fp_text = &pcb.footprints[0].field("Reference").unwrap().text;
+        assert_eq!(fp_text.text, "R1");
+        assert!(fp_text.effects.italic);
+    }
+
+    #[test]
+    fn test_rule_areas() {
+        let content = r#"(kicad_pcb
+  (zone (net 0) (net_name "") (layers "F.Cu" "In1.Cu") (uuid "ra-1") (name "Antenna")
+    (hatch edge 0.5)
+    (keepout (tracks not_allowed) (vias not_allowed) (pads allowed) (copperpour not_allowed) (footprints allowed))
+    (polygon (pts (xy 0 0) (xy 10 0) (xy 10 10) (xy 0 10))))
+  (zone (net 0) (net_name "") (layers "*.Cu") (uuid "ra-2")
+    (keepout (tracks allowed) (vias allowed) (pads allowed) (copperpour allowed) (footprints not_allowed))
+    (polygon (pts (xy 20 20) (xy 30 20) (xy 30 30))))
+  (zone (net 1) (net_name "GND") (layer "B.Cu") (polygon (pts (xy 0 0) (xy 1 0) (xy 1 1))))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        assert_eq!(pcb.zones.len(), 1);
+        assert_eq!(pcb.rule_areas.len(), 2);
+
+        let antenna = &pcb.rule_areas[0];
+        assert_eq!(antenna.name.as_deref(), Some("Antenna"));
+        assert_eq!(antenna.keepout, KeepoutRules { tracks: true, vias: true, pads: false, copper_pour: true, footprints: false });
+        assert!(antenna.applies_to_layer("In1.Cu"));
+        assert!(!antenna.applies_to_layer("B.Cu"));
+        assert!(antenna.contains(&Point::new(5.0, 5.0)));
+        assert!(!antenna.contains(&Point::new(15.0, 5.0)));
+
+        let placement = &pcb.rule_areas[1];
+        assert!(placement.keepout.footprints && !placement.keepout.tracks);
+        assert!(placement.applies_to_layer("B.Cu"));
+        assert!(!placement.applies_to_layer("F.SilkS"));
+    }
+
+    #[test]
+    fn test_zone_fill_settings() {
+        let content = r#"(kicad_pcb
+  (zone (net 1) (net_name "GND") (layer "F.Cu") (hatch edge 0.5)
+    (connect_pads (clearance 0.3)) (min_thickness 0.25)
+    (fill yes (mode hatch) (thermal_gap 0.4) (thermal_bridge_width 0.6) (island_removal_mode 2) (island_area_min 5)
+      (hatch_thickness 0.8) (hatch_gap 1.2) (hatch_orientation 45))
+    (polygon (pts (xy 0 0) (xy 10 0) (xy 10 10))))
+  (zone (net 1) (net_name "GND") (layer "B.Cu")
+    (fill yes (thermal_gap 0.5) (thermal_bridge_width 0.5) (island_removal_mode 1))
+    (polygon (pts (xy 0 0) (xy 10 0) (xy 10 10))))
+  (zone (net 1) (net_name "GND") (layer "In1.Cu") (polygon (pts (xy 0 0) (xy 1 0) (xy 1 1))))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        let hatched = &pcb.zones[0].fill;
+        assert_eq!(hatched.mode, ZoneFillMode::Hatch { thickness: Length::mm(0.8), gap: Length::mm(1.2), orientation: 45.0 });
+        assert_eq!((hatched.thermal_gap, hatched.thermal_bridge_width), (Length::mm(0.4), Length::mm(0.6)));
+        assert_eq!(hatched.island_removal, IslandRemoval::BelowArea(5.0));
+        assert_eq!(pcb.zones[1].fill.island_removal, IslandRemoval::Never);
+        assert_eq!(pcb.zones[2].fill, ZoneFill::default());
+
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        let fills: Vec<_> = written.zones.iter().map(|z| &z.fill).collect();
+        assert_eq!(fills, pcb.zones.iter().map(|z| &z.fill).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_board_metadata() {
+        let content = r#"(kicad_pcb
+  (version 20250401)
+  (generator "pcbnew")
+  (generator_version "9.99")
+  (general (thickness 1.6062) (legacy_teardrops no))
+  (paper "A4" portrait)
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        assert_eq!(pcb.board_thickness, Some(Length::mm(1.6062)));
+        assert_eq!(pcb.paper_size.as_deref(), Some("A4"));
+        assert_eq!(pcb.metadata.generator_version.as_deref(), Some("9.99"));
+        assert!(!pcb.metadata.legacy_teardrops);
+        assert!(pcb.metadata.paper_portrait);
+        assert_eq!(pcb.metadata.paper_custom_size, None);
+
+        let custom = parse_pcb(r#"(kicad_pcb (general (thickness 0.8) (legacy_teardrops yes)) (paper "User" 431.8 279.4))"#).unwrap();
+        assert!(custom.metadata.legacy_teardrops);
+        assert_eq!(custom.metadata.generator_version, None);
+        assert_eq!(custom.metadata.paper_custom_size, Some((431.8, 279.4)));
+    }
+
+    #[test]
+    fn test_stackup() {
+        let content = r#"(kicad_pcb
+  (general (thickness 1.6))
+  (setup
+    (stackup
+      (layer "F.Mask" (type "Top Solder Mask") (color "Green") (thickness 0.01))
+      (layer "F.Cu" (type "copper") (thickness 0.035))
+      (layer "dielectric 1" (type "prepreg") (thickness 0.2) (material "FR4") (epsilon_r 4.5) (loss_tangent 0.02))
+      (layer "In1.Cu" (type "copper") (thickness 0.0175))
+      (layer "dielectric 2" (type "core") (thickness 1.2))
+      (layer "B.Cu" (type "copper") (thickness 0.035))
+      (copper_finish "ENIG")
+      (dielectric_constraints no)
+      (edge_connector bevelled))
+    (pad_to_mask_clearance 0)
+    (aux_axis_origin 100 150))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        assert_eq!(pcb.stackup.len(), 6);
+        assert_eq!(pcb.metadata.aux_axis_origin, Some(Point::new(100.0, 150.0)));
+        assert_eq!(pcb.metadata.grid_origin, None);
+        let prepreg = &pcb.stackup[2];
+        assert_eq!(prepreg.layer_type, "prepreg");
+        assert_eq!(prepreg.material.as_deref(), Some("FR4"));
+        assert_eq!((prepreg.epsilon_r, prepreg.loss_tangent), (Some(4.5), Some(0.02)));
+        assert_eq!(pcb.stackup[0].color.as_deref(), Some("Green"));
+        assert_eq!(pcb.metadata.copper_finish.as_deref(), Some("ENIG"));
+        assert_eq!(pcb.metadata.edge_connector.as_deref(), Some("bevelled"));
+        assert!(!pcb.metadata.dielectric_constraints && !pcb.metadata.castellated_pads);
+
+        assert!((pcb.copper_distance("F.Cu", "In1.Cu").unwrap().as_mm() - 0.2525).abs() < 1e-9);
+        assert!((pcb.copper_distance("B.Cu", "F.Cu").unwrap().as_mm() - 1.4875).abs() < 1e-9);
+        assert_eq!(pcb.copper_distance("In1.Cu", "In1.Cu"), Some(Length::ZERO));
+        assert_eq!(pcb.copper_distance("F.Cu", "In5.Cu"), None);
+
+        let bare = parse_pcb(r#"(kicad_pcb (general (thickness 0.8)))"#).unwrap();
+        assert_eq!(bare.copper_distance("B.Cu", "F.Cu"), Some(Length::mm(0.8)));
+        assert_eq!(bare.copper_distance("F.Cu", "In1.Cu"), None);
+    }
+
+    #[test]
+    fn test_title_block() {
+        let content = r#"(kicad_pcb
+  (title_block
+    (title "CPArti: Artix-7 FPGA Development Board")
+    (date "2024-08-22")
+    (rev "1.0")
+    (company "AAWO")
+    (comment 1 "Reviewed")
+    (comment 4 "Fab: JLC04161H-7628")
+  )
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let title_block = pcb.title_block.unwrap();
+
+        assert_eq!(title_block.title, "CPArti: Artix-7 FPGA Development Board");
+        assert_eq!(title_block.date, "2024-08-22");
+        assert_eq!(title_block.revision, "1.0");
+        assert_eq!(title_block.company, "AAWO");
+        assert_eq!(title_block.comments.len(), 2);
+        assert_eq!(title_block.comments[&4], "Fab: JLC04161H-7628");
+
+        assert!(parse_pcb("(kicad_pcb (version 1))").unwrap().title_block.is_none());
+    }
+
+    #[test]
+    fn test_footprint_properties() {
+        let content = r#"(kicad_pcb
+  (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 10 10)
+    (property "Reference" "U3" (at 0 -3) (layer "F.SilkS"))
+    (property "Value" "LM358" (at 0 3) (layer "F.Fab"))
+    (property "Datasheet" "" (at 0 0) (layer "F.Fab") (hide yes))
+    (property "MPN" "LM358DR" (at 0 0) (layer "F.Fab") (hide yes))
+  )
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let fp = &pcb.footprints[0];
+
+        assert_eq!(fp.properties.len(), 4);
+        assert_eq!(fp.property("Reference"), Some("U3"));
+        assert_eq!(fp.property("Value"), Some("LM358"));
+        assert_eq!(fp.property("Datasheet"), Some(""));
+        assert_eq!(fp.property("MPN"), Some("LM358DR"));
+        assert_eq!(fp.property("LCSC"), None);
+    }
+
+    #[test]
+    fn test_footprint_attributes() {
+        let content = r#"(kicad_pcb
+  (footprint "R" (at 0 0) (attr smd))
+  (footprint "TP" (at 0 0) (attr through_hole exclude_from_pos_files exclude_from_bom))
+  (footprint "Logo" (at 0 0) (attr board_only exclude_from_pos_files exclude_from_bom))
+  (footprint "C" (at 0 0) (attr smd dnp))
+  (footprint "Legacy" (at 0 0) (attr virtual))
+  (footprint "Fiducial" (at 0 0) (attr smd allow_missing_courtyard) (dnp yes))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let attrs: Vec<_> = pcb.footprints.iter().map(|f| &f.attributes).collect();
+
+        assert_eq!(attrs[0].mount, "smd");
+        assert!(pcb.footprints[0].in_bom() && pcb.footprints[0].in_pos_files());
+
+        assert_eq!(attrs[1].mount, "through_hole");
+        assert!(attrs[1].exclude_from_bom && attrs[1].exclude_from_pos_files && !attrs[1].board_only);
+        assert!(!pcb.footprints[1].in_bom());
+
+        assert!(attrs[2].board_only);
+        assert_eq!(attrs[2].mount, "");
+
+        assert!(attrs[3].dnp);
+        assert!(pcb.footprints[3].in_bom());
+
+        assert!(attrs[4].board_only && !pcb.footprints[4].in_pos_files());
+        assert!(attrs[5].dnp && attrs[5].allow_missing_courtyard);
+    }
+
+    #[test]
+    fn test_mask_paste_margins() {
+        let content = r#"(kicad_pcb
+  (setup (pad_to_mask_clearance 0.05) (pad_to_paste_clearance -0.02) (pad_to_paste_clearance_ratio -0.05))
+  (footprint "QFN" (layer "F.Cu") (at 0 0) (property "Reference" "U1")
+    (solder_mask_margin 0.03) (solder_paste_ratio -0.1)
+    (pad "1" smd rect (at 0 0) (size 1 0.5) (layers "F.Cu" "F.Paste" "F.Mask"))
+    (pad "EP" smd rect (at 0 2) (size 2 2) (layers "F.Cu" "F.Paste" "F.Mask")
+      (solder_mask_margin 0) (solder_paste_margin_ratio -0.25)))
+  (footprint "R" (layer "F.Cu") (at 5 0) (property "Reference" "R1")
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu" "F.Paste" "F.Mask"))))"#;
+        let pcb = parse_pcb(content).unwrap();
+        assert_eq!(pcb.metadata.margins.solder_mask_margin, Some(Length::mm(0.05)));
+        let (u1, r1) = (&pcb.footprints[0], &pcb.footprints[1]);
+
+        // Pad inherits everything from its footprint, which defers the paste margin to the board
+        let pin = pcb.pad_margins(u1, &u1.pads[0]);
+        assert_eq!((pin.solder_mask_margin, pin.solder_paste_margin, pin.solder_paste_ratio), (Some(Length::mm(0.03)), Some(Length::mm(-0.02)), Some(-0.1)));
+        let paste = pin.paste_size(&u1.pads[0].size);
+        assert!((paste.x.as_mm() - 0.76).abs() < 1e-9 && (paste.y.as_mm() - 0.36).abs() < 1e-9);
+
+        // A pad's own zero margin still wins over its footprint's
+        let exposed = pcb.pad_margins(u1, &u1.pads[1]);
+        assert_eq!((exposed.solder_mask_margin, exposed.solder_paste_ratio), (Some(Length::ZERO), Some(-0.25)));
+        assert_eq!(exposed.mask_size(&u1.pads[1].size), Point::new(2.0, 2.0));
+        assert_eq!(pcb.pad_margins(r1, &r1.pads[0]), pcb.metadata.margins);
+
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(written.footprints[0].margins, u1.margins);
+        assert_eq!(written.footprints[0].pads[1].margins, u1.pads[1].margins);
+    }
+
+    #[test]
+    fn test_fp_text_and_property_fields() {
+        let content = r#"(kicad_pcb
+  (footprint "R_0603" (layer "F.Cu") (at 10 10)
+    (fp_text reference "R1" (at 0 -1.5) (layer "F.SilkS") (effects (font (size 1 1) (thickness 0.15))))
+    (fp_text value "10k" (at 0 1.5) (layer "F.Fab") hide (effects (font (size 1 1) (thickness 0.15))))
+    (fp_text user "${REFERENCE}" (at 0 0) (layer "F.Fab") (effects (font (size 0.5 0.5) (thickness 0.08)))))
+  (footprint "R_0603" (layer "F.Cu") (at 20 10)
+    (property "Reference" "R2" (at 0 -1.5 0) (layer "F.SilkS") (effects (font (size 1 1) (thickness 0.15))))
+    (property "Value" "10k" (at 0 1.5 0) (layer "F.Fab") (hide yes) (effects (font (size 1 1) (thickness 0.15))))
+    (property "MPN" "RC0603")))"#;
+        let pcb = parse_pcb(content).unwrap();
+        let (old, new) = (&pcb.footprints[0], &pcb.footprints[1]);
+        assert_eq!((old.reference(), old.value()), ("R1", "10k"));
+        assert_eq!((new.reference(), new.value()), ("R2", "10k"));
+        assert_eq!(new.property("MPN"), Some("RC0603"));
+        assert!(new.field("MPN").is_none());
+
+        // Both vintages place the fields the same way, and only user texts stay texts
+        for footprint in [old, new] {
+            assert_eq!(footprint.field("Reference").unwrap().text.position, Point::new(0.0, -1.5));
+            assert!(footprint.field("Value").unwrap().hidden);
+        }
+        assert_eq!(old.texts.len(), 1);
+        assert!(new.texts.is_empty());
+
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(written.footprints[0].fields, old.fields);
+        assert_eq!(written.footprints[0].texts, old.texts);
+        // Properties without a placement are written the way KiCad places new ones
+        assert_eq!(written.footprints[1].fields[..2], new.fields[..]);
+        assert_eq!(written.footprints[1].field("MPN"), Some(&FieldText::new("MPN", "RC0603")));
+    }
+
+    #[test]
+    fn test_bezier_graphics() {
+        let content = r#"(kicad_pcb
+  (gr_line (start 0 0) (end 20 0) (stroke (width 0.1) (type solid)) (layer "Edge.Cuts"))
+  (gr_curve (pts (xy 20 0) (xy 20 10) (xy 0 10) (xy 0 0)) (stroke (width 0.1) (type solid)) (layer "Edge.Cuts"))
+  (footprint "Logo" (layer "F.Cu") (at 50 50)
+    (fp_curve (pts (xy 0 0) (xy 1 1) (xy 2 1) (xy 3 0)) (stroke (width 0.15) (type solid)) (layer "F.SilkS"))))"#;
+        let pcb = parse_pcb(content).unwrap();
+        assert!(matches!(&pcb.graphics[1], Graphic::Bezier { control1, .. } if *control1 == Point::new(20.0, 10.0)));
+        assert!(matches!(&pcb.footprints[0].graphics[0], Graphic::Bezier { width, .. } if *width == Length::mm(0.15)));
+
+        // The curve closes the outline against the straight edge
+        let outline = pcb.board_outline();
+        assert_eq!(outline.len(), 1);
+        assert!((outline[0].area() - 120.0).abs() < 0.5);
+
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(written.graphics, pcb.graphics);
+        assert_eq!(written.footprints[0].graphics, pcb.footprints[0].graphics);
+    }
+
+    #[test]
+    fn test_plot_params() {
+        let pcb = parse_pcb(include_str!("../../assets/fpga.kicad_pcb")).unwrap();
+        let params = pcb.metadata.plot_params.as_ref().unwrap();
+        assert_eq!(params.format, PlotFormat::Gerber);
+        assert_eq!(params.output_directory, "Gerber/");
+        assert_eq!(params.drill_marks, DrillMarks::None);
+        assert!(params.gerber_extensions && !params.gerber_attributes && !params.mirror);
+        assert!(params.common_layers.is_empty());
+
+        let names: Vec<&str> = pcb.plot_layers().iter().map(|l| l.name.as_str()).collect();
+        for layer in ["F.Cu", "B.Cu", "In1.Cu", "F.Mask", "F.SilkS", "Edge.Cuts"] {
+            assert!(names.contains(&layer), "{} not plotted", layer);
+        }
+        assert!(!names.contains(&"F.Adhes"));
+
+        assert_eq!(layer_mask_ids("0x00010fc_ffffffff").unwrap().len(), 32 + 7);
+        assert_eq!(layer_mask_ids("0x0000000_00000005"), Some(vec![0, 2]));
+        assert_eq!(layer_mask_ids("0xzz"), None);
+    }
+
+    #[test]
+    fn test_via_finish() {
+        let content = r#"(kicad_pcb
+  (setup (tenting front) (covering (front no) (back no)))
+  (via (at 0 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu")
+    (tenting (front yes) (back yes)) (capping yes) (filling yes))
+  (via (at 2 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (plugging (back yes))))"#;
+        let pcb = parse_pcb(content).unwrap();
+        let defaults = pcb.metadata.via_defaults;
+        assert_eq!(defaults.tenting, ViaSides { front: Some(true), back: Some(false) });
+        assert_eq!(defaults.covering.front, Some(false));
+
+        let capped = pcb.via_finish(&pcb.vias[0]);
+        assert_eq!(capped.tenting, ViaSides { front: Some(true), back: Some(true) });
+        assert_eq!(capped.ipc_4761().as_deref(), Some("VII"));
+
+        // Tenting comes from the board, plugging from the via
+        let plugged = pcb.via_finish(&pcb.vias[1]);
+        assert_eq!(plugged.tenting, defaults.tenting);
+        assert_eq!((plugged.plugging.front, plugged.plugging.back), (Some(false), Some(true)));
+        assert_eq!(plugged.ipc_4761().as_deref(), Some("III-a"));
+        assert_eq!(ViaFinish::default().ipc_4761(), None);
+
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(written.vias[0].finish, pcb.vias[0].finish);
+        assert_eq!(written.vias[1].finish, pcb.vias[1].finish);
+    }
+
+    #[test]
+    fn test_footprint_models() {
+        let content = r#"(kicad_pcb
+  (footprint "Capacitor_SMD:C_0805" (at 10 10)
+    (model "${KICAD8_3DMODEL_DIR}/Capacitor_SMD.3dshapes/C_0805.step"
+      (offset (xyz 0 0.5 1)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 90)))
+    (model "legacy.wrl" hide (at (xyz 0.1 0 0)) (scale (xyz 2 2 2)) (rotate (xyz 0 0 0)))
+  )
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let models = &pcb.footprints[0].models;
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].path, "${KICAD8_3DMODEL_DIR}/Capacitor_SMD.3dshapes/C_0805.step");
+        assert_eq!(models[0].offset, [0.0, 0.5, 1.0]);
+        assert_eq!(models[0].rotate, [0.0, 0.0, 90.0]);
+        assert_eq!(models[0].model_type(), ModelType::Step);
+        assert!(!models[0].hide);
+
+        assert!(models[1].hide);
+        assert!((models[1].offset[0] - 2.54).abs() < 1e-9);
+        assert_eq!(models[1].scale, [2.0; 3]);
+        assert_eq!(models[1].model_type(), ModelType::Wrl);
+    }
+
+    #[test]
+    fn test_embedded_model_resolution() {
+        let content = r#"(kicad_pcb
+  (footprint "Custom:QFN" (at 0 0)
+    (model "kicad-embed://qfn.step" (offset (xyz 0 0 0)) (scale (xyz 1 1 1)) (rotate (xyz 0 0 0)))
+    (model "kicad-embed://board-level.step")
+    (model "kicad-embed://missing.step")
+    (model "${KICAD8_3DMODEL_DIR}/Package_DFN_QFN.3dshapes/QFN.step")
+    (embedded_files (file (name "qfn.step") (type model) (data |KLUv/QBYIQAAZGF0YQ==|)))
+  )
+  (embedded_files (file (name "board-level.step") (type model) (data |KLUv/QBYIQAAZGF0YQ==|)))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let fp = &pcb.footprints[0];
+
+        assert_eq!(pcb.embedded_files.len(), 1);
+        assert_eq!(fp.embedded_files.len(), 1);
+        assert_eq!(fp.models[0].embedded_name(), Some("qfn.step"));
+
+        let resolved: Vec<_> = fp
+            .models
+            .iter()
+            .map(|m| pcb.resolve_embedded_model(fp, m).map(|f| f.name.as_str()))
+            .collect();
+        assert_eq!(resolved, vec![Some("qfn.step"), Some("board-level.step"), None, None]);
+    }
+
+    #[test]
+    fn test_group_resolution() {
+        let content = r#"(kicad_pcb
+  (footprint "R" (uuid "fp-1") (at 0 0))
+  (footprint "C" (uuid "fp-2") (at 5 0))
+  (segment (start 0 0) (end 5 0) (width 0.2) (layer "F.Cu") (uuid "trk-1"))
+  (group "Inner" (uuid "grp-inner") (members "fp-2"))
+  (group "Power" (uuid "grp-outer") (locked yes) (members "fp-1" "trk-1" "grp-inner" "gfx-1"))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+
+        assert_eq!(pcb.groups.len(), 2);
+        let power = pcb.group("Power").unwrap();
+        assert!(power.locked);
+        assert_eq!(power.members.len(), 4);
+
+        let members = pcb.group_members(power);
+        assert_eq!(members.len(), 3);
+        assert!(matches!(members[0], GroupMember::Footprint(f) if f.name == "R"));
+        assert!(matches!(members[1], GroupMember::Track(_)));
+        assert!(matches!(members[2], GroupMember::Group(g) if g.name == "Inner"));
+
+        let names: Vec<_> = pcb.group_footprints(power).iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["R", "C"]);
+    }
+
+    #[test]
+    fn test_parse_image() {
+        // 1x1 PNG header (signature + IHDR) split across two data strings
+        let content = r#"(kicad_pcb
+  (image (at 120 80) (layer "F.SilkS") (scale 0.5) (uuid "img-1")
+    (data "iVBORw0KGgoAAAANSUhEUgAAAAEA" "AAABCAYAAAAfFcSJ"))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let image = &pcb.images[0];
+
+        assert_eq!(image.position, Point::new(120.0, 80.0));
+        assert_eq!(image.layer, "F.SilkS");
+        assert_eq!(image.scale, 0.5);
+        assert_eq!(image.uuid, "img-1");
+        assert!(image.is_png());
+        assert_eq!(image.png_size(), Some((1, 1)));
+    }
+
+    #[test]
+    fn test_board_outline_with_footprint_cutout() {
+        // Rectangular board with a slot drawn inside a footprint rotated by 90°
+        let content = r#"(kicad_pcb
+  (gr_rect (start 0 0) (end 50 40) (stroke (width 0.05) (type default)) (fill none) (layer "Edge.Cuts"))
+  (footprint "Slot" (layer "F.Cu") (at 25 20 90)
+    (fp_rect (start -5 -1) (end 5 1) (stroke (width 0.05) (type default)) (fill none) (layer "Edge.Cuts"))
+  )
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        let polygons = pcb.board_outline();
+
+        assert_eq!(polygons.len(), 1);
+        assert_eq!(polygons[0].cutouts.len(), 1);
+        assert!((polygons[0].area() - (2000.0 - 20.0)).abs() < 1e-9);
+        let (start, end) = bounds(&polygons[0].cutouts[0]);
+        assert!((start.x.as_mm() - 24.0).abs() < 1e-9 && (end.y.as_mm() - 25.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_tuning_pattern() {
+        let content = r#"(kicad_pcb
+  (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net 1) (uuid "m-1"))
+  (segment (start 1 0) (end 2 0) (width 0.2) (layer "F.Cu") (net 1) (uuid "other"))
+  (generated (uuid "tp-1") (type tuning_pattern) (name "Tuning Pattern") (layer "F.Cu")
+    (base_line (pts (xy 0 0) (xy 10 0)))
+    (corner_radius_percent 80) (end (xy 10 0)) (initial_side "left") (is_time_domain no)
+    (last_netname "DAC_D2") (last_status "too_short") (last_tuning "22.1000 mm")
+    (max_amplitude 1) (min_amplitude 0.2) (min_spacing 0.14) (origin (xy 0 0))
+    (rounded yes) (single_sided yes)
+    (target_delay 23) (target_delay_max 23.1) (target_delay_min 22.9)
+    (target_length 22.9) (target_length_max 23) (target_length_min 22.8)
+    (target_skew 0) (target_skew_max 0.1) (target_skew_min -0.1)
+    (tuning_mode "single") (members "m-1"))
+  (generated (uuid "tp-2") (type tuning_pattern) (layer "F.Cu")
+    (base_line (pts (xy 0 1) (xy 10 1))) (base_line_coupled (pts (xy 0 2) (xy 10 2)))
+    (last_netname "CLK_P") (target_skew 0) (target_skew_max 0.1) (target_skew_min -0.1)
+    (target_length 40) (target_length_max 41) (target_length_min 39)
+    (tuning_mode "diff_pair_skew"))
+)"#;
+        let pcb = parse_pcb(content).unwrap();
+        assert_eq!(pcb.tuning_patterns.len(), 2);
+
+        let single = &pcb.tuning_patterns[0];
+        assert_eq!(single.mode, TuningMode::Single);
+        assert_eq!(single.status, TuningStatus::TooShort);
+        assert_eq!(single.net.as_deref(), Some("DAC_D2"));
+        assert_eq!(single.end, Point::new(10.0, 0.0));
+        assert_eq!(single.base_line.len(), 2);
+        assert_eq!(single.meander.corner_radius_percent, 80.0);
+        assert_eq!(single.meander.initial_side, "left");
+        assert!(single.meander.single_sided);
+        assert_eq!(single.target_skew, None);
+        assert_eq!(single.target_delay, None);
+        let target = single.target_length.unwrap();
+        assert_eq!((target.min, target.target, target.max), (22.8, 22.9, 23.0));
+        assert!(!target.accepts(22.1));
+
+        let skew = &pcb.tuning_patterns[1];
+        assert_eq!(skew.mode, TuningMode::DiffPairSkew);
+        assert_eq!(skew.target_length, None);
+        assert_eq!(skew.target_skew.unwrap().max, 0.1);
+        assert_eq!(skew.base_line_coupled.as_ref().map(Vec::len), Some(2));
+
+        let tuned = pcb.tuned_nets();
+        assert_eq!(tuned.len(), 1);
+        assert_eq!(tuned["DAC_D2"].target, 22.9);
+        assert_eq!(pcb.tuning_patterns_for_net("CLK_P").count(), 1);
+        let tracks = pcb.tuning_tracks(single);
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].uuid, "m-1");
+    }
+
+    #[test]
+    fn test_board_stats() {
+        let content = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (1 "In1.Cu" signal) (2 "In2.Cu" signal) (31 "B.Cu" signal) (44 "Edge.Cuts" user))
+  (gr_rect (start 0 0) (end 20 10) (stroke (width 0.05) (type default)) (fill none) (layer "Edge.Cuts"))
+  (footprint "R" (layer "F.Cu") (at 5 5) (property "Reference" "R1")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu")))
+  (footprint "R" (layer "F.Cu") (at 5 8) (property "Reference" "R2")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu")))
+  (footprint "J" (layer "F.Cu") (at 15 5) (property "Reference" "J10")
+    (pad "1" thru_hole circle (at 0 0) (size 1.7 1.7) (drill 1) (layers "*.Cu"))
+    (pad "" np_thru_hole circle (at 3 0) (size 2 2) (drill 2) (layers "*.Cu")))
+  (segment (start 0 0) (end 3 4) (width 0.15) (layer "F.Cu"))
+  (segment (start 0 0) (end 0 5) (width 0.3) (layer "B.Cu"))
+  (arc (start 0 0) (mid 1 1) (end 2 0) (width 0.2) (layer "B.Cu"))
+  (via (at 1 1) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu"))
+  (via blind (at 2 1) (size 0.6) (drill 0.3) (layers "F.Cu" "In1.Cu"))
+  (via micro (at 3 1) (size 0.3) (drill 0.1) (layers "F.Cu" "In1.Cu"))
+)"#;
+        let stats = parse_pcb(content).unwrap().stats();
+
+        assert_eq!((stats.layer_count, stats.copper_layer_count), (5, 4));
+        assert_eq!(stats.component_count, 3);
+        assert_eq!(stats.components_by_prefix.get("R"), Some(&2));
+        assert_eq!(stats.components_by_prefix.get("J"), Some(&1));
+        assert_eq!(stats.pad_count, 5);
+        assert_eq!(stats.track_count, 3);
+        assert!((stats.track_length - (10.0 + std::f64::consts::PI)).abs() < 1e-9);
+        assert_eq!(stats.track_length_by_layer.get("F.Cu"), Some(&5.0));
+        assert_eq!(stats.via_count, 3);
+        assert_eq!(stats.vias_by_type.get(&ViaType::Through), Some(&1));
+        assert_eq!(stats.vias_by_type.get(&ViaType::BlindBuried), Some(&1));
+        assert_eq!(stats.vias_by_type.get(&ViaType::Micro), Some(&1));
+        assert_eq!(stats.min_drill, Some(0.1));
+        assert_eq!(stats.min_track_width, Some(0.15));
+        assert_eq!(stats.board_area, Some(200.0));
+
+        assert_eq!(parse_pcb("(kicad_pcb)").unwrap().stats().board_area, None);
+    }
+
+    #[test]
+    fn test_pads_absolute() {
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "R" (layer "F.Cu") (at 10 20 90) (property "Reference" "R1")
+    (pad "1" smd rect (at -1 0 90) (size 1 1) (layers "F.Cu"))
+    (pad "2" smd rect (at 1 0 90) (size 1 1) (layers "F.Cu")))
+  (footprint "C" (layer "B.Cu") (at 30 20 180)
+    (pad "1" smd rect (at 0.5 0 180) (size 1 1) (layers "B.Cu"))))"#).unwrap();
+        let pads: Vec<_> = pcb.pads_absolute().collect();
+
+        assert_eq!(pads.len(), 3);
+        assert_eq!((pads[0].reference, pads[0].pad.number.as_str()), ("R1", "1"));
+        assert!((pads[0].position.x.as_mm() - 10.0).abs() < 1e-9 && (pads[0].position.y.as_mm() - 21.0).abs() < 1e-9);
+        assert!((pads[1].position.y.as_mm() - 19.0).abs() < 1e-9);
+        assert_eq!(pads[1].rotation, 90.0);
+        assert!(!pads[1].bottom);
+        assert_eq!(pads[2].reference, "");
+        assert!(pads[2].bottom);
+        assert!((pads[2].position.x.as_mm() - 29.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_footprint_by_ref() {
+        let mut pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "R" (property "Reference" "R1") (property "Value" "10k"))
+  (footprint "Logo")
+  (footprint "U" (property "Reference" "U1")))"#).unwrap();
+
+        assert_eq!(pcb.refs().collect::<Vec<_>>(), ["R1", "U1"]);
+        assert_eq!(pcb.footprint_by_ref("R1").and_then(|f| f.property("Value")), Some("10k"));
+        assert_eq!(pcb.footprint_by_ref("U1").map(|f| f.name.as_str()), Some("U"));
+        assert!(pcb.footprint_by_ref("C1").is_none());
+
+        // Edits after indexing are still seen
+        pcb.footprints.remove(0);
+        pcb.footprints[1].properties.insert("Reference".to_string(), "U2".to_string());
+        assert!(pcb.footprint_by_ref("R1").is_none());
+        assert_eq!(pcb.footprint_by_ref("U2").map(|f| f.name.as_str()), Some("U"));
+        pcb.reindex();
+        assert!(pcb.footprint_by_ref("U1").is_none());
+        assert_eq!(pcb.clone(), pcb);
+    }
+
+    #[test]
+    fn test_progress_callback() {
+        use crate::progress::{ParseStage, Progress};
+        use std::cell::RefCell;
+
+        let content = include_str!("../../assets/fpga.kicad_pcb");
+        let reports: RefCell<Vec<Progress>> = RefCell::new(Vec::new());
+        let pcb = PcbParser::new(content).on_progress(|p| reports.borrow_mut().push(p)).parse().unwrap();
+        let reports = reports.into_inner();
+
+        assert!(reports.len() > 10);
+        assert!(reports.windows(2).all(|w| w[0].fraction() <= w[1].fraction()));
+        assert_eq!(reports[0].stage, ParseStage::Reading);
+        let last = reports.last().unwrap();
+        assert_eq!((last.stage, last.bytes_processed, last.fraction()), (ParseStage::Converting, content.len(), 1.0));
+        assert!(last.elements_parsed >= pcb.footprints.len() + pcb.tracks.len());
+    }
+
+    #[test]
+    fn test_cancel_mid_parse() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let content = include_str!("../../assets/fpga.kicad_pcb");
+        let cancel = AtomicBool::new(false);
+        let last = std::cell::Cell::new(0.0);
+        let result = PcbParser::new(content)
+            .cancel_on(&cancel)
+            .on_progress(|p| {
+                last.set(p.fraction());
+                if p.fraction() > 0.3 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            })
+            .parse();
+
+        assert!(matches!(result, Err(KicadError::Cancelled)));
+        assert!(last.get() < 0.6);
+        cancel.store(false, Ordering::Relaxed);
+        assert!(PcbParser::new(content).cancel_on(&cancel).parse().is_ok());
+    }
+
+    #[test]
+    fn test_config_limits() {
+        let content = include_str!("../../assets/fpga.kicad_pcb");
+        assert!(PcbParser::new(content).with_config(ParserConfig::untrusted()).parse().is_ok());
+        let strict = ParserConfig { strict: true, ..Default::default() };
+        assert!(PcbParser::new(content).with_config(strict).parse().is_ok());
+
+        let shallow = ParserConfig { max_depth: 3, ..Default::default() };
+        let err = PcbParser::new(content).with_config(shallow).parse().unwrap_err();
+        assert!(err.to_string().contains("nested deeper than 3"));
+        let few = ParserConfig { max_elements: 100, ..Default::default() };
+        let err = PcbParser::new(content).with_config(few).parse().unwrap_err();
+        assert!(err.to_string().contains("more than 100 board elements"));
+    }
+
+    #[test]
+    fn test_config_skipping_and_strict() {
+        let content = r#"(kicad_pcb (version 20240108)
+  (layers (0 "F.Cu" signal) (44 "Edge.Cuts" user))
+  (gr_line (start 0 0) (end 10 0) (layer "Edge.Cuts") (width 0.1))
+  (gr_line (start 0 5) (end 10 5) (layer "F.SilkS") (width 0.1))
+  (zone (net 0) (net_name "") (layer "F.Cu") (polygon (pts (xy 0 0) (xy 1 0) (xy 1 1))))
+  (dimension (type aligned) (layer "Dwgs.User"))
+  (teardrop_magic 1))"#;
+        let lenient = parse_pcb(content).unwrap();
+        assert_eq!((lenient.graphics.len(), lenient.zones.len()), (2, 1));
+        assert!(lenient.skipped.is_empty());
+
+        let config = ParserConfig { parse_zones: false, parse_graphics: false, keep_skipped: true, ..Default::default() };
+        let pcb = PcbParser::new(content).with_config(config).parse().unwrap();
+        assert_eq!((pcb.graphics.len(), pcb.zones.len()), (1, 0));
+        assert_eq!(pcb.graphics[0].layer(), "Edge.Cuts");
+        assert_eq!(pcb.skipped.len(), 4);
+        assert!(pcb.skipped[0].starts_with("(gr_line (start 0 5)"));
+        assert_eq!(pcb.skipped[3], "(teardrop_magic 1)");
+
+        let strict = ParserConfig { strict: true, ..Default::default() };
+        let err = PcbParser::new(content).with_config(strict.clone()).parse().unwrap_err();
+        assert!(matches!(err, KicadError::UnexpectedToken(ref m) if m.contains("teardrop_magic")));
+        let known = content.replace("\n  (teardrop_magic 1)", "");
+        assert!(PcbParser::new(&known).with_config(strict).parse().is_ok());
+    }
+
+    #[test]
+    fn test_rejects_non_board() {
+        assert!(parse_pcb("(kicad_symbol_lib (version 1))").is_err());
+        assert!(parse_pcb("(kicad_pcb (version 1)").is_err());
+    }
+}
@@ -0,0 +1,791 @@
+//! Selector queries over a parsed board
+//!
+//! A [`Selector`] is a list of steps separated by whitespace, each an element
+//! kind followed by attribute filters. Every step after the first searches the
+//! elements matched by the previous one, so `footprint[ref=U*] pad[net=GND]`
+//! finds the ground pads of the ICs. A step may be written with a leading `.`
+//! (`footprint .pad`), which means the same.
+//!
+//! Filters are `[attr]` (attribute present), `[attr=glob]`, `[attr!=glob]`
+//! and numeric comparisons `[attr<n]`, `[attr<=n]`, `[attr>n]`, `[attr>=n]`.
+//! Values may be quoted. `=` compares numbers numerically when both sides are
+//! numbers, and list attributes such as a pad's layers match when any entry does.
+//!
+//! ```rust
+//! use kiparse::pcb::{parse_pcb, query::Selector};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "GND") (net 2 "3V3")
+//!   (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 10 10)
+//!     (property "Reference" "U1")
+//!     (pad "4" smd rect (at -2 1) (size 1 0.5) (layers "F.Cu") (net 1 "GND"))
+//!     (pad "8" smd rect (at 2 -1) (size 1 0.5) (layers "F.Cu") (net 2 "3V3")))
+//! )"#)?;
+//! let selector = Selector::parse(r#"footprint[ref=U*] .pad[net="3V3"]"#)?;
+//! let pads = selector.select(&pcb);
+//!
+//! assert_eq!(pads.len(), 1);
+//! assert_eq!(pads[0].attr("number"), vec!["8".to_string()]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashSet;
+use std::fmt;
+
+use super::types::{
+    Footprint, Graphic, Layer, Model3D, Pad, PcbFile, Text, Track, TrackArc, Via, Zone,
+};
+use crate::error::{KicadError, Result};
+use crate::symbol::query::glob_match;
+use crate::units::Length;
+
+/// Kind of board element a selector step matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ElementKind {
+    Footprint,
+    Pad,
+    Track,
+    Arc,
+    Via,
+    Zone,
+    Net,
+    Text,
+    Graphic,
+    Model,
+    Layer,
+}
+
+impl ElementKind {
+    const ALL: [ElementKind; 11] = [
+        ElementKind::Footprint,
+        ElementKind::Pad,
+        ElementKind::Track,
+        ElementKind::Arc,
+        ElementKind::Via,
+        ElementKind::Zone,
+        ElementKind::Net,
+        ElementKind::Text,
+        ElementKind::Graphic,
+        ElementKind::Model,
+        ElementKind::Layer,
+    ];
+
+    /// Kind from its selector name; KiCad's own keywords are accepted too
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "footprint" | "module" => ElementKind::Footprint,
+            "pad" => ElementKind::Pad,
+            "track" | "segment" => ElementKind::Track,
+            "arc" => ElementKind::Arc,
+            "via" => ElementKind::Via,
+            "zone" => ElementKind::Zone,
+            "net" => ElementKind::Net,
+            "text" | "gr_text" | "fp_text" => ElementKind::Text,
+            "graphic" => ElementKind::Graphic,
+            "model" => ElementKind::Model,
+            "layer" => ElementKind::Layer,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ElementKind::Footprint => "footprint",
+            ElementKind::Pad => "pad",
+            ElementKind::Track => "track",
+            ElementKind::Arc => "arc",
+            ElementKind::Via => "via",
+            ElementKind::Zone => "zone",
+            ElementKind::Net => "net",
+            ElementKind::Text => "text",
+            ElementKind::Graphic => "graphic",
+            ElementKind::Model => "model",
+            ElementKind::Layer => "layer",
+        }
+    }
+
+    /// Kinds that can be searched for within an element of this kind
+    fn children(self) -> &'static [ElementKind] {
+        use ElementKind::*;
+        match self {
+            Footprint => &[Pad, Text, Graphic, Model],
+            Net => &[Pad, Track, Arc, Via, Zone],
+            Layer => &[Footprint, Pad, Track, Arc, Via, Zone, Text, Graphic],
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for ElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Comparison in an attribute filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterOp {
+    Exists,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// One `[attr op value]` filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    pub attr: String,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+impl Filter {
+    /// Whether an element with these attribute values passes the filter
+    pub fn accepts(&self, values: &[String]) -> bool {
+        let number = self.value.parse::<f64>().ok();
+        let compare = |v: &String, test: fn(f64, f64) -> bool| match (v.parse::<f64>(), number) {
+            (Ok(v), Some(n)) => test(v, n),
+            _ => false,
+        };
+        let equal = |v: &String| match (v.parse::<f64>(), number) {
+            (Ok(v), Some(n)) => (v - n).abs() < 1e-9,
+            // Wildcard layers such as `*.Cu` match the concrete layer asked for
+            _ => glob_match(&self.value, v) || (v.starts_with('*') && glob_match(v, &self.value)),
+        };
+        match self.op {
+            FilterOp::Exists => !values.is_empty(),
+            FilterOp::Eq => values.iter().any(equal),
+            FilterOp::Ne => !values.iter().any(equal),
+            FilterOp::Lt => values.iter().any(|v| compare(v, |a, b| a < b)),
+            FilterOp::Le => values.iter().any(|v| compare(v, |a, b| a <= b)),
+            FilterOp::Gt => values.iter().any(|v| compare(v, |a, b| a > b)),
+            FilterOp::Ge => values.iter().any(|v| compare(v, |a, b| a >= b)),
+        }
+    }
+}
+
+/// One step of a selector: an element kind and its filters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step {
+    pub kind: ElementKind,
+    pub filters: Vec<Filter>,
+}
+
+/// Parsed selector; see the [module docs](self) for the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub steps: Vec<Step>,
+}
+
+impl Selector {
+    pub fn parse(input: &str) -> Result<Self> {
+        let chars: Vec<char> = input.chars().collect();
+        let mut i = 0;
+        let mut steps: Vec<Step> = Vec::new();
+        loop {
+            while i < chars.len() && (chars[i].is_whitespace() || chars[i] == '.') {
+                i += 1;
+            }
+            if i == chars.len() {
+                break;
+            }
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let name: String = chars[start..i].iter().collect();
+            let kind = ElementKind::from_name(&name).ok_or_else(|| {
+                let known: Vec<&str> = ElementKind::ALL.iter().map(|k| k.name()).collect();
+                error(
+                    start,
+                    format!(
+                        "expected an element kind ({}), found '{}'",
+                        known.join(", "),
+                        if name.is_empty() {
+                            chars[start].to_string()
+                        } else {
+                            name
+                        }
+                    ),
+                )
+            })?;
+            if let Some(parent) = steps.last() {
+                if !parent.kind.children().contains(&kind) {
+                    return Err(error(
+                        start,
+                        format!("{} elements have no {} children", parent.kind, kind),
+                    ));
+                }
+            }
+            let mut filters = Vec::new();
+            while chars.get(i) == Some(&'[') {
+                let (filter, end) = parse_filter(&chars, i + 1)?;
+                filters.push(filter);
+                i = end;
+            }
+            if i < chars.len() && !chars[i].is_whitespace() && chars[i] != '.' {
+                return Err(error(i, format!("unexpected '{}'", chars[i])));
+            }
+            steps.push(Step { kind, filters });
+        }
+        if steps.is_empty() {
+            return Err(KicadError::ParseError("Empty selector".to_string()));
+        }
+        Ok(Selector { steps })
+    }
+
+    /// Elements matched by the last step, in board order, each listed once
+    pub fn select<'a>(&self, pcb: &'a PcbFile) -> Vec<Element<'a>> {
+        let mut current: Vec<Element<'a>> = Vec::new();
+        for (n, step) in self.steps.iter().enumerate() {
+            let candidates = if n == 0 {
+                top_level(pcb, step.kind)
+            } else {
+                current
+                    .iter()
+                    .flat_map(|parent| children(pcb, parent, step.kind))
+                    .collect()
+            };
+            let mut seen = HashSet::new();
+            current = candidates
+                .into_iter()
+                .filter(|e| step.filters.iter().all(|f| f.accepts(&e.attr(&f.attr))))
+                .filter(|e| seen.insert(e.identity()))
+                .collect();
+        }
+        current
+    }
+}
+
+fn error(position: usize, message: String) -> KicadError {
+    KicadError::ParseError(format!("selector column {}: {}", position + 1, message))
+}
+
+/// Parse `attr op value]` starting after the `[`; returns the filter and the index after `]`
+fn parse_filter(chars: &[char], mut i: usize) -> Result<(Filter, usize)> {
+    let skip_space = |i: &mut usize| {
+        while *i < chars.len() && chars[*i].is_whitespace() {
+            *i += 1;
+        }
+    };
+    skip_space(&mut i);
+    let start = i;
+    while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '-' | '.')) {
+        i += 1;
+    }
+    let attr: String = chars[start..i].iter().collect();
+    if attr.is_empty() {
+        return Err(error(start, "expected an attribute name".to_string()));
+    }
+    skip_space(&mut i);
+    let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+    let (op, width) = match (two.as_str(), chars.get(i)) {
+        ("!=", _) => (FilterOp::Ne, 2),
+        ("<=", _) => (FilterOp::Le, 2),
+        (">=", _) => (FilterOp::Ge, 2),
+        (_, Some('=')) => (FilterOp::Eq, 1),
+        (_, Some('<')) => (FilterOp::Lt, 1),
+        (_, Some('>')) => (FilterOp::Gt, 1),
+        (_, Some(']')) => {
+            let filter = Filter {
+                attr,
+                op: FilterOp::Exists,
+                value: String::new(),
+            };
+            return Ok((filter, i + 1));
+        }
+        _ => {
+            return Err(error(
+                i,
+                "expected '=', '!=', '<', '<=', '>', '>=' or ']'".to_string(),
+            ))
+        }
+    };
+    i += width;
+    skip_space(&mut i);
+
+    let value = if let Some(&quote @ ('"' | '\'')) = chars.get(i) {
+        let start = i + 1;
+        let end = chars[start..]
+            .iter()
+            .position(|&c| c == quote)
+            .map(|p| start + p)
+            .ok_or_else(|| error(i, "unterminated string".to_string()))?;
+        i = end + 1;
+        chars[start..end].iter().collect()
+    } else {
+        let start = i;
+        while i < chars.len() && chars[i] != ']' {
+            i += 1;
+        }
+        chars[start..i]
+            .iter()
+            .collect::<String>()
+            .trim_end()
+            .to_string()
+    };
+    skip_space(&mut i);
+    if chars.get(i) != Some(&']') {
+        return Err(error(i, "expected ']'".to_string()));
+    }
+    Ok((Filter { attr, op, value }, i + 1))
+}
+
+/// Board element matched by a selector
+///
+/// Serializes as the underlying element with a `"type"` tag; pads, texts,
+/// graphics and models inside a footprint carry its reference.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
+pub enum Element<'a> {
+    Footprint(&'a Footprint),
+    Pad {
+        reference: &'a str,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        pad: &'a Pad,
+        #[cfg_attr(feature = "serde", serde(skip))]
+        footprint: &'a Footprint,
+    },
+    Track(&'a Track),
+    Arc(&'a TrackArc),
+    Via(&'a Via),
+    Zone(&'a Zone),
+    Net {
+        id: u32,
+        name: &'a str,
+    },
+    Text {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        reference: Option<&'a str>,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        text: &'a Text,
+    },
+    Graphic {
+        #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+        reference: Option<&'a str>,
+        graphic: &'a Graphic,
+    },
+    Model {
+        reference: &'a str,
+        #[cfg_attr(feature = "serde", serde(flatten))]
+        model: &'a Model3D,
+    },
+    Layer(&'a Layer),
+}
+
+impl<'a> Element<'a> {
+    pub fn kind(&self) -> ElementKind {
+        match self {
+            Element::Footprint(_) => ElementKind::Footprint,
+            Element::Pad { .. } => ElementKind::Pad,
+            Element::Track(_) => ElementKind::Track,
+            Element::Arc(_) => ElementKind::Arc,
+            Element::Via(_) => ElementKind::Via,
+            Element::Zone(_) => ElementKind::Zone,
+            Element::Net { .. } => ElementKind::Net,
+            Element::Text { .. } => ElementKind::Text,
+            Element::Graphic { .. } => ElementKind::Graphic,
+            Element::Model { .. } => ElementKind::Model,
+            Element::Layer(_) => ElementKind::Layer,
+        }
+    }
+
+    /// Values of an attribute, empty when the element does not have it
+    ///
+    /// Footprint attributes: `ref`, `value`, `footprint`, `layer`, `side`
+    /// (`top`/`bottom`), `x`, `y`, `rotation`, `mount`, `dnp`, `locked`, `pads`
+    /// and any property by name. Pads: `ref`, `number`, `net`, `type`, `shape`,
+    /// `layer`, `x`, `y` (on the board), `width`, `height`, `drill`,
+    /// `pin_function`, `pin_type`. Tracks and arcs: `net`, `layer`, `width`,
+    /// `length`. Vias: `net`, `type`, `size`, `drill`, `layer`, `x`, `y`.
+    /// Zones: `net`, `layer`, `priority`. Nets: `name`, `id`. Texts: `text`,
+    /// `layer`, `x`, `y`. Graphics: `type`, `layer`, `width`. Models: `ref`,
+    /// `path`, `hide`. Layers: `name`, `id`, `type`, `user_name`. Most elements
+    /// also have `uuid`.
+    pub fn attr(&self, name: &str) -> Vec<String> {
+        let one = |value: &str| vec![value.to_string()];
+        let some = |value: Option<&str>| value.map(|v| vec![v.to_string()]).unwrap_or_default();
+        let num = |value: f64| vec![value.to_string()];
+        let len = |value: Length| num(value.as_mm());
+        let flag = |value: bool| vec![value.to_string()];
+        match self {
+            Element::Footprint(fp) => match name {
+                "ref" | "reference" => some(fp.property("Reference")),
+                "value" => some(fp.property("Value")),
+                "footprint" | "lib_id" => one(&fp.name),
+                "layer" => one(&fp.layer),
+                "side" => one(if fp.layer.starts_with("B.") {
+                    "bottom"
+                } else {
+                    "top"
+                }),
+                "x" => len(fp.position.x),
+                "y" => len(fp.position.y),
+                "rotation" => num(fp.rotation),
+                "mount" => some(Some(fp.attributes.mount.as_str()).filter(|m| !m.is_empty())),
+                "dnp" => flag(fp.attributes.dnp),
+                "locked" => flag(fp.locked),
+                "pads" => num(fp.pads.len() as f64),
+                "uuid" => one(&fp.uuid),
+                _ => some(fp.property(name).or_else(|| {
+                    fp.properties
+                        .iter()
+                        .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                        .map(|(_, v)| v.as_str())
+                })),
+            },
+            Element::Pad {
+                reference,
+                pad,
+                footprint,
+            } => match name {
+                "ref" | "reference" => one(reference),
+                "number" | "pin" => one(&pad.number),
+                "net" => some(pad.net.as_deref()),
+                "type" => one(&pad.pad_type),
+                "shape" => one(&pad.shape),
+                "layer" => pad.layers.clone(),
+                "x" => len(footprint.pad_position(pad).x),
+                "y" => len(footprint.pad_position(pad).y),
+                "width" => len(pad.size.x),
+                "height" => len(pad.size.y),
+                "drill" => pad.drill.map(len).unwrap_or_default(),
+                "pin_function" => some(pad.pin_function.as_deref()),
+                "pin_type" => some(pad.pin_type.as_deref()),
+                _ => Vec::new(),
+            },
+            Element::Track(track) => match name {
+                "net" => some(track.net.as_deref()),
+                "layer" => one(&track.layer),
+                "width" => len(track.width),
+                "length" => num(track.length()),
+                "uuid" => one(&track.uuid),
+                _ => Vec::new(),
+            },
+            Element::Arc(arc) => match name {
+                "net" => some(arc.net.as_deref()),
+                "layer" => one(&arc.layer),
+                "width" => len(arc.width),
+                "length" => num(arc.length()),
+                "uuid" => one(&arc.uuid),
+                _ => Vec::new(),
+            },
+            Element::Via(via) => match name {
+                "net" => some(via.net.as_deref()),
+                "type" => one(match via.via_type {
+                    super::types::ViaType::Through => "through",
+                    super::types::ViaType::BlindBuried => "blind",
+                    super::types::ViaType::Micro => "micro",
+                }),
+                "size" => len(via.size),
+                "drill" => len(via.drill),
+                "layer" => via.layers.clone(),
+                "x" => len(via.position.x),
+                "y" => len(via.position.y),
+                "uuid" => one(&via.uuid),
+                _ => Vec::new(),
+            },
+            Element::Zone(zone) => match name {
+                "net" => some(zone.net.as_deref()),
+                "layer" => zone.layers.clone(),
+                "priority" => num(zone.priority as f64),
+                "uuid" => one(&zone.uuid),
+                _ => Vec::new(),
+            },
+            Element::Net { id, name: net } => match name {
+                "name" | "net" => one(net),
+                "id" => num(*id as f64),
+                _ => Vec::new(),
+            },
+            Element::Text { reference, text } => match name {
+                "ref" | "reference" => some(*reference),
+                "text" => one(&text.text),
+                "layer" => one(&text.layer),
+                "x" => len(text.position.x),
+                "y" => len(text.position.y),
+                _ => Vec::new(),
+            },
+            Element::Graphic { reference, graphic } => match name {
+                "ref" | "reference" => some(*reference),
+                "type" => one(match graphic {
+                    Graphic::Line { .. } => "line",
+                    Graphic::Circle { .. } => "circle",
+                    Graphic::Arc { .. } => "arc",
+                    Graphic::Rectangle { .. } => "rectangle",
+                    Graphic::Polygon { .. } => "polygon",
+                    Graphic::Bezier { .. } => "bezier",
+                }),
+                "layer" => one(graphic.layer()),
+                "width" => len(match graphic {
+                    Graphic::Line { width, .. }
+                    | Graphic::Circle { width, .. }
+                    | Graphic::Arc { width, .. }
+                    | Graphic::Rectangle { width, .. }
+                    | Graphic::Polygon { width, .. }
+                    | Graphic::Bezier { width, .. } => *width,
+                }),
+                _ => Vec::new(),
+            },
+            Element::Model { reference, model } => match name {
+                "ref" | "reference" => one(reference),
+                "path" => one(&model.path),
+                "hide" => flag(model.hide),
+                _ => Vec::new(),
+            },
+            Element::Layer(layer) => match name {
+                "name" => one(&layer.name),
+                "id" => num(layer.id as f64),
+                "type" => one(&layer.layer_type),
+                "user_name" => some(layer.user_name.as_deref()),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    /// Address of the underlying board item, for de-duplication
+    fn identity(&self) -> (ElementKind, usize) {
+        let address = match self {
+            Element::Footprint(fp) => *fp as *const Footprint as usize,
+            Element::Pad { pad, .. } => *pad as *const Pad as usize,
+            Element::Track(track) => *track as *const Track as usize,
+            Element::Arc(arc) => *arc as *const TrackArc as usize,
+            Element::Via(via) => *via as *const Via as usize,
+            Element::Zone(zone) => *zone as *const Zone as usize,
+            Element::Net { id, .. } => *id as usize,
+            Element::Text { text, .. } => *text as *const Text as usize,
+            Element::Graphic { graphic, .. } => *graphic as *const Graphic as usize,
+            Element::Model { model, .. } => *model as *const Model3D as usize,
+            Element::Layer(layer) => *layer as *const Layer as usize,
+        };
+        (self.kind(), address)
+    }
+
+    /// Whether the element is on `layer`; pad and zone wildcards such as `*.Cu` match
+    pub fn on_layer(&self, layer: &str) -> bool {
+        let on = |l: &String| l == layer || (l.starts_with('*') && glob_match(l, layer));
+        match self {
+            Element::Footprint(fp) => fp.layer == layer,
+            Element::Pad { pad, .. } => pad.layers.iter().any(on),
+            Element::Track(track) => track.layer == layer,
+            Element::Arc(arc) => arc.layer == layer,
+            Element::Via(via) => via_spans(via, layer),
+            Element::Zone(zone) => zone.layers.iter().any(on),
+            Element::Text { text, .. } => text.layer == layer,
+            Element::Graphic { graphic, .. } => graphic.layer() == layer,
+            _ => false,
+        }
+    }
+}
+
+/// Whether a via's copper is on `layer`; through vias are on every copper layer
+fn via_spans(via: &Via, layer: &str) -> bool {
+    via.layers.iter().any(|l| l == layer)
+        || (via.via_type == super::types::ViaType::Through && layer.ends_with(".Cu"))
+}
+
+fn reference(footprint: &Footprint) -> &str {
+    footprint.reference()
+}
+
+fn footprint_pads(footprint: &Footprint) -> impl Iterator<Item = Element<'_>> {
+    footprint.pads.iter().map(move |pad| Element::Pad {
+        reference: reference(footprint),
+        pad,
+        footprint,
+    })
+}
+
+/// Every element of a kind on the board, in file order
+fn top_level(pcb: &PcbFile, kind: ElementKind) -> Vec<Element<'_>> {
+    match kind {
+        ElementKind::Footprint => pcb.footprints.iter().map(Element::Footprint).collect(),
+        ElementKind::Pad => pcb.footprints.iter().flat_map(footprint_pads).collect(),
+        ElementKind::Track => pcb.tracks.iter().map(Element::Track).collect(),
+        ElementKind::Arc => pcb.arcs.iter().map(Element::Arc).collect(),
+        ElementKind::Via => pcb.vias.iter().map(Element::Via).collect(),
+        ElementKind::Zone => pcb.zones.iter().map(Element::Zone).collect(),
+        ElementKind::Net => {
+            let mut nets: Vec<(&u32, &String)> = pcb
+                .nets
+                .iter()
+                .filter(|(_, name)| !name.is_empty())
+                .collect();
+            nets.sort();
+            nets.into_iter()
+                .map(|(&id, name)| Element::Net { id, name })
+                .collect()
+        }
+        ElementKind::Text => pcb
+            .texts
+            .iter()
+            .map(|text| Element::Text {
+                reference: None,
+                text,
+            })
+            .collect(),
+        ElementKind::Graphic => pcb
+            .graphics
+            .iter()
+            .map(|graphic| Element::Graphic {
+                reference: None,
+                graphic,
+            })
+            .collect(),
+        ElementKind::Model => pcb
+            .footprints
+            .iter()
+            .flat_map(|fp| {
+                fp.models.iter().map(move |model| Element::Model {
+                    reference: reference(fp),
+                    model,
+                })
+            })
+            .collect(),
+        ElementKind::Layer => {
+            let mut layers: Vec<&Layer> = pcb.layers.values().collect();
+            layers.sort_by_key(|l| l.id);
+            layers.into_iter().map(Element::Layer).collect()
+        }
+    }
+}
+
+/// Elements of `kind` within `parent`
+fn children<'a>(pcb: &'a PcbFile, parent: &Element<'a>, kind: ElementKind) -> Vec<Element<'a>> {
+    match *parent {
+        Element::Footprint(fp) => {
+            let name = reference(fp);
+            let reference = Some(name);
+            match kind {
+                ElementKind::Pad => footprint_pads(fp).collect(),
+                ElementKind::Text => fp
+                    .fields
+                    .iter()
+                    .map(|field| &field.text)
+                    .chain(&fp.texts)
+                    .map(|text| Element::Text { reference, text })
+                    .collect(),
+                ElementKind::Graphic => fp
+                    .graphics
+                    .iter()
+                    .map(|graphic| Element::Graphic { reference, graphic })
+                    .collect(),
+                ElementKind::Model => fp
+                    .models
+                    .iter()
+                    .map(|model| Element::Model {
+                        reference: name,
+                        model,
+                    })
+                    .collect(),
+                _ => Vec::new(),
+            }
+        }
+        Element::Net { name, .. } => top_level(pcb, kind)
+            .into_iter()
+            .filter(|e| e.attr("net").iter().any(|n| n == name))
+            .collect(),
+        Element::Layer(layer) => {
+            let mut found: Vec<Element<'a>> = top_level(pcb, kind)
+                .into_iter()
+                .filter(|e| e.on_layer(&layer.name))
+                .collect();
+            // Footprint texts and graphics are on a layer as much as board ones
+            if matches!(kind, ElementKind::Text | ElementKind::Graphic) {
+                for fp in &pcb.footprints {
+                    found.extend(
+                        children(pcb, &Element::Footprint(fp), kind)
+                            .into_iter()
+                            .filter(|e| e.on_layer(&layer.name)),
+                    );
+                }
+            }
+            found
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal) (44 "Edge.Cuts" user))
+  (net 0 "") (net 1 "GND") (net 2 "3V3")
+  (footprint "Package_SO:SOIC-8" (layer "F.Cu") (at 10 10)
+    (property "Reference" "U1") (property "Value" "LM358")
+    (pad "4" smd rect (at -2 1) (size 1 0.5) (layers "F.Cu") (net 1 "GND"))
+    (pad "8" smd rect (at 2 -1) (size 1 0.5) (layers "F.Cu") (net 2 "3V3")))
+  (footprint "R_0603" (layer "B.Cu") (at 20 10) (property "Reference" "R1")
+    (pad "1" thru_hole circle (at -1 0) (size 1 1) (drill 0.6) (layers "*.Cu") (net 1 "GND"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "B.Cu") (net 2 "3V3")))
+  (segment (start 0 0) (end 10 0) (width 0.25) (layer "F.Cu") (net 1))
+  (segment (start 0 0) (end 5 0) (width 0.5) (layer "B.Cu") (net 2))
+  (via (at 5 5) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+)"#;
+
+    fn select<'a>(pcb: &'a PcbFile, selector: &str) -> Vec<Element<'a>> {
+        Selector::parse(selector).unwrap().select(pcb)
+    }
+
+    fn refs(elements: &[Element]) -> Vec<String> {
+        elements
+            .iter()
+            .map(|e| format!("{}.{}", e.attr("ref").join(""), e.attr("number").join("")))
+            .collect()
+    }
+
+    #[test]
+    fn test_selectors() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        assert_eq!(select(&pcb, "footprint").len(), 2);
+        assert_eq!(
+            refs(&select(&pcb, "footprint[ref=U*] .pad[net=\"3V3\"]")),
+            ["U1.8"]
+        );
+        assert_eq!(refs(&select(&pcb, "pad[net=GND]")), ["U1.4", "R1.1"]);
+        assert_eq!(refs(&select(&pcb, "net[name=3V3] pad")), ["U1.8", "R1.2"]);
+        // `*.Cu` pads are on every copper layer
+        assert_eq!(
+            refs(&select(&pcb, "layer[name=B.Cu] pad")),
+            ["R1.1", "R1.2"]
+        );
+        assert_eq!(refs(&select(&pcb, "pad[layer=B.Cu][drill]")), ["R1.1"]);
+        assert_eq!(
+            select(&pcb, "footprint[side=bottom]")[0].attr("ref"),
+            ["R1"]
+        );
+        assert_eq!(select(&pcb, "footprint[Value=LM358]").len(), 1);
+        assert_eq!(select(&pcb, "track[width>0.3]")[0].attr("layer"), ["B.Cu"]);
+        assert_eq!(select(&pcb, "track[width=0.25]").len(), 1);
+        assert_eq!(select(&pcb, "track[net!=GND]").len(), 1);
+        assert_eq!(select(&pcb, "net[name=GND] via").len(), 1);
+        // Layer steps list each item once even when the parent step matched several layers
+        assert_eq!(select(&pcb, "layer[type=signal] via").len(), 1);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let message = |s: &str| Selector::parse(s).unwrap_err().to_string();
+        assert!(message("footprint[ref=U1").contains("expected ']'"));
+        assert!(message("part").contains("expected an element kind"));
+        assert!(message("via pad").contains("via elements have no pad children"));
+        assert!(message("footprint[ref~U1]").contains("column 14"));
+        assert!(message("  ").contains("Empty selector"));
+    }
+
+    #[test]
+    fn test_serialize() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let pads = select(&pcb, "pad[number=8]");
+        let json = serde_json::to_value(&pads).unwrap();
+        assert_eq!(json[0]["type"], "pad");
+        assert_eq!(json[0]["reference"], "U1");
+        assert_eq!(json[0]["net"], "3V3");
+    }
+}
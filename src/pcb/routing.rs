@@ -0,0 +1,305 @@
+//! Routing statistics per net
+//!
+//! Summarises the tracks and arcs of each net: how many segments it has, the
+//! narrowest and widest of them and how much copper was routed. Net lengths add
+//! via barrels measured through the stackup and the pad-to-die length inside
+//! packages, and differential pairs are compared for skew.
+//!
+//! ```rust
+//! use kiparse::pcb::{parse_pcb, routing::net_track_stats};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (net 0 "") (net 1 "+5V")
+//!   (segment (start 0 0) (end 10 0) (width 0.5) (layer "F.Cu") (net 1))
+//!   (segment (start 10 0) (end 10 2) (width 0.15) (layer "F.Cu") (net 1))
+//! )"#)?;
+//! let stats = net_track_stats(&pcb);
+//!
+//! assert_eq!(stats[0].net, "+5V");
+//! assert_eq!(stats[0].min_width, 0.15);
+//! assert_eq!(stats[0].total_length, 12.0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use super::types::PcbFile;
+use crate::units::Length;
+
+/// Track width and length summary of one net
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetTrackStats {
+    pub net: String,
+    /// Number of track segments and arcs
+    pub segments: usize,
+    pub min_width: f64,
+    pub max_width: f64,
+    /// Width averaged over the routed length, so short stubs weigh little
+    pub average_width: f64,
+    /// Routed centreline length in mm across all layers
+    pub total_length: f64,
+}
+
+impl NetTrackStats {
+    /// Whether any segment of the net is narrower than `width`
+    pub fn has_segment_narrower_than(&self, width: f64) -> bool {
+        self.min_width < width
+    }
+}
+
+/// Track statistics of every net with routed copper, sorted by net name
+///
+/// Tracks without a net are skipped. Vias are not counted here; see [`net_lengths`].
+pub fn net_track_stats(pcb: &PcbFile) -> Vec<NetTrackStats> {
+    let segments = pcb
+        .tracks
+        .iter()
+        .map(|t| (&t.net, t.width.as_mm(), t.length()))
+        .chain(pcb.arcs.iter().map(|a| (&a.net, a.width.as_mm(), a.length())));
+
+    let mut stats: BTreeMap<&str, NetTrackStats> = BTreeMap::new();
+    for (net, width, length) in segments {
+        let Some(net) = net.as_deref() else { continue };
+        let entry = stats.entry(net).or_insert_with(|| NetTrackStats {
+            net: net.to_string(),
+            segments: 0,
+            min_width: f64::INFINITY,
+            max_width: 0.0,
+            average_width: 0.0,
+            total_length: 0.0,
+        });
+        entry.segments += 1;
+        entry.min_width = entry.min_width.min(width);
+        entry.max_width = entry.max_width.max(width);
+        // Accumulates width * length until normalised below
+        entry.average_width += width * length;
+        entry.total_length += length;
+    }
+
+    stats
+        .into_values()
+        .map(|mut s| {
+            s.average_width = if s.total_length > 0.0 {
+                s.average_width / s.total_length
+            } else {
+                s.min_width
+            };
+            s
+        })
+        .collect()
+}
+
+/// Routed length of one net
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetLength {
+    pub net: String,
+    /// Length of tracks and arcs in mm
+    pub track_length: f64,
+    /// Summed barrel length of the net's vias in mm
+    pub via_length: f64,
+    pub via_count: usize,
+    /// Summed pad-to-die length of the net's pads in mm
+    pub die_length: f64,
+}
+
+impl NetLength {
+    /// Track, via and die length in mm
+    pub fn total(&self) -> f64 {
+        self.track_length + self.via_length + self.die_length
+    }
+}
+
+/// Length mismatch between the two members of a differential pair
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffPairSkew {
+    pub positive: NetLength,
+    pub negative: NetLength,
+}
+
+impl DiffPairSkew {
+    /// Positive minus negative total length in mm
+    pub fn skew(&self) -> f64 {
+        self.positive.total() - self.negative.total()
+    }
+}
+
+/// Routed length of every net with tracks, vias or pad die lengths, sorted by net name
+///
+/// Each via is counted with the full span of its drilled layers, taken from the
+/// stackup (see [`PcbFile::copper_distance`]). Vias whose span is unknown, such as
+/// blind vias on a board without stackup, add no length but are still counted.
+/// Pads add their `die_length`, so packages with internal bond wires are matched
+/// the way KiCad's length tuner does.
+pub fn net_lengths(pcb: &PcbFile) -> Vec<NetLength> {
+    let tracks = pcb.tracks.iter().map(|t| (&t.net, t.length(), None));
+    let arcs = pcb.arcs.iter().map(|a| (&a.net, a.length(), None));
+    let vias = pcb.vias.iter().map(|v| {
+        let span = match (v.layers.first(), v.layers.last()) {
+            (Some(from), Some(to)) => pcb.copper_distance(from, to),
+            _ => None,
+        };
+        (&v.net, 0.0, Some(span.map_or(0.0, Length::as_mm)))
+    });
+
+    let mut lengths: BTreeMap<&str, NetLength> = BTreeMap::new();
+    for (net, track_length, via_length) in tracks.chain(arcs).chain(vias) {
+        let Some(net) = net.as_deref() else { continue };
+        let entry = lengths.entry(net).or_insert_with(|| unrouted(net));
+        entry.track_length += track_length;
+        if let Some(via_length) = via_length {
+            entry.via_length += via_length;
+            entry.via_count += 1;
+        }
+    }
+    let pads = pcb.footprints.iter().flat_map(|f| &f.pads);
+    for pad in pads {
+        if let (Some(net), Some(die_length)) = (pad.net.as_deref(), pad.die_length) {
+            lengths.entry(net).or_insert_with(|| unrouted(net)).die_length += die_length.as_mm();
+        }
+    }
+    lengths.into_values().collect()
+}
+
+/// Skew of every differential pair on the board, sorted by positive net name
+///
+/// Pairs are recognised the way KiCad does: two declared nets whose names differ only
+/// in a trailing `P`/`N` or `+`/`-`, e.g. `USB_D+`/`USB_D-` or `LVDS0_P`/`LVDS0_N`.
+pub fn diff_pair_skews(pcb: &PcbFile) -> Vec<DiffPairSkew> {
+    let lengths: BTreeMap<String, NetLength> = net_lengths(pcb)
+        .into_iter()
+        .map(|l| (l.net.clone(), l))
+        .collect();
+    let length_of = |net: &str| lengths.get(net).cloned().unwrap_or_else(|| unrouted(net));
+
+    let declared: std::collections::BTreeSet<&str> = pcb.nets.values().map(String::as_str).collect();
+    declared
+        .iter()
+        .filter_map(|positive| {
+            let negative = diff_pair_partner(positive)?;
+            declared.contains(negative.as_str()).then(|| DiffPairSkew {
+                positive: length_of(positive),
+                negative: length_of(&negative),
+            })
+        })
+        .collect()
+}
+
+fn unrouted(net: &str) -> NetLength {
+    NetLength {
+        net: net.to_string(),
+        track_length: 0.0,
+        via_length: 0.0,
+        via_count: 0,
+        die_length: 0.0,
+    }
+}
+
+/// Name of the negative member for a positive differential pair net name
+fn diff_pair_partner(net: &str) -> Option<String> {
+    let base = net.strip_suffix('P').map(|b| (b, 'N'));
+    let (base, suffix) = base.or_else(|| net.strip_suffix('+').map(|b| (b, '-')))?;
+    (!base.is_empty()).then(|| format!("{}{}", base, suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+    use crate::units::Length;
+
+    #[test]
+    fn test_net_track_stats() {
+        let pcb = parse_pcb(
+            r#"(kicad_pcb
+  (net 0 "") (net 1 "GND") (net 2 "+3V3")
+  (segment (start 0 0) (end 3 4) (width 0.3) (layer "F.Cu") (net 2))
+  (segment (start 3 4) (end 3 9) (width 0.5) (layer "B.Cu") (net 2))
+  (arc (start 0 0) (mid 1 1) (end 2 0) (width 0.2) (layer "F.Cu") (net 1))
+  (segment (start 5 5) (end 5 5) (width 0.2) (layer "F.Cu") (net 1))
+  (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu"))
+)"#,
+        )
+        .unwrap();
+        let stats = net_track_stats(&pcb);
+        assert_eq!(stats.len(), 2);
+
+        let power = &stats[0];
+        assert_eq!(power.net, "+3V3");
+        assert_eq!(power.segments, 2);
+        assert_eq!((power.min_width, power.max_width), (0.3, 0.5));
+        assert!((power.total_length - 10.0).abs() < 1e-9);
+        assert!((power.average_width - 0.4).abs() < 1e-9);
+        assert!(power.has_segment_narrower_than(0.4));
+
+        // Half circle of radius 1 plus a zero-length stub
+        let gnd = &stats[1];
+        assert_eq!(gnd.segments, 2);
+        assert!((gnd.total_length - std::f64::consts::PI).abs() < 1e-9);
+        assert!((gnd.average_width - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_net_lengths_and_skew() {
+        let pcb = parse_pcb(
+            r#"(kicad_pcb
+  (general (thickness 1.6))
+  (net 0 "") (net 1 "USB_D+") (net 2 "USB_D-") (net 3 "CLK_P") (net 4 "CLK_N") (net 5 "VCAP")
+  (segment (start 0 0) (end 10 0) (width 0.2) (layer "F.Cu") (net 1))
+  (via (at 10 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+  (segment (start 10 0) (end 12 0) (width 0.2) (layer "B.Cu") (net 1))
+  (segment (start 0 1) (end 11 1) (width 0.2) (layer "F.Cu") (net 2))
+  (via (at 0 5) (size 0.6) (drill 0.3) (layers "F.Cu" "In1.Cu") (net 3))
+  (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net 5))
+  (footprint "USB" (at 20 0) (property "Reference" "J1")
+    (pad "3" smd rect (at 0 0) (size 0.5 1) (layers "F.Cu") (net 2 "USB_D-") (pinfunction "D-") (pintype "bidirectional") (die_length 1.1)))
+)"#,
+        )
+        .unwrap();
+
+        let lengths = net_lengths(&pcb);
+        let nets: Vec<_> = lengths.iter().map(|l| l.net.as_str()).collect();
+        assert_eq!(nets, ["CLK_P", "USB_D+", "USB_D-", "VCAP"]);
+
+        let clk = &lengths[0];
+        assert_eq!((clk.via_count, clk.via_length), (1, 0.0));
+        let usb = &lengths[1];
+        assert_eq!((usb.track_length, usb.via_length, usb.total()), (12.0, 1.6, 13.6));
+
+        let skews = diff_pair_skews(&pcb);
+        assert_eq!(skews.len(), 2);
+        assert_eq!((skews[0].positive.net.as_str(), skews[0].negative.net.as_str()), ("CLK_P", "CLK_N"));
+        assert_eq!(skews[0].skew(), 0.0);
+        assert_eq!(skews[1].negative.net, "USB_D-");
+        assert_eq!(skews[1].negative.die_length, 1.1);
+        assert!((skews[1].skew() - 1.5).abs() < 1e-9);
+
+        let pad = &pcb.footprints[0].pads[0];
+        assert_eq!((pad.pin_function.as_deref(), pad.pin_type.as_deref()), (Some("D-"), Some("bidirectional")));
+        let written = parse_pcb(&pcb.to_kicad_pcb()).unwrap();
+        assert_eq!(written.footprints[0].pads[0].die_length, Some(Length::mm(1.1)));
+    }
+
+    #[test]
+    fn test_via_length_through_stackup() {
+        let pcb = parse_pcb(
+            r#"(kicad_pcb
+  (general (thickness 1.6))
+  (setup (stackup
+    (layer "F.Mask" (type "Top Solder Mask") (thickness 0.01))
+    (layer "F.Cu" (type "copper") (thickness 0.035))
+    (layer "dielectric 1" (type "core") (thickness 1.51))
+    (layer "B.Cu" (type "copper") (thickness 0.035))
+    (layer "B.Mask" (type "Bottom Solder Mask") (thickness 0.01))))
+  (net 0 "") (net 1 "SIG")
+  (via (at 0 0) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu") (net 1))
+)"#,
+        )
+        .unwrap();
+
+        let sig = &net_lengths(&pcb)[0];
+        assert!((sig.via_length - 1.58).abs() < 1e-9);
+    }
+}
@@ -58,7 +58,7 @@ pub fn parse_layers_only(content: &str) -> Result<PcbFile> {
                 if let Some(layer) = parse_layer_line(line) {
                     pcb.layers.insert(layer.id, layer);
                 }
-            } else if line.starts_with(')') && pcb.layers.len() > 0 {
+            } else if line.starts_with(')') && !pcb.layers.is_empty() {
                 break;
             }
         }
@@ -0,0 +1,355 @@
+//! R-tree over the elements of a board
+//!
+//! [`PcbFile::spatial_index`] builds a [`SpatialIndex`] on demand: footprints,
+//! pads, tracks, arcs, vias, zones, board drawings and board texts are packed
+//! into an R-tree by their bounding boxes, so "what is within 2 mm of here" and
+//! window selections only look at nearby elements. Hits are checked against the
+//! element's actual outline (pad shapes, track capsules, zone outlines), not just
+//! its bounding box. The index borrows the board and is not updated when the
+//! board changes; build a new one after editing.
+//!
+//! ```rust
+//! use kiparse::pcb::{parse_pcb, Point, Rect};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "R" (at 10 10) (property "Reference" "R1")
+//!     (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu"))
+//!     (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu")))
+//!   (segment (start 0 0) (end 20 0) (width 0.2) (layer "F.Cu"))
+//!   (via (at 50 50) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu")))"#)?;
+//! let index = pcb.spatial_index();
+//!
+//! let near = index.within(&Point::new(9.0, 8.0), 2.0);
+//! assert_eq!(near.iter().map(|e| e.kind().name()).collect::<Vec<_>>(), ["pad", "footprint"]);
+//! let window = Rect { start: Point::new(40.0, 40.0), end: Point::new(60.0, 60.0) };
+//! assert_eq!(index.in_rect(&window)[0].kind().name(), "via");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::copper::{pad_shape, Shape, EPSILON};
+use super::geometry::{arc_from_points, bounds, flatten_arc, outline_piece, to_board, OutlinePiece};
+use super::query::Element;
+use super::types::{Footprint, Graphic, PcbFile, Point, Rect};
+use crate::units::Length;
+
+/// Maximum number of children of an R-tree node
+const NODE_SIZE: usize = 16;
+
+/// Elements of a board packed into an R-tree, see the [module docs](self)
+pub struct SpatialIndex<'a> {
+    entries: Vec<Entry<'a>>,
+    nodes: Vec<Node>,
+}
+
+struct Entry<'a> {
+    element: Element<'a>,
+    shapes: Vec<Shape>,
+    min: Point,
+    max: Point,
+}
+
+impl Entry<'_> {
+    /// Bounding box area
+    fn area(&self) -> f64 {
+        (self.max.x - self.min.x).as_mm() * (self.max.y - self.min.y).as_mm()
+    }
+}
+
+struct Node {
+    min: Point,
+    max: Point,
+    /// Entry indices for leaves, node indices otherwise
+    children: Vec<usize>,
+    leaf: bool,
+}
+
+/// Item being packed into a node: index with its bounding box
+type Packed = (usize, Point, Point);
+
+impl<'a> SpatialIndex<'a> {
+    /// Index every footprint, pad, track, arc, via, zone, board drawing and board text
+    pub fn new(pcb: &'a PcbFile) -> Self {
+        let mut entries = Vec::new();
+        let mut add = |element: Element<'a>, shapes: Vec<Shape>| {
+            if shapes.is_empty() {
+                return;
+            }
+            let corners: Vec<Point> = shapes
+                .iter()
+                .flat_map(|s| {
+                    let (min, max) = s.bounds();
+                    [min, max]
+                })
+                .collect();
+            let (min, max) = bounds(&corners);
+            entries.push(Entry { element, shapes, min, max });
+        };
+
+        for footprint in &pcb.footprints {
+            add(Element::Footprint(footprint), vec![footprint_shape(footprint)]);
+            let reference = footprint.property("Reference").unwrap_or_default();
+            for pad in &footprint.pads {
+                add(Element::Pad { reference, pad, footprint }, vec![pad_shape(footprint, pad)]);
+            }
+        }
+        for track in &pcb.tracks {
+            add(Element::Track(track), vec![capsule(&track.start, &track.end, track.width.as_mm())]);
+        }
+        for arc in &pcb.arcs {
+            let points = match arc_from_points(&arc.start, &arc.mid, &arc.end) {
+                Some(circle) => flatten_arc(&circle),
+                None => vec![arc.start.clone(), arc.end.clone()],
+            };
+            add(Element::Arc(arc), polyline(&points, arc.width.as_mm()));
+        }
+        for via in &pcb.vias {
+            add(Element::Via(via), vec![capsule(&via.position, &via.position, via.size.as_mm())]);
+        }
+        for zone in pcb.zones.iter().filter(|z| !z.polygon.is_empty()) {
+            add(Element::Zone(zone), vec![Shape::Polygon(zone.polygon.clone())]);
+        }
+        for graphic in &pcb.graphics {
+            add(Element::Graphic { reference: None, graphic }, graphic_shapes(graphic));
+        }
+        for text in &pcb.texts {
+            add(Element::Text { reference: None, text }, vec![capsule(&text.position, &text.position, 0.0)]);
+        }
+
+        let nodes = pack(&entries);
+        SpatialIndex { entries, nodes }
+    }
+
+    /// Number of indexed elements
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Elements whose outline comes within `radius` mm of `at`, nearest first
+    ///
+    /// Elements at the same distance, such as a pad and its footprint under the
+    /// point, are ordered smallest first.
+    ///
+    /// Combine with [`Element::on_layer`] to restrict the hits to one layer.
+    pub fn within(&self, at: &Point, radius: f64) -> Vec<Element<'a>> {
+        let probe = capsule(at, at, 0.0);
+        let reach = Length::mm(radius);
+        let min = Point { x: at.x - reach, y: at.y - reach };
+        let max = Point { x: at.x + reach, y: at.y + reach };
+        let mut hits: Vec<(f64, f64, usize)> = self
+            .search(&min, &max)
+            .into_iter()
+            .map(|i| (self.distance(i, &probe), self.entries[i].area(), i))
+            .filter(|(d, _, _)| *d <= radius + EPSILON)
+            .collect();
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)).then(a.2.cmp(&b.2)));
+        hits.into_iter().map(|(_, _, i)| self.entries[i].element).collect()
+    }
+
+    /// Nearest element within `radius` mm of `at`, e.g. for picking under the cursor
+    pub fn nearest(&self, at: &Point, radius: f64) -> Option<Element<'a>> {
+        self.within(at, radius).into_iter().next()
+    }
+
+    /// Elements touching the window `rect`, in board order
+    pub fn in_rect(&self, rect: &Rect) -> Vec<Element<'a>> {
+        let (min, max) = bounds(&[rect.start.clone(), rect.end.clone()]);
+        let window = Shape::Polygon(rectangle(&min, &max));
+        self.search(&min, &max)
+            .into_iter()
+            .filter(|&i| self.distance(i, &window) <= EPSILON)
+            .map(|i| self.entries[i].element)
+            .collect()
+    }
+
+    fn distance(&self, entry: usize, shape: &Shape) -> f64 {
+        self.entries[entry]
+            .shapes
+            .iter()
+            .map(|s| s.distance(shape))
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Entries whose bounding box overlaps `min`-`max`, in index order
+    fn search(&self, min: &Point, max: &Point) -> Vec<usize> {
+        let overlaps = |lo: &Point, hi: &Point| lo.x <= max.x && hi.x >= min.x && lo.y <= max.y && hi.y >= min.y;
+        let mut found = Vec::new();
+        let mut stack: Vec<usize> = self.nodes.len().checked_sub(1).into_iter().collect();
+        while let Some(n) = stack.pop() {
+            let node = &self.nodes[n];
+            if !overlaps(&node.min, &node.max) {
+                continue;
+            }
+            if node.leaf {
+                found.extend(node.children.iter().filter(|&&i| overlaps(&self.entries[i].min, &self.entries[i].max)));
+            } else {
+                stack.extend(&node.children);
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+impl PcbFile {
+    /// R-tree over the board's elements for proximity and window queries
+    ///
+    /// Built on every call; keep the index around while the board is unchanged.
+    pub fn spatial_index(&self) -> SpatialIndex<'_> {
+        SpatialIndex::new(self)
+    }
+}
+
+/// Sort-tile-recursive bulk load; the root is the last node
+fn pack(entries: &[Entry]) -> Vec<Node> {
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut level: Vec<Packed> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (i, e.min.clone(), e.max.clone()))
+        .collect();
+    let mut leaf = true;
+    while !level.is_empty() {
+        let start = nodes.len();
+        for group in tiles(level) {
+            let corners: Vec<Point> = group.iter().flat_map(|(_, min, max)| [min.clone(), max.clone()]).collect();
+            let (min, max) = bounds(&corners);
+            nodes.push(Node { min, max, children: group.into_iter().map(|(i, _, _)| i).collect(), leaf });
+        }
+        if nodes.len() - start == 1 {
+            break;
+        }
+        level = (start..nodes.len()).map(|i| (i, nodes[i].min.clone(), nodes[i].max.clone())).collect();
+        leaf = false;
+    }
+    nodes
+}
+
+/// Split items into groups of up to [`NODE_SIZE`]: vertical slabs by x, then runs by y
+fn tiles(mut items: Vec<Packed>) -> Vec<Vec<Packed>> {
+    let center = |(_, min, max): &Packed| Point { x: (min.x + max.x) / 2.0, y: (min.y + max.y) / 2.0 };
+    let groups = (items.len() + NODE_SIZE - 1) / NODE_SIZE;
+    let slabs = (groups as f64).sqrt().ceil() as usize;
+    let slab_len = NODE_SIZE * ((groups + slabs - 1) / slabs);
+
+    items.sort_by(|a, b| center(a).x.total_cmp(&center(b).x));
+    let mut tiles = Vec::with_capacity(groups);
+    for slab in items.chunks_mut(slab_len) {
+        slab.sort_by(|a, b| center(a).y.total_cmp(&center(b).y));
+        tiles.extend(slab.chunks(NODE_SIZE).map(<[Packed]>::to_vec));
+    }
+    tiles
+}
+
+/// Segment `a`-`b` drawn with `width`
+fn capsule(a: &Point, b: &Point, width: f64) -> Shape {
+    Shape::Capsule { a: a.clone(), b: b.clone(), radius: width / 2.0 }
+}
+
+fn polyline(points: &[Point], width: f64) -> Vec<Shape> {
+    points.windows(2).map(|pair| capsule(&pair[0], &pair[1], width)).collect()
+}
+
+/// Stroke of a drawing, plus its interior when filled
+fn graphic_shapes(graphic: &Graphic) -> Vec<Shape> {
+    let (width, filled) = match graphic {
+        Graphic::Line { width, .. } | Graphic::Arc { width, .. } | Graphic::Bezier { width, .. } => (width.as_mm(), false),
+        Graphic::Circle { width, filled, .. }
+        | Graphic::Rectangle { width, filled, .. }
+        | Graphic::Polygon { width, filled, .. } => (width.as_mm(), *filled),
+    };
+    match outline_piece(graphic) {
+        OutlinePiece::Open(points) => polyline(&points, width),
+        OutlinePiece::Closed(mut points) => {
+            let interior = filled.then(|| Shape::Polygon(points.clone()));
+            if let Some(first) = points.first().cloned() {
+                points.push(first);
+            }
+            polyline(&points, width).into_iter().chain(interior).collect()
+        }
+    }
+}
+
+/// Rectangle around a footprint's pads and drawings, or its origin when it has neither
+fn footprint_shape(footprint: &Footprint) -> Shape {
+    let mut corners: Vec<Point> = Vec::new();
+    for pad in &footprint.pads {
+        let (min, max) = pad_shape(footprint, pad).bounds();
+        corners.extend([min, max]);
+    }
+    for graphic in &footprint.graphics {
+        for shape in graphic_shapes(graphic) {
+            let (min, max) = shape.bounds();
+            corners.extend(rectangle(&min, &max).iter().map(|p| to_board(p, &footprint.position, footprint.rotation)));
+        }
+    }
+    if corners.is_empty() {
+        return capsule(&footprint.position, &footprint.position, 0.0);
+    }
+    let (min, max) = bounds(&corners);
+    Shape::Polygon(rectangle(&min, &max))
+}
+
+/// Corners of the axis-aligned rectangle `min`-`max`
+fn rectangle(min: &Point, max: &Point) -> Vec<Point> {
+    vec![min.clone(), Point { x: max.x, y: min.y }, max.clone(), Point { x: min.x, y: max.y }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+    use crate::pcb::query::ElementKind;
+
+    const FPGA: &str = include_str!("../../assets/fpga.kicad_pcb");
+
+    fn pt(x: f64, y: f64) -> Point {
+        Point::new(x, y)
+    }
+
+    #[test]
+    fn test_within_and_window() {
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "R" (at 10 10) (property "Reference" "R1")
+    (pad "1" smd rect (at -1 0) (size 1 1) (layers "F.Cu"))
+    (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu")))
+  (segment (start 0 0) (end 20 0) (width 0.2) (layer "F.Cu"))
+  (segment (start 0 5) (end 20 5) (width 0.2) (layer "B.Cu"))
+  (gr_rect (start 30 30) (end 40 40) (layer "Edge.Cuts") (width 0.1)))"#).unwrap();
+        let index = pcb.spatial_index();
+        assert_eq!(index.len(), 6);
+
+        let near = index.within(&pt(11.0, 10.0), 0.1);
+        assert_eq!(near.iter().map(|e| e.kind()).collect::<Vec<_>>(), [ElementKind::Pad, ElementKind::Footprint]);
+        assert_eq!(near[0].attr("number"), ["2"]);
+        let tracks = index.within(&pt(5.0, 2.5), 2.5);
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks.iter().filter(|e| e.on_layer("B.Cu")).count(), 1);
+        assert!(index.within(&pt(5.0, 2.5), 2.0).is_empty());
+
+        // Inside an unfilled rectangle, only its edges count
+        assert!(index.within(&pt(35.0, 35.0), 1.0).is_empty());
+        assert_eq!(index.nearest(&pt(35.0, 39.5), 1.0).unwrap().kind(), ElementKind::Graphic);
+        assert_eq!(index.in_rect(&Rect { start: pt(25.0, 25.0), end: pt(31.0, 31.0) }).len(), 1);
+        assert_eq!(index.in_rect(&Rect { start: pt(-1.0, -1.0), end: pt(50.0, 50.0) }).len(), 6);
+    }
+
+    #[test]
+    fn test_matches_linear_scan() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let index = pcb.spatial_index();
+        let everything = index.in_rect(&Rect { start: pt(-1e6, -1e6), end: pt(1e6, 1e6) });
+        assert_eq!(everything.len(), index.len());
+        assert!(index.nodes.len() > 1);
+
+        let via = &pcb.vias[pcb.vias.len() / 2];
+        let probe = capsule(&via.position, &via.position, 0.0);
+        let expected = (0..index.entries.len()).filter(|&i| index.distance(i, &probe) <= 2.0 + EPSILON).count();
+        let near = index.within(&via.position, 2.0);
+        assert_eq!(near.len(), expected);
+        assert!(matches!(near[0], Element::Via(_) | Element::Track(_) | Element::Pad { .. } | Element::Zone(_)));
+    }
+}
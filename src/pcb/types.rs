@@ -4,30 +4,53 @@
 //! It includes structures for points, rectangles, arcs, layers, footprints, tracks, vias, zones, texts, and graphics.
 //! The structures are designed to be serializable and deserializable using Serde.
 //! The `PcbFile` structure serves as the main entry point for parsing and manipulating PCB files.
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+use crate::collections::HashMap;
+
+#[cfg(not(any(feature = "std", feature = "serde")))]
+use crate::float::Float;
+use crate::embedded::{embedded_name, EmbeddedFile};
+use crate::units::Length;
+
+/// A position or a width/height pair, in mm
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
-    pub x: f64,
-    pub y: f64,
+    pub x: Length,
+    pub y: Length,
+}
+
+impl Point {
+    /// Point at `x`, `y` in mm
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x: Length::mm(x), y: Length::mm(y) }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rect {
     pub start: Point,
     pub end: Point,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Arc {
     pub center: Point,
     pub start_angle: f64,
     pub end_angle: f64,
-    pub radius: f64,
+    pub radius: Length,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Layer {
     pub id: i32,
     pub name: String,
@@ -35,22 +58,437 @@ pub struct Layer {
     pub user_name: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PcbFile {
     pub version: String,
     pub generator: String,
-    pub board_thickness: Option<f64>,
+    pub board_thickness: Option<Length>,
     pub paper_size: Option<String>,
+    pub metadata: BoardMetadata,
+    pub title_block: Option<TitleBlock>,
     pub layers: HashMap<i32, Layer>,
+    /// Physical layer stack from `(setup (stackup ...))`, top to bottom
+    pub stackup: Vec<StackupLayer>,
     pub footprints: Vec<Footprint>,
     pub tracks: Vec<Track>,
+    /// Curved track segments (`arc` at board level)
+    pub arcs: Vec<TrackArc>,
     pub vias: Vec<Via>,
     pub zones: Vec<Zone>,
+    pub rule_areas: Vec<RuleArea>,
     pub texts: Vec<Text>,
+    pub text_boxes: Vec<TextBox>,
     pub graphics: Vec<Graphic>,
+    pub nets: HashMap<u32, String>,
+    pub net_classes: HashMap<String, NetClass>,
+    pub groups: Vec<Group>,
+    pub tuning_patterns: Vec<TuningPattern>,
+    pub images: Vec<Image>,
+    pub embedded_files: Vec<EmbeddedFile>,
+    /// Original text of board elements the parser skipped, when asked to keep them
+    /// with [`ParserConfig::keep_skipped`](super::pcb_parser::ParserConfig::keep_skipped)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skipped: Vec<String>,
+    /// Reference lookup table behind [`PcbFile::footprint_by_ref`]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) footprint_index: FootprintIndex,
+}
+
+/// Reference to footprint position map, built on the first lookup
+///
+/// Copies start without an index and build their own; comparisons ignore it.
+#[derive(Default)]
+pub(crate) struct FootprintIndex(once_cell::race::OnceBox<HashMap<String, usize>>);
+
+impl Clone for FootprintIndex {
+    fn clone(&self) -> Self {
+        FootprintIndex::default()
+    }
+}
+
+impl PartialEq for FootprintIndex {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl core::fmt::Debug for FootprintIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("FootprintIndex")
+    }
+}
+
+/// Bitmap image placed on the board, such as a logo or assembly drawing
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Image {
+    pub position: Point,
+    pub layer: String,
+    pub scale: f64,
+    pub uuid: String,
+    /// Decoded image bytes (normally a PNG file)
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    /// Whether the payload carries the PNG signature
+    pub fn is_png(&self) -> bool {
+        self.data.starts_with(b"\x89PNG\r\n\x1a\n")
+    }
+
+    /// Pixel width and height read from the PNG header
+    pub fn png_size(&self) -> Option<(u32, u32)> {
+        if !self.is_png() || self.data.len() < 24 {
+            return None;
+        }
+        let be = |at: usize| {
+            u32::from_be_bytes([self.data[at], self.data[at + 1], self.data[at + 2], self.data[at + 3]])
+        };
+        // IHDR is the first chunk; width and height follow its length and type fields
+        Some((be(16), be(20)))
+    }
+}
+
+/// Named collection of board items, referenced by UUID
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    pub name: String,
+    pub uuid: String,
+    pub locked: bool,
+    /// UUIDs of the member footprints, tracks, vias, zones or nested groups
+    pub members: Vec<String>,
+}
+
+/// A board item resolved from a group member UUID
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupMember<'a> {
+    Footprint(&'a Footprint),
+    Track(&'a Track),
+    Via(&'a Via),
+    Zone(&'a Zone),
+    Group(&'a Group),
+}
+
+/// Length-tuning (meander) pattern generated by KiCad's interactive router (KiCad 8+)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TuningPattern {
+    pub uuid: String,
+    pub name: String,
+    pub layer: String,
+    pub mode: TuningMode,
+    /// Net the pattern was last tuned for
+    pub net: Option<String>,
+    /// Router verdict from the last update
+    pub status: TuningStatus,
+    /// Length reported by the router at the last update, as displayed (may be localized)
+    pub last_tuning: Option<String>,
+    pub origin: Point,
+    pub end: Point,
+    /// Original (untuned) track path the meanders were built on
+    pub base_line: Vec<Point>,
+    /// Path of the coupled track for differential pair tuning
+    pub base_line_coupled: Option<Vec<Point>>,
+    /// Length window in mm, absent when tuning by delay or skew
+    pub target_length: Option<LengthTarget>,
+    /// Skew window in mm for differential pair skew tuning
+    pub target_skew: Option<LengthTarget>,
+    /// Delay window in ps when tuning in the time domain
+    pub target_delay: Option<LengthTarget>,
+    pub time_domain: bool,
+    pub meander: MeanderParameters,
+    /// UUIDs of the generated tracks and arcs forming the pattern
+    pub members: Vec<String>,
+}
+
+/// What a tuning pattern adjusts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TuningMode {
+    Single,
+    DiffPair,
+    DiffPairSkew,
+}
+
+/// Outcome of the router's last length check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TuningStatus {
+    Tuned,
+    TooShort,
+    TooLong,
+    Unknown,
+}
+
+/// Target value with its acceptable range
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthTarget {
+    pub min: f64,
+    pub target: f64,
+    pub max: f64,
+}
+
+impl LengthTarget {
+    /// Whether `value` falls inside the tolerance window
+    pub fn accepts(&self, value: f64) -> bool {
+        value >= self.min && value <= self.max
+    }
+}
+
+/// Meander shape settings of a tuning pattern
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MeanderParameters {
+    pub min_amplitude: Length,
+    pub max_amplitude: Length,
+    pub min_spacing: Length,
+    pub corner_radius_percent: f64,
+    pub rounded: bool,
+    pub single_sided: bool,
+    /// Side of the base line the first meander goes to (`"left"`, `"right"` or `"default"`)
+    pub initial_side: String,
+}
+
+/// Board-level metadata from the file header, `general`, `paper` and `setup` sections
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardMetadata {
+    /// KiCad release that wrote the file, e.g. `"9.0"` (KiCad 8+)
+    pub generator_version: Option<String>,
+    pub legacy_teardrops: bool,
+    pub paper_portrait: bool,
+    /// Sheet width and height in mm for custom (`User`) paper
+    pub paper_custom_size: Option<(f64, f64)>,
+    /// Drill/place file origin set in the board setup
+    pub aux_axis_origin: Option<Point>,
+    pub grid_origin: Option<Point>,
+    /// Surface finish from the stackup, e.g. `"ENIG"` or `"None"`
+    pub copper_finish: Option<String>,
+    /// Dielectric thicknesses are fab constraints, not just nominal values
+    pub dielectric_constraints: bool,
+    /// Edge card connector: `"yes"` or `"bevelled"`
+    pub edge_connector: Option<String>,
+    pub castellated_pads: bool,
+    pub edge_plating: bool,
+    /// Board-wide mask and paste clearances from the setup
+    /// (`pad_to_mask_clearance`, `pad_to_paste_clearance[_ratio]`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub margins: MaskPasteMargins,
+    /// Via tenting and other protection applied where a via sets none itself
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub via_defaults: ViaFinish,
+    /// Settings of KiCad's plot dialog (`pcbplotparams`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub plot_params: Option<PlotParams>,
+}
+
+/// Plot dialog settings saved with the board, see [`PcbFile::plot_layers`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlotParams {
+    /// Ids of the layers selected for plotting
+    pub layers: Vec<i32>,
+    /// Ids of the layers plotted onto every selected layer (KiCad 8+)
+    pub common_layers: Vec<i32>,
+    pub format: PlotFormat,
+    /// Output directory, relative to the project
+    pub output_directory: String,
+    pub mirror: bool,
+    pub drill_marks: DrillMarks,
+    /// Plot in outline (sketch) mode instead of filled
+    pub sketch: bool,
+    /// Coordinates relative to the drill/place file origin
+    pub use_aux_origin: bool,
+    /// Include the drawing sheet border and title block
+    pub plot_frame_ref: bool,
+    pub subtract_mask_from_silk: bool,
+    pub sketch_pads_on_fab: bool,
+    /// Protel-style file extensions (`.gtl`, `.gbl`, ...)
+    pub gerber_extensions: bool,
+    /// X2 file attributes
+    pub gerber_attributes: bool,
+    /// X2 net and component attributes
+    pub gerber_advanced_attributes: bool,
+    pub gerber_job_file: bool,
+    /// Coordinate decimals in Gerber files, 5 or 6
+    pub gerber_precision: Option<u32>,
+    pub svg_precision: Option<u32>,
+    pub black_and_white: bool,
+}
+
+/// Output format chosen in the plot dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PlotFormat {
+    Hpgl,
+    Gerber,
+    Postscript,
+    Dxf,
+    Pdf,
+    Svg,
+}
+
+impl PlotFormat {
+    /// Format for KiCad's `outputformat` number, `None` for unknown values
+    pub fn from_code(code: u32) -> Option<Self> {
+        Some(match code {
+            0 => PlotFormat::Hpgl,
+            1 => PlotFormat::Gerber,
+            2 => PlotFormat::Postscript,
+            3 => PlotFormat::Dxf,
+            4 => PlotFormat::Pdf,
+            5 => PlotFormat::Svg,
+            _ => return None,
+        })
+    }
+}
+
+/// Drill hole marks drawn on plotted copper and fab layers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DrillMarks {
+    None,
+    Small,
+    Full,
+}
+
+/// Layer ids set in a KiCad layer mask such as `0x00010fc_ffffffff`
+///
+/// The lowest bit of the last digit is layer 0; masks of any width are accepted.
+pub fn layer_mask_ids(mask: &str) -> Option<Vec<i32>> {
+    let digits = mask.strip_prefix("0x").unwrap_or(mask).replace('_', "");
+    let mut ids = Vec::new();
+    for (position, digit) in digits.chars().rev().enumerate() {
+        let value = digit.to_digit(16)?;
+        ids.extend((0..4).filter(|bit| value & (1 << bit) != 0).map(|bit| position as i32 * 4 + bit));
+    }
+    Some(ids)
+}
+
+/// Solder mask and paste clearances of a pad, footprint or board
+///
+/// Unset values defer to the next level out: pad, then footprint, then board.
+/// [`PcbFile::pad_margins`] resolves them for one pad.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaskPasteMargins {
+    /// Solder mask opening beyond the pad edge in mm
+    pub solder_mask_margin: Option<Length>,
+    /// Paste aperture beyond the pad edge in mm, usually zero or negative
+    pub solder_paste_margin: Option<Length>,
+    /// Paste aperture change as a fraction of the pad size, e.g. `-0.1`
+    pub solder_paste_ratio: Option<f64>,
+}
+
+impl MaskPasteMargins {
+    /// These margins, with any unset one taken from `fallback`
+    pub fn or(self, fallback: MaskPasteMargins) -> Self {
+        MaskPasteMargins {
+            solder_mask_margin: self.solder_mask_margin.or(fallback.solder_mask_margin),
+            solder_paste_margin: self.solder_paste_margin.or(fallback.solder_paste_margin),
+            solder_paste_ratio: self.solder_paste_ratio.or(fallback.solder_paste_ratio),
+        }
+    }
+
+    /// Whether no margin is set
+    pub fn is_empty(&self) -> bool {
+        *self == MaskPasteMargins::default()
+    }
+
+    /// Solder mask opening for a pad of `size`
+    pub fn mask_size(&self, size: &Point) -> Point {
+        let margin = self.solder_mask_margin.unwrap_or_default();
+        let side = |v: Length| (v + 2.0 * margin).max(Length::ZERO);
+        Point { x: side(size.x), y: side(size.y) }
+    }
+
+    /// Paste aperture for a pad of `size`: the margin plus the ratio of each side,
+    /// on both edges, as KiCad computes it
+    pub fn paste_size(&self, size: &Point) -> Point {
+        let margin = self.solder_paste_margin.unwrap_or_default();
+        let ratio = self.solder_paste_ratio.unwrap_or(0.0);
+        let side = |v: Length| (v + 2.0 * (margin + v * ratio)).max(Length::ZERO);
+        Point { x: side(size.x), y: side(size.y) }
+    }
+}
+
+/// One physical layer of the board stackup: copper, dielectric, mask, paste or silk
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StackupLayer {
+    /// Board layer name (`"F.Cu"`) or dielectric label (`"dielectric 1"`)
+    pub name: String,
+    /// Stackup type such as `"copper"`, `"core"`, `"prepreg"` or `"Top Solder Mask"`
+    pub layer_type: String,
+    /// Thickness in mm
+    pub thickness: Option<Length>,
+    pub material: Option<String>,
+    pub epsilon_r: Option<f64>,
+    pub loss_tangent: Option<f64>,
+    /// Colour of mask and silkscreen layers, e.g. `"Green"` or `"#808080FF"`
+    pub color: Option<String>,
+}
+
+/// Aggregate board figures, see [`PcbFile::stats`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardStats {
+    pub layer_count: usize,
+    pub copper_layer_count: usize,
+    pub component_count: usize,
+    /// Footprints per reference designator prefix (`R`, `C`, `U`, ...)
+    pub components_by_prefix: BTreeMap<String, usize>,
+    pub pad_count: usize,
+    /// Track and arc segments
+    pub track_count: usize,
+    /// Routed track and arc length in mm
+    pub track_length: f64,
+    pub track_length_by_layer: BTreeMap<String, f64>,
+    pub via_count: usize,
+    pub vias_by_type: BTreeMap<ViaType, usize>,
+    /// Smallest via or plated/non-plated pad drill in mm
+    pub min_drill: Option<f64>,
+    pub min_track_width: Option<f64>,
+    /// Board area in mm² from the Edge.Cuts outline, cutouts excluded
+    pub board_area: Option<f64>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Drawing sheet title block fields
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TitleBlock {
+    pub title: String,
+    pub date: String,
+    pub revision: String,
+    pub company: String,
+    /// Numbered comment lines (1-9)
+    pub comments: HashMap<u32, String>,
+}
+
+/// Net class design rules and the nets assigned to the class
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetClass {
+    pub name: String,
+    pub description: String,
+    pub clearance: Option<Length>,
+    pub track_width: Option<Length>,
+    pub via_diameter: Option<Length>,
+    pub via_drill: Option<Length>,
+    pub uvia_diameter: Option<Length>,
+    pub uvia_drill: Option<Length>,
+    pub diff_pair_width: Option<Length>,
+    pub diff_pair_gap: Option<Length>,
+    pub nets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Footprint {
     pub name: String,
     pub uuid: String,
@@ -59,53 +497,515 @@ pub struct Footprint {
     pub layer: String,
     pub locked: bool,
     pub placed: bool,
+    pub attributes: FootprintAttributes,
     pub properties: HashMap<String, String>,
+    /// Schematic symbol path (`/sheet-uuid/.../symbol-uuid`)
+    pub path: Option<String>,
+    /// Name and file of the schematic sheet holding the symbol
+    pub sheet_name: Option<String>,
+    pub sheet_file: Option<String>,
+    /// Pad numbers deliberately joined by the footprint's own copper, one list
+    /// per `(net_tie_pad_groups ...)` entry
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub net_tie_pad_groups: Vec<Vec<String>>,
+    /// Mask and paste clearances for all pads of the footprint
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub margins: MaskPasteMargins,
+    /// Where properties such as the reference are drawn, whichever way the
+    /// file stores them; their values are in `properties`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fields: Vec<FieldText>,
     pub pads: Vec<Pad>,
     pub graphics: Vec<Graphic>,
+    /// Free `fp_text user` texts
     pub texts: Vec<Text>,
+    pub models: Vec<Model3D>,
+    pub embedded_files: Vec<EmbeddedFile>,
+}
+
+/// A footprint property drawn on the board: a KiCad 8+ `(property ...)` with a
+/// position, or a KiCad 6/7 `(fp_text reference ...)` / `(fp_text value ...)`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldText {
+    /// Property name; `fp_text reference` and `value` become `Reference` and `Value`
+    pub name: String,
+    /// The drawn text, positioned relative to the footprint
+    pub text: Text,
+    pub hidden: bool,
+}
+
+impl FieldText {
+    /// Placement KiCad gives a new property on the front: the reference on the
+    /// silkscreen, the value on the fabrication layer and anything else hidden there
+    pub fn new(name: &str, value: &str) -> Self {
+        let (layer, hidden) = match name {
+            "Reference" => ("F.SilkS", false),
+            "Value" => ("F.Fab", false),
+            _ => ("F.Fab", true),
+        };
+        FieldText {
+            name: name.to_string(),
+            text: Text {
+                text: value.to_string(),
+                position: Point::default(),
+                layer: layer.to_string(),
+                effects: TextEffects {
+                    font_size: Point::new(1.0, 1.0),
+                    thickness: Length::mm(0.15),
+                    bold: false,
+                    italic: false,
+                    justify: None,
+                },
+            },
+            hidden,
+        }
+    }
+}
+
+/// Footprint `(attr ...)` fabrication flags
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FootprintAttributes {
+    /// Mounting type: `smd`, `through_hole`, or empty when unspecified
+    pub mount: String,
+    pub board_only: bool,
+    pub exclude_from_pos_files: bool,
+    pub exclude_from_bom: bool,
+    pub dnp: bool,
+    pub allow_missing_courtyard: bool,
+}
+
+/// 3D model file format, from the file extension
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ModelType {
+    Wrl,
+    Step,
+    Iges,
+    Other,
+}
+
+impl ModelType {
+    /// Classify a model by its file extension
+    pub fn from_path(path: &str) -> Self {
+        if path.ends_with(".wrl") {
+            ModelType::Wrl
+        } else if path.ends_with(".step") || path.ends_with(".stp") {
+            ModelType::Step
+        } else if path.ends_with(".igs") || path.ends_with(".iges") {
+            ModelType::Iges
+        } else {
+            ModelType::Other
+        }
+    }
+}
+
+/// 3D model attached to a footprint, with its placement transform
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Model3D {
+    pub path: String,
+    /// Offset from the footprint origin in mm (x, y, z)
+    pub offset: [f64; 3],
+    pub scale: [f64; 3],
+    /// Rotation about the x, y and z axes in degrees
+    pub rotate: [f64; 3],
+    pub hide: bool,
+    pub opacity: Option<f64>,
+}
+
+impl Model3D {
+    /// Model format derived from the file extension
+    pub fn model_type(&self) -> ModelType {
+        ModelType::from_path(&self.path)
+    }
+
+    /// Embedded file name when the path is a `kicad-embed://` reference
+    pub fn embedded_name(&self) -> Option<&str> {
+        embedded_name(&self.path)
+    }
+}
+
+impl Footprint {
+    /// Board position of one of this footprint's pads
+    pub fn pad_position(&self, pad: &Pad) -> Point {
+        super::geometry::to_board(&pad.position, &self.position, self.rotation)
+    }
+
+    /// Value of a footprint property such as `"Reference"`, `"Value"` or a custom field
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    /// Reference designator, empty when the footprint has none
+    ///
+    /// Files from KiCad 6 and 7 (`fp_text reference`) and 8+ (`property`) give the same result.
+    pub fn reference(&self) -> &str {
+        self.property("Reference").unwrap_or_default()
+    }
+
+    /// Value field, empty when the footprint has none
+    pub fn value(&self) -> &str {
+        self.property("Value").unwrap_or_default()
+    }
+
+    /// Position, layer and visibility of the drawn property `name`
+    pub fn field(&self, name: &str) -> Option<&FieldText> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    /// Whether KiCad would list the part in a bill of materials
+    pub fn in_bom(&self) -> bool {
+        !self.attributes.exclude_from_bom && !self.attributes.board_only
+    }
+
+    /// Whether KiCad would write the part to placement (.pos) files
+    pub fn in_pos_files(&self) -> bool {
+        !self.attributes.exclude_from_pos_files && !self.attributes.board_only
+    }
+
+    /// Whether the footprint is a net tie, joining pads of different nets on purpose
+    pub fn is_net_tie(&self) -> bool {
+        self.net_tie_pad_groups.iter().any(|group| group.len() > 1)
+    }
+
+    /// Net-tie group holding pad `number`, with the pad itself
+    pub fn net_tie_group(&self, number: &str) -> Option<&[String]> {
+        self.net_tie_pad_groups
+            .iter()
+            .find(|group| group.iter().any(|n| n == number))
+            .map(Vec::as_slice)
+    }
+}
+
+/// A pad placed on the board, as yielded by [`PcbFile::pads_absolute`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoardPad<'a> {
+    /// Reference of the owning footprint, empty when it has none
+    pub reference: &'a str,
+    pub footprint: &'a Footprint,
+    pub pad: &'a Pad,
+    /// Pad centre in board coordinates
+    pub position: Point,
+    /// Pad orientation on the board in degrees
+    pub rotation: f64,
+    /// Whether the footprint sits on the bottom side
+    pub bottom: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pad {
     pub number: String,
     pub pad_type: String,
     pub shape: String,
+    /// Position relative to the footprint origin, in the footprint's frame
     pub position: Point,
+    /// Pad orientation on the board in degrees, footprint rotation included
+    pub rotation: f64,
     pub size: Point,
-    pub drill: Option<f64>,
+    pub drill: Option<Length>,
+    /// Width and height of an oval drill (slot), in the pad's frame
+    pub drill_oval: Option<Point>,
     pub layers: Vec<String>,
     pub net: Option<String>,
+    /// Schematic pin name and electrical type (`passive`, `input`, ...)
+    pub pin_function: Option<String>,
+    pub pin_type: Option<String>,
+    /// Length from the pad to the die inside the package in mm, for length matching
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub die_length: Option<Length>,
     pub roundrect_ratio: Option<f64>,
+    /// The pad's own mask and paste clearances
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub margins: MaskPasteMargins,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Track {
     pub start: Point,
     pub end: Point,
-    pub width: f64,
+    pub width: Length,
     pub layer: String,
     pub net: Option<String>,
+    pub uuid: String,
+}
+
+impl Track {
+    /// Centreline length in mm
+    pub fn length(&self) -> f64 {
+        super::geometry::distance(&self.start, &self.end)
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Track segment drawn as a circular arc through `mid`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackArc {
+    pub start: Point,
+    pub mid: Point,
+    pub end: Point,
+    pub width: Length,
+    pub layer: String,
+    pub net: Option<String>,
+    pub uuid: String,
+}
+
+impl TrackArc {
+    /// Centreline length in mm along the arc; collinear points are measured straight
+    pub fn length(&self) -> f64 {
+        use super::geometry::{arc_from_points, distance};
+        match arc_from_points(&self.start, &self.mid, &self.end) {
+            Some(arc) => (arc.end_angle - arc.start_angle).to_radians() * arc.radius.as_mm(),
+            None => distance(&self.start, &self.end),
+        }
+    }
+}
+
+/// Drilling style of a via
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViaType {
+    /// Drilled through every layer
+    Through,
+    /// Starts or ends on an inner layer (`blind` covers buried vias too)
+    BlindBuried,
+    /// Laser-drilled between adjacent layers
+    Micro,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Via {
     pub position: Point,
-    pub size: f64,
-    pub drill: f64,
+    pub via_type: ViaType,
+    pub size: Length,
+    pub drill: Length,
     pub layers: Vec<String>,
     pub net: Option<String>,
+    pub uuid: String,
+    /// Tenting, covering, plugging, filling and capping set on this via (KiCad 9)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub finish: ViaFinish,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Via protection settings, per via or as the board default
+///
+/// Unset values on a via follow the board's
+/// [`via_defaults`](BoardMetadata::via_defaults); [`PcbFile::via_finish`]
+/// resolves them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViaFinish {
+    /// Solder mask over the via
+    pub tenting: ViaSides,
+    /// Extra covering material over the via
+    pub covering: ViaSides,
+    /// Hole plugged from a side
+    pub plugging: ViaSides,
+    /// Hole filled completely
+    pub filling: Option<bool>,
+    /// Filled hole plated over
+    pub capping: Option<bool>,
+}
+
+/// A via setting for the front and back side of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ViaSides {
+    pub front: Option<bool>,
+    pub back: Option<bool>,
+}
+
+impl ViaSides {
+    fn or(self, fallback: ViaSides) -> Self {
+        ViaSides { front: self.front.or(fallback.front), back: self.back.or(fallback.back) }
+    }
+
+    /// Sides with the setting on: 0, 1 or 2
+    fn count(&self) -> usize {
+        usize::from(self.front == Some(true)) + usize::from(self.back == Some(true))
+    }
+}
+
+impl ViaFinish {
+    /// These settings, with any unset one taken from `fallback`
+    pub fn or(self, fallback: ViaFinish) -> Self {
+        ViaFinish {
+            tenting: self.tenting.or(fallback.tenting),
+            covering: self.covering.or(fallback.covering),
+            plugging: self.plugging.or(fallback.plugging),
+            filling: self.filling.or(fallback.filling),
+            capping: self.capping.or(fallback.capping),
+        }
+    }
+
+    /// IPC-4761 via protection type, e.g. `"I-b"` for a via tented on both sides
+    ///
+    /// `a` and `b` suffixes mark one or both sides; `None` means unprotected.
+    pub fn ipc_4761(&self) -> Option<String> {
+        let sides = |s: &ViaSides| if s.count() == 2 { "b" } else { "a" };
+        let covered = self.covering.count() > 0;
+        let filled = self.filling == Some(true);
+        Some(if filled && self.capping == Some(true) {
+            "VII".to_string()
+        } else if filled && covered {
+            format!("VI-{}", sides(&self.covering))
+        } else if filled {
+            "V".to_string()
+        } else if self.plugging.count() > 0 && covered {
+            format!("IV-{}", sides(&self.plugging))
+        } else if self.plugging.count() > 0 {
+            format!("III-{}", sides(&self.plugging))
+        } else if covered {
+            format!("II-{}", sides(&self.covering))
+        } else if self.tenting.count() > 0 {
+            format!("I-{}", sides(&self.tenting))
+        } else {
+            return None;
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Zone {
+    pub uuid: String,
     pub net: Option<String>,
+    /// First layer of the zone; see `layers` for multi-layer zones
     pub layer: String,
+    pub layers: Vec<String>,
     pub priority: i32,
     pub connect_pads: bool,
+    /// Zone outline as drawn
     pub polygon: Vec<Point>,
+    /// Copper actually poured, as computed by KiCad's last zone fill
+    pub filled_polygons: Vec<FilledPolygon>,
+    /// Fill mode, thermal reliefs and island removal from `(fill ...)`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fill: ZoneFill,
+}
+
+/// Pour settings of a zone
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ZoneFill {
+    pub mode: ZoneFillMode,
+    /// Gap between a pad and the surrounding pour, bridged by the thermal spokes
+    pub thermal_gap: Length,
+    /// Width of the thermal relief spokes
+    pub thermal_bridge_width: Length,
+    pub island_removal: IslandRemoval,
+}
+
+/// KiCad's defaults: solid, 0.5 mm thermal gap and spokes, islands removed
+impl Default for ZoneFill {
+    fn default() -> Self {
+        ZoneFill {
+            mode: ZoneFillMode::Solid,
+            thermal_gap: Length::mm(0.5),
+            thermal_bridge_width: Length::mm(0.5),
+            island_removal: IslandRemoval::Always,
+        }
+    }
+}
+
+/// Solid copper or a grid of hatch lines
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ZoneFillMode {
+    Solid,
+    /// Lines `thickness` wide with `gap` between them, rotated by `orientation` degrees
+    Hatch { thickness: Length, gap: Length, orientation: f64 },
+}
+
+/// Which isolated copper islands a zone fill removes
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IslandRemoval {
+    /// Every island (`island_removal_mode 0`)
+    Always,
+    /// None (`island_removal_mode 1`)
+    Never,
+    /// Islands smaller than this area in mm² (`island_removal_mode 2`)
+    BelowArea(f64),
+}
+
+/// One island of poured copper; holes are joined to the outline by cut lines
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilledPolygon {
+    pub layer: String,
+    pub points: Vec<Point>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Closed board outline reconstructed from Edge.Cuts, with its interior cutouts
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardPolygon {
+    pub outline: Vec<Point>,
+    pub cutouts: Vec<Vec<Point>>,
+}
+
+impl BoardPolygon {
+    /// Board area in mm² (outline minus cutouts)
+    pub fn area(&self) -> f64 {
+        use super::geometry::polygon_area;
+        polygon_area(&self.outline).abs()
+            - self.cutouts.iter().map(|c| polygon_area(c).abs()).sum::<f64>()
+    }
+
+    /// Axis-aligned bounding box of the outline
+    pub fn bounds(&self) -> Rect {
+        let (start, end) = super::geometry::bounds(&self.outline);
+        Rect { start, end }
+    }
+}
+
+/// Rule area (keepout zone) restricting what may be placed inside its outline
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleArea {
+    pub name: Option<String>,
+    pub uuid: String,
+    /// Layers the rules apply to; may contain wildcards such as `"*.Cu"`
+    pub layers: Vec<String>,
+    pub keepout: KeepoutRules,
+    pub polygon: Vec<Point>,
+}
+
+/// Object types excluded by a rule area; `true` means the object is not allowed
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeepoutRules {
+    pub tracks: bool,
+    pub vias: bool,
+    pub pads: bool,
+    pub copper_pour: bool,
+    pub footprints: bool,
+}
+
+impl RuleArea {
+    /// Whether the area's rules apply on `layer`, honoring `*.Cu` and `F&B.Cu` wildcards
+    pub fn applies_to_layer(&self, layer: &str) -> bool {
+        self.layers.iter().any(|l| match l.as_str() {
+            "*.Cu" => layer.ends_with(".Cu"),
+            "F&B.Cu" => layer == "F.Cu" || layer == "B.Cu",
+            other => other == layer,
+        })
+    }
+
+    /// Whether `point` lies inside the area outline
+    pub fn contains(&self, point: &Point) -> bool {
+        super::geometry::point_in_polygon(point, &self.polygon)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Text {
     pub text: String,
     pub position: Point,
@@ -113,49 +1013,119 @@ pub struct Text {
     pub effects: TextEffects,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Framed multi-line text block (`gr_text_box`, KiCad 7+)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextBox {
+    pub text: String,
+    pub start: Point,
+    pub end: Point,
+    /// Left, top, right and bottom margins between frame and text
+    pub margins: Option<[Length; 4]>,
+    pub layer: String,
+    pub effects: TextEffects,
+    pub border: bool,
+    pub border_width: Length,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TextEffects {
     pub font_size: Point,
-    pub thickness: f64,
+    pub thickness: Length,
     pub bold: bool,
     pub italic: bool,
     pub justify: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Graphic {
     Line {
         start: Point,
         end: Point,
         layer: String,
-        width: f64,
+        width: Length,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
     },
     Circle {
         center: Point,
-        radius: f64,
+        radius: Length,
         layer: String,
-        width: f64,
+        width: Length,
         filled: bool,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
     },
     Arc {
         arc: Arc,
         layer: String,
-        width: f64,
+        width: Length,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
     },
     Rectangle {
         rect: Rect,
         layer: String,
-        width: f64,
+        width: Length,
         filled: bool,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
     },
     Polygon {
         points: Vec<Point>,
         layer: String,
-        width: f64,
+        width: Length,
         filled: bool,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
+    },
+    /// Cubic Bezier curve (`gr_curve`, `fp_curve`) from `start` to `end`
+    Bezier {
+        start: Point,
+        control1: Point,
+        control2: Point,
+        end: Point,
+        layer: String,
+        width: Length,
+        #[cfg_attr(feature = "serde", serde(default))]
+        uuid: String,
     },
 }
 
+impl Graphic {
+    /// Layer the graphic is drawn on
+    pub fn layer(&self) -> &str {
+        match self {
+            Graphic::Line { layer, .. }
+            | Graphic::Circle { layer, .. }
+            | Graphic::Arc { layer, .. }
+            | Graphic::Rectangle { layer, .. }
+            | Graphic::Polygon { layer, .. }
+            | Graphic::Bezier { layer, .. } => layer,
+        }
+    }
+
+    /// Identifier of the drawing, empty when the file gives none
+    pub fn uuid(&self) -> &str {
+        match self {
+            Graphic::Line { uuid, .. }
+            | Graphic::Circle { uuid, .. }
+            | Graphic::Arc { uuid, .. }
+            | Graphic::Rectangle { uuid, .. }
+            | Graphic::Polygon { uuid, .. }
+            | Graphic::Bezier { uuid, .. } => uuid,
+        }
+    }
+}
+
+impl Default for PcbFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl PcbFile {
     pub fn new() -> Self {
         Self {
@@ -163,14 +1133,320 @@ impl PcbFile {
             generator: String::new(),
             board_thickness: None,
             paper_size: None,
+            metadata: BoardMetadata::default(),
+            title_block: None,
             layers: HashMap::new(),
+            stackup: Vec::new(),
             footprints: Vec::new(),
             tracks: Vec::new(),
+            arcs: Vec::new(),
             vias: Vec::new(),
             zones: Vec::new(),
+            rule_areas: Vec::new(),
             texts: Vec::new(),
+            text_boxes: Vec::new(),
             graphics: Vec::new(),
+            nets: HashMap::new(),
+            net_classes: HashMap::new(),
+            groups: Vec::new(),
+            tuning_patterns: Vec::new(),
+            images: Vec::new(),
+            embedded_files: Vec::new(),
+            skipped: Vec::new(),
+            footprint_index: FootprintIndex::default(),
+        }
+    }
+
+    /// Footprint with the given reference designator, e.g. `"U1"`
+    ///
+    /// The first call indexes the footprints by reference, later calls are hash
+    /// lookups. Hits are checked against the footprint list and misses fall back
+    /// to a scan, so the answer stays right after `footprints` is edited; call
+    /// [`PcbFile::reindex`] afterwards to get fast lookups back.
+    pub fn footprint_by_ref(&self, reference: &str) -> Option<&Footprint> {
+        let index = self.footprint_index.0.get_or_init(|| {
+            let mut index = HashMap::new();
+            for (i, footprint) in self.footprints.iter().enumerate() {
+                if let Some(reference) = footprint.property("Reference") {
+                    index.entry(reference.to_string()).or_insert(i);
+                }
+            }
+            Box::new(index)
+        });
+        let is_match = |f: &&Footprint| f.property("Reference") == Some(reference);
+        index
+            .get(reference)
+            .and_then(|&i| self.footprints.get(i))
+            .filter(is_match)
+            .or_else(|| self.footprints.iter().find(is_match))
+    }
+
+    /// Drop the reference index after editing `footprints`
+    pub fn reindex(&mut self) {
+        self.footprint_index = FootprintIndex::default();
+    }
+
+    /// Reference designators of the footprints in board order; footprints without one are skipped
+    pub fn refs(&self) -> impl Iterator<Item = &str> {
+        self.footprints.iter().filter_map(|f| f.property("Reference"))
+    }
+
+    /// Tuning patterns applied to the net named `net`
+    pub fn tuning_patterns_for_net<'a>(&'a self, net: &'a str) -> impl Iterator<Item = &'a TuningPattern> + 'a {
+        self.tuning_patterns
+            .iter()
+            .filter(move |p| p.net.as_deref() == Some(net))
+    }
+
+    /// Length-tuned nets with their target length windows
+    ///
+    /// Patterns without a length target (delay or skew tuning) are skipped. When a net
+    /// carries several patterns the first one wins; KiCad keeps their targets in sync.
+    pub fn tuned_nets(&self) -> HashMap<&str, LengthTarget> {
+        let mut nets = HashMap::new();
+        for pattern in &self.tuning_patterns {
+            if let (Some(net), Some(target)) = (&pattern.net, pattern.target_length) {
+                nets.entry(net.as_str()).or_insert(target);
+            }
+        }
+        nets
+    }
+
+    /// Tracks generated by a tuning pattern
+    pub fn tuning_tracks(&self, pattern: &TuningPattern) -> Vec<&Track> {
+        self.tracks
+            .iter()
+            .filter(|t| pattern.members.contains(&t.uuid))
+            .collect()
+    }
+
+    /// Board outline polygons reconstructed from Edge.Cuts graphics
+    ///
+    /// Board-level drawings and footprint Edge.Cuts drawings (transformed to board
+    /// coordinates) are chained into closed outlines with their cutouts.
+    pub fn board_outline(&self) -> Vec<BoardPolygon> {
+        use super::geometry::{outline_piece, reconstruct_polygons, to_board, OutlinePiece};
+
+        let on_edge = |g: &&Graphic| g.layer() == "Edge.Cuts";
+        let mut pieces: Vec<OutlinePiece> = self.graphics.iter().filter(on_edge).map(outline_piece).collect();
+        for footprint in &self.footprints {
+            for graphic in footprint.graphics.iter().filter(on_edge) {
+                let transform = |points: Vec<Point>| {
+                    points
+                        .iter()
+                        .map(|p| to_board(p, &footprint.position, footprint.rotation))
+                        .collect()
+                };
+                pieces.push(match outline_piece(graphic) {
+                    OutlinePiece::Open(points) => OutlinePiece::Open(transform(points)),
+                    OutlinePiece::Closed(points) => OutlinePiece::Closed(transform(points)),
+                });
+            }
         }
+        reconstruct_polygons(pieces)
+    }
+
+    /// Embedded file a footprint's `kicad-embed://` model refers to
+    ///
+    /// Files embedded in the footprint take precedence over those embedded in the board.
+    /// Returns `None` for models referenced by a regular file path.
+    pub fn resolve_embedded_model<'a>(&'a self, footprint: &'a Footprint, model: &Model3D) -> Option<&'a EmbeddedFile> {
+        let name = model.embedded_name()?;
+        footprint
+            .embedded_files
+            .iter()
+            .chain(&self.embedded_files)
+            .find(|f| f.name == name)
+    }
+
+    /// First group with the given name
+    pub fn group(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Resolve the direct members of a group to board items
+    ///
+    /// Member UUIDs that refer to items not modeled in [`PcbFile`] (e.g. graphics)
+    /// are skipped.
+    pub fn group_members(&self, group: &Group) -> Vec<GroupMember<'_>> {
+        group
+            .members
+            .iter()
+            .filter_map(|uuid| self.group_member(uuid))
+            .collect()
+    }
+
+    /// Footprints in a group, including those in nested groups
+    pub fn group_footprints(&self, group: &Group) -> Vec<&Footprint> {
+        let mut footprints = Vec::new();
+        let mut pending = vec![group];
+        let mut visited = Vec::new();
+        while let Some(group) = pending.pop() {
+            if visited.contains(&group.uuid.as_str()) {
+                continue;
+            }
+            visited.push(group.uuid.as_str());
+            for member in self.group_members(group) {
+                match member {
+                    GroupMember::Footprint(f) => footprints.push(f),
+                    GroupMember::Group(g) => pending.push(g),
+                    _ => {}
+                }
+            }
+        }
+        footprints
+    }
+
+    fn group_member(&self, uuid: &str) -> Option<GroupMember<'_>> {
+        if uuid.is_empty() {
+            return None;
+        }
+        if let Some(f) = self.footprints.iter().find(|f| f.uuid == uuid) {
+            return Some(GroupMember::Footprint(f));
+        }
+        if let Some(t) = self.tracks.iter().find(|t| t.uuid == uuid) {
+            return Some(GroupMember::Track(t));
+        }
+        if let Some(v) = self.vias.iter().find(|v| v.uuid == uuid) {
+            return Some(GroupMember::Via(v));
+        }
+        if let Some(z) = self.zones.iter().find(|z| z.uuid == uuid) {
+            return Some(GroupMember::Zone(z));
+        }
+        self.groups.iter().find(|g| g.uuid == uuid).map(GroupMember::Group)
+    }
+
+    /// Name of the declared net with the given number
+    pub fn net_name(&self, id: u32) -> Option<&str> {
+        self.nets.get(&id).map(String::as_str)
+    }
+
+    /// Number of the declared net with the given name
+    pub fn net_id(&self, name: &str) -> Option<u32> {
+        self.nets.iter().find(|(_, n)| *n == name).map(|(id, _)| *id)
+    }
+
+    /// Vertical distance between two copper layers, e.g. a via barrel length
+    ///
+    /// Sums the stackup layers from `from` to `to`, both copper layers included, so a
+    /// through span measures copper to copper as the board thickness does. Without a
+    /// stackup only the outer-layer pair is known, as the board thickness.
+    pub fn copper_distance(&self, from: &str, to: &str) -> Option<Length> {
+        if from == to {
+            return Some(Length::ZERO);
+        }
+        if self.stackup.is_empty() {
+            let outer = |a: &str, b: &str| a == "F.Cu" && b == "B.Cu";
+            return if outer(from, to) || outer(to, from) { self.board_thickness } else { None };
+        }
+        let a = self.stackup.iter().position(|l| l.name == from)?;
+        let b = self.stackup.iter().position(|l| l.name == to)?;
+        let (a, b) = (a.min(b), a.max(b));
+        Some(self.stackup[a..=b].iter().filter_map(|l| l.thickness).sum())
+    }
+
+    /// Counts, lengths and minimum feature sizes of the whole board
+    pub fn stats(&self) -> BoardStats {
+        let mut components_by_prefix = BTreeMap::new();
+        for footprint in &self.footprints {
+            let reference = footprint.property("Reference").unwrap_or_default();
+            let prefix: String = reference.chars().take_while(|c| !c.is_ascii_digit()).collect();
+            *components_by_prefix.entry(prefix).or_insert(0) += 1;
+        }
+
+        let mut track_length_by_layer = BTreeMap::new();
+        let segments = self
+            .tracks
+            .iter()
+            .map(|t| (&t.layer, t.width.as_mm(), t.length()))
+            .chain(self.arcs.iter().map(|a| (&a.layer, a.width.as_mm(), a.length())));
+        let mut min_track_width: Option<f64> = None;
+        for (layer, width, length) in segments {
+            *track_length_by_layer.entry(layer.clone()).or_insert(0.0) += length;
+            min_track_width = Some(min_track_width.map_or(width, |w| w.min(width)));
+        }
+
+        let mut vias_by_type = BTreeMap::new();
+        for via in &self.vias {
+            *vias_by_type.entry(via.via_type).or_insert(0) += 1;
+        }
+        let pads = self.footprints.iter().flat_map(|f| &f.pads);
+        let min_drill = self
+            .vias
+            .iter()
+            .map(|v| v.drill.as_mm())
+            .chain(pads.clone().filter_map(|p| p.drill.map(Length::as_mm)))
+            .filter(|d| *d > 0.0)
+            .fold(None, |min: Option<f64>, d| Some(min.map_or(d, |m| m.min(d))));
+
+        let outline = self.board_outline();
+        BoardStats {
+            layer_count: self.layers.len(),
+            copper_layer_count: self.layers.values().filter(|l| l.name.ends_with(".Cu")).count(),
+            component_count: self.footprints.len(),
+            components_by_prefix,
+            pad_count: pads.count(),
+            track_count: self.tracks.len() + self.arcs.len(),
+            track_length: track_length_by_layer.values().sum(),
+            track_length_by_layer,
+            via_count: self.vias.len(),
+            vias_by_type,
+            min_drill,
+            min_track_width,
+            board_area: (!outline.is_empty()).then(|| outline.iter().map(BoardPolygon::area).sum()),
+        }
+    }
+
+    /// Every footprint pad with its board position, in footprint order
+    ///
+    /// Pad positions in the file are local to the footprint; this applies the
+    /// footprint's position and rotation. Bottom-side footprints need no extra
+    /// mirroring: KiCad stores their pads already flipped.
+    pub fn pads_absolute(&self) -> impl Iterator<Item = BoardPad<'_>> {
+        self.footprints.iter().flat_map(|footprint| {
+            let reference = footprint.property("Reference").unwrap_or_default();
+            let bottom = footprint.layer == "B.Cu";
+            footprint.pads.iter().map(move |pad| BoardPad {
+                reference,
+                footprint,
+                pad,
+                position: footprint.pad_position(pad),
+                rotation: pad.rotation,
+                bottom,
+            })
+        })
+    }
+
+    /// Mask and paste margins in effect for a pad of `footprint`
+    ///
+    /// Each value comes from the pad if set there, else from the footprint, else
+    /// from the board setup; use [`MaskPasteMargins::mask_size`] and
+    /// [`MaskPasteMargins::paste_size`] for the resulting openings.
+    pub fn pad_margins(&self, footprint: &Footprint, pad: &Pad) -> MaskPasteMargins {
+        pad.margins.or(footprint.margins).or(self.metadata.margins)
+    }
+
+    /// Layers selected in the saved plot settings, in layer id order
+    ///
+    /// Empty when the board has no `pcbplotparams`; ids the board does not define are skipped.
+    pub fn plot_layers(&self) -> Vec<&Layer> {
+        let Some(params) = &self.metadata.plot_params else { return Vec::new() };
+        params.layers.iter().filter_map(|id| self.layers.get(id)).collect()
+    }
+
+    /// Protection of a via with the board defaults applied; settings neither
+    /// the via nor the board makes are off
+    pub fn via_finish(&self, via: &Via) -> ViaFinish {
+        let off = ViaSides { front: Some(false), back: Some(false) };
+        let none = ViaFinish { tenting: off, covering: off, plugging: off, filling: Some(false), capping: Some(false) };
+        via.finish.or(self.metadata.via_defaults).or(none)
+    }
+
+    /// Net class a net is explicitly assigned to, if any
+    pub fn net_class_of(&self, net_name: &str) -> Option<&NetClass> {
+        self.net_classes
+            .values()
+            .find(|c| c.nets.iter().any(|n| n == net_name))
     }
 
     pub fn get_footprints_on_layer(&self, layer_name: &str) -> Vec<&Footprint> {
@@ -188,12 +1464,27 @@ impl PcbFile {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// Library symbol with its properties, pins and graphics
+///
+/// Pins and graphics of all units and body styles are collected into the flat
+/// lists; each [`Pin`] records the unit it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Symbol {
     pub name: String,
-    pub pin_names_offset: f64,
+    /// Parent symbol this one is derived from (`(extends "Parent")`)
+    pub extends: Option<String>,
+    pub pin_names_offset: Length,
+    pub pin_names_hidden: bool,
+    pub pin_numbers_hidden: bool,
+    pub power: bool,
     pub in_bom: bool,
     pub on_board: bool,
+    pub exclude_from_sim: bool,
+    /// Number of units (gates); 1 for single-unit symbols
+    pub unit_count: u32,
+    /// Custom unit display names (KiCad 7+), keyed by unit number
+    pub unit_names: HashMap<u32, String>,
     pub properties: Vec<Property>,
     pub pins: Vec<Pin>,
     pub rectangles: Vec<Rectangle>,
@@ -202,7 +1493,126 @@ pub struct Symbol {
     pub polylines: Vec<Polyline>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+impl Symbol {
+    /// Electrical pins across all units
+    ///
+    /// Pins repeated on the De Morgan alternate body style are reported once, and
+    /// pins shared by all units (unit 0) are listed once rather than per unit.
+    pub fn pins(&self) -> Vec<&Pin> {
+        let mut seen = crate::collections::HashSet::new();
+        let mut pins: Vec<&Pin> = self.pins.iter().filter(|p| p.body_style != 2).collect();
+        pins.extend(self.pins.iter().filter(|p| p.body_style == 2));
+        pins.retain(|p| seen.insert((p.unit, p.number.as_str())));
+        pins
+    }
+
+    /// Pins of a single unit, including the pins common to all units
+    pub fn unit_pins(&self, unit: u32) -> Vec<&Pin> {
+        self.pins()
+            .into_iter()
+            .filter(|p| p.unit == unit || p.unit == 0)
+            .collect()
+    }
+
+    /// Value of the named property
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.value.as_str())
+    }
+
+    /// All properties as a name -> value map
+    pub fn property_map(&self) -> HashMap<&str, &str> {
+        self.properties
+            .iter()
+            .map(|p| (p.name.as_str(), p.value.as_str()))
+            .collect()
+    }
+
+    /// Description, from `Description` or the KiCad 6/7 `ki_description` property
+    pub fn description(&self) -> Option<&str> {
+        self.property("Description").or_else(|| self.property("ki_description"))
+    }
+
+    /// Search keywords from `ki_keywords`
+    pub fn keywords(&self) -> Vec<&str> {
+        self.property("ki_keywords")
+            .map(|k| k.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Footprint filter patterns from `ki_fp_filters`
+    pub fn fp_filters(&self) -> Vec<&str> {
+        self.property("ki_fp_filters")
+            .map(|f| f.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// First pin with the given number
+    pub fn pin(&self, number: &str) -> Option<&Pin> {
+        self.pins.iter().find(|p| p.number == number)
+    }
+
+    /// Merge what a derived symbol inherits from `parent`
+    ///
+    /// Pins, graphics, units and pin display settings come from the parent, since
+    /// derived symbols cannot define their own. Properties missing here are copied
+    /// over; properties set on the derived symbol win.
+    pub fn inherit_from(&mut self, parent: &Symbol) {
+        self.pin_names_offset = parent.pin_names_offset;
+        self.pin_names_hidden = parent.pin_names_hidden;
+        self.pin_numbers_hidden = parent.pin_numbers_hidden;
+        self.power = parent.power;
+        self.unit_count = parent.unit_count;
+        self.unit_names = parent.unit_names.clone();
+        self.pins = parent.pins.clone();
+        self.rectangles = parent.rectangles.clone();
+        self.circles = parent.circles.clone();
+        self.arcs = parent.arcs.clone();
+        self.polylines = parent.polylines.clone();
+        for property in &parent.properties {
+            if !self.properties.iter().any(|p| p.name == property.name) {
+                self.properties.push(property.clone());
+            }
+        }
+    }
+}
+
+/// Direction a pin points from its connection point towards the symbol body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PinOrientation {
+    Right,
+    Up,
+    Left,
+    Down,
+}
+
+impl Pin {
+    /// Orientation derived from the pin rotation (0° points right, 90° up)
+    pub fn orientation(&self) -> PinOrientation {
+        match (self.rotation.rem_euclid(360.0) / 90.0).round() as u32 % 4 {
+            1 => PinOrientation::Up,
+            2 => PinOrientation::Left,
+            3 => PinOrientation::Down,
+            _ => PinOrientation::Right,
+        }
+    }
+
+    /// Where the pin meets the symbol body, at `length` from the connection point
+    pub fn body_end(&self) -> Point {
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        // Symbol coordinates have y pointing up
+        Point {
+            x: self.at.x + self.length * cos,
+            y: self.at.y + self.length * sin,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Property {
     pub name: String,
     pub value: String,
@@ -211,79 +1621,113 @@ pub struct Property {
     pub effects: Option<Effects>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Effects {
     pub font: Font,
     pub justify: Option<String>,
     pub hide: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     pub size: Point,
-    pub thickness: Option<f64>,
+    pub thickness: Option<Length>,
     pub bold: bool,
     pub italic: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pin {
     pub number: String,
     pub name: String,
+    /// Electrical type, e.g. `input`, `bidirectional`, `power_in`
     pub pin_type: String,
+    /// Graphic style, e.g. `line`, `inverted`, `clock`
+    pub shape: String,
     pub at: Point,
-    pub length: f64,
+    pub length: Length,
     pub rotation: f64,
+    pub hidden: bool,
+    /// Unit the pin belongs to; 0 means common to all units
+    pub unit: u32,
+    /// Body style (1 normal, 2 De Morgan alternate); 0 means common to both
+    pub body_style: u32,
     pub name_effects: Option<Effects>,
     pub number_effects: Option<Effects>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rectangle {
     pub start: Point,
     pub end: Point,
     pub stroke: Stroke,
     pub fill: Fill,
+    /// Unit the drawing belongs to; 0 means common to all units
+    pub unit: u32,
+    /// Body style (1 normal, 2 De Morgan alternate); 0 means common to both
+    pub body_style: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Circle {
     pub center: Point,
-    pub radius: f64,
+    pub radius: Length,
     pub stroke: Stroke,
     pub fill: Fill,
+    /// Unit the drawing belongs to; 0 means common to all units
+    pub unit: u32,
+    /// Body style (1 normal, 2 De Morgan alternate); 0 means common to both
+    pub body_style: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolArc {
     pub start: Point,
     pub mid: Point,
     pub end: Point,
     pub stroke: Stroke,
     pub fill: Fill,
+    /// Unit the drawing belongs to; 0 means common to all units
+    pub unit: u32,
+    /// Body style (1 normal, 2 De Morgan alternate); 0 means common to both
+    pub body_style: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Polyline {
     pub points: Vec<Point>,
     pub stroke: Stroke,
     pub fill: Fill,
+    /// Unit the drawing belongs to; 0 means common to all units
+    pub unit: u32,
+    /// Body style (1 normal, 2 De Morgan alternate); 0 means common to both
+    pub body_style: u32,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stroke {
-    pub width: f64,
+    pub width: Length,
     pub stroke_type: String,
     pub color: Option<Color>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fill {
     pub fill_type: String,
     pub color: Option<Color>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -0,0 +1,122 @@
+//! Board elements by UUID
+//!
+//! KiCad gives footprints, tracks, vias, zones and drawings an identifier that
+//! survives edits (`uuid`, or `tstamp` before KiCad 7), so tools that follow
+//! elements across revisions of a board key them by it. [`PcbFile::by_uuid`]
+//! finds a single element; [`PcbFile::uuid_index`] hashes all of them once for
+//! repeated lookups. Like the spatial index, a [`UuidIndex`] borrows the board
+//! and is not updated when the board changes.
+//!
+//! ```rust
+//! use kiparse::pcb::{parse_pcb, query::Element};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "R" (uuid "fp-1") (at 10 10) (property "Reference" "R1")
+//!     (fp_line (start -1 0) (end 1 0) (stroke (width 0.12)) (layer "F.SilkS") (uuid "fp-line-1")))
+//!   (segment (start 0 0) (end 20 0) (width 0.2) (layer "F.Cu") (tstamp "trk-1"))
+//!   (gr_line (start 0 0) (end 50 0) (stroke (width 0.05)) (layer "Edge.Cuts") (uuid "edge-1")))"#)?;
+//!
+//! assert_eq!(pcb.by_uuid("trk-1").unwrap().kind().name(), "track");
+//! let index = pcb.uuid_index();
+//! assert_eq!(index.len(), 4);
+//! assert!(matches!(index.get("fp-line-1"), Some(Element::Graphic { reference: Some("R1"), .. })));
+//! assert!(index.get("missing").is_none());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use super::query::Element;
+use super::types::PcbFile;
+
+/// Elements of a board keyed by UUID, see the [module docs](self)
+pub struct UuidIndex<'a> {
+    elements: HashMap<&'a str, Element<'a>>,
+}
+
+impl<'a> UuidIndex<'a> {
+    /// Index every element of `pcb` that has a UUID
+    ///
+    /// Should a UUID appear twice, the element found first in the file wins, as
+    /// with [`PcbFile::by_uuid`].
+    pub fn new(pcb: &'a PcbFile) -> Self {
+        let mut elements = HashMap::new();
+        for (uuid, element) in identified(pcb) {
+            elements.entry(uuid).or_insert(element);
+        }
+        UuidIndex { elements }
+    }
+
+    /// Element with the given UUID
+    pub fn get(&self, uuid: &str) -> Option<Element<'a>> {
+        self.elements.get(uuid).copied()
+    }
+
+    /// Number of indexed elements
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+}
+
+/// Elements with a UUID in file order: footprints with their drawings, then
+/// tracks, arcs, vias, zones and board drawings
+fn identified(pcb: &PcbFile) -> impl Iterator<Item = (&str, Element<'_>)> {
+    let footprints = pcb.footprints.iter().flat_map(|footprint| {
+        let reference = Some(footprint.reference());
+        std::iter::once((footprint.uuid.as_str(), Element::Footprint(footprint))).chain(
+            footprint
+                .graphics
+                .iter()
+                .map(move |graphic| (graphic.uuid(), Element::Graphic { reference, graphic })),
+        )
+    });
+    footprints
+        .chain(pcb.tracks.iter().map(|t| (t.uuid.as_str(), Element::Track(t))))
+        .chain(pcb.arcs.iter().map(|a| (a.uuid.as_str(), Element::Arc(a))))
+        .chain(pcb.vias.iter().map(|v| (v.uuid.as_str(), Element::Via(v))))
+        .chain(pcb.zones.iter().map(|z| (z.uuid.as_str(), Element::Zone(z))))
+        .chain(pcb.graphics.iter().map(|g| (g.uuid(), Element::Graphic { reference: None, graphic: g })))
+        .filter(|(uuid, _)| !uuid.is_empty())
+}
+
+impl PcbFile {
+    /// Footprint, track, arc, via, zone or drawing with the given UUID
+    ///
+    /// Scans the board; for many lookups build a [`uuid_index`](Self::uuid_index) instead.
+    pub fn by_uuid(&self, uuid: &str) -> Option<Element<'_>> {
+        identified(self).find(|(u, _)| *u == uuid).map(|(_, element)| element)
+    }
+
+    /// Hash map from UUID to element for repeated lookups
+    ///
+    /// Built on every call; keep the index around while the board is unchanged.
+    pub fn uuid_index(&self) -> UuidIndex<'_> {
+        UuidIndex::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcb::parse_pcb;
+    use crate::pcb::query::Element;
+
+    #[test]
+    fn test_uuid_index_matches_lookup() {
+        let pcb = parse_pcb(include_str!("../../assets/fpga.kicad_pcb")).unwrap();
+        let index = pcb.uuid_index();
+        assert!(index.len() > pcb.footprints.len() + pcb.tracks.len());
+
+        let footprint = &pcb.footprints[0];
+        assert!(matches!(index.get(&footprint.uuid), Some(Element::Footprint(f)) if f.uuid == footprint.uuid));
+        for via in pcb.vias.iter().take(20) {
+            assert!(matches!(pcb.by_uuid(&via.uuid), Some(Element::Via(v)) if std::ptr::eq(v, via)));
+            assert!(matches!(index.get(&via.uuid), Some(Element::Via(v)) if std::ptr::eq(v, via)));
+        }
+        assert!(pcb.by_uuid("").is_none());
+        assert!(index.get("").is_none());
+    }
+}
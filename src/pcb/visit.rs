@@ -0,0 +1,175 @@
+//! Visitor over every element of a board
+//!
+//! Implement [`PcbVisitor`] for the element kinds an analysis pass cares about;
+//! every method has an empty default, so a pass only overrides what it needs.
+//! [`PcbFile::walk`] then calls the visitor for each element in file order:
+//! layers (by number), nets (by number), footprints with their pads, drawings
+//! and texts, then tracks, track arcs, vias, zones, rule areas and board-level
+//! drawings, texts and text boxes.
+//!
+//! ```rust
+//! use kiparse::pcb::{parse_pcb, Pad, Footprint, Via, visit::PcbVisitor};
+//!
+//! #[derive(Default)]
+//! struct Holes(usize);
+//!
+//! impl PcbVisitor for Holes {
+//!     fn visit_pad(&mut self, _footprint: &Footprint, pad: &Pad) {
+//!         self.0 += pad.drill.is_some() as usize;
+//!     }
+//!     fn visit_via(&mut self, _via: &Via) {
+//!         self.0 += 1;
+//!     }
+//! }
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (footprint "MountingHole" (at 5 5) (pad "" np_thru_hole circle (at 0 0) (size 3 3) (drill 3)))
+//!   (via (at 10 10) (size 0.6) (drill 0.3) (layers "F.Cu" "B.Cu")))"#)?;
+//! let mut holes = Holes::default();
+//! pcb.walk(&mut holes);
+//! assert_eq!(holes.0, 2);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use super::types::{
+    Footprint, Graphic, Layer, Pad, PcbFile, RuleArea, Text, TextBox, Track, TrackArc, Via, Zone,
+};
+
+/// Callbacks for [`PcbFile::walk`], one per element kind
+///
+/// Footprint children are passed together with their footprint, so pad and
+/// drawing positions (which are footprint-local) can be placed on the board.
+#[allow(unused_variables)]
+pub trait PcbVisitor {
+    fn visit_layer(&mut self, layer: &Layer) {}
+    fn visit_net(&mut self, id: u32, name: &str) {}
+    /// Called before the footprint's pads, drawings and texts
+    fn visit_footprint(&mut self, footprint: &Footprint) {}
+    fn visit_pad(&mut self, footprint: &Footprint, pad: &Pad) {}
+    fn visit_footprint_graphic(&mut self, footprint: &Footprint, graphic: &Graphic) {}
+    fn visit_footprint_text(&mut self, footprint: &Footprint, text: &Text) {}
+    fn visit_track(&mut self, track: &Track) {}
+    fn visit_arc(&mut self, arc: &TrackArc) {}
+    fn visit_via(&mut self, via: &Via) {}
+    fn visit_zone(&mut self, zone: &Zone) {}
+    fn visit_rule_area(&mut self, area: &RuleArea) {}
+    /// Board-level drawing (`gr_*`), including the Edge.Cuts outline
+    fn visit_graphic(&mut self, graphic: &Graphic) {}
+    fn visit_text(&mut self, text: &Text) {}
+    fn visit_text_box(&mut self, text_box: &TextBox) {}
+}
+
+impl PcbFile {
+    /// Call `visitor` for every element of the board, see the [module docs](super::visit)
+    pub fn walk<V: PcbVisitor + ?Sized>(&self, visitor: &mut V) {
+        let mut layers: Vec<&Layer> = self.layers.values().collect();
+        layers.sort_by_key(|l| l.id);
+        for layer in layers {
+            visitor.visit_layer(layer);
+        }
+        let mut nets: Vec<(&u32, &String)> = self.nets.iter().collect();
+        nets.sort();
+        for (id, name) in nets {
+            visitor.visit_net(*id, name);
+        }
+
+        for footprint in &self.footprints {
+            visitor.visit_footprint(footprint);
+            for pad in &footprint.pads {
+                visitor.visit_pad(footprint, pad);
+            }
+            for graphic in &footprint.graphics {
+                visitor.visit_footprint_graphic(footprint, graphic);
+            }
+            for text in footprint.fields.iter().map(|f| &f.text).chain(&footprint.texts) {
+                visitor.visit_footprint_text(footprint, text);
+            }
+        }
+
+        self.tracks.iter().for_each(|t| visitor.visit_track(t));
+        self.arcs.iter().for_each(|a| visitor.visit_arc(a));
+        self.vias.iter().for_each(|v| visitor.visit_via(v));
+        self.zones.iter().for_each(|z| visitor.visit_zone(z));
+        self.rule_areas.iter().for_each(|r| visitor.visit_rule_area(r));
+        self.graphics.iter().for_each(|g| visitor.visit_graphic(g));
+        self.texts.iter().for_each(|t| visitor.visit_text(t));
+        self.text_boxes.iter().for_each(|t| visitor.visit_text_box(t));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+    use std::collections::BTreeMap;
+
+    const FPGA: &str = include_str!("../../assets/fpga.kicad_pcb");
+
+    /// Counts elements per kind, in the order they were visited
+    #[derive(Default)]
+    struct Counter {
+        order: Vec<&'static str>,
+        counts: BTreeMap<&'static str, usize>,
+    }
+
+    impl Counter {
+        fn hit(&mut self, kind: &'static str) {
+            if self.order.last() != Some(&kind) {
+                self.order.push(kind);
+            }
+            *self.counts.entry(kind).or_insert(0) += 1;
+        }
+    }
+
+    impl PcbVisitor for Counter {
+        fn visit_layer(&mut self, _: &Layer) {
+            self.hit("layer");
+        }
+        fn visit_net(&mut self, _: u32, _: &str) {
+            self.hit("net");
+        }
+        fn visit_pad(&mut self, _: &Footprint, _: &Pad) {
+            self.hit("pad");
+        }
+        fn visit_track(&mut self, _: &Track) {
+            self.hit("track");
+        }
+        fn visit_via(&mut self, _: &Via) {
+            self.hit("via");
+        }
+        fn visit_zone(&mut self, _: &Zone) {
+            self.hit("zone");
+        }
+    }
+
+    #[test]
+    fn test_walk_visits_every_element() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let mut counter = Counter::default();
+        pcb.walk(&mut counter);
+
+        assert_eq!(counter.counts["layer"], pcb.layers.len());
+        assert_eq!(counter.counts["net"], pcb.nets.len());
+        assert_eq!(counter.counts["pad"], pcb.stats().pad_count);
+        assert_eq!(counter.counts["track"], pcb.tracks.len());
+        assert_eq!(counter.counts["via"], pcb.vias.len());
+        assert_eq!(counter.order[..3], ["layer", "net", "pad"]);
+    }
+
+    #[test]
+    fn test_footprint_children_come_with_their_footprint() {
+        struct PadRefs(Vec<String>);
+        impl PcbVisitor for PadRefs {
+            fn visit_pad(&mut self, footprint: &Footprint, pad: &Pad) {
+                let reference = footprint.property("Reference").unwrap_or_default();
+                self.0.push(format!("{}.{}", reference, pad.number));
+            }
+        }
+        let pcb = parse_pcb(r#"(kicad_pcb
+  (footprint "R" (property "Reference" "R1") (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu")) (pad "2" smd rect (at 1 0) (size 1 1) (layers "F.Cu")))
+  (footprint "C" (property "Reference" "C1") (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu"))))"#).unwrap();
+        let mut refs = PadRefs(Vec::new());
+        pcb.walk(&mut refs);
+        assert_eq!(refs.0, ["R1.1", "R1.2", "C1.1"]);
+    }
+}
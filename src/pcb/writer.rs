@@ -0,0 +1,626 @@
+//! `.kicad_pcb` writer
+//!
+//! Turns a [`PcbFile`] back into board text in KiCad 8's layout, so boards built
+//! with the [builders](super::builder) or modified in code can be opened in
+//! Pcbnew. Layers, nets, footprints (properties, attributes, drawings, pads and
+//! 3D models), board drawings and texts, tracks, vias and zones are written;
+//! net classes, the stackup, groups, images, rule areas and tuning patterns are
+//! not. Elements without a uuid are written without one and KiCad assigns fresh
+//! ones on load.
+//!
+//! ```rust
+//! use kiparse::pcb::parse_pcb;
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb (version 20240108) (generator "pcbnew")
+//!   (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+//!   (net 0 "") (net 1 "GND")
+//!   (segment (start 0 0) (end 5 0) (width 0.25) (layer "F.Cu") (net 1)))"#)?;
+//! let text = pcb.to_kicad_pcb();
+//!
+//! assert!(text.contains("(segment\n\t\t(start 0 0)\n\t\t(end 5 0)"));
+//! assert_eq!(parse_pcb(&text)?.tracks, pcb.tracks);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use super::types::{
+    FieldText, Footprint, Graphic, IslandRemoval, MaskPasteMargins, Model3D, Pad, PcbFile, Point, Text,
+    TextEffects, TrackArc, Via, ViaType, Zone, ZoneFillMode,
+};
+use crate::sexpr::SExpr;
+use crate::units::Length;
+
+/// File format version written when the board does not carry a numeric one
+pub const DEFAULT_VERSION: &str = "20240108";
+
+/// Footprint properties KiCad writes first, in this order
+const MANDATORY_PROPERTIES: [&str; 5] = ["Reference", "Value", "Footprint", "Datasheet", "Description"];
+
+/// `.kicad_pcb` text for a whole board
+pub fn write_pcb(pcb: &PcbFile) -> String {
+    let nets = NetTable::new(pcb);
+    let version = if !pcb.version.is_empty() && pcb.version.chars().all(|c| c.is_ascii_digit()) {
+        pcb.version.as_str()
+    } else {
+        DEFAULT_VERSION
+    };
+
+    let mut items = vec![
+        SExpr::list("version", vec![SExpr::atom(version)]),
+        SExpr::list("generator", vec![SExpr::string("kiparse")]),
+        SExpr::list("generator_version", vec![SExpr::string(crate::VERSION)]),
+        SExpr::list(
+            "general",
+            vec![SExpr::list("thickness", vec![SExpr::number(pcb.board_thickness.map_or(1.6, Length::as_mm))])],
+        ),
+        SExpr::list("paper", vec![SExpr::string(pcb.paper_size.as_deref().unwrap_or("A4"))]),
+    ];
+    if let Some(title_block) = &pcb.title_block {
+        let mut fields = Vec::new();
+        for (name, value) in [
+            ("title", &title_block.title),
+            ("date", &title_block.date),
+            ("rev", &title_block.revision),
+            ("company", &title_block.company),
+        ] {
+            if !value.is_empty() {
+                fields.push(SExpr::list(name, vec![SExpr::string(value)]));
+            }
+        }
+        let mut comments: Vec<_> = title_block.comments.iter().collect();
+        comments.sort();
+        for (n, text) in comments {
+            fields.push(SExpr::list("comment", vec![SExpr::atom(n.to_string()), SExpr::string(text)]));
+        }
+        items.push(SExpr::list("title_block", fields));
+    }
+
+    let mut layers: Vec<_> = pcb.layers.values().collect();
+    layers.sort_by_key(|l| l.id);
+    items.push(SExpr::list(
+        "layers",
+        layers
+            .into_iter()
+            .map(|layer| {
+                let mut entry = vec![
+                    SExpr::atom(layer.id.to_string()),
+                    SExpr::string(&layer.name),
+                    SExpr::atom(&layer.layer_type),
+                ];
+                if let Some(user_name) = &layer.user_name {
+                    entry.push(SExpr::string(user_name));
+                }
+                SExpr::List(entry)
+            })
+            .collect(),
+    ));
+    items.extend(nets.declarations());
+
+    for footprint in &pcb.footprints {
+        items.push(footprint_sexpr(footprint, &nets));
+    }
+    for graphic in &pcb.graphics {
+        items.push(graphic_sexpr(graphic, "gr"));
+    }
+    for text in &pcb.texts {
+        items.push(text_sexpr(text, vec![SExpr::atom("gr_text")]));
+    }
+    for track in &pcb.tracks {
+        items.push(SExpr::list(
+            "segment",
+            with_uuid(
+                vec![
+                    xy("start", &track.start),
+                    xy("end", &track.end),
+                    length("width", track.width),
+                    string("layer", &track.layer),
+                    nets.reference(track.net.as_deref()),
+                ],
+                &track.uuid,
+            ),
+        ));
+    }
+    for arc in &pcb.arcs {
+        items.push(track_arc_sexpr(arc, &nets));
+    }
+    for via in &pcb.vias {
+        items.push(via_sexpr(via, &nets));
+    }
+    for zone in &pcb.zones {
+        items.push(zone_sexpr(zone, &nets));
+    }
+    SExpr::list("kicad_pcb", items).to_kicad_string()
+}
+
+impl PcbFile {
+    /// The board as `.kicad_pcb` text, see [`write_pcb`]
+    pub fn to_kicad_pcb(&self) -> String {
+        write_pcb(self)
+    }
+}
+
+/// Net numbers for the `(net N)` references, starting from the board's own table
+struct NetTable {
+    ids: HashMap<String, u32>,
+    names: Vec<(u32, String)>,
+}
+
+impl NetTable {
+    /// The board's net table plus any net used on the board but missing from it
+    fn new(pcb: &PcbFile) -> Self {
+        let mut names: Vec<(u32, String)> = pcb.nets.iter().map(|(id, name)| (*id, name.clone())).collect();
+        if !pcb.nets.contains_key(&0) {
+            names.push((0, String::new()));
+        }
+        names.sort();
+        let mut table = NetTable {
+            ids: names.iter().map(|(id, name)| (name.clone(), *id)).collect(),
+            names,
+        };
+
+        let used = pcb
+            .footprints
+            .iter()
+            .flat_map(|f| f.pads.iter().map(|p| &p.net))
+            .chain(pcb.tracks.iter().map(|t| &t.net))
+            .chain(pcb.arcs.iter().map(|a| &a.net))
+            .chain(pcb.vias.iter().map(|v| &v.net))
+            .chain(pcb.zones.iter().map(|z| &z.net))
+            .flatten();
+        for name in used {
+            if !table.ids.contains_key(name) {
+                let id = table.names.last().map_or(0, |(id, _)| id + 1);
+                table.ids.insert(name.clone(), id);
+                table.names.push((id, name.clone()));
+            }
+        }
+        table
+    }
+
+    fn declarations(&self) -> impl Iterator<Item = SExpr> + '_ {
+        self.names
+            .iter()
+            .map(|(id, name)| SExpr::list("net", vec![SExpr::atom(id.to_string()), SExpr::string(name)]))
+    }
+
+    fn id(&self, net: Option<&str>) -> u32 {
+        net.and_then(|n| self.ids.get(n)).copied().unwrap_or(0)
+    }
+
+    /// `(net N)` as tracks and vias reference it
+    fn reference(&self, net: Option<&str>) -> SExpr {
+        SExpr::list("net", vec![SExpr::atom(self.id(net).to_string())])
+    }
+
+    /// `(net N "name")` as pads carry it
+    fn named(&self, net: &str) -> SExpr {
+        SExpr::list("net", vec![SExpr::atom(self.id(Some(net)).to_string()), SExpr::string(net)])
+    }
+}
+
+fn footprint_sexpr(footprint: &Footprint, nets: &NetTable) -> SExpr {
+    let mut items = vec![SExpr::string(&footprint.name)];
+    if footprint.locked {
+        items.push(SExpr::atom("locked"));
+    }
+    if footprint.placed {
+        items.push(SExpr::atom("placed"));
+    }
+    items.push(string("layer", &footprint.layer));
+    if !footprint.uuid.is_empty() {
+        items.push(string("uuid", &footprint.uuid));
+    }
+    items.push(at(&footprint.position, footprint.rotation));
+
+    let back = footprint.layer == "B.Cu";
+    for (name, value) in ordered_properties(footprint) {
+        let field = match footprint.field(name) {
+            Some(field) => field.clone(),
+            None => {
+                let mut field = FieldText::new(name, value);
+                if back {
+                    field.text.layer = super::builder::flip_layer(&field.text.layer);
+                }
+                field
+            }
+        };
+        let mut property = vec![
+            SExpr::string(name),
+            SExpr::string(value),
+            at(&field.text.position, footprint.rotation),
+            string("layer", &field.text.layer),
+        ];
+        if field.hidden {
+            property.push(SExpr::list("hide", vec![SExpr::yes_no(true)]));
+        }
+        property.push(effects(&field.text.effects));
+        items.push(SExpr::list("property", property));
+    }
+
+    if let Some(path) = &footprint.path {
+        items.push(string("path", path));
+    }
+    if let Some(sheet_name) = &footprint.sheet_name {
+        items.push(string("sheetname", sheet_name));
+    }
+    if let Some(sheet_file) = &footprint.sheet_file {
+        items.push(string("sheetfile", sheet_file));
+    }
+    items.extend(margins(&footprint.margins, "solder_paste_ratio"));
+    if !footprint.net_tie_pad_groups.is_empty() {
+        items.push(SExpr::list(
+            "net_tie_pad_groups",
+            footprint.net_tie_pad_groups.iter().map(|group| SExpr::string(group.join(", "))).collect(),
+        ));
+    }
+
+    let attributes = &footprint.attributes;
+    let mut attr: Vec<SExpr> = Vec::new();
+    if !attributes.mount.is_empty() {
+        attr.push(SExpr::atom(&attributes.mount));
+    }
+    for (set, flag) in [
+        (attributes.board_only, "board_only"),
+        (attributes.exclude_from_pos_files, "exclude_from_pos_files"),
+        (attributes.exclude_from_bom, "exclude_from_bom"),
+        (attributes.allow_missing_courtyard, "allow_missing_courtyard"),
+        (attributes.dnp, "dnp"),
+    ] {
+        if set {
+            attr.push(SExpr::atom(flag));
+        }
+    }
+    if !attr.is_empty() {
+        items.push(SExpr::list("attr", attr));
+    }
+
+    items.extend(footprint.graphics.iter().map(|g| graphic_sexpr(g, "fp")));
+    items.extend(
+        footprint
+            .texts
+            .iter()
+            .map(|t| text_sexpr(t, vec![SExpr::atom("fp_text"), SExpr::atom("user")])),
+    );
+    items.extend(footprint.pads.iter().map(|p| pad_sexpr(p, nets)));
+    items.extend(footprint.models.iter().map(model_sexpr));
+    SExpr::list("footprint", items)
+}
+
+fn pad_sexpr(pad: &Pad, nets: &NetTable) -> SExpr {
+    let mut items = vec![
+        SExpr::string(&pad.number),
+        SExpr::atom(&pad.pad_type),
+        SExpr::atom(&pad.shape),
+        at(&pad.position, pad.rotation),
+        SExpr::list("size", vec![SExpr::number(pad.size.x.as_mm()), SExpr::number(pad.size.y.as_mm())]),
+    ];
+    match (pad.drill, &pad.drill_oval) {
+        (_, Some(oval)) => items.push(SExpr::list(
+            "drill",
+            vec![SExpr::atom("oval"), SExpr::number(oval.x.as_mm()), SExpr::number(oval.y.as_mm())],
+        )),
+        (Some(drill), None) => items.push(length("drill", drill)),
+        (None, None) => {}
+    }
+    items.push(SExpr::list("layers", pad.layers.iter().map(SExpr::string).collect()));
+    if let Some(ratio) = pad.roundrect_ratio {
+        // Ratios are not lengths; KiCad keeps more than six decimals for them
+        items.push(SExpr::list("roundrect_rratio", vec![SExpr::atom(ratio.to_string())]));
+    }
+    if let Some(net) = &pad.net {
+        items.push(nets.named(net));
+    }
+    if let Some(function) = &pad.pin_function {
+        items.push(string("pinfunction", function));
+    }
+    if let Some(pin_type) = &pad.pin_type {
+        items.push(string("pintype", pin_type));
+    }
+    if let Some(die_length) = pad.die_length {
+        items.push(length("die_length", die_length));
+    }
+    items.extend(margins(&pad.margins, "solder_paste_margin_ratio"));
+    SExpr::list("pad", items)
+}
+
+fn model_sexpr(model: &Model3D) -> SExpr {
+    let xyz = |name: &str, v: [f64; 3]| {
+        SExpr::list(name, vec![SExpr::list("xyz", v.iter().copied().map(SExpr::number).collect())])
+    };
+    let mut items = vec![SExpr::string(&model.path)];
+    if model.hide {
+        items.push(SExpr::list("hide", vec![SExpr::yes_no(true)]));
+    }
+    if let Some(opacity) = model.opacity {
+        items.push(number("opacity", opacity));
+    }
+    items.push(xyz("offset", model.offset));
+    items.push(xyz("scale", model.scale));
+    items.push(xyz("rotate", model.rotate));
+    SExpr::list("model", items)
+}
+
+/// Footprint properties in the order KiCad writes them: the mandatory ones, then the rest by name
+pub(crate) fn ordered_properties(footprint: &Footprint) -> Vec<(&String, &String)> {
+    let mut names: Vec<&String> = footprint
+        .properties
+        .keys()
+        .filter(|k| !MANDATORY_PROPERTIES.contains(&k.as_str()))
+        .collect();
+    names.sort();
+    MANDATORY_PROPERTIES
+        .iter()
+        .filter_map(|name| footprint.properties.get_key_value(*name))
+        .chain(names.into_iter().filter_map(|name| footprint.properties.get_key_value(name)))
+        .collect()
+}
+
+/// A drawing as `gr_*` (`prefix` "gr") or `fp_*` (`prefix` "fp")
+fn graphic_sexpr(graphic: &Graphic, prefix: &str) -> SExpr {
+    let stroke = |width: Length| {
+        SExpr::list("stroke", vec![length("width", width), SExpr::list("type", vec![SExpr::atom("solid")])])
+    };
+    let fill = |filled: bool| SExpr::list("fill", vec![SExpr::atom(if filled { "solid" } else { "none" })]);
+    let (kind, items) = match graphic {
+        Graphic::Line { start, end, layer, width, .. } => {
+            ("line", vec![xy("start", start), xy("end", end), stroke(*width), string("layer", layer)])
+        }
+        Graphic::Circle { center, radius, layer, width, filled, .. } => {
+            let end = Point { x: center.x + *radius, y: center.y };
+            (
+                "circle",
+                vec![xy("center", center), xy("end", &end), stroke(*width), fill(*filled), string("layer", layer)],
+            )
+        }
+        Graphic::Arc { arc, layer, width, .. } => {
+            let on_arc = |degrees: f64| Point {
+                x: arc.center.x + arc.radius * degrees.to_radians().cos(),
+                y: arc.center.y + arc.radius * degrees.to_radians().sin(),
+            };
+            (
+                "arc",
+                vec![
+                    xy("start", &on_arc(arc.start_angle)),
+                    xy("mid", &on_arc((arc.start_angle + arc.end_angle) / 2.0)),
+                    xy("end", &on_arc(arc.end_angle)),
+                    stroke(*width),
+                    string("layer", layer),
+                ],
+            )
+        }
+        Graphic::Rectangle { rect, layer, width, filled, .. } => (
+            "rect",
+            vec![xy("start", &rect.start), xy("end", &rect.end), stroke(*width), fill(*filled), string("layer", layer)],
+        ),
+        Graphic::Polygon { points, layer, width, filled, .. } => (
+            "poly",
+            vec![pts(points), stroke(*width), fill(*filled), string("layer", layer)],
+        ),
+        Graphic::Bezier { start, control1, control2, end, layer, width, .. } => (
+            "curve",
+            vec![
+                pts(&[start.clone(), control1.clone(), control2.clone(), end.clone()]),
+                stroke(*width),
+                string("layer", layer),
+            ],
+        ),
+    };
+    SExpr::list(&format!("{}_{}", prefix, kind), with_uuid(items, graphic.uuid()))
+}
+
+/// A text after its element head, e.g. `gr_text` or `fp_text user`
+fn text_sexpr(text: &Text, head: Vec<SExpr>) -> SExpr {
+    let mut items = head;
+    items.push(SExpr::string(&text.text));
+    items.push(at(&text.position, 0.0));
+    items.push(string("layer", &text.layer));
+    items.push(effects(&text.effects));
+    SExpr::List(items)
+}
+
+fn effects(effects: &TextEffects) -> SExpr {
+    let mut font = vec![
+        SExpr::list("size", vec![SExpr::number(effects.font_size.x.as_mm()), SExpr::number(effects.font_size.y.as_mm())]),
+        length("thickness", effects.thickness),
+    ];
+    if effects.bold {
+        font.push(SExpr::list("bold", vec![SExpr::yes_no(true)]));
+    }
+    if effects.italic {
+        font.push(SExpr::list("italic", vec![SExpr::yes_no(true)]));
+    }
+    let mut items = vec![SExpr::list("font", font)];
+    if let Some(justify) = &effects.justify {
+        items.push(SExpr::list("justify", justify.split_whitespace().map(SExpr::atom).collect()));
+    }
+    SExpr::list("effects", items)
+}
+
+fn track_arc_sexpr(arc: &TrackArc, nets: &NetTable) -> SExpr {
+    SExpr::list(
+        "arc",
+        with_uuid(
+            vec![
+                xy("start", &arc.start),
+                xy("mid", &arc.mid),
+                xy("end", &arc.end),
+                length("width", arc.width),
+                string("layer", &arc.layer),
+                nets.reference(arc.net.as_deref()),
+            ],
+            &arc.uuid,
+        ),
+    )
+}
+
+fn via_sexpr(via: &Via, nets: &NetTable) -> SExpr {
+    let mut items = match via.via_type {
+        ViaType::Through => Vec::new(),
+        ViaType::BlindBuried => vec![SExpr::atom("blind")],
+        ViaType::Micro => vec![SExpr::atom("micro")],
+    };
+    items.extend([
+        at(&via.position, 0.0),
+        length("size", via.size),
+        length("drill", via.drill),
+        SExpr::list("layers", via.layers.iter().map(SExpr::string).collect()),
+    ]);
+    let finish = &via.finish;
+    for (name, sides) in [("tenting", finish.tenting), ("covering", finish.covering), ("plugging", finish.plugging)] {
+        let sides: Vec<SExpr> = [("front", sides.front), ("back", sides.back)]
+            .into_iter()
+            .filter_map(|(side, set)| Some(SExpr::list(side, vec![SExpr::yes_no(set?)])))
+            .collect();
+        if !sides.is_empty() {
+            items.push(SExpr::list(name, sides));
+        }
+    }
+    for (name, set) in [("filling", finish.filling), ("capping", finish.capping)] {
+        if let Some(set) = set {
+            items.push(SExpr::list(name, vec![SExpr::yes_no(set)]));
+        }
+    }
+    items.push(nets.reference(via.net.as_deref()));
+    SExpr::list("via", with_uuid(items, &via.uuid))
+}
+
+fn zone_sexpr(zone: &Zone, nets: &NetTable) -> SExpr {
+    let mut items = vec![
+        nets.reference(zone.net.as_deref()),
+        string("net_name", zone.net.as_deref().unwrap_or_default()),
+    ];
+    if zone.layers.len() > 1 {
+        items.push(SExpr::list("layers", zone.layers.iter().map(SExpr::string).collect()));
+    } else {
+        items.push(string("layer", &zone.layer));
+    }
+    if !zone.uuid.is_empty() {
+        items.push(string("uuid", &zone.uuid));
+    }
+    items.push(SExpr::list("hatch", vec![SExpr::atom("edge"), SExpr::number(0.5)]));
+    if zone.priority != 0 {
+        items.push(SExpr::list("priority", vec![SExpr::atom(zone.priority.to_string())]));
+    }
+    let mut connect = Vec::new();
+    if !zone.connect_pads {
+        connect.push(SExpr::atom("no"));
+    }
+    connect.push(number("clearance", 0.5));
+    items.push(SExpr::list("connect_pads", connect));
+    items.push(number("min_thickness", 0.25));
+    let fill = &zone.fill;
+    let mut settings = Vec::new();
+    if let ZoneFillMode::Hatch { .. } = fill.mode {
+        settings.push(SExpr::list("mode", vec![SExpr::atom("hatch")]));
+    }
+    settings.push(length("thermal_gap", fill.thermal_gap));
+    settings.push(length("thermal_bridge_width", fill.thermal_bridge_width));
+    match fill.island_removal {
+        IslandRemoval::Always => {}
+        IslandRemoval::Never => settings.push(SExpr::list("island_removal_mode", vec![SExpr::atom("1")])),
+        IslandRemoval::BelowArea(area) => {
+            settings.push(SExpr::list("island_removal_mode", vec![SExpr::atom("2")]));
+            settings.push(number("island_area_min", area));
+        }
+    }
+    if let ZoneFillMode::Hatch { thickness, gap, orientation } = fill.mode {
+        settings.push(length("hatch_thickness", thickness));
+        settings.push(length("hatch_gap", gap));
+        settings.push(number("hatch_orientation", orientation));
+    }
+    items.push(SExpr::list("fill", settings));
+    items.push(SExpr::list("polygon", vec![pts(&zone.polygon)]));
+    for fill in &zone.filled_polygons {
+        items.push(SExpr::list("filled_polygon", vec![string("layer", &fill.layer), pts(&fill.points)]));
+    }
+    SExpr::list("zone", items)
+}
+
+/// Local mask and paste margins; pads and footprints name the paste ratio differently
+fn margins(margins: &MaskPasteMargins, ratio_name: &str) -> Vec<SExpr> {
+    [
+        ("solder_mask_margin", margins.solder_mask_margin.map(Length::as_mm)),
+        ("solder_paste_margin", margins.solder_paste_margin.map(Length::as_mm)),
+        (ratio_name, margins.solder_paste_ratio),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| Some(number(name, value?)))
+    .collect()
+}
+
+/// `(at x y [angle])`, leaving out a zero angle as KiCad does
+fn at(point: &Point, angle: f64) -> SExpr {
+    let mut args = vec![SExpr::number(point.x.as_mm()), SExpr::number(point.y.as_mm())];
+    if angle != 0.0 {
+        args.push(SExpr::number(angle));
+    }
+    SExpr::list("at", args)
+}
+
+fn xy(name: &str, point: &Point) -> SExpr {
+    SExpr::list(name, vec![SExpr::number(point.x.as_mm()), SExpr::number(point.y.as_mm())])
+}
+
+fn pts(points: &[Point]) -> SExpr {
+    SExpr::list("pts", points.iter().map(|p| xy("xy", p)).collect())
+}
+
+fn number(name: &str, value: f64) -> SExpr {
+    SExpr::list(name, vec![SExpr::number(value)])
+}
+
+fn length(name: &str, value: Length) -> SExpr {
+    number(name, value.as_mm())
+}
+
+fn string(name: &str, value: &str) -> SExpr {
+    SExpr::list(name, vec![SExpr::string(value)])
+}
+
+fn with_uuid(mut items: Vec<SExpr>, uuid: &str) -> Vec<SExpr> {
+    if !uuid.is_empty() {
+        items.push(string("uuid", uuid));
+    }
+    items
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const FPGA: &str = include_str!("../../assets/fpga.kicad_pcb");
+
+    #[test]
+    fn test_board_round_trip() {
+        let pcb = parse_pcb(FPGA).unwrap();
+        let text = write_pcb(&pcb);
+        assert!(text.starts_with("(kicad_pcb\n\t(version "));
+        let written = parse_pcb(&text).unwrap();
+
+        assert_eq!(written.layers, pcb.layers);
+        assert_eq!(written.nets, pcb.nets);
+        assert_eq!(written.tracks, pcb.tracks);
+        assert_eq!(written.arcs, pcb.arcs);
+        assert_eq!(written.vias, pcb.vias);
+        assert_eq!(written.zones, pcb.zones);
+        assert_eq!(written.footprints.len(), pcb.footprints.len());
+        for (a, b) in written.footprints.iter().zip(&pcb.footprints) {
+            assert_eq!(a.properties, b.properties);
+            assert_eq!(a.attributes, b.attributes);
+            assert_eq!(a.pads, b.pads);
+            assert_eq!(a.models, b.models);
+        }
+    }
+
+    #[test]
+    fn test_unlisted_nets_are_numbered() {
+        let mut pcb = parse_pcb(r#"(kicad_pcb (net 0 "") (net 1 "GND")
+  (segment (start 0 0) (end 1 0) (width 0.2) (layer "F.Cu") (net 1)))"#).unwrap();
+        pcb.tracks[0].net = Some("VCC".to_string());
+        let text = write_pcb(&pcb);
+        assert!(text.contains("(net 2 \"VCC\")"));
+        assert_eq!(parse_pcb(&text).unwrap().tracks[0].net.as_deref(), Some("VCC"));
+    }
+}
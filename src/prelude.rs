@@ -10,7 +10,7 @@
 //! ## What's included
 //! 
 //! - **Error types**: `Result`, `KicadError`
-//! - **Main parsing functions**: `parse_layers_only`, `parse_symbol_lib`
+//! - **Main parsing functions**: `parse_layers_only`, `parse_pcb`, `parse_symbol_lib`
 //! - **Core data types**: `PcbFile`, `Layer`, `Symbol`, `Point`, etc.
 
 // Re-export error types (most commonly used)
@@ -18,14 +18,23 @@ pub use crate::error::{KicadError, Result};
 
 // Re-export main parsing functions
 pub use crate::pcb::parse_layers_only;
+#[cfg(feature = "regex")]
 pub use crate::pcb::detail_parser::DetailParser;
+pub use crate::pcb::pcb_parser::{parse_pcb, PcbParser};
 pub use crate::symbol::symbol_parser::parse_symbol_lib;
+pub use crate::symbol::lib_parser::parse_symbol_lib_full;
 
 // Re-export core PCB types
 pub use crate::pcb::types::{
     PcbFile, Layer, Track, Footprint, Pad, Via, Zone, Text, Graphic,
-    Point, Rect, Arc
+    Point, Rect, Arc, NetClass, TextBox, Group, Image, Model3D, TitleBlock, RuleArea,
+    FootprintAttributes, BoardMetadata, BoardPolygon, StackupLayer, BoardStats, ViaType,
+    TuningPattern, TuningMode, TuningStatus, LengthTarget, MeanderParameters,
+    TrackArc, FilledPolygon
 };
 
 // Re-export Symbol types
-pub use crate::symbol::types::Symbol;
\ No newline at end of file
+pub use crate::symbol::types::Symbol;
+
+// Unit conversions
+pub use crate::units::Length;
\ No newline at end of file
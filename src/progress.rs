@@ -0,0 +1,193 @@
+//! Progress reporting and cancellation for long parses
+//!
+//! [`PcbParser`](crate::pcb::PcbParser) and [`DetailParser`](crate::pcb::DetailParser)
+//! accept a callback through `on_progress` that is called with a [`Progress`]
+//! as the input is worked through, so a GUI can drive a progress bar on boards of
+//! a hundred megabytes or more. The callback is called at most a few hundred
+//! times per pass and once more when the pass is done.
+//!
+//! Both also take an `AtomicBool` through `cancel_on`. Setting it from another
+//! thread stops the parse at the next element with
+//! [`KicadError::Cancelled`](crate::KicadError::Cancelled).
+//!
+//! ```rust
+//! use std::cell::Cell;
+//! use kiparse::pcb::PcbParser;
+//!
+//! let content = r#"(kicad_pcb (version 20240108)
+//!   (segment (start 0 0) (end 10 0) (width 0.25) (layer "F.Cu"))
+//!   (segment (start 10 0) (end 10 5) (width 0.25) (layer "F.Cu")))"#;
+//! let done = Cell::new(0.0);
+//! let pcb = PcbParser::new(content)
+//!     .on_progress(|p| done.set(p.fraction()))
+//!     .parse()?;
+//!
+//! assert_eq!(pcb.tracks.len(), 2);
+//! assert_eq!(done.get(), 1.0);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! ```rust
+//! use std::sync::atomic::{AtomicBool, Ordering};
+//! use kiparse::{pcb::PcbParser, KicadError};
+//!
+//! let cancel = AtomicBool::new(false);
+//! cancel.store(true, Ordering::Relaxed); // e.g. from the GUI thread
+//! let result = PcbParser::new("(kicad_pcb (version 20240108))").cancel_on(&cancel).parse();
+//! assert!(matches!(result, Err(KicadError::Cancelled)));
+//! ```
+
+use alloc::boxed::Box;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{KicadError, Result};
+
+/// Part of the parse a [`Progress`] report comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseStage {
+    /// Reading the text into S-expressions
+    Reading,
+    /// Building the typed board from the S-expressions
+    Converting,
+    /// One regex pass of a [`DetailParser`](crate::pcb::DetailParser) extraction
+    Scanning,
+}
+
+/// How far a parse has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    pub stage: ParseStage,
+    /// Input bytes the stage has worked through
+    pub bytes_processed: usize,
+    pub total_bytes: usize,
+    /// Top-level elements (footprints, tracks, ...) read, converted or found so far
+    pub elements_parsed: usize,
+}
+
+/// Share of a full board parse spent reading, for [`Progress::fraction`]
+const READING_SHARE: f64 = 0.6;
+
+impl Progress {
+    /// Overall completion between 0 and 1
+    ///
+    /// A [`PcbParser`](crate::pcb::PcbParser) parse counts reading as the first
+    /// 60% and converting as the rest; a scan covers the whole range by itself.
+    pub fn fraction(&self) -> f64 {
+        let done = if self.total_bytes == 0 {
+            1.0
+        } else {
+            (self.bytes_processed as f64 / self.total_bytes as f64).min(1.0)
+        };
+        match self.stage {
+            ParseStage::Reading => done * READING_SHARE,
+            ParseStage::Converting => READING_SHARE + done * (1.0 - READING_SHARE),
+            ParseStage::Scanning => done,
+        }
+    }
+}
+
+/// Callback registered with `on_progress`
+pub(crate) type ProgressCallback<'a> = Box<dyn Fn(Progress) + 'a>;
+
+/// Bytes between two reports: a two-hundredth of the input, at least 64 KiB
+fn report_step(total: usize) -> usize {
+    (total / 200).max(64 * 1024)
+}
+
+/// Rate-limited reporting of one stage to an optional callback, with an
+/// optional cancellation flag checked at every element
+pub(crate) struct Reporter<'c> {
+    callback: Option<&'c dyn Fn(Progress)>,
+    cancel: Option<&'c AtomicBool>,
+    progress: Progress,
+    next: usize,
+}
+
+impl<'c> Reporter<'c> {
+    pub(crate) fn new(
+        callback: Option<&'c dyn Fn(Progress)>,
+        cancel: Option<&'c AtomicBool>,
+        stage: ParseStage,
+        total_bytes: usize,
+    ) -> Self {
+        Reporter {
+            callback,
+            cancel,
+            progress: Progress { stage, bytes_processed: 0, total_bytes, elements_parsed: 0 },
+            next: 0,
+        }
+    }
+
+    /// Fail with [`KicadError::Cancelled`] once the cancellation flag is set
+    pub(crate) fn check(&self) -> Result<()> {
+        match self.cancel {
+            Some(cancel) if cancel.load(Ordering::Relaxed) => Err(KicadError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Count an element ending at byte `end`, reporting when enough input has passed
+    pub(crate) fn element(&mut self, end: usize) -> Result<()> {
+        self.check()?;
+        self.progress.elements_parsed += 1;
+        self.progress.bytes_processed = end;
+        if let Some(callback) = self.callback {
+            if end >= self.next {
+                self.next = end + report_step(self.progress.total_bytes);
+                callback(self.progress);
+            }
+        }
+        Ok(())
+    }
+
+    /// Report the stage as complete
+    pub(crate) fn finish(&mut self) {
+        self.progress.bytes_processed = self.progress.total_bytes;
+        if let Some(callback) = self.callback {
+            callback(self.progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_fraction() {
+        let at = |stage, bytes| Progress { stage, bytes_processed: bytes, total_bytes: 100, elements_parsed: 0 };
+        assert_eq!(at(ParseStage::Reading, 50).fraction(), 0.3);
+        assert_eq!(at(ParseStage::Converting, 0).fraction(), 0.6);
+        assert_eq!(at(ParseStage::Converting, 100).fraction(), 1.0);
+        assert_eq!(at(ParseStage::Scanning, 25).fraction(), 0.25);
+    }
+
+    #[test]
+    fn test_reports_are_rate_limited() {
+        let reports = RefCell::new(Vec::new());
+        let callback = |p: Progress| reports.borrow_mut().push(p);
+        let total = 10_000_000;
+        let mut reporter = Reporter::new(Some(&callback), None, ParseStage::Reading, total);
+        for end in (1..=total).step_by(100) {
+            reporter.element(end).unwrap();
+        }
+        reporter.finish();
+
+        let reports = reports.into_inner();
+        assert!(reports.len() > 100 && reports.len() <= 202);
+        assert!(reports.windows(2).all(|w| w[0].bytes_processed <= w[1].bytes_processed));
+        let last = reports.last().unwrap();
+        assert_eq!((last.bytes_processed, last.elements_parsed), (total, 100_000));
+    }
+
+    #[test]
+    fn test_cancel_stops_at_next_element() {
+        let cancel = AtomicBool::new(false);
+        let mut reporter = Reporter::new(None, Some(&cancel), ParseStage::Scanning, 100);
+        assert!(reporter.element(10).is_ok());
+        cancel.store(true, Ordering::Relaxed);
+        assert!(matches!(reporter.element(20), Err(KicadError::Cancelled)));
+        assert_eq!(reporter.progress.elements_parsed, 1);
+    }
+}
@@ -0,0 +1,432 @@
+//! Raster and vector rendering of board layers
+//!
+//! Draws copper, mask, paste, silkscreen and Edge.Cuts layers into an RGBA
+//! [`Raster`] at a chosen resolution, one layer at a time or as a composite top
+//! view, for thumbnails and previews. [`render_svg`] and [`render_dxf`] write
+//! the same layers as vector drawings. Each layer is converted to filled polygons
+//! in board millimetres:
+//!
+//! - tracks, track arcs and stroked graphics become capsules along their path
+//! - vias, pads and zone fills are drawn as their copper outline
+//! - mask and paste layers show the pad openings
+//! - drilled holes are punched out in the background colour
+//!
+//! Text is not rendered. The view covers the board outline (or the drawn
+//! geometry if the board has none) plus a margin. With the `png` feature,
+//! [`Raster::to_png`] encodes the result. [`render_symbol_svg`] draws library
+//! symbols for documentation.
+//!
+//! ```rust
+//! use kiparse::{parse_pcb, render::{render_layer, RenderOptions}};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (gr_rect (start 0 0) (end 10 5) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+//!   (segment (start 1 2.5) (end 9 2.5) (width 1) (layer "F.Cu") (net 0))
+//! )"#)?;
+//! let options = RenderOptions { dpi: 254.0, margin: 0.0, ..RenderOptions::default() };
+//! let raster = render_layer(&pcb, "F.Cu", &options)?;
+//!
+//! assert_eq!((raster.width, raster.height), (100, 50));
+//! assert_eq!(raster.pixel(50, 25), options.color("F.Cu"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod raster;
+mod symbol;
+mod vector;
+
+pub use raster::{Color, Raster};
+pub use symbol::{render_symbol_svg, SymbolSvgOptions};
+pub use vector::{render_dxf, render_svg};
+
+use crate::cam::drill::drill_files;
+use crate::cam::gerber::pad_on_layer;
+use crate::error::{KicadError, Result};
+use crate::pcb::copper::{copper_layers, expand_layers, pad_shape, Shape};
+use crate::pcb::geometry::{
+    arc_from_points, bounds, flatten_arc, outline_piece, to_board, OutlinePiece,
+};
+use crate::pcb::types::{Graphic, PcbFile, Point};
+use crate::units::{Length, MM_PER_INCH};
+use raster::Mask;
+use std::collections::HashMap;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+/// Segments used for a half circle when drawing round ends and vias
+const HALF_CIRCLE_STEPS: usize = 12;
+
+/// Largest image side accepted, in pixels
+const MAX_SIZE: f64 = 16384.0;
+
+/// Resolution, colours and framing of a rendering
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Pixels per inch
+    pub dpi: f64,
+    pub background: Color,
+    /// Samples per pixel along each axis, for anti-aliasing
+    pub supersample: u32,
+    /// Space around the board in mm
+    pub margin: f64,
+    /// Colours overriding [`layer_color`]
+    pub colors: HashMap<String, Color>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            dpi: 300.0,
+            background: Color::rgb(0, 16, 35),
+            supersample: 2,
+            margin: 1.0,
+            colors: HashMap::new(),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// Colour a layer is drawn in
+    pub fn color(&self, layer: &str) -> Color {
+        self.colors
+            .get(layer)
+            .copied()
+            .unwrap_or_else(|| layer_color(layer))
+    }
+}
+
+/// Default layer colour, following KiCad's classic theme
+pub fn layer_color(layer: &str) -> Color {
+    match layer {
+        "F.Cu" => Color::rgb(200, 52, 52),
+        "B.Cu" => Color::rgb(77, 127, 196),
+        "In1.Cu" => Color::rgb(127, 200, 127),
+        "In2.Cu" => Color::rgb(206, 125, 44),
+        l if l.ends_with(".Cu") => Color::rgb(194, 194, 0),
+        "F.SilkS" => Color::rgb(242, 237, 161),
+        "B.SilkS" => Color::rgb(232, 178, 167),
+        "F.Mask" => Color::rgba(216, 100, 255, 100),
+        "B.Mask" => Color::rgba(2, 255, 238, 100),
+        "F.Paste" => Color::rgba(180, 160, 154, 230),
+        "B.Paste" => Color::rgba(0, 194, 194, 230),
+        "Edge.Cuts" => Color::rgb(208, 210, 205),
+        _ => Color::rgb(132, 132, 132),
+    }
+}
+
+/// Render a single layer
+pub fn render_layer(pcb: &PcbFile, layer: &str, options: &RenderOptions) -> Result<Raster> {
+    render_layers(pcb, &[layer], options)
+}
+
+/// Render layers on top of each other, the first one at the bottom
+pub fn render_layers(pcb: &PcbFile, layers: &[&str], options: &RenderOptions) -> Result<Raster> {
+    let copper = copper_layers(pcb);
+    let drawn: Vec<(&str, Vec<Vec<Point>>)> = layers
+        .iter()
+        .map(|&layer| (layer, layer_polygons(pcb, layer, &copper)))
+        .collect();
+    let holes = if layers.iter().any(|l| l.ends_with(".Cu")) {
+        hole_polygons(pcb)
+    } else {
+        Vec::new()
+    };
+
+    let view = View::new(pcb, drawn.iter().flat_map(|(_, p)| p), options)?;
+    let mut raster = Raster::new(view.width, view.height, options.background);
+    for (layer, polygons) in &drawn {
+        raster.composite(&view.mask(polygons), options.color(layer));
+    }
+    if !holes.is_empty() {
+        raster.composite(&view.mask(&holes), options.background);
+    }
+    Ok(raster)
+}
+
+/// Render the board as seen from the top: back layers first, Edge.Cuts last
+pub fn render_composite(pcb: &PcbFile, options: &RenderOptions) -> Result<Raster> {
+    let copper = copper_layers(pcb);
+    let mut layers = vec!["B.SilkS"];
+    layers.extend(copper.iter().rev().map(String::as_str));
+    layers.extend(["F.SilkS", "Edge.Cuts"]);
+    render_layers(pcb, &layers, options)
+}
+
+/// Mapping from board millimetres to mask samples
+struct View {
+    origin: Point,
+    /// Samples per mm
+    scale: f64,
+    width: u32,
+    height: u32,
+    supersample: u32,
+}
+
+impl View {
+    fn new<'a>(
+        pcb: &PcbFile,
+        drawn: impl Iterator<Item = &'a Vec<Point>>,
+        options: &RenderOptions,
+    ) -> Result<View> {
+        if options.dpi.is_nan() || options.dpi <= 0.0 || options.supersample == 0 {
+            return Err(KicadError::InvalidFormat(
+                "Rendering needs a positive dpi and supersample".to_string(),
+            ));
+        }
+        let (min, max) = frame(pcb, drawn, options.margin)?;
+        let pixels_per_mm = options.dpi / MM_PER_INCH;
+        let size = |extent: f64| (extent * pixels_per_mm).ceil().max(1.0);
+        let (width, height) = (size((max.x - min.x).as_mm()), size((max.y - min.y).as_mm()));
+        if width > MAX_SIZE || height > MAX_SIZE {
+            return Err(KicadError::InvalidFormat(format!(
+                "Rendering of {}x{} pixels is too large",
+                width, height
+            )));
+        }
+        Ok(View {
+            origin: min,
+            scale: pixels_per_mm * options.supersample as f64,
+            width: width as u32,
+            height: height as u32,
+            supersample: options.supersample,
+        })
+    }
+
+    fn mask(&self, polygons: &[Vec<Point>]) -> Mask {
+        let mut mask = Mask::new(self.width, self.height, self.supersample);
+        let mut samples = Vec::new();
+        for polygon in polygons {
+            samples.clear();
+            samples.extend(polygon.iter().map(|p| {
+                (
+                    (p.x - self.origin.x).as_mm() * self.scale,
+                    (p.y - self.origin.y).as_mm() * self.scale,
+                )
+            }));
+            mask.fill_polygon(&samples);
+        }
+        mask
+    }
+}
+
+/// Area shown: the board outline, or the drawn geometry if there is none, plus `margin`
+fn frame<'a>(
+    pcb: &PcbFile,
+    drawn: impl Iterator<Item = &'a Vec<Point>>,
+    margin: f64,
+) -> Result<(Point, Point)> {
+    let outline: Vec<Point> = pcb
+        .board_outline()
+        .into_iter()
+        .flat_map(|p| p.outline)
+        .collect();
+    let points = if outline.is_empty() {
+        drawn.flatten().cloned().collect()
+    } else {
+        outline
+    };
+    if points.is_empty() {
+        return Err(KicadError::InvalidFormat("Nothing to render".to_string()));
+    }
+    let (min, max) = bounds(&points);
+    let margin = Length::mm(margin);
+    Ok((
+        Point {
+            x: min.x - margin,
+            y: min.y - margin,
+        },
+        Point {
+            x: max.x + margin,
+            y: max.y + margin,
+        },
+    ))
+}
+
+/// Filled outlines of everything drawn on a layer
+fn layer_polygons(pcb: &PcbFile, layer: &str, copper: &[String]) -> Vec<Vec<Point>> {
+    let mut polygons = item_polygons(pcb, layer, copper);
+    for graphic in pcb.graphics.iter().filter(|g| g.layer() == layer) {
+        graphic_polygons(&mut polygons, graphic, |p| p.clone());
+    }
+    for footprint in &pcb.footprints {
+        for graphic in footprint.graphics.iter().filter(|g| g.layer() == layer) {
+            graphic_polygons(&mut polygons, graphic, |p| {
+                to_board(p, &footprint.position, footprint.rotation)
+            });
+        }
+    }
+    polygons
+}
+
+/// Outlines of the zone fills, tracks, vias and pads on a layer
+fn item_polygons(pcb: &PcbFile, layer: &str, copper: &[String]) -> Vec<Vec<Point>> {
+    let mut polygons = Vec::new();
+
+    if layer.ends_with(".Cu") {
+        for zone in &pcb.zones {
+            for fill in zone.filled_polygons.iter().filter(|f| f.layer == layer) {
+                polygons.push(fill.points.clone());
+            }
+        }
+        for track in pcb.tracks.iter().filter(|t| t.layer == layer) {
+            polygons.push(capsule(&track.start, &track.end, track.width.as_mm() / 2.0));
+        }
+        for arc in pcb.arcs.iter().filter(|a| a.layer == layer) {
+            let path = match arc_from_points(&arc.start, &arc.mid, &arc.end) {
+                Some(a) => flatten_arc(&a),
+                None => vec![arc.start.clone(), arc.end.clone()],
+            };
+            stroke(&mut polygons, &path, arc.width.as_mm());
+        }
+        for via in &pcb.vias {
+            if expand_layers(&via.layers, copper, true)
+                .iter()
+                .any(|l| l == layer)
+            {
+                polygons.push(capsule(&via.position, &via.position, via.size.as_mm() / 2.0));
+            }
+        }
+    }
+
+    for footprint in &pcb.footprints {
+        for pad in footprint
+            .pads
+            .iter()
+            .filter(|p| pad_on_layer(p, layer, copper))
+        {
+            polygons.push(match pad_shape(footprint, pad) {
+                Shape::Capsule { a, b, radius } => capsule(&a, &b, radius),
+                Shape::Polygon(points) => points,
+            });
+        }
+    }
+    polygons
+}
+
+/// Outlines of all drilled holes and slots
+fn hole_polygons(pcb: &PcbFile) -> Vec<Vec<Point>> {
+    drill_files(pcb)
+        .iter()
+        .flat_map(|file| &file.holes)
+        .map(|hole| {
+            let end = hole.slot_end.as_ref().unwrap_or(&hole.position);
+            capsule(&hole.position, end, hole.diameter / 2.0)
+        })
+        .collect()
+}
+
+/// Fill and stroke of a drawing, with `transform` taking its points to the board
+fn graphic_polygons(
+    polygons: &mut Vec<Vec<Point>>,
+    graphic: &Graphic,
+    transform: impl Fn(&Point) -> Point,
+) {
+    let (width, filled) = match graphic {
+        Graphic::Line { width, .. } | Graphic::Arc { width, .. } | Graphic::Bezier { width, .. } => (width.as_mm(), false),
+        Graphic::Circle { width, filled, .. }
+        | Graphic::Rectangle { width, filled, .. }
+        | Graphic::Polygon { width, filled, .. } => (width.as_mm(), *filled),
+    };
+    let (points, closed) = match outline_piece(graphic) {
+        OutlinePiece::Open(points) => (points, false),
+        OutlinePiece::Closed(points) => (points, true),
+    };
+    let mut points: Vec<Point> = points.iter().map(&transform).collect();
+    if filled && closed {
+        polygons.push(points.clone());
+    }
+    if width > 0.0 && !points.is_empty() {
+        if closed {
+            points.push(points[0].clone());
+        }
+        stroke(polygons, &points, width);
+    }
+}
+
+/// Capsules along a polyline
+fn stroke(polygons: &mut Vec<Vec<Point>>, path: &[Point], width: f64) {
+    if path.len() == 1 {
+        polygons.push(capsule(&path[0], &path[0], width / 2.0));
+    }
+    for pair in path.windows(2) {
+        polygons.push(capsule(&pair[0], &pair[1], width / 2.0));
+    }
+}
+
+/// Outline of all points within `radius` of the segment `a`-`b`
+fn capsule(a: &Point, b: &Point, radius: f64) -> Vec<Point> {
+    let heading = (b.y - a.y).as_mm().atan2((b.x - a.x).as_mm());
+    let ends = [(b, heading - FRAC_PI_2), (a, heading + FRAC_PI_2)];
+    ends.iter()
+        .flat_map(|&(center, from)| {
+            (0..=HALF_CIRCLE_STEPS).map(move |i| {
+                let angle = from + PI * i as f64 / HALF_CIRCLE_STEPS as f64;
+                Point {
+                    x: center.x + Length::mm(radius * angle.cos()),
+                    y: center.y + Length::mm(radius * angle.sin()),
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "A")
+  (gr_rect (start 0 0) (end 20 10) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+  (footprint "J" (layer "F.Cu") (at 15 5) (property "Reference" "J1")
+    (pad "1" thru_hole circle (at 0 0) (size 4 4) (drill 2) (layers "*.Cu" "*.Mask") (net 1 "A")))
+  (segment (start 2 5) (end 15 5) (width 1) (layer "F.Cu") (net 1))
+  (gr_line (start 2 8) (end 10 8) (width 0.5) (layer "F.SilkS"))
+)"#;
+
+    fn options() -> RenderOptions {
+        RenderOptions {
+            dpi: 254.0,
+            margin: 0.0,
+            ..RenderOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_render_layer() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let options = options();
+        let raster = render_layer(&pcb, "F.Cu", &options).unwrap();
+        assert_eq!((raster.width, raster.height), (200, 100));
+        assert_eq!(raster.pixels.len(), 200 * 100 * 4);
+
+        let red = options.color("F.Cu");
+        assert_eq!(raster.pixel(50, 50), red);
+        assert_eq!(raster.pixel(50, 80), options.background);
+        // Pad ring around the drilled hole
+        assert_eq!(raster.pixel(150, 32), red);
+        assert_eq!(raster.pixel(150, 50), options.background);
+
+        let mask = render_layer(&pcb, "F.Mask", &options).unwrap();
+        assert_ne!(mask.pixel(150, 32), options.background);
+        assert_eq!(mask.pixel(50, 50), options.background);
+    }
+
+    #[test]
+    fn test_render_composite() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let mut options = options();
+        options
+            .colors
+            .insert("F.SilkS".to_string(), Color::rgb(255, 255, 255));
+        let raster = render_composite(&pcb, &options).unwrap();
+        assert_eq!(raster.pixel(60, 80), Color::rgb(255, 255, 255));
+        assert_eq!(raster.pixel(50, 50), options.color("F.Cu"));
+        // Through-hole pad shows front copper over back copper
+        assert_eq!(raster.pixel(150, 32), options.color("F.Cu"));
+        assert_ne!(raster.pixel(0, 50), options.background);
+
+        options.dpi = 0.0;
+        assert!(render_composite(&pcb, &options).is_err());
+    }
+}
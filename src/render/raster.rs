@@ -0,0 +1,205 @@
+//! Polygon rasterizer and RGBA pixel buffer
+
+/// Straight (non-premultiplied) RGBA colour
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Color { r, g, b, a }
+    }
+}
+
+/// RGBA image, rows top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct Raster {
+    pub width: u32,
+    pub height: u32,
+    /// Four bytes per pixel
+    pub pixels: Vec<u8>,
+}
+
+impl Raster {
+    /// Image filled with `background`
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        let pixel = [background.r, background.g, background.b, background.a];
+        Raster {
+            width,
+            height,
+            pixels: pixel.repeat(width as usize * height as usize),
+        }
+    }
+
+    /// Colour of one pixel
+    pub fn pixel(&self, x: u32, y: u32) -> Color {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        let p = &self.pixels[i..i + 4];
+        Color::rgba(p[0], p[1], p[2], p[3])
+    }
+
+    /// Blend `color` over the image where `mask` has coverage
+    pub(crate) fn composite(&mut self, mask: &Mask, color: Color) {
+        let s = mask.supersample as usize;
+        let samples = (s * s) as u32;
+        for y in 0..self.height as usize {
+            for x in 0..self.width as usize {
+                let mut covered = 0u32;
+                for sy in 0..s {
+                    let row = (y * s + sy) * mask.width;
+                    covered += mask.bits[row + x * s..row + x * s + s]
+                        .iter()
+                        .map(|&b| b as u32)
+                        .sum::<u32>();
+                }
+                if covered == 0 {
+                    continue;
+                }
+                let alpha = color.a as u32 * covered / samples;
+                let i = (y * self.width as usize + x) * 4;
+                let dst = &mut self.pixels[i..i + 4];
+                let blend = |src: u8, dst: u8| {
+                    ((src as u32 * alpha + dst as u32 * (255 - alpha)) / 255) as u8
+                };
+                dst[0] = blend(color.r, dst[0]);
+                dst[1] = blend(color.g, dst[1]);
+                dst[2] = blend(color.b, dst[2]);
+                dst[3] = (alpha + dst[3] as u32 * (255 - alpha) / 255) as u8;
+            }
+        }
+    }
+
+    /// Encode as a PNG file
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> crate::error::Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        let mut encoder = png::Encoder::new(&mut bytes, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(bytes)
+    }
+}
+
+/// Binary coverage of one layer at `supersample` times the image resolution
+pub(crate) struct Mask {
+    pub width: usize,
+    pub height: usize,
+    pub supersample: u32,
+    bits: Vec<u8>,
+}
+
+impl Mask {
+    pub fn new(image_width: u32, image_height: u32, supersample: u32) -> Self {
+        let width = (image_width * supersample) as usize;
+        let height = (image_height * supersample) as usize;
+        Mask {
+            width,
+            height,
+            supersample,
+            bits: vec![0; width * height],
+        }
+    }
+
+    /// Set every sample whose centre lies inside `polygon` (even-odd rule)
+    ///
+    /// Coordinates are in mask samples. Uses an active edge list, so large zone
+    /// fills cost one pass over their rows.
+    pub fn fill_polygon(&mut self, polygon: &[(f64, f64)]) {
+        struct Edge {
+            y_top: f64,
+            y_bottom: f64,
+            x_top: f64,
+            slope: f64,
+        }
+        let mut edges: Vec<Edge> = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .filter(|(a, b)| a.1 != b.1)
+            .map(|(&a, &b)| {
+                let (top, bottom) = if a.1 < b.1 { (a, b) } else { (b, a) };
+                Edge {
+                    y_top: top.1,
+                    y_bottom: bottom.1,
+                    x_top: top.0,
+                    slope: (bottom.0 - top.0) / (bottom.1 - top.1),
+                }
+            })
+            .collect();
+        if edges.is_empty() {
+            return;
+        }
+        edges.sort_by(|a, b| a.y_top.total_cmp(&b.y_top));
+
+        let y_min = edges[0].y_top;
+        let y_max = edges.iter().map(|e| e.y_bottom).fold(f64::MIN, f64::max);
+        let first_row = ((y_min - 0.5).ceil().max(0.0)) as usize;
+        let last_row = ((y_max - 0.5).floor().min(self.height as f64 - 1.0)) as i64;
+        if last_row < first_row as i64 {
+            return;
+        }
+
+        let mut next = 0;
+        let mut active: Vec<&Edge> = Vec::new();
+        let mut crossings: Vec<f64> = Vec::new();
+        for row in first_row..=last_row as usize {
+            let y = row as f64 + 0.5;
+            while next < edges.len() && edges[next].y_top <= y {
+                active.push(&edges[next]);
+                next += 1;
+            }
+            active.retain(|e| e.y_bottom > y);
+            crossings.clear();
+            crossings.extend(
+                active
+                    .iter()
+                    .filter(|e| e.y_top <= y)
+                    .map(|e| e.x_top + (y - e.y_top) * e.slope),
+            );
+            crossings.sort_by(f64::total_cmp);
+
+            let line = &mut self.bits[row * self.width..(row + 1) * self.width];
+            for pair in crossings.chunks_exact(2) {
+                let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((pair[1] - 0.5).ceil().max(0.0) as usize).min(self.width);
+                if start < end {
+                    line[start..end].fill(1);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_and_composite() {
+        let mut mask = Mask::new(4, 4, 2);
+        // Square over the left half of the image, and a triangle at the top right
+        mask.fill_polygon(&[(0.0, 0.0), (4.0, 0.0), (4.0, 8.0), (0.0, 8.0)]);
+        mask.fill_polygon(&[(4.0, 0.0), (8.0, 0.0), (4.0, 4.0)]);
+
+        let mut raster = Raster::new(4, 4, Color::rgb(0, 0, 0));
+        raster.composite(&mask, Color::rgb(200, 100, 0));
+        assert_eq!(raster.pixel(0, 0), Color::rgb(200, 100, 0));
+        assert_eq!(raster.pixel(1, 3), Color::rgb(200, 100, 0));
+        assert_eq!(raster.pixel(3, 3), Color::rgb(0, 0, 0));
+        assert_eq!(raster.pixel(2, 0), Color::rgb(200, 100, 0));
+        // Pixel (3, 0) has one of its four samples under the triangle
+        assert_eq!(raster.pixel(3, 0), Color::rgb(49, 24, 0));
+        assert_eq!(raster.pixel(3, 1), Color::rgb(0, 0, 0));
+    }
+}
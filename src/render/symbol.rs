@@ -0,0 +1,394 @@
+//! SVG drawings of library symbols
+
+use std::fmt::Write;
+
+use super::vector::{escape, num};
+use super::Color;
+use crate::error::{KicadError, Result};
+use crate::pcb::types::{Fill, Pin, PinOrientation, Point, Stroke, Symbol};
+use crate::units::Length;
+
+/// Stroke width KiCad uses for drawings with a width of 0, in mm
+const DEFAULT_LINE_WIDTH: f64 = 0.1524;
+
+/// Radius of the inversion bubble and size of the clock wedge on pins, in mm
+const PIN_DECORATION: f64 = 0.635;
+
+/// Gap between a pin and the name or number written along it, in mm
+const PIN_TEXT_GAP: f64 = 0.254;
+
+/// Unit, framing and colours of a symbol drawing
+#[derive(Debug, Clone)]
+pub struct SymbolSvgOptions {
+    /// Unit to draw; drawings common to all units are always included
+    pub unit: u32,
+    /// Draw the De Morgan alternate body style instead of the normal one
+    pub alternate: bool,
+    /// Space around the symbol in mm
+    pub margin: f64,
+    /// Draw visible fields such as Reference and Value
+    pub fields: bool,
+    pub body: Color,
+    /// Fill of drawings with `(fill (type background))`
+    pub body_fill: Color,
+    pub pin: Color,
+    pub pin_name: Color,
+    pub pin_number: Color,
+    pub field: Color,
+}
+
+impl Default for SymbolSvgOptions {
+    /// Unit 1 in the colours of KiCad's default schematic theme
+    fn default() -> Self {
+        SymbolSvgOptions {
+            unit: 1,
+            alternate: false,
+            margin: 1.27,
+            fields: true,
+            body: Color::rgb(132, 0, 0),
+            body_fill: Color::rgb(255, 255, 194),
+            pin: Color::rgb(132, 0, 0),
+            pin_name: Color::rgb(0, 100, 100),
+            pin_number: Color::rgb(169, 0, 0),
+            field: Color::rgb(0, 100, 100),
+        }
+    }
+}
+
+/// Draw one unit of a symbol with its pins, pin names and numbers and fields
+///
+/// The image is sized in mm at 1:1 scale and framed by [`Symbol::bounds`] plus
+/// the margin. Text uses a sans-serif font at the size given in the library, so
+/// its width differs somewhat from KiCad's stroke font. Pins are drawn as plain
+/// lines except for the `inverted`, `clock` and `inverted_clock` shapes. Derived
+/// symbols need [`resolve_extends`](crate::symbol::resolve_extends) first, as
+/// their graphics belong to the parent.
+pub fn render_symbol_svg(symbol: &Symbol, options: &SymbolSvgOptions) -> Result<String> {
+    let style = if options.alternate { 2 } else { 1 };
+    let shown = |unit: u32, body_style: u32| {
+        (unit == 0 || unit == options.unit) && (body_style == 0 || body_style == style)
+    };
+    let body = symbol.body_bounds(options.unit).ok_or_else(|| {
+        KicadError::InvalidFormat(format!("Symbol {} has nothing to draw in unit {}", symbol.name, options.unit))
+    })?;
+    let frame = if options.fields { symbol.bounds(options.unit).unwrap_or(body) } else { body };
+    // Symbol y points up, SVG y points down
+    let margin = Length::mm(options.margin);
+    let (x, y) = (frame.start.x - margin, -frame.end.y - margin);
+    let width = frame.end.x - frame.start.x + 2.0 * margin;
+    let height = frame.end.y - frame.start.y + 2.0 * margin;
+
+    let mut svg = Svg { out: String::new(), options };
+    svg.out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg.out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"{} {} {w} {h}\">",
+        num(x.as_mm()),
+        num(y.as_mm()),
+        w = num(width.as_mm()),
+        h = num(height.as_mm())
+    );
+    let _ = writeln!(svg.out, "<title>{}</title>", escape(&symbol.name));
+
+    svg.out.push_str("<g id=\"body\" stroke-linecap=\"round\" stroke-linejoin=\"round\">\n");
+    for rectangle in symbol.rectangles.iter().filter(|r| shown(r.unit, r.body_style)) {
+        let (a, b) = (&rectangle.start, &rectangle.end);
+        let attrs = svg.paint(&rectangle.stroke, &rectangle.fill);
+        let _ = writeln!(
+            svg.out,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            num(a.x.min(b.x).as_mm()),
+            num(-a.y.max(b.y).as_mm()),
+            num((a.x - b.x).abs().as_mm()),
+            num((a.y - b.y).abs().as_mm()),
+            attrs
+        );
+    }
+    for circle in symbol.circles.iter().filter(|c| shown(c.unit, c.body_style)) {
+        let attrs = svg.paint(&circle.stroke, &circle.fill);
+        let _ = writeln!(
+            svg.out,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>",
+            num(circle.center.x.as_mm()),
+            num(-circle.center.y.as_mm()),
+            num(circle.radius.as_mm()),
+            attrs
+        );
+    }
+    for arc in symbol.arcs.iter().filter(|a| shown(a.unit, a.body_style)) {
+        let attrs = svg.paint(&arc.stroke, &arc.fill);
+        let _ = writeln!(svg.out, "<path d=\"{}\" {}/>", arc_path(&arc.start, &arc.mid, &arc.end), attrs);
+    }
+    for polyline in symbol.polylines.iter().filter(|p| shown(p.unit, p.body_style) && !p.points.is_empty()) {
+        let attrs = svg.paint(&polyline.stroke, &polyline.fill);
+        let mut d = format!("M{} {}", num(polyline.points[0].x.as_mm()), num(-polyline.points[0].y.as_mm()));
+        for p in &polyline.points[1..] {
+            let _ = write!(d, "L{} {}", num(p.x.as_mm()), num(-p.y.as_mm()));
+        }
+        let _ = writeln!(svg.out, "<path d=\"{}\" {}/>", d, attrs);
+    }
+    svg.out.push_str("</g>\n");
+
+    let pins: Vec<&Pin> = symbol.pins.iter().filter(|p| !p.hidden && shown(p.unit, p.body_style)).collect();
+    let _ = writeln!(
+        svg.out,
+        "<g id=\"pins\" stroke=\"{}\" stroke-width=\"{}\" fill=\"none\" stroke-linecap=\"round\">",
+        hex(options.pin),
+        num(DEFAULT_LINE_WIDTH)
+    );
+    for pin in &pins {
+        svg.pin(pin);
+    }
+    svg.out.push_str("</g>\n");
+    svg.out.push_str("<g id=\"pin-text\" font-family=\"sans-serif\">\n");
+    for pin in &pins {
+        svg.pin_text(symbol, pin);
+    }
+    svg.out.push_str("</g>\n");
+
+    if options.fields {
+        svg.out.push_str("<g id=\"fields\" font-family=\"sans-serif\">\n");
+        for property in &symbol.properties {
+            let Some(effects) = property.effects.as_ref() else { continue };
+            if effects.hide || property.value.is_empty() {
+                continue;
+            }
+            let justify = effects.justify.as_deref().unwrap_or_default();
+            let has = |word: &str| justify.split_whitespace().any(|j| j == word);
+            let anchor = if has("left") {
+                "start"
+            } else if has("right") {
+                "end"
+            } else {
+                "middle"
+            };
+            let baseline = if has("top") {
+                "hanging"
+            } else if has("bottom") {
+                "auto"
+            } else {
+                "central"
+            };
+            let at = Point { x: property.at.x, y: -property.at.y };
+            svg.text(&at, &property.value, effects.font.size.y.as_mm(), anchor, baseline, false, options.field);
+        }
+        svg.out.push_str("</g>\n");
+    }
+    svg.out.push_str("</svg>\n");
+    Ok(svg.out)
+}
+
+struct Svg<'o> {
+    out: String,
+    options: &'o SymbolSvgOptions,
+}
+
+impl Svg<'_> {
+    /// Stroke and fill attributes of a body drawing
+    fn paint(&self, stroke: &Stroke, fill: &Fill) -> String {
+        let width = if stroke.width > Length::ZERO { stroke.width.as_mm() } else { DEFAULT_LINE_WIDTH };
+        let stroke_color = stroke.color.as_ref().map_or(self.options.body, to_color);
+        let fill = match fill.fill_type.as_str() {
+            "outline" => hex(stroke_color),
+            "background" => hex(self.options.body_fill),
+            "color" => fill.color.as_ref().map_or("none".to_string(), |c| hex(to_color(c))),
+            _ => "none".to_string(),
+        };
+        format!("stroke=\"{}\" stroke-width=\"{}\" fill=\"{}\"", hex(stroke_color), num(width), fill)
+    }
+
+    /// Pin line from the connection point to the body, with its decoration
+    fn pin(&mut self, pin: &Pin) {
+        let (sin, cos) = pin.rotation.to_radians().sin_cos();
+        // Direction towards the body and its normal, in SVG coordinates
+        let (dx, dy) = (cos, -sin);
+        let (x, y, length) = (pin.at.x.as_mm(), -pin.at.y.as_mm(), pin.length.as_mm());
+        let end = (x + dx * length, y + dy * length);
+        let inverted = pin.shape == "inverted" || pin.shape == "inverted_clock";
+        let line_end = if inverted && length > 2.0 * PIN_DECORATION {
+            (end.0 - dx * 2.0 * PIN_DECORATION, end.1 - dy * 2.0 * PIN_DECORATION)
+        } else {
+            end
+        };
+        let _ = writeln!(
+            self.out,
+            "<path d=\"M{} {}L{} {}\"/>",
+            num(x),
+            num(y),
+            num(line_end.0),
+            num(line_end.1)
+        );
+        if inverted {
+            let _ = writeln!(
+                self.out,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>",
+                num(end.0 - dx * PIN_DECORATION),
+                num(end.1 - dy * PIN_DECORATION),
+                num(PIN_DECORATION)
+            );
+        }
+        if pin.shape == "clock" || pin.shape == "inverted_clock" {
+            // Wedge inside the body at the pin's end
+            let (nx, ny) = (-dy * PIN_DECORATION, dx * PIN_DECORATION);
+            let _ = writeln!(
+                self.out,
+                "<path d=\"M{} {}L{} {}L{} {}\"/>",
+                num(end.0 + nx),
+                num(end.1 + ny),
+                num(end.0 + dx * PIN_DECORATION),
+                num(end.1 + dy * PIN_DECORATION),
+                num(end.0 - nx),
+                num(end.1 - ny)
+            );
+        }
+    }
+
+    /// Pin name and number placed the way KiCad does
+    ///
+    /// With a name offset the name sits inside the body past the pin's end and the
+    /// number above the pin; without one the name goes above and the number below.
+    fn pin_text(&mut self, symbol: &Symbol, pin: &Pin) {
+        let orientation = pin.orientation();
+        let vertical = matches!(orientation, PinOrientation::Up | PinOrientation::Down);
+        let end = pin.body_end();
+        let middle = Point { x: (pin.at.x + end.x) / 2.0, y: -(pin.at.y + end.y) / 2.0 };
+        // "Above" the pin in the text's frame; vertical text is turned to read upwards
+        let beside = |gap: f64| if vertical {
+            Point { x: middle.x - Length::mm(gap), y: middle.y }
+        } else {
+            Point { x: middle.x, y: middle.y - Length::mm(gap) }
+        };
+        let size = |effects: &Option<crate::pcb::types::Effects>| {
+            effects.as_ref().map_or(1.27, |e| e.font.size.y.as_mm())
+        };
+        let visible = |effects: &Option<crate::pcb::types::Effects>| effects.as_ref().map_or(true, |e| !e.hide);
+        let name_shown = !symbol.pin_names_hidden && visible(&pin.name_effects) && !pin.name.is_empty() && pin.name != "~";
+        let number_shown = !symbol.pin_numbers_hidden && visible(&pin.number_effects);
+        let (name_color, number_color) = (self.options.pin_name, self.options.pin_number);
+
+        if name_shown {
+            if symbol.pin_names_offset > Length::ZERO {
+                let (sin, cos) = pin.rotation.to_radians().sin_cos();
+                let at = Point {
+                    x: end.x + cos * symbol.pin_names_offset,
+                    y: -(end.y + sin * symbol.pin_names_offset),
+                };
+                let anchor = match orientation {
+                    PinOrientation::Right | PinOrientation::Up => "start",
+                    PinOrientation::Left | PinOrientation::Down => "end",
+                };
+                self.text(&at, &pin.name, size(&pin.name_effects), anchor, "central", vertical, name_color);
+            } else {
+                self.text(&beside(PIN_TEXT_GAP), &pin.name, size(&pin.name_effects), "middle", "auto", vertical, name_color);
+            }
+        }
+        if number_shown {
+            let number_size = size(&pin.number_effects);
+            if name_shown && symbol.pin_names_offset <= Length::ZERO {
+                self.text(&beside(-PIN_TEXT_GAP), &pin.number, number_size, "middle", "hanging", vertical, number_color);
+            } else {
+                self.text(&beside(PIN_TEXT_GAP), &pin.number, number_size, "middle", "auto", vertical, number_color);
+            }
+        }
+    }
+
+    /// Text at a point in SVG coordinates, turned to read upwards when `vertical`
+    #[allow(clippy::too_many_arguments)]
+    fn text(&mut self, at: &Point, text: &str, size: f64, anchor: &str, baseline: &str, vertical: bool, color: Color) {
+        let (x, y) = (num(at.x.as_mm()), num(at.y.as_mm()));
+        let _ = write!(
+            self.out,
+            "<text x=\"{x}\" y=\"{y}\" font-size=\"{}\" text-anchor=\"{}\" dominant-baseline=\"{}\" fill=\"{}\"",
+            num(size),
+            anchor,
+            baseline,
+            hex(color),
+            x = x,
+            y = y
+        );
+        if vertical {
+            let _ = write!(self.out, " transform=\"rotate(-90 {} {})\"", x, y);
+        }
+        let _ = writeln!(self.out, ">{}</text>", escape(text));
+    }
+}
+
+/// SVG path of the arc from `start` through `mid` to `end`, given in symbol coordinates
+fn arc_path(start: &Point, mid: &Point, end: &Point) -> String {
+    let [s, m, e] = [start, mid, end].map(|p| (p.x.as_mm(), -p.y.as_mm()));
+    let (ax, ay, bx, by) = (s.0 - m.0, s.1 - m.1, e.0 - m.0, e.1 - m.1);
+    let cross = ax * by - ay * bx;
+    let chord = ((s.0 - e.0).powi(2) + (s.1 - e.1).powi(2)).sqrt();
+    let moveto = format!("M{} {}", num(s.0), num(s.1));
+    if cross.abs() < 1e-12 {
+        return format!("{}L{} {}", moveto, num(e.0), num(e.1));
+    }
+    // The inscribed angle at `mid` is half the arc not containing it
+    let sin = cross.abs() / ((ax * ax + ay * ay).sqrt() * (bx * bx + by * by).sqrt());
+    let radius = chord / (2.0 * sin);
+    let large = ax * bx + ay * by > 0.0;
+    // Turning from start to end through mid; positive is the SVG sweep direction
+    let sweep = (m.0 - s.0) * (e.1 - s.1) - (m.1 - s.1) * (e.0 - s.0) > 0.0;
+    format!(
+        "{}A{r} {r} 0 {} {} {} {}",
+        moveto,
+        large as u8,
+        sweep as u8,
+        num(e.0),
+        num(e.1),
+        r = num(radius)
+    )
+}
+
+fn to_color(color: &crate::pcb::types::Color) -> Color {
+    Color::rgba(color.r, color.g, color.b, color.a)
+}
+
+fn hex(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::parse_symbol_library;
+
+    const LIB: &str = r#"(kicad_symbol_lib
+  (symbol "74LS74" (pin_names (offset 1.016))
+    (property "Reference" "U" (at -5.08 8.89 0) (effects (font (size 1.27 1.27)) (justify left)))
+    (property "Value" "74LS74" (at 0 -8.89 0) (effects (font (size 1.27 1.27))))
+    (property "Datasheet" "x" (at 0 0 0) (effects (font (size 1.27 1.27)) hide))
+    (symbol "74LS74_1_1"
+      (rectangle (start -5.08 7.62) (end 5.08 -7.62) (stroke (width 0.254) (type default)) (fill (type background)))
+      (arc (start 0 -7.62) (mid 1.27 -6.35) (end 0 -5.08) (stroke (width 0) (type default)) (fill (type none)))
+      (pin input line (at -7.62 2.54 0) (length 2.54) (name "D" (effects (font (size 1.27 1.27)))) (number "2"))
+      (pin input clock (at -7.62 0 0) (length 2.54) (name "C" (effects (font (size 1.27 1.27)))) (number "3"))
+      (pin output inverted (at 7.62 -2.54 180) (length 2.54) (name "~{Q}" (effects (font (size 1.27 1.27)))) (number "6")))
+    (symbol "74LS74_2_1"
+      (rectangle (start 20 7.62) (end 30 -7.62) (stroke (width 0.254) (type default)) (fill (type background))))))"#;
+
+    #[test]
+    fn test_symbol_svg() {
+        let lib = parse_symbol_library("demo", LIB).unwrap();
+        let options = SymbolSvgOptions { margin: 0.0, fields: false, ..SymbolSvgOptions::default() };
+        let svg = render_symbol_svg(&lib.symbols[0], &options).unwrap();
+        assert!(svg.contains("width=\"15.24mm\" height=\"15.24mm\" viewBox=\"-7.62 -7.62 15.24 15.24\""));
+        assert!(svg.contains("<rect x=\"-5.08\" y=\"-7.62\" width=\"10.16\" height=\"15.24\" stroke=\"#840000\" stroke-width=\"0.254\" fill=\"#ffffc2\"/>"));
+        // Half circle bulging right, drawn counter-clockwise on screen from the bottom
+        assert!(svg.contains("<path d=\"M0 7.62A1.27 1.27 0 0 0 0 5.08\" stroke=\"#840000\" stroke-width=\"0.1524\" fill=\"none\"/>"));
+        // Three pin lines, the inversion bubble and the clock wedge
+        assert_eq!(svg.matches("<circle").count(), 1);
+        assert!(svg.contains("<path d=\"M-5.08 0.635L-4.445 0L-5.08 -0.635\"/>"));
+        // Names inside the body past the offset, numbers above the pins
+        assert!(svg.contains("<text x=\"-4.064\" y=\"-2.54\" font-size=\"1.27\" text-anchor=\"start\" dominant-baseline=\"central\" fill=\"#006464\">D</text>"));
+        assert!(svg.contains(">~{Q}</text>"));
+        assert!(svg.contains("<text x=\"-6.35\" y=\"-2.794\" font-size=\"1.27\" text-anchor=\"middle\" dominant-baseline=\"auto\" fill=\"#a90000\">2</text>"));
+        assert_eq!(svg.matches("<rect").count(), 1);
+
+        let with_fields = render_symbol_svg(&lib.symbols[0], &SymbolSvgOptions::default()).unwrap();
+        assert!(with_fields.contains(">U</text>") && with_fields.contains(">74LS74</text>"));
+        assert!(!with_fields.contains(">x</text>"));
+        let unit3 = SymbolSvgOptions { unit: 3, ..SymbolSvgOptions::default() };
+        assert!(render_symbol_svg(&lib.symbols[0], &unit3).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! SVG and DXF output of board layers
+
+use std::fmt::Write;
+
+use super::{frame, hole_polygons, item_polygons, layer_polygons, Color, RenderOptions};
+use crate::error::{KicadError, Result};
+use crate::pcb::copper::copper_layers;
+use crate::pcb::geometry::{outline_piece, to_board, OutlinePiece};
+use crate::pcb::types::{Arc, Graphic, PcbFile, Point};
+
+/// Draw layers as filled SVG paths, the first one at the bottom
+///
+/// Uses the colours, background and margin of `options`; the image is sized in
+/// mm at 1:1 scale. Holes are drawn in the background colour over copper layers.
+pub fn render_svg(pcb: &PcbFile, layers: &[&str], options: &RenderOptions) -> Result<String> {
+    let copper = copper_layers(pcb);
+    let drawn: Vec<(&str, Vec<Vec<Point>>)> = layers
+        .iter()
+        .map(|&layer| (layer, layer_polygons(pcb, layer, &copper)))
+        .collect();
+    let (min, max) = frame(pcb, drawn.iter().flat_map(|(_, p)| p), options.margin)?;
+    let (width, height) = ((max.x - min.x).as_mm(), (max.y - min.y).as_mm());
+
+    let mut svg = String::new();
+    svg.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}mm\" height=\"{h}mm\" viewBox=\"{} {} {w} {h}\">",
+        num(min.x.as_mm()),
+        num(min.y.as_mm()),
+        w = num(width),
+        h = num(height)
+    );
+    if options.background.a > 0 {
+        let _ = writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" {}/>",
+            num(min.x.as_mm()),
+            num(min.y.as_mm()),
+            num(width),
+            num(height),
+            fill(options.background)
+        );
+    }
+    let mut group = |id: &str, color: Color, polygons: &[Vec<Point>]| {
+        let _ = writeln!(svg, "<g id=\"{}\" {}>", escape(id), fill(color));
+        for polygon in polygons.iter().filter(|p| p.len() > 2) {
+            let mut d = format!("M{} {}", num(polygon[0].x.as_mm()), num(polygon[0].y.as_mm()));
+            for p in &polygon[1..] {
+                let _ = write!(d, "L{} {}", num(p.x.as_mm()), num(p.y.as_mm()));
+            }
+            let _ = writeln!(svg, "<path d=\"{}Z\"/>", d);
+        }
+        svg.push_str("</g>\n");
+    };
+    for (layer, polygons) in &drawn {
+        group(layer, options.color(layer), polygons);
+    }
+    if layers.iter().any(|l| l.ends_with(".Cu")) {
+        let background = Color {
+            a: 255,
+            ..options.background
+        };
+        group("Holes", background, &hole_polygons(pcb));
+    }
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Write layers to an AutoCAD R12 DXF file, one DXF layer per board layer
+///
+/// Drawings keep their geometry (lines, circles, arcs and closed polylines along
+/// their centreline), so an Edge.Cuts export can be used as a mechanical outline.
+/// Tracks, vias, pads and zone fills become closed polylines around their copper.
+/// The Y axis points up, as CAD tools expect.
+pub fn render_dxf(pcb: &PcbFile, layers: &[&str]) -> Result<String> {
+    if layers.is_empty() {
+        return Err(KicadError::InvalidFormat("No layers to export".to_string()));
+    }
+    let copper = copper_layers(pcb);
+    let mut dxf = Dxf::default();
+    dxf.pair(0, "SECTION");
+    dxf.pair(2, "TABLES");
+    dxf.pair(0, "TABLE");
+    dxf.pair(2, "LAYER");
+    dxf.pair(70, &layers.len().to_string());
+    for layer in layers {
+        dxf.pair(0, "LAYER");
+        dxf.pair(2, &dxf_name(layer));
+        dxf.pair(70, "0");
+        dxf.pair(62, "7");
+        dxf.pair(6, "CONTINUOUS");
+    }
+    dxf.pair(0, "ENDTAB");
+    dxf.pair(0, "ENDSEC");
+    dxf.pair(0, "SECTION");
+    dxf.pair(2, "ENTITIES");
+
+    for &layer in layers {
+        let name = dxf_name(layer);
+        for polygon in item_polygons(pcb, layer, &copper) {
+            dxf.polyline(&name, &polygon, true);
+        }
+        for graphic in pcb.graphics.iter().filter(|g| g.layer() == layer) {
+            dxf.graphic(&name, graphic, &Point::default(), 0.0);
+        }
+        for footprint in &pcb.footprints {
+            for graphic in footprint.graphics.iter().filter(|g| g.layer() == layer) {
+                dxf.graphic(&name, graphic, &footprint.position, footprint.rotation);
+            }
+        }
+    }
+
+    dxf.pair(0, "ENDSEC");
+    dxf.pair(0, "EOF");
+    Ok(dxf.out)
+}
+
+#[derive(Default)]
+struct Dxf {
+    out: String,
+}
+
+impl Dxf {
+    fn pair(&mut self, code: u32, value: &str) {
+        let _ = write!(self.out, "{:>3}\n{}\n", code, value);
+    }
+
+    /// Point as X and Y group codes, flipped to Y up
+    fn point(&mut self, code: u32, p: &Point) {
+        self.pair(code, &num(p.x.as_mm()));
+        self.pair(code + 10, &num(-p.y.as_mm()));
+    }
+
+    fn polyline(&mut self, layer: &str, points: &[Point], closed: bool) {
+        self.pair(0, "POLYLINE");
+        self.pair(8, layer);
+        self.pair(66, "1");
+        self.pair(70, if closed { "1" } else { "0" });
+        for p in points {
+            self.pair(0, "VERTEX");
+            self.pair(8, layer);
+            self.point(10, p);
+        }
+        self.pair(0, "SEQEND");
+        self.pair(8, layer);
+    }
+
+    /// Drawing of a footprint at `origin` and `rotation` (zero for board drawings)
+    fn graphic(&mut self, layer: &str, graphic: &Graphic, origin: &Point, rotation: f64) {
+        let board = |p: &Point| to_board(p, origin, rotation);
+        match graphic {
+            Graphic::Line { start, end, .. } => {
+                self.pair(0, "LINE");
+                self.pair(8, layer);
+                self.point(10, &board(start));
+                self.point(11, &board(end));
+            }
+            Graphic::Circle { center, radius, .. } => {
+                self.pair(0, "CIRCLE");
+                self.pair(8, layer);
+                self.point(10, &board(center));
+                self.pair(40, &num(radius.as_mm()));
+            }
+            Graphic::Arc { arc, .. } => self.arc(layer, arc, &board(&arc.center), rotation),
+            Graphic::Rectangle { .. } | Graphic::Polygon { .. } | Graphic::Bezier { .. } => {
+                let (points, closed) = match outline_piece(graphic) {
+                    OutlinePiece::Open(points) => (points, false),
+                    OutlinePiece::Closed(points) => (points, true),
+                };
+                let points: Vec<Point> = points.iter().map(board).collect();
+                self.polyline(layer, &points, closed);
+            }
+        }
+    }
+
+    /// Arc around `center` (on the board); footprint rotation turns its angles clockwise
+    fn arc(&mut self, layer: &str, arc: &Arc, center: &Point, rotation: f64) {
+        // Flipping Y mirrors the angles and reverses the sweep, which DXF draws counter-clockwise
+        let start = (rotation - arc.end_angle).rem_euclid(360.0);
+        let end = (rotation - arc.start_angle).rem_euclid(360.0);
+        self.pair(0, "ARC");
+        self.pair(8, layer);
+        self.point(10, center);
+        self.pair(40, &num(arc.radius.as_mm()));
+        self.pair(50, &num(start));
+        self.pair(51, &num(end));
+    }
+}
+
+/// DXF layer names cannot contain dots in some readers; `F.Cu` becomes `F_Cu`
+fn dxf_name(layer: &str) -> String {
+    layer.replace(['.', ' '], "_")
+}
+
+/// Coordinate with at most four decimals and no trailing zeros
+pub(super) fn num(value: f64) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn fill(color: Color) -> String {
+    let mut attrs = format!("fill=\"#{:02x}{:02x}{:02x}\"", color.r, color.g, color.b);
+    if color.a < 255 {
+        let _ = write!(attrs, " fill-opacity=\"{}\"", num(color.a as f64 / 255.0));
+    }
+    attrs
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+  (net 0 "") (net 1 "A")
+  (gr_rect (start 0 0) (end 20 10) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+  (gr_arc (start 20 5) (mid 17 8) (end 14 5) (stroke (width 0.1) (type default)) (layer "Edge.Cuts"))
+  (footprint "J" (layer "F.Cu") (at 15 5) (property "Reference" "J1")
+    (pad "1" thru_hole circle (at 0 0) (size 4 4) (drill 2) (layers "*.Cu" "*.Mask") (net 1 "A")))
+  (segment (start 2 5) (end 15 5) (width 1) (layer "F.Cu") (net 1))
+)"#;
+
+    #[test]
+    fn test_svg() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let options = RenderOptions {
+            margin: 0.0,
+            ..RenderOptions::default()
+        };
+        let svg = render_svg(&pcb, &["F.Cu", "Edge.Cuts"], &options).unwrap();
+        assert!(svg.contains("width=\"20mm\" height=\"10mm\" viewBox=\"0 0 20 10\""));
+        assert!(svg.contains("<g id=\"F.Cu\" fill=\"#c83434\">"));
+        assert!(svg.contains("<g id=\"Holes\" fill=\"#001023\">"));
+        // Track and pad, four stroked rectangle sides, 36 arc segments and the hole
+        assert_eq!(svg.matches("<path").count(), 2 + 4 + 36 + 1);
+    }
+
+    #[test]
+    fn test_dxf() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let dxf = render_dxf(&pcb, &["Edge.Cuts"]).unwrap();
+        assert!(dxf.contains("  2\nEdge_Cuts\n"));
+        assert_eq!(dxf.matches("POLYLINE").count(), 1);
+        // Arc bulging downwards on the board, i.e. from 180 to 360 degrees with Y up
+        let arc = &dxf[dxf.find("ARC").unwrap()..];
+        assert!(
+            arc.starts_with("ARC\n  8\nEdge_Cuts\n 10\n17\n 20\n-5\n 40\n3\n 50\n180\n 51\n0\n")
+        );
+        assert!(dxf.ends_with("  0\nEOF\n"));
+        assert_eq!(num(-0.00001), "0");
+    }
+}
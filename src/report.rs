@@ -0,0 +1,551 @@
+//! Design review reports
+//!
+//! Collects a board summary, the stackup and layer table, a BOM summary,
+//! clearance check findings and 3D model coverage into a [`DesignReport`] that
+//! renders as Markdown or as a standalone HTML page.
+//!
+//! ```rust
+//! use kiparse::{parse_pcb, report::{DesignReport, ReportOptions}};
+//!
+//! let pcb = parse_pcb(r#"(kicad_pcb
+//!   (layers (0 "F.Cu" signal) (31 "B.Cu" signal))
+//!   (title_block (title "Blinky") (rev "B"))
+//! )"#)?;
+//! let report = DesignReport::from_pcb(&pcb, &ReportOptions::default());
+//! let markdown = report.to_markdown();
+//!
+//! assert!(markdown.starts_with("# Blinky rev. B\n"));
+//! assert!(markdown.contains("| Copper layers | 2 |"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::bom::{natural_order, Bom};
+use crate::pcb::copper::CopperItem;
+use crate::pcb::types::ModelType;
+use crate::pcb::drc::{check_clearance, DrcConfig};
+use crate::pcb::types::{BoardStats, Layer, PcbFile, Point, StackupLayer};
+use crate::units::Length;
+
+/// What goes into the report
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReportOptions {
+    /// Clearance check settings; `None` leaves the check out
+    pub drc: Option<DrcConfig>,
+    /// Most clearance violations listed individually
+    pub max_drc_rows: usize,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            drc: Some(DrcConfig::default()),
+            max_drc_rows: 50,
+        }
+    }
+}
+
+/// 3D model assignment of the footprints that carry a part
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModelCoverage {
+    /// Footprints that are not board-only
+    pub components: usize,
+    pub with_models: usize,
+    /// References of components without any model, in natural order
+    pub missing: Vec<String>,
+    /// Model count per format (`STEP`, `WRL`, ...)
+    pub by_type: BTreeMap<String, usize>,
+}
+
+impl ModelCoverage {
+    pub fn from_pcb(pcb: &PcbFile) -> Self {
+        let mut coverage = ModelCoverage {
+            components: 0,
+            with_models: 0,
+            missing: Vec::new(),
+            by_type: BTreeMap::new(),
+        };
+        for footprint in pcb.footprints.iter().filter(|f| !f.attributes.board_only) {
+            coverage.components += 1;
+            if footprint.models.is_empty() {
+                coverage.missing.push(
+                    footprint
+                        .property("Reference")
+                        .unwrap_or_default()
+                        .to_string(),
+                );
+                continue;
+            }
+            coverage.with_models += 1;
+            for model in &footprint.models {
+                let name = match model.model_type() {
+                    ModelType::Step => "STEP",
+                    ModelType::Wrl => "WRL",
+                    ModelType::Iges => "IGES",
+                    ModelType::Other => "Other",
+                };
+                *coverage.by_type.entry(name.to_string()).or_insert(0) += 1;
+            }
+        }
+        coverage.missing.sort_by(|a, b| natural_order(a, b));
+        coverage
+    }
+
+    /// Share of components with a model in percent; 100 for an empty board
+    pub fn percent(&self) -> f64 {
+        if self.components == 0 {
+            100.0
+        } else {
+            self.with_models as f64 * 100.0 / self.components as f64
+        }
+    }
+}
+
+/// Clearance violation with its items described for people
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrcFinding {
+    /// Items such as `R1 pad 2` or `Via`
+    pub a: String,
+    pub b: String,
+    pub net_a: Option<String>,
+    pub net_b: Option<String>,
+    pub layer: String,
+    pub location: Point,
+    pub actual: f64,
+    pub required: f64,
+}
+
+/// Everything a design review report shows
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DesignReport {
+    pub title: String,
+    pub revision: String,
+    pub date: String,
+    pub company: String,
+    pub stats: BoardStats,
+    /// Outline width and height in mm
+    pub size: Option<(f64, f64)>,
+    pub thickness: Option<f64>,
+    pub net_count: usize,
+    /// Board layers by id
+    pub layers: Vec<Layer>,
+    pub stackup: Vec<StackupLayer>,
+    pub bom: Bom,
+    /// Clearance violations, or `None` when the check was not run
+    pub drc: Option<Vec<DrcFinding>>,
+    pub max_drc_rows: usize,
+    pub models: ModelCoverage,
+}
+
+/// Rendering-neutral piece of the report
+enum Block {
+    Heading(usize, String),
+    Paragraph(String),
+    Table(Vec<&'static str>, Vec<Vec<String>>),
+}
+
+impl DesignReport {
+    pub fn from_pcb(pcb: &PcbFile, options: &ReportOptions) -> Self {
+        let title_block = pcb.title_block.clone().unwrap_or_default();
+        let size = pcb.board_outline().first().map(|outline| {
+            let bounds = outline.bounds();
+            ((bounds.end.x - bounds.start.x).as_mm(), (bounds.end.y - bounds.start.y).as_mm())
+        });
+        let mut layers: Vec<Layer> = pcb.layers.values().cloned().collect();
+        layers.sort_by_key(|l| l.id);
+        let drc = options.drc.as_ref().map(|config| {
+            check_clearance(pcb, config)
+                .into_iter()
+                .map(|v| DrcFinding {
+                    a: item_name(pcb, v.a),
+                    b: item_name(pcb, v.b),
+                    net_a: v.net_a,
+                    net_b: v.net_b,
+                    layer: v.layer,
+                    location: v.location,
+                    actual: v.actual.as_mm(),
+                    required: v.required.as_mm(),
+                })
+                .collect()
+        });
+
+        DesignReport {
+            title: title_block.title,
+            revision: title_block.revision,
+            date: title_block.date,
+            company: title_block.company,
+            stats: pcb.stats(),
+            size,
+            thickness: pcb.board_thickness.map(Length::as_mm),
+            net_count: pcb.nets.values().filter(|n| !n.is_empty()).count(),
+            layers,
+            stackup: pcb.stackup.clone(),
+            bom: Bom::from_pcb(pcb),
+            drc,
+            max_drc_rows: options.max_drc_rows,
+            models: ModelCoverage::from_pcb(pcb),
+        }
+    }
+
+    /// Report as GitHub-flavoured Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => {
+                    out.push_str(&format!("{} {}\n\n", "#".repeat(level), text))
+                }
+                Block::Paragraph(text) => out.push_str(&format!("{}\n\n", text)),
+                Block::Table(headers, rows) => {
+                    let row = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+                    out.push_str(&row(headers.iter().map(|h| h.to_string()).collect()));
+                    out.push_str(&row(headers.iter().map(|_| "---".to_string()).collect()));
+                    for cells in rows {
+                        out.push_str(&row(cells.iter().map(|c| c.replace('|', "\\|")).collect()));
+                    }
+                    out.push('\n');
+                }
+            }
+        }
+        out.truncate(out.trim_end().len());
+        out.push('\n');
+        out
+    }
+
+    /// Report as a standalone HTML page
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        for block in self.blocks() {
+            match block {
+                Block::Heading(level, text) => {
+                    body.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(&text)))
+                }
+                Block::Paragraph(text) => body.push_str(&format!("<p>{}</p>\n", escape(&text))),
+                Block::Table(headers, rows) => {
+                    body.push_str("<table>\n<tr>");
+                    for header in headers {
+                        body.push_str(&format!("<th>{}</th>", escape(header)));
+                    }
+                    body.push_str("</tr>\n");
+                    for cells in rows {
+                        body.push_str("<tr>");
+                        for cell in cells {
+                            body.push_str(&format!("<td>{}</td>", escape(&cell)));
+                        }
+                        body.push_str("</tr>\n");
+                    }
+                    body.push_str("</table>\n");
+                }
+            }
+        }
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+             <style>table {{ border-collapse: collapse; }} th, td {{ border: 1px solid #ccc; padding: 2px 8px; }}</style>\n\
+             </head>\n<body>\n{}</body>\n</html>\n",
+            escape(&self.heading()),
+            body
+        )
+    }
+
+    fn heading(&self) -> String {
+        let title = if self.title.is_empty() {
+            "Design report"
+        } else {
+            &self.title
+        };
+        if self.revision.is_empty() {
+            title.to_string()
+        } else {
+            format!("{} rev. {}", title, self.revision)
+        }
+    }
+
+    fn blocks(&self) -> Vec<Block> {
+        let mm = |v: f64| format!("{:.3} mm", v);
+        let optional_mm = |v: Option<f64>| v.map(mm).unwrap_or_else(|| "-".to_string());
+        let stats = &self.stats;
+        let mut blocks = vec![Block::Heading(1, self.heading())];
+        let byline: Vec<&str> = [self.company.as_str(), self.date.as_str()]
+            .into_iter()
+            .filter(|s| !s.is_empty())
+            .collect();
+        if !byline.is_empty() {
+            blocks.push(Block::Paragraph(byline.join(", ")));
+        }
+
+        blocks.push(Block::Heading(2, "Board summary".to_string()));
+        let vias = if stats.vias_by_type.is_empty() {
+            "0".to_string()
+        } else {
+            let types: Vec<String> = stats
+                .vias_by_type
+                .iter()
+                .map(|(via_type, count)| format!("{} {:?}", count, via_type))
+                .collect();
+            format!("{} ({})", stats.via_count, types.join(", "))
+        };
+        let summary = vec![
+            (
+                "Size",
+                self.size
+                    .map_or("-".to_string(), |(w, h)| format!("{:.2} x {:.2} mm", w, h)),
+            ),
+            (
+                "Area",
+                stats
+                    .board_area
+                    .map_or("-".to_string(), |a| format!("{:.1} mm²", a)),
+            ),
+            ("Thickness", optional_mm(self.thickness)),
+            ("Copper layers", stats.copper_layer_count.to_string()),
+            ("Components", stats.component_count.to_string()),
+            ("Pads", stats.pad_count.to_string()),
+            ("Nets", self.net_count.to_string()),
+            ("Track segments", stats.track_count.to_string()),
+            ("Track length", format!("{:.1} mm", stats.track_length)),
+            ("Vias", vias),
+            ("Narrowest track", optional_mm(stats.min_track_width)),
+            ("Smallest drill", optional_mm(stats.min_drill)),
+        ];
+        blocks.push(Block::Table(
+            vec!["Property", "Value"],
+            summary
+                .into_iter()
+                .map(|(k, v)| vec![k.to_string(), v])
+                .collect(),
+        ));
+
+        blocks.push(Block::Heading(2, "Stackup".to_string()));
+        if self.stackup.is_empty() {
+            blocks.push(Block::Paragraph("No stackup defined.".to_string()));
+        } else {
+            let number = |v: Option<f64>| v.map_or(String::new(), |v| format!("{}", v));
+            let rows = self
+                .stackup
+                .iter()
+                .map(|l| {
+                    vec![
+                        l.name.clone(),
+                        l.layer_type.clone(),
+                        l.thickness.map_or(String::new(), |t| mm(t.as_mm())),
+                        l.material.clone().unwrap_or_default(),
+                        number(l.epsilon_r),
+                        number(l.loss_tangent),
+                    ]
+                })
+                .collect();
+            blocks.push(Block::Table(
+                vec![
+                    "Layer",
+                    "Type",
+                    "Thickness",
+                    "Material",
+                    "εr",
+                    "Loss tangent",
+                ],
+                rows,
+            ));
+        }
+
+        blocks.push(Block::Heading(2, "Layers".to_string()));
+        let rows = self
+            .layers
+            .iter()
+            .map(|l| {
+                vec![
+                    l.id.to_string(),
+                    l.name.clone(),
+                    l.layer_type.clone(),
+                    l.user_name.clone().unwrap_or_default(),
+                ]
+            })
+            .collect();
+        blocks.push(Block::Table(vec!["ID", "Name", "Type", "User name"], rows));
+
+        blocks.push(Block::Heading(2, "Bill of materials".to_string()));
+        blocks.push(Block::Paragraph(format!(
+            "{} line items, {} parts to place.",
+            self.bom.lines.len(),
+            self.bom.total_quantity()
+        )));
+        if !stats.components_by_prefix.is_empty() {
+            let rows = stats
+                .components_by_prefix
+                .iter()
+                .map(|(prefix, count)| vec![prefix.clone(), count.to_string()])
+                .collect();
+            blocks.push(Block::Table(vec!["Prefix", "Footprints"], rows));
+        }
+
+        blocks.push(Block::Heading(2, "Design rule check".to_string()));
+        match &self.drc {
+            None => blocks.push(Block::Paragraph("Clearance check not run.".to_string())),
+            Some(findings) if findings.is_empty() => {
+                blocks.push(Block::Paragraph("No clearance violations.".to_string()))
+            }
+            Some(findings) => {
+                let mut text = format!("{} clearance violations", findings.len());
+                if findings.len() > self.max_drc_rows {
+                    text.push_str(&format!(", first {} listed", self.max_drc_rows));
+                }
+                text.push('.');
+                blocks.push(Block::Paragraph(text));
+                let net = |n: &Option<String>| n.clone().unwrap_or_else(|| "-".to_string());
+                let rows = findings
+                    .iter()
+                    .take(self.max_drc_rows)
+                    .map(|f| {
+                        vec![
+                            f.layer.clone(),
+                            format!("{} / {}", f.a, f.b),
+                            format!("{} / {}", net(&f.net_a), net(&f.net_b)),
+                            mm(f.actual),
+                            mm(f.required),
+                            format!("({:.3}, {:.3})", f.location.x.as_mm(), f.location.y.as_mm()),
+                        ]
+                    })
+                    .collect();
+                blocks.push(Block::Table(
+                    vec!["Layer", "Items", "Nets", "Gap", "Required", "Location"],
+                    rows,
+                ));
+            }
+        }
+
+        let models = &self.models;
+        blocks.push(Block::Heading(2, "3D models".to_string()));
+        blocks.push(Block::Paragraph(format!(
+            "{} of {} components ({:.1}%) have a 3D model.",
+            models.with_models,
+            models.components,
+            models.percent()
+        )));
+        if !models.by_type.is_empty() {
+            let rows = models
+                .by_type
+                .iter()
+                .map(|(kind, count)| vec![kind.clone(), count.to_string()])
+                .collect();
+            blocks.push(Block::Table(vec!["Format", "Models"], rows));
+        }
+        if !models.missing.is_empty() {
+            blocks.push(Block::Paragraph(format!(
+                "Missing: {}",
+                models.missing.join(", ")
+            )));
+        }
+        blocks
+    }
+}
+
+/// Short human-readable name of a copper item
+fn item_name(pcb: &PcbFile, item: CopperItem) -> String {
+    match item {
+        CopperItem::Pad { footprint, pad } => {
+            let footprint = &pcb.footprints[footprint];
+            format!(
+                "{} pad {}",
+                footprint.property("Reference").unwrap_or("?"),
+                footprint.pads[pad].number
+            )
+        }
+        CopperItem::Track(_) => "Track".to_string(),
+        CopperItem::Arc(_) => "Arc".to_string(),
+        CopperItem::Via(_) => "Via".to_string(),
+        CopperItem::Zone { zone, .. } => match pcb.zones[zone].net.as_deref() {
+            Some(net) => format!("Zone {}", net),
+            None => "Zone".to_string(),
+        },
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::parse_pcb;
+
+    const BOARD: &str = r#"(kicad_pcb
+  (general (thickness 1.6))
+  (layers (0 "F.Cu" signal) (31 "B.Cu" signal) (44 "Edge.Cuts" user))
+  (title_block (title "Sensor <v2>") (date "2026-10-01") (rev "C") (company "ACME"))
+  (setup
+    (stackup
+      (layer "F.Cu" (type "copper") (thickness 0.035))
+      (layer "dielectric 1" (type "core") (thickness 1.51) (material "FR4") (epsilon_r 4.5) (loss_tangent 0.02))
+      (layer "B.Cu" (type "copper") (thickness 0.035))))
+  (net 0 "") (net 1 "A") (net 2 "B")
+  (gr_rect (start 0 0) (end 40 30) (stroke (width 0.1) (type default)) (fill none) (layer "Edge.Cuts"))
+  (footprint "R_0603" (layer "F.Cu") (at 10 10) (property "Reference" "R1") (property "Value" "10k")
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 1 "A"))
+    (model "${KICAD8_3DMODEL_DIR}/R_0603.step"))
+  (footprint "R_0603" (layer "F.Cu") (at 20 10) (property "Reference" "R2") (property "Value" "10k")
+    (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu") (net 2 "B")))
+  (segment (start 10 10.6) (end 20 10.6) (width 0.2) (layer "F.Cu") (net 2))
+)"#;
+
+    #[test]
+    fn test_report_contents() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let options = ReportOptions {
+            drc: Some(DrcConfig {
+                min_clearance: Length::mm(0.2),
+                use_net_classes: false,
+            }),
+            ..Default::default()
+        };
+        let report = DesignReport::from_pcb(&pcb, &options);
+
+        assert_eq!(report.size, Some((40.0, 30.0)));
+        assert_eq!(report.net_count, 2);
+        assert_eq!(report.models.missing, ["R2"]);
+        assert_eq!(report.models.percent(), 50.0);
+        let drc = report.drc.as_ref().unwrap();
+        assert_eq!(drc.len(), 1);
+        assert_eq!(
+            (drc[0].a.as_str(), drc[0].b.as_str()),
+            ("R1 pad 1", "Track")
+        );
+
+        let skipped = DesignReport::from_pcb(
+            &pcb,
+            &ReportOptions {
+                drc: None,
+                max_drc_rows: 0,
+            },
+        );
+        assert!(skipped.to_markdown().contains("Clearance check not run."));
+    }
+
+    #[test]
+    fn test_markdown_and_html() {
+        let pcb = parse_pcb(BOARD).unwrap();
+        let report = DesignReport::from_pcb(&pcb, &ReportOptions::default());
+
+        let markdown = report.to_markdown();
+        assert!(
+            markdown.starts_with("# Sensor <v2> rev. C\n\nACME, 2026-10-01\n\n## Board summary\n")
+        );
+        assert!(markdown.contains("| Size | 40.00 x 30.00 mm |\n| Area | 1200.0 mm² |\n"));
+        assert!(markdown.contains("| dielectric 1 | core | 1.510 mm | FR4 | 4.5 | 0.02 |"));
+        assert!(markdown.contains("| 44 | Edge.Cuts | user |  |"));
+        assert!(markdown.contains("1 line items, 2 parts to place."));
+        assert!(markdown.contains("| STEP | 1 |\n\nMissing: R2\n"));
+
+        let html = report.to_html();
+        assert!(html.contains("<title>Sensor &lt;v2&gt; rev. C</title>"));
+        assert!(html.contains("<tr><td>Copper layers</td><td>2</td></tr>"));
+        assert!(html.ends_with("</body>\n</html>\n"));
+    }
+}
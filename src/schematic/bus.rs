@@ -0,0 +1,79 @@
+//! Bus names and their members
+//!
+//! A bus carries the nets its name lists. KiCad knows three forms, which
+//! [`bus_members`] expands into net names:
+//!
+//! - vector buses, `D[0..7]` for `D0` to `D7`
+//! - groups, `{SDA SCL}`; with a prefix, `I2C{SDA SCL}` stands for `I2C.SDA`
+//!   and `I2C.SCL`. Members may be vector buses or aliases themselves
+//! - bus aliases defined in the sheet with `bus_alias`, used on their own or
+//!   inside a group
+//!
+//! During net derivation a bus joins, member by member, the nets of the same
+//! name in its sheet, and through hierarchical bus pins and global bus labels
+//! the same members elsewhere; a bus never becomes a net itself.
+//!
+//! ```rust
+//! use kiparse::schematic::{bus::bus_members, BusAlias};
+//!
+//! let aliases = [BusAlias { name: "UART".to_string(), members: vec!["TX".to_string(), "RX".to_string()] }];
+//! assert_eq!(bus_members("A[1..3]", &aliases).unwrap(), ["A1", "A2", "A3"]);
+//! assert_eq!(bus_members("DBG{UART RST}", &aliases).unwrap(), ["DBG.TX", "DBG.RX", "DBG.RST"]);
+//! assert_eq!(bus_members("UART", &aliases).unwrap(), ["TX", "RX"]);
+//! assert!(bus_members("GND", &aliases).is_none());
+//! ```
+
+use super::types::{BusAlias, Schematic};
+
+/// Aliases nest at most this deep, which stops aliases that contain themselves
+const MAX_ALIAS_DEPTH: usize = 8;
+
+/// Net names a bus name stands for, or `None` when `name` is a net name
+pub fn bus_members(name: &str, aliases: &[BusAlias]) -> Option<Vec<String>> {
+    expand(name.trim(), aliases, 0)
+}
+
+impl Schematic {
+    /// [`bus_members`] with the bus aliases of this sheet
+    pub fn bus_members(&self, name: &str) -> Option<Vec<String>> {
+        bus_members(name, &self.bus_aliases)
+    }
+}
+
+fn expand(name: &str, aliases: &[BusAlias], depth: usize) -> Option<Vec<String>> {
+    if depth > MAX_ALIAS_DEPTH {
+        return None;
+    }
+    if let Some(alias) = aliases.iter().find(|a| a.name == name) {
+        return Some(alias.members.iter().flat_map(|m| member(m, aliases, depth + 1)).collect());
+    }
+    if let Some(inner) = name.strip_suffix('}') {
+        let (prefix, inner) = inner.split_once('{')?;
+        let members = inner
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|m| !m.is_empty())
+            .flat_map(|m| member(m, aliases, depth + 1));
+        return Some(match prefix {
+            "" => members.collect(),
+            prefix => members.map(|m| format!("{}.{}", prefix, m)).collect(),
+        });
+    }
+    vector(name)
+}
+
+/// A group or alias member: a bus expanded, or a single net
+fn member(name: &str, aliases: &[BusAlias], depth: usize) -> Vec<String> {
+    expand(name, aliases, depth).unwrap_or_else(|| vec![name.to_string()])
+}
+
+/// Members of a vector bus such as `D[0..7]` or `A[15..8]`
+fn vector(name: &str) -> Option<Vec<String>> {
+    let (prefix, range) = name.strip_suffix(']')?.split_once('[')?;
+    let (start, end) = range.split_once("..")?;
+    let (start, end): (u32, u32) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    if prefix.is_empty() {
+        return None;
+    }
+    let indices: Vec<u32> = if start <= end { (start..=end).collect() } else { (end..=start).rev().collect() };
+    Some(indices.into_iter().map(|i| format!("{}{}", prefix, i)).collect())
+}
@@ -0,0 +1,578 @@
+//! Net derivation from schematic sheets
+//!
+//! Joins wires, junctions, labels, sheet pins and symbol pins into nets the way
+//! Eeschema does: items connect where a connection point lands on another item's
+//! connection point or anywhere along a wire, while wires that merely cross stay
+//! apart unless a junction sits on the crossing. Groups sharing a label text or a
+//! power symbol value are the same net.
+//!
+//! Across a [`Hierarchy`](super::Hierarchy), local labels stay within their
+//! sheet instance, a hierarchical label joins the pin of the same name on the
+//! sheet symbol that places its sheet, and global labels and power symbols join
+//! everywhere; see [`Hierarchy::nets`](super::Hierarchy::nets). Buses join
+//! their members to the nets of the same names, as described in [`bus`](super::bus).
+//!
+//! Nets are named by their strongest driver: a global label, then a power symbol
+//! (or a hidden power input pin), then a local label, a hierarchical label and a
+//! sheet pin; ties go to the sheet nearest the root, then the alphabetically
+//! first name. Hierarchical labels and sheet pins rank alike across sheets, so
+//! a connection through a sheet symbol is named from the parent sheet. Local and hierarchical names get the sheet path, e.g. `/OUT` or
+//! `/Power/OUT`. Unnamed nets are called after their first pin, e.g.
+//! `Net-(R1-Pad1)`.
+//!
+//! ```rust
+//! use kiparse::schematic::parse_schematic;
+//!
+//! let schematic = parse_schematic(r#"(kicad_sch (version 20231120)
+//!   (lib_symbols
+//!     (symbol "Device:R"
+//!       (symbol "R_1_1"
+//!         (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+//!         (pin passive line (at 0 -3.81 90) (length 1.27) (name "~") (number "2")))))
+//!   (symbol (lib_id "Device:R") (at 100 50 0) (unit 1) (property "Reference" "R1"))
+//!   (wire (pts (xy 100 53.81) (xy 100 60)))
+//!   (label "OUT" (at 100 60 0))
+//! )"#)?;
+//! let nets = schematic.nets();
+//!
+//! assert_eq!(nets[0].name, "/OUT");
+//! assert_eq!(nets[0].nodes[0].pin, "2");
+//! assert_eq!(nets[1].name, "Net-(R1-Pad1)");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::bom::natural_order;
+use crate::pcb::types::Point;
+
+use super::types::{LabelKind, Schematic, SchematicSymbol, Wire};
+
+/// Coordinates closer than this (mm) are the same connection point
+const EPSILON: f64 = 1e-3;
+
+/// Symbol pin on a net
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NetNode {
+    pub reference: String,
+    pub pin: String,
+    pub pin_name: String,
+    /// Electrical type, e.g. `passive` or `power_in`
+    pub pin_type: String,
+}
+
+/// Connected items of a sheet under one name
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchematicNet {
+    pub name: String,
+    /// Pins in reference and pin number order; power symbols are left out
+    pub nodes: Vec<NetNode>,
+}
+
+/// A net name candidate, stronger drivers first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Driver {
+    Global,
+    Power,
+    Local,
+    Hierarchical,
+    SheetPin,
+}
+
+/// An item with a connection point
+enum Item {
+    Pin { node: NetNode, power_net: Option<String>, power_symbol: bool },
+    Label { kind: LabelKind, text: String },
+    /// Pin of the sheet symbol at this index of the sheet's `sheets`
+    SheetPin { sheet: usize, name: String },
+    Junction,
+    NoConnect,
+    /// One member of a bus, with the global labels, sheet pins (by sheet
+    /// symbol) and hierarchical labels naming the bus
+    BusMember { name: String, global: bool, sheet_pins: Vec<(usize, String)>, hierarchical: Vec<String> },
+}
+
+/// What joins items that are not wired together
+#[derive(PartialEq, Eq, Hash)]
+enum Shared<'a> {
+    /// Global label or power net, in any sheet
+    Global(&'a str),
+    /// Label text within one sheet instance
+    Label(usize, &'a str),
+    /// Sheet pin of a sheet instance's parent, by sheet symbol, and the
+    /// hierarchical label of the same name inside it
+    SheetPin(usize, usize, &'a str),
+    /// A member of a bus sheet pin, and of the hierarchical bus label inside
+    BusPinMember(usize, usize, &'a str, &'a str),
+}
+
+/// One sheet instance to derive nets from
+pub(crate) struct SheetView<'a> {
+    pub schematic: &'a Schematic,
+    /// Name path, e.g. `/` or `/Power/`
+    pub path: String,
+    /// Parent instance and the index of this sheet's symbol in its `sheets`
+    pub parent: Option<(usize, usize)>,
+    /// Placed symbols with the reference and unit annotated for this instance
+    pub symbols: Vec<(&'a SchematicSymbol, String, u32)>,
+}
+
+impl<'a> SheetView<'a> {
+    /// A lone sheet as the root, annotated as placed
+    pub fn root(schematic: &'a Schematic) -> Self {
+        SheetView {
+            schematic,
+            path: "/".to_string(),
+            parent: None,
+            symbols: schematic.symbols.iter().map(|s| (s, s.reference().to_string(), s.unit)).collect(),
+        }
+    }
+}
+
+/// Symbol pin of a [`Connection`]
+pub(crate) struct ConnectedPin {
+    pub node: NetNode,
+    /// Index of the sheet instance the pin is placed in
+    pub sheet: usize,
+    pub at: Point,
+    /// Net a power symbol or hidden power input pin joins
+    pub power_net: Option<String>,
+    pub power_symbol: bool,
+}
+
+/// Label or sheet pin of a [`Connection`]
+pub(crate) struct ConnectedLabel {
+    pub sheet: usize,
+    pub driver: Driver,
+    /// Text as placed, without the sheet path
+    pub text: String,
+    pub at: Point,
+}
+
+/// Everything on one net, across sheets
+pub(crate) struct Connection {
+    pub name: String,
+    /// Pins in reference and pin number order, power symbols included
+    pub pins: Vec<ConnectedPin>,
+    pub labels: Vec<ConnectedLabel>,
+    /// No-connect flags by sheet instance
+    pub no_connects: Vec<(usize, Point)>,
+}
+
+impl Schematic {
+    /// Nets of the sheet, sorted by name
+    pub fn nets(&self) -> Vec<SchematicNet> {
+        nets(self)
+    }
+}
+
+/// Derive the nets of one sheet, see the [module docs](self)
+pub fn nets(schematic: &Schematic) -> Vec<SchematicNet> {
+    derive(&[SheetView::root(schematic)])
+}
+
+/// Derive the nets of sheet instances, the root first
+pub(crate) fn derive(sheets: &[SheetView]) -> Vec<SchematicNet> {
+    connections(sheets)
+        .into_iter()
+        .map(|connection| SchematicNet {
+            name: connection.name,
+            nodes: connection.pins.into_iter().filter(|p| !p.node.reference.starts_with('#')).map(|p| p.node).collect(),
+        })
+        .collect()
+}
+
+/// Connected items of sheet instances, the root first, sorted by net name
+pub(crate) fn connections(sheets: &[SheetView]) -> Vec<Connection> {
+    // Items of every sheet, joined where they are wired together
+    let mut items: Vec<(usize, Item, Point)> = Vec::new();
+    let mut parent: Vec<usize> = Vec::new();
+    for (s, sheet) in sheets.iter().enumerate() {
+        let mut first_of_group: HashMap<usize, usize> = HashMap::new();
+        for (item, at, group) in connect(sheet) {
+            let index = items.len();
+            parent.push(index);
+            match first_of_group.get(&group) {
+                Some(&other) => union(&mut parent, index, other),
+                None => {
+                    first_of_group.insert(group, index);
+                }
+            }
+            items.push((s, item, at));
+        }
+    }
+
+    // Groups sharing a name are one net: label texts within a sheet, global
+    // labels and power nets anywhere, sheet pins with the labels they lead to
+    let mut by_name: HashMap<Shared, usize> = HashMap::new();
+    for (i, (s, item, _)) in items.iter().enumerate() {
+        let mut shared = Vec::new();
+        match item {
+            Item::Label { kind: LabelKind::Global, text } => shared.push(Shared::Global(text)),
+            Item::Label { kind, text } => {
+                shared.push(Shared::Label(*s, text));
+                if let (LabelKind::Hierarchical, Some((p, sheet))) = (kind, sheets[*s].parent) {
+                    shared.push(Shared::SheetPin(p, sheet, text));
+                }
+            }
+            Item::Pin { power_net: Some(net), .. } => shared.push(Shared::Global(net)),
+            Item::SheetPin { sheet, name } => shared.push(Shared::SheetPin(*s, *sheet, name)),
+            Item::BusMember { name, global, sheet_pins, hierarchical } => {
+                shared.push(Shared::Label(*s, name));
+                if *global {
+                    shared.push(Shared::Global(name));
+                }
+                for (sheet, pin) in sheet_pins {
+                    shared.push(Shared::BusPinMember(*s, *sheet, pin, name));
+                }
+                if let Some((p, sheet)) = sheets[*s].parent {
+                    for label in hierarchical {
+                        shared.push(Shared::BusPinMember(p, sheet, label, name));
+                    }
+                }
+            }
+            _ => {}
+        }
+        for name in shared {
+            match by_name.get(&name) {
+                Some(&other) => union(&mut parent, i, other),
+                None => {
+                    by_name.insert(name, i);
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<(usize, Item, Point)>> = BTreeMap::new();
+    for (i, item) in items.into_iter().enumerate() {
+        let root = find(&mut parent, i);
+        groups.entry(root).or_default().push(item);
+    }
+
+    let mut connections: Vec<Connection> = Vec::new();
+    for group in groups.into_values() {
+        let mut drivers: Vec<(Driver, usize, String)> = Vec::new();
+        let mut pins: Vec<ConnectedPin> = Vec::new();
+        let mut labels: Vec<ConnectedLabel> = Vec::new();
+        let mut no_connects: Vec<(usize, Point)> = Vec::new();
+        for (s, item, at) in group {
+            let path = &sheets[s].path;
+            let depth = path.matches('/').count();
+            match item {
+                Item::Pin { node, power_net, power_symbol } => {
+                    if let Some(net) = &power_net {
+                        drivers.push((Driver::Power, 0, net.clone()));
+                    }
+                    if !pins.iter().any(|p| p.node.reference == node.reference && p.node.pin == node.pin) {
+                        pins.push(ConnectedPin { node, sheet: s, at, power_net, power_symbol });
+                    }
+                }
+                Item::Label { kind, text } => {
+                    let driver = match kind {
+                        LabelKind::Global => Driver::Global,
+                        LabelKind::Local => Driver::Local,
+                        LabelKind::Hierarchical => Driver::Hierarchical,
+                    };
+                    let name = if kind == LabelKind::Global { text.clone() } else { format!("{}{}", path, text) };
+                    drivers.push((driver, if kind == LabelKind::Global { 0 } else { depth }, name));
+                    labels.push(ConnectedLabel { sheet: s, driver, text, at });
+                }
+                Item::SheetPin { name, .. } => {
+                    drivers.push((Driver::SheetPin, depth, format!("{}{}", path, name)));
+                    labels.push(ConnectedLabel { sheet: s, driver: Driver::SheetPin, text: name, at });
+                }
+                Item::NoConnect => no_connects.push((s, at)),
+                Item::Junction | Item::BusMember { .. } => {}
+            }
+        }
+        pins.sort_by(|a, b| {
+            natural_order(&a.node.reference, &b.node.reference).then_with(|| natural_order(&a.node.pin, &b.node.pin))
+        });
+        // A sheet pin and the hierarchical label inside are one connection,
+        // named from the sheet nearest the root
+        let name = drivers
+            .into_iter()
+            .min_by(|a, b| (a.0.min(Driver::Hierarchical), a.1, a.0, &a.2).cmp(&(b.0.min(Driver::Hierarchical), b.1, b.0, &b.2)))
+            .map(|(_, _, name)| name);
+        let name = match name {
+            Some(name) => name,
+            None => match pins.iter().find(|p| !p.node.reference.starts_with('#')) {
+                Some(pin) => default_name(&pin.node),
+                // Junctions and no-connect flags on a bare wire
+                None => continue,
+            },
+        };
+        connections.push(Connection { name, pins, labels, no_connects });
+    }
+    connections.sort_by(|a, b| natural_order(&a.name, &b.name));
+    connections
+}
+
+/// Point items of a sheet with the group of items wired to each
+fn connect(sheet: &SheetView) -> Vec<(Item, Point, usize)> {
+    let schematic = sheet.schematic;
+    let mut items: Vec<(Item, Point)> = Vec::new();
+    // Labels and sheet pins naming a bus, with its members
+    let mut bus_items: Vec<(Item, Vec<String>, Point)> = Vec::new();
+    for (symbol, reference, unit) in &sheet.symbols {
+        let Some(lib) = schematic.lib_symbol(symbol) else {
+            continue;
+        };
+        for (pin, at) in schematic.symbol_unit_pins(symbol, *unit) {
+            // Power symbols name the net after their value; hidden power inputs of
+            // ordinary symbols join the global net named after the pin
+            let power_net = if lib.power {
+                symbol.property("Value").map(str::to_string)
+            } else if pin.hidden && pin.pin_type == "power_in" {
+                Some(pin.name.clone())
+            } else {
+                None
+            };
+            let node = NetNode {
+                reference: reference.clone(),
+                pin: pin.number.clone(),
+                pin_name: pin.name.clone(),
+                pin_type: pin.pin_type.clone(),
+            };
+            items.push((Item::Pin { node, power_net, power_symbol: lib.power }, at));
+        }
+    }
+    for label in &schematic.labels {
+        let item = Item::Label { kind: label.kind, text: label.text.clone() };
+        match schematic.bus_members(&label.text) {
+            Some(members) => bus_items.push((item, members, label.at.clone())),
+            None => items.push((item, label.at.clone())),
+        }
+    }
+    for (index, sheet) in schematic.sheets.iter().enumerate() {
+        for pin in &sheet.pins {
+            let item = Item::SheetPin { sheet: index, name: pin.name.clone() };
+            match schematic.bus_members(&pin.name) {
+                Some(members) => bus_items.push((item, members, pin.at.clone())),
+                None => items.push((item, pin.at.clone())),
+            }
+        }
+    }
+    for junction in &schematic.junctions {
+        items.push((Item::Junction, junction.clone()));
+    }
+    for flag in &schematic.no_connects {
+        items.push((Item::NoConnect, flag.clone()));
+    }
+
+    let points: Vec<&Point> = items.iter().map(|(_, at)| at).collect();
+    let (roots, sizes) = join(&schematic.wires, &points);
+    // A sheet pin with nothing attached in this sheet neither joins nor names anything
+    let mut connected: Vec<(Item, Point, usize)> = items
+        .into_iter()
+        .zip(roots)
+        .filter(|((item, _), root)| !matches!(item, Item::SheetPin { .. }) || sizes[root] > 1)
+        .map(|((item, at), root)| (item, at, root))
+        .collect();
+
+    // Buses join their labels and pins like wires do (junctions included), then
+    // each member becomes an item of its own
+    let mut points: Vec<&Point> = bus_items.iter().map(|(_, _, at)| at).collect();
+    points.extend(&schematic.junctions);
+    let (roots, sizes) = join(&schematic.buses, &points);
+    let mut buses: BTreeMap<usize, Vec<(Item, Vec<String>, Point)>> = BTreeMap::new();
+    for (bus_item, root) in bus_items.into_iter().zip(roots) {
+        if matches!(bus_item.0, Item::SheetPin { .. }) && sizes[&root] == 1 {
+            continue;
+        }
+        buses.entry(root).or_default().push(bus_item);
+    }
+    // Group numbers past those of the wired items
+    let mut group = connected.iter().map(|(_, _, g)| g + 1).max().unwrap_or(0);
+    for bus in buses.into_values() {
+        let mut members: Vec<String> = Vec::new();
+        let (mut global, mut sheet_pins, mut hierarchical) = (false, Vec::new(), Vec::new());
+        for (item, names, _) in &bus {
+            for name in names {
+                if !members.contains(name) {
+                    members.push(name.clone());
+                }
+            }
+            match item {
+                Item::Label { kind: LabelKind::Global, .. } => global = true,
+                Item::Label { kind: LabelKind::Hierarchical, text } => hierarchical.push(text.clone()),
+                Item::SheetPin { sheet, name } => sheet_pins.push((*sheet, name.clone())),
+                _ => {}
+            }
+        }
+        let at = bus[0].2.clone();
+        for name in members {
+            let item = Item::BusMember { name, global, sheet_pins: sheet_pins.clone(), hierarchical: hierarchical.clone() };
+            connected.push((item, at.clone(), group));
+            group += 1;
+        }
+    }
+    connected
+}
+
+/// Union-find over segments and the points on them, crossing segments staying
+/// apart unless a point sits on the crossing
+///
+/// Returns the group of each point and the number of segments and points in
+/// each group.
+fn join(segments: &[Wire], points: &[&Point]) -> (Vec<usize>, HashMap<usize, usize>) {
+    let mut parent: Vec<usize> = (0..segments.len() + points.len()).collect();
+    let mut at_point: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut connect = |parent: &mut Vec<usize>, index: usize, point: &Point| {
+        if let Some(&other) = at_point.get(&key(point)) {
+            union(parent, index, other);
+        } else {
+            at_point.insert(key(point), index);
+        }
+        for (s, segment) in segments.iter().enumerate() {
+            if s != index && on_wire(point, segment) {
+                union(parent, index, s);
+            }
+        }
+    };
+    for (s, segment) in segments.iter().enumerate() {
+        connect(&mut parent, s, &segment.start);
+        connect(&mut parent, s, &segment.end);
+    }
+    for (i, point) in points.iter().enumerate() {
+        connect(&mut parent, segments.len() + i, point);
+    }
+
+    let roots: Vec<usize> = (0..parent.len()).map(|i| find(&mut parent, i)).collect();
+    let mut sizes: HashMap<usize, usize> = HashMap::new();
+    for root in &roots {
+        *sizes.entry(*root).or_default() += 1;
+    }
+    (roots[segments.len()..].to_vec(), sizes)
+}
+
+/// Name of an unlabelled net after one of its pins, as Eeschema does
+fn default_name(node: &NetNode) -> String {
+    if node.pin_name.is_empty() || node.pin_name == "~" {
+        format!("Net-({}-Pad{})", node.reference, node.pin)
+    } else {
+        format!("Net-({}-{})", node.reference, node.pin_name)
+    }
+}
+
+fn key(point: &Point) -> (i64, i64) {
+    ((point.x.as_mm() / EPSILON).round() as i64, (point.y.as_mm() / EPSILON).round() as i64)
+}
+
+/// Whether `point` lies on the wire, ends included
+fn on_wire(point: &Point, wire: &Wire) -> bool {
+    let (dx, dy) = ((wire.end.x - wire.start.x).as_mm(), (wire.end.y - wire.start.y).as_mm());
+    let (px, py) = ((point.x - wire.start.x).as_mm(), (point.y - wire.start.y).as_mm());
+    let length_sq = dx * dx + dy * dy;
+    if length_sq < EPSILON * EPSILON {
+        return px.abs() < EPSILON && py.abs() < EPSILON;
+    }
+    let t = (px * dx + py * dy) / length_sq;
+    let cross = (px * dy - py * dx).abs() / length_sq.sqrt();
+    cross < EPSILON && (-EPSILON..=1.0 + EPSILON).contains(&t)
+}
+
+fn find(parent: &mut [usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        parent[i] = parent[parent[i]];
+        i = parent[i];
+    }
+    i
+}
+
+fn union(parent: &mut [usize], a: usize, b: usize) {
+    let (a, b) = (find(parent, a), find(parent, b));
+    if a != b {
+        parent[a.max(b)] = a.min(b);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pcb::types::Point;
+    use crate::schematic::{parse_schematic, Hierarchy};
+
+    const DIVIDER: &str = include_str!("../../assets/divider.kicad_sch");
+
+    #[test]
+    fn test_divider_nets() {
+        let schematic = parse_schematic(DIVIDER).unwrap();
+        let nets = schematic.nets();
+        let summary: Vec<(String, Vec<String>)> = nets
+            .iter()
+            .map(|n| (n.name.clone(), n.nodes.iter().map(|p| format!("{}.{}", p.reference, p.pin)).collect()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/OUT".to_string(), vec!["R1.2".to_string(), "R2.1".to_string()]),
+                ("GND".to_string(), vec!["R2.2".to_string(), "V1.2".to_string()]),
+                ("VIN".to_string(), vec!["R1.1".to_string(), "V1.1".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crossing_wires_need_a_junction() {
+        let sheet = |junction: &str| {
+            format!(
+                r#"(kicad_sch
+  (wire (pts (xy 0 5) (xy 10 5))) (wire (pts (xy 5 0) (xy 5 10)))
+  (label "A" (at 0 5 0)) (label "B" (at 5 0 0)) {})"#,
+                junction
+            )
+        };
+        let apart = parse_schematic(&sheet("")).unwrap().nets();
+        assert_eq!(apart.len(), 2);
+        let joined = parse_schematic(&sheet("(junction (at 5 5))")).unwrap().nets();
+        assert_eq!(joined.len(), 1);
+        assert_eq!(joined[0].name, "/A");
+    }
+
+    #[test]
+    fn test_bus_members_through_a_sheet() {
+        let lib = r#"(lib_symbols (symbol "Device:R" (symbol "R_1_1"
+    (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+    (pin passive line (at 0 -3.81 90) (length 1.27) (name "~") (number "2")))))"#;
+        let root = format!(
+            r#"(kicad_sch (uuid "root") {}
+  (symbol (lib_id "Device:R") (at 50 50 0) (unit 1) (property "Reference" "R1"))
+  (symbol (lib_id "Device:R") (at 60 50 0) (unit 1) (property "Reference" "R2"))
+  (label "D0" (at 50 53.81 0)) (label "D1" (at 60 53.81 0))
+  (bus (pts (xy 40 70) (xy 100 70))) (label "D[0..1]" (at 70 70 0))
+  (sheet (at 100 60) (size 20 20) (uuid "s") (property "Sheetname" "Sub") (property "Sheetfile" "sub.kicad_sch")
+    (pin "D[0..1]" input (at 100 70 180))))"#,
+            lib
+        );
+        let sub = format!(
+            r#"(kicad_sch (uuid "sub") {}
+  (bus_alias "DATA" (members "D[0..1]"))
+  (symbol (lib_id "Device:R") (at 50 50 0) (unit 1) (property "Reference" "R3"))
+  (symbol (lib_id "Device:R") (at 60 50 0) (unit 1) (property "Reference" "R4"))
+  (label "D0" (at 50 46.19 0)) (label "D1" (at 60 46.19 0))
+  (bus (pts (xy 20 30) (xy 40 30))) (bus_entry (at 40 30) (size 2.54 2.54))
+  (hierarchical_label "D[0..1]" (shape input) (at 20 30 180)))"#,
+            lib
+        );
+        let hierarchy = Hierarchy::build("top.kicad_sch", &root, |_| Ok(sub.clone())).unwrap();
+        let child = &hierarchy.files[1].schematic;
+        assert_eq!(child.bus_entries[0].end(), Point::new(42.54, 32.54));
+        assert_eq!(child.bus_members("DATA").unwrap(), ["D0", "D1"]);
+
+        let named: Vec<(String, Vec<String>)> = hierarchy
+            .nets()
+            .iter()
+            .filter(|n| n.name.starts_with('/'))
+            .map(|n| (n.name.clone(), n.nodes.iter().map(|p| format!("{}.{}", p.reference, p.pin)).collect()))
+            .collect();
+        assert_eq!(
+            named,
+            vec![
+                ("/D0".to_string(), vec!["R1.2".to_string(), "R3.1".to_string()]),
+                ("/D1".to_string(), vec!["R2.2".to_string(), "R4.1".to_string()]),
+            ]
+        );
+    }
+}
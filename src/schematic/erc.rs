@@ -0,0 +1,245 @@
+//! Electrical rule checks on schematic connectivity
+//!
+//! [`run_erc`] checks the nets of a sheet, and [`Hierarchy::erc`] those of a
+//! whole design, for the mistakes Eeschema's ERC reports most often: symbol pins
+//! connected to nothing, several outputs driving one net, power inputs nothing
+//! supplies and nets given different names within a sheet. Each
+//! [`ErcViolation`] carries the sheet path and sheet coordinates of the first
+//! item involved.
+//!
+//! As in Eeschema, power symbols have power input pins, so a net fed only by
+//! power symbols needs a `PWR_FLAG` or a regulator's power output to count as
+//! driven. Pins with a no-connect flag or of the `no_connect` type are not
+//! reported as unconnected, nor are hidden power inputs, which connect by name.
+//!
+//! ```rust
+//! use kiparse::schematic::{erc::ErcCheck, parse_schematic};
+//!
+//! let schematic = parse_schematic(r##"(kicad_sch (version 20231120)
+//!   (lib_symbols
+//!     (symbol "Device:R"
+//!       (symbol "R_1_1"
+//!         (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+//!         (pin passive line (at 0 -3.81 90) (length 1.27) (name "~") (number "2"))))
+//!     (symbol "power:GND" (power)
+//!       (symbol "GND_1_1" (pin power_in line (at 0 0 270) (length 0) hide (name "GND") (number "1")))))
+//!   (symbol (lib_id "Device:R") (at 100 50 0) (unit 1) (property "Reference" "R1"))
+//!   (symbol (lib_id "power:GND") (at 100 53.81 0) (unit 1) (property "Reference" "#PWR01") (property "Value" "GND"))
+//! )"##)?;
+//! let violations = schematic.erc();
+//!
+//! let checks: Vec<ErcCheck> = violations.iter().map(|v| v.check).collect();
+//! assert_eq!(checks, [ErcCheck::UnconnectedPin, ErcCheck::PowerNotDriven]);
+//! assert_eq!(violations[0].items, ["R1.1"]);
+//! assert_eq!(violations[1].net, "GND");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use super::connectivity::{connections, ConnectedPin, Connection, Driver, SheetView};
+use super::hierarchy::Hierarchy;
+use super::types::Schematic;
+use crate::pcb::types::Point;
+use crate::validate::Severity;
+
+/// Check an [`ErcViolation`] comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ErcCheck {
+    /// A symbol pin connected to nothing, without a no-connect flag
+    UnconnectedPin,
+    /// More than one output or power output pin on a net
+    OutputConflict,
+    /// Power input pins on a net without a power output pin
+    PowerNotDriven,
+    /// Labels or power symbols of different names on one net within a sheet
+    MultipleNetNames,
+}
+
+impl ErcCheck {
+    /// Name used in reports, e.g. `unconnected_pin`
+    pub fn name(self) -> &'static str {
+        match self {
+            ErcCheck::UnconnectedPin => "unconnected_pin",
+            ErcCheck::OutputConflict => "output_conflict",
+            ErcCheck::PowerNotDriven => "power_not_driven",
+            ErcCheck::MultipleNetNames => "multiple_net_names",
+        }
+    }
+}
+
+/// One finding of [`run_erc`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErcViolation {
+    pub check: ErcCheck,
+    pub severity: Severity,
+    pub net: String,
+    /// Pins involved as `REF.PIN`; the conflicting names for
+    /// [`ErcCheck::MultipleNetNames`]
+    pub items: Vec<String>,
+    /// Name path of the sheet the first item is in, e.g. `/` or `/Power/`
+    pub sheet: String,
+    /// Connection point of the first item, in sheet coordinates
+    pub location: Point,
+    pub message: String,
+}
+
+impl Schematic {
+    /// [`run_erc`] on this sheet
+    pub fn erc(&self) -> Vec<ErcViolation> {
+        run_erc(self)
+    }
+}
+
+impl Hierarchy {
+    /// Electrical rule checks of the whole design, see the [`erc`](super::erc)
+    /// module docs
+    ///
+    /// Sheets used more than once are checked once per instance, under the
+    /// references annotated for each.
+    pub fn erc(&self) -> Vec<ErcViolation> {
+        check(&self.views())
+    }
+}
+
+/// Electrical rule checks of one sheet, in the order of [`ErcCheck`] and then
+/// of net names
+///
+/// Unconnected pins, output conflicts and undriven power inputs are errors;
+/// multiple net names are warnings, as Eeschema just picks one of the names.
+pub fn run_erc(schematic: &Schematic) -> Vec<ErcViolation> {
+    check(&[SheetView::root(schematic)])
+}
+
+fn check(sheets: &[SheetView]) -> Vec<ErcViolation> {
+    let mut violations = Vec::new();
+    for connection in connections(sheets) {
+        let violation = |check, severity, items: Vec<String>, sheet: usize, location: &Point, message: String| ErcViolation {
+            check,
+            severity,
+            net: connection.name.clone(),
+            items,
+            sheet: sheets[sheet].path.clone(),
+            location: location.clone(),
+            message,
+        };
+        let pin_names = |pins: &[&ConnectedPin]| -> Vec<String> {
+            pins.iter().map(|p| format!("{}.{}", p.node.reference, p.node.pin)).collect()
+        };
+
+        if let [pin] = connection.pins.as_slice() {
+            let alone = connection.labels.is_empty() && connection.no_connects.is_empty();
+            if alone && pin.power_net.is_none() && pin.node.pin_type != "no_connect" {
+                let items = pin_names(&[pin]);
+                let message = format!("Pin {} is not connected", items[0]);
+                violations.push(violation(ErcCheck::UnconnectedPin, Severity::Error, items, pin.sheet, &pin.at, message));
+            }
+        }
+
+        let outputs: Vec<_> = connection.pins.iter().filter(|p| is_output(&p.node.pin_type)).collect();
+        if outputs.len() > 1 {
+            let items = pin_names(&outputs);
+            let message = format!("Outputs {} drive net {} together", items.join(", "), connection.name);
+            violations.push(violation(ErcCheck::OutputConflict, Severity::Error, items, outputs[0].sheet, &outputs[0].at, message));
+        }
+
+        let inputs: Vec<_> = connection.pins.iter().filter(|p| p.node.pin_type == "power_in").collect();
+        if !inputs.is_empty() && !connection.pins.iter().any(|p| p.node.pin_type == "power_out") {
+            let items = pin_names(&inputs);
+            let message = format!("Power input {} on net {} is not driven by a power output", items[0], connection.name);
+            violations.push(violation(ErcCheck::PowerNotDriven, Severity::Error, items, inputs[0].sheet, &inputs[0].at, message));
+        }
+
+        for (sheet, names) in names_by_sheet(&connection) {
+            if names.len() > 1 {
+                let items: Vec<String> = names.iter().map(|(name, _)| name.clone()).collect();
+                let message = format!("Net {} has the names {} in sheet {}", connection.name, items.join(", "), sheets[sheet].path);
+                violations.push(violation(ErcCheck::MultipleNetNames, Severity::Warning, items, sheet, &names[0].1, message));
+            }
+        }
+    }
+    violations.sort_by_key(|v| v.check);
+    violations
+}
+
+fn is_output(pin_type: &str) -> bool {
+    pin_type == "output" || pin_type == "power_out"
+}
+
+/// Distinct names given by labels and power symbols, per sheet instance, with
+/// where each is first placed
+fn names_by_sheet(connection: &Connection) -> BTreeMap<usize, Vec<(String, Point)>> {
+    let labels = connection
+        .labels
+        .iter()
+        .filter(|l| matches!(l.driver, Driver::Global | Driver::Local))
+        .map(|l| (l.sheet, &l.text, &l.at));
+    let power = connection
+        .pins
+        .iter()
+        .filter(|p| p.power_symbol)
+        .filter_map(|p| Some((p.sheet, p.power_net.as_ref()?, &p.at)));
+    let mut names: BTreeMap<usize, Vec<(String, Point)>> = BTreeMap::new();
+    for (sheet, name, at) in labels.chain(power) {
+        let sheet_names = names.entry(sheet).or_default();
+        if !sheet_names.iter().any(|(n, _)| n == name) {
+            sheet_names.push((name.clone(), at.clone()));
+        }
+    }
+    for sheet_names in names.values_mut() {
+        sheet_names.sort_by(|a, b| a.0.cmp(&b.0));
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::parse_schematic;
+
+    const DIVIDER: &str = include_str!("../../assets/divider.kicad_sch");
+
+    #[test]
+    fn test_divider_needs_a_power_flag() {
+        let violations = parse_schematic(DIVIDER).unwrap().erc();
+        let found: Vec<(ErcCheck, &str)> = violations.iter().map(|v| (v.check, v.net.as_str())).collect();
+        assert_eq!(found, [(ErcCheck::PowerNotDriven, "GND")]);
+        assert_eq!(violations[0].sheet, "/");
+    }
+
+    #[test]
+    fn test_conflicts_and_flags() {
+        let schematic = parse_schematic(r#"(kicad_sch
+  (lib_symbols
+    (symbol "Logic:BUF"
+      (symbol "BUF_1_1"
+        (pin input line (at -5.08 0 0) (length 2.54) (name "A") (number "1"))
+        (pin output line (at 5.08 0 180) (length 2.54) (name "Y") (number "2"))
+        (pin no_connect line (at 0 5.08 270) (length 2.54) (name "NC") (number "3")))))
+  (symbol (lib_id "Logic:BUF") (at 50 50 0) (unit 1) (property "Reference" "U1"))
+  (symbol (lib_id "Logic:BUF") (at 70 50 0) (unit 1) (property "Reference" "U2"))
+  (symbol (lib_id "Logic:BUF") (at 70 60 0) (unit 1) (property "Reference" "U3"))
+  (no_connect (at 44.92 50))
+  (wire (pts (xy 55.08 50) (xy 64.92 50)))
+  (label "Y" (at 62 50 0)) (label "X" (at 60 50 0))
+  (label "Z" (at 75.08 50 0)) (label "Z" (at 75.08 60 0)))"#).unwrap();
+        let violations = schematic.erc();
+        let found: Vec<(ErcCheck, Severity, &str, Vec<&str>)> = violations
+            .iter()
+            .map(|v| (v.check, v.severity, v.net.as_str(), v.items.iter().map(String::as_str).collect()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (ErcCheck::UnconnectedPin, Severity::Error, "Net-(U3-A)", vec!["U3.1"]),
+                (ErcCheck::OutputConflict, Severity::Error, "/Z", vec!["U2.2", "U3.2"]),
+                (ErcCheck::MultipleNetNames, Severity::Warning, "/X", vec!["X", "Y"]),
+            ]
+        );
+        assert_eq!(violations[0].location, Point::new(64.92, 60.0));
+        assert_eq!(violations[2].location, Point::new(60.0, 50.0));
+    }
+}
@@ -0,0 +1,387 @@
+//! Sheet hierarchy of a multi-sheet design
+//!
+//! A KiCad design is a root sheet whose sheet symbols refer to child sheet
+//! files, which may refer to further sheets; a file used by several sheet
+//! symbols is one sheet drawn several times. [`Hierarchy`] loads every file
+//! once and builds the tree of sheet instances, each with its name path (e.g.
+//! `/Power/`) and uuid path. [`Hierarchy::symbols`] lists every placed symbol
+//! once per sheet instance with the reference, unit and properties annotated
+//! for that instance, [`Hierarchy::labels`] does the same for labels, and
+//! [`Hierarchy::nets`] derives the nets of the design across sheets.
+//!
+//! Child file names are relative to the directory of the sheet that refers to
+//! them, as in Eeschema.
+//!
+//! ```rust
+//! use kiparse::schematic::Hierarchy;
+//!
+//! let root = r#"(kicad_sch (uuid "root")
+//!   (sheet (at 0 0) (size 20 10) (uuid "ch1") (property "Sheetname" "CH1") (property "Sheetfile" "channel.kicad_sch"))
+//!   (sheet (at 0 20) (size 20 10) (uuid "ch2") (property "Sheetname" "CH2") (property "Sheetfile" "channel.kicad_sch")))"#;
+//! let channel = r#"(kicad_sch (uuid "channel")
+//!   (symbol (lib_id "Device:R") (at 10 10 0) (uuid "r") (property "Reference" "R?") (property "Value" "1k")
+//!     (instances (project "demo" (path "/root/ch1" (reference "R1") (unit 1)) (path "/root/ch2" (reference "R2") (unit 1))))))"#;
+//!
+//! let hierarchy = Hierarchy::build("demo.kicad_sch", root, |_| Ok(channel.to_string()))?;
+//! assert_eq!(hierarchy.sheets.len(), 3);
+//! assert_eq!(hierarchy.files.len(), 2);
+//! let references: Vec<String> = hierarchy.symbols().iter().map(|s| s.qualified_reference()).collect();
+//! assert_eq!(references, ["/CH1/R1", "/CH2/R2"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::connectivity::{derive, SchematicNet, SheetView};
+use super::parser::parse_schematic;
+use super::types::{Label, LabelKind, Schematic, SchematicSymbol, SymbolInstance};
+use crate::error::{KicadError, Result};
+
+/// A loaded sheet file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetFile {
+    /// Path relative to the root file's directory, as used to read it
+    pub file: String,
+    pub schematic: Schematic,
+}
+
+/// One place a sheet file appears in the hierarchy
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetInstance {
+    /// Sheet name from the parent's sheet symbol; empty for the root
+    pub name: String,
+    /// Path of the sheet file, relative to the root file's directory
+    pub file: String,
+    /// Sheet names from the root, e.g. `/` or `/Power/LDO/`
+    pub path: String,
+    /// Sheet uuids from the root uuid, the form symbol instances refer to
+    pub uuid_path: String,
+    pub parent: Option<usize>,
+    /// Indices of child sheets in [`Hierarchy::sheets`], in file order
+    pub children: Vec<usize>,
+    /// Index of the sheet's file in [`Hierarchy::files`]
+    pub schematic: usize,
+}
+
+/// Sheet tree of a design, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hierarchy {
+    /// Each file once, the root first
+    pub files: Vec<SheetFile>,
+    /// Sheet instances depth first, the root first
+    pub sheets: Vec<SheetInstance>,
+}
+
+/// A placed symbol in one sheet instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalSymbol<'a> {
+    /// Index of the sheet instance in [`Hierarchy::sheets`]
+    pub sheet: usize,
+    /// Name path of the sheet, e.g. `/Power/`
+    pub sheet_path: String,
+    pub symbol: &'a SchematicSymbol,
+    /// Reference annotated for this instance
+    pub reference: String,
+    pub unit: u32,
+    /// Properties of the symbol with this instance's reference and, for KiCad 6
+    /// designs, its value and footprint
+    pub properties: HashMap<String, String>,
+    /// Instance data the reference comes from, when the symbol has any
+    pub instance: Option<&'a SymbolInstance>,
+}
+
+impl HierarchicalSymbol<'_> {
+    /// Reference prefixed with the sheet path, e.g. `/Power/U1`
+    pub fn qualified_reference(&self) -> String {
+        format!("{}{}", self.sheet_path, self.reference)
+    }
+
+    /// Value of the named property for this instance
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+}
+
+/// A label in one sheet instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct HierarchicalLabel<'a> {
+    /// Index of the sheet instance in [`Hierarchy::sheets`]
+    pub sheet: usize,
+    /// Name path of the sheet, e.g. `/Power/`
+    pub sheet_path: String,
+    pub label: &'a Label,
+}
+
+impl HierarchicalLabel<'_> {
+    /// Net name the label gives, e.g. `/Power/VOUT`; global labels keep their text
+    pub fn net_name(&self) -> String {
+        match self.label.kind {
+            LabelKind::Global => self.label.text.clone(),
+            _ => format!("{}{}", self.sheet_path, self.label.text),
+        }
+    }
+}
+
+impl Hierarchy {
+    /// Load the root sheet at `root` and every sheet it refers to from disk
+    pub fn load(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let dir = root.parent().unwrap_or_else(|| Path::new(""));
+        let name = root.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        let content = std::fs::read_to_string(root)?;
+        Self::build(&name, &content, |file| Ok(std::fs::read_to_string(dir.join(file))?))
+    }
+
+    /// Build the hierarchy from the root sheet's content, reading child sheets
+    /// through `read`
+    ///
+    /// `read` gets each file path relative to the root file's directory and is
+    /// called once per file. A sheet that contains itself, directly or further
+    /// down, is an error.
+    pub fn build<F>(root_file: &str, root: &str, mut read: F) -> Result<Self>
+    where
+        F: FnMut(&str) -> Result<String>,
+    {
+        let schematic = parse_schematic(root)?;
+        let uuid_path = format!("/{}", schematic.uuid.as_deref().unwrap_or_default());
+        let mut hierarchy = Hierarchy {
+            files: vec![SheetFile { file: root_file.to_string(), schematic }],
+            sheets: vec![SheetInstance {
+                name: String::new(),
+                file: root_file.to_string(),
+                path: "/".to_string(),
+                uuid_path,
+                parent: None,
+                children: Vec::new(),
+                schematic: 0,
+            }],
+        };
+        hierarchy.add_children(0, &mut read)?;
+        Ok(hierarchy)
+    }
+
+    fn add_children<F>(&mut self, parent: usize, read: &mut F) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<String>,
+    {
+        let sheets = self.files[self.sheets[parent].schematic].schematic.sheets.clone();
+        let dir = Path::new(&self.sheets[parent].file).parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        for sheet in sheets {
+            let file = dir.join(&sheet.file).to_string_lossy().replace('\\', "/");
+            let mut ancestor = Some(parent);
+            while let Some(index) = ancestor {
+                if self.sheets[index].file == file {
+                    return Err(KicadError::InvalidFormat(format!(
+                        "Sheet {} includes its own file {}",
+                        sheet.name, file
+                    )));
+                }
+                ancestor = self.sheets[index].parent;
+            }
+            let schematic = match self.files.iter().position(|f| f.file == file) {
+                Some(index) => index,
+                None => {
+                    let schematic = parse_schematic(&read(&file)?)?;
+                    self.files.push(SheetFile { file: file.clone(), schematic });
+                    self.files.len() - 1
+                }
+            };
+            let parent_sheet = &self.sheets[parent];
+            let instance = SheetInstance {
+                path: format!("{}{}/", parent_sheet.path, sheet.name),
+                uuid_path: format!("{}/{}", parent_sheet.uuid_path, sheet.uuid.as_deref().unwrap_or_default()),
+                name: sheet.name,
+                file,
+                parent: Some(parent),
+                children: Vec::new(),
+                schematic,
+            };
+            self.sheets.push(instance);
+            let index = self.sheets.len() - 1;
+            self.sheets[parent].children.push(index);
+            self.add_children(index, read)?;
+        }
+        Ok(())
+    }
+
+    pub fn root(&self) -> &SheetInstance {
+        &self.sheets[0]
+    }
+
+    /// Contents of a sheet instance's file
+    pub fn schematic(&self, sheet: &SheetInstance) -> &Schematic {
+        &self.files[sheet.schematic].schematic
+    }
+
+    /// Nets of the whole design, sorted by name
+    ///
+    /// Pins are listed under the reference annotated for their sheet instance;
+    /// see [`connectivity`](super::connectivity) for how sheets connect and how
+    /// nets are named.
+    pub fn nets(&self) -> Vec<SchematicNet> {
+        derive(&self.views())
+    }
+
+    /// Sheet instances annotated for net derivation
+    pub(crate) fn views(&self) -> Vec<SheetView<'_>> {
+        let mut views: Vec<SheetView> = self
+            .sheets
+            .iter()
+            .enumerate()
+            .map(|(index, sheet)| SheetView {
+                schematic: self.schematic(sheet),
+                path: sheet.path.clone(),
+                // Children were added in the order of the parent's sheet symbols
+                parent: sheet.parent.and_then(|p| Some((p, self.sheets[p].children.iter().position(|&c| c == index)?))),
+                symbols: Vec::new(),
+            })
+            .collect();
+        for symbol in self.symbols() {
+            views[symbol.sheet].symbols.push((symbol.symbol, symbol.reference, symbol.unit));
+        }
+        views
+    }
+
+    /// Every local, global and hierarchical label once per sheet instance,
+    /// sheets depth first and labels in file order
+    pub fn labels(&self) -> Vec<HierarchicalLabel<'_>> {
+        let mut labels = Vec::new();
+        for (index, sheet) in self.sheets.iter().enumerate() {
+            for label in &self.schematic(sheet).labels {
+                labels.push(HierarchicalLabel { sheet: index, sheet_path: sheet.path.clone(), label });
+            }
+        }
+        labels
+    }
+
+    /// Every placed symbol once per sheet instance, sheets depth first
+    ///
+    /// The reference and unit come from the symbol's instance data for the sheet
+    /// (KiCad 7+) or the root sheet's `symbol_instances` (KiCad 6), falling back
+    /// to the symbol's own Reference property.
+    pub fn symbols(&self) -> Vec<HierarchicalSymbol<'_>> {
+        let legacy = &self.files[0].schematic.symbol_instances;
+        let mut symbols = Vec::new();
+        for (index, sheet) in self.sheets.iter().enumerate() {
+            // KiCad 6 paths leave out the root uuid and end with the symbol's uuid
+            let legacy_path = sheet.uuid_path.splitn(3, '/').nth(2).map_or(String::new(), |p| format!("/{}", p));
+            for symbol in &self.schematic(sheet).symbols {
+                let instance: Option<&SymbolInstance> =
+                    symbol.instances.iter().find(|i| i.path == sheet.uuid_path).or_else(|| {
+                        let uuid = symbol.uuid.as_deref()?;
+                        legacy.iter().find(|i| i.path == format!("{}/{}", legacy_path, uuid))
+                    });
+                let mut properties = symbol.properties.clone();
+                let (reference, unit) = match instance {
+                    Some(instance) => {
+                        properties.insert("Reference".to_string(), instance.reference.clone());
+                        for (name, value) in [("Value", &instance.value), ("Footprint", &instance.footprint)] {
+                            if let Some(value) = value {
+                                properties.insert(name.to_string(), value.clone());
+                            }
+                        }
+                        (instance.reference.clone(), instance.unit)
+                    }
+                    None => (symbol.reference().to_string(), symbol.unit),
+                };
+                symbols.push(HierarchicalSymbol {
+                    sheet: index,
+                    sheet_path: sheet.path.clone(),
+                    symbol,
+                    reference,
+                    unit,
+                    properties,
+                    instance,
+                });
+            }
+        }
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROOT: &str = r#"(kicad_sch (uuid "r00t")
+  (symbol (lib_id "Device:C") (at 0 0 0) (uuid "c") (property "Reference" "C?") (property "Value" "100n"))
+  (sheet (at 0 0) (size 20 10) (uuid "s1") (property "Sheetname" "Power") (property "Sheetfile" "power/power.kicad_sch"))
+  (symbol_instances
+    (path "/c" (reference "C1") (unit 1) (value "100n") (footprint "Capacitor_SMD:C_0603"))
+    (path "/s1/u" (reference "U1") (unit 2) (value "LM358") (footprint ""))
+    (path "/s1/s2/r" (reference "R5") (unit 1) (value "4k7") (footprint ""))))"#;
+    const POWER: &str = r#"(kicad_sch (uuid "p")
+  (symbol (lib_id "Amplifier_Operational:LM358") (at 0 0 0) (uuid "u") (property "Reference" "U?"))
+  (sheet (at 0 0) (size 20 10) (uuid "s2") (property "Sheet name" "Divider") (property "Sheet file" "divider.kicad_sch")))"#;
+    const DIVIDER: &str = r#"(kicad_sch (uuid "d")
+  (symbol (lib_id "Device:R") (at 0 0 0) (uuid "r") (property "Reference" "R?") (property "Value" "10k")))"#;
+
+    fn files() -> HashMap<&'static str, &'static str> {
+        [("power/power.kicad_sch", POWER), ("power/divider.kicad_sch", DIVIDER)].into_iter().collect()
+    }
+
+    #[test]
+    fn test_tree_and_legacy_instances() {
+        let mut reads = Vec::new();
+        let hierarchy = Hierarchy::build("top.kicad_sch", ROOT, |file| {
+            reads.push(file.to_string());
+            files().get(file).map(|c| c.to_string()).ok_or_else(|| KicadError::MissingField(file.to_string()))
+        })
+        .unwrap();
+        assert_eq!(reads, ["power/power.kicad_sch", "power/divider.kicad_sch"]);
+
+        let paths: Vec<(&str, &str, Option<usize>)> =
+            hierarchy.sheets.iter().map(|s| (s.path.as_str(), s.uuid_path.as_str(), s.parent)).collect();
+        assert_eq!(paths, [("/", "/r00t", None), ("/Power/", "/r00t/s1", Some(0)), ("/Power/Divider/", "/r00t/s1/s2", Some(1))]);
+        assert_eq!(hierarchy.root().children, [1]);
+
+        let symbols = hierarchy.symbols();
+        let found: Vec<(String, u32, Option<&str>)> =
+            symbols.iter().map(|s| (s.qualified_reference(), s.unit, s.property("Value"))).collect();
+        assert_eq!(
+            found,
+            [
+                ("/C1".to_string(), 1, Some("100n")),
+                ("/Power/U1".to_string(), 2, Some("LM358")),
+                ("/Power/Divider/R5".to_string(), 1, Some("4k7")),
+            ]
+        );
+        assert_eq!(symbols[0].property("Footprint"), Some("Capacitor_SMD:C_0603"));
+    }
+
+    #[test]
+    fn test_recursive_sheet_is_an_error() {
+        let looped = r#"(kicad_sch (uuid "l")
+  (sheet (at 0 0) (size 20 10) (uuid "s") (property "Sheetname" "Again") (property "Sheetfile" "loop.kicad_sch")))"#;
+        let result = Hierarchy::build("loop.kicad_sch", looped, |_| Ok(looped.to_string()));
+        assert!(matches!(result, Err(KicadError::InvalidFormat(m)) if m.contains("Again")));
+    }
+
+    #[test]
+    fn test_labels_per_sheet_instance() {
+        let root = r#"(kicad_sch (uuid "r")
+  (global_label "SDA" (shape bidirectional) (at 10 10 0))
+  (sheet (at 0 0) (size 20 10) (uuid "a") (property "Sheetname" "A") (property "Sheetfile" "ch.kicad_sch"))
+  (sheet (at 0 20) (size 20 10) (uuid "b") (property "Sheetname" "B") (property "Sheetfile" "ch.kicad_sch")))"#;
+        let channel = r#"(kicad_sch (uuid "c")
+  (label "mid_point" (at 5 5 0)) (hierarchical_label "OUT" (shape output) (at 0 5 180)))"#;
+        let hierarchy = Hierarchy::build("top.kicad_sch", root, |_| Ok(channel.to_string())).unwrap();
+
+        let labels: Vec<(&str, String, Option<&str>)> = hierarchy
+            .labels()
+            .iter()
+            .map(|l| (l.label.kind.name(), l.net_name(), l.label.shape.as_deref()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("global", "SDA".to_string(), Some("bidirectional")),
+                ("local", "/A/mid_point".to_string(), None),
+                ("hierarchical", "/A/OUT".to_string(), Some("output")),
+                ("local", "/B/mid_point".to_string(), None),
+                ("hierarchical", "/B/OUT".to_string(), Some("output")),
+            ]
+        );
+    }
+}
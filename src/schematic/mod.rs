@@ -0,0 +1,38 @@
+//! Schematic parsing module for KiCad .kicad_sch files
+//!
+//! [`parse_schematic`] reads one sheet into a [`Schematic`]: the embedded
+//! library symbols, placed symbols with their properties and instances, wires,
+//! junctions, labels and hierarchical sheets. [`Schematic::nets`] derives the
+//! sheet's nets from that, [`Schematic::erc`] checks them, and [`Hierarchy`]
+//! loads the sheets of a multi-sheet design.
+//!
+//! ## Usage Example
+//!
+//! ```rust
+//! use kiparse::schematic::parse_schematic;
+//!
+//! let schematic = parse_schematic(r#"(kicad_sch (version 20231120)
+//!   (symbol (lib_id "Device:R") (at 100 50 0) (unit 1)
+//!     (property "Reference" "R1") (property "Value" "10k"))
+//! )"#)?;
+//!
+//! for symbol in &schematic.symbols {
+//!     println!("{} {}", symbol.reference(), symbol.property("Value").unwrap_or(""));
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+pub mod types;
+pub mod parser;
+pub mod connectivity;
+pub mod bus;
+pub mod hierarchy;
+pub mod erc;
+
+// Re-export commonly used items
+pub use types::*;
+pub use parser::parse_schematic;
+pub use connectivity::{NetNode, SchematicNet};
+pub use bus::bus_members;
+pub use hierarchy::{HierarchicalLabel, HierarchicalSymbol, Hierarchy, SheetInstance};
+pub use erc::{run_erc, ErcCheck, ErcViolation};
@@ -0,0 +1,236 @@
+//! `.kicad_sch` parser
+//!
+//! Reads one sheet file into a [`Schematic`] with the S-expression reader. The
+//! embedded `lib_symbols` are parsed into the same rich [`Symbol`](crate::pcb::types::Symbol)
+//! model as symbol libraries, so placed symbols can be resolved to their pins.
+
+use std::collections::HashMap;
+
+use crate::error::{KicadError, Result};
+use crate::pcb::types::Point;
+use crate::sexpr::{parse_sexpr, SExpr};
+use crate::symbol::lib_parser::parse_lib_symbol;
+
+use super::types::{
+    BusAlias, BusEntry, InstanceVariant, Label, LabelKind, Schematic, SchematicSymbol, Sheet, SheetPin,
+    SymbolInstance, Wire,
+};
+
+/// Parse a `.kicad_sch` sheet
+pub fn parse_schematic(content: &str) -> Result<Schematic> {
+    let root = parse_sexpr(content)?;
+    if !root.is("kicad_sch") {
+        return Err(KicadError::InvalidFormat("Expected a kicad_sch file".to_string()));
+    }
+
+    let mut schematic = Schematic {
+        version: root.child_str("version").unwrap_or("unknown").to_string(),
+        generator: root.child_str("generator").unwrap_or("unknown").to_string(),
+        uuid: uuid(&root),
+        ..Schematic::default()
+    };
+    if let Some(lib_symbols) = root.child("lib_symbols") {
+        schematic.lib_symbols = lib_symbols.children("symbol").map(parse_lib_symbol).collect();
+    }
+
+    for item in root.lists() {
+        match item.name() {
+            Some("symbol") => schematic.symbols.push(parse_symbol(item)),
+            Some("wire") => {
+                let pts = item.pts();
+                if let [start, end, ..] = pts.as_slice() {
+                    schematic.wires.push(Wire {
+                        start: point(*start),
+                        end: point(*end),
+                        uuid: uuid(item),
+                    });
+                }
+            }
+            Some("bus") => {
+                let pts = item.pts();
+                if let [start, end, ..] = pts.as_slice() {
+                    schematic.buses.push(Wire {
+                        start: point(*start),
+                        end: point(*end),
+                        uuid: uuid(item),
+                    });
+                }
+            }
+            Some("bus_entry") => schematic.bus_entries.push(BusEntry {
+                at: at(item).0,
+                size: point(item.child_xy("size").unwrap_or((2.54, 2.54))),
+                uuid: uuid(item),
+            }),
+            Some("bus_alias") => schematic.bus_aliases.push(BusAlias {
+                name: item.arg_str(0).unwrap_or_default().to_string(),
+                members: item
+                    .child("members")
+                    .map(|m| m.args().iter().filter_map(|a| a.as_str()).map(str::to_string).collect())
+                    .unwrap_or_default(),
+            }),
+            Some("junction") => schematic.junctions.push(at(item).0),
+            Some("no_connect") => schematic.no_connects.push(at(item).0),
+            Some(name @ ("label" | "global_label" | "hierarchical_label")) => {
+                let (at, rotation) = at(item);
+                schematic.labels.push(Label {
+                    kind: match name {
+                        "global_label" => LabelKind::Global,
+                        "hierarchical_label" => LabelKind::Hierarchical,
+                        _ => LabelKind::Local,
+                    },
+                    text: item.arg_str(0).unwrap_or_default().to_string(),
+                    at,
+                    rotation,
+                    shape: item.child_str("shape").map(str::to_string),
+                    uuid: uuid(item),
+                });
+            }
+            Some("sheet") => schematic.sheets.push(parse_sheet(item)),
+            Some("symbol_instances") => {
+                schematic.symbol_instances = item.children("path").map(|path| parse_instance("", path)).collect()
+            }
+            _ => {}
+        }
+    }
+    Ok(schematic)
+}
+
+fn parse_symbol(node: &SExpr) -> SchematicSymbol {
+    let (at, rotation) = at(node);
+    let properties: HashMap<String, String> = node
+        .children("property")
+        .filter_map(|p| Some((p.arg_str(0)?.to_string(), p.arg_str(1)?.to_string())))
+        .collect();
+    let instances = node
+        .children("instances")
+        .flat_map(|i| i.children("project"))
+        .flat_map(|project| {
+            let name = project.arg_str(0).unwrap_or_default();
+            project.children("path").map(move |path| parse_instance(name, path))
+        })
+        .collect();
+    SchematicSymbol {
+        lib_id: node.child_str("lib_id").unwrap_or_default().to_string(),
+        lib_name: node.child_str("lib_name").map(str::to_string),
+        at,
+        rotation,
+        mirror: node.child_str("mirror").map(str::to_string),
+        unit: node.child_f64("unit").map_or(1, |u| u as u32),
+        body_style: node.child_f64("convert").or_else(|| node.child_f64("body_style")).map_or(1, |c| c as u32),
+        in_bom: node.child_bool("in_bom").unwrap_or(true),
+        on_board: node.child_bool("on_board").unwrap_or(true),
+        exclude_from_sim: node.child_bool("exclude_from_sim").unwrap_or(false),
+        dnp: node.child_bool("dnp").unwrap_or(false),
+        uuid: uuid(node),
+        properties,
+        instances,
+    }
+}
+
+/// `(path "/..." (reference "R1") (unit 1) ...)` of a symbol's instances
+fn parse_instance(project: &str, path: &SExpr) -> SymbolInstance {
+    SymbolInstance {
+        project: project.to_string(),
+        path: path.arg_str(0).unwrap_or_default().to_string(),
+        reference: path.child_str("reference").unwrap_or_default().to_string(),
+        unit: path.child_f64("unit").map_or(1, |u| u as u32),
+        value: path.child_str("value").map(str::to_string),
+        footprint: path.child_str("footprint").map(str::to_string),
+        variants: path.children("variant").map(parse_variant).collect(),
+    }
+}
+
+/// `(variant (name "Lite") (dnp yes) (field (name "Value") (value "0R")))` of an instance
+fn parse_variant(node: &SExpr) -> InstanceVariant {
+    InstanceVariant {
+        name: node.child_str("name").unwrap_or_default().to_string(),
+        dnp: node.child_bool("dnp"),
+        in_bom: node.child_bool("in_bom"),
+        fields: node
+            .children("field")
+            .filter_map(|f| Some((f.child_str("name")?.to_string(), f.child_str("value")?.to_string())))
+            .collect(),
+    }
+}
+
+fn parse_sheet(node: &SExpr) -> Sheet {
+    let property = |names: &[&str]| {
+        node.children("property")
+            .find(|p| p.arg_str(0).map_or(false, |n| names.contains(&n)))
+            .and_then(|p| p.arg_str(1))
+            .unwrap_or_default()
+            .to_string()
+    };
+    Sheet {
+        // KiCad 6 used "Sheet name" / "Sheet file"
+        name: property(&["Sheetname", "Sheet name"]),
+        file: property(&["Sheetfile", "Sheet file"]),
+        at: at(node).0,
+        size: node.child_xy("size").map(point).unwrap_or_default(),
+        pins: node
+            .children("pin")
+            .map(|pin| SheetPin {
+                name: pin.arg_str(0).unwrap_or_default().to_string(),
+                shape: pin.arg_str(1).unwrap_or_default().to_string(),
+                at: at(pin).0,
+            })
+            .collect(),
+        uuid: uuid(node),
+    }
+}
+
+/// `(at x y [angle])`
+fn at(node: &SExpr) -> (Point, f64) {
+    let at = node.child("at");
+    let arg = |i| at.and_then(|a| a.arg_f64(i)).unwrap_or(0.0);
+    (Point::new(arg(0), arg(1)), arg(2))
+}
+
+fn point((x, y): (f64, f64)) -> Point {
+    Point::new(x, y)
+}
+
+fn uuid(node: &SExpr) -> Option<String> {
+    node.child_str("uuid").map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIVIDER: &str = include_str!("../../assets/divider.kicad_sch");
+
+    #[test]
+    fn test_parse_sheet_items() {
+        let schematic = parse_schematic(DIVIDER).unwrap();
+        assert_eq!(schematic.version, "20231120");
+        assert_eq!(schematic.lib_symbols.len(), 3);
+        assert_eq!(schematic.symbols.len(), 4);
+        assert_eq!(schematic.wires.len(), 7);
+        assert_eq!(schematic.labels.len(), 2);
+        assert_eq!(schematic.labels[1].kind, LabelKind::Global);
+
+        let r1 = schematic.symbol_by_ref("R1").unwrap();
+        assert_eq!(r1.property("Value"), Some("10k"));
+        assert_eq!(r1.instances[0].reference, "R1");
+        let mut pins: Vec<_> = schematic
+            .symbol_pins(r1)
+            .into_iter()
+            .map(|(pin, at)| (pin.number.clone(), at))
+            .collect();
+        pins.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(pins[0], ("1".to_string(), Point::new(100.0, 46.19)));
+        assert_eq!(pins[1], ("2".to_string(), Point::new(100.0, 53.81)));
+    }
+
+    #[test]
+    fn test_rotated_and_mirrored_symbols() {
+        let mut symbol = parse_symbol(&parse_sexpr(r#"(symbol (lib_id "Device:R") (at 10 20 90) (unit 1))"#).unwrap());
+        let pin = Point::new(0.0, 3.81);
+        assert_eq!(symbol.to_sheet(&pin), Point::new(6.19, 20.0));
+        symbol.rotation = 0.0;
+        symbol.mirror = Some("x".to_string());
+        assert_eq!(symbol.to_sheet(&pin), Point::new(10.0, 23.81));
+        assert!(parse_schematic("(kicad_pcb)").is_err());
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use crate::pcb::types::{Pin, Point, Symbol};
+
+/// One `.kicad_sch` sheet file
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schematic {
+    pub version: String,
+    pub generator: String,
+    pub uuid: Option<String>,
+    /// Library symbols embedded in the sheet, which placed symbols refer to
+    pub lib_symbols: Vec<Symbol>,
+    pub symbols: Vec<SchematicSymbol>,
+    pub wires: Vec<Wire>,
+    pub junctions: Vec<Point>,
+    pub no_connects: Vec<Point>,
+    pub labels: Vec<Label>,
+    pub sheets: Vec<Sheet>,
+    /// Annotations of every symbol in the design, kept in the root sheet by
+    /// KiCad 6; each path ends with the symbol's uuid
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub symbol_instances: Vec<SymbolInstance>,
+    /// Bus segments, which carry the members their labels name
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub buses: Vec<Wire>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bus_entries: Vec<BusEntry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bus_aliases: Vec<BusAlias>,
+}
+
+/// A symbol placed on the sheet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchematicSymbol {
+    /// Library identifier, e.g. `Device:R`
+    pub lib_id: String,
+    /// Name of the embedded library symbol when it differs from `lib_id`
+    pub lib_name: Option<String>,
+    pub at: Point,
+    pub rotation: f64,
+    /// `x` (mirrored about the X axis) or `y` (about the Y axis)
+    pub mirror: Option<String>,
+    pub unit: u32,
+    /// 1 for the normal body, 2 for the De Morgan alternate
+    pub body_style: u32,
+    pub in_bom: bool,
+    pub on_board: bool,
+    pub exclude_from_sim: bool,
+    pub dnp: bool,
+    pub uuid: Option<String>,
+    pub properties: HashMap<String, String>,
+    /// Annotations per sheet instance (KiCad 7+)
+    pub instances: Vec<SymbolInstance>,
+}
+
+/// Reference and unit of a symbol in one instance of its sheet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolInstance {
+    pub project: String,
+    /// Sheet uuids from the root, e.g. `/` followed by the root uuid
+    pub path: String,
+    pub reference: String,
+    pub unit: u32,
+    /// Per-instance value, only written by KiCad 6
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub value: Option<String>,
+    /// Per-instance footprint, only written by KiCad 6
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub footprint: Option<String>,
+    /// Overrides of this instance in named design variants (KiCad 10)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub variants: Vec<InstanceVariant>,
+}
+
+impl SymbolInstance {
+    /// Overrides of the named design variant, if the instance has any
+    pub fn variant(&self, name: &str) -> Option<&InstanceVariant> {
+        self.variants.iter().find(|v| v.name == name)
+    }
+}
+
+/// How a symbol instance differs in one design variant
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstanceVariant {
+    pub name: String,
+    /// Do-not-populate flag replacing the symbol's in this variant
+    pub dnp: Option<bool>,
+    /// Exclusion from the BOM replacing the symbol's in this variant
+    pub in_bom: Option<bool>,
+    /// Field values replacing the symbol's properties, e.g. another `Value`
+    pub fields: HashMap<String, String>,
+}
+
+/// Wire segment
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Wire {
+    pub start: Point,
+    pub end: Point,
+    pub uuid: Option<String>,
+}
+
+/// Diagonal stub from a bus to a wire
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusEntry {
+    pub at: Point,
+    /// Offset of the other end from `at`, usually 2.54 in each direction
+    pub size: Point,
+    pub uuid: Option<String>,
+}
+
+impl BusEntry {
+    pub fn end(&self) -> Point {
+        Point { x: self.at.x + self.size.x, y: self.at.y + self.size.y }
+    }
+}
+
+/// Name standing for a group of bus members, e.g. `I2C` for `SDA SCL`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusAlias {
+    pub name: String,
+    /// Net names or vector buses such as `D[0..7]`
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LabelKind {
+    /// Connects within the sheet
+    Local,
+    /// Connects across every sheet
+    Global,
+    /// Connects to the matching pin of the sheet symbol in the parent sheet
+    Hierarchical,
+}
+
+impl LabelKind {
+    /// Name used in reports: `local`, `global` or `hierarchical`
+    pub fn name(self) -> &'static str {
+        match self {
+            LabelKind::Local => "local",
+            LabelKind::Global => "global",
+            LabelKind::Hierarchical => "hierarchical",
+        }
+    }
+}
+
+/// Net label
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Label {
+    pub kind: LabelKind,
+    pub text: String,
+    pub at: Point,
+    pub rotation: f64,
+    /// Signal direction of global and hierarchical labels, e.g. `input`
+    pub shape: Option<String>,
+    pub uuid: Option<String>,
+}
+
+/// Hierarchical sheet symbol referring to a child sheet file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sheet {
+    pub name: String,
+    /// Path of the child sheet relative to this one
+    pub file: String,
+    pub at: Point,
+    pub size: Point,
+    pub pins: Vec<SheetPin>,
+    pub uuid: Option<String>,
+}
+
+/// Sheet entry matching a hierarchical label in the child sheet
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SheetPin {
+    pub name: String,
+    pub shape: String,
+    pub at: Point,
+}
+
+impl Schematic {
+    /// Library symbol a placed symbol was instantiated from
+    pub fn lib_symbol(&self, symbol: &SchematicSymbol) -> Option<&Symbol> {
+        let name = symbol.lib_name.as_deref().unwrap_or(&symbol.lib_id);
+        self.lib_symbols.iter().find(|s| s.name == name)
+    }
+
+    /// Pins of a placed symbol with their connection points in sheet coordinates
+    ///
+    /// Only the pins of the placed unit and body style (and those common to all)
+    /// are returned.
+    pub fn symbol_pins<'a>(&'a self, symbol: &SchematicSymbol) -> Vec<(&'a Pin, Point)> {
+        self.symbol_unit_pins(symbol, symbol.unit)
+    }
+
+    /// [`symbol_pins`](Self::symbol_pins) of the given unit, for sheet instances
+    /// annotated with a unit of their own
+    pub fn symbol_unit_pins<'a>(&'a self, symbol: &SchematicSymbol, unit: u32) -> Vec<(&'a Pin, Point)> {
+        let Some(lib) = self.lib_symbol(symbol) else {
+            return Vec::new();
+        };
+        lib.pins
+            .iter()
+            .filter(|p| p.unit == 0 || p.unit == unit)
+            .filter(|p| p.body_style == 0 || p.body_style == symbol.body_style)
+            .map(|p| (p, symbol.to_sheet(&p.at)))
+            .collect()
+    }
+
+    /// Placed symbol with the given reference
+    pub fn symbol_by_ref(&self, reference: &str) -> Option<&SchematicSymbol> {
+        self.symbols.iter().find(|s| s.reference() == reference)
+    }
+}
+
+impl SchematicSymbol {
+    /// Value of the named property
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    /// Reference designator, e.g. `R1` (`#PWR01` for power symbols)
+    pub fn reference(&self) -> &str {
+        self.property("Reference").unwrap_or_default()
+    }
+
+    /// Convert a point of the library symbol (y up) to sheet coordinates (y down)
+    ///
+    /// The mirror applies in library space, then the rotation, then the offset.
+    pub fn to_sheet(&self, local: &Point) -> Point {
+        let (mut x, mut y) = (local.x.as_mm(), local.y.as_mm());
+        match self.mirror.as_deref() {
+            Some("x") => y = -y,
+            Some("y") => x = -x,
+            _ => {}
+        }
+        let y = -y;
+        let (sin, cos) = self.rotation.to_radians().sin_cos();
+        let round = |v: f64| (v * 1e4).round() / 1e4;
+        Point::new(
+            round(self.at.x.as_mm() + x * cos + y * sin),
+            round(self.at.y.as_mm() - x * sin + y * cos),
+        )
+    }
+}
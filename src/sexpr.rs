@@ -0,0 +1,477 @@
+//! Minimal S-expression reader for KiCad files
+//!
+//! KiCad stores boards, schematics and libraries as S-expressions. This module
+//! turns that text into a small tree of [`SExpr`] nodes using plain Rust string
+//! handling (no lexer or parser library), which the typed parsers then walk.
+//! Trees built with [`SExpr::list`] and friends are written back out with
+//! [`SExpr::to_kicad_string`].
+//!
+//! ```rust
+//! use kiparse::sexpr::parse_sexpr;
+//!
+//! let tree = parse_sexpr(r#"(segment (start 1 2) (end 3 4) (layer "F.Cu"))"#)?;
+//! assert_eq!(tree.name(), Some("segment"));
+//! assert_eq!(tree.child_str("layer"), Some("F.Cu"));
+//! assert_eq!(tree.child("end").and_then(|e| e.arg_f64(1)), Some(4.0));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::error::{KicadError, Result};
+
+/// A node of a parsed S-expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum SExpr {
+    /// Bare token such as `segment`, `yes` or `0.25`
+    Atom(String),
+    /// Quoted string with escapes resolved
+    Str(String),
+    /// Parenthesized list; the first item is usually the element name
+    List(Vec<SExpr>),
+}
+
+impl SExpr {
+    /// Bare token
+    pub fn atom(value: impl Into<String>) -> SExpr {
+        SExpr::Atom(value.into())
+    }
+
+    /// Quoted string
+    pub fn string(value: impl Into<String>) -> SExpr {
+        SExpr::Str(value.into())
+    }
+
+    /// Number written the way KiCad does: at most six decimals, no trailing zeros
+    pub fn number(value: f64) -> SExpr {
+        let text = format!("{:.6}", value);
+        let text = text.trim_end_matches('0').trim_end_matches('.');
+        SExpr::Atom(if text == "-0" { "0".to_string() } else { text.to_string() })
+    }
+
+    /// `(yes)`/`(no)` style boolean atom
+    pub fn yes_no(value: bool) -> SExpr {
+        SExpr::atom(if value { "yes" } else { "no" })
+    }
+
+    /// List named `name` followed by `args`
+    pub fn list(name: &str, args: Vec<SExpr>) -> SExpr {
+        let mut items = Vec::with_capacity(args.len() + 1);
+        items.push(SExpr::atom(name));
+        items.extend(args);
+        SExpr::List(items)
+    }
+
+    /// Element name (the leading atom) of a list, e.g. `"footprint"`
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            SExpr::List(items) => match items.first() {
+                Some(SExpr::Atom(name)) => Some(name),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether this node is a list named `name`
+    pub fn is(&self, name: &str) -> bool {
+        self.name() == Some(name)
+    }
+
+    /// All items of a list including the name, or an empty slice for atoms
+    pub fn items(&self) -> &[SExpr] {
+        match self {
+            SExpr::List(items) => items,
+            _ => &[],
+        }
+    }
+
+    /// Items following the element name
+    pub fn args(&self) -> &[SExpr] {
+        match self {
+            SExpr::List(items) if !items.is_empty() => &items[1..],
+            _ => &[],
+        }
+    }
+
+    /// Text of an atom or string node
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SExpr::Atom(s) | SExpr::Str(s) => Some(s),
+            SExpr::List(_) => None,
+        }
+    }
+
+    /// Numeric value of an atom or string node
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_str().and_then(|s| s.parse().ok())
+    }
+
+    /// Argument `index` (0 is the first item after the name) as text
+    pub fn arg_str(&self, index: usize) -> Option<&str> {
+        self.args().get(index).and_then(SExpr::as_str)
+    }
+
+    /// Argument `index` (0 is the first item after the name) as a number
+    pub fn arg_f64(&self, index: usize) -> Option<f64> {
+        self.args().get(index).and_then(SExpr::as_f64)
+    }
+
+    /// First child list named `name`
+    pub fn child(&self, name: &str) -> Option<&SExpr> {
+        self.args().iter().find(|c| c.is(name))
+    }
+
+    /// All child lists named `name`
+    pub fn children<'s>(&'s self, name: &'s str) -> impl Iterator<Item = &'s SExpr> + 's {
+        self.args().iter().filter(move |c| c.is(name))
+    }
+
+    /// All child lists, in file order
+    pub fn lists(&self) -> impl Iterator<Item = &SExpr> {
+        self.args().iter().filter(|c| matches!(c, SExpr::List(_)))
+    }
+
+    /// First argument of child `name` as text, e.g. `(layer "F.Cu")` -> `"F.Cu"`
+    pub fn child_str(&self, name: &str) -> Option<&str> {
+        self.child(name).and_then(|c| c.arg_str(0))
+    }
+
+    /// First argument of child `name` as a number, e.g. `(width 0.25)` -> `0.25`
+    pub fn child_f64(&self, name: &str) -> Option<f64> {
+        self.child(name).and_then(|c| c.arg_f64(0))
+    }
+
+    /// Boolean child in either `(name)`, `(name yes)` or `(name no)` form
+    pub fn child_bool(&self, name: &str) -> Option<bool> {
+        self.child(name).map(|c| match c.arg_str(0) {
+            Some(v) => v == "yes" || v == "true",
+            None => true,
+        })
+    }
+
+    /// Whether a bare atom `flag` appears among the arguments, e.g. `locked`
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.args()
+            .iter()
+            .any(|a| matches!(a, SExpr::Atom(s) if s == flag))
+    }
+
+    /// Whether `flag` is set either as a bare atom or as a boolean child
+    pub fn flag(&self, flag: &str) -> bool {
+        self.has_flag(flag) || self.child_bool(flag).unwrap_or(false)
+    }
+
+    /// Two numeric arguments of child `name`, e.g. `(at 10 20)` -> `(10, 20)`
+    pub fn child_xy(&self, name: &str) -> Option<(f64, f64)> {
+        let c = self.child(name)?;
+        Some((c.arg_f64(0)?, c.arg_f64(1)?))
+    }
+
+    /// Points of a `(pts (xy x y) ...)` child
+    pub fn pts(&self) -> Vec<(f64, f64)> {
+        self.child("pts")
+            .map(|pts| {
+                pts.children("xy")
+                    .filter_map(|xy| Some((xy.arg_f64(0)?, xy.arg_f64(1)?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl SExpr {
+    /// Text in KiCad's file layout
+    ///
+    /// Lists holding only atoms and strings stay on one line; other lists put each
+    /// child list on its own line, indented with a tab per level, as KiCad 8 does.
+    pub fn to_kicad_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write(&self, out: &mut String, depth: usize) {
+        match self {
+            SExpr::Atom(atom) => out.push_str(atom),
+            SExpr::Str(text) => {
+                out.push('"');
+                for c in text.chars() {
+                    match c {
+                        '"' => out.push_str("\\\""),
+                        '\\' => out.push_str("\\\\"),
+                        '\n' => out.push_str("\\n"),
+                        c => out.push(c),
+                    }
+                }
+                out.push('"');
+            }
+            SExpr::List(items) => {
+                out.push('(');
+                let nested = items.iter().any(|i| matches!(i, SExpr::List(_)));
+                for (i, item) in items.iter().enumerate() {
+                    if nested && matches!(item, SExpr::List(_)) {
+                        out.push('\n');
+                        out.push_str(&"\t".repeat(depth + 1));
+                    } else if i > 0 {
+                        out.push(' ');
+                    }
+                    item.write(out, depth + 1);
+                }
+                if nested {
+                    out.push('\n');
+                    out.push_str(&"\t".repeat(depth));
+                }
+                out.push(')');
+            }
+        }
+    }
+}
+
+/// Parse the first S-expression in `content`
+///
+/// Leading whitespace is skipped; anything after the first complete list is ignored.
+pub fn parse_sexpr(content: &str) -> Result<SExpr> {
+    let mut reader = Reader::new(content);
+    reader.skip_whitespace();
+    if reader.peek().is_none() {
+        return Err(KicadError::ParseError("Empty input".to_string()));
+    }
+    reader.read()
+}
+
+/// Parse the first S-expression in `content`, calling `on_child` with the byte
+/// range of each list directly inside it; an error from `on_child` stops the read
+///
+/// Lists nested deeper than `max_depth` (the outermost list being depth 1) are
+/// rejected.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub(crate) fn parse_sexpr_tracked(
+    content: &str,
+    max_depth: usize,
+    on_child: &mut dyn FnMut(usize, usize) -> Result<()>,
+) -> Result<SExpr> {
+    let mut reader = Reader::new(content);
+    reader.max_depth = max_depth;
+    reader.on_child = Some(on_child);
+    reader.skip_whitespace();
+    if reader.peek().is_none() {
+        return Err(KicadError::ParseError("Empty input".to_string()));
+    }
+    reader.read()
+}
+
+/// Parse every top-level S-expression in `content`
+pub fn parse_sexpr_all(content: &str) -> Result<Vec<SExpr>> {
+    let mut reader = Reader::new(content);
+    let mut nodes = Vec::new();
+    loop {
+        reader.skip_whitespace();
+        if reader.peek().is_none() {
+            return Ok(nodes);
+        }
+        nodes.push(reader.read()?);
+    }
+}
+
+/// Byte-level cursor over the input that tracks the current line for errors
+struct Reader<'a> {
+    bytes: &'a [u8],
+    text: &'a str,
+    pos: usize,
+    line: usize,
+    max_depth: usize,
+    /// Start of the list currently open directly inside the root
+    child_start: usize,
+    /// Called with the byte range of each list closed directly inside the root
+    on_child: Option<&'a mut dyn FnMut(usize, usize) -> Result<()>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            bytes: text.as_bytes(),
+            text,
+            pos: 0,
+            line: 1,
+            max_depth: usize::MAX,
+            child_start: 0,
+            on_child: None,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn error(&self, msg: &str) -> KicadError {
+        KicadError::ParseError(format!("line {}: {}", self.line, msg))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b) = self.peek() {
+            if b == b'\n' {
+                self.line += 1;
+            } else if !b.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn read(&mut self) -> Result<SExpr> {
+        // Explicit stack so deeply nested input cannot overflow the call stack
+        // Each open list remembers its starting line for unbalanced input errors
+        let mut stack: Vec<(usize, Vec<SExpr>)> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let node = match self.peek() {
+                None => {
+                    let opened = stack.last().map_or(self.line, |(line, _)| *line);
+                    return Err(self.error(&format!(
+                        "unexpected end of input, unbalanced parenthesis (list opened at line {})",
+                        opened
+                    )));
+                }
+                Some(b'(') => {
+                    if stack.len() == self.max_depth {
+                        return Err(self.error(&format!("lists nested deeper than {}", self.max_depth)));
+                    }
+                    if stack.len() == 1 {
+                        self.child_start = self.pos;
+                    }
+                    self.pos += 1;
+                    stack.push((self.line, Vec::new()));
+                    continue;
+                }
+                Some(b')') => {
+                    self.pos += 1;
+                    match stack.pop() {
+                        Some((_, items)) => {
+                            if let (1, Some(on_child)) = (stack.len(), self.on_child.as_mut()) {
+                                on_child(self.child_start, self.pos)?;
+                            }
+                            SExpr::List(items)
+                        }
+                        None => return Err(self.error("unexpected ')'")),
+                    }
+                }
+                Some(b'"') => self.read_string()?,
+                Some(_) => self.read_atom(),
+            };
+            match stack.last_mut() {
+                Some((_, parent)) => parent.push(node),
+                None => return Ok(node),
+            }
+        }
+    }
+
+    fn read_string(&mut self) -> Result<SExpr> {
+        self.pos += 1;
+        let mut value = String::new();
+        let mut start = self.pos;
+        while let Some(b) = self.peek() {
+            match b {
+                b'"' => {
+                    value.push_str(&self.text[start..self.pos]);
+                    self.pos += 1;
+                    return Ok(SExpr::Str(value));
+                }
+                b'\\' => {
+                    value.push_str(&self.text[start..self.pos]);
+                    let escaped = match self.text[self.pos + 1..].chars().next() {
+                        Some(ch) => ch,
+                        None => break,
+                    };
+                    match escaped {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        '\\' | '"' => value.push(escaped),
+                        other => {
+                            value.push('\\');
+                            value.push(other);
+                        }
+                    }
+                    self.pos += 1 + escaped.len_utf8();
+                    start = self.pos;
+                }
+                b'\n' => {
+                    self.line += 1;
+                    self.pos += 1;
+                }
+                _ => self.pos += 1,
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    fn read_atom(&mut self) -> SExpr {
+        let start = self.pos;
+        while let Some(b) = self.peek() {
+            if b.is_ascii_whitespace() || b == b'(' || b == b')' || b == b'"' {
+                break;
+            }
+            self.pos += 1;
+        }
+        SExpr::Atom(self.text[start..self.pos].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nested() {
+        let tree = parse_sexpr(r#"(kicad_pcb (version 20240108) (layers (0 "F.Cu" signal)))"#).unwrap();
+        assert_eq!(tree.name(), Some("kicad_pcb"));
+        assert_eq!(tree.child_f64("version"), Some(20240108.0));
+        let layer = &tree.child("layers").unwrap().args()[0];
+        assert_eq!(layer.items()[0].as_str(), Some("0"));
+        assert_eq!(layer.items()[1], SExpr::Str("F.Cu".to_string()));
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        let tree = parse_sexpr(r#"(gr_text "a \"b\"\nc")"#).unwrap();
+        assert_eq!(tree.arg_str(0), Some("a \"b\"\nc"));
+        let tree = parse_sexpr("(gr_text \"a\\é b\")").unwrap();
+        assert_eq!(tree.arg_str(0), Some("a\\é b"));
+    }
+
+    #[test]
+    fn test_flags_and_bools() {
+        let tree = parse_sexpr("(footprint locked (placed yes) (hide no) (unlocked))").unwrap();
+        assert!(tree.has_flag("locked"));
+        assert_eq!(tree.child_bool("placed"), Some(true));
+        assert_eq!(tree.child_bool("hide"), Some(false));
+        assert!(tree.flag("unlocked"));
+        assert!(!tree.flag("dnp"));
+    }
+
+    #[test]
+    fn test_write_round_trip() {
+        let tree = SExpr::list(
+            "property",
+            vec![
+                SExpr::string("Value"),
+                SExpr::string("say \"hi\"\\n"),
+                SExpr::list("at", vec![SExpr::number(1.27), SExpr::number(-0.0000001), SExpr::number(90.0)]),
+                SExpr::list("hide", vec![SExpr::yes_no(true)]),
+            ],
+        );
+        let text = tree.to_kicad_string();
+        assert_eq!(text, "(property \"Value\" \"say \\\"hi\\\"\\\\n\"\n\t(at 1.27 0 90)\n\t(hide yes)\n)\n");
+        assert_eq!(parse_sexpr(&text).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_unbalanced_reports_line() {
+        let err = parse_sexpr("(a\n(b 1)\n").unwrap_err();
+        assert!(err.to_string().contains("line 3"));
+        assert!(err.to_string().contains("opened at line 1"));
+    }
+}
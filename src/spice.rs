@@ -0,0 +1,359 @@
+//! SPICE netlist export
+//!
+//! Turns a parsed schematic sheet into a SPICE netlist for ngspice or other
+//! simulators, without going through Eeschema. Nets come from
+//! [`Schematic::nets`]; the `GND` net becomes node `0`. Each simulated part
+//! becomes one element whose kind, pin order and value follow the same fields
+//! as KiCad's simulator:
+//!
+//! - `Sim.Device` (`R`, `C`, `L`, `V`, `I`, `D`, `NPN`, `PMOS`, `SUBCKT`, ...)
+//!   picks the element letter; without it the reference prefix is used
+//! - `Sim.Pins` (`1=+ 2=-`, `1=K 2=A`) maps symbol pins to model pins, which are
+//!   written in SPICE order (anode before cathode, collector-base-emitter, ...)
+//!   or, for subcircuits, in the order listed
+//! - `Sim.Params` supplies the value (`r=10k`, `dc=5`) and `Sim.Name` the model
+//!   or subcircuit name; otherwise the `Value` field is converted to SPICE
+//!   notation (`4k7` to `4.7k`, `1M` to `1Meg`, `100nF` to `100n`)
+//! - `Sim.Library` files are `.include`d
+//!
+//! The KiCad 5 fields `Spice_Primitive`, `Spice_Model`, `Spice_Node_Sequence`,
+//! `Spice_Lib_File` and `Spice_Netlist_Enabled` are honoured as well. Symbols
+//! with `(exclude_from_sim yes)` and power symbols are left out.
+//!
+//! ```rust
+//! use kiparse::{schematic::parse_schematic, spice::SpiceNetlist};
+//!
+//! let schematic = parse_schematic(include_str!("../assets/divider.kicad_sch"))?;
+//! let spice = SpiceNetlist::from_schematic(&schematic).to_spice();
+//!
+//! assert!(spice.contains("V1 VIN 0 dc 5\n"));
+//! assert!(spice.contains("R2 /OUT 0 4.7k\n"));
+//! assert!(spice.ends_with(".end\n"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::bom::natural_order;
+use crate::schematic::{Schematic, SchematicSymbol};
+
+/// Model pin order of the built-in SPICE devices
+const PIN_ORDER: &[(&str, &[&str])] = &[
+    ("D", &["A", "K"]),
+    ("Q", &["C", "B", "E", "S"]),
+    ("M", &["D", "G", "S", "B"]),
+    ("J", &["D", "G", "S"]),
+    ("R", &["+", "-"]),
+    ("C", &["+", "-"]),
+    ("L", &["+", "-"]),
+    ("V", &["+", "-"]),
+    ("I", &["+", "-"]),
+];
+
+/// One line of the netlist: `NAME NODE... VALUE`
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpiceElement {
+    /// Element name, the reference with the device letter prepended when it does
+    /// not already start with it (`U1` as a subcircuit becomes `XU1`)
+    pub name: String,
+    pub reference: String,
+    pub nodes: Vec<String>,
+    /// Value, model name or source specification
+    pub value: String,
+}
+
+/// SPICE netlist of a schematic
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpiceNetlist {
+    pub title: String,
+    /// Model libraries to `.include`
+    pub includes: Vec<String>,
+    pub elements: Vec<SpiceElement>,
+}
+
+impl SpiceNetlist {
+    /// Netlist of the simulated symbols of `schematic`
+    pub fn from_schematic(schematic: &Schematic) -> Self {
+        let mut pin_nets: HashMap<(&str, &str), String> = HashMap::new();
+        let nets = schematic.nets();
+        for net in &nets {
+            for node in &net.nodes {
+                pin_nets.insert((&node.reference, &node.pin), node_name(&net.name));
+            }
+        }
+
+        // The units of a multi-unit part make up one element
+        let mut parts: Vec<(&str, Vec<&SchematicSymbol>)> = Vec::new();
+        for symbol in &schematic.symbols {
+            let reference = symbol.reference();
+            if reference.starts_with('#') || symbol.exclude_from_sim {
+                continue;
+            }
+            match parts.iter_mut().find(|(r, _)| *r == reference) {
+                Some((_, units)) => units.push(symbol),
+                None => parts.push((reference, vec![symbol])),
+            }
+        }
+        parts.sort_by(|a, b| natural_order(a.0, b.0));
+
+        let mut netlist = SpiceNetlist {
+            title: "KiCad schematic".to_string(),
+            includes: Vec::new(),
+            elements: Vec::new(),
+        };
+        for (reference, units) in parts {
+            let field = |name: &str| {
+                units
+                    .iter()
+                    .find_map(|u| u.property(name))
+                    .filter(|v| !v.trim().is_empty())
+            };
+            if field("Spice_Netlist_Enabled").map_or(false, |v| v.eq_ignore_ascii_case("n")) {
+                continue;
+            }
+            let mut pins: Vec<&str> = Vec::new();
+            for unit in &units {
+                for (pin, _) in schematic.symbol_pins(unit) {
+                    if !pins.contains(&pin.number.as_str()) {
+                        pins.push(&pin.number);
+                    }
+                }
+            }
+            if pins.is_empty() {
+                continue;
+            }
+            pins.sort_by(|a, b| natural_order(a, b));
+
+            let device = field("Sim.Device")
+                .or_else(|| field("Spice_Primitive"))
+                .unwrap_or(reference)
+                .to_ascii_uppercase();
+            let letter = device_letter(&device);
+            let pins = match (field("Sim.Pins"), field("Spice_Node_Sequence")) {
+                (Some(map), _) => model_pin_order(map, letter),
+                (None, Some(sequence)) => sequence
+                    .split(|c: char| c.is_whitespace() || c == ',')
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                (None, None) => pins,
+            };
+            let nodes = pins
+                .iter()
+                .map(|pin| {
+                    pin_nets
+                        .get(&(reference, *pin))
+                        .cloned()
+                        .unwrap_or_else(|| format!("NC-{}-{}", reference, pin))
+                })
+                .collect();
+
+            let value = element_value(&device, letter, &field);
+            for library in [field("Sim.Library"), field("Spice_Lib_File")].into_iter().flatten() {
+                if !netlist.includes.iter().any(|l| l == library) {
+                    netlist.includes.push(library.to_string());
+                }
+            }
+            let starts_with_letter = reference
+                .chars()
+                .next()
+                .map_or(false, |c| c.to_ascii_uppercase() == letter);
+            netlist.elements.push(SpiceElement {
+                name: if starts_with_letter {
+                    reference.to_string()
+                } else {
+                    format!("{}{}", letter, reference)
+                },
+                reference: reference.to_string(),
+                nodes,
+                value,
+            });
+        }
+        netlist
+    }
+
+    /// The netlist as SPICE text
+    pub fn to_spice(&self) -> String {
+        let mut out = format!(".title {}\n", self.title);
+        for library in &self.includes {
+            out.push_str(&format!(".include \"{}\"\n", library));
+        }
+        for element in &self.elements {
+            out.push_str(&element.name);
+            for node in &element.nodes {
+                out.push(' ');
+                out.push_str(node);
+            }
+            if !element.value.is_empty() {
+                out.push(' ');
+                out.push_str(&element.value);
+            }
+            out.push('\n');
+        }
+        out.push_str(".end\n");
+        out
+    }
+}
+
+/// SPICE element letter for a `Sim.Device` or legacy primitive
+fn device_letter(device: &str) -> char {
+    match device {
+        "NPN" | "PNP" => 'Q',
+        "NMOS" | "PMOS" => 'M',
+        "NJFET" | "PJFET" => 'J',
+        "SUBCKT" => 'X',
+        _ => device.chars().next().filter(char::is_ascii_alphabetic).unwrap_or('X'),
+    }
+}
+
+/// Symbol pins of a `Sim.Pins` map in the order the model expects them
+fn model_pin_order(map: &str, letter: char) -> Vec<&str> {
+    let mut pairs: Vec<(&str, &str)> = map
+        .split_whitespace()
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    if let Some((_, order)) = PIN_ORDER.iter().find(|(l, _)| l.starts_with(letter)) {
+        let rank = |model: &str| {
+            order
+                .iter()
+                .position(|p| p.eq_ignore_ascii_case(model))
+                .unwrap_or(order.len())
+        };
+        // Stable, so unknown model pins keep their listed order at the end
+        pairs.sort_by_key(|(_, model)| rank(model));
+    }
+    pairs.into_iter().map(|(symbol, _)| symbol).collect()
+}
+
+/// Value, model or source specification of an element
+fn element_value<'a>(device: &str, letter: char, field: &impl Fn(&str) -> Option<&'a str>) -> String {
+    let params: Vec<(&str, &str)> = field("Sim.Params")
+        .map(|p| p.split_whitespace().filter_map(|kv| kv.split_once('=')).collect())
+        .unwrap_or_default();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| *v)
+    };
+    let value = field("Value").unwrap_or_default();
+
+    if field("Sim.Device").is_none() {
+        return match field("Spice_Model") {
+            Some(model) => model.to_string(),
+            None => spice_value(value),
+        };
+    }
+    match letter {
+        'R' | 'C' | 'L' => spice_value(param(&device[..1]).unwrap_or(value)),
+        'V' | 'I' => {
+            let kind = field("Sim.Type").unwrap_or("DC").to_ascii_lowercase();
+            if kind == "dc" {
+                format!("dc {}", spice_value(param("dc").unwrap_or(value)))
+            } else {
+                let args: Vec<String> = params.iter().map(|(_, v)| spice_value(v)).collect();
+                format!("{}({})", kind, args.join(" "))
+            }
+        }
+        _ => field("Sim.Name").unwrap_or(value).to_string(),
+    }
+}
+
+/// Convert a schematic value such as `4k7`, `1M` or `100nF` to SPICE notation
+///
+/// Values that do not start with a number are returned unchanged.
+pub fn spice_value(value: &str) -> String {
+    let value = value.trim().replace(['µ', 'μ'], "u");
+    let number_len = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    if number_len == 0 || value[..number_len].parse::<f64>().is_err() {
+        return value;
+    }
+    let (number, rest) = value.split_at(number_len);
+
+    // SPICE reads a lone M as milli; mega is Meg
+    let (prefix, rest) = if rest.len() >= 3 && rest[..3].eq_ignore_ascii_case("meg") {
+        ("Meg", &rest[3..])
+    } else {
+        match rest.chars().next() {
+            Some(c @ ('p' | 'n' | 'u' | 'm' | 'G' | 'T')) => (&rest[..c.len_utf8()], &rest[1..]),
+            Some('k' | 'K') => ("k", &rest[1..]),
+            Some('M') => ("Meg", &rest[1..]),
+            // 2R2 and 2E2 style decimal markers
+            Some('R' | 'r' | 'E') if rest[1..].starts_with(|c: char| c.is_ascii_digit()) => ("", &rest[1..]),
+            _ => ("", rest),
+        }
+    };
+    // Digits after the prefix are decimals: 4k7 is 4.7k
+    let decimals_len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    if decimals_len > 0 && !number.contains('.') {
+        format!("{}.{}{}", number, &rest[..decimals_len], prefix)
+    } else {
+        format!("{}{}", number, prefix)
+    }
+}
+
+/// SPICE node name of a net: ground is `0`, characters SPICE cannot read become `_`
+fn node_name(net: &str) -> String {
+    if net == "GND" || net == "0" || net == "/GND" {
+        return "0".to_string();
+    }
+    net.chars()
+        .map(|c| if c.is_whitespace() || matches!(c, '(' | ')' | ',' | '=') { '_' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schematic::parse_schematic;
+
+    #[test]
+    fn test_divider_netlist() {
+        let schematic = parse_schematic(include_str!("../assets/divider.kicad_sch")).unwrap();
+        let netlist = SpiceNetlist::from_schematic(&schematic);
+        assert_eq!(
+            netlist.to_spice(),
+            ".title KiCad schematic\nR1 VIN /OUT 10k\nR2 /OUT 0 4.7k\nV1 VIN 0 dc 5\n.end\n"
+        );
+    }
+
+    #[test]
+    fn test_models_and_pin_order() {
+        let schematic = parse_schematic(r#"(kicad_sch
+  (lib_symbols
+    (symbol "Device:D"
+      (symbol "D_1_1"
+        (pin passive line (at -3.81 0 0) (length 2.54) (name "K") (number "1"))
+        (pin passive line (at 3.81 0 180) (length 2.54) (name "A") (number "2")))))
+  (symbol (lib_id "Device:D") (at 50 50 0) (unit 1)
+    (property "Reference" "CR1") (property "Value" "1N4148")
+    (property "Sim.Device" "D") (property "Sim.Pins" "1=K 2=A")
+    (property "Sim.Library" "diodes.lib") (property "Sim.Name" "D1N4148"))
+  (symbol (lib_id "Device:D") (at 50 60 0) (unit 1) (exclude_from_sim yes)
+    (property "Reference" "D2") (property "Value" "1N4148"))
+  (label "K" (at 46.19 50 0)) (label "A" (at 53.81 50 0))
+)"#).unwrap();
+        let netlist = SpiceNetlist::from_schematic(&schematic);
+        assert_eq!(netlist.elements.len(), 1);
+        assert_eq!(
+            netlist.to_spice(),
+            ".title KiCad schematic\n.include \"diodes.lib\"\nDCR1 /A /K D1N4148\n.end\n"
+        );
+    }
+
+    #[test]
+    fn test_spice_values() {
+        assert_eq!(spice_value("4k7"), "4.7k");
+        assert_eq!(spice_value("2R2"), "2.2");
+        assert_eq!(spice_value("1M"), "1Meg");
+        assert_eq!(spice_value("2.2Meg"), "2.2Meg");
+        assert_eq!(spice_value("100nF"), "100n");
+        assert_eq!(spice_value("10µH"), "10u");
+        assert_eq!(spice_value("1m"), "1m");
+        assert_eq!(spice_value("LM358"), "LM358");
+        assert_eq!(node_name("Net-(R1-Pad1)"), "Net-_R1-Pad1_");
+    }
+}
@@ -0,0 +1,200 @@
+//! Electrical sanity checks on library symbols
+//!
+//! [`check_symbol`] looks for the pin mistakes a library review usually catches
+//! by eye: the same pin number used twice, gaps in a numeric pin sequence, pins
+//! stacked on the same connection point and pins of zero length.
+//! [`SymbolLib::check`] runs it over a whole library.
+//!
+//! Pins repeated on the De Morgan body style are checked once. Derived symbols
+//! (`extends`) are skipped since their pins belong to the parent, and power
+//! symbols may have zero-length pins.
+//!
+//! ```rust
+//! use kiparse::symbol::{check::SymbolCheck, parse_symbol_library};
+//!
+//! let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+//!   (symbol "U" (symbol "U_1_1"
+//!     (pin input line (at -5 0 0) (length 2.54) (name "A") (number "1"))
+//!     (pin input line (at -5 -2.54 0) (length 2.54) (name "B") (number "1"))
+//!     (pin output line (at 5 0 180) (length 2.54) (name "Y") (number "4")))))"#)?;
+//!
+//! let checks: Vec<SymbolCheck> = lib.check().iter().map(|i| i.check).collect();
+//! assert_eq!(checks, [SymbolCheck::DuplicatePin, SymbolCheck::MissingPins]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::bom::natural_order;
+use crate::pcb::types::{Pin, Point, Symbol};
+use crate::symbol::types::SymbolLib;
+use crate::units::Length;
+use crate::validate::Severity;
+
+/// Check a [`SymbolIssue`] comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SymbolCheck {
+    /// The same pin number on more than one pin
+    DuplicatePin,
+    /// Numbers missing from a numeric pin sequence, e.g. 1, 2, 4
+    MissingPins,
+    /// Pins of one unit sharing a connection point
+    StackedPins,
+    ZeroLengthPin,
+    /// Assigned footprint missing from the footprints checked against, see
+    /// [`check_footprint`](super::footprint_check::check_footprint)
+    FootprintNotFound,
+    /// Assigned footprint not accepted by the symbol's `ki_fp_filters`
+    FootprintFilter,
+    /// Pins with no pad of the same number on the assigned footprint
+    MissingPads,
+}
+
+impl SymbolCheck {
+    /// Name used in reports, e.g. `duplicate_pin`
+    pub fn name(self) -> &'static str {
+        match self {
+            SymbolCheck::DuplicatePin => "duplicate_pin",
+            SymbolCheck::MissingPins => "missing_pins",
+            SymbolCheck::StackedPins => "stacked_pins",
+            SymbolCheck::ZeroLengthPin => "zero_length_pin",
+            SymbolCheck::FootprintNotFound => "footprint_not_found",
+            SymbolCheck::FootprintFilter => "footprint_filter",
+            SymbolCheck::MissingPads => "missing_pads",
+        }
+    }
+}
+
+/// One finding of [`check_symbol`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolIssue {
+    pub symbol: String,
+    pub check: SymbolCheck,
+    pub severity: Severity,
+    /// Pin numbers involved; the missing numbers for [`SymbolCheck::MissingPins`]
+    pub pins: Vec<String>,
+    /// Connection point of the first pin involved, in symbol coordinates
+    pub location: Option<Point>,
+    pub message: String,
+}
+
+/// Pin checks of one symbol, in the order of [`SymbolCheck`]
+///
+/// Duplicate numbers are errors; gaps, stacked pins and zero-length pins are
+/// warnings since they are sometimes intended.
+pub fn check_symbol(symbol: &Symbol) -> Vec<SymbolIssue> {
+    let mut issues = Vec::new();
+    if symbol.extends.is_some() {
+        return issues;
+    }
+    let issue = |check, severity, pins: Vec<String>, location: Option<&Point>, message: String| SymbolIssue {
+        symbol: symbol.name.clone(),
+        check,
+        severity,
+        pins,
+        location: location.cloned(),
+        message,
+    };
+    let pins: Vec<&Pin> = symbol.pins.iter().filter(|p| p.body_style != 2).collect();
+
+    let mut by_number: BTreeMap<&str, Vec<&Pin>> = BTreeMap::new();
+    for pin in &pins {
+        by_number.entry(pin.number.as_str()).or_default().push(pin);
+    }
+    let mut numbers: Vec<&str> = by_number.keys().copied().collect();
+    numbers.sort_by(|a, b| natural_order(a, b));
+    for number in &numbers {
+        let same = &by_number[number];
+        if same.len() > 1 {
+            let names: Vec<&str> = same.iter().map(|p| p.name.as_str()).collect();
+            let message = format!("Pin number {} is used by {} pins ({})", number, same.len(), names.join(", "));
+            issues.push(issue(SymbolCheck::DuplicatePin, Severity::Error, vec![number.to_string()], Some(&same[0].at), message));
+        }
+    }
+
+    // Gaps only make sense when every pin is numbered 1, 2, 3, ...
+    let numeric: Option<Vec<u32>> = numbers.iter().map(|n| n.parse().ok()).collect();
+    if let Some(present) = numeric {
+        let max = present.iter().copied().max().unwrap_or(0);
+        let missing: Vec<String> = (1..max).filter(|n| !present.contains(n)).map(|n| n.to_string()).collect();
+        if !missing.is_empty() {
+            let message = format!("Pin numbers 1 to {} are missing {}", max, missing.join(", "));
+            issues.push(issue(SymbolCheck::MissingPins, Severity::Warning, missing, None, message));
+        }
+    }
+
+    let mut stacks: Vec<Vec<&Pin>> = Vec::new();
+    for pin in &pins {
+        let stacked = |other: &&Pin| {
+            other.unit == pin.unit
+                && (other.at.x - pin.at.x).abs().as_mm() < 1e-6
+                && (other.at.y - pin.at.y).abs().as_mm() < 1e-6
+        };
+        match stacks.iter_mut().find(|stack| stacked(&stack[0])) {
+            Some(stack) => stack.push(pin),
+            None => stacks.push(vec![pin]),
+        }
+    }
+    for stack in stacks.into_iter().filter(|s| s.len() > 1) {
+        let numbers: Vec<String> = stack.iter().map(|p| p.number.clone()).collect();
+        let message = format!("Pins {} are stacked at ({}, {})", numbers.join(", "), stack[0].at.x.as_mm(), stack[0].at.y.as_mm());
+        issues.push(issue(SymbolCheck::StackedPins, Severity::Warning, numbers, Some(&stack[0].at), message));
+    }
+
+    if !symbol.power {
+        for pin in pins.iter().filter(|p| p.length == Length::ZERO) {
+            let message = format!("Pin {} ({}) has zero length", pin.number, pin.name);
+            issues.push(issue(SymbolCheck::ZeroLengthPin, Severity::Warning, vec![pin.number.clone()], Some(&pin.at), message));
+        }
+    }
+    issues
+}
+
+impl SymbolLib {
+    /// [`check_symbol`] findings for every symbol, in library order
+    pub fn check(&self) -> Vec<SymbolIssue> {
+        self.symbols.iter().flat_map(check_symbol).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::parse_symbol_library;
+
+    #[test]
+    fn test_pin_checks() {
+        let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+  (symbol "REG" (symbol "REG_1_1"
+    (pin power_in line (at -5 0 0) (length 2.54) (name "VIN") (number "1"))
+    (pin power_in line (at 0 -5 90) (length 2.54) (name "GND") (number "2"))
+    (pin passive line (at 0 -5 90) (length 2.54) hide (name "GND") (number "5"))
+    (pin power_out line (at 5 0 180) (length 0) (name "VOUT") (number "3"))
+    (pin output line (at 5 -2.54 180) (length 2.54) (name "PG") (number "3")))
+    (symbol "REG_1_2"
+      (pin power_in line (at -5 0 0) (length 2.54) (name "VIN") (number "1"))))
+  (symbol "REG_ALT" (extends "REG"))
+  (symbol "GND" (power) (symbol "GND_0_1"
+    (pin power_in line (at 0 0 0) (length 0) hide (name "GND") (number "1")))))"#).unwrap();
+
+        let issues = lib.check();
+        let found: Vec<(SymbolCheck, &[String])> = issues.iter().map(|i| (i.check, i.pins.as_slice())).collect();
+        assert_eq!(
+            found,
+            [
+                (SymbolCheck::DuplicatePin, &["3".to_string()][..]),
+                (SymbolCheck::MissingPins, &["4".to_string()][..]),
+                (SymbolCheck::StackedPins, &["2".to_string(), "5".to_string()][..]),
+                (SymbolCheck::ZeroLengthPin, &["3".to_string()][..]),
+            ]
+        );
+        assert!(issues.iter().all(|i| i.symbol == "REG"));
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].message, "Pin number 3 is used by 2 pins (VOUT, PG)");
+        assert!(issues[1..].iter().all(|i| i.severity == Severity::Warning));
+        assert_eq!(issues[2].location, Some(Point::new(0.0, -5.0)));
+    }
+}
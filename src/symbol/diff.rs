@@ -0,0 +1,421 @@
+//! Structured differences between two versions of a symbol or library
+//!
+//! [`diff_symbols`] compares an old and a new version of a symbol: pins added,
+//! removed or changed (matched by unit, body style and number), body drawings
+//! added, removed, moved or reshaped, and changed fields and attributes.
+//! [`diff_libraries`] does the same for every symbol two libraries share and
+//! lists the symbols only one of them has, which is what a review of a library
+//! pull request needs.
+//!
+//! ```rust
+//! use kiparse::symbol::{diff_libraries, parse_symbol_library};
+//!
+//! let old = parse_symbol_library("old", r#"(kicad_symbol_lib
+//!   (symbol "R" (property "Value" "R")
+//!     (symbol "R_0_1" (rectangle (start -1 -2.5) (end 1 2.5)))
+//!     (symbol "R_1_1" (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1")))))"#)?;
+//! let new = parse_symbol_library("new", r#"(kicad_symbol_lib
+//!   (symbol "R" (property "Value" "R_Small")
+//!     (symbol "R_0_1" (rectangle (start -1 -2) (end 1 3)))
+//!     (symbol "R_1_1" (pin passive line (at 0 3.81 270) (length 1.27) (name "A") (number "1"))))
+//!   (symbol "C"))"#)?;
+//! let diff = diff_libraries(&old, &new);
+//!
+//! assert_eq!(diff.added_symbols, ["C"]);
+//! let r = &diff.changed_symbols[0];
+//! assert_eq!(r.pin_changes[0].to.as_deref(), Some("A"));
+//! assert_eq!(r.graphic_changes[0].change, kiparse::symbol::diff::GraphicChangeKind::Moved);
+//! assert_eq!(r.property_changes[0].property, "Value");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::bom::natural_order;
+use crate::pcb::diff::DIFF_TOLERANCE;
+use crate::pcb::types::{Fill, Pin, Point, Stroke, Symbol};
+use crate::symbol::types::SymbolLib;
+use crate::units::Length;
+
+/// Everything that changed between two versions of a symbol
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolDiff {
+    pub name: String,
+    /// Pin changes in unit, body style and natural pin number order
+    pub pin_changes: Vec<PinChange>,
+    pub graphic_changes: Vec<GraphicChange>,
+    /// Field value changes, then attributes such as `power` or `unit_count`
+    pub property_changes: Vec<PropertyChange>,
+}
+
+impl SymbolDiff {
+    /// Whether the symbols are the same in every compared respect
+    pub fn is_empty(&self) -> bool {
+        self.pin_changes.is_empty() && self.graphic_changes.is_empty() && self.property_changes.is_empty()
+    }
+}
+
+/// Symbols added, removed and changed between two libraries
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LibraryDiff {
+    pub added_symbols: Vec<String>,
+    pub removed_symbols: Vec<String>,
+    /// Differences of symbols in both libraries, by name
+    pub changed_symbols: Vec<SymbolDiff>,
+}
+
+impl LibraryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_symbols.is_empty() && self.removed_symbols.is_empty() && self.changed_symbols.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinChange {
+    pub unit: u32,
+    pub body_style: u32,
+    pub number: String,
+    /// Changed property (`name`, `type`, `shape`, `position`, `length`,
+    /// `rotation` or `hidden`), or `pin` when the pin was added or removed
+    pub property: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PropertyChange {
+    pub property: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// What happened to a body drawing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum GraphicChangeKind {
+    Added,
+    Removed,
+    /// Same shape at another place
+    Moved,
+    /// Same kind of drawing with a different shape
+    Reshaped,
+    /// Same geometry with another stroke or fill
+    Restyled,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphicChange {
+    /// `rectangle`, `circle`, `arc` or `polyline`
+    pub kind: String,
+    pub unit: u32,
+    pub body_style: u32,
+    pub change: GraphicChangeKind,
+    /// Old and new geometry, e.g. `(0 0) (2.54 5.08)` for a rectangle
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Compare `old` with `new`
+///
+/// Drawings are compared within each kind, unit and body style: identical
+/// geometry is matched first, then the remaining drawings pair up in file order
+/// as moved or reshaped, and the rest count as added or removed.
+pub fn diff_symbols(old: &Symbol, new: &Symbol) -> SymbolDiff {
+    SymbolDiff {
+        name: new.name.clone(),
+        pin_changes: diff_pins(old, new),
+        graphic_changes: diff_graphics(old, new),
+        property_changes: diff_properties(old, new),
+    }
+}
+
+/// Compare two libraries; symbols are matched by name
+pub fn diff_libraries(old: &SymbolLib, new: &SymbolLib) -> LibraryDiff {
+    fn by_name(lib: &SymbolLib) -> BTreeMap<&str, &Symbol> {
+        lib.symbols.iter().map(|s| (s.name.as_str(), s)).collect()
+    }
+    let (old_symbols, new_symbols) = (by_name(old), by_name(new));
+    LibraryDiff {
+        added_symbols: new_symbols.keys().filter(|n| !old_symbols.contains_key(*n)).map(|n| n.to_string()).collect(),
+        removed_symbols: old_symbols.keys().filter(|n| !new_symbols.contains_key(*n)).map(|n| n.to_string()).collect(),
+        changed_symbols: old_symbols
+            .iter()
+            .filter_map(|(name, before)| Some(diff_symbols(before, new_symbols.get(name)?)))
+            .filter(|d| !d.is_empty())
+            .collect(),
+    }
+}
+
+fn diff_pins(old: &Symbol, new: &Symbol) -> Vec<PinChange> {
+    fn by_key(symbol: &Symbol) -> BTreeMap<(u32, u32, String), &Pin> {
+        symbol.pins.iter().map(|p| ((p.unit, p.body_style, p.number.clone()), p)).collect()
+    }
+    let (old_pins, new_pins) = (by_key(old), by_key(new));
+    let keys: BTreeSet<&(u32, u32, String)> = old_pins.keys().chain(new_pins.keys()).collect();
+    let mut keys: Vec<&(u32, u32, String)> = keys.into_iter().collect();
+    keys.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)).then_with(|| natural_order(&a.2, &b.2)));
+
+    let mut changes = Vec::new();
+    for key in keys {
+        let &(unit, body_style, ref number) = key;
+        let change = |property: &str, from: Option<String>, to: Option<String>| PinChange {
+            unit,
+            body_style,
+            number: number.clone(),
+            property: property.to_string(),
+            from,
+            to,
+        };
+        match (old_pins.get(key), new_pins.get(key)) {
+            (Some(before), Some(after)) => {
+                let position = |p: &Pin| format!("{} {}", p.at.x.as_mm(), p.at.y.as_mm());
+                let moved = (before.at.x - after.at.x).abs().as_mm() > DIFF_TOLERANCE
+                    || (before.at.y - after.at.y).abs().as_mm() > DIFF_TOLERANCE;
+                let properties = [
+                    ("name", before.name != after.name, before.name.clone(), after.name.clone()),
+                    ("type", before.pin_type != after.pin_type, before.pin_type.clone(), after.pin_type.clone()),
+                    ("shape", before.shape != after.shape, before.shape.clone(), after.shape.clone()),
+                    ("position", moved, position(before), position(after)),
+                    (
+                        "length",
+                        (before.length - after.length).abs().as_mm() > DIFF_TOLERANCE,
+                        before.length.as_mm().to_string(),
+                        after.length.as_mm().to_string(),
+                    ),
+                    (
+                        "rotation",
+                        (before.rotation - after.rotation).abs() > DIFF_TOLERANCE,
+                        before.rotation.to_string(),
+                        after.rotation.to_string(),
+                    ),
+                    ("hidden", before.hidden != after.hidden, before.hidden.to_string(), after.hidden.to_string()),
+                ];
+                for (property, changed, from, to) in properties {
+                    if changed {
+                        changes.push(change(property, Some(from), Some(to)));
+                    }
+                }
+            }
+            (before, after) => changes.push(change("pin", before.map(|p| p.name.clone()), after.map(|p| p.name.clone()))),
+        }
+    }
+    changes
+}
+
+fn diff_properties(old: &Symbol, new: &Symbol) -> Vec<PropertyChange> {
+    let values = |symbol: &Symbol| -> BTreeMap<String, String> {
+        symbol.properties.iter().map(|p| (p.name.clone(), p.value.clone())).collect()
+    };
+    let (old_values, new_values) = (values(old), values(new));
+    let mut changes = Vec::new();
+    let names: BTreeSet<&String> = old_values.keys().chain(new_values.keys()).collect();
+    for name in names {
+        let (from, to) = (old_values.get(name), new_values.get(name));
+        if from != to {
+            changes.push(PropertyChange { property: name.clone(), from: from.cloned(), to: to.cloned() });
+        }
+    }
+
+    let attributes = |s: &Symbol| {
+        [
+            ("extends", s.extends.clone()),
+            ("power", Some(s.power.to_string())),
+            ("in_bom", Some(s.in_bom.to_string())),
+            ("on_board", Some(s.on_board.to_string())),
+            ("exclude_from_sim", Some(s.exclude_from_sim.to_string())),
+            ("unit_count", Some(s.unit_count.to_string())),
+            ("pin_names_offset", Some(s.pin_names_offset.as_mm().to_string())),
+            ("pin_names_hidden", Some(s.pin_names_hidden.to_string())),
+            ("pin_numbers_hidden", Some(s.pin_numbers_hidden.to_string())),
+        ]
+    };
+    for ((property, from), (_, to)) in attributes(old).into_iter().zip(attributes(new)) {
+        if from != to {
+            changes.push(PropertyChange { property: property.to_string(), from, to });
+        }
+    }
+    changes
+}
+
+/// A body drawing reduced to what the comparison needs
+struct Drawing {
+    kind: &'static str,
+    unit: u32,
+    body_style: u32,
+    /// Defining points, plus the radius as a point for circles
+    points: Vec<Point>,
+    style: (String, String),
+}
+
+fn drawings(symbol: &Symbol) -> Vec<Drawing> {
+    let style = |stroke: &Stroke, fill: &Fill| {
+        (format!("{} {}", stroke.width.as_mm(), stroke.stroke_type), fill.fill_type.clone())
+    };
+    let mut all = Vec::new();
+    for r in &symbol.rectangles {
+        let points = vec![r.start.clone(), r.end.clone()];
+        all.push(Drawing { kind: "rectangle", unit: r.unit, body_style: r.body_style, points, style: style(&r.stroke, &r.fill) });
+    }
+    for c in &symbol.circles {
+        let points = vec![c.center.clone(), Point { x: c.radius, y: Length::ZERO }];
+        all.push(Drawing { kind: "circle", unit: c.unit, body_style: c.body_style, points, style: style(&c.stroke, &c.fill) });
+    }
+    for a in &symbol.arcs {
+        let points = vec![a.start.clone(), a.mid.clone(), a.end.clone()];
+        all.push(Drawing { kind: "arc", unit: a.unit, body_style: a.body_style, points, style: style(&a.stroke, &a.fill) });
+    }
+    for p in &symbol.polylines {
+        all.push(Drawing {
+            kind: "polyline",
+            unit: p.unit,
+            body_style: p.body_style,
+            points: p.points.clone(),
+            style: style(&p.stroke, &p.fill),
+        });
+    }
+    all
+}
+
+impl Drawing {
+    fn describe(&self) -> String {
+        let point = |p: &Point| format!("({} {})", p.x.as_mm(), p.y.as_mm());
+        if self.kind == "circle" {
+            return format!("{} r {}", point(&self.points[0]), self.points[1].x.as_mm());
+        }
+        self.points.iter().map(point).collect::<Vec<_>>().join(" ")
+    }
+
+    fn same_geometry(&self, other: &Drawing) -> bool {
+        self.points.len() == other.points.len()
+            && self.points.iter().zip(&other.points).all(|(p, q)| {
+                (p.x - q.x).abs().as_mm() <= DIFF_TOLERANCE && (p.y - q.y).abs().as_mm() <= DIFF_TOLERANCE
+            })
+    }
+
+    /// Same shape shifted by one offset; a circle keeps its radius
+    fn translated(&self, other: &Drawing) -> bool {
+        if self.points.len() != other.points.len() {
+            return false;
+        }
+        let shapes = if self.kind == "circle" { 1 } else { self.points.len() };
+        let (dx, dy) = (other.points[0].x - self.points[0].x, other.points[0].y - self.points[0].y);
+        let shifted = self.points[..shapes].iter().zip(&other.points).all(|(p, q)| {
+            (q.x - p.x - dx).abs().as_mm() <= DIFF_TOLERANCE && (q.y - p.y - dy).abs().as_mm() <= DIFF_TOLERANCE
+        });
+        shifted && (self.kind != "circle" || (self.points[1].x - other.points[1].x).abs().as_mm() <= DIFF_TOLERANCE)
+    }
+}
+
+fn diff_graphics(old: &Symbol, new: &Symbol) -> Vec<GraphicChange> {
+    let group = |symbol: &Symbol| {
+        let mut groups: BTreeMap<(u32, u32, &'static str), Vec<Drawing>> = BTreeMap::new();
+        for drawing in drawings(symbol) {
+            groups.entry((drawing.unit, drawing.body_style, drawing.kind)).or_default().push(drawing);
+        }
+        groups
+    };
+    let (mut old_groups, mut new_groups) = (group(old), group(new));
+    let keys: BTreeSet<(u32, u32, &'static str)> = old_groups.keys().chain(new_groups.keys()).copied().collect();
+
+    let mut changes = Vec::new();
+    for key in keys {
+        let (unit, body_style, kind) = key;
+        let mut before = old_groups.remove(&key).unwrap_or_default();
+        let mut after = new_groups.remove(&key).unwrap_or_default();
+        let mut change = |change, from: Option<&Drawing>, to: Option<&Drawing>| {
+            changes.push(GraphicChange {
+                kind: kind.to_string(),
+                unit,
+                body_style,
+                change,
+                from: from.map(Drawing::describe),
+                to: to.map(Drawing::describe),
+            })
+        };
+
+        // Identical geometry first, so a single edit does not shift every pairing
+        let mut i = 0;
+        while i < before.len() {
+            match after.iter().position(|d| d.same_geometry(&before[i])) {
+                Some(j) => {
+                    let (b, a) = (before.remove(i), after.remove(j));
+                    if b.style != a.style {
+                        change(GraphicChangeKind::Restyled, Some(&b), Some(&a));
+                    }
+                }
+                None => i += 1,
+            }
+        }
+        let paired = before.len().min(after.len());
+        for (b, a) in before.iter().zip(&after) {
+            let kind = if b.translated(a) { GraphicChangeKind::Moved } else { GraphicChangeKind::Reshaped };
+            change(kind, Some(b), Some(a));
+        }
+        for b in &before[paired..] {
+            change(GraphicChangeKind::Removed, Some(b), None);
+        }
+        for a in &after[paired..] {
+            change(GraphicChangeKind::Added, None, Some(a));
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::parse_symbol_library;
+
+    #[test]
+    fn test_diff_symbols() {
+        let old = parse_symbol_library("old", r#"(kicad_symbol_lib
+  (symbol "U" (property "Reference" "U") (property "Footprint" "SOIC-8")
+    (symbol "U_0_1"
+      (rectangle (start -5 5) (end 5 -5) (stroke (width 0.254)) (fill (type background)))
+      (circle (center 0 0) (radius 1))
+      (polyline (pts (xy 0 0) (xy 1 1))))
+    (symbol "U_1_1"
+      (pin input line (at -7.5 0 0) (length 2.5) (name "IN") (number "1"))
+      (pin output line (at 7.5 0 180) (length 2.5) (name "OUT") (number "2"))
+      (pin power_in line (at 0 7.5 270) (length 2.5) (name "V+") (number "8")))))"#).unwrap();
+        let new = parse_symbol_library("new", r#"(kicad_symbol_lib
+  (symbol "U" (power) (property "Reference" "U") (property "Datasheet" "u.pdf")
+    (symbol "U_0_1"
+      (rectangle (start -5 5) (end 5 -5) (stroke (width 0.254)) (fill (type none)))
+      (circle (center 2 2) (radius 1))
+      (polyline (pts (xy 0 0) (xy 1 2))))
+    (symbol "U_1_1"
+      (pin input inverted (at -7.5 0 0) (length 2.5) (name "IN") (number "1"))
+      (pin output line (at 7.5 2.5 180) (length 2.5) (name "OUT") (number "2"))
+      (pin power_in line (at 0 -7.5 90) (length 2.5) (name "V-") (number "4")))))"#).unwrap();
+        let diff = diff_symbols(&old.symbols[0], &new.symbols[0]);
+
+        let pins: Vec<(&str, &str)> = diff.pin_changes.iter().map(|c| (c.number.as_str(), c.property.as_str())).collect();
+        assert_eq!(pins, [("1", "shape"), ("2", "position"), ("4", "pin"), ("8", "pin")]);
+        assert_eq!(diff.pin_changes[1].to.as_deref(), Some("7.5 2.5"));
+        assert_eq!((diff.pin_changes[2].from.as_ref(), diff.pin_changes[2].to.as_deref()), (None, Some("V-")));
+
+        let graphics: Vec<(&str, GraphicChangeKind)> = diff.graphic_changes.iter().map(|c| (c.kind.as_str(), c.change)).collect();
+        assert_eq!(
+            graphics,
+            [
+                ("circle", GraphicChangeKind::Moved),
+                ("polyline", GraphicChangeKind::Reshaped),
+                ("rectangle", GraphicChangeKind::Restyled),
+            ]
+        );
+        assert_eq!(diff.graphic_changes[0].to.as_deref(), Some("(2 2) r 1"));
+
+        let properties: Vec<(&str, Option<&str>)> = diff.property_changes.iter().map(|c| (c.property.as_str(), c.to.as_deref())).collect();
+        assert_eq!(properties, [("Datasheet", Some("u.pdf")), ("Footprint", None), ("power", Some("true"))]);
+
+        assert!(diff_symbols(&old.symbols[0], &old.symbols[0]).is_empty());
+        assert!(diff_libraries(&old, &old.clone()).is_empty());
+    }
+}
@@ -0,0 +1,192 @@
+//! Checks of a symbol against its assigned footprint
+//!
+//! [`check_footprint`] looks up the footprint named in a symbol's `Footprint`
+//! property in a [`FootprintSet`], then checks that the symbol's `ki_fp_filters`
+//! accept it and that every pin number has a pad on it. Findings are
+//! [`SymbolIssue`]s, like those of [`check_symbol`](super::check_symbol).
+//! Symbols without an assigned footprint are not checked.
+//!
+//! ```rust
+//! use kiparse::pcb::parse_footprint;
+//! use kiparse::symbol::{check::SymbolCheck, footprint_check::FootprintSet, parse_symbol_library};
+//!
+//! let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+//!   (symbol "LDO" (property "Footprint" "Package_TO_SOT_SMD:SOT-23")
+//!     (property "ki_fp_filters" "SOT?23*")
+//!     (symbol "LDO_1_1"
+//!       (pin power_in line (at -5 0 0) (length 2.5) (name "VIN") (number "1"))
+//!       (pin power_out line (at 5 0 180) (length 2.5) (name "VOUT") (number "2"))
+//!       (pin power_in line (at 0 -5 90) (length 2.5) (name "GND") (number "3"))
+//!       (pin input line (at -5 -2.5 0) (length 2.5) (name "EN") (number "4")))))"#)?;
+//! let sot23 = vec![parse_footprint(r#"(footprint "SOT-23"
+//!   (pad "1" smd rect (at -1 0.95) (size 0.9 0.8) (layers "F.Cu"))
+//!   (pad "2" smd rect (at -1 -0.95) (size 0.9 0.8) (layers "F.Cu"))
+//!   (pad "3" smd rect (at 1 0) (size 0.9 0.8) (layers "F.Cu")))"#)?];
+//!
+//! let mut footprints = FootprintSet::new();
+//! footprints.add_library("Package_TO_SOT_SMD", &sot23);
+//! let issues = lib.check_footprints(&footprints);
+//! assert_eq!(issues[0].check, SymbolCheck::MissingPads);
+//! assert_eq!(issues[0].pins, ["4"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::{BTreeSet, HashMap};
+
+use super::check::{SymbolCheck, SymbolIssue};
+use super::query::accepts_footprint;
+use crate::bom::natural_order;
+use crate::pcb::types::{Footprint, Symbol};
+use crate::symbol::types::SymbolLib;
+use crate::validate::Severity;
+
+/// Footprints to check symbols against, by `Library:Name`
+#[derive(Debug, Clone, Default)]
+pub struct FootprintSet<'a> {
+    footprints: HashMap<String, &'a Footprint>,
+}
+
+impl<'a> FootprintSet<'a> {
+    pub fn new() -> Self {
+        FootprintSet::default()
+    }
+
+    /// Add a footprint under its own name, e.g. a board footprint named `Lib:Name`
+    pub fn add(&mut self, footprint: &'a Footprint) {
+        self.footprints.insert(footprint.name.clone(), footprint);
+    }
+
+    /// Add the footprints of a library (a `.pretty` directory) under `library:name`
+    pub fn add_library(&mut self, library: &str, footprints: &'a [Footprint]) {
+        for footprint in footprints {
+            let name = footprint.name.rsplit(':').next().unwrap_or(&footprint.name);
+            self.footprints.insert(format!("{}:{}", library, name), footprint);
+        }
+    }
+
+    /// Footprint with the given `Library:Name` id
+    ///
+    /// When there is no exact match, the name alone is looked up, provided a
+    /// single footprint has it; this covers ids and footprints without a library.
+    pub fn get(&self, id: &str) -> Option<&'a Footprint> {
+        if let Some(footprint) = self.footprints.get(id) {
+            return Some(footprint);
+        }
+        let name = |id: &'_ str| id.rsplit(':').next().unwrap_or(id).to_string();
+        let wanted = name(id);
+        let mut found = self.footprints.iter().filter(|(key, _)| name(key) == wanted);
+        match (found.next(), found.next()) {
+            (Some((_, footprint)), None) => Some(footprint),
+            _ => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.footprints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.footprints.is_empty()
+    }
+}
+
+/// Footprint checks of one symbol, in the order of [`SymbolCheck`]
+///
+/// A footprint that cannot be found and pins without a pad are errors; a
+/// footprint the filters do not accept is a warning. Derived symbols need
+/// [`resolve_extends`](super::resolve_extends) first, as their pins belong to
+/// the parent.
+pub fn check_footprint(symbol: &Symbol, footprints: &FootprintSet) -> Vec<SymbolIssue> {
+    let mut issues = Vec::new();
+    let id = symbol.property("Footprint").unwrap_or_default().trim();
+    if id.is_empty() {
+        return issues;
+    }
+    let issue = |check, severity, pins: Vec<String>, message: String| SymbolIssue {
+        symbol: symbol.name.clone(),
+        check,
+        severity,
+        pins,
+        location: None,
+        message,
+    };
+
+    let Some(footprint) = footprints.get(id) else {
+        let message = format!("Footprint {} not found", id);
+        issues.push(issue(SymbolCheck::FootprintNotFound, Severity::Error, Vec::new(), message));
+        return issues;
+    };
+    if !accepts_footprint(symbol, id) {
+        let message = format!("Footprint {} does not match the filters {}", id, symbol.fp_filters().join(" "));
+        issues.push(issue(SymbolCheck::FootprintFilter, Severity::Warning, Vec::new(), message));
+    }
+
+    let pads: BTreeSet<&str> = footprint.pads.iter().map(|p| p.number.as_str()).collect();
+    let mut missing: Vec<String> = symbol
+        .pins()
+        .iter()
+        .map(|p| p.number.as_str())
+        .filter(|n| !pads.contains(n))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    missing.sort_by(|a, b| natural_order(a, b));
+    if !missing.is_empty() {
+        let message = format!("Footprint {} has no pad for pins {}", id, missing.join(", "));
+        issues.push(issue(SymbolCheck::MissingPads, Severity::Error, missing, message));
+    }
+    issues
+}
+
+impl SymbolLib {
+    /// [`check_footprint`] findings for every symbol, in library order
+    pub fn check_footprints(&self, footprints: &FootprintSet) -> Vec<SymbolIssue> {
+        self.symbols.iter().flat_map(|s| check_footprint(s, footprints)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::{parse_footprint, parse_pcb};
+    use crate::symbol::parse_symbol_library;
+
+    #[test]
+    fn test_footprint_checks() {
+        let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+  (symbol "R" (property "Footprint" "Resistor_SMD:R_0603") (property "ki_fp_filters" "R_*")
+    (symbol "R_1_1"
+      (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+      (pin passive line (at 0 -3.81 90) (length 1.27) (name "~") (number "2"))))
+  (symbol "C" (property "Footprint" "Resistor_SMD:R_0603") (property "ki_fp_filters" "C_*")
+    (symbol "C_1_1" (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))))
+  (symbol "U" (property "Footprint" "Package_SO:SOIC-8"))
+  (symbol "TP"))"#).unwrap();
+        let r0603 = [parse_footprint(r#"(footprint "R_0603" (layer "F.Cu")
+  (pad "1" smd roundrect (at -0.8 0) (size 0.8 0.9) (layers "F.Cu" "F.Paste" "F.Mask"))
+  (pad "2" smd roundrect (at 0.8 0) (size 0.8 0.9) (layers "F.Cu" "F.Paste" "F.Mask")))"#).unwrap()];
+        let mut footprints = FootprintSet::new();
+        footprints.add_library("Resistor_SMD", &r0603);
+
+        let issues = lib.check_footprints(&footprints);
+        let found: Vec<(&str, SymbolCheck, Severity)> =
+            issues.iter().map(|i| (i.symbol.as_str(), i.check, i.severity)).collect();
+        assert_eq!(
+            found,
+            [
+                ("C", SymbolCheck::FootprintFilter, Severity::Warning),
+                ("U", SymbolCheck::FootprintNotFound, Severity::Error),
+            ]
+        );
+
+        // Board footprints carry their library in the name; a bare id falls back to the name
+        let board = parse_pcb(r#"(kicad_pcb (footprint "Package_SO:SOIC-8" (property "Reference" "U1")
+  (pad "1" smd rect (at 0 0) (size 1 1) (layers "F.Cu"))))"#).unwrap();
+        let mut on_board = FootprintSet::new();
+        on_board.add(&board.footprints[0]);
+        assert!(on_board.get("SOIC-8").is_some());
+        assert!(check_footprint(&lib.symbols[2], &on_board).is_empty());
+        assert!(parse_footprint("(kicad_pcb)").is_err());
+    }
+}
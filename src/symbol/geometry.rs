@@ -0,0 +1,158 @@
+//! Extents and grid alignment of library symbols
+//!
+//! Symbol coordinates are in millimeters with y pointing up, as in the
+//! `.kicad_sym` file. [`Symbol::bounds`] covers the body drawings, visible pins
+//! and visible fields of one unit; [`Symbol::off_grid_pins`] finds pins whose
+//! connection point misses the schematic grid, which keeps wires from
+//! attaching in KiCad.
+//!
+//! ```rust
+//! use kiparse::symbol::parse_symbol_library;
+//!
+//! let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+//!   (symbol "R" (symbol "R_0_1" (rectangle (start -1.016 -2.54) (end 1.016 2.54)))
+//!     (symbol "R_1_1"
+//!       (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))
+//!       (pin passive line (at 0 -3.8 90) (length 1.26) (name "~") (number "2")))))"#)?;
+//!
+//! let r = &lib.symbols[0];
+//! let body = r.body_bounds(1).unwrap();
+//! assert_eq!((body.start.y.as_mm(), body.end.y.as_mm()), (-3.8, 3.81));
+//! assert_eq!(r.off_grid_pins(1.27).iter().map(|p| p.number.as_str()).collect::<Vec<_>>(), ["2"]);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::pcb::geometry::{arc_from_points, bounds, flatten_arc};
+use crate::pcb::types::{Pin, Point, Property, Rect, Symbol};
+
+/// Average glyph width of KiCad's stroke font as a share of the font size
+const GLYPH_WIDTH: f64 = 0.9;
+
+impl Symbol {
+    /// Box around the drawings and visible pins of `unit` in the normal body style
+    ///
+    /// Unit 0 covers every unit. Drawings are measured along their centre lines,
+    /// so strokes may reach half their width beyond the box. `None` when the unit
+    /// has nothing to draw.
+    pub fn body_bounds(&self, unit: u32) -> Option<Rect> {
+        let shown = |item_unit: u32, body_style: u32| {
+            body_style != 2 && (unit == 0 || item_unit == 0 || item_unit == unit)
+        };
+        let mut points: Vec<Point> = Vec::new();
+        for rectangle in self.rectangles.iter().filter(|r| shown(r.unit, r.body_style)) {
+            points.extend([rectangle.start.clone(), rectangle.end.clone()]);
+        }
+        for circle in self.circles.iter().filter(|c| shown(c.unit, c.body_style)) {
+            let (c, r) = (&circle.center, circle.radius);
+            points.extend([Point { x: c.x - r, y: c.y - r }, Point { x: c.x + r, y: c.y + r }]);
+        }
+        for arc in self.arcs.iter().filter(|a| shown(a.unit, a.body_style)) {
+            match arc_from_points(&arc.start, &arc.mid, &arc.end) {
+                Some(circle) => points.extend(flatten_arc(&circle)),
+                None => points.extend([arc.start.clone(), arc.end.clone()]),
+            }
+        }
+        for polyline in self.polylines.iter().filter(|p| shown(p.unit, p.body_style)) {
+            points.extend(polyline.points.iter().cloned());
+        }
+        for pin in self.pins.iter().filter(|p| !p.hidden && shown(p.unit, p.body_style)) {
+            points.extend([pin.at.clone(), pin.body_end()]);
+        }
+        to_rect(&points)
+    }
+
+    /// [`body_bounds`](Self::body_bounds) grown to take in the visible fields
+    ///
+    /// Text extents are estimated from the font size and character count.
+    pub fn bounds(&self, unit: u32) -> Option<Rect> {
+        let mut points: Vec<Point> = self.body_bounds(unit).map(|r| vec![r.start, r.end]).unwrap_or_default();
+        for property in &self.properties {
+            if let Some((min, max)) = text_extent(property) {
+                points.extend([min, max]);
+            }
+        }
+        to_rect(&points)
+    }
+
+    /// Pins whose connection point is not on a multiple of `grid` mm
+    ///
+    /// KiCad libraries keep pins on 1.27 mm (50 mil); many house styles use 2.54 mm.
+    pub fn off_grid_pins(&self, grid: f64) -> Vec<&Pin> {
+        let on_grid = |v: f64| {
+            let steps = v / grid;
+            (steps - steps.round()).abs() * grid < 1e-4
+        };
+        self.pins.iter().filter(|p| !on_grid(p.at.x.as_mm()) || !on_grid(p.at.y.as_mm())).collect()
+    }
+}
+
+fn to_rect(points: &[Point]) -> Option<Rect> {
+    if points.is_empty() {
+        return None;
+    }
+    let (start, end) = bounds(points);
+    Some(Rect { start, end })
+}
+
+/// Estimated corners of a visible, non-empty field, honoring its justification
+fn text_extent(property: &Property) -> Option<(Point, Point)> {
+    let effects = property.effects.as_ref()?;
+    if effects.hide || property.value.is_empty() {
+        return None;
+    }
+    let size = &effects.font.size;
+    let width = property.value.chars().count() as f64 * size.x * GLYPH_WIDTH;
+    let height = size.y;
+    let justify = effects.justify.as_deref().unwrap_or_default();
+    let has = |word: &str| justify.split_whitespace().any(|j| j == word);
+    let at = &property.at;
+    let x = if has("left") {
+        at.x
+    } else if has("right") {
+        at.x - width
+    } else {
+        at.x - width / 2.0
+    };
+    // y points up: top-justified text hangs below its anchor
+    let y = if has("top") {
+        at.y - height
+    } else if has("bottom") {
+        at.y
+    } else {
+        at.y - height / 2.0
+    };
+    Some((Point { x, y }, Point { x: x + width, y: y + height }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::symbol::parse_symbol_library;
+    use crate::units::Length;
+
+    #[test]
+    fn test_bounds_by_unit_and_fields() {
+        let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+  (symbol "OPAMP"
+    (property "Reference" "U" (at 0 5.08 0) (effects (font (size 1.27 1.27)) (justify left)))
+    (property "Footprint" "" (at 0 0 0) (effects (font (size 1.27 1.27)) hide))
+    (symbol "OPAMP_1_1"
+      (polyline (pts (xy -5.08 5.08) (xy 5.08 0) (xy -5.08 -5.08) (xy -5.08 5.08)))
+      (pin output line (at 7.62 0 180) (length 2.54) (name "~") (number "1")))
+    (symbol "OPAMP_2_1"
+      (circle (center 20 0) (radius 2))
+      (pin power_in line (at 20 -5.08 90) (length 3.08) hide (name "V-") (number "4")))))"#).unwrap();
+        let opamp = &lib.symbols[0];
+
+        let unit1 = opamp.body_bounds(1).unwrap();
+        assert_eq!((unit1.start.x, unit1.end.x, unit1.end.y), (Length::mm(-5.08), Length::mm(7.62), Length::mm(5.08)));
+        let unit2 = opamp.body_bounds(2).unwrap();
+        assert_eq!((unit2.start.x, unit2.start.y, unit2.end.x), (Length::mm(18.0), Length::mm(-2.0), Length::mm(22.0)));
+        assert_eq!(opamp.body_bounds(0).unwrap().end.x, Length::mm(22.0));
+        assert!(opamp.body_bounds(3).is_none());
+
+        // "U" left-justified at y 5.08 reaches above the body
+        let with_text = opamp.bounds(1).unwrap();
+        assert!((with_text.end.y.as_mm() - (5.08 + 1.27 / 2.0)).abs() < 1e-9);
+        assert_eq!(with_text.start, unit1.start);
+    }
+}
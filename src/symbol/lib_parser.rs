@@ -0,0 +1,459 @@
+//! Full symbol library parser
+//!
+//! Builds the rich [`Symbol`] model (properties, pins, units and graphics) from a
+//! `.kicad_sym` library using the S-expression reader. [`parse_lib_symbol`] also
+//! accepts the `lib_symbols` entries embedded in schematics.
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(not(any(feature = "std", feature = "serde")))]
+use crate::float::Float;
+use crate::error::Result;
+use crate::pcb::types::{
+    Circle, Color, Effects, Fill, Font, Pin, Point, Polyline, Property, Rectangle, Stroke, Symbol,
+    SymbolArc,
+};
+use crate::sexpr::{parse_sexpr_all, SExpr};
+use crate::symbol::types::SymbolLib;
+use crate::units::Length;
+use crate::collections::HashMap;
+
+/// Parse every symbol of a KiCad symbol library with pins, units and graphics
+///
+/// Accepts a complete `(kicad_symbol_lib ...)` file as well as bare `(symbol ...)`
+/// snippets.
+pub fn parse_symbol_lib_full(content: &str) -> Result<Vec<Symbol>> {
+    let mut symbols = Vec::new();
+    for node in parse_sexpr_all(content)? {
+        if node.is("kicad_symbol_lib") {
+            symbols.extend(node.children("symbol").map(parse_lib_symbol));
+        } else if node.is("symbol") {
+            symbols.push(parse_lib_symbol(&node));
+        }
+    }
+    Ok(symbols)
+}
+
+/// Parse a `.kicad_sym` file into a [`SymbolLib`] named `name`
+pub fn parse_symbol_library(name: &str, content: &str) -> Result<SymbolLib> {
+    let root = crate::sexpr::parse_sexpr(content)?;
+    if !root.is("kicad_symbol_lib") {
+        return Err(crate::error::KicadError::InvalidFormat(
+            "Expected a (kicad_symbol_lib ...) root element".to_string(),
+        ));
+    }
+    Ok(SymbolLib {
+        name: name.to_string(),
+        version: root.child_str("version").unwrap_or("unknown").to_string(),
+        generator: root.child_str("generator").unwrap_or("unknown").to_string(),
+        symbols: root.children("symbol").map(parse_lib_symbol).collect(),
+    })
+}
+
+/// Parse one top-level `(symbol "Name" ...)` definition including its unit sub-symbols
+pub fn parse_lib_symbol(node: &SExpr) -> Symbol {
+    let pin_names = node.child("pin_names");
+    let mut symbol = Symbol {
+        name: node.arg_str(0).unwrap_or_default().to_string(),
+        extends: node.child_str("extends").map(str::to_string),
+        pin_names_offset: pin_names.and_then(|p| length(p, "offset")).unwrap_or(Length::mm(0.508)),
+        pin_names_hidden: pin_names.map_or(false, |p| p.flag("hide")),
+        pin_numbers_hidden: node.child("pin_numbers").map_or(false, |p| p.flag("hide")),
+        power: node.child("power").is_some(),
+        in_bom: node.child_bool("in_bom").unwrap_or(true),
+        on_board: node.child_bool("on_board").unwrap_or(true),
+        exclude_from_sim: node.child_bool("exclude_from_sim").unwrap_or(false),
+        unit_count: 1,
+        unit_names: HashMap::new(),
+        properties: node
+            .children("property")
+            .enumerate()
+            .map(|(i, p)| parse_property(p, i as i32))
+            .collect(),
+        pins: Vec::new(),
+        rectangles: Vec::new(),
+        circles: Vec::new(),
+        arcs: Vec::new(),
+        polylines: Vec::new(),
+    };
+
+    add_drawings(&mut symbol, node, 0, 0);
+    for unit in node.children("symbol") {
+        let (number, body_style) = unit.arg_str(0).map(unit_suffix).unwrap_or((0, 0));
+        if let Some(name) = unit.child_str("unit_name") {
+            symbol.unit_names.insert(number, name.to_string());
+        }
+        symbol.unit_count = symbol.unit_count.max(number);
+        add_drawings(&mut symbol, unit, number, body_style);
+    }
+    symbol
+}
+
+/// Resolve `extends` inheritance for every symbol of a library in place
+///
+/// Derived symbols receive the pins, graphics and missing properties of their
+/// parent, following chains of derivation. Symbols whose parent is missing, or
+/// that form a cycle, are left as parsed.
+pub fn resolve_extends(symbols: &mut [Symbol]) {
+    let resolved: Vec<Option<Symbol>> = symbols
+        .iter()
+        .map(|s| s.extends.as_ref().and_then(|_| resolve_symbol(s, symbols)))
+        .collect();
+    for (symbol, resolved) in symbols.iter_mut().zip(resolved) {
+        if let Some(resolved) = resolved {
+            *symbol = resolved;
+        }
+    }
+}
+
+/// Fully resolved copy of a derived symbol, looking parents up by name in `library`
+///
+/// Returns `None` when a parent is missing or the derivation chain is cyclic.
+pub fn resolve_symbol(symbol: &Symbol, library: &[Symbol]) -> Option<Symbol> {
+    let mut chain = vec![symbol];
+    while let Some(parent_name) = &chain[chain.len() - 1].extends {
+        let parent = library.iter().find(|s| &s.name == parent_name)?;
+        if chain.iter().any(|s| s.name == parent.name) {
+            return None;
+        }
+        chain.push(parent);
+    }
+
+    let mut resolved = chain.pop()?.clone();
+    while let Some(child) = chain.pop() {
+        let mut derived = child.clone();
+        derived.inherit_from(&resolved);
+        resolved = derived;
+    }
+    Some(resolved)
+}
+
+/// Unit and body style from a sub-symbol name such as `"R_0805_1_1"`
+fn unit_suffix(name: &str) -> (u32, u32) {
+    let mut parts = name.rsplitn(3, '_');
+    let body_style = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let unit = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    (unit, body_style)
+}
+
+fn add_drawings(symbol: &mut Symbol, node: &SExpr, unit: u32, body_style: u32) {
+    for item in node.lists() {
+        match item.name() {
+            Some("pin") => symbol.pins.push(parse_pin(item, unit, body_style)),
+            Some("rectangle") => symbol.rectangles.push(Rectangle {
+                start: point(item, "start"),
+                end: point(item, "end"),
+                stroke: stroke(item),
+                fill: fill(item),
+                unit,
+                body_style,
+            }),
+            Some("circle") => symbol.circles.push(Circle {
+                center: point(item, "center"),
+                radius: length(item, "radius").unwrap_or_default(),
+                stroke: stroke(item),
+                fill: fill(item),
+                unit,
+                body_style,
+            }),
+            Some("arc") => symbol.arcs.push(SymbolArc {
+                start: point(item, "start"),
+                mid: point(item, "mid"),
+                end: point(item, "end"),
+                stroke: stroke(item),
+                fill: fill(item),
+                unit,
+                body_style,
+            }),
+            Some("polyline") | Some("bezier") => symbol.polylines.push(Polyline {
+                points: item.pts().into_iter().map(|(x, y)| Point::new(x, y)).collect(),
+                stroke: stroke(item),
+                fill: fill(item),
+                unit,
+                body_style,
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Parse `(pin TYPE SHAPE (at x y angle) (length l) (name "N" ..) (number "1" ..))`
+fn parse_pin(node: &SExpr, unit: u32, body_style: u32) -> Pin {
+    let at = node.child("at");
+    let name = node.child("name");
+    let number = node.child("number");
+    Pin {
+        number: number.and_then(|n| n.arg_str(0)).unwrap_or_default().to_string(),
+        name: name.and_then(|n| n.arg_str(0)).unwrap_or_default().to_string(),
+        pin_type: node.arg_str(0).unwrap_or("unspecified").to_string(),
+        shape: node.arg_str(1).unwrap_or("line").to_string(),
+        at: Point::new(
+            at.and_then(|a| a.arg_f64(0)).unwrap_or(0.0),
+            at.and_then(|a| a.arg_f64(1)).unwrap_or(0.0),
+        ),
+        length: length(node, "length").unwrap_or_default(),
+        rotation: at.and_then(|a| a.arg_f64(2)).unwrap_or(0.0),
+        hidden: node.flag("hide"),
+        unit,
+        body_style,
+        name_effects: name.and_then(effects),
+        number_effects: number.and_then(effects),
+    }
+}
+
+/// Parse `(property "Name" "Value" (at ..) (effects ..))`; `index` stands in for the
+/// `(id N)` field dropped in KiCad 8
+fn parse_property(node: &SExpr, index: i32) -> Property {
+    let mut effects = effects(node);
+    // KiCad 8 moved the hide flag from the effects to the property itself
+    if let Some(e) = effects.as_mut() {
+        e.hide |= node.flag("hide");
+    }
+    Property {
+        name: node.arg_str(0).unwrap_or_default().to_string(),
+        value: node.arg_str(1).unwrap_or_default().to_string(),
+        id: node.child_f64("id").map_or(index, |id| id as i32),
+        at: point(node, "at"),
+        effects,
+    }
+}
+
+fn effects(node: &SExpr) -> Option<Effects> {
+    let effects = node.child("effects")?;
+    let font = effects.child("font");
+    Some(Effects {
+        font: Font {
+            size: font.map(|f| point(f, "size")).unwrap_or(Point::new(1.27, 1.27)),
+            thickness: font.and_then(|f| length(f, "thickness")),
+            bold: font.map_or(false, |f| f.flag("bold")),
+            italic: font.map_or(false, |f| f.flag("italic")),
+        },
+        justify: effects.child("justify").map(|j| {
+            j.args()
+                .iter()
+                .filter_map(SExpr::as_str)
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+        hide: effects.flag("hide"),
+    })
+}
+
+fn stroke(node: &SExpr) -> Stroke {
+    let stroke = node.child("stroke");
+    Stroke {
+        width: stroke.and_then(|s| length(s, "width")).unwrap_or_default(),
+        stroke_type: stroke
+            .and_then(|s| s.child_str("type"))
+            .unwrap_or("default")
+            .to_string(),
+        color: stroke.and_then(color),
+    }
+}
+
+fn fill(node: &SExpr) -> Fill {
+    let fill = node.child("fill");
+    Fill {
+        fill_type: fill.and_then(|f| f.child_str("type")).unwrap_or("none").to_string(),
+        color: fill.and_then(color),
+    }
+}
+
+/// Parse `(color R G B A)`; an all-zero color means "use the default" and maps to `None`
+fn color(node: &SExpr) -> Option<Color> {
+    let c = node.child("color")?;
+    let channel = |i| c.arg_f64(i).unwrap_or(0.0);
+    let color = Color {
+        r: channel(0) as u8,
+        g: channel(1) as u8,
+        b: channel(2) as u8,
+        a: (channel(3) * 255.0).round() as u8,
+    };
+    if color == (Color { r: 0, g: 0, b: 0, a: 0 }) {
+        None
+    } else {
+        Some(color)
+    }
+}
+
+fn point(node: &SExpr, name: &str) -> Point {
+    node.child_xy(name)
+        .map(|(x, y)| Point::new(x, y))
+        .unwrap_or_default()
+}
+
+/// Length in mm from a `(name value)` child
+fn length(node: &SExpr, name: &str) -> Option<Length> {
+    node.child_f64(name).map(Length::mm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pcb::types::PinOrientation;
+
+    const LIBRARY: &str = r##"(kicad_symbol_lib
+  (version 20231120)
+  (generator "kicad_symbol_editor")
+  (symbol "LM358"
+    (pin_names (offset 0.127))
+    (exclude_from_sim no) (in_bom yes) (on_board yes)
+    (property "Reference" "U" (at 0 5.08 0) (effects (font (size 1.27 1.27)) (justify left)))
+    (property "Footprint" "" (at 0 0 0) (effects (font (size 1.27 1.27)) (hide yes)))
+    (symbol "LM358_0_1"
+      (polyline (pts (xy -5.08 5.08) (xy 5.08 0) (xy -5.08 -5.08) (xy -5.08 5.08))
+        (stroke (width 0.254) (type default)) (fill (type background)))
+    )
+    (symbol "LM358_1_1"
+      (pin output line (at 7.62 0 180) (length 2.54)
+        (name "~" (effects (font (size 1.27 1.27))))
+        (number "1" (effects (font (size 1.27 1.27)))))
+      (pin input line (at -7.62 2.54 0) (length 2.54) (name "+") (number "3"))
+    )
+    (symbol "LM358_2_1"
+      (unit_name "B")
+      (pin input inverted (at -7.62 -2.54 0) (length 2.54) hide (name "-") (number "6"))
+    )
+    (symbol "LM358_3_1"
+      (rectangle (start -2.54 2.54) (end 2.54 -2.54)
+        (stroke (width 0) (type dash) (color 255 0 0 0.5)) (fill (type none)))
+      (circle (center 0 0) (radius 1) (stroke (width 0.1) (type default)) (fill (type outline)))
+      (arc (start 1 0) (mid 0 1) (end -1 0) (stroke (width 0) (type default)) (fill (type none)))
+      (pin power_in line (at 0 7.62 270) (length 2.54) (name "V+" (effects (font (size 1.27 1.27)))) (number "8" (effects (font (size 1.27 1.27)))))
+    )
+  )
+  (symbol "GND" (power) (pin_numbers hide) (pin_names (offset 0) hide)
+    (property "Reference" "#PWR" (id 0) (at 0 -6.35 0) (effects (font (size 1.27 1.27)) hide))
+  )
+)"##;
+
+    #[test]
+    fn test_parse_symbol_lib_full() {
+        let symbols = parse_symbol_lib_full(LIBRARY).unwrap();
+        assert_eq!(symbols.len(), 2);
+
+        let opamp = &symbols[0];
+        assert_eq!(opamp.name, "LM358");
+        assert_eq!(opamp.pin_names_offset, Length::mm(0.127));
+        assert_eq!(opamp.unit_count, 3);
+        assert_eq!(opamp.unit_names.get(&2).map(String::as_str), Some("B"));
+        assert!(opamp.in_bom && opamp.on_board && !opamp.power);
+
+        assert_eq!(opamp.properties.len(), 2);
+        assert_eq!(opamp.properties[1].id, 1);
+        assert!(opamp.properties[1].effects.as_ref().unwrap().hide);
+        assert_eq!(opamp.properties[0].effects.as_ref().unwrap().justify.as_deref(), Some("left"));
+
+        assert_eq!(opamp.pins.len(), 4);
+        let out = &opamp.pins[0];
+        assert_eq!((out.number.as_str(), out.name.as_str()), ("1", "~"));
+        assert_eq!((out.pin_type.as_str(), out.shape.as_str()), ("output", "line"));
+        assert_eq!((out.unit, out.body_style), (1, 1));
+        assert_eq!(out.at, Point::new(7.62, 0.0));
+        assert_eq!((out.length, out.rotation), (Length::mm(2.54), 180.0));
+        let inverting = &opamp.pins[2];
+        assert_eq!(inverting.shape, "inverted");
+        assert!(inverting.hidden);
+        assert_eq!(inverting.unit, 2);
+
+        assert_eq!(opamp.polylines[0].points.len(), 4);
+        assert_eq!(opamp.polylines[0].fill.fill_type, "background");
+        let rect = &opamp.rectangles[0];
+        assert_eq!(rect.stroke.stroke_type, "dash");
+        assert_eq!(rect.stroke.color, Some(Color { r: 255, g: 0, b: 0, a: 128 }));
+        assert_eq!(opamp.circles[0].radius, Length::mm(1.0));
+        assert_eq!(opamp.arcs[0].mid, Point::new(0.0, 1.0));
+
+        let gnd = &symbols[1];
+        assert!(gnd.power && gnd.pin_names_hidden && gnd.pin_numbers_hidden);
+        assert!(gnd.properties[0].effects.as_ref().unwrap().hide);
+    }
+
+    #[test]
+    fn test_pin_api() {
+        let symbols = parse_symbol_lib_full(r#"(symbol "74LS00"
+  (symbol "74LS00_0_0" (pin power_in line (at 0 12.7 270) (length 5.08) (name "VCC") (number "14")))
+  (symbol "74LS00_1_1" (pin input line (at -7.62 2.54 0) (length 3.81) (name "A") (number "1")))
+  (symbol "74LS00_1_2" (pin input inverted (at -7.62 2.54 0) (length 3.81) (name "A") (number "1")))
+  (symbol "74LS00_2_1" (pin output line (at 7.62 0 180) (length 3.81) (name "Y") (number "6")))
+)"#).unwrap();
+        let gate = &symbols[0];
+
+        assert_eq!(gate.pins.len(), 4);
+        let numbers: Vec<_> = gate.pins().iter().map(|p| p.number.as_str()).collect();
+        assert_eq!(numbers, vec!["14", "1", "6"]);
+        assert_eq!(gate.pins()[1].shape, "line");
+
+        let unit2: Vec<_> = gate.unit_pins(2).iter().map(|p| p.number.as_str()).collect();
+        assert_eq!(unit2, vec!["14", "6"]);
+
+        let vcc = gate.pin("14").unwrap();
+        assert_eq!(vcc.orientation(), PinOrientation::Down);
+        assert_eq!(gate.pin("6").unwrap().orientation(), PinOrientation::Left);
+        let end = gate.pin("1").unwrap().body_end();
+        assert!((end.x.as_mm() + 3.81).abs() < 1e-9 && (end.y.as_mm() - 2.54).abs() < 1e-9);
+        let end = vcc.body_end();
+        assert!(end.x.abs().as_mm() < 1e-9 && (end.y.as_mm() - 7.62).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resolve_extends() {
+        let mut symbols = parse_symbol_lib_full(r#"(kicad_symbol_lib
+  (symbol "LM358" (pin_names (offset 0.127))
+    (property "Reference" "U") (property "Value" "LM358") (property "Datasheet" "lm358.pdf")
+    (symbol "LM358_1_1" (pin output line (at 7.62 0 180) (length 2.54) (name "~") (number "1")))
+    (symbol "LM358_2_1" (pin output line (at 7.62 0 180) (length 2.54) (name "~") (number "7"))))
+  (symbol "LM2904" (extends "LM358") (property "Value" "LM2904"))
+  (symbol "LM2904V" (extends "LM2904") (property "Value" "LM2904V") (property "Grade" "Automotive"))
+  (symbol "Orphan" (extends "Missing") (property "Value" "Orphan"))
+  (symbol "A" (extends "B")) (symbol "B" (extends "A"))
+)"#).unwrap();
+        assert_eq!(symbols[1].extends.as_deref(), Some("LM358"));
+        assert!(symbols[1].pins.is_empty());
+
+        resolve_extends(&mut symbols);
+        let derived = &symbols[2];
+        assert_eq!(derived.pins.len(), 2);
+        assert_eq!(derived.unit_count, 2);
+        assert_eq!(derived.pin_names_offset, Length::mm(0.127));
+        let value = |s: &Symbol, name: &str| {
+            s.properties.iter().find(|p| p.name == name).map(|p| p.value.clone())
+        };
+        assert_eq!(value(derived, "Value").as_deref(), Some("LM2904V"));
+        assert_eq!(value(derived, "Datasheet").as_deref(), Some("lm358.pdf"));
+        assert_eq!(value(derived, "Grade").as_deref(), Some("Automotive"));
+        assert_eq!(value(&symbols[1], "Value").as_deref(), Some("LM2904"));
+        assert_eq!(derived.property("Datasheet"), Some("lm358.pdf"));
+        assert_eq!(derived.property_map().len(), 4);
+
+        assert!(symbols[3].pins.is_empty());
+        assert!(resolve_symbol(&symbols[4], &symbols).is_none());
+    }
+
+    #[test]
+    fn test_parse_symbol_library() {
+        let library = include_str!("../../assets/example_components.kicad_sym");
+        let lib = parse_symbol_library("example", library).unwrap();
+        assert_eq!(lib.name, "example");
+        assert_eq!(lib.version, "20231120");
+        assert!(lib.symbol("R_0805").is_some());
+        assert!(parse_symbol_library("x", "(kicad_pcb)").is_err());
+    }
+
+    #[test]
+    fn test_bare_symbol_and_example_library() {
+        let symbols = parse_symbol_lib_full(r#"(symbol "R" (symbol "R_1_1" (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1"))))"#).unwrap();
+        assert_eq!(symbols[0].pins.len(), 1);
+        assert_eq!(symbols[0].unit_count, 1);
+
+        let library = include_str!("../../assets/example_components.kicad_sym");
+        let symbols = parse_symbol_lib_full(library).unwrap();
+        assert!(symbols.iter().all(|s| !s.name.is_empty()));
+        assert!(symbols.iter().any(|s| !s.pins.is_empty()));
+        assert_eq!(symbols[0].description(), Some("Resistor SMD 0805"));
+        assert_eq!(symbols[0].keywords(), vec!["R", "res", "resistor"]);
+        assert_eq!(symbols[0].fp_filters(), vec!["R_*"]);
+    }
+}
@@ -0,0 +1,198 @@
+//! Merging symbol libraries
+//!
+//! Combines several [`SymbolLib`]s into one, collapsing identical copies of a
+//! symbol and detecting symbols that share a name but differ in content.
+//!
+//! ```rust
+//! use kiparse::symbol::{merge_with, parse_symbol_library, ConflictPolicy};
+//!
+//! let a = parse_symbol_library("a", r#"(kicad_symbol_lib (symbol "R" (property "Value" "R")))"#)?;
+//! let b = parse_symbol_library("b", r#"(kicad_symbol_lib (symbol "R" (property "Value" "10k")))"#)?;
+//! let (merged, report) = merge_with(&[a, b], ConflictPolicy::Rename);
+//!
+//! assert_eq!(report.conflicts[0].name, "R");
+//! assert!(merged.symbol("R").is_some() && merged.symbol("R_b").is_some());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use crate::pcb::types::Symbol;
+use crate::symbol::types::SymbolLib;
+
+/// How to resolve symbols with the same name but different content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the symbol from the earliest library
+    KeepFirst,
+    /// Keep the symbol from the latest library
+    KeepLast,
+    /// Keep all versions; later ones get a `_<library name>` suffix
+    Rename,
+}
+
+/// Symbols that share a name but differ in content
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolConflict {
+    pub name: String,
+    /// Libraries defining a differing version, in input order
+    pub libraries: Vec<String>,
+}
+
+/// What happened while merging
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MergeReport {
+    /// Names of symbols that appeared more than once with identical content
+    pub duplicates: Vec<String>,
+    pub conflicts: Vec<SymbolConflict>,
+}
+
+/// Merge libraries, keeping the first version of conflicting symbols
+pub fn merge(libs: &[SymbolLib]) -> SymbolLib {
+    merge_with(libs, ConflictPolicy::KeepFirst).0
+}
+
+/// Merge libraries and report duplicates and conflicts
+///
+/// Identical symbols (same name and content hash) are kept once. The merged
+/// library takes its version and generator from the first input. With
+/// [`ConflictPolicy::Rename`], `extends` references inside a library follow its
+/// renamed symbols.
+pub fn merge_with(libs: &[SymbolLib], policy: ConflictPolicy) -> (SymbolLib, MergeReport) {
+    let mut merged = SymbolLib {
+        name: "merged".to_string(),
+        version: libs.first().map(|l| l.version.clone()).unwrap_or_default(),
+        generator: libs.first().map(|l| l.generator.clone()).unwrap_or_default(),
+        symbols: Vec::new(),
+    };
+    let mut report = MergeReport::default();
+    // Name -> (index in merged.symbols, content hashes seen so far)
+    let mut seen: HashMap<String, (usize, Vec<u64>)> = HashMap::new();
+
+    for lib in libs {
+        let mut renames = HashMap::new();
+        // Positions in the merged library holding this library's symbols
+        let mut added = Vec::new();
+        for symbol in &lib.symbols {
+            let hash = content_hash(symbol);
+            let (index, hashes) = match seen.get_mut(&symbol.name) {
+                None => {
+                    seen.insert(symbol.name.clone(), (merged.symbols.len(), vec![hash]));
+                    added.push(merged.symbols.len());
+                    merged.symbols.push(symbol.clone());
+                    continue;
+                }
+                Some(entry) => entry,
+            };
+            if hashes.contains(&hash) {
+                report.duplicates.push(symbol.name.clone());
+                continue;
+            }
+            hashes.push(hash);
+            let index = *index;
+
+            match report.conflicts.iter_mut().find(|c| c.name == symbol.name) {
+                Some(conflict) => conflict.libraries.push(lib.name.clone()),
+                None => report.conflicts.push(SymbolConflict {
+                    name: symbol.name.clone(),
+                    libraries: vec![source_of(libs, &symbol.name, lib), lib.name.clone()],
+                }),
+            }
+            match policy {
+                ConflictPolicy::KeepFirst => {}
+                ConflictPolicy::KeepLast => {
+                    added.push(index);
+                    merged.symbols[index] = symbol.clone();
+                }
+                ConflictPolicy::Rename => {
+                    let renamed = format!("{}_{}", symbol.name, lib.name);
+                    renames.insert(symbol.name.clone(), renamed.clone());
+                    let mut copy = symbol.clone();
+                    copy.name = renamed.clone();
+                    seen.insert(renamed, (merged.symbols.len(), vec![hash]));
+                    added.push(merged.symbols.len());
+                    merged.symbols.push(copy);
+                }
+            }
+        }
+
+        // Derived symbols of this library must point at its renamed parents
+        for index in added {
+            if let Some(parent) = merged.symbols[index].extends.as_mut() {
+                if let Some(renamed) = renames.get(parent) {
+                    *parent = renamed.clone();
+                }
+            }
+        }
+    }
+
+    (merged, report)
+}
+
+/// Name of the earliest library that defines `name`, before `current`
+fn source_of(libs: &[SymbolLib], name: &str, current: &SymbolLib) -> String {
+    libs.iter()
+        .take_while(|l| !std::ptr::eq(*l, current))
+        .find(|l| l.symbol(name).is_some())
+        .map(|l| l.name.clone())
+        .unwrap_or_default()
+}
+
+/// Hash of a symbol's content, ignoring its name
+pub fn content_hash(symbol: &Symbol) -> u64 {
+    let mut copy = symbol.clone();
+    copy.name.clear();
+    // Unit names live in a HashMap whose iteration order is not stable
+    let mut unit_names: Vec<_> = copy.unit_names.drain().collect();
+    unit_names.sort();
+    let mut hasher = DefaultHasher::new();
+    format!("{:?}{:?}", copy, unit_names).hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::lib_parser::parse_symbol_library;
+
+    fn lib(name: &str, content: &str) -> SymbolLib {
+        parse_symbol_library(name, &format!("(kicad_symbol_lib {})", content)).unwrap()
+    }
+
+    #[test]
+    fn test_merge_duplicates_and_conflicts() {
+        let r = r#"(symbol "R" (property "Value" "R") (symbol "R_1_1" (pin passive line (at 0 0 0) (length 1) (name "~") (number "1"))))"#;
+        let a = lib("a", &format!(r#"{} (symbol "C" (property "Value" "C"))"#, r));
+        let b = lib("b", &format!(r#"{} (symbol "C" (property "Value" "C_Polarized"))"#, r));
+        let c = lib("c", r#"(symbol "C" (property "Value" "C_Small")) (symbol "L" (property "Value" "L"))"#);
+        let libs = [a, b, c];
+
+        let (merged, report) = merge_with(&libs, ConflictPolicy::KeepFirst);
+        let names: Vec<_> = merged.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["R", "C", "L"]);
+        assert_eq!(report.duplicates, vec!["R"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].libraries, vec!["a", "b", "c"]);
+        assert_eq!(merged.symbol("C").unwrap().property("Value"), Some("C"));
+        assert_eq!(merge(&libs), merged);
+
+        let (merged, _) = merge_with(&libs, ConflictPolicy::KeepLast);
+        assert_eq!(merged.symbol("C").unwrap().property("Value"), Some("C_Small"));
+
+        let (merged, _) = merge_with(&libs, ConflictPolicy::Rename);
+        let names: Vec<_> = merged.symbols.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["R", "C", "C_b", "C_c", "L"]);
+    }
+
+    #[test]
+    fn test_rename_follows_extends() {
+        let a = lib("a", r#"(symbol "OPAMP" (property "Value" "A"))"#);
+        let b = lib("b", r#"(symbol "OPAMP" (property "Value" "B")) (symbol "TL072" (extends "OPAMP"))"#);
+
+        let (merged, _) = merge_with(&[a, b], ConflictPolicy::Rename);
+        assert_eq!(merged.symbol("TL072").unwrap().extends.as_deref(), Some("OPAMP_b"));
+        assert!(merged.symbol("OPAMP_b").is_some());
+    }
+}
@@ -22,8 +22,44 @@
 //! ```
 
 pub mod types;
+#[cfg(feature = "std")]
 pub mod symbol_parser;
+pub mod lib_parser;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(feature = "std")]
+pub mod writer;
+#[cfg(feature = "std")]
+pub mod check;
+#[cfg(feature = "std")]
+pub mod geometry;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod pinout;
+#[cfg(feature = "std")]
+pub mod footprint_check;
 
 // Re-export commonly used items
 pub use types::*;
-pub use symbol_parser::parse_symbol_lib;
\ No newline at end of file
+#[cfg(feature = "std")]
+pub use symbol_parser::parse_symbol_lib;
+pub use lib_parser::{
+    parse_lib_symbol, parse_symbol_lib_full, parse_symbol_library, resolve_extends, resolve_symbol,
+};
+#[cfg(feature = "std")]
+pub use merge::{merge, merge_with, ConflictPolicy, MergeReport, SymbolConflict};
+#[cfg(feature = "std")]
+pub use query::SymbolQuery;
+#[cfg(feature = "std")]
+pub use writer::write_symbol_library;
+#[cfg(feature = "std")]
+pub use check::{check_symbol, SymbolCheck, SymbolIssue};
+#[cfg(feature = "std")]
+pub use diff::{diff_libraries, diff_symbols, LibraryDiff, SymbolDiff};
+#[cfg(feature = "std")]
+pub use pinout::{PinSide, Pinout};
+#[cfg(feature = "std")]
+pub use footprint_check::{check_footprint, FootprintSet};
\ No newline at end of file
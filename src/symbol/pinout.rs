@@ -0,0 +1,258 @@
+//! Pinout tables of library symbols
+//!
+//! [`Symbol::pinout`] lists the pins of each unit by the side of the body they
+//! leave from, top to bottom and left to right, the way a datasheet pinout
+//! table reads. The [`Pinout`] can be used as data or rendered with
+//! [`Pinout::to_markdown`] for documentation and [`Pinout::to_text`] for a
+//! terminal.
+//!
+//! ```rust
+//! use kiparse::symbol::{parse_symbol_library, pinout::PinSide};
+//!
+//! let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+//!   (symbol "BUF" (symbol "BUF_1_1"
+//!     (pin output line (at 5.08 0 180) (length 2.54) (name "Y") (number "2"))
+//!     (pin input line (at -5.08 0 0) (length 2.54) (name "A") (number "1"))
+//!     (pin power_in line (at 0 5.08 270) (length 2.54) (name "VCC") (number "5")))))"#)?;
+//!
+//! let pinout = lib.symbols[0].pinout();
+//! let sides: Vec<PinSide> = pinout.units[0].pins.iter().map(|p| p.side).collect();
+//! assert_eq!(sides, [PinSide::Left, PinSide::Right, PinSide::Top]);
+//! assert!(pinout.to_markdown().contains("| Left | 1 | A | input |"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+
+use crate::pcb::types::{Pin, PinOrientation, Symbol};
+
+/// Side of the symbol body a pin leaves from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum PinSide {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl PinSide {
+    /// Side of a pin, from the direction it points in towards the body
+    pub fn of(pin: &Pin) -> Self {
+        match pin.orientation() {
+            PinOrientation::Right => PinSide::Left,
+            PinOrientation::Left => PinSide::Right,
+            PinOrientation::Down => PinSide::Top,
+            PinOrientation::Up => PinSide::Bottom,
+        }
+    }
+}
+
+impl fmt::Display for PinSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PinSide::Left => "Left",
+            PinSide::Right => "Right",
+            PinSide::Top => "Top",
+            PinSide::Bottom => "Bottom",
+        })
+    }
+}
+
+/// One row of a pinout table
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinoutPin {
+    pub number: String,
+    pub name: String,
+    /// Electrical type, e.g. `input`, `power_in`
+    pub pin_type: String,
+    pub shape: String,
+    pub side: PinSide,
+    pub hidden: bool,
+}
+
+/// Pins of one unit, by side
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinoutUnit {
+    /// Unit number; 0 for pins common to all units
+    pub unit: u32,
+    /// `A`, `B`, ... or the custom unit name; `all` for unit 0
+    pub label: String,
+    pub pins: Vec<PinoutPin>,
+}
+
+/// Pinout of a symbol, see the [module docs](self)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Pinout {
+    pub symbol: String,
+    /// Units in number order; common pins (unit 0) come first
+    pub units: Vec<PinoutUnit>,
+}
+
+impl Symbol {
+    /// Pins grouped by unit, then by side of the body
+    ///
+    /// Pins repeated on the De Morgan body style are listed once. Within a side,
+    /// pins are ordered top to bottom or left to right.
+    pub fn pinout(&self) -> Pinout {
+        let mut pins = self.pins();
+        pins.sort_by(|a, b| {
+            let (side_a, side_b) = (PinSide::of(a), PinSide::of(b));
+            a.unit.cmp(&b.unit).then(side_a.cmp(&side_b)).then_with(|| match side_a {
+                PinSide::Left | PinSide::Right => b.at.y.total_cmp(&a.at.y),
+                PinSide::Top | PinSide::Bottom => a.at.x.total_cmp(&b.at.x),
+            })
+        });
+        let mut units: Vec<PinoutUnit> = Vec::new();
+        for pin in pins {
+            if units.last().map_or(true, |u| u.unit != pin.unit) {
+                units.push(PinoutUnit { unit: pin.unit, label: self.unit_label(pin.unit), pins: Vec::new() });
+            }
+            let unit = units.last_mut().expect("unit pushed above");
+            unit.pins.push(PinoutPin {
+                number: pin.number.clone(),
+                name: pin.name.clone(),
+                pin_type: pin.pin_type.clone(),
+                shape: pin.shape.clone(),
+                side: PinSide::of(pin),
+                hidden: pin.hidden,
+            });
+        }
+        Pinout { symbol: self.name.clone(), units }
+    }
+
+    fn unit_label(&self, unit: u32) -> String {
+        match (unit, self.unit_names.get(&unit)) {
+            (0, _) => "all".to_string(),
+            (_, Some(name)) => name.clone(),
+            (n, None) if n <= 26 => char::from(b'A' + (n - 1) as u8).to_string(),
+            (n, None) => n.to_string(),
+        }
+    }
+}
+
+impl Pinout {
+    /// Header and cells of each pin, shared by the Markdown and text layouts
+    ///
+    /// The unit column is left out when all pins belong to one unit.
+    pub fn rows(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let with_unit = self.units.len() > 1;
+        let mut header: Vec<String> = Vec::new();
+        if with_unit {
+            header.push("Unit".to_string());
+        }
+        header.extend(["Side", "Pin", "Name", "Type"].iter().map(|h| h.to_string()));
+        let mut rows = Vec::new();
+        for unit in &self.units {
+            for pin in &unit.pins {
+                let mut row = Vec::new();
+                if with_unit {
+                    row.push(unit.label.clone());
+                }
+                let side = if pin.hidden { format!("{} (hidden)", pin.side) } else { pin.side.to_string() };
+                row.extend([side, pin.number.clone(), pin.name.clone(), pin.pin_type.clone()]);
+                rows.push(row);
+            }
+        }
+        (header, rows)
+    }
+
+    /// GitHub-flavoured Markdown table
+    pub fn to_markdown(&self) -> String {
+        let (header, rows) = self.rows();
+        let cell = |s: &str| s.replace('|', "\\|");
+        let mut md = format!("| {} |\n", header.join(" | "));
+        md.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+        for row in rows {
+            md.push_str(&format!("| {} |\n", row.iter().map(|c| cell(c)).collect::<Vec<_>>().join(" | ")));
+        }
+        md
+    }
+
+    /// Table with ASCII borders for a terminal
+    pub fn to_text(&self) -> String {
+        let (header, rows) = self.rows();
+        let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let rule: String = widths.iter().map(|w| format!("+{}", "-".repeat(w + 2))).collect::<String>() + "+\n";
+        let line = |cells: &[String]| {
+            let mut text: String = cells
+                .iter()
+                .zip(&widths)
+                .map(|(c, w)| format!("| {}{} ", c, " ".repeat(w - c.chars().count())))
+                .collect();
+            text.push_str("|\n");
+            text
+        };
+        let mut text = rule.clone();
+        text.push_str(&line(&header));
+        text.push_str(&rule);
+        for row in &rows {
+            text.push_str(&line(row));
+        }
+        text.push_str(&rule);
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::parse_symbol_library;
+
+    #[test]
+    fn test_pinout_by_unit_and_side() {
+        let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib
+  (symbol "LM358"
+    (symbol "LM358_0_1"
+      (pin power_in line (at 0 -7.62 90) (length 3.81) hide (name "V-") (number "4"))
+      (pin power_in line (at 0 7.62 270) (length 3.81) hide (name "V+") (number "8")))
+    (symbol "LM358_1_1"
+      (pin input line (at -7.62 -2.54 0) (length 2.54) (name "-") (number "2"))
+      (pin input line (at -7.62 2.54 0) (length 2.54) (name "+") (number "3"))
+      (pin output line (at 7.62 0 180) (length 2.54) (name "~") (number "1")))
+    (symbol "LM358_1_2"
+      (pin input line (at -7.62 2.54 0) (length 2.54) (name "+") (number "3")))
+    (symbol "LM358_2_1" (unit_name "B|")
+      (pin input line (at -7.62 2.54 0) (length 2.54) (name "+") (number "5")))))"#).unwrap();
+        let pinout = lib.symbols[0].pinout();
+
+        let order: Vec<(u32, &str, PinSide)> = pinout
+            .units
+            .iter()
+            .flat_map(|u| u.pins.iter().map(move |p| (u.unit, p.number.as_str(), p.side)))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (0, "8", PinSide::Top),
+                (0, "4", PinSide::Bottom),
+                (1, "3", PinSide::Left),
+                (1, "2", PinSide::Left),
+                (1, "1", PinSide::Right),
+                (2, "5", PinSide::Left),
+            ]
+        );
+        let labels: Vec<&str> = pinout.units.iter().map(|u| u.label.as_str()).collect();
+        assert_eq!(labels, ["all", "A", "B|"]);
+
+        let md = pinout.to_markdown();
+        assert!(md.starts_with("| Unit | Side | Pin | Name | Type |\n|---|---|---|---|---|\n"));
+        assert!(md.contains("| all | Top (hidden) | 8 | V+ | power_in |\n"));
+        assert!(md.contains("| B\\| | Left | 5 | + | input |\n"));
+
+        let text = pinout.to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 6 + 4);
+        assert_eq!(lines[0], "+------+-----------------+-----+------+----------+");
+        assert_eq!(lines[5], "| A    | Left            | 3   | +    | input    |");
+    }
+}
@@ -0,0 +1,214 @@
+//! Searching parsed symbol libraries
+//!
+//! ```rust
+//! use kiparse::symbol::{parse_symbol_library, SymbolQuery};
+//!
+//! let lib = parse_symbol_library("example", r#"(kicad_symbol_lib
+//!   (symbol "LM358" (property "ki_keywords" "dual opamp"))
+//!   (symbol "R" (property "ki_keywords" "resistor"))
+//! )"#)?;
+//! let found = SymbolQuery::new().name("LM*").keyword("opamp").run(&lib.symbols);
+//!
+//! assert_eq!(found.len(), 1);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::pcb::types::Symbol;
+
+/// Builder for filtering symbols; every criterion set must match
+#[derive(Debug, Clone, Default)]
+pub struct SymbolQuery {
+    name: Option<String>,
+    keywords: Vec<String>,
+    description: Option<String>,
+    min_pins: Option<usize>,
+    max_pins: Option<usize>,
+    footprint: Option<String>,
+}
+
+impl SymbolQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Symbol name matches a glob pattern (`*` and `?` wildcards, case-sensitive)
+    pub fn name(mut self, pattern: &str) -> Self {
+        self.name = Some(pattern.to_string());
+        self
+    }
+
+    /// `ki_keywords` contains this keyword (case-insensitive); may be repeated
+    pub fn keyword(mut self, keyword: &str) -> Self {
+        self.keywords.push(keyword.to_lowercase());
+        self
+    }
+
+    /// Description contains this text (case-insensitive)
+    pub fn description_contains(mut self, text: &str) -> Self {
+        self.description = Some(text.to_lowercase());
+        self
+    }
+
+    /// Exactly `count` pins, counted across units
+    pub fn pin_count(self, count: usize) -> Self {
+        self.min_pins(count).max_pins(count)
+    }
+
+    pub fn min_pins(mut self, count: usize) -> Self {
+        self.min_pins = Some(count);
+        self
+    }
+
+    pub fn max_pins(mut self, count: usize) -> Self {
+        self.max_pins = Some(count);
+        self
+    }
+
+    /// Symbol's footprint filters accept this footprint (`Name` or `Library:Name`)
+    ///
+    /// Symbols without filters accept every footprint, as in KiCad's footprint chooser.
+    pub fn footprint(mut self, footprint: &str) -> Self {
+        self.footprint = Some(footprint.to_string());
+        self
+    }
+
+    /// Whether `symbol` satisfies every criterion
+    pub fn matches(&self, symbol: &Symbol) -> bool {
+        if let Some(pattern) = &self.name {
+            if !glob_match(pattern, &symbol.name) {
+                return false;
+            }
+        }
+        if !self.keywords.is_empty() {
+            let keywords: Vec<String> = symbol.keywords().iter().map(|k| k.to_lowercase()).collect();
+            if !self.keywords.iter().all(|k| keywords.contains(k)) {
+                return false;
+            }
+        }
+        if let Some(text) = &self.description {
+            let description = symbol.description().unwrap_or_default().to_lowercase();
+            if !description.contains(text.as_str()) {
+                return false;
+            }
+        }
+        if self.min_pins.is_some() || self.max_pins.is_some() {
+            let pins = symbol.pins().len();
+            if self.min_pins.map_or(false, |min| pins < min) || self.max_pins.map_or(false, |max| pins > max) {
+                return false;
+            }
+        }
+        if let Some(footprint) = &self.footprint {
+            if !accepts_footprint(symbol, footprint) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Matching symbols, in input order
+    pub fn run<'a, I>(&self, symbols: I) -> Vec<&'a Symbol>
+    where
+        I: IntoIterator<Item = &'a Symbol>,
+    {
+        symbols.into_iter().filter(|s| self.matches(s)).collect()
+    }
+}
+
+/// Whether a symbol's `ki_fp_filters` accept `footprint`
+///
+/// Filters without a library part match the footprint name alone; filters with one
+/// (`Lib:Pattern`) need the full `Library:Name` form.
+pub fn accepts_footprint(symbol: &Symbol, footprint: &str) -> bool {
+    let filters = symbol.fp_filters();
+    if filters.is_empty() {
+        return true;
+    }
+    let name = footprint.rsplit(':').next().unwrap_or(footprint);
+    filters.iter().any(|filter| {
+        if filter.contains(':') {
+            glob_match(filter, footprint)
+        } else {
+            glob_match(filter, name)
+        }
+    })
+}
+
+/// Match `text` against a glob `pattern` supporting `*` (any run) and `?` (one character)
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried against
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::lib_parser::parse_symbol_lib_full;
+
+    const LIBRARY: &str = r#"(kicad_symbol_lib
+  (symbol "LM358" (property "Description" "Dual Operational Amplifier")
+    (property "ki_keywords" "dual opamp") (property "ki_fp_filters" "SOIC*3.9x4.9mm*P1.27mm* DIP*W7.62mm*")
+    (symbol "LM358_1_1" (pin output line (at 0 0 0) (length 1) (name "~") (number "1"))
+      (pin input line (at 0 0 0) (length 1) (name "+") (number "3")))
+    (symbol "LM358_3_1" (pin power_in line (at 0 0 0) (length 1) (name "V+") (number "8"))))
+  (symbol "TL074" (property "Description" "Quad JFET-Input Operational Amplifier")
+    (property "ki_keywords" "quad opamp") (property "ki_fp_filters" "Package_SO:SOIC*"))
+  (symbol "R" (property "Description" "Resistor") (property "ki_keywords" "R res resistor"))
+)"#;
+
+    fn names(symbols: Vec<&Symbol>) -> Vec<&str> {
+        symbols.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let symbols = parse_symbol_lib_full(LIBRARY).unwrap();
+
+        assert_eq!(names(SymbolQuery::new().run(&symbols)), vec!["LM358", "TL074", "R"]);
+        assert_eq!(names(SymbolQuery::new().name("?L*").run(&symbols)), vec!["TL074"]);
+        assert_eq!(names(SymbolQuery::new().keyword("OPAMP").run(&symbols)), vec!["LM358", "TL074"]);
+        assert_eq!(names(SymbolQuery::new().keyword("opamp").keyword("dual").run(&symbols)), vec!["LM358"]);
+        assert_eq!(names(SymbolQuery::new().description_contains("jfet").run(&symbols)), vec!["TL074"]);
+        assert_eq!(names(SymbolQuery::new().pin_count(3).run(&symbols)), vec!["LM358"]);
+        assert_eq!(names(SymbolQuery::new().min_pins(1).max_pins(2).run(&symbols)), Vec::<&str>::new());
+
+        let soic = SymbolQuery::new().footprint("Package_SO:SOIC-8_3.9x4.9mm_P1.27mm");
+        assert_eq!(names(soic.run(&symbols)), vec!["LM358", "TL074", "R"]);
+        let dip = SymbolQuery::new().footprint("DIP-8_W7.62mm");
+        assert_eq!(names(dip.run(&symbols)), vec!["LM358", "R"]);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("R_*", "R_0805"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXXbYYbc"));
+        assert!(glob_match("C?", "C1"));
+        assert!(!glob_match("C?", "C10"));
+        assert!(!glob_match("R_*", "C_0805"));
+    }
+}
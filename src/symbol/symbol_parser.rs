@@ -1,6 +1,7 @@
 use super::types::*;
 use crate::error::{KicadError, Result};
 use logos::Logos;
+use std::collections::HashMap;
 
 #[derive(Logos, Debug, PartialEq)]
 #[logos(skip r"[ \t\n\f]+")]
@@ -72,6 +73,7 @@ fn parse_symbol(lex: &mut logos::Lexer<Token>) -> Result<Option<Symbol>> {
     let mut symbol = Symbol {
         name: symbol_name,
         description: String::new(),
+        properties: HashMap::new(),
     };
     
     let mut depth = 1;
@@ -85,10 +87,8 @@ fn parse_symbol(lex: &mut logos::Lexer<Token>) -> Result<Option<Symbol>> {
                 // Check if this is a property element
                 if let Some(Ok(Token::Property)) = lex.next() {
                     depth -= 1; // We'll handle the closing paren in parse_property
-                    if let Some(description) = parse_property(lex)? {
-                        if symbol.description.is_empty() {
-                            symbol.description = description;
-                        }
+                    if let Some((name, value)) = parse_property(lex)? {
+                        symbol.properties.entry(name).or_insert(value);
                     }
                 } else {
                     // Skip other elements by consuming tokens until balanced
@@ -110,37 +110,34 @@ fn parse_symbol(lex: &mut logos::Lexer<Token>) -> Result<Option<Symbol>> {
         }
     }
     
+    // KiCad 6/7 libraries keep the description in ki_description
+    symbol.description = symbol
+        .properties
+        .get("Description")
+        .or_else(|| symbol.properties.get("ki_description"))
+        .cloned()
+        .unwrap_or_default();
+    
     Ok(Some(symbol))
 }
 
-fn parse_property(lex: &mut logos::Lexer<Token>) -> Result<Option<String>> {
-    // Expect property name
+fn parse_property(lex: &mut logos::Lexer<Token>) -> Result<Option<(String, String)>> {
+    // Expect property name and value; "Description" lexes as its own token when unquoted
     let property_name = match lex.next() {
-        Some(Ok(Token::String(s))) => s,
-        Some(Ok(Token::Ident(s))) => s,
-        _ => return Ok(None),
+        Some(Ok(Token::String(s))) => Some(s),
+        Some(Ok(Token::Ident(s))) => Some(s),
+        Some(Ok(Token::Description)) => Some("Description".to_string()),
+        _ => None,
+    };
+    let value = match lex.next() {
+        Some(Ok(Token::String(s))) => Some(s),
+        Some(Ok(Token::Ident(s))) => Some(s),
+        Some(Ok(Token::Number(n))) => Some(n.to_string()),
+        Some(Ok(Token::RParen)) => return Ok(None),
+        _ => None,
     };
     
-    // Check if this is a Description property
-    if property_name == "Description" {
-        // Expect property value
-        if let Some(Ok(Token::String(description))) = lex.next() {
-            // Skip to closing paren
-            let mut depth = 1;
-            while depth > 0 {
-                match lex.next() {
-                    Some(Ok(Token::LParen)) => depth += 1,
-                    Some(Ok(Token::RParen)) => depth -= 1,
-                    Some(Ok(_)) => {}
-                    Some(Err(_)) => {}
-                    None => break,
-                }
-            }
-            return Ok(Some(description));
-        }
-    }
-    
-    // Skip non-Description properties
+    // Skip the rest of the property (position, effects) up to its closing paren
     let mut depth = 1;
     while depth > 0 {
         match lex.next() {
@@ -152,7 +149,7 @@ fn parse_property(lex: &mut logos::Lexer<Token>) -> Result<Option<String>> {
         }
     }
     
-    Ok(None)
+    Ok(property_name.zip(value))
 }
 
 fn skip_element(lex: &mut logos::Lexer<Token>, depth: &mut i32) -> Result<()> {
@@ -230,4 +227,29 @@ mod tests {
         assert_eq!(symbols[0].name, "Unknown");
         assert_eq!(symbols[0].description, "");
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_property_map() {
+        let content = r#"
+        (symbol "R_0805"
+          (property "Reference" "R" (at 0 1.65 0) (effects (font (size 1.27 1.27))))
+          (property "Value" "R_0805" (at 0 -1.65 0))
+          (property "Footprint" "" (at 1.778 0 90) (effects (font (size 1.27 1.27)) (hide yes)))
+          (property "ki_keywords" "R res resistor")
+          (property "ki_fp_filters" "R_*")
+          (property "ki_description" "Resistor")
+          (property "Manufacturer" "Yageo")
+        )
+        "#;
+        
+        let symbols = parse_symbol_lib(content).unwrap();
+        let symbol = &symbols[0];
+        assert_eq!(symbol.properties.len(), 7);
+        assert_eq!(symbol.property("Reference"), Some("R"));
+        assert_eq!(symbol.property("Footprint"), Some(""));
+        assert_eq!(symbol.property("Manufacturer"), Some("Yageo"));
+        assert_eq!(symbol.keywords(), vec!["R", "res", "resistor"]);
+        assert_eq!(symbol.fp_filters(), vec!["R_*"]);
+        assert_eq!(symbol.description, "Resistor");
+    }
+}
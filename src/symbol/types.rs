@@ -1,30 +1,93 @@
-use serde::{Deserialize, Serialize};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::collections::HashMap;
+
+use crate::pcb::types::Symbol as LibSymbol;
+use crate::units::Length;
 
 /// A point in 2D space
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
-    pub x: f64,
-    pub y: f64,
+    pub x: Length,
+    pub y: Length,
 }
 
 /// A KiCad symbol definition
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Symbol {
     pub name: String,
     pub description: String,
+    /// Every property by name: Reference, Value, Footprint, Datasheet, `ki_keywords`,
+    /// `ki_fp_filters` and custom fields
+    pub properties: HashMap<String, String>,
+}
+
+impl Symbol {
+    /// Value of the named property
+    pub fn property(&self, name: &str) -> Option<&str> {
+        self.properties.get(name).map(String::as_str)
+    }
+
+    /// Search keywords from `ki_keywords`
+    pub fn keywords(&self) -> Vec<&str> {
+        self.property("ki_keywords")
+            .map(|k| k.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+
+    /// Footprint filter patterns from `ki_fp_filters`
+    pub fn fp_filters(&self) -> Vec<&str> {
+        self.property("ki_fp_filters")
+            .map(|f| f.split_whitespace().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// `Name - description`, or just the name without a description
+impl core::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.description.is_empty() {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{} - {}", self.name, self.description)
+        }
+    }
+}
+
+/// A parsed symbol library with full symbol definitions
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SymbolLib {
+    /// Library nickname, e.g. the file stem; not stored in the file itself
+    pub name: String,
+    pub version: String,
+    pub generator: String,
+    pub symbols: Vec<LibSymbol>,
+}
+
+impl SymbolLib {
+    /// Symbol with the given name
+    pub fn symbol(&self, name: &str) -> Option<&LibSymbol> {
+        self.symbols.iter().find(|s| s.name == name)
+    }
 }
 
 /// Font properties for text elements
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Font {
     pub size: Point,
-    pub thickness: Option<f64>,
+    pub thickness: Option<Length>,
     pub bold: bool,
     pub italic: bool,
 }
 
 /// Text effects including font and styling
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Effects {
     pub font: Font,
     pub justify: Option<String>,
@@ -32,7 +95,8 @@ pub struct Effects {
 }
 
 /// Color representation
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -41,15 +105,17 @@ pub struct Color {
 }
 
 /// Stroke properties for drawing elements
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stroke {
-    pub width: f64,
+    pub width: Length,
     pub stroke_type: String,
     pub color: Option<Color>,
 }
 
 /// Fill properties for drawing elements
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Fill {
     pub fill_type: String,
     pub color: Option<Color>,
@@ -0,0 +1,273 @@
+//! Symbol library writer
+//!
+//! Turns the rich [`Symbol`] model back into `.kicad_sym` text in KiCad 8's
+//! layout, so libraries built or converted in code (merged libraries, imported
+//! Altium symbols, ...) can be opened in KiCad. Drawings and pins go into the
+//! `NAME_unit_bodystyle` sub-symbols their unit and body style call for; derived
+//! symbols are written with only their `extends` reference and own properties.
+//!
+//! ```rust
+//! use kiparse::symbol::{parse_symbol_library, write_symbol_library};
+//!
+//! let lib = parse_symbol_library("demo", r#"(kicad_symbol_lib (version 20231120)
+//!   (symbol "R" (property "Reference" "R") (property "Value" "R")
+//!     (symbol "R_1_1" (pin passive line (at 0 3.81 270) (length 1.27) (name "~") (number "1")))))"#)?;
+//! let text = write_symbol_library(&lib);
+//!
+//! assert!(text.starts_with("(kicad_symbol_lib\n\t(version 20231120)\n\t(generator \"kiparse\")"));
+//! assert_eq!(parse_symbol_library("demo", &text)?.symbols, lib.symbols);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::pcb::types::{Color, Effects, Fill, Pin, Point, Property, Stroke, Symbol};
+use crate::sexpr::SExpr;
+use crate::symbol::types::SymbolLib;
+
+/// File format version written when the library does not carry a numeric one
+pub const DEFAULT_VERSION: &str = "20231120";
+
+/// `.kicad_sym` text for a whole library
+pub fn write_symbol_library(lib: &SymbolLib) -> String {
+    let version = if !lib.version.is_empty() && lib.version.chars().all(|c| c.is_ascii_digit()) {
+        lib.version.as_str()
+    } else {
+        DEFAULT_VERSION
+    };
+    let mut items = vec![
+        SExpr::list("version", vec![SExpr::atom(version)]),
+        SExpr::list("generator", vec![SExpr::string("kiparse")]),
+        SExpr::list("generator_version", vec![SExpr::string(crate::VERSION)]),
+    ];
+    items.extend(lib.symbols.iter().map(symbol_sexpr));
+    SExpr::list("kicad_symbol_lib", items).to_kicad_string()
+}
+
+impl SymbolLib {
+    /// The library as `.kicad_sym` text, see [`write_symbol_library`]
+    pub fn to_kicad_sym(&self) -> String {
+        write_symbol_library(self)
+    }
+}
+
+/// Top-level `(symbol "Name" ...)` definition with its unit sub-symbols
+pub fn symbol_sexpr(symbol: &Symbol) -> SExpr {
+    let mut items = vec![SExpr::string(&symbol.name)];
+    if let Some(parent) = &symbol.extends {
+        items.push(SExpr::list("extends", vec![SExpr::string(parent)]));
+    } else {
+        if symbol.power {
+            items.push(SExpr::list("power", vec![]));
+        }
+        if symbol.pin_numbers_hidden {
+            items.push(SExpr::list("pin_numbers", vec![hide()]));
+        }
+        let mut pin_names = vec![SExpr::list("offset", vec![SExpr::number(symbol.pin_names_offset.as_mm())])];
+        if symbol.pin_names_hidden {
+            pin_names.push(hide());
+        }
+        items.push(SExpr::list("pin_names", pin_names));
+    }
+    items.push(flag("exclude_from_sim", symbol.exclude_from_sim));
+    items.push(flag("in_bom", symbol.in_bom));
+    items.push(flag("on_board", symbol.on_board));
+    items.extend(symbol.properties.iter().map(property));
+    if symbol.extends.is_some() {
+        return SExpr::list("symbol", items);
+    }
+
+    // Sub-symbols keyed by (unit, body style); every unit gets one so the unit count survives
+    let mut units: BTreeMap<(u32, u32), Vec<SExpr>> = BTreeMap::new();
+    for unit in 1..=symbol.unit_count.max(1) {
+        units.insert((unit, 1), Vec::new());
+    }
+    let mut add = |unit: u32, body_style: u32, item: SExpr| {
+        units.entry((unit, body_style)).or_default().push(item);
+    };
+    for r in &symbol.rectangles {
+        let item = SExpr::list(
+            "rectangle",
+            vec![xy("start", &r.start), xy("end", &r.end), stroke(&r.stroke), fill(&r.fill)],
+        );
+        add(r.unit, r.body_style, item);
+    }
+    for c in &symbol.circles {
+        let item = SExpr::list(
+            "circle",
+            vec![
+                xy("center", &c.center),
+                SExpr::list("radius", vec![SExpr::number(c.radius.as_mm())]),
+                stroke(&c.stroke),
+                fill(&c.fill),
+            ],
+        );
+        add(c.unit, c.body_style, item);
+    }
+    for a in &symbol.arcs {
+        let item = SExpr::list(
+            "arc",
+            vec![xy("start", &a.start), xy("mid", &a.mid), xy("end", &a.end), stroke(&a.stroke), fill(&a.fill)],
+        );
+        add(a.unit, a.body_style, item);
+    }
+    for p in &symbol.polylines {
+        let pts = p.points.iter().map(|point| xy("xy", point)).collect();
+        let item = SExpr::list("polyline", vec![SExpr::list("pts", pts), stroke(&p.stroke), fill(&p.fill)]);
+        add(p.unit, p.body_style, item);
+    }
+    for pin in &symbol.pins {
+        add(pin.unit, pin.body_style, pin_sexpr(pin));
+    }
+
+    for ((unit, body_style), mut drawings) in units {
+        let mut sub = vec![SExpr::string(format!("{}_{}_{}", symbol.name, unit, body_style))];
+        if body_style == 1 {
+            if let Some(name) = symbol.unit_names.get(&unit) {
+                sub.push(SExpr::list("unit_name", vec![SExpr::string(name)]));
+            }
+        }
+        sub.append(&mut drawings);
+        items.push(SExpr::list("symbol", sub));
+    }
+    SExpr::list("symbol", items)
+}
+
+fn pin_sexpr(pin: &Pin) -> SExpr {
+    let mut items = vec![
+        SExpr::atom(&pin.pin_type),
+        SExpr::atom(&pin.shape),
+        SExpr::list(
+            "at",
+            vec![SExpr::number(pin.at.x.as_mm()), SExpr::number(pin.at.y.as_mm()), SExpr::number(pin.rotation)],
+        ),
+        SExpr::list("length", vec![SExpr::number(pin.length.as_mm())]),
+    ];
+    if pin.hidden {
+        items.push(hide());
+    }
+    for (name, text, effects) in [("name", &pin.name, &pin.name_effects), ("number", &pin.number, &pin.number_effects)] {
+        let mut field = vec![SExpr::string(text)];
+        field.extend(effects.as_ref().map(effects_sexpr));
+        items.push(SExpr::list(name, field));
+    }
+    SExpr::list("pin", items)
+}
+
+fn property(property: &Property) -> SExpr {
+    let mut items = vec![
+        SExpr::string(&property.name),
+        SExpr::string(&property.value),
+        SExpr::list(
+            "at",
+            vec![SExpr::number(property.at.x.as_mm()), SExpr::number(property.at.y.as_mm()), SExpr::number(0.0)],
+        ),
+    ];
+    items.extend(property.effects.as_ref().map(effects_sexpr));
+    SExpr::list("property", items)
+}
+
+fn effects_sexpr(effects: &Effects) -> SExpr {
+    let font = &effects.font;
+    let mut font_items = vec![xy("size", &font.size)];
+    if let Some(thickness) = font.thickness {
+        font_items.push(SExpr::list("thickness", vec![SExpr::number(thickness.as_mm())]));
+    }
+    if font.bold {
+        font_items.push(flag("bold", true));
+    }
+    if font.italic {
+        font_items.push(flag("italic", true));
+    }
+    let mut items = vec![SExpr::list("font", font_items)];
+    if let Some(justify) = &effects.justify {
+        items.push(SExpr::list("justify", justify.split_whitespace().map(SExpr::atom).collect()));
+    }
+    if effects.hide {
+        items.push(hide());
+    }
+    SExpr::list("effects", items)
+}
+
+fn stroke(stroke: &Stroke) -> SExpr {
+    let mut items = vec![
+        SExpr::list("width", vec![SExpr::number(stroke.width.as_mm())]),
+        SExpr::list("type", vec![SExpr::atom(&stroke.stroke_type)]),
+    ];
+    items.extend(stroke.color.as_ref().map(color));
+    SExpr::list("stroke", items)
+}
+
+fn fill(fill: &Fill) -> SExpr {
+    let mut items = vec![SExpr::list("type", vec![SExpr::atom(&fill.fill_type)])];
+    items.extend(fill.color.as_ref().map(color));
+    SExpr::list("fill", items)
+}
+
+/// `(color R G B A)` with alpha as a fraction
+fn color(color: &Color) -> SExpr {
+    SExpr::list(
+        "color",
+        vec![
+            SExpr::number(color.r.into()),
+            SExpr::number(color.g.into()),
+            SExpr::number(color.b.into()),
+            SExpr::number((f64::from(color.a) / 255.0 * 1000.0).round() / 1000.0),
+        ],
+    )
+}
+
+fn xy(name: &str, point: &Point) -> SExpr {
+    SExpr::list(name, vec![SExpr::number(point.x.as_mm()), SExpr::number(point.y.as_mm())])
+}
+
+fn flag(name: &str, value: bool) -> SExpr {
+    SExpr::list(name, vec![SExpr::yes_no(value)])
+}
+
+fn hide() -> SExpr {
+    flag("hide", true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::symbol::lib_parser::{parse_symbol_lib_full, parse_symbol_library};
+
+    #[test]
+    fn test_round_trip_example_library() {
+        let library = include_str!("../../assets/example_components.kicad_sym");
+        let lib = parse_symbol_library("example", library).unwrap();
+        let written = lib.to_kicad_sym();
+        let reparsed = parse_symbol_library("example", &written).unwrap();
+        assert_eq!(reparsed.version, "20231120");
+        assert_eq!(reparsed.generator, "kiparse");
+        assert_eq!(reparsed.symbols, lib.symbols);
+    }
+
+    #[test]
+    fn test_units_and_derived_symbols() {
+        let symbols = parse_symbol_lib_full(r#"(kicad_symbol_lib
+  (symbol "LM358" (pin_names (offset 0.127) hide) (property "Reference" "U")
+    (symbol "LM358_0_1" (polyline (pts (xy 0 0) (xy 1 1)) (stroke (width 0.254) (type default)) (fill (type background))))
+    (symbol "LM358_1_1" (pin output line (at 7.62 0 180) (length 2.54) (name "~") (number "1")))
+    (symbol "LM358_2_1" (unit_name "B") (pin input inverted (at -7.62 -2.54 0) (length 2.54) hide (name "-") (number "6")))
+    (symbol "LM358_2_2" (rectangle (start 0 0) (end 1 1) (stroke (width 0) (type dash) (color 255 0 0 0.5)) (fill (type none)))))
+  (symbol "LM2904" (extends "LM358") (property "Value" "LM2904"))
+)"#).unwrap();
+        let lib = SymbolLib { symbols, ..SymbolLib::default() };
+        let written = lib.to_kicad_sym();
+
+        assert!(written.contains("(symbol \"LM358_2_1\"\n\t\t\t(unit_name \"B\")"));
+        assert!(written.contains("(pin_names\n\t\t\t(offset 0.127)\n\t\t\t(hide yes)"));
+        assert!(written.contains("(color 255 0 0 0.502)"));
+        let derived = written.split("(symbol \"LM2904\"").nth(1).unwrap();
+        assert!(derived.contains("(extends \"LM358\")") && !derived.contains("pin_names"));
+
+        let reparsed = parse_symbol_lib_full(&written).unwrap();
+        assert_eq!(reparsed[0].rectangles[0].body_style, 2);
+        assert_eq!(reparsed[0].polylines[0].unit, 0);
+        assert_eq!(reparsed[1], lib.symbols[1]);
+        assert_eq!(reparsed[0], lib.symbols[0]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Unit-aware lengths
+//!
+//! KiCad works in millimeters, and the board and symbol types hold their
+//! coordinates, widths, drills and thicknesses as [`Length`]s: millimeters that
+//! cross into other units — mils for fabricators, inches for placement files and
+//! Eagle, micrometers for plating — through one place instead of scattered
+//! `* 39.3701` factors. Angles, ratios and areas stay plain `f64`s.
+//!
+//! ```rust
+//! use kiparse::units::Length;
+//!
+//! let width = Length::mm(0.254);
+//! assert!((width.as_mils() - 10.0).abs() < 1e-9);
+//! assert_eq!(Length::parse("8mil"), Some(Length::mils(8.0)));
+//! assert_eq!(format!("{:.2}", Length::inches(0.1)), "2.54 mm");
+//! ```
+
+use core::fmt;
+use core::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// Millimeters in an inch
+pub const MM_PER_INCH: f64 = 25.4;
+
+/// Millimeters in a mil (a thousandth of an inch)
+pub const MM_PER_MIL: f64 = 0.0254;
+
+/// A length or coordinate, stored in millimeters
+///
+/// Serializes as the bare millimeter value, so it can replace an `f64` field
+/// without changing the JSON or binary layout.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Length(f64);
+
+impl Length {
+    pub const ZERO: Length = Length(0.0);
+
+    pub fn mm(value: f64) -> Self {
+        Length(value)
+    }
+
+    pub fn mils(value: f64) -> Self {
+        Length(value * MM_PER_MIL)
+    }
+
+    pub fn inches(value: f64) -> Self {
+        Length(value * MM_PER_INCH)
+    }
+
+    pub fn um(value: f64) -> Self {
+        Length(value / 1000.0)
+    }
+
+    pub fn as_mm(self) -> f64 {
+        self.0
+    }
+
+    pub fn as_mils(self) -> f64 {
+        self.0 / MM_PER_MIL
+    }
+
+    pub fn as_inches(self) -> f64 {
+        self.0 / MM_PER_INCH
+    }
+
+    pub fn as_um(self) -> f64 {
+        self.0 * 1000.0
+    }
+
+    pub fn abs(self) -> Self {
+        Length(self.0.abs())
+    }
+
+    pub fn min(self, other: Length) -> Self {
+        Length(self.0.min(other.0))
+    }
+
+    pub fn max(self, other: Length) -> Self {
+        Length(self.0.max(other.0))
+    }
+
+    /// Total order for sorting, as [`f64::total_cmp`]
+    pub fn total_cmp(&self, other: &Length) -> core::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+
+    /// Parse a number with an optional unit suffix: `mm`, `mil`/`mils`/`thou`,
+    /// `in`/`inch`/`"`, `um`/`µm`/`mic`; bare numbers are millimeters
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let split = text
+            .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E')))
+            .unwrap_or(text.len());
+        let (number, unit) = text.split_at(split);
+        let value: f64 = number.trim().parse().ok()?;
+        let length = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "mm" => Length::mm(value),
+            "mil" | "mils" | "thou" => Length::mils(value),
+            "in" | "inch" | "inches" | "\"" => Length::inches(value),
+            "um" | "µm" | "mic" => Length::um(value),
+            _ => return None,
+        };
+        Some(length)
+    }
+}
+
+impl From<f64> for Length {
+    fn from(mm: f64) -> Self {
+        Length(mm)
+    }
+}
+
+impl From<Length> for f64 {
+    fn from(length: Length) -> Self {
+        length.0
+    }
+}
+
+/// Millimeters with the unit, honoring a precision such as `{:.3}`
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match f.precision() {
+            Some(precision) => write!(f, "{:.*} mm", precision, self.0),
+            None => write!(f, "{} mm", self.0),
+        }
+    }
+}
+
+impl Add for Length {
+    type Output = Length;
+    fn add(self, other: Length) -> Length {
+        Length(self.0 + other.0)
+    }
+}
+
+impl AddAssign for Length {
+    fn add_assign(&mut self, other: Length) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for Length {
+    type Output = Length;
+    fn sub(self, other: Length) -> Length {
+        Length(self.0 - other.0)
+    }
+}
+
+impl SubAssign for Length {
+    fn sub_assign(&mut self, other: Length) {
+        self.0 -= other.0;
+    }
+}
+
+impl Neg for Length {
+    type Output = Length;
+    fn neg(self) -> Length {
+        Length(-self.0)
+    }
+}
+
+impl Mul<f64> for Length {
+    type Output = Length;
+    fn mul(self, factor: f64) -> Length {
+        Length(self.0 * factor)
+    }
+}
+
+impl Mul<Length> for f64 {
+    type Output = Length;
+    fn mul(self, length: Length) -> Length {
+        Length(self * length.0)
+    }
+}
+
+impl Div<f64> for Length {
+    type Output = Length;
+    fn div(self, divisor: f64) -> Length {
+        Length(self.0 / divisor)
+    }
+}
+
+/// Ratio of two lengths
+impl Div for Length {
+    type Output = f64;
+    fn div(self, other: Length) -> f64 {
+        self.0 / other.0
+    }
+}
+
+impl core::iter::Sum for Length {
+    fn sum<I: Iterator<Item = Length>>(iter: I) -> Length {
+        Length(iter.map(|l| l.0).sum())
+    }
+}
+
+/// Square millimeters in square inches
+pub fn mm2_to_sq_in(mm2: f64) -> f64 {
+    mm2 / (MM_PER_INCH * MM_PER_INCH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert!(close(Length::mils(100.0).as_mm(), 2.54));
+        assert!(close(Length::mm(1.0).as_mils(), 39.370078740157));
+        assert!(close(Length::inches(1.0).as_mils(), 1000.0));
+        assert!(close(Length::um(35.0).as_mm(), 0.035));
+        assert!(close((Length::mm(1.0) + Length::mils(10.0) * 2.0).as_mm(), 1.508));
+        assert_eq!(Length::mm(3.0) / Length::mm(1.5), 2.0);
+        assert_eq!([Length::mm(1.0), Length::mm(2.0)].into_iter().sum::<Length>(), Length::mm(3.0));
+        assert!(close(mm2_to_sq_in(645.16), 1.0));
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        assert_eq!(Length::parse("0.2"), Some(Length::mm(0.2)));
+        assert_eq!(Length::parse(" 10 mil"), Some(Length::mils(10.0)));
+        assert_eq!(Length::parse("0.05inch"), Some(Length::inches(0.05)));
+        assert_eq!(Length::parse("-35um"), Some(Length::um(-35.0)));
+        assert_eq!(Length::parse("1e-1mm"), Some(Length::mm(0.1)));
+        assert_eq!(Length::parse("3 furlongs"), None);
+        assert_eq!(Length::parse("mm"), None);
+        assert_eq!(Length::mm(1.5).to_string(), "1.5 mm");
+        assert_eq!(format!("{:.1}", Length::mils(10.0)), "0.3 mm");
+    }
+}
@@ -0,0 +1,426 @@
+//! File validation for pre-merge checks
+//!
+//! [`validate`] checks a KiCad board, schematic, symbol library or footprint file
+//! and lists what is wrong with it, with line numbers where possible:
+//!
+//! - syntax errors (unbalanced parentheses, unterminated strings) and failures of
+//!   the typed parser are errors
+//! - top-level sections this crate does not know, a missing or unexpected file
+//!   format version and trailing content after the root list are warnings
+//!
+//! ```rust
+//! use kiparse::validate::{validate, FileKind, Severity};
+//!
+//! let result = validate("(kicad_pcb (version 20240108)\n  (generator \"pcbnew\")\n  (teardrops 1))");
+//! assert_eq!(result.kind, Some(FileKind::Board));
+//! assert!(result.is_valid());
+//! assert_eq!(result.issues[0].severity, Severity::Warning);
+//! assert_eq!(result.issues[0].line, Some(3));
+//!
+//! let broken = validate("(kicad_pcb (version 20240108)\n  (net 0 \"\")");
+//! assert!(!broken.is_valid());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::error::KicadError;
+use crate::sexpr::{parse_sexpr_all, SExpr};
+
+/// How serious an [`Issue`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// One problem found in a file
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Issue {
+    pub severity: Severity,
+    /// 1-based line number, when the problem can be located
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Kind of KiCad file, from its root element
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FileKind {
+    Board,
+    Schematic,
+    SymbolLibrary,
+    Footprint,
+}
+
+impl FileKind {
+    fn from_root(name: &str) -> Option<Self> {
+        match name {
+            "kicad_pcb" => Some(FileKind::Board),
+            "kicad_sch" => Some(FileKind::Schematic),
+            "kicad_symbol_lib" => Some(FileKind::SymbolLibrary),
+            "footprint" | "module" => Some(FileKind::Footprint),
+            _ => None,
+        }
+    }
+
+    /// Oldest (KiCad 6) and newest file format versions this crate was tested with
+    fn versions(self) -> (u32, u32) {
+        match self {
+            FileKind::Board => (20211014, 20250401),
+            FileKind::Schematic => (20211123, 20250114),
+            FileKind::SymbolLibrary => (20211014, 20241209),
+            FileKind::Footprint => (20211014, 20241229),
+        }
+    }
+
+    /// Known top-level sections; `None` when sections are not checked
+    fn sections(self) -> Option<&'static [&'static str]> {
+        match self {
+            FileKind::Board => Some(BOARD_SECTIONS),
+            FileKind::Schematic => Some(SCHEMATIC_SECTIONS),
+            FileKind::SymbolLibrary => {
+                Some(&["version", "generator", "generator_version", "symbol"])
+            }
+            FileKind::Footprint => None,
+        }
+    }
+}
+
+const BOARD_SECTIONS: &[&str] = &[
+    "version",
+    "generator",
+    "generator_version",
+    "general",
+    "paper",
+    "page",
+    "title_block",
+    "layers",
+    "setup",
+    "property",
+    "net",
+    "net_class",
+    "footprint",
+    "module",
+    "gr_line",
+    "gr_arc",
+    "gr_circle",
+    "gr_rect",
+    "gr_poly",
+    "gr_curve",
+    "gr_bbox",
+    "gr_text",
+    "gr_text_box",
+    "gr_vector",
+    "dimension",
+    "segment",
+    "arc",
+    "via",
+    "zone",
+    "group",
+    "generated",
+    "target",
+    "image",
+    "table",
+    "embedded_fonts",
+    "embedded_files",
+];
+
+const SCHEMATIC_SECTIONS: &[&str] = &[
+    "version",
+    "generator",
+    "generator_version",
+    "uuid",
+    "paper",
+    "page",
+    "title_block",
+    "lib_symbols",
+    "junction",
+    "no_connect",
+    "bus_entry",
+    "wire",
+    "bus",
+    "polyline",
+    "text",
+    "text_box",
+    "label",
+    "global_label",
+    "hierarchical_label",
+    "netclass_flag",
+    "directive_label",
+    "symbol",
+    "sheet",
+    "sheet_instances",
+    "symbol_instances",
+    "image",
+    "rectangle",
+    "circle",
+    "arc",
+    "bezier",
+    "table",
+    "bus_alias",
+    "rule_area",
+    "group",
+    "embedded_fonts",
+    "embedded_files",
+];
+
+/// Outcome of [`validate`]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Validation {
+    /// File kind, when the root element was recognised
+    pub kind: Option<FileKind>,
+    /// File format version from `(version ...)`
+    pub version: Option<u32>,
+    /// Problems in file order, located ones first
+    pub issues: Vec<Issue>,
+}
+
+impl Validation {
+    /// Whether no errors were found (warnings are allowed)
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues.iter().filter(|i| i.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|i| i.severity == Severity::Warning)
+    }
+}
+
+/// Check the syntax, structure and format version of a KiCad file
+pub fn validate(content: &str) -> Validation {
+    let mut result = Validation {
+        kind: None,
+        version: None,
+        issues: Vec::new(),
+    };
+    let nodes = match parse_sexpr_all(content) {
+        Ok(nodes) => nodes,
+        Err(error) => {
+            result.issues.push(located(Severity::Error, &error));
+            return result;
+        }
+    };
+    let Some(root) = nodes.first() else {
+        result
+            .issues
+            .push(issue(Severity::Error, None, "File is empty".to_string()));
+        return result;
+    };
+    if nodes.len() > 1 {
+        result.issues.push(issue(
+            Severity::Warning,
+            None,
+            format!(
+                "Ignored content after the root list ({} expression(s))",
+                nodes.len() - 1
+            ),
+        ));
+    }
+
+    let root_name = root.name().unwrap_or_default();
+    let Some(kind) = FileKind::from_root(root_name) else {
+        result.issues.push(issue(
+            Severity::Error,
+            Some(first_line(content)),
+            format!("Unknown root element '{}', not a KiCad board, schematic, symbol library or footprint", root_name),
+        ));
+        return result;
+    };
+    result.kind = Some(kind);
+
+    check_version(&mut result, root, kind);
+    if let Some(known) = kind.sections() {
+        let lines = section_lines(content);
+        for (i, section) in root.lists().enumerate() {
+            let name = section.name().unwrap_or_default();
+            if !known.contains(&name) {
+                result.issues.push(issue(
+                    Severity::Warning,
+                    lines.get(i).copied(),
+                    format!("Unknown section '{}' is ignored", name),
+                ));
+            }
+        }
+    }
+
+    let parsed = match kind {
+        FileKind::Board => crate::pcb::parse_pcb(content).map(|_| ()),
+        FileKind::SymbolLibrary => crate::symbol::parse_symbol_lib_full(content).map(|_| ()),
+        FileKind::Schematic | FileKind::Footprint => Ok(()),
+    };
+    if let Err(error) = parsed {
+        result.issues.push(located(Severity::Error, &error));
+    }
+
+    result.issues.sort_by_key(|i| (i.line.is_none(), i.line));
+    result
+}
+
+fn check_version(result: &mut Validation, root: &SExpr, kind: FileKind) {
+    let Some(text) = root.child_str("version") else {
+        result.issues.push(issue(
+            Severity::Warning,
+            None,
+            "No (version ...) found; the file predates KiCad 6 or was not written by KiCad"
+                .to_string(),
+        ));
+        return;
+    };
+    let Ok(version) = text.parse::<u32>() else {
+        result.issues.push(issue(
+            Severity::Error,
+            None,
+            format!("Invalid file format version '{}'", text),
+        ));
+        return;
+    };
+    result.version = Some(version);
+    let (oldest, newest) = kind.versions();
+    if version < oldest {
+        result.issues.push(issue(
+            Severity::Warning,
+            None,
+            format!(
+                "Format version {} is older than KiCad 6 ({}); legacy constructs may be skipped",
+                version, oldest
+            ),
+        ));
+    } else if version > newest {
+        result.issues.push(issue(
+            Severity::Warning,
+            None,
+            format!("Format version {} is newer than the newest known ({}); new constructs may be skipped", version, newest),
+        ));
+    }
+}
+
+fn issue(severity: Severity, line: Option<usize>, message: String) -> Issue {
+    Issue {
+        severity,
+        line,
+        message,
+    }
+}
+
+/// Issue from a parse error, taking the line from a `line N: ...` message
+fn located(severity: Severity, error: &KicadError) -> Issue {
+    let message = match error {
+        KicadError::ParseError(message) => message.clone(),
+        other => other.to_string(),
+    };
+    let line = message
+        .strip_prefix("line ")
+        .and_then(|rest| rest.split_once(": "));
+    match line.and_then(|(n, rest)| Some((n.parse().ok()?, rest))) {
+        Some((line, rest)) => issue(severity, Some(line), rest.to_string()),
+        None => issue(severity, None, message),
+    }
+}
+
+/// Line of the first non-blank character
+fn first_line(content: &str) -> usize {
+    1 + content[..content.len() - content.trim_start().len()]
+        .matches('\n')
+        .count()
+}
+
+/// Starting line of each list directly inside the root list, in order
+fn section_lines(content: &str) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let (mut line, mut depth) = (1, 0usize);
+    let (mut in_string, mut escaped) = (false, false);
+    for c in content.chars() {
+        if c == '\n' {
+            line += 1;
+        }
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => {
+                depth += 1;
+                if depth == 2 {
+                    lines.push(line);
+                }
+            }
+            ')' => {
+                depth = depth.saturating_sub(1);
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax_errors() {
+        let result = validate("(kicad_pcb (version 20240108)\n  (gr_text \"open\n)");
+        assert!(!result.is_valid());
+        assert_eq!(result.issues[0].line, Some(3));
+        assert!(result.issues[0].message.contains("unterminated string"));
+
+        let result = validate("(kicad_sch (version 20231120)\n  (wire (pts (xy 0 0) (xy 1 0))\n");
+        assert_eq!(result.errors().count(), 1);
+        assert!(result.issues[0].message.contains("opened at line 2"));
+
+        assert!(!validate("  \n").is_valid());
+        let unknown = validate("\n(kicad_wks (version 20231120))");
+        assert_eq!(unknown.issues[0].line, Some(2));
+        assert!(!unknown.is_valid());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let content = r#"(kicad_symbol_lib (version 20990101) (generator "x")
+  (symbol "R" (property "Reference" "R" (at 0 0 0)))
+  (symbol_doc "R")
+  (symbol "C" (property "Reference" "C" (at 0 0 0))))
+(extra)"#;
+        let result = validate(content);
+        assert!(result.is_valid());
+        assert_eq!(result.kind, Some(FileKind::SymbolLibrary));
+        assert_eq!(result.version, Some(20990101));
+        let messages: Vec<(Option<usize>, &str)> = result
+            .warnings()
+            .map(|w| (w.line, w.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages[0],
+            (Some(3), "Unknown section 'symbol_doc' is ignored")
+        );
+        assert_eq!(
+            messages[1].1,
+            "Ignored content after the root list (1 expression(s))"
+        );
+        assert!(messages[2].1.contains("newer than the newest known"));
+
+        let old = validate("(kicad_pcb (version 20171130) (host pcbnew 5.1))");
+        assert!(old
+            .warnings()
+            .any(|w| w.message.contains("older than KiCad 6")));
+        assert!(old.warnings().any(|w| w.message.contains("'host'")));
+        assert!(!validate("(kicad_pcb (version x))").is_valid());
+    }
+}